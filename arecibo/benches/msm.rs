@@ -0,0 +1,82 @@
+//! Benchmarks the CPU MSM backend of `provider::cpu_msm` against the paths used without the
+//! `cpu_msm` feature, on the BN256 and Grumpkin curves of the commitment keys.
+//!
+//! Run with `cargo bench --bench msm`.
+use arecibo::provider::cpu_msm::{msm, FixedBaseMSM, MsmCurveAffine};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ff::Field;
+use group::{Curve, Group};
+use halo2curves::{bn256, grumpkin, msm::best_multiexp, CurveAffine};
+use rand::thread_rng;
+use rayon::prelude::*;
+
+const SIZES: [usize; 4] = [1 << 10, 1 << 12, 1 << 14, 1 << 16];
+
+fn random_bases<C: CurveAffine>(n: usize) -> Vec<C> {
+  let bases_proj = (0..n)
+    .into_par_iter()
+    .map(|_| C::CurveExt::random(thread_rng()))
+    .collect::<Vec<_>>();
+  let mut bases = vec![C::identity(); n];
+  C::CurveExt::batch_normalize(&bases_proj, &mut bases);
+  bases
+}
+
+fn bench_curve<C>(
+  c: &mut Criterion,
+  curve: &str,
+  accelerated: Option<fn(&[C], &[C::ScalarExt]) -> C::CurveExt>,
+) where
+  C: CurveAffine + MsmCurveAffine<Scalar = C::ScalarExt, Curve = C::CurveExt>,
+{
+  let mut group = c.benchmark_group(format!("MSM-{curve}"));
+  group.sample_size(10);
+
+  let max_size = *SIZES.iter().max().unwrap();
+  let bases = random_bases::<C>(max_size);
+  let scalars = (0..max_size)
+    .map(|_| C::ScalarExt::random(thread_rng()))
+    .collect::<Vec<_>>();
+  let fixed_base = FixedBaseMSM::new(&bases);
+
+  for n in SIZES {
+    let (bases, scalars) = (&bases[..n], &scalars[..n]);
+    assert_eq!(msm(bases, scalars), best_multiexp(scalars, bases));
+    assert_eq!(fixed_base.msm(scalars), best_multiexp(scalars, bases));
+
+    group.bench_with_input(BenchmarkId::new("best_multiexp", n), &n, |b, _| {
+      b.iter(|| best_multiexp(black_box(scalars), black_box(bases)))
+    });
+    if let Some(accelerated) = accelerated {
+      group.bench_with_input(BenchmarkId::new("grumpkin_msm", n), &n, |b, _| {
+        b.iter(|| accelerated(black_box(bases), black_box(scalars)))
+      });
+    }
+    group.bench_with_input(BenchmarkId::new("cpu_msm", n), &n, |b, _| {
+      b.iter(|| msm(black_box(bases), black_box(scalars)))
+    });
+    group.bench_with_input(BenchmarkId::new("cpu_msm_fixed_base", n), &n, |b, _| {
+      b.iter(|| fixed_base.msm(black_box(scalars)))
+    });
+  }
+  group.finish();
+}
+
+fn bench_bn256(c: &mut Criterion) {
+  #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+  let accelerated = Some(grumpkin_msm::bn256 as fn(&[_], &[_]) -> _);
+  #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+  let accelerated = None;
+  bench_curve::<bn256::G1Affine>(c, "bn256", accelerated);
+}
+
+fn bench_grumpkin(c: &mut Criterion) {
+  #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+  let accelerated = Some(grumpkin_msm::grumpkin as fn(&[_], &[_]) -> _);
+  #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+  let accelerated = None;
+  bench_curve::<grumpkin::G1Affine>(c, "grumpkin", accelerated);
+}
+
+criterion_group!(msm_benches, bench_bn256, bench_grumpkin);
+criterion_main!(msm_benches);
@@ -102,6 +102,12 @@ pub enum NovaError {
   /// returned when non-recursive SNARK is provided
   #[error("NoCircuit")]
   NotRecursive,
+  /// returned when a shard does not line up with its shard plan or with its neighbouring shards
+  #[error("InvalidShard: {0}")]
+  InvalidShard(String),
+  /// returned when a proof artifact cannot be (de)serialized
+  #[error("SerializationError: {0}")]
+  SerializationError(String),
 }
 
 /// Errors specific to the Polynomial commitment scheme
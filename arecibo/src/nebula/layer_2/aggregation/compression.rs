@@ -2,13 +2,16 @@
 
 use super::{AggregationPublicParams, AggregationRecursiveSNARK};
 use crate::{
+  constants::{BN_N_LIMBS, NIO_CYCLE_FOLD, NUM_FE_IN_EMULATED_POINT, NUM_HASH_BITS},
+  cyclefold::util::absorb_primary_relaxed_r1cs,
   errors::NovaError,
+  gadgets::scalar_as_base,
   nebula::nifs::{CycleFoldRelaxedNIFS, PrimaryNIFS, PrimaryRelaxedNIFS},
   r1cs::{R1CSInstance, RelaxedR1CSInstance},
   traits::{
     commitment::CommitmentEngineTrait,
     snark::{BatchedRelaxedR1CSSNARKTrait, RelaxedR1CSSNARKTrait},
-    CurveCycleEquipped, Dual, Engine,
+    AbsorbInROTrait, CurveCycleEquipped, Dual, Engine, ROTrait,
   },
   DerandKey,
 };
@@ -53,6 +56,10 @@ where
   snark_secondary: S2,
 
   // primary data
+  num_steps: usize,
+  zi: Vec<E::Scalar>,
+  prev_IC: E::Scalar,
+  r_i: E::Scalar,
   nifs_verifier: PrimaryNIFS<E>,
   r_U_verifier: RelaxedR1CSInstance<E>,
   l_u_verifier: R1CSInstance<E>,
@@ -190,6 +197,10 @@ where
       snark_primary,
       snark_secondary,
       // primary data
+      num_steps: rs.rs.num_steps(),
+      zi: rs.rs.zi.clone(),
+      prev_IC: rs.rs.prev_IC,
+      r_i: rs.rs.r_i,
       nifs_verifier,
       nifs_r_verifier,
       r_U_verifier,
@@ -230,12 +241,66 @@ where
     })
   }
 
+  /// Hash of the Layer 1 IOs aggregated by the proof, as output by the verifier circuit
+  pub fn outputs_hash(&self) -> Option<E::Scalar> {
+    self.zi.get(1).copied()
+  }
+
   /// Verify the correctness of the [`CompressedSNARK`]
   pub fn verify(
     &self,
     pp: &AggregationPublicParams<E>,
     vk: &VerifierKey<E, S1, S2>,
   ) -> Result<(), NovaError> {
+    if self.num_steps == 0 || self.zi.len() != 2 || self.l_u_verifier.X.len() != 2 {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    // The verifier circuit outputs the hash of the running instances it folded
+    let hash_U = pp.hash_running_instances(
+      &self.r_U_F,
+      &self.r_U_ops,
+      &self.r_U_scan,
+      &self.r_U_cyclefold,
+    );
+    if self.zi[0] != hash_U {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    // Calculate the hashes of the primary running instance and cyclefold running instance
+    let (hash_primary, hash_cyclefold) = {
+      let mut hasher_p = <Dual<E> as Engine>::RO::new(
+        pp.pp.ro_consts.clone(),
+        4 + 2 * pp.pp.F_arity_primary + 2 * NUM_FE_IN_EMULATED_POINT + 3, // (digest, num_steps, prev_IC, r_i) + 2 * arity "(z0, zi)" + U
+      );
+      hasher_p.absorb(pp.pp.digest());
+      hasher_p.absorb(E::Scalar::from(self.num_steps as u64));
+      for e in pp.z0().iter().chain(&self.zi) {
+        hasher_p.absorb(*e);
+      }
+      absorb_primary_relaxed_r1cs::<E, Dual<E>>(&self.r_U_verifier, &mut hasher_p);
+      hasher_p.absorb(self.prev_IC);
+      hasher_p.absorb(self.r_i);
+      let hash_primary = hasher_p.squeeze(NUM_HASH_BITS);
+      let mut hasher_c = <Dual<E> as Engine>::RO::new(
+        pp.pp.ro_consts.clone(),
+        1 + 1 + 1 + 3 + 3 + 1 + NIO_CYCLE_FOLD * BN_N_LIMBS,
+      );
+      hasher_c.absorb(pp.pp.digest());
+      hasher_c.absorb(E::Scalar::from(self.num_steps as u64));
+      self.r_U_secondary_verifier.absorb_in_ro(&mut hasher_c);
+      hasher_c.absorb(self.r_i);
+      let hash_cyclefold = hasher_c.squeeze(NUM_HASH_BITS);
+      (hash_primary, hash_cyclefold)
+    };
+
+    // Verify the hashes equal the public IO for the final primary instance
+    if scalar_as_base::<Dual<E>>(hash_primary) != self.l_u_verifier.X[0]
+      || scalar_as_base::<Dual<E>>(hash_cyclefold) != self.l_u_verifier.X[1]
+    {
+      return Err(NovaError::ProofVerifyError);
+    }
+
     // Primary SNARK
     let U_f = self.nifs_verifier.verify(
       &pp.pp.ro_consts,
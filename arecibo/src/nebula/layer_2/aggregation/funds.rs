@@ -0,0 +1,223 @@
+//! Aggregates the compliance proofs of many funds that share the same circuit into a single
+//! compressed proof.
+//!
+//! Each fund brings its own Layer 1 proof, with its own `z0`/`zi`. The [`FundAggregator`] folds
+//! them through the Aggregation layer and compresses the result with
+//! [`CompressedSNARK`](super::compression::CompressedSNARK). The resulting
+//! [`AggregatedFundProof`] lists the public outputs of every fund so a verifier can attribute
+//! compliance fund by fund.
+//!
+//! The Aggregation verifier circuit checks every fund's outputs against the public IO of the
+//! fund's last F instance, which it folds, and chains them into a running hash it outputs. The
+//! listed outputs are therefore bound by the proof: [`AggregatedFundProof::verify`] recomputes that
+//! hash over them.
+use super::{
+  compression::{CompressedSNARK, ProverKey, VerifierKey},
+  AggregationPublicParams, AggregationRecursiveSNARK,
+};
+use crate::{
+  errors::NovaError,
+  nebula::{
+    layer_2::utils::chain_io_hash,
+    traits::{Layer1RSTrait, MemoryCommitmentsTraits},
+  },
+  traits::{
+    snark::{BatchedRelaxedR1CSSNARKTrait, RelaxedR1CSSNARKTrait},
+    CurveCycleEquipped, Dual,
+  },
+};
+use ff::Field;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// The Layer 1 proof of a single fund
+pub struct FundProof<E, RS>
+where
+  E: CurveCycleEquipped,
+  RS: Layer1RSTrait<E>,
+{
+  /// Identifier of the fund, e.g. the hash of its vault address
+  pub fund_id: E::Scalar,
+  /// Layer 1 proof of the fund's compliance IVC
+  pub rs: RS,
+}
+
+impl<E, RS> FundProof<E, RS>
+where
+  E: CurveCycleEquipped,
+  RS: Layer1RSTrait<E>,
+{
+  /// Creates a new fund proof
+  pub fn new(fund_id: E::Scalar, rs: RS) -> Self {
+    Self { fund_id, rs }
+  }
+
+  /// Public outputs of the fund, read from its F proof
+  pub fn output(&self) -> FundOutput<E> {
+    FundOutput {
+      fund_id: self.fund_id,
+      num_steps: self.rs.F().num_steps(),
+      z0: self.rs.F().z0.clone(),
+      zi: self.rs.F().zi.clone(),
+    }
+  }
+}
+
+/// Public outputs of a single fund in an aggregated proof
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FundOutput<E>
+where
+  E: CurveCycleEquipped,
+{
+  /// Identifier of the fund
+  pub fund_id: E::Scalar,
+  /// Number of steps proven by the fund's IVC
+  pub num_steps: usize,
+  /// Initial IVC state of the fund
+  pub z0: Vec<E::Scalar>,
+  /// Final IVC state of the fund
+  pub zi: Vec<E::Scalar>,
+}
+
+/// Funds carry no memory, so the memory commitments handed to the Aggregation layer are zero
+struct NoMemory<E>(PhantomData<E>);
+
+impl<E> MemoryCommitmentsTraits<E> for NoMemory<E>
+where
+  E: CurveCycleEquipped,
+{
+  fn C_IS(&self) -> E::Scalar {
+    E::Scalar::ZERO
+  }
+
+  fn C_FS(&self) -> E::Scalar {
+    E::Scalar::ZERO
+  }
+}
+
+/// A compressed proof aggregating the compliance proofs of several funds
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct AggregatedFundProof<E, S1, S2>
+where
+  E: CurveCycleEquipped,
+  S1: BatchedRelaxedR1CSSNARKTrait<E>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E>>,
+{
+  snark: CompressedSNARK<E, S1, S2>,
+  pub(super) funds: Vec<FundOutput<E>>,
+}
+
+impl<E, S1, S2> AggregatedFundProof<E, S1, S2>
+where
+  E: CurveCycleEquipped,
+  S1: BatchedRelaxedR1CSSNARKTrait<E>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E>>,
+{
+  /// Public outputs of every aggregated fund, in aggregation order
+  pub fn funds(&self) -> &[FundOutput<E>] {
+    &self.funds
+  }
+
+  /// Public outputs of the fund with the given identifier
+  pub fn fund(&self, fund_id: &E::Scalar) -> Option<&FundOutput<E>> {
+    self.funds.iter().find(|fund| fund.fund_id == *fund_id)
+  }
+
+  /// Verifies the compressed proof and checks that the listed per-fund outputs are the ones it
+  /// aggregated
+  pub fn verify(
+    &self,
+    pp: &AggregationPublicParams<E>,
+    vk: &VerifierKey<E, S1, S2>,
+  ) -> Result<(), NovaError> {
+    self.snark.verify(pp, vk)?;
+    let outputs_hash = self.funds.iter().fold(E::Scalar::ZERO, |acc, fund| {
+      chain_io_hash::<E>(
+        &pp.pp.ro_consts,
+        acc,
+        fund.fund_id,
+        fund.num_steps,
+        &fund.z0,
+        &fund.zi,
+      )
+    });
+    if self.snark.outputs_hash() != Some(outputs_hash) {
+      return Err(NovaError::ProofVerifyError);
+    }
+    Ok(())
+  }
+}
+
+/// Aggregates the Layer 1 proofs of several funds that share the same circuit
+pub struct FundAggregator<'a, E>
+where
+  E: CurveCycleEquipped,
+{
+  pp: &'a AggregationPublicParams<E>,
+}
+
+impl<'a, E> FundAggregator<'a, E>
+where
+  E: CurveCycleEquipped,
+{
+  /// Creates an aggregator over the given Aggregation public parameters
+  pub fn new(pp: &'a AggregationPublicParams<E>) -> Self {
+    Self { pp }
+  }
+
+  fn check_funds<RS>(funds: &[FundProof<E, RS>]) -> Result<(), NovaError>
+  where
+    RS: Layer1RSTrait<E>,
+  {
+    if funds.is_empty() {
+      return Err(NovaError::InvalidNumSteps);
+    }
+    // every fund must appear once, otherwise its outputs could not be attributed
+    for (i, fund) in funds.iter().enumerate() {
+      if funds[..i].iter().any(|other| other.fund_id == fund.fund_id) {
+        return Err(NovaError::InvalidIndex);
+      }
+    }
+    Ok(())
+  }
+
+  /// Folds the fund proofs, in order, into a single Aggregation proof
+  #[tracing::instrument(skip_all, name = "FundAggregator::aggregate")]
+  pub fn aggregate<RS>(
+    &self,
+    funds: &[FundProof<E, RS>],
+  ) -> Result<AggregationRecursiveSNARK<E>, NovaError>
+  where
+    RS: Layer1RSTrait<E>,
+  {
+    Self::check_funds(funds)?;
+    let no_memory = NoMemory(PhantomData);
+    let mut aggregation_rs =
+      AggregationRecursiveSNARK::new(self.pp, funds[0].fund_id, &funds[0].rs, &no_memory)?;
+    for fund in funds {
+      aggregation_rs.prove_step(self.pp, fund.fund_id, &fund.rs, &no_memory)?;
+    }
+    Ok(aggregation_rs)
+  }
+
+  /// Aggregates the fund proofs and compresses the result into a single proof listing the public
+  /// outputs of every fund
+  #[tracing::instrument(skip_all, name = "FundAggregator::prove")]
+  pub fn prove<RS, S1, S2>(
+    &self,
+    pk: &ProverKey<E, S1, S2>,
+    funds: &[FundProof<E, RS>],
+  ) -> Result<AggregatedFundProof<E, S1, S2>, NovaError>
+  where
+    RS: Layer1RSTrait<E>,
+    S1: BatchedRelaxedR1CSSNARKTrait<E>,
+    S2: RelaxedR1CSSNARKTrait<Dual<E>>,
+  {
+    let aggregation_rs = self.aggregate(funds)?;
+    let snark = CompressedSNARK::prove(self.pp, pk, &aggregation_rs)?;
+    let funds = funds.iter().map(FundProof::output).collect::<Vec<_>>();
+    Ok(AggregatedFundProof { snark, funds })
+  }
+}
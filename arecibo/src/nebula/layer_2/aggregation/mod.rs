@@ -6,7 +6,7 @@ use crate::{
   gadgets::scalar_as_base,
   nebula::{
     augmented_circuit::AugmentedCircuitParams,
-    layer_2::utils::{absorb_U, absorb_U_bn, random_fold_and_derandom},
+    layer_2::utils::{absorb_U, absorb_U_bn, random_fold_and_derandom, Layer1IO},
     nifs::{CycleFoldRelaxedNIFS, PrimaryNIFS, PrimaryRelaxedNIFS},
     rs::{PublicParams, RecursiveSNARK},
   },
@@ -27,6 +27,7 @@ use super::{nifs::NIFS, utils::Layer2FoldingData};
 use crate::nebula::traits::{Layer1PPTrait, Layer1RSTrait, MemoryCommitmentsTraits};
 
 pub mod compression;
+pub mod funds;
 mod verifier_circuit;
 
 /// Defines the public parameters for the Aggregation layer
@@ -72,8 +73,17 @@ where
     let (circuit_shape_scan, ck_scan, digest_scan) = pp_scan.into_shape_ck_digest();
 
     // Get Public Params for Verifier Circuit
-    let verifier_circuit: VerifierCircuit<E> =
-      VerifierCircuit::new(aug_params, ro_consts, None, None, None, None);
+    let verifier_circuit: VerifierCircuit<E> = VerifierCircuit::new(
+      aug_params,
+      ro_consts,
+      circuit_shape_F.F_arity,
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+    );
     let pp: PublicParams<E> =
       PublicParams::setup(&verifier_circuit, ck_hint_primary, ck_hint_cyclefold);
 
@@ -130,6 +140,40 @@ where
     )
   }
 
+  /// Initial state of the verifier circuit: the hash of the default running instances and an empty
+  /// hash of the aggregated IOs
+  fn z0(&self) -> Vec<E::Scalar> {
+    let F_shape = &self.circuit_shape_F.r1cs_shape;
+    let shape_ops = &self.circuit_shape_ops.r1cs_shape;
+    let shape_scan = &self.circuit_shape_scan.r1cs_shape;
+    let hash_U = self.hash_running_instances(
+      &RelaxedR1CSInstance::default(&*self.ck, F_shape),
+      &RelaxedR1CSInstance::default(&*self.ck, shape_ops),
+      &RelaxedR1CSInstance::default(&*self.ck, shape_scan),
+      &self.default_cyclefold_instance().0,
+    );
+    vec![hash_U, E::Scalar::ZERO]
+  }
+
+  /// Hash of the running instances, as output by the verifier circuit in `zi[0]`
+  fn hash_running_instances(
+    &self,
+    r_U_F: &RelaxedR1CSInstance<E>,
+    r_U_ops: &RelaxedR1CSInstance<E>,
+    r_U_scan: &RelaxedR1CSInstance<E>,
+    r_U_cyclefold: &RelaxedR1CSInstance<Dual<E>>,
+  ) -> E::Scalar {
+    let mut ro = <Dual<E> as Engine>::RO::new(
+      self.pp.ro_consts.clone(),
+      3 * (2 * NUM_FE_IN_EMULATED_POINT + 3) + (3 + 3 + BN_N_LIMBS + NIO_CYCLE_FOLD * BN_N_LIMBS), // 3 * (U.W + U.comm_E + U.X + U.u) + U_cyclefold
+    );
+    absorb_U::<E>(r_U_F, &mut ro);
+    absorb_U::<E>(r_U_ops, &mut ro);
+    absorb_U::<E>(r_U_scan, &mut ro);
+    absorb_U_bn(r_U_cyclefold, &mut ro);
+    scalar_as_base::<Dual<E>>(ro.squeeze(NUM_CHALLENGE_BITS))
+  }
+
   fn primary_r1cs_shapes(&self) -> Vec<&R1CSShape<E>> {
     vec![
       &self.circuit_shape_F.r1cs_shape,
@@ -170,9 +214,11 @@ where
   E: CurveCycleEquipped,
 {
   #[tracing::instrument(skip_all, name = "AggregationRecursiveSNARK::new")]
-  /// Constructs a new AggregationRecursiveSNARK instance
+  /// Constructs a new AggregationRecursiveSNARK instance, whose first step aggregates the Layer 1
+  /// proof of `id`
   pub fn new(
     pp: &AggregationPublicParams<E>,
+    id: E::Scalar,
     layer1_rs: &impl Layer1RSTrait<E>,
     _U: &impl MemoryCommitmentsTraits<E>,
   ) -> Result<Self, NovaError> {
@@ -210,6 +256,45 @@ where
       Some(U2_secondary_F.clone()),
     );
 
+    /*
+     * ********************************  F IO fold ********************************
+     */
+    // The last F instance is not part of the F running instance, but its public IO binds the
+    // Layer 1 z0 and zi, so it is folded in as well
+    let U1_io = new_r_U_F.clone();
+    let U2_io = RelaxedR1CSInstance::from_r1cs_instance(&layer1_rs.F().l_u_primary);
+    let W2_io = RelaxedR1CSWitness::from_r1cs_witness(
+      &pp.circuit_shape_F.r1cs_shape,
+      &layer1_rs.F().l_w_primary,
+    );
+    let (U2_secondary_io, W2_secondary_io) = pp.default_cyclefold_instance();
+    let (nifs_io, (new_r_U_F, new_r_W_F), (r_U_cyclefold_temp1, r_W_cyclefold_temp1)) =
+      NIFS::prove(
+        (pp.ck(), pp.ck_cyclefold()),
+        &pp.pp.ro_consts,
+        &pp.digest_F,
+        (
+          &pp.circuit_shape_F.r1cs_shape,
+          &pp.pp.circuit_shape_cyclefold.r1cs_shape,
+        ),
+        (&U1_io, &new_r_W_F),
+        (&U2_io, &W2_io),
+        (&r_U_cyclefold_temp1, &r_W_cyclefold_temp1),
+        (&U2_secondary_io, &W2_secondary_io),
+      )?;
+    let E_new_io = new_r_U_F.comm_E;
+    let W_new_io = new_r_U_F.comm_W;
+    let folding_data_io = Layer2FoldingData::new(
+      Some(pp.digest_F),
+      Some(nifs_io),
+      Some(U1_io),
+      Some(U2_io),
+      Some(E_new_io),
+      Some(W_new_io),
+      Some(U2_secondary_io),
+    );
+    let io = Layer1IO::new(id, layer1_rs.F());
+
     /*
      * ********************************  ops fold ********************************
      */
@@ -284,24 +369,16 @@ where
     let verifier_circuit: VerifierCircuit<E> = VerifierCircuit::new(
       pp.augmented_circuit_params(),
       pp.pp.ro_consts_circuit.clone(),
+      pp.circuit_shape_F.F_arity,
       Some(folding_data_F),
+      Some(folding_data_io),
+      Some(io),
       Some(folding_data_ops),
       Some(folding_data_scan),
       Some(r_U_cyclefold.clone()),
     );
 
-    let z0 = {
-      let mut ro = <Dual<E> as Engine>::RO::new(
-        pp.pp.ro_consts.clone(),
-        3 * (2 * NUM_FE_IN_EMULATED_POINT + 3) + (3 + 3 + BN_N_LIMBS + NIO_CYCLE_FOLD * BN_N_LIMBS), // 3 * (U.W + U.comm_E + U.X + U.u) + U_cyclefold
-      );
-      absorb_U::<E>(&r_U_F, &mut ro);
-      absorb_U::<E>(&r_U_ops, &mut ro);
-      absorb_U::<E>(&r_U_scan, &mut ro);
-      absorb_U_bn(&r_U_cyclefold, &mut ro);
-      let hash_U = scalar_as_base::<Dual<E>>(ro.squeeze(NUM_CHALLENGE_BITS));
-      vec![hash_U]
-    };
+    let z0 = pp.z0();
     let mut IC_i = E::Scalar::ZERO;
     let mut rs = RecursiveSNARK::new(&pp.pp, &verifier_circuit, &z0)?;
     rs.prove_step(&pp.pp, &verifier_circuit, IC_i)?;
@@ -323,10 +400,11 @@ where
   }
 
   #[tracing::instrument(skip_all, name = "AggregationRecursiveSNARK::prove_step")]
-  /// Proves a step in the aggregation proof
+  /// Proves a step in the aggregation proof, which aggregates the Layer 1 proof of `id`
   pub fn prove_step(
     &mut self,
     pp: &AggregationPublicParams<E>,
+    id: E::Scalar,
     layer1_rs: &impl Layer1RSTrait<E>,
     _U: &impl MemoryCommitmentsTraits<E>,
   ) -> Result<(), NovaError> {
@@ -363,6 +441,45 @@ where
       Some(U2_secondary_F.clone()),
     );
 
+    /*
+     * ********************************  F IO fold ********************************
+     */
+    // The last F instance is not part of the F running instance, but its public IO binds the
+    // Layer 1 z0 and zi, so it is folded in as well
+    let U1_io = new_r_U_F.clone();
+    let U2_io = RelaxedR1CSInstance::from_r1cs_instance(&layer1_rs.F().l_u_primary);
+    let W2_io = RelaxedR1CSWitness::from_r1cs_witness(
+      &pp.circuit_shape_F.r1cs_shape,
+      &layer1_rs.F().l_w_primary,
+    );
+    let (U2_secondary_io, W2_secondary_io) = pp.default_cyclefold_instance();
+    let (nifs_io, (new_r_U_F, new_r_W_F), (r_U_cyclefold_temp1, r_W_cyclefold_temp1)) =
+      NIFS::prove(
+        (pp.ck(), pp.ck_cyclefold()),
+        &pp.pp.ro_consts,
+        &pp.digest_F,
+        (
+          &pp.circuit_shape_F.r1cs_shape,
+          &pp.pp.circuit_shape_cyclefold.r1cs_shape,
+        ),
+        (&U1_io, &new_r_W_F),
+        (&U2_io, &W2_io),
+        (&r_U_cyclefold_temp1, &r_W_cyclefold_temp1),
+        (&U2_secondary_io, &W2_secondary_io),
+      )?;
+    let E_new_io = new_r_U_F.comm_E;
+    let W_new_io = new_r_U_F.comm_W;
+    let folding_data_io = Layer2FoldingData::new(
+      Some(pp.digest_F),
+      Some(nifs_io),
+      Some(U1_io),
+      Some(U2_io),
+      Some(E_new_io),
+      Some(W_new_io),
+      Some(U2_secondary_io),
+    );
+    let io = Layer1IO::new(id, layer1_rs.F());
+
     /*
      * ********************************  ops fold ********************************
      */
@@ -428,7 +545,10 @@ where
     let verifier_circuit: VerifierCircuit<E> = VerifierCircuit::new(
       pp.augmented_circuit_params(),
       pp.pp.ro_consts_circuit.clone(),
+      pp.circuit_shape_F.F_arity,
       Some(folding_data_F),
+      Some(folding_data_io),
+      Some(io),
       Some(folding_data_ops),
       Some(folding_data_scan),
       Some(self.r_U_cyclefold.clone()),
@@ -450,9 +570,19 @@ where
   #[tracing::instrument(skip_all, name = "AggregationRecursiveSNARK::verify")]
   /// Verifies the aggregation proof
   pub fn verify(&self, pp: &AggregationPublicParams<E>) -> Result<(), NovaError> {
-    self
+    let zi = self
       .rs
       .verify(&pp.pp, self.rs.num_steps(), &self.z0, self.IC_i)?;
+    // the running instances must be the ones the verifier circuit folded
+    let hash_U = pp.hash_running_instances(
+      &self.r_U_F,
+      &self.r_U_ops,
+      &self.r_U_scan,
+      &self.r_U_cyclefold,
+    );
+    if self.z0 != pp.z0() || zi[0] != hash_U {
+      return Err(NovaError::ProofVerifyError);
+    }
     let (res_r_F, (res_r_ops, (res_r_scan, res_r_cyclefold))) = rayon::join(
       || {
         pp.circuit_shape_F
@@ -663,8 +793,9 @@ where
 #[cfg(test)]
 mod test {
   use super::{
-    compression::CompressedSNARK, AggregationPublicParams, AggregationRecursiveSNARK,
-    Layer1PPTrait, Layer1RSTrait,
+    compression::CompressedSNARK,
+    funds::{FundAggregator, FundProof},
+    AggregationPublicParams, AggregationRecursiveSNARK, Layer1PPTrait, Layer1RSTrait,
   };
   use crate::{
     frontend::{num::AllocatedNum, ConstraintSystem, SynthesisError},
@@ -715,11 +846,17 @@ mod test {
     let aggregation_pp =
       AggregationPublicParams::<E1>::setup(node_pp, &*default_ck_hint(), &*default_ck_hint());
     let mut aggregation_engine =
-      AggregationRecursiveSNARK::new(&aggregation_pp, &nodes_rs[0], &(F::ZERO, F::ZERO)).unwrap();
+      AggregationRecursiveSNARK::new(&aggregation_pp, F::ZERO, &nodes_rs[0], &(F::ZERO, F::ZERO))
+        .unwrap();
 
-    for node_rs in nodes_rs.iter() {
+    for (i, node_rs) in nodes_rs.iter().enumerate() {
       aggregation_engine
-        .prove_step(&aggregation_pp, node_rs, &(F::ZERO, F::ZERO))
+        .prove_step(
+          &aggregation_pp,
+          F::from(i as u64),
+          node_rs,
+          &(F::ZERO, F::ZERO),
+        )
         .unwrap();
     }
 
@@ -731,6 +868,47 @@ mod test {
     snark.verify(&aggregation_pp, &vk).unwrap();
   }
 
+  #[test]
+  fn test_fund_aggregator() {
+    let (node_pp, nodes_rs) = node_nw(3);
+    let aggregation_pp =
+      AggregationPublicParams::<E1>::setup(node_pp, &*default_ck_hint(), &*default_ck_hint());
+    let (pk, vk) = CompressedSNARK::<E1, S1, S2>::setup(&aggregation_pp).unwrap();
+
+    let funds = nodes_rs
+      .into_iter()
+      .enumerate()
+      .map(|(i, node_rs)| FundProof::new(F::from(i as u64 + 1), node_rs))
+      .collect::<Vec<_>>();
+    let aggregator = FundAggregator::new(&aggregation_pp);
+    let proof = aggregator.prove(&pk, &funds).unwrap();
+    proof.verify(&aggregation_pp, &vk).unwrap();
+
+    // Each fund's outputs can be looked up by its identifier
+    assert_eq!(proof.funds().len(), funds.len());
+    for fund in &funds {
+      assert_eq!(proof.fund(&fund.fund_id), Some(&fund.output()));
+    }
+
+    // The listed outputs are bound by the proof
+    let mut tampered = proof.clone();
+    tampered.funds[0].zi[0] += F::ONE;
+    assert!(tampered.verify(&aggregation_pp, &vk).is_err());
+    let mut tampered = proof.clone();
+    tampered.funds[1].fund_id = F::from(42);
+    assert!(tampered.verify(&aggregation_pp, &vk).is_err());
+    let mut tampered = proof.clone();
+    tampered.funds.pop();
+    assert!(tampered.verify(&aggregation_pp, &vk).is_err());
+
+    // A fund can only be aggregated once
+    let duplicated = vec![
+      FundProof::new(F::ONE, funds.into_iter().next().unwrap().rs),
+      FundProof::new(F::ONE, node_nw(1).1.pop().unwrap()),
+    ];
+    assert!(aggregator.aggregate(&duplicated).is_err());
+  }
+
   struct NodePP {
     pp1: PublicParams<E1>,
     pp2: PublicParams<E1>,
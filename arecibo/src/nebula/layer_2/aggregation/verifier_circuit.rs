@@ -1,5 +1,7 @@
 use crate::{
-  constants::{BN_N_LIMBS, NIO_CYCLE_FOLD, NUM_CHALLENGE_BITS, NUM_FE_IN_EMULATED_POINT},
+  constants::{
+    BN_N_LIMBS, NIO_CYCLE_FOLD, NUM_CHALLENGE_BITS, NUM_FE_IN_EMULATED_POINT, NUM_HASH_BITS,
+  },
   cyclefold::gadgets::{emulated::AllocatedEmulRelaxedR1CSInstance, AllocatedCycleFoldInstance},
   frontend::{num::AllocatedNum, ConstraintSystem, SynthesisError},
   gadgets::{emulated::AllocatedEmulPoint, le_bits_to_num},
@@ -10,7 +12,7 @@ use crate::{
         r1cs::AllocatedRelaxedR1CSInstanceBn, CycleFoldNIFSVerifierGadget,
         CycleFoldRelaxedNIFSVerifierGadget, NIFSVerifierGadget, PrimaryNIFSVerifierGadget,
       },
-      utils::Layer2FoldingData,
+      utils::{Layer1IO, Layer2FoldingData},
     },
     rs::StepCircuit,
   },
@@ -28,11 +30,14 @@ where
   E: CurveCycleEquipped,
 {
   folding_data_F: Option<Layer2FoldingData<E>>,
+  folding_data_io: Option<Layer2FoldingData<E>>,
+  io: Option<Layer1IO<E>>,
   folding_data_ops: Option<Layer2FoldingData<E>>,
   folding_data_scan: Option<Layer2FoldingData<E>>,
   U1_secondary: Option<RelaxedR1CSInstance<Dual<E>>>,
   params: AugmentedCircuitParams,
   ro_consts: ROConstantsCircuit<Dual<E>>,
+  arity_F: usize,
 }
 
 impl<E> StepCircuit<E::Scalar> for VerifierCircuit<E>
@@ -40,7 +45,7 @@ where
   E: CurveCycleEquipped,
 {
   fn arity(&self) -> usize {
-    1
+    2
   }
 
   fn synthesize<CS: ConstraintSystem<E::Scalar>>(
@@ -58,6 +63,16 @@ where
         &self.folding_data_F,
       )?;
 
+    // alloc witness for the last F instance, whose public IO binds the Layer 1 z0 and zi
+    let (pp_digest_io, _, U2_io, E_new_io, W_new_io, U2_secondary_io, nifs_io) =
+      VerifierCircuit::alloc_folding_data(
+        cs.namespace(|| "alloc folding data io"),
+        &self.params,
+        &self.folding_data_io,
+      )?;
+    let (id, num_steps, z0_F, zi_F, prev_IC_F, r_i_F) =
+      self.alloc_io(cs.namespace(|| "alloc io"))?;
+
     // alloc witness for ops
     let (pp_digest_ops, U1_ops, U2_ops, E_new_ops, W_new_ops, U2_secondary_ops, nifs_ops) =
       VerifierCircuit::alloc_folding_data(
@@ -108,6 +123,81 @@ where
       W_new_F,
     )?;
 
+    // The last F instance is a strict instance of the same circuit: u = 1 and E = 0
+    cs.enforce(
+      || "pp_digest_io == pp_digest_F",
+      |lc| lc + pp_digest_io.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + pp_digest_F.get_variable(),
+    );
+    cs.enforce(
+      || "U2_io.u == 1",
+      |lc| lc + U2_io.u.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + CS::one(),
+    );
+    cs.enforce(
+      || "U2_io.comm_E is infinity",
+      |lc| lc + U2_io.comm_E.is_infinity.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + CS::one(),
+    );
+
+    // Its public IO is the hash of the Layer 1 IO and of the running instances folded above, as
+    // checked by the Layer 1 verifier
+    let mut ro_p = <Dual<E> as Engine>::ROCircuit::new(
+      self.ro_consts.clone(),
+      4 + 2 * self.arity_F + 2 * NUM_FE_IN_EMULATED_POINT + 3, // (digest, num_steps, prev_IC, r_i) + 2 * arity "(z0, zi)" + U
+    );
+    ro_p.absorb(&pp_digest_F);
+    ro_p.absorb(&num_steps);
+    for e in z0_F.iter().chain(&zi_F) {
+      ro_p.absorb(e);
+    }
+    U2_F.absorb_in_ro(cs.namespace(|| "absorb U2_F"), &mut ro_p)?;
+    ro_p.absorb(&prev_IC_F);
+    ro_p.absorb(&r_i_F);
+    let hash_p_bits = ro_p.squeeze(cs.namespace(|| "hash_p bits"), NUM_HASH_BITS)?;
+    let hash_p = le_bits_to_num(cs.namespace(|| "hash_p"), &hash_p_bits)?;
+    cs.enforce(
+      || "hash_p == U2_io.x0",
+      |lc| lc + hash_p.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + U2_io.x0.get_variable(),
+    );
+
+    let mut ro_c = <Dual<E> as Engine>::ROCircuit::new(
+      self.ro_consts.clone(),
+      1 + 1 + 1 + 3 + 3 + 1 + NIO_CYCLE_FOLD * BN_N_LIMBS, // r_i + pp + i + W + E + u + X
+    );
+    ro_c.absorb(&pp_digest_F);
+    ro_c.absorb(&num_steps);
+    U2_secondary_F.absorb_in_ro_with_u_num(cs.namespace(|| "absorb U2_secondary_F"), &mut ro_c)?;
+    ro_c.absorb(&r_i_F);
+    let hash_c_bits = ro_c.squeeze(cs.namespace(|| "hash_c bits"), NUM_HASH_BITS)?;
+    let hash_c = le_bits_to_num(cs.namespace(|| "hash_c"), &hash_c_bits)?;
+    cs.enforce(
+      || "hash_c == U2_io.x1",
+      |lc| lc + hash_c.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + U2_io.x1.get_variable(),
+    );
+
+    // NIFS.V for the last F instance
+    let (U_F, U_secondary_temp_1) = nifs_io.verify(
+      cs.namespace(|| "nifs_io"),
+      self.ro_consts.clone(),
+      self.params.limb_width,
+      self.params.n_limbs,
+      &U_F,
+      &U2_io,
+      &U_secondary_temp_1,
+      &U2_secondary_io,
+      &pp_digest_F,
+      E_new_io,
+      W_new_io,
+    )?;
+
     // NIFS.V for ops
     let (U_ops, U_secondary_temp_2) = nifs_ops.verify(
       cs.namespace(|| "nifs_ops"),
@@ -149,7 +239,21 @@ where
     U_secondary.absorb_in_ro(cs.namespace(|| "absorb folded U_secondary"), &mut ro)?;
     let hash_U_bits = ro.squeeze(cs.namespace(|| "hash_folded_U bits"), NUM_CHALLENGE_BITS)?;
     let hash_U = le_bits_to_num(cs.namespace(|| "hash_folded_U"), &hash_U_bits)?;
-    Ok(vec![hash_U])
+
+    // chain the Layer 1 IO into the running hash of the aggregated IOs
+    let mut ro = <Dual<E> as Engine>::ROCircuit::new(
+      self.ro_consts.clone(),
+      3 + 2 * self.arity_F, // acc + id + num_steps + z0 + zi
+    );
+    ro.absorb(&z[1]);
+    ro.absorb(&id);
+    ro.absorb(&num_steps);
+    for e in z0_F.iter().chain(&zi_F) {
+      ro.absorb(e);
+    }
+    let hash_io_bits = ro.squeeze(cs.namespace(|| "hash_io bits"), NUM_HASH_BITS)?;
+    let hash_io = le_bits_to_num(cs.namespace(|| "hash_io"), &hash_io_bits)?;
+    Ok(vec![hash_U, hash_io])
   }
 
   fn non_deterministic_advice(&self) -> Vec<E::Scalar> {
@@ -161,10 +265,14 @@ impl<E> VerifierCircuit<E>
 where
   E: CurveCycleEquipped,
 {
+  #[allow(clippy::too_many_arguments)]
   pub fn new(
     params: AugmentedCircuitParams,
     ro_consts: ROConstantsCircuit<Dual<E>>,
+    arity_F: usize,
     folding_data_F: Option<Layer2FoldingData<E>>,
+    folding_data_io: Option<Layer2FoldingData<E>>,
+    io: Option<Layer1IO<E>>,
     folding_data_ops: Option<Layer2FoldingData<E>>,
     folding_data_scan: Option<Layer2FoldingData<E>>,
     U1_secondary: Option<RelaxedR1CSInstance<Dual<E>>>,
@@ -172,7 +280,10 @@ where
     Self {
       params,
       ro_consts,
+      arity_F,
       folding_data_F,
+      folding_data_io,
+      io,
       folding_data_ops,
       folding_data_scan,
       U1_secondary,
@@ -193,6 +304,54 @@ where
       self.params.n_limbs,
     )
   }
+
+  fn alloc_io<CS>(
+    &self,
+    mut cs: CS,
+  ) -> Result<
+    (
+      AllocatedNum<E::Scalar>,      // id
+      AllocatedNum<E::Scalar>,      // num_steps
+      Vec<AllocatedNum<E::Scalar>>, // z0
+      Vec<AllocatedNum<E::Scalar>>, // zi
+      AllocatedNum<E::Scalar>,      // prev_IC
+      AllocatedNum<E::Scalar>,      // r_i
+    ),
+    SynthesisError,
+  >
+  where
+    CS: ConstraintSystem<E::Scalar>,
+  {
+    let alloc_scalar = |cs: &mut CS, name: &str, value: Option<E::Scalar>| {
+      AllocatedNum::alloc(cs.namespace(|| name.to_string()), || {
+        Ok(value.unwrap_or(E::Scalar::ZERO))
+      })
+    };
+    let alloc_state = |cs: &mut CS, name: &str, state: Option<&Vec<E::Scalar>>| {
+      (0..self.arity_F)
+        .map(|i| {
+          alloc_scalar(
+            cs,
+            &format!("{name}[{i}]"),
+            state.and_then(|state| state.get(i).copied()),
+          )
+        })
+        .collect::<Result<Vec<_>, _>>()
+    };
+    let io = self.io.as_ref();
+    let id = alloc_scalar(&mut cs, "id", io.map(|io| io.id))?;
+    let num_steps = alloc_scalar(
+      &mut cs,
+      "num_steps",
+      io.map(|io| E::Scalar::from(io.num_steps as u64)),
+    )?;
+    let z0 = alloc_state(&mut cs, "z0", io.map(|io| &io.z0))?;
+    let zi = alloc_state(&mut cs, "zi", io.map(|io| &io.zi))?;
+    let prev_IC = alloc_scalar(&mut cs, "prev_IC", io.map(|io| io.prev_IC))?;
+    let r_i = alloc_scalar(&mut cs, "r_i", io.map(|io| io.r_i))?;
+    Ok((id, num_steps, z0, zi, prev_IC, r_i))
+  }
+
   fn alloc_folding_data<CS>(
     mut cs: CS,
     params: &AugmentedCircuitParams,
@@ -1,6 +1,6 @@
-use super::{AllocatedPoint, BigNat};
+use super::{AllocatedPoint, BigNat, Num};
 use crate::{
-  frontend::{ConstraintSystem, SynthesisError},
+  frontend::{ConstraintSystem, LinearCombination, SynthesisError},
   gadgets::f_to_nat,
   r1cs::RelaxedR1CSInstance,
   traits::{commitment::CommitmentTrait, Engine, ROCircuitTrait},
@@ -101,4 +101,52 @@ impl<E: Engine, const N: usize> AllocatedRelaxedR1CSInstanceBn<E, N> {
 
     Ok(())
   }
+
+  /// Absorb the provided instance in the RO with U.u as a single element, which is how the Layer 1
+  /// augmented circuit hashes its CycleFold running instance
+  pub fn absorb_in_ro_with_u_num<CS: ConstraintSystem<<E as Engine>::Base>>(
+    &self,
+    mut cs: CS,
+    ro: &mut E::ROCircuit,
+  ) -> Result<(), SynthesisError> {
+    ro.absorb(&self.W.x);
+    ro.absorb(&self.W.y);
+    ro.absorb(&self.W.is_infinity);
+    ro.absorb(&self.E.x);
+    ro.absorb(&self.E.y);
+    ro.absorb(&self.E.is_infinity);
+
+    // u << |E::Base|, so recomposing its limbs does not wrap around
+    let shift = E::Base::from(2).pow_vartime([self.u.params.limb_width as u64]);
+    let (u_lc, u_value, _) = self.u.limbs.iter().enumerate().fold(
+      (LinearCombination::zero(), Some(E::Base::ZERO), E::Base::ONE),
+      |(lc, value, coeff), (i, limb)| {
+        (
+          lc + (coeff, limb),
+          value
+            .zip(self.u.limb_values.as_ref())
+            .map(|(value, limbs)| value + coeff * limbs[i]),
+          coeff * shift,
+        )
+      },
+    );
+    let u = Num::new(u_value, u_lc).as_allocated_num(cs.namespace(|| "convert u to num"))?;
+    ro.absorb(&u);
+
+    self.X.iter().enumerate().try_for_each(|(idx, X)| {
+      X.as_limbs()
+        .iter()
+        .enumerate()
+        .try_for_each(|(i, limb)| -> Result<(), SynthesisError> {
+          ro.absorb(
+            &limb.as_allocated_num(
+              cs.namespace(|| format!("convert limb {i} of X_r[{idx}] to num")),
+            )?,
+          );
+          Ok(())
+        })
+    })?;
+
+    Ok(())
+  }
 }
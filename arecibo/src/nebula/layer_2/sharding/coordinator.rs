@@ -0,0 +1,321 @@
+//! Coordinates sharded proving of a long IVC over several independent workers.
+//!
+//! A run of `num_steps` step-circuit invocations is split by a [`ShardPlan`] into contiguous
+//! [`ShardRange`]s. Each range is proven as an independent Layer 1 proof (possibly in a separate
+//! process or on a separate machine), wrapped together with its boundary state in a [`Shard`] and
+//! shipped back as bytes. The [`ShardCoordinator`] then checks that the shards line up with the
+//! plan and with each other and folds them, in order, through the Sharding layer into a single
+//! [`ShardingRecursiveSNARK`].
+//!
+//! The boundary states (`z_start` of every shard) must be known before the workers start. For
+//! compliance-style computations they are obtained by running the step function natively, which is
+//! cheap compared to proving.
+//!
+//! The boundary of a shard is never trusted as shipped: it is derived from the Layer 1 proof of the
+//! shard (the IVC states of the F proof and the memory commitments of the scan proof), and a shard
+//! whose boundary does not match its proof is rejected.
+use super::{ShardingPublicParams, ShardingRecursiveSNARK};
+use crate::{
+  errors::NovaError,
+  nebula::{
+    ic::IC,
+    traits::{Layer1RSTrait, MemoryCommitmentsTraits},
+  },
+  traits::{CurveCycleEquipped, Dual, ROConstants},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Splits `num_steps` step-circuit invocations into `num_shards` contiguous ranges
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardPlan {
+  num_steps: usize,
+  num_shards: usize,
+}
+
+/// The half-open range of steps `[start, end)` proven by a single shard
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardRange {
+  /// Position of the shard in the plan
+  pub index: usize,
+  /// First step proven by the shard
+  pub start: usize,
+  /// One past the last step proven by the shard
+  pub end: usize,
+}
+
+impl ShardRange {
+  /// Number of steps proven by the shard
+  pub const fn len(&self) -> usize {
+    self.end - self.start
+  }
+
+  /// Returns true if the shard proves no steps
+  pub const fn is_empty(&self) -> bool {
+    self.start == self.end
+  }
+}
+
+impl ShardPlan {
+  /// Creates a plan splitting `num_steps` steps into `num_shards` shards. Every shard must prove at
+  /// least one step.
+  pub fn new(num_steps: usize, num_shards: usize) -> Result<Self, NovaError> {
+    if num_shards == 0 || num_steps < num_shards {
+      return Err(NovaError::InvalidNumSteps);
+    }
+    Ok(Self {
+      num_steps,
+      num_shards,
+    })
+  }
+
+  /// Total number of steps covered by the plan
+  pub const fn num_steps(&self) -> usize {
+    self.num_steps
+  }
+
+  /// Number of shards in the plan
+  pub const fn num_shards(&self) -> usize {
+    self.num_shards
+  }
+
+  /// Returns the range of the `index`-th shard. The first `num_steps % num_shards` shards take one
+  /// extra step, so shard sizes differ by at most one.
+  pub fn range(&self, index: usize) -> Result<ShardRange, NovaError> {
+    if index >= self.num_shards {
+      return Err(NovaError::InvalidIndex);
+    }
+    let base = self.num_steps / self.num_shards;
+    let extra = self.num_steps % self.num_shards;
+    let start = index * base + index.min(extra);
+    let len = base + usize::from(index < extra);
+    Ok(ShardRange {
+      index,
+      start,
+      end: start + len,
+    })
+  }
+
+  /// Returns the ranges of all the shards in order
+  pub fn ranges(&self) -> Vec<ShardRange> {
+    (0..self.num_shards)
+      .map(|index| self.range(index).expect("index is within the plan"))
+      .collect()
+  }
+}
+
+/// Boundary state of a shard: the IVC state it starts from and ends at, and the memory commitments
+/// it consumes and produces
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ShardBoundary<E>
+where
+  E: CurveCycleEquipped,
+{
+  /// Range of steps proven by the shard
+  pub range: ShardRange,
+  /// IVC state the shard starts from
+  pub z_start: Vec<E::Scalar>,
+  /// IVC state the shard ends at
+  pub z_end: Vec<E::Scalar>,
+  /// Commitment to the initial memory of the shard
+  pub C_IS: E::Scalar,
+  /// Commitment to the final memory of the shard
+  pub C_FS: E::Scalar,
+}
+
+impl<E> ShardBoundary<E>
+where
+  E: CurveCycleEquipped,
+{
+  /// Derives the boundary attested by the Layer 1 proof of `range`: the IVC states the F proof
+  /// starts from and ends at, and the incremental commitments to the initial and final memory of
+  /// the scan proof. The F proof must have proven exactly `range.len()` steps.
+  pub fn from_rs<RS>(range: ShardRange, rs: &RS) -> Result<Self, NovaError>
+  where
+    RS: Layer1RSTrait<E>,
+  {
+    if rs.F().num_steps() != range.len() {
+      return Err(NovaError::InvalidShard(format!(
+        "shard {} proves {} steps, expected {}",
+        range.index,
+        rs.F().num_steps(),
+        range.len()
+      )));
+    }
+    let ro_consts = ROConstants::<Dual<E>>::default();
+    let scan = rs.scan();
+    Ok(Self {
+      range,
+      z_start: rs.F().z0.clone(),
+      z_end: rs.F().zi.clone(),
+      C_IS: IC::<E>::increment_comm_w(&ro_consts, scan.prev_IC.0, scan.comm_omega_prev.0),
+      C_FS: IC::<E>::increment_comm_w(&ro_consts, scan.prev_IC.1, scan.comm_omega_prev.1),
+    })
+  }
+}
+
+impl<E> MemoryCommitmentsTraits<E> for ShardBoundary<E>
+where
+  E: CurveCycleEquipped,
+{
+  fn C_IS(&self) -> E::Scalar {
+    self.C_IS
+  }
+
+  fn C_FS(&self) -> E::Scalar {
+    self.C_FS
+  }
+}
+
+/// A Layer 1 proof of one shard together with its boundary state
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(serialize = "RS: Serialize", deserialize = "RS: DeserializeOwned"))]
+pub struct Shard<E, RS>
+where
+  E: CurveCycleEquipped,
+  RS: Layer1RSTrait<E>,
+{
+  /// Boundary state of the shard
+  pub boundary: ShardBoundary<E>,
+  /// Layer 1 proof of the shard
+  pub rs: RS,
+}
+
+impl<E, RS> Shard<E, RS>
+where
+  E: CurveCycleEquipped,
+  RS: Layer1RSTrait<E>,
+{
+  /// Wraps the Layer 1 proof of `range`, with the boundary derived from the proof (see
+  /// [`ShardBoundary::from_rs`])
+  pub fn new(range: ShardRange, rs: RS) -> Result<Self, NovaError> {
+    let boundary = ShardBoundary::from_rs(range, &rs)?;
+    Ok(Self { boundary, rs })
+  }
+
+  /// Checks that the boundary of the shard is the one attested by its Layer 1 proof
+  pub fn check(&self) -> Result<(), NovaError> {
+    if ShardBoundary::from_rs(self.boundary.range, &self.rs)? != self.boundary {
+      return Err(NovaError::InvalidShard(format!(
+        "boundary of shard {} does not match its proof",
+        self.boundary.range.index
+      )));
+    }
+    Ok(())
+  }
+}
+
+impl<E, RS> Shard<E, RS>
+where
+  E: CurveCycleEquipped,
+  RS: Layer1RSTrait<E> + Serialize + DeserializeOwned,
+{
+  /// Serializes the shard so it can be shipped from a worker to the coordinator
+  pub fn to_bytes(&self) -> Result<Vec<u8>, NovaError> {
+    bincode::serialize(self).map_err(|e| NovaError::SerializationError(e.to_string()))
+  }
+
+  /// Deserializes a shard produced by [`Shard::to_bytes`], and checks its boundary against its
+  /// proof
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, NovaError> {
+    let shard: Self =
+      bincode::deserialize(bytes).map_err(|e| NovaError::SerializationError(e.to_string()))?;
+    shard.check()?;
+    Ok(shard)
+  }
+}
+
+/// Checks shards produced by independent workers and folds them through the Sharding layer
+pub struct ShardCoordinator<'a, E>
+where
+  E: CurveCycleEquipped,
+{
+  pp: &'a ShardingPublicParams<E>,
+  plan: ShardPlan,
+}
+
+impl<'a, E> ShardCoordinator<'a, E>
+where
+  E: CurveCycleEquipped,
+{
+  /// Creates a coordinator for the given plan
+  pub fn new(pp: &'a ShardingPublicParams<E>, plan: ShardPlan) -> Self {
+    Self { pp, plan }
+  }
+
+  /// Returns the plan the coordinator merges shards for
+  pub fn plan(&self) -> &ShardPlan {
+    &self.plan
+  }
+
+  /// Checks that `shards` cover the plan in order and chain into each other: the boundary of each
+  /// shard must be the one attested by its proof, and the IVC state and the memory commitment each
+  /// shard ends with must be the ones the next shard starts from.
+  pub fn check_shards<RS>(&self, shards: &[Shard<E, RS>]) -> Result<(), NovaError>
+  where
+    RS: Layer1RSTrait<E>,
+  {
+    if shards.len() != self.plan.num_shards() {
+      return Err(NovaError::InvalidShard(format!(
+        "got {} shards, plan expects {}",
+        shards.len(),
+        self.plan.num_shards()
+      )));
+    }
+    for (expected, shard) in self.plan.ranges().iter().zip(shards) {
+      if shard.boundary.range != *expected {
+        return Err(NovaError::InvalidShard(format!(
+          "shard {} covers steps {}..{}, plan expects {}..{}",
+          expected.index,
+          shard.boundary.range.start,
+          shard.boundary.range.end,
+          expected.start,
+          expected.end
+        )));
+      }
+      shard.check()?;
+    }
+    for pair in shards.windows(2) {
+      let (prev, next) = (&pair[0].boundary, &pair[1].boundary);
+      if prev.z_end != next.z_start {
+        return Err(NovaError::InvalidShard(format!(
+          "state at the end of shard {} does not match the start of shard {}",
+          prev.range.index, next.range.index
+        )));
+      }
+      if prev.C_FS != next.C_IS {
+        return Err(NovaError::InvalidShard(format!(
+          "final memory of shard {} does not match the initial memory of shard {}",
+          prev.range.index, next.range.index
+        )));
+      }
+    }
+    Ok(())
+  }
+
+  /// Folds the shards, in plan order, into a single Sharding proof
+  #[tracing::instrument(skip_all, name = "ShardCoordinator::merge")]
+  pub fn merge<RS>(&self, shards: &[Shard<E, RS>]) -> Result<ShardingRecursiveSNARK<E>, NovaError>
+  where
+    RS: Layer1RSTrait<E>,
+  {
+    self.check_shards(shards)?;
+    let mut sharding_rs = ShardingRecursiveSNARK::new(self.pp, &shards[0].rs, &shards[0].boundary)?;
+    for shard in shards {
+      sharding_rs.prove_step(self.pp, &shard.rs, &shard.boundary)?;
+    }
+    Ok(sharding_rs)
+  }
+
+  /// Deserializes the shards produced by [`Shard::to_bytes`] and folds them
+  pub fn merge_bytes<RS>(&self, shards: &[Vec<u8>]) -> Result<ShardingRecursiveSNARK<E>, NovaError>
+  where
+    RS: Layer1RSTrait<E> + Serialize + DeserializeOwned,
+  {
+    let shards = shards
+      .iter()
+      .map(|bytes| Shard::<E, RS>::from_bytes(bytes))
+      .collect::<Result<Vec<_>, _>>()?;
+    self.merge(&shards)
+  }
+}
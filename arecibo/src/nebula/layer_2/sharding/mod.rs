@@ -27,6 +27,7 @@ use std::sync::Arc;
 use verifier_circuit::VerifierCircuit;
 
 pub mod compression;
+pub mod coordinator;
 mod verifier_circuit;
 
 /// Defines the public parameters for the Sharding layer
@@ -667,8 +668,9 @@ where
 #[cfg(test)]
 mod test {
   use super::{
-    compression::CompressedSNARK, Layer1PPTrait, Layer1RSTrait, ShardingPublicParams,
-    ShardingRecursiveSNARK,
+    compression::CompressedSNARK,
+    coordinator::{Shard, ShardCoordinator, ShardPlan},
+    Layer1PPTrait, Layer1RSTrait, ShardingPublicParams, ShardingRecursiveSNARK,
   };
   use crate::{
     frontend::{num::AllocatedNum, ConstraintSystem, SynthesisError},
@@ -681,6 +683,7 @@ mod test {
     traits::{snark::default_ck_hint, Dual, Engine},
   };
   use ff::{Field, PrimeField};
+  use serde::{Deserialize, Serialize};
 
   type E1 = Bn256EngineIPA;
   type F = <E1 as Engine>::Scalar;
@@ -697,6 +700,12 @@ mod test {
       .in_scope(|| sharding_node(node_pp, &nodes_rs));
   }
 
+  #[test]
+  fn test_shard_coordinator() {
+    let (node_pp, nodes_rs) = node_nw(3);
+    sharding_coordinator(node_pp, nodes_rs);
+  }
+
   fn sharding_node(node_pp: NodePP, nodes_rs: &[NodeRS]) {
     let sharding_pp =
       ShardingPublicParams::<E1>::setup(node_pp, &*default_ck_hint(), &*default_ck_hint());
@@ -715,6 +724,52 @@ mod test {
     snark.verify(&sharding_pp, &vk).unwrap();
   }
 
+  fn sharding_coordinator(node_pp: NodePP, nodes_rs: Vec<NodeRS>) {
+    let sharding_pp =
+      ShardingPublicParams::<E1>::setup(node_pp, &*default_ck_hint(), &*default_ck_hint());
+    let steps_per_shard = nodes_rs[0].F().num_steps();
+    let plan = ShardPlan::new(steps_per_shard * nodes_rs.len(), nodes_rs.len()).unwrap();
+
+    // Every worker proves its range independently and ships the shard back as bytes
+    let shard_bytes = plan
+      .ranges()
+      .into_iter()
+      .zip(nodes_rs)
+      .map(|(range, node_rs)| {
+        Shard::<E1, NodeRS>::new(range, node_rs)
+          .unwrap()
+          .to_bytes()
+          .unwrap()
+      })
+      .collect::<Vec<_>>();
+
+    let coordinator = ShardCoordinator::new(&sharding_pp, plan);
+    let sharding_engine = coordinator.merge_bytes::<NodeRS>(&shard_bytes).unwrap();
+    sharding_engine.verify(&sharding_pp).unwrap();
+
+    // Shards handed over out of order are rejected
+    let mut shards = shard_bytes
+      .iter()
+      .map(|bytes| Shard::<E1, NodeRS>::from_bytes(bytes).unwrap())
+      .collect::<Vec<_>>();
+    shards.swap(0, 1);
+    assert!(coordinator.check_shards(&shards).is_err());
+    shards.swap(0, 1);
+
+    // A shard claiming a boundary its proof does not attest is rejected, even if the claimed
+    // boundaries line up with each other
+    let z_tampered = vec![F::from(42u64)];
+    shards[0].boundary.z_end = z_tampered.clone();
+    shards[1].boundary.z_start = z_tampered;
+    assert!(coordinator.check_shards(&shards).is_err());
+    assert!(Shard::<E1, NodeRS>::from_bytes(&shards[1].to_bytes().unwrap()).is_err());
+
+    shards[0].boundary.z_end = shards[0].rs.F().zi.clone();
+    shards[1].boundary.z_start = shards[1].rs.F().z0.clone();
+    shards[1].boundary.C_IS += F::ONE;
+    assert!(coordinator.check_shards(&shards).is_err());
+  }
+
   struct NodePP {
     pp1: PublicParams<E1>,
     pp2: PublicParams<E1>,
@@ -739,6 +794,8 @@ mod test {
     }
   }
 
+  #[derive(Serialize, Deserialize)]
+  #[serde(bound = "")]
   struct NodeRS {
     rs1: RecursiveSNARK<E1>,
     rs2: RecursiveSNARK<E1>,
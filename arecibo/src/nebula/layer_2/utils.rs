@@ -1,9 +1,9 @@
 use crate::{
-  constants::{BN_LIMB_WIDTH, BN_N_LIMBS, NUM_CHALLENGE_BITS},
+  constants::{BN_LIMB_WIDTH, BN_N_LIMBS, NUM_CHALLENGE_BITS, NUM_HASH_BITS},
   cyclefold::util::absorb_primary_commitment,
   errors::NovaError,
   gadgets::{f_to_nat, nat_to_limbs, scalar_as_base},
-  nebula::nifs::PrimaryRelaxedNIFS,
+  nebula::{nifs::PrimaryRelaxedNIFS, rs::RecursiveSNARK},
   r1cs::{R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness},
   traits::{
     commitment::CommitmentEngineTrait, AbsorbInROTrait, CurveCycleEquipped, Dual, Engine,
@@ -108,6 +108,62 @@ where
   }
 }
 
+/// Public IO of a Layer 1 proof, as hashed into the public IO of its last primary instance, tagged
+/// with the identifier of the proof's owner
+#[derive(Debug, Clone)]
+pub struct Layer1IO<E>
+where
+  E: CurveCycleEquipped,
+{
+  pub(crate) id: E::Scalar,
+  pub(crate) num_steps: usize,
+  pub(crate) z0: Vec<E::Scalar>,
+  pub(crate) zi: Vec<E::Scalar>,
+  pub(crate) prev_IC: E::Scalar,
+  pub(crate) r_i: E::Scalar,
+}
+
+impl<E> Layer1IO<E>
+where
+  E: CurveCycleEquipped,
+{
+  pub fn new(id: E::Scalar, rs: &RecursiveSNARK<E>) -> Self {
+    Self {
+      id,
+      num_steps: rs.num_steps(),
+      z0: rs.z0.clone(),
+      zi: rs.zi.clone(),
+      prev_IC: rs.prev_IC,
+      r_i: rs.r_i,
+    }
+  }
+}
+
+/// Chains the public IO of a Layer 1 proof into `acc`, as the Aggregation verifier circuit does
+pub(crate) fn chain_io_hash<E>(
+  ro_consts: &ROConstants<Dual<E>>,
+  acc: E::Scalar,
+  id: E::Scalar,
+  num_steps: usize,
+  z0: &[E::Scalar],
+  zi: &[E::Scalar],
+) -> E::Scalar
+where
+  E: CurveCycleEquipped,
+{
+  let mut ro = <Dual<E> as Engine>::RO::new(
+    ro_consts.clone(),
+    3 + z0.len() + zi.len(), // acc + id + num_steps + z0 + zi
+  );
+  ro.absorb(acc);
+  ro.absorb(id);
+  ro.absorb(E::Scalar::from(num_steps as u64));
+  for e in z0.iter().chain(zi) {
+    ro.absorb(*e);
+  }
+  scalar_as_base::<Dual<E>>(ro.squeeze(NUM_HASH_BITS))
+}
+
 pub fn random_fold_and_derandom<E>(
   S: &R1CSShape<E>,
   ck: &CommitmentKey<E>,
@@ -10,6 +10,8 @@ pub mod fund_circuit;
 pub mod fund_flow;
 /// Simplified fund compliance test (without compression).
 pub mod fund_flow_simple;
+/// Spartan verifier full flow test.
+pub mod spartan_flow;
 
 /// Tests for the decider circuit.
 #[cfg(test)]
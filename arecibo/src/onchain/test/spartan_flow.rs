@@ -0,0 +1,89 @@
+#![allow(clippy::upper_case_acronyms)]
+
+#[cfg(test)]
+mod tests {
+  ///
+  /// This example performs the Spartan flow:
+  /// - fold the circuit with Nova+CycleFold's IVC
+  /// - prove the primary running instance with Spartan+HyperKZG
+  /// - generate the Solidity contract that verifies the Spartan proof
+  /// - verify the proof in the EVM
+  /// The contract only checks the satisfiability of the primary running instance, not the IVC
+  use crate::{
+    nebula::rs::{PublicParams, RecursiveSNARK},
+    onchain::{
+      test::circuit::CubicFCircuit,
+      verifiers::spartan::{prepare_relaxed_r1cs_calldata, SolidityRelaxedR1CSVerifierKey},
+    },
+    provider::{Bn256EngineKZG, GrumpkinEngine},
+    traits::{snark::RelaxedR1CSSNARKTrait, Engine},
+  };
+  use ff::Field;
+  use halo2curves::bn256::Bn256;
+
+  #[cfg(feature = "solidity")]
+  use crate::onchain::{
+    eth::evm::{compile_solidity, Evm},
+    verifiers::spartan::get_relaxed_r1cs_verifier_template,
+  };
+
+  type E1 = Bn256EngineKZG;
+  type E2 = GrumpkinEngine;
+  type EE1 = crate::provider::hyperkzg::EvaluationEngine<Bn256, E1>;
+  type EE2 = crate::provider::ipa_pc::EvaluationEngine<E2>;
+  type S1 = crate::spartan::snark::RelaxedR1CSSNARK<E1, EE1>; // non-preprocessing SNARK
+  type S2 = crate::spartan::snark::RelaxedR1CSSNARK<E2, EE2>; // non-preprocessing SNARK
+
+  #[test]
+  fn test_spartan_flow() {
+    let num_steps = 3;
+    let f_circuit = CubicFCircuit::new();
+    let rs_pp = PublicParams::<E1>::setup(&f_circuit, &*S1::ck_floor(), &*S2::ck_floor());
+    let z0 = vec![<E1 as Engine>::Scalar::from(3u64)];
+    let mut rs = RecursiveSNARK::<E1>::new(&rs_pp, &f_circuit, &z0).unwrap();
+    let mut IC_i = <E1 as Engine>::Scalar::ZERO;
+    for _i in 0..num_steps {
+      rs.prove_step(&rs_pp, &f_circuit, IC_i).unwrap();
+      IC_i = rs.increment_commitment(&rs_pp, &f_circuit);
+    }
+    rs.verify(&rs_pp, num_steps, &z0, IC_i).unwrap();
+
+    // prove the primary running instance with Spartan
+    let S = &rs_pp.circuit_shape_primary.r1cs_shape;
+    let (pk, vk) = S1::setup(rs_pp.ck_primary.clone(), S).unwrap();
+    let snark = S1::prove(&rs_pp.ck_primary, &pk, S, &rs.r_U_primary, &rs.r_W_primary).unwrap();
+    snark.verify(&vk, &rs.r_U_primary).unwrap();
+
+    let spartan_vk = SolidityRelaxedR1CSVerifierKey::from(&vk);
+    let calldata = prepare_relaxed_r1cs_calldata(&vk, &rs.r_U_primary, &snark).unwrap();
+    assert_eq!(
+      calldata.len(),
+      4 + 32 * (spartan_vk.instance_len() + spartan_vk.proof_len() + spartan_vk.matrices_len())
+    );
+
+    #[cfg(feature = "solidity")]
+    {
+      let spartan_solidity_code = get_relaxed_r1cs_verifier_template(spartan_vk.clone());
+      let spartan_verifier_bytecode =
+        compile_solidity(&spartan_solidity_code, "RelaxedR1CSSpartanVerifier");
+      let mut evm = Evm::default();
+      let verifier_address = evm.create(spartan_verifier_bytecode);
+
+      let (gas, output) = evm.call(verifier_address, calldata.clone());
+      println!("Spartan verification gas: {:?}", gas);
+      assert_eq!(*output.last().unwrap(), 1);
+
+      // tamper with eval_W, which sits right after the inner sum-check
+      let mut invalid_calldata = calldata.clone();
+      let eval_W_offset = 4
+        + 32
+          * (spartan_vk.instance_len()
+            + 3 * spartan_vk.num_rounds_x()
+            + 4
+            + 2 * spartan_vk.num_rounds_y());
+      invalid_calldata[eval_W_offset + 31] ^= 1;
+      let (_, output) = evm.call(verifier_address, invalid_calldata);
+      assert_eq!(*output.last().unwrap(), 0);
+    }
+  }
+}
@@ -31,6 +31,23 @@ pub fn get_function_selector_for_nova_cyclefold_verifier(
     [hash[0], hash[1], hash[2], hash[3]]
 }
 
+/// Computes the function selector for the Spartan verifier of relaxed R1CS instances
+/// It is computed on the fly since it depends on the lengths of the instance, proof and matrices arrays
+pub fn get_function_selector_for_relaxed_r1cs_verifier(
+    instance_len: usize,
+    proof_len: usize,
+    matrices_len: usize,
+) -> [u8; 4] {
+    let fn_sig = format!(
+        "verifyRelaxedR1CSProof(uint256[{}],uint256[{}],uint256[{}])",
+        instance_len, proof_len, matrices_len
+    );
+    let mut hasher = Keccak256::new();
+    hasher.update(fn_sig.as_bytes());
+    let hash = hasher.finalize();
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
 /// Header inclusion template
 #[derive(Template)]
 #[template(path = "header_template.askama.sol", ext = "sol")]
@@ -5,6 +5,8 @@
 pub const PRAGMA_GROTH16_VERIFIER: &str = "pragma solidity >=0.7.0 <0.9.0;"; // from snarkjs, avoid changing
 /// Pragma statements for KZG verifiers
 pub const PRAGMA_KZG10_VERIFIER: &str = "pragma solidity >=0.8.1 <=0.8.4;";
+/// Pragma statements for Spartan verifiers
+pub const PRAGMA_SPARTAN_VERIFIER: &str = "pragma solidity >=0.8.4 <0.9.0;";
 
 /// GPL3 SDPX License identifier
 pub const GPL3_SDPX_IDENTIFIER: &str = "// SPDX-License-Identifier: GPL-3.0";
@@ -14,6 +16,7 @@ pub const MIT_SDPX_IDENTIFIER: &str = "// SPDX-License-Identifier: MIT";
 pub mod groth16;
 pub mod kzg;
 pub mod nebula;
+pub mod spartan;
 
 use serde::{Serialize, de::DeserializeOwned};
 use std::io::{Write, Read};
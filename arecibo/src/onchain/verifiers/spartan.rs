@@ -0,0 +1,306 @@
+//! Spartan verifier of relaxed R1CS instances
+//!
+//! Renders a Solidity verifier for [`RelaxedR1CSSNARK`] over BN254 with the HyperKZG evaluation
+//! engine, and encodes its proofs as calldata. Unlike the NovaCycleFold decider, the proof is checked
+//! directly on the EVM, without a Groth16 wrapper.
+//!
+//! This is not an IVC verifier. The contract only checks that the given relaxed R1CS instance, eg.
+//! the primary running instance `r_U_primary` of a recursive SNARK, is satisfiable. It does not
+//! check the folding of the last incoming instance into it, the CycleFold (secondary) instances,
+//! nor that the public IO of the instance hashes the number of steps, `z_0` and `z_i`. Accepting a
+//! proof hence says nothing about the output of the IVC: use the NovaCycleFold decider for that.
+//!
+//! This version of Spartan does not use preprocessing, so the contract evaluates the R1CS matrices.
+//! They are sent in calldata alongside the proof and checked against a digest embedded in the
+//! contract, hence the gas cost grows with the number of non-zero entries of the circuit.
+//! Only the BN254 side is covered: the CycleFold SNARK of `nebula::compression::CompressedSNARK`
+//! lives on Grumpkin, for which the EVM has no precompiles.
+use crate::errors::NovaError;
+use crate::onchain::eth::ToEth;
+use crate::onchain::utils::encoding::{g1_to_fq_repr, g2_to_fq_repr, G1Repr, G2Repr};
+use crate::onchain::utils::HeaderInclusion;
+use crate::onchain::verifiers::{ProtocolVerifierKey, PRAGMA_SPARTAN_VERIFIER};
+use crate::provider::hyperkzg::EvaluationEngine;
+use crate::provider::kzg_commitment::KZGVerifierKey;
+use crate::provider::Bn256EngineKZG;
+use crate::r1cs::{R1CSShape, RelaxedR1CSInstance, SparseMatrix};
+use crate::spartan::snark::{RelaxedR1CSSNARK, VerifierKey};
+use crate::spartan::sumcheck::SumcheckProof;
+use crate::traits::snark::DigestHelperTrait;
+use askama::Template;
+use halo2curves::bn256::{Bn256, Fr};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// Spartan SNARK of a relaxed R1CS instance, verified by [`SolidityRelaxedR1CSVerifier`]
+pub type SpartanSNARK = RelaxedR1CSSNARK<Bn256EngineKZG, EvaluationEngine<Bn256, Bn256EngineKZG>>;
+/// Native verifier key of [`SpartanSNARK`]
+pub type SpartanSNARKVerifierKey = VerifierKey<Bn256EngineKZG, EvaluationEngine<Bn256, Bn256EngineKZG>>;
+
+/// Solidity Keccak256 transcript
+#[derive(Template, Default)]
+#[template(path = "keccak_transcript.askama.sol", ext = "sol")]
+pub struct SolidityKeccakTranscript;
+
+/// Solidity HyperKZG verifier
+#[derive(Template, Default)]
+#[template(path = "hyperkzg_verifier.askama.sol", ext = "sol")]
+pub struct SolidityHyperKZGVerifier {
+    /// The generator of `G1`.
+    pub(crate) g1: G1Repr,
+    /// The generator of `G2`.
+    pub(crate) h: G2Repr,
+    /// `beta` times the generator of `G2`.
+    pub(crate) beta_h: G2Repr,
+}
+
+impl From<KZGVerifierKey<Bn256>> for SolidityHyperKZGVerifier {
+    fn from(vk: KZGVerifierKey<Bn256>) -> Self {
+        Self {
+            g1: g1_to_fq_repr(vk.g),
+            h: g2_to_fq_repr(vk.h),
+            beta_h: g2_to_fq_repr(vk.beta_h),
+        }
+    }
+}
+
+/// Verifier key of [`SolidityRelaxedR1CSVerifier`]
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SolidityRelaxedR1CSVerifierKey {
+    /// Digest of the native verifier key
+    pub vk_digest: Fr,
+    /// Number of constraints of the padded R1CS shape
+    pub num_cons: usize,
+    /// Number of witness variables of the padded R1CS shape
+    pub num_vars: usize,
+    /// Number of public inputs
+    pub num_io: usize,
+    /// Number of non-zero entries of A, B and C
+    pub nnz: [usize; 3],
+    /// Keccak256 of the R1CS matrices, as encoded by [`matrices_to_eth`]
+    pub matrices_digest: [u8; 32],
+    /// HyperKZG verification key
+    pub kzg_vk: KZGVerifierKey<Bn256>,
+}
+
+impl From<&SpartanSNARKVerifierKey> for SolidityRelaxedR1CSVerifierKey {
+    fn from(vk: &SpartanSNARKVerifierKey) -> Self {
+        let matrices = matrices_to_eth(&vk.S);
+        Self {
+            vk_digest: vk.digest(),
+            num_cons: vk.S.num_cons,
+            num_vars: vk.S.num_vars,
+            num_io: vk.S.num_io,
+            nnz: [vk.S.A.len(), vk.S.B.len(), vk.S.C.len()],
+            matrices_digest: Keccak256::digest(matrices).into(),
+            kzg_vk: vk.vk_ee.clone(),
+        }
+    }
+}
+
+impl SolidityRelaxedR1CSVerifierKey {
+    /// Number of sum-check rounds over the constraints
+    pub fn num_rounds_x(&self) -> usize {
+        self.num_cons.ilog2() as usize
+    }
+
+    /// Number of sum-check rounds over the variables `(W, u, X)`
+    pub fn num_rounds_y(&self) -> usize {
+        self.num_vars.ilog2() as usize + 1
+    }
+
+    /// Number of sum-check rounds batching the evaluation claims about `W` and `E`
+    pub fn num_rounds_batch(&self) -> usize {
+        (self.num_rounds_y() - 1).max(self.num_rounds_x())
+    }
+
+    /// Length of the `instance` array of the verifier: `[comm_W[2], comm_E[2], u, X]`
+    pub fn instance_len(&self) -> usize {
+        5 + self.num_io
+    }
+
+    /// Length of the `proof` array of the verifier
+    pub fn proof_len(&self) -> usize {
+        let (num_rounds_x, num_rounds_y, num_rounds_batch) =
+            (self.num_rounds_x(), self.num_rounds_y(), self.num_rounds_batch());
+        // outer sum-check, claims_outer and eval_E
+        3 * num_rounds_x + 4
+            // inner sum-check and eval_W
+            + 2 * num_rounds_y + 1
+            // batching sum-check and evals_batch
+            + 2 * num_rounds_batch + 2
+            // HyperKZG comms, evals, R_x, C_Q and C_H
+            + 2 * (num_rounds_batch - 1) + 3 * num_rounds_batch + 3 + 4
+    }
+
+    /// Length of the `matrices` array of the verifier
+    pub fn matrices_len(&self) -> usize {
+        2 * self.nnz.iter().sum::<usize>()
+    }
+
+    /// Selector of `verifyRelaxedR1CSProof` for this circuit
+    pub fn function_selector(&self) -> [u8; 4] {
+        crate::onchain::utils::get_function_selector_for_relaxed_r1cs_verifier(
+            self.instance_len(),
+            self.proof_len(),
+            self.matrices_len(),
+        )
+    }
+}
+
+/// Solidity Spartan verifier of relaxed R1CS instances, see the module documentation for its scope
+#[derive(Template, Default)]
+#[template(path = "spartan_hyperkzg_verifier.askama.sol", ext = "sol")]
+pub struct SolidityRelaxedR1CSVerifier {
+    transcript: SolidityKeccakTranscript,
+    hyperkzg_verifier: SolidityHyperKZGVerifier,
+    vk_digest: String,
+    matrices_digest: String,
+    num_io: usize,
+    num_rounds_x: usize,
+    num_rounds_y: usize,
+    num_rounds_batch: usize,
+    // the public IO polynomial skips its first `x_num_common` variables, see `SparsePolynomial::evaluate`
+    x_num_common: usize,
+    nnz_a: usize,
+    nnz_b: usize,
+    nnz_c: usize,
+    instance_len: usize,
+    proof_len: usize,
+    matrices_len: usize,
+}
+
+impl From<SolidityRelaxedR1CSVerifierKey> for SolidityRelaxedR1CSVerifier {
+    fn from(value: SolidityRelaxedR1CSVerifierKey) -> Self {
+        let num_vars_z = (value.num_io + 1).next_power_of_two().ilog2() as usize;
+        Self {
+            transcript: SolidityKeccakTranscript,
+            vk_digest: format!("{:?}", value.vk_digest),
+            matrices_digest: format!("0x{}", hex::encode(value.matrices_digest)),
+            num_io: value.num_io,
+            num_rounds_x: value.num_rounds_x(),
+            num_rounds_y: value.num_rounds_y(),
+            num_rounds_batch: value.num_rounds_batch(),
+            x_num_common: value.num_rounds_y() - 2 - num_vars_z,
+            nnz_a: value.nnz[0],
+            nnz_b: value.nnz[1],
+            nnz_c: value.nnz[2],
+            instance_len: value.instance_len(),
+            proof_len: value.proof_len(),
+            matrices_len: value.matrices_len(),
+            hyperkzg_verifier: SolidityHyperKZGVerifier::from(value.kzg_vk),
+        }
+    }
+}
+
+/// Get the Solidity verifier of relaxed R1CS instances for the given verifier key
+pub fn get_relaxed_r1cs_verifier_template(spartan_vk: SolidityRelaxedR1CSVerifierKey) -> String {
+    HeaderInclusion::<SolidityRelaxedR1CSVerifier>::builder()
+        .pragma_version(PRAGMA_SPARTAN_VERIFIER)
+        .template(spartan_vk)
+        .build()
+        .render()
+        .unwrap()
+}
+
+impl ProtocolVerifierKey for SolidityRelaxedR1CSVerifierKey {
+    const PROTOCOL_NAME: &'static str = "SpartanHyperKZGRelaxedR1CS";
+
+    fn render_as_template(self, pragma: Option<String>) -> Vec<u8> {
+        HeaderInclusion::<SolidityRelaxedR1CSVerifier>::builder()
+            .pragma_version(pragma.unwrap_or(PRAGMA_SPARTAN_VERIFIER.to_string()))
+            .template(self)
+            .build()
+            .render()
+            .unwrap()
+            .into_bytes()
+    }
+}
+
+/// Encodes the non-zero entries of A, B and C as `[row << 128 | col, val]` words, in that order
+pub fn matrices_to_eth(S: &R1CSShape<Bn256EngineKZG>) -> Vec<u8> {
+    let encode = |M: &SparseMatrix<Fr>| -> Vec<u8> {
+        if M.len() == 0 {
+            return Vec::new();
+        }
+        M.iter()
+            .flat_map(|(row, col, val)| {
+                let mut entry = [0u8; 32];
+                entry[8..16].copy_from_slice(&(row as u64).to_be_bytes());
+                entry[24..32].copy_from_slice(&(col as u64).to_be_bytes());
+                [entry.to_vec(), val.to_eth()].concat()
+            })
+            .collect()
+    };
+    [encode(&S.A), encode(&S.B), encode(&S.C)].concat()
+}
+
+fn sumcheck_to_eth(
+    sc_proof: &SumcheckProof<Bn256EngineKZG>,
+    num_rounds: usize,
+    degree: usize,
+) -> Result<Vec<u8>, NovaError> {
+    if sc_proof.compressed_polys.len() != num_rounds
+        || sc_proof
+            .compressed_polys
+            .iter()
+            .any(|poly| poly.coeffs_except_linear_term.len() != degree)
+    {
+        return Err(NovaError::InvalidSumcheckProof);
+    }
+    Ok(sc_proof
+        .compressed_polys
+        .iter()
+        .flat_map(|poly| poly.coeffs_except_linear_term.to_eth())
+        .collect())
+}
+
+/// Prepares solidity calldata for calling the RelaxedR1CSSpartanVerifier contract
+pub fn prepare_relaxed_r1cs_calldata(
+    vk: &SpartanSNARKVerifierKey,
+    U: &RelaxedR1CSInstance<Bn256EngineKZG>,
+    snark: &SpartanSNARK,
+) -> Result<Vec<u8>, NovaError> {
+    let spartan_vk = SolidityRelaxedR1CSVerifierKey::from(vk);
+    if U.X.len() != spartan_vk.num_io {
+        return Err(NovaError::InvalidInputLength);
+    }
+    let num_rounds_batch = spartan_vk.num_rounds_batch();
+    let eval_arg = &snark.eval_arg;
+    if eval_arg.comms.len() + 1 != num_rounds_batch
+        || eval_arg.evals.len() != 3
+        || eval_arg.evals.iter().any(|evals| evals.len() != num_rounds_batch)
+        || eval_arg.R_x.len() != 3
+    {
+        return Err(NovaError::ProofVerifyError);
+    }
+
+    Ok(
+        [
+            spartan_vk.function_selector().to_eth(),
+            // instance
+            U.comm_W.to_eth(),
+            U.comm_E.to_eth(),
+            U.u.to_eth(),
+            U.X.to_eth(),
+            // proof
+            sumcheck_to_eth(&snark.sc_proof_outer, spartan_vk.num_rounds_x(), 3)?,
+            snark.claims_outer.0.to_eth(),
+            snark.claims_outer.1.to_eth(),
+            snark.claims_outer.2.to_eth(),
+            snark.eval_E.to_eth(),
+            sumcheck_to_eth(&snark.sc_proof_inner, spartan_vk.num_rounds_y(), 2)?,
+            snark.eval_W.to_eth(),
+            sumcheck_to_eth(&snark.sc_proof_batch, num_rounds_batch, 2)?,
+            snark.evals_batch.to_eth(),
+            eval_arg.comms.to_eth(),
+            eval_arg.evals.iter().flat_map(|evals| evals.to_eth()).collect(),
+            eval_arg.R_x.to_eth(),
+            eval_arg.C_Q.to_eth(),
+            eval_arg.C_H.to_eth(),
+            // matrices
+            matrices_to_eth(&vk.S),
+        ]
+        .concat(),
+    )
+}
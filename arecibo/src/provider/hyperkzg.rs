@@ -43,11 +43,11 @@ use std::sync::Arc;
   deserialize = "E::G1Affine: Deserialize<'de>, E::Fr: Deserialize<'de>"
 ))]
 pub struct EvaluationArgument<E: Engine> {
-  comms: Vec<E::G1Affine>,
-  evals: Vec<Vec<E::Fr>>,
-  R_x: Vec<E::Fr>,
-  C_Q: E::G1Affine,
-  C_H: E::G1Affine,
+  pub(crate) comms: Vec<E::G1Affine>,
+  pub(crate) evals: Vec<Vec<E::Fr>>,
+  pub(crate) R_x: Vec<E::Fr>,
+  pub(crate) C_Q: E::G1Affine,
+  pub(crate) C_H: E::G1Affine,
 }
 
 /// Provides an implementation of a polynomial evaluation engine using KZG
@@ -12,7 +12,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use crate::{
-  digest::SimpleDigestible, frontend::{gpu::GpuName, ConstraintSystem, SynthesisError}, gadgets::AllocatedPoint, provider::{pedersen::Commitment, traits::DlogGroup, util::fb_msm}, traits::{
+  digest::SimpleDigestible, frontend::{gpu::GpuName, ConstraintSystem, SynthesisError}, gadgets::AllocatedPoint, provider::{pedersen::Commitment, traits::DlogGroup, util::{cpu_msm::FixedBaseCache, fb_msm}}, traits::{
     commitment::{CommitmentEngineTrait, Len},
     Engine as NovaEngine, Group, TranscriptReprTrait,
   } 
@@ -38,6 +38,9 @@ pub struct UniversalKZGParam<E: Engine> {
   pub powers_of_h: Vec<E::G2Affine>,
   /// A generator of G1
   pub h: E::G1Affine,
+  /// Precomputed tables for `powers_of_g`, only built with the `cpu_msm` feature
+  #[serde(skip)]
+  pub fixed_base: FixedBaseCache<E::G1Affine>,
 }
 
 impl<E: Engine> PartialEq for UniversalKZGParam<E> {
@@ -185,6 +188,7 @@ where
       powers_of_g,
       powers_of_h,
       h,
+      fixed_base: FixedBaseCache::default(),
     }
   }
 }
@@ -269,6 +273,12 @@ where
   E::G1: DlogGroup<ScalarExt = E::Fr, AffineExt = E::G1Affine>,
   {
     assert!(ck.length() >= v.len());
+    #[cfg(feature = "cpu_msm")]
+    if v.len() >= crate::provider::util::cpu_msm::CPU_MSM_THRESHOLD {
+      return Commitment {
+        comm: ck.fixed_base.get_or_init(&ck.powers_of_g).msm(v) + ck.h * *r,
+      };
+    }
     let mut scalars = v.to_vec();
     scalars.push(*r);
     let mut bases = ck.powers_of_g[..v.len()].to_vec();
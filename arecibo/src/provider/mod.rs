@@ -16,6 +16,7 @@ pub(crate) mod traits;
 
 #[allow(dead_code)]
 pub(crate) mod util;
+pub use util::cpu_msm;
 
 // crate-private modules
 mod keccak;
@@ -201,7 +202,10 @@ mod test {
     bn256_grumpkin::{bn256, grumpkin},
     secp_secq::{secp256k1, secq256k1},
     traits::DlogGroup,
-    util::msm::cpu_best_msm,
+    util::{
+      cpu_msm::{self, MsmCurveAffine},
+      msm::cpu_best_msm,
+    },
   };
   use digest::{ExtendableOutput, Update};
   use group::{ff::Field, Curve, Group};
@@ -267,6 +271,51 @@ mod test {
     test_msm_with::<secq256k1::Scalar, secq256k1::Affine>();
   }
 
+  fn test_cpu_msm_with<F: Field, A>()
+  where
+    A: CurveAffine<ScalarExt = F> + MsmCurveAffine<Scalar = F, Curve = A::CurveExt>,
+  {
+    for n in [1, 7, 100, 1 << 10] {
+      let mut coeffs = (0..n).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+      let mut bases = (0..n)
+        .map(|_| A::from(A::generator() * F::random(OsRng)))
+        .collect::<Vec<_>>();
+      // hit the doubling, opposite points and identity cases of the batch-affine additions
+      if n > 4 {
+        bases[1] = bases[0];
+        bases[2] = -bases[0];
+        bases[3] = A::identity();
+        coeffs[1] = coeffs[0];
+        coeffs[2] = coeffs[0];
+        coeffs[4] = F::ZERO;
+      }
+      let expected = cpu_best_msm(&bases, &coeffs);
+
+      assert_eq!(expected, cpu_msm::msm(&bases, &coeffs));
+      for fixed_base in [
+        cpu_msm::FixedBaseMSM::new(&bases),
+        cpu_msm::FixedBaseMSM::with_window(&bases, 2),
+      ] {
+        assert_eq!(fixed_base.len(), n);
+        assert_eq!(expected, fixed_base.msm(&coeffs));
+        assert_eq!(
+          cpu_best_msm(&bases[1..], &coeffs[..n - 1]),
+          fixed_base.msm_at(1, &coeffs[..n - 1])
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_cpu_msm() {
+    test_cpu_msm_with::<pallas::Scalar, pallas::Affine>();
+    test_cpu_msm_with::<vesta::Scalar, vesta::Affine>();
+    test_cpu_msm_with::<bn256::Scalar, bn256::Affine>();
+    test_cpu_msm_with::<grumpkin::Scalar, grumpkin::Affine>();
+    test_cpu_msm_with::<secp256k1::Scalar, secp256k1::Affine>();
+    test_cpu_msm_with::<secq256k1::Scalar, secq256k1::Affine>();
+  }
+
   #[test]
   fn test_bn256_from_label() {
     impl_cycle_pair_test!(bn256);
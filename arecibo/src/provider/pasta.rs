@@ -1,6 +1,9 @@
 //! This module implements the Nova traits for `pallas::Point`, `pallas::Scalar`, `vesta::Point`, `vesta::Scalar`.
 use crate::{
-  provider::{traits::DlogGroup, util::msm::cpu_best_msm},
+  provider::{
+    traits::DlogGroup,
+    util::{cpu_msm::MsmCurveAffine, msm::cpu_best_msm},
+  },
   traits::{Group, PrimeFieldExt, TranscriptReprTrait},
 };
 use derive_more::{From, Into};
@@ -11,7 +14,7 @@ use num_bigint::BigInt;
 use num_traits::Num;
 use pasta_curves::{
   self,
-  arithmetic::{CurveAffine, CurveExt},
+  arithmetic::{Coordinates, CurveAffine, CurveExt},
   pallas, vesta,
 };
 use rayon::prelude::*;
@@ -64,6 +67,10 @@ macro_rules! impl_traits {
         name = "<_ as Group>::vartime_multiscalar_mul"
       )]
       fn vartime_multiscalar_mul(scalars: &[Self::ScalarExt], bases: &[Self::Affine]) -> Self {
+        #[cfg(feature = "cpu_msm")]
+        if scalars.len() >= crate::provider::util::cpu_msm::CPU_MSM_THRESHOLD {
+          return crate::provider::util::cpu_msm::msm(bases, scalars);
+        }
         #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
         if scalars.len() >= 128 {
           grumpkin_msm::pasta::$name(bases, scalars)
@@ -153,6 +160,20 @@ macro_rules! impl_traits {
       }
     }
 
+    impl MsmCurveAffine for $name::Affine {
+      type Base = $name::Base;
+
+      fn xy(&self) -> Option<($name::Base, $name::Base)> {
+        Option::from(self.coordinates()).map(|c: Coordinates<$name::Affine>| (*c.x(), *c.y()))
+      }
+
+      fn from_xy_unchecked(x: $name::Base, y: $name::Base) -> Self {
+        // pasta points keep their coordinates private, but an affine point is laid out as its
+        // coordinates (x, y), as asserted above, so we build it without the on-curve check
+        unsafe { std::mem::transmute::<[$name::Base; 2], $name::Affine>([x, y]) }
+      }
+    }
+
     impl<G: DlogGroup> TranscriptReprTrait<G> for $name::Affine {
       fn to_transcript_bytes(&self) -> Vec<u8> {
         let (x, y, is_infinity_byte) = {
@@ -198,7 +219,10 @@ mod tests {
   use pasta_curves::{pallas, vesta};
   use rand::thread_rng;
 
-  use crate::provider::{traits::DlogGroup, util::msm::cpu_best_msm};
+  use crate::provider::{
+    traits::DlogGroup,
+    util::{cpu_msm::MsmCurveAffine, msm::cpu_best_msm},
+  };
 
   #[test]
   fn test_pallas_msm_correctness() {
@@ -231,4 +255,16 @@ mod tests {
 
     assert_eq!(cpu_msm, gpu_msm);
   }
+
+  #[test]
+  fn test_from_xy_unchecked() {
+    for point in pallas::Point::from_label(b"test", 4) {
+      let (x, y) = point.xy().unwrap();
+      assert_eq!(pallas::Affine::from_xy_unchecked(x, y), point);
+    }
+    for point in vesta::Point::from_label(b"test", 4) {
+      let (x, y) = point.xy().unwrap();
+      assert_eq!(vesta::Affine::from_xy_unchecked(x, y), point);
+    }
+  }
 }
@@ -3,7 +3,7 @@ use crate::{
   errors::NovaError,
   frontend::{AllocatedBit, ConstraintSystem, SynthesisError},
   gadgets::AllocatedPoint,
-  provider::traits::DlogGroup,
+  provider::{traits::DlogGroup, util::cpu_msm::FixedBaseCache},
   traits::{
     commitment::{CommitmentEngineTrait, CommitmentTrait, Len},
     AbsorbInROTrait, Engine, ROTrait, TranscriptReprTrait,
@@ -35,6 +35,9 @@ where
   // Look for the static assertions in provider macros for a justification
   pub(crate) ck: Vec<<E::GE as PrimeCurve>::Affine>,
   pub(crate) h: Option<<E::GE as PrimeCurve>::Affine>,
+  // precomputed tables for `ck`, only built with the `cpu_msm` feature
+  #[serde(skip)]
+  pub(crate) fixed_base: FixedBaseCache<<E::GE as PrimeCurve>::Affine>,
 }
 
 impl<E> Len for CommitmentKey<E>
@@ -237,6 +240,7 @@ where
     Self::CommitmentKey {
       ck: ck.to_vec(),
       h: Some(*h),
+      fixed_base: FixedBaseCache::default(),
     }
   }
 
@@ -255,6 +259,19 @@ where
   ) -> Self::Commitment {
     assert!(ck.ck.len() > idx);
     assert!(ck.ck.len() - idx >= v.len());
+    #[cfg(feature = "cpu_msm")]
+    if v.len() >= crate::provider::util::cpu_msm::CPU_MSM_THRESHOLD {
+      let comm = ck.fixed_base.get_or_init(&ck.ck).msm_at(idx, v);
+      return match ck.h {
+        Some(h) => Commitment {
+          comm: comm + h * *r,
+        },
+        None => {
+          assert_eq!(*r, E::Scalar::ZERO);
+          Commitment { comm }
+        }
+      };
+    }
     if ck.h.is_some() {
       let mut scalars: Vec<E::Scalar> = v.to_vec();
       scalars.push(*r);
@@ -352,10 +369,12 @@ where
       CommitmentKey {
         ck: self.ck[0..n].to_vec(),
         h: self.h,
+        fixed_base: FixedBaseCache::default(),
       },
       CommitmentKey {
         ck: self.ck[n..].to_vec(),
         h: self.h,
+        fixed_base: FixedBaseCache::default(),
       },
     )
   }
@@ -366,7 +385,11 @@ where
       c.extend(other.ck.clone());
       c
     };
-    CommitmentKey { ck, h: self.h }
+    CommitmentKey {
+      ck,
+      h: self.h,
+      fixed_base: FixedBaseCache::default(),
+    }
   }
 
   // combines the left and right halves of `self` using `w1` and `w2` as the weights
@@ -382,6 +405,7 @@ where
     Self {
       ck: ck_affine,
       h: L.h,
+      fixed_base: FixedBaseCache::default(),
     }
   }
 
@@ -389,6 +413,8 @@ where
   fn scale(&mut self, r: &E::Scalar) {
     let ck_scaled: Vec<E::GE> = self.ck.par_iter().map(|g| *g * r).collect();
     E::GE::batch_normalize(&ck_scaled, &mut self.ck);
+    // the precomputed tables no longer match the bases
+    self.fixed_base = FixedBaseCache::default();
   }
 
   /// reinterprets a vector of commitments as a set of generators
@@ -399,6 +425,10 @@ where
       .collect::<Result<Vec<E::GE>, NovaError>>()?;
     let mut ck = vec![<E::GE as PrimeCurve>::Affine::identity(); d.len()];
     E::GE::batch_normalize(&d, &mut ck);
-    Ok(Self { ck, h: None })
+    Ok(Self {
+      ck,
+      h: None,
+      fixed_base: FixedBaseCache::default(),
+    })
   }
 }
@@ -1,4 +1,7 @@
-use crate::traits::{Group, TranscriptReprTrait};
+use crate::{
+  provider::util::cpu_msm::MsmCurveAffine,
+  traits::{Group, TranscriptReprTrait},
+};
 use group::{
   prime::{PrimeCurve, PrimeCurveAffine},
   GroupEncoding,
@@ -23,7 +26,8 @@ pub trait DlogGroup:
     + Send
     // technical bounds, should disappear when associated_type_bounds stabilizes
     + Mul<Self::ScalarExt, Output = Self>
-    + PrimeCurveAffine<Curve = Self, Scalar = Self::ScalarExt>;
+    + PrimeCurveAffine<Curve = Self, Scalar = Self::ScalarExt>
+    + MsmCurveAffine;
   type Compressed: Clone
     + Debug
     + Eq
@@ -94,6 +98,10 @@ macro_rules! impl_traits {
       type Compressed = $name::Compressed;
 
       fn vartime_multiscalar_mul(scalars: &[Self::ScalarExt], bases: &[Self::AffineExt]) -> Self {
+        #[cfg(feature = "cpu_msm")]
+        if scalars.len() >= $crate::provider::util::cpu_msm::CPU_MSM_THRESHOLD {
+          return $crate::provider::util::cpu_msm::msm(bases, scalars);
+        }
         #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
         if scalars.len() >= 128 {
           $large_msm_method(bases, scalars)
@@ -189,6 +197,24 @@ macro_rules! impl_traits {
       }
     }
 
+    impl $crate::provider::util::cpu_msm::MsmCurveAffine for $name::Affine {
+      type Base = $name::Base;
+
+      fn xy(&self) -> Option<($name::Base, $name::Base)> {
+        let coordinates = self.coordinates();
+        if coordinates.is_some().unwrap_u8() == 1 && ($name::Affine::identity() != *self) {
+          let c = coordinates.unwrap();
+          Some((*c.x(), *c.y()))
+        } else {
+          None
+        }
+      }
+
+      fn from_xy_unchecked(x: $name::Base, y: $name::Base) -> Self {
+        $name::Affine { x, y }
+      }
+    }
+
     impl<G: DlogGroup> TranscriptReprTrait<G> for $name::Affine {
       fn to_transcript_bytes(&self) -> Vec<u8> {
         let (x, y, is_infinity_byte) = {
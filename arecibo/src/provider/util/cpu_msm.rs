@@ -0,0 +1,406 @@
+//! # Multi-threaded CPU Multi-Scalar Multiplication
+//!
+//! This module provides a CPU backend for the multi-scalar multiplications (MSMs) that dominate
+//! commitment computations on machines without a GPU. It combines:
+//! - Pippenger's bucket method with signed digits, which halves the number of buckets per window;
+//! - batch-affine addition, where the points of all the buckets are added pairwise in affine
+//!   coordinates, so that every round of additions shares a single field inversion
+//!   (Montgomery's trick) instead of paying for mixed projective additions;
+//! - precomputed bases for fixed commitment keys ([`FixedBaseMSM`]), where every base is stored
+//!   together with its `2^{c*k}` multiples, so that all the windows collapse into a single bucket
+//!   pass and no doublings are needed at commit time.
+//!
+//! The addition formulas assume short Weierstrass curves with `a = 0`, which holds for all the
+//! curves supported by this crate, and scalars whose `to_repr` is little-endian.
+//!
+//! The commitment providers switch to this backend when the `cpu_msm` feature is enabled.
+use ff::{BatchInvert, Field, PrimeField};
+use group::{prime::PrimeCurveAffine, Curve, Group};
+use rayon::prelude::*;
+use std::{
+  fmt,
+  sync::{Arc, OnceLock},
+};
+
+/// Minimum number of scalars from which the `cpu_msm` feature routes an MSM to this module.
+/// Below it, batch-affine rounds are too small to amortize their inversions.
+pub const CPU_MSM_THRESHOLD: usize = 1 << 10;
+
+/// Largest supported window, in bits.
+const MAX_WINDOW_SIZE: usize = 24;
+
+/// Affine points with access to their coordinates, as needed by batch-affine addition.
+pub trait MsmCurveAffine: PrimeCurveAffine + Send + Sync {
+  /// The base field of the curve
+  type Base: Field;
+
+  /// Returns the affine coordinates of the point, or `None` for the identity
+  fn xy(&self) -> Option<(Self::Base, Self::Base)>;
+
+  /// Builds a point from coordinates that are known to be on the curve
+  fn from_xy_unchecked(x: Self::Base, y: Self::Base) -> Self;
+}
+
+/// Determines the window size (in bits) for a bucket pass over `num_points` points.
+///
+/// Signed digits halve the number of buckets, so `ln(n)` keeps the bucket accumulation cheap
+/// relative to the additions of the points themselves.
+pub fn get_msm_window_size(num_points: usize) -> usize {
+  ((num_points.max(1) as f64).ln().ceil() as usize).clamp(4, 20)
+}
+
+/// Number of signed digits of `window` bits needed to recode any scalar of `F`.
+/// The extra bit accounts for the carry out of the last digit.
+fn num_windows<F: PrimeField>(window: usize) -> usize {
+  (F::NUM_BITS as usize + 1).div_ceil(window)
+}
+
+/// Reads `window <= 24` bits of the little-endian `bytes` starting at bit `offset`.
+fn read_bits(bytes: &[u8], offset: usize, window: usize) -> u32 {
+  let mut buf = [0u8; 4];
+  for (dst, src) in buf.iter_mut().zip(bytes.iter().skip(offset / 8)) {
+    *dst = *src;
+  }
+  (u32::from_le_bytes(buf) >> (offset % 8)) & ((1 << window) - 1)
+}
+
+/// Recodes `scalar` into signed digits in `(-2^{window-1}, 2^{window-1}]`, least significant first.
+fn signed_digits<F: PrimeField>(scalar: &F, window: usize, digits: &mut [i32]) {
+  let repr = scalar.to_repr();
+  let bytes = repr.as_ref();
+  let half = 1i32 << (window - 1);
+  let mut carry = 0i32;
+  for (k, digit) in digits.iter_mut().enumerate() {
+    let d = read_bits(bytes, k * window, window) as i32 + carry;
+    carry = i32::from(d > half);
+    *digit = d - (carry << window);
+  }
+  debug_assert_eq!(carry, 0);
+}
+
+/// Recodes every scalar into `num_windows` signed digits, laid out scalar after scalar.
+fn recode<F: PrimeField>(scalars: &[F], window: usize, num_windows: usize) -> Vec<i32> {
+  let mut digits = vec![0i32; scalars.len() * num_windows];
+  digits
+    .par_chunks_mut(num_windows)
+    .zip(scalars.par_iter())
+    .for_each(|(digits, scalar)| signed_digits(scalar, window, digits));
+  digits
+}
+
+/// Computes `points[lhs] + points[rhs]` for every `(lhs, rhs, _)` in `pairs`, inverting all the
+/// denominators of the slopes at once.
+fn batch_add<C: MsmCurveAffine>(points: &[C], pairs: &[(usize, usize, usize)]) -> Vec<C> {
+  let (numerators, mut denominators): (Vec<_>, Vec<_>) = pairs
+    .iter()
+    .map(
+      |&(lhs, rhs, _)| match (points[lhs].xy(), points[rhs].xy()) {
+        (Some((x1, y1)), Some((x2, y2))) if x1 != x2 => (y2 - y1, x2 - x1),
+        // doubling, with a = 0
+        (Some((x1, y1)), Some((_, y2))) if y1 == y2 && !bool::from(y1.is_zero()) => {
+          let xx = x1.square();
+          (xx.double() + xx, y1.double())
+        }
+        // one of the points is the identity, or they are opposite
+        _ => (C::Base::ZERO, C::Base::ZERO),
+      },
+    )
+    .unzip();
+
+  // zeros are left untouched
+  denominators.iter_mut().batch_invert();
+
+  pairs
+    .iter()
+    .zip(numerators.iter().zip(denominators.iter()))
+    .map(|(&(lhs, rhs, _), (num, den_inv))| {
+      let (p, q) = (points[lhs], points[rhs]);
+      match (p.xy(), q.xy()) {
+        (None, _) => q,
+        (_, None) => p,
+        (Some((x1, y1)), Some((x2, _))) => {
+          if bool::from(den_inv.is_zero()) {
+            C::identity()
+          } else {
+            let lambda = *num * den_inv;
+            let x3 = lambda.square() - x1 - x2;
+            let y3 = lambda * (x1 - x3) - y1;
+            C::from_xy_unchecked(x3, y3)
+          }
+        }
+      }
+    })
+    .collect()
+}
+
+/// Halves every bucket until it holds at most one point. Each round adds pairs of points from
+/// all the buckets in one batch, so that the whole round costs a single inversion.
+fn reduce_buckets<C: MsmCurveAffine>(points: &mut [C], starts: &[usize], lens: &mut [usize]) {
+  let mut pairs = Vec::new();
+  let mut moves = Vec::new();
+  loop {
+    pairs.clear();
+    moves.clear();
+    for (start, len) in starts.iter().zip(lens.iter_mut()) {
+      if *len < 2 {
+        continue;
+      }
+      let half = *len / 2;
+      pairs.extend((0..half).map(|j| (start + 2 * j, start + 2 * j + 1, start + j)));
+      if *len % 2 == 1 {
+        moves.push((start + *len - 1, start + half));
+      }
+      *len = half + *len % 2;
+    }
+    if pairs.is_empty() {
+      break;
+    }
+
+    // the sums land in the first half of each bucket, and the odd points right after them;
+    // neither overwrites an input before it has been read
+    let sums = batch_add(points, &pairs);
+    for (&(_, _, dst), sum) in pairs.iter().zip(sums) {
+      points[dst] = sum;
+    }
+    for &(src, dst) in &moves {
+      points[dst] = points[src];
+    }
+  }
+}
+
+/// Computes `sum_b (b + 1) * bucket_b`, where each `(digit, base)` term adds `sign(digit) * base`
+/// to bucket `|digit| - 1`.
+fn bucket_sum<C, I>(num_buckets: usize, terms: I) -> C::Curve
+where
+  C: MsmCurveAffine,
+  I: Iterator<Item = (i32, C)> + Clone,
+{
+  // counting sort of the points by bucket: bucket b spans starts[b]..starts[b + 1]
+  let mut starts = vec![0usize; num_buckets + 1];
+  for (digit, _) in terms.clone() {
+    if digit != 0 {
+      starts[digit.unsigned_abs() as usize] += 1;
+    }
+  }
+  let mut end = 0;
+  for start in starts.iter_mut() {
+    end += *start;
+    *start = end;
+  }
+
+  let mut points = vec![C::identity(); starts[num_buckets]];
+  let mut next = starts.clone();
+  for (digit, base) in terms {
+    if digit != 0 {
+      let b = digit.unsigned_abs() as usize - 1;
+      points[next[b]] = if digit < 0 { -base } else { base };
+      next[b] += 1;
+    }
+  }
+
+  let mut lens = starts.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+  reduce_buckets(&mut points, &starts[..num_buckets], &mut lens);
+
+  // running sum from the largest bucket down
+  let mut running = C::Curve::identity();
+  let mut sum = C::Curve::identity();
+  for (start, len) in starts[..num_buckets].iter().zip(&lens).rev() {
+    if *len == 1 {
+      running += points[*start];
+    }
+    sum += running;
+  }
+  sum
+}
+
+/// Computes `sum_i scalars[i] * bases[i]` with Pippenger's bucket method, signed digits and
+/// batch-affine bucket accumulation. The windows are processed in parallel.
+pub fn msm<C: MsmCurveAffine>(bases: &[C], scalars: &[C::Scalar]) -> C::Curve {
+  let n = bases.len().min(scalars.len());
+  if n == 0 {
+    return C::Curve::identity();
+  }
+  let window = get_msm_window_size(n);
+  let num_windows = num_windows::<C::Scalar>(window);
+  let digits = recode(&scalars[..n], window, num_windows);
+
+  let window_sums = (0..num_windows)
+    .into_par_iter()
+    .map(|k| {
+      bucket_sum(
+        1 << (window - 1),
+        digits
+          .chunks_exact(num_windows)
+          .map(|d| d[k])
+          .zip(bases[..n].iter().copied()),
+      )
+    })
+    .collect::<Vec<_>>();
+
+  window_sums
+    .into_iter()
+    .rev()
+    .fold(C::Curve::identity(), |mut acc, window_sum| {
+      for _ in 0..window {
+        acc = acc.double();
+      }
+      acc + window_sum
+    })
+}
+
+/// Precomputed tables for MSMs against a fixed set of bases, such as a commitment key.
+///
+/// Every base `G_i` is stored with its multiples `2^{c*k} * G_i` for each window `k`, so that
+/// `sum_i s_i * G_i = sum_{i,k} d_{i,k} * 2^{c*k} * G_i` for the signed digits `d_{i,k}` of `s_i`
+/// is computed with a single bucket pass and no doublings.
+/// The table takes `ceil((NUM_BITS + 1) / c)` times the memory of the bases.
+#[derive(Clone)]
+pub struct FixedBaseMSM<C> {
+  window: usize,
+  num_windows: usize,
+  // table[i * num_windows + k] = 2^{window * k} * bases[i]
+  table: Vec<C>,
+}
+
+impl<C> fmt::Debug for FixedBaseMSM<C> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("FixedBaseMSM")
+      .field("window", &self.window)
+      .field("num_windows", &self.num_windows)
+      .field("table_len", &self.table.len())
+      .finish()
+  }
+}
+
+impl<C: MsmCurveAffine> FixedBaseMSM<C> {
+  /// Precomputes the tables for `bases`, picking the window size from the number of bases
+  pub fn new(bases: &[C]) -> Self {
+    Self::with_window(bases, get_msm_window_size(bases.len()))
+  }
+
+  /// Precomputes the tables for `bases` with a window of `window` bits.
+  /// Larger windows shrink the table but add buckets to accumulate.
+  pub fn with_window(bases: &[C], window: usize) -> Self {
+    assert!(
+      (2..=MAX_WINDOW_SIZE).contains(&window),
+      "window size must be in 2..={MAX_WINDOW_SIZE}"
+    );
+    let num_windows = num_windows::<C::Scalar>(window);
+
+    let table_proj = bases
+      .par_iter()
+      .flat_map_iter(|base| {
+        let mut p = base.to_curve();
+        (0..num_windows).map(move |_| {
+          let multiple = p;
+          for _ in 0..window {
+            p = p.double();
+          }
+          multiple
+        })
+      })
+      .collect::<Vec<_>>();
+
+    let mut table = vec![C::identity(); table_proj.len()];
+    let chunk = table_proj
+      .len()
+      .div_ceil(rayon::current_num_threads())
+      .max(1);
+    table
+      .par_chunks_mut(chunk)
+      .zip(table_proj.par_chunks(chunk))
+      .for_each(|(affine, proj)| C::Curve::batch_normalize(proj, affine));
+
+    Self {
+      window,
+      num_windows,
+      table,
+    }
+  }
+
+  /// Number of bases covered by the tables
+  pub fn len(&self) -> usize {
+    self.table.len() / self.num_windows
+  }
+
+  /// Whether the tables cover no base
+  pub fn is_empty(&self) -> bool {
+    self.table.is_empty()
+  }
+
+  /// Computes `sum_i scalars[i] * bases[i]`
+  pub fn msm(&self, scalars: &[C::Scalar]) -> C::Curve {
+    self.msm_at(0, scalars)
+  }
+
+  /// Computes `sum_i scalars[i] * bases[offset + i]`.
+  /// The scalars are split among the threads, each running its own bucket pass.
+  pub fn msm_at(&self, offset: usize, scalars: &[C::Scalar]) -> C::Curve {
+    assert!(
+      offset + scalars.len() <= self.len(),
+      "not enough bases (req: {} from offset {}) in the table (length: {})",
+      scalars.len(),
+      offset,
+      self.len()
+    );
+    if scalars.is_empty() {
+      return C::Curve::identity();
+    }
+    let table = &self.table[offset * self.num_windows..(offset + scalars.len()) * self.num_windows];
+    let chunk = scalars.len().div_ceil(rayon::current_num_threads()).max(1);
+
+    scalars
+      .par_chunks(chunk)
+      .zip(table.par_chunks(chunk * self.num_windows))
+      .map(|(scalars, table)| {
+        let digits = recode(scalars, self.window, self.num_windows);
+        bucket_sum(
+          1 << (self.window - 1),
+          digits.iter().copied().zip(table.iter().copied()),
+        )
+      })
+      .reduce(C::Curve::identity, |a, b| a + b)
+  }
+}
+
+/// A [`FixedBaseMSM`] built on first use.
+///
+/// It is meant to be embedded in commitment keys: clones share the tables, while serialization
+/// skips it and equality ignores it, so the keys keep their format.
+pub struct FixedBaseCache<C>(OnceLock<Arc<FixedBaseMSM<C>>>);
+
+impl<C: MsmCurveAffine> FixedBaseCache<C> {
+  /// Returns the tables for `bases`, building them if needed.
+  /// The caller must pass the same bases on every call.
+  pub fn get_or_init(&self, bases: &[C]) -> &FixedBaseMSM<C> {
+    self.0.get_or_init(|| Arc::new(FixedBaseMSM::new(bases)))
+  }
+}
+
+impl<C> Default for FixedBaseCache<C> {
+  fn default() -> Self {
+    Self(OnceLock::new())
+  }
+}
+
+impl<C> Clone for FixedBaseCache<C> {
+  fn clone(&self) -> Self {
+    Self(self.0.clone())
+  }
+}
+
+impl<C> fmt::Debug for FixedBaseCache<C> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("FixedBaseCache")
+      .field(&self.0.get().is_some())
+      .finish()
+  }
+}
+
+impl<C> PartialEq for FixedBaseCache<C> {
+  fn eq(&self, _other: &Self) -> bool {
+    true
+  }
+}
+
+impl<C> Eq for FixedBaseCache<C> {}
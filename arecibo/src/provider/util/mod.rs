@@ -1,4 +1,5 @@
 //! Utilities for provider module.
+pub mod cpu_msm;
 pub(in crate::provider) mod fb_msm;
 pub mod msm {
   use halo2curves::{msm::best_multiexp, CurveAffine};
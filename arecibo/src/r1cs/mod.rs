@@ -727,6 +727,16 @@ impl<E: Engine> RelaxedR1CSWitness<E> {
     }
   }
 
+  /// Initializes a new `RelaxedR1CSWitness` from an `R1CSWitness`, with a zero error vector
+  pub(crate) fn from_r1cs_witness(S: &R1CSShape<E>, witness: &R1CSWitness<E>) -> Self {
+    Self {
+      W: witness.W.clone(),
+      r_W: witness.r_W,
+      E: vec![E::Scalar::ZERO; S.num_cons],
+      r_E: E::Scalar::ZERO,
+    }
+  }
+
   /// Folds an incoming `R1CSWitness` into the current one
   pub(crate) fn fold(
     &self,
@@ -821,6 +831,17 @@ impl<E: Engine> RelaxedR1CSInstance<E> {
     }
   }
 
+  /// Initializes a new `RelaxedR1CSInstance` from an `R1CSInstance`, with `u = 1` and a zero
+  /// error commitment
+  pub(crate) fn from_r1cs_instance(instance: &R1CSInstance<E>) -> Self {
+    Self {
+      comm_W: instance.comm_W,
+      comm_E: Commitment::<E>::default(),
+      u: E::Scalar::ONE,
+      X: instance.X.clone(),
+    }
+  }
+
   /// Folds an incoming `RelaxedR1CSInstance` into the current one
   pub(crate) fn fold(&self, U2: &R1CSInstance<E>, comm_T: &Commitment<E>, r: &E::Scalar) -> Self {
     let (X1, u1, comm_W_1, comm_E_1) =
@@ -46,8 +46,8 @@ pub struct ProverKey<E: Engine, EE: EvaluationEngineTrait<E>> {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
 pub struct VerifierKey<E: Engine, EE: EvaluationEngineTrait<E>> {
-  vk_ee: EE::VerifierKey,
-  S: R1CSShape<E>,
+  pub(crate) vk_ee: EE::VerifierKey,
+  pub(crate) S: R1CSShape<E>,
   #[serde(skip, default = "OnceCell::new")]
   digest: OnceCell<E::Scalar>,
 }
@@ -84,14 +84,14 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> DigestHelperTrait<E> for VerifierK
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
 pub struct RelaxedR1CSSNARK<E: Engine, EE: EvaluationEngineTrait<E>> {
-  sc_proof_outer: SumcheckProof<E>,
-  claims_outer: (E::Scalar, E::Scalar, E::Scalar),
-  eval_E: E::Scalar,
-  sc_proof_inner: SumcheckProof<E>,
-  eval_W: E::Scalar,
-  sc_proof_batch: SumcheckProof<E>,
-  evals_batch: Vec<E::Scalar>,
-  eval_arg: EE::EvaluationArgument,
+  pub(crate) sc_proof_outer: SumcheckProof<E>,
+  pub(crate) claims_outer: (E::Scalar, E::Scalar, E::Scalar),
+  pub(crate) eval_E: E::Scalar,
+  pub(crate) sc_proof_inner: SumcheckProof<E>,
+  pub(crate) eval_W: E::Scalar,
+  pub(crate) sc_proof_batch: SumcheckProof<E>,
+  pub(crate) evals_batch: Vec<E::Scalar>,
+  pub(crate) eval_arg: EE::EvaluationArgument,
 }
 
 impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for RelaxedR1CSSNARK<E, EE> {
@@ -0,0 +1,239 @@
+/**
+ * @notice  HyperKZG evaluation argument verifier over BN254.
+ * @dev     Mirrors arecibo's `provider::hyperkzg::EvaluationEngine::verify`, drawing its challenges from a
+ *          `KeccakTranscript` shared with the caller.
+ */
+contract HyperKZGVerifier {
+    using KeccakTranscript for KeccakTranscript.Transcript;
+
+    // prime of field F_p over which y^2 = x^3 + 3 is defined
+    uint256 internal constant BN254_PRIME_FIELD =
+        21888242871839275222246405745257275088696311157297823662689037894645226208583;
+    uint256 internal constant BN254_SCALAR_FIELD =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+
+    // generator of G_1
+    uint256 internal constant G1_X = {{ g1.0[0] }};
+    uint256 internal constant G1_Y = {{ g1.0[1] }};
+    // generator of G_2, as [x.c0, x.c1, y.c0, y.c1]
+    uint256 internal constant H_X_C0 = {{ h.0[0][0] }};
+    uint256 internal constant H_X_C1 = {{ h.0[0][1] }};
+    uint256 internal constant H_Y_C0 = {{ h.0[1][0] }};
+    uint256 internal constant H_Y_C1 = {{ h.0[1][1] }};
+    // beta times the generator of G_2
+    uint256 internal constant BETA_H_X_C0 = {{ beta_h.0[0][0] }};
+    uint256 internal constant BETA_H_X_C1 = {{ beta_h.0[0][1] }};
+    uint256 internal constant BETA_H_Y_C0 = {{ beta_h.0[1][0] }};
+    uint256 internal constant BETA_H_Y_C1 = {{ beta_h.0[1][1] }};
+
+    /**
+     * @notice  A HyperKZG evaluation argument for a polynomial in `ell` variables.
+     * @param   comms  Commitments to the folded polynomials P_1, ..., P_{ell-1}
+     * @param   evals  Evaluations of P_0, ..., P_{ell-1} at r, -r and r^2
+     * @param   R_x  Coefficients of the degree 2 polynomial interpolating the batched evaluations
+     * @param   C_Q  Commitment to the quotient polynomial
+     * @param   C_H  Commitment to the opening polynomial
+     */
+    struct HyperKZGProof {
+        uint256[2][] comms;
+        uint256[][3] evals;
+        uint256[3] R_x;
+        uint256[2] C_Q;
+        uint256[2] C_H;
+    }
+
+    function frSub(uint256 a, uint256 b) internal pure returns (uint256) {
+        return addmod(a, BN254_SCALAR_FIELD - (b % BN254_SCALAR_FIELD), BN254_SCALAR_FIELD);
+    }
+
+    function frMul(uint256 a, uint256 b) internal pure returns (uint256) {
+        return mulmod(a, b, BN254_SCALAR_FIELD);
+    }
+
+    function frAdd(uint256 a, uint256 b) internal pure returns (uint256) {
+        return addmod(a, b, BN254_SCALAR_FIELD);
+    }
+
+    /**
+     * @notice  Evaluates the polynomial with coefficients `coeffs` (lowest degree first) at `x`.
+     */
+    function evalUniPoly(uint256[] memory coeffs, uint256 x) internal pure returns (uint256 result) {
+        for (uint256 i = coeffs.length; i > 0; i--) {
+            result = frAdd(frMul(result, x), coeffs[i - 1]);
+        }
+    }
+
+    function evalQuadratic(uint256[3] memory coeffs, uint256 x) internal pure returns (uint256) {
+        return frAdd(coeffs[0], frMul(x, frAdd(coeffs[1], frMul(x, coeffs[2]))));
+    }
+
+    /**
+     * @notice  Performs scalar multiplication in G_1.
+     */
+    function ecMul(uint256[2] memory p, uint256 s) internal view returns (uint256[2] memory r) {
+        uint256[3] memory input = [p[0], p[1], s];
+        bool success;
+        assembly {
+            success := staticcall(gas(), 7, input, 0x60, r, 0x40)
+        }
+        require(success, "bn254: scalar mul failed");
+    }
+
+    /**
+     * @notice  Adds two points in G_1.
+     */
+    function ecAdd(uint256[2] memory p1, uint256[2] memory p2) internal view returns (uint256[2] memory r) {
+        uint256[4] memory input = [p1[0], p1[1], p2[0], p2[1]];
+        bool success;
+        assembly {
+            success := staticcall(gas(), 6, input, 0x80, r, 0x40)
+        }
+        require(success, "bn254: point add failed");
+    }
+
+    /**
+     * @notice  Negates a point in G_1.
+     */
+    function ecNeg(uint256[2] memory p) internal pure returns (uint256[2] memory) {
+        if (p[0] == 0 && p[1] == 0) {
+            return p;
+        }
+        return [p[0], BN254_PRIME_FIELD - (p[1] % BN254_PRIME_FIELD)];
+    }
+
+    /**
+     * @notice  Checks e(a, BETA_H) * e(b, H) == 1.
+     * @dev     G_2 coordinates are passed to the precompile imaginary part first.
+     */
+    function pairingCheck(uint256[2] memory a, uint256[2] memory b) internal view returns (bool) {
+        uint256[12] memory input = [
+            a[0],
+            a[1],
+            BETA_H_X_C1,
+            BETA_H_X_C0,
+            BETA_H_Y_C1,
+            BETA_H_Y_C0,
+            b[0],
+            b[1],
+            H_X_C1,
+            H_X_C0,
+            H_Y_C1,
+            H_Y_C0
+        ];
+        uint256[1] memory out;
+        bool success;
+        assembly {
+            success := staticcall(gas(), 8, input, 0x180, out, 0x20)
+        }
+        require(success, "bn254: pairing failed");
+        return out[0] == 1;
+    }
+
+    /**
+     * @notice  Checks that the folded polynomials were built with the Gemini transformation:
+     *          2 * r * P_{i+1}(r^2) == r * (1 - x_i) * (P_i(r) + P_i(-r)) + x_i * (P_i(r) - P_i(-r)),
+     *          where x_i runs over `point` from its last coordinate and P_ell is the constant P_of_x.
+     */
+    function checkFolding(uint256[] memory point, uint256 P_of_x, uint256[][3] memory evals, uint256 r)
+        internal
+        pure
+        returns (bool)
+    {
+        uint256 ell = point.length;
+        for (uint256 i = 0; i < ell; i++) {
+            uint256 x_i = point[ell - 1 - i];
+            uint256 even = frAdd(evals[0][i], evals[1][i]);
+            uint256 odd = frSub(evals[0][i], evals[1][i]);
+            uint256 eval_r_squared = i + 1 < ell ? evals[2][i + 1] : P_of_x;
+            if (frMul(eval_r_squared, frMul(2, r)) != frAdd(frMul(r, frMul(frSub(1, x_i), even)), frMul(x_i, odd))) {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /**
+     * @notice  Computes C + q * comms[0] + q^2 * comms[1] + ...
+     */
+    function batchCommitments(uint256[2] memory C, uint256[2][] memory comms, uint256 q)
+        internal
+        view
+        returns (uint256[2] memory C_P)
+    {
+        C_P = C;
+        if (comms.length == 0) {
+            return C_P;
+        }
+        C_P = comms[comms.length - 1];
+        for (uint256 i = comms.length - 1; i > 0; i--) {
+            C_P = ecAdd(ecMul(C_P, q), comms[i - 1]);
+        }
+        C_P = ecAdd(ecMul(C_P, q), C);
+    }
+
+    /**
+     * @notice  Verifies that the polynomial committed in `C` evaluates to `P_of_x` at `point`.
+     */
+    function verifyHyperKZG(
+        KeccakTranscript.Transcript memory t,
+        uint256[2] memory C,
+        uint256[] memory point,
+        uint256 P_of_x,
+        HyperKZGProof memory pi
+    ) internal view returns (bool) {
+        require(pi.comms.length + 1 == point.length, "HyperKZG: wrong number of commitments");
+
+        {
+            bytes memory comms;
+            for (uint256 i = 0; i < pi.comms.length; i++) {
+                comms = abi.encodePacked(comms, KeccakTranscript.pointBytes(pi.comms[i], false));
+            }
+            t.absorb("c", comms);
+        }
+        uint256 r = t.squeeze("c");
+
+        {
+            bytes memory evals;
+            for (uint256 i = 0; i < 3; i++) {
+                require(pi.evals[i].length == point.length, "HyperKZG: wrong number of evaluations");
+                evals = abi.encodePacked(evals, KeccakTranscript.scalarsBytes(pi.evals[i]));
+            }
+            t.absorb("v", evals);
+        }
+        uint256 q = t.squeeze("r");
+
+        // R(r), R(-r) and R(r^2) must match the evaluations batched with q
+        uint256 r_squared = frMul(r, r);
+        if (
+            evalUniPoly(pi.evals[0], q) != evalQuadratic(pi.R_x, r)
+                || evalUniPoly(pi.evals[1], q) != evalQuadratic(pi.R_x, frSub(0, r))
+                || evalUniPoly(pi.evals[2], q) != evalQuadratic(pi.R_x, r_squared)
+        ) {
+            return false;
+        }
+
+        if (!checkFolding(point, P_of_x, pi.evals, r)) {
+            return false;
+        }
+
+        uint256[2] memory C_P = batchCommitments(C, pi.comms, q);
+
+        t.absorb("C_Q", KeccakTranscript.pointBytes(pi.C_Q, false));
+        uint256 a = t.squeeze("a");
+
+        // D(a) = (a - r) * (a + r) * (a - r^2) = a^3 - r^2 * a^2 - r^2 * a + r^4
+        uint256 D_a = frAdd(
+            frMul(frMul(a, a), frSub(a, r_squared)),
+            frSub(frMul(r_squared, r_squared), frMul(r_squared, a))
+        );
+
+        // C_K = C_P - (C_Q * D(a) + G_1 * R(a))
+        uint256[2] memory C_K = ecAdd(
+            C_P,
+            ecNeg(ecAdd(ecMul(pi.C_Q, D_a), ecMul([G1_X, G1_Y], evalQuadratic(pi.R_x, a))))
+        );
+
+        // e(C_H, beta * H) * e(-a * C_H - C_K, H) == 1
+        return pairingCheck(pi.C_H, ecNeg(ecAdd(ecMul(pi.C_H, a), C_K)));
+    }
+}
@@ -0,0 +1,101 @@
+/**
+ * @notice  Keccak256 Fiat-Shamir transcript over the BN254 scalar field.
+ * @dev     Byte-compatible with arecibo's `provider::keccak::Keccak256Transcript`: absorbed values are
+ *          buffered and hashed together with the 64-byte state on every squeeze. Scalars and base field
+ *          elements are serialized little-endian, as `to_transcript_bytes` does.
+ */
+library KeccakTranscript {
+    uint256 internal constant BN254_SCALAR_FIELD =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+    // 2^256 mod BN254_SCALAR_FIELD, used to reduce the 64-byte state into a challenge
+    uint256 internal constant TWO_POW_256 = 0x0e0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffb;
+
+    struct Transcript {
+        uint16 round;
+        bytes32 stateLo;
+        bytes32 stateHi;
+        bytes buffer;
+    }
+
+    /**
+     * @notice  Initializes a transcript, like `Keccak256Transcript::new(label)`.
+     */
+    function init(bytes memory label) internal pure returns (Transcript memory t) {
+        bytes memory input = abi.encodePacked("NoTR", label);
+        t.stateLo = keccak256(abi.encodePacked(input, uint8(0)));
+        t.stateHi = keccak256(abi.encodePacked(input, uint8(1)));
+    }
+
+    /**
+     * @notice  Appends `label || data` to the transcript.
+     */
+    function absorb(Transcript memory t, bytes memory label, bytes memory data) internal pure {
+        t.buffer = abi.encodePacked(t.buffer, label, data);
+    }
+
+    /**
+     * @notice  Squeezes a challenge out of the transcript and updates its state.
+     */
+    function squeeze(Transcript memory t, bytes memory label) internal pure returns (uint256) {
+        bytes memory input = abi.encodePacked(
+            t.buffer, "NoDS", uint8(t.round), uint8(t.round >> 8), t.stateLo, t.stateHi, label
+        );
+        bytes32 lo = keccak256(abi.encodePacked(input, uint8(0)));
+        bytes32 hi = keccak256(abi.encodePacked(input, uint8(1)));
+
+        t.round += 1;
+        t.stateLo = lo;
+        t.stateHi = hi;
+        t.buffer = "";
+
+        // the state is read as a 512-bit little-endian integer: lo + hi * 2^256
+        return addmod(
+            reverseBytes(uint256(lo)),
+            mulmod(reverseBytes(uint256(hi)), TWO_POW_256, BN254_SCALAR_FIELD),
+            BN254_SCALAR_FIELD
+        );
+    }
+
+    /**
+     * @notice  Serializes a scalar as `to_transcript_bytes` does.
+     */
+    function scalarBytes(uint256 s) internal pure returns (bytes memory) {
+        require(s < BN254_SCALAR_FIELD, "Transcript: scalar is not reduced");
+        return abi.encodePacked(reverseBytes(s));
+    }
+
+    /**
+     * @notice  Serializes a slice of scalars as `to_transcript_bytes` does.
+     */
+    function scalarsBytes(uint256[] memory s) internal pure returns (bytes memory data) {
+        for (uint256 i = 0; i < s.length; i++) {
+            data = abi.encodePacked(data, scalarBytes(s[i]));
+        }
+    }
+
+    /**
+     * @notice  Serializes a G1 point as `x || y || flag`.
+     * @dev     Commitments set `flag` to 1 for every point but the identity, while bare affine points always
+     *          set it to 0.
+     */
+    function pointBytes(uint256[2] memory p, bool isCommitment) internal pure returns (bytes memory) {
+        uint8 flag = isCommitment && (p[0] != 0 || p[1] != 0) ? 1 : 0;
+        return abi.encodePacked(reverseBytes(p[0]), reverseBytes(p[1]), flag);
+    }
+
+    /**
+     * @notice  Reverses the byte order of a word, converting between big and little endian.
+     */
+    function reverseBytes(uint256 v) internal pure returns (uint256) {
+        v = ((v & 0xff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00) >> 8)
+            | ((v & 0x00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff) << 8);
+        v = ((v & 0xffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000) >> 16)
+            | ((v & 0x0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff) << 16);
+        v = ((v & 0xffffffff00000000ffffffff00000000ffffffff00000000ffffffff00000000) >> 32)
+            | ((v & 0x00000000ffffffff00000000ffffffff00000000ffffffff00000000ffffffff) << 32);
+        v = ((v & 0xffffffffffffffff0000000000000000ffffffffffffffff0000000000000000) >> 64)
+            | ((v & 0x0000000000000000ffffffffffffffff0000000000000000ffffffffffffffff) << 64);
+        v = (v >> 128) | (v << 128);
+        return v;
+    }
+}
@@ -0,0 +1,368 @@
+/*
+    Spartan + HyperKZG verifier.
+
+    Verifies arecibo's `spartan::snark::RelaxedR1CSSNARK` over BN254 with the HyperKZG evaluation
+    engine, without a Groth16 wrapper. The contract replays the Keccak256 transcript of the native
+    verifier, checks the outer, inner and batching sum-checks, and checks the final HyperKZG
+    evaluation argument with the pairing precompile.
+
+    This version of Spartan does not use preprocessing, so the verifier evaluates the R1CS matrices
+    itself. They are passed in calldata and checked against the digest embedded at generation time;
+    the gas cost therefore grows with the number of non-zero entries of the circuit.
+
+    This is not an IVC verifier: it only checks that the given relaxed R1CS instance is satisfiable.
+    The folding of the last incoming instance, the CycleFold instances and the hash of (i, z_0, z_i)
+    in the public IO are not checked, so a valid proof says nothing about the output of the IVC.
+*/
+
+/* =============================== */
+/* Keccak256 transcript */
+{{ transcript }}
+
+/* =============================== */
+/* HyperKZG verifier methods */
+{{ hyperkzg_verifier }}
+
+/* =============================== */
+/* Spartan verifier */
+/**
+ * @title   RelaxedR1CSSpartanVerifier contract, for verifying Spartan proofs of relaxed R1CS instances.
+ *          It does not verify an IVC proof, see above.
+ * @dev     This is an askama template which, when templated, features the HyperKZG verifier from which
+ *          this contract inherits.
+ */
+contract RelaxedR1CSSpartanVerifier is HyperKZGVerifier {
+    using KeccakTranscript for KeccakTranscript.Transcript;
+
+    // digest of the native verifier key, absorbed first in the transcript
+    uint256 internal constant VK_DIGEST = {{ vk_digest }};
+    // keccak256 of the R1CS matrices, encoded as in `matrices` below
+    bytes32 internal constant MATRICES_DIGEST = {{ matrices_digest }};
+
+    uint256 internal constant NUM_IO = {{ num_io }};
+    uint256 internal constant NUM_ROUNDS_X = {{ num_rounds_x }};
+    uint256 internal constant NUM_ROUNDS_Y = {{ num_rounds_y }};
+    // the W and E evaluation claims are batched over max(NUM_ROUNDS_Y - 1, NUM_ROUNDS_X) rounds
+    uint256 internal constant NUM_ROUNDS_BATCH = {{ num_rounds_batch }};
+    // the public IO polynomial (u, X) is evaluated over its last NUM_ROUNDS_Y - 1 - X_NUM_COMMON variables
+    uint256 internal constant X_NUM_COMMON = {{ x_num_common }};
+
+    uint256 internal constant NNZ_A = {{ nnz_a }};
+    uint256 internal constant NNZ_B = {{ nnz_b }};
+    uint256 internal constant NNZ_C = {{ nnz_c }};
+
+    // offsets in the `instance` array
+    uint256 internal constant COMM_W = 0;
+    uint256 internal constant COMM_E = 2;
+    uint256 internal constant U = 4;
+    uint256 internal constant X = 5;
+
+    // offsets in the `proof` array
+    uint256 internal constant SC_OUTER = 0;
+    uint256 internal constant CLAIMS_OUTER = SC_OUTER + 3 * NUM_ROUNDS_X;
+    uint256 internal constant EVAL_E = CLAIMS_OUTER + 3;
+    uint256 internal constant SC_INNER = EVAL_E + 1;
+    uint256 internal constant EVAL_W = SC_INNER + 2 * NUM_ROUNDS_Y;
+    uint256 internal constant SC_BATCH = EVAL_W + 1;
+    uint256 internal constant EVALS_BATCH = SC_BATCH + 2 * NUM_ROUNDS_BATCH;
+    uint256 internal constant PCS_COMMS = EVALS_BATCH + 2;
+    uint256 internal constant PCS_EVALS = PCS_COMMS + 2 * (NUM_ROUNDS_BATCH - 1);
+    uint256 internal constant PCS_R_X = PCS_EVALS + 3 * NUM_ROUNDS_BATCH;
+    uint256 internal constant PCS_C_Q = PCS_R_X + 3;
+    uint256 internal constant PCS_C_H = PCS_C_Q + 2;
+
+    /**
+     * @notice  Verifies a Spartan proof of satisfiability of a relaxed R1CS instance.
+     * @dev     The selector of this function is "dynamic", since the array lengths depend on the circuit.
+     * @param   instance  [comm_W[2], comm_E[2], u, X]
+     * @param   proof  [sc_outer, claims_outer[3], eval_E, sc_inner, eval_W, sc_batch, evals_batch[2],
+     *                 hyperkzg_comms, hyperkzg_evals, R_x[3], C_Q[2], C_H[2]], where every sum-check round
+     *                 is given by the coefficients of its polynomial except the linear one
+     * @param   matrices  [row << 128 | col, val] for every non-zero entry of A, then B, then C
+     */
+    function verifyRelaxedR1CSProof(
+        uint256[{{ instance_len }}] calldata instance,
+        uint256[{{ proof_len }}] calldata proof,
+        uint256[{{ matrices_len }}] calldata matrices
+    ) public view returns (bool) {
+        require(keccak256(abi.encodePacked(matrices)) == MATRICES_DIGEST, "Spartan: unexpected R1CS matrices");
+
+        KeccakTranscript.Transcript memory t = KeccakTranscript.init("RelaxedR1CSSNARK");
+        t.absorb("vk", KeccakTranscript.scalarBytes(VK_DIGEST));
+        t.absorb("U", instanceBytes(instance));
+
+        uint256[] memory r_x = outerSumcheck(t, instance, proof);
+        uint256[] memory r_y = innerSumcheck(t, instance, proof, matrices, r_x);
+        return batchedOpening(t, instance, proof, r_x, r_y);
+    }
+
+    function instanceBytes(uint256[{{ instance_len }}] calldata instance) internal pure returns (bytes memory) {
+        uint256[] memory X_u = new uint256[](NUM_IO);
+        for (uint256 i = 0; i < NUM_IO; i++) {
+            X_u[i] = instance[X + i];
+        }
+        return abi.encodePacked(
+            KeccakTranscript.pointBytes([instance[COMM_W], instance[COMM_W + 1]], true),
+            KeccakTranscript.pointBytes([instance[COMM_E], instance[COMM_E + 1]], true),
+            KeccakTranscript.scalarBytes(instance[U]),
+            KeccakTranscript.scalarsBytes(X_u)
+        );
+    }
+
+    /**
+     * @notice  Runs the sum-check verifier over `numRounds` rounds of degree `degree` polynomials, read from
+     *          `proof` starting at `offset`.
+     * @return  e  The final claim
+     * @return  r  The challenges of every round
+     */
+    function sumcheck(
+        KeccakTranscript.Transcript memory t,
+        uint256[{{ proof_len }}] calldata proof,
+        uint256 offset,
+        uint256 numRounds,
+        uint256 degree,
+        uint256 claim
+    ) internal pure returns (uint256 e, uint256[] memory r) {
+        e = claim;
+        r = new uint256[](numRounds);
+        for (uint256 i = 0; i < numRounds; i++) {
+            uint256[] memory coeffs = new uint256[](degree);
+            for (uint256 j = 0; j < degree; j++) {
+                coeffs[j] = proof[offset + i * degree + j];
+            }
+            (e, r[i]) = sumcheckRound(t, coeffs, e);
+        }
+    }
+
+    /**
+     * @notice  Checks a single sum-check round, whose polynomial is sent without its linear term.
+     * @return  e  The evaluation of the round polynomial at the round challenge
+     * @return  r_i  The round challenge
+     */
+    function sumcheckRound(KeccakTranscript.Transcript memory t, uint256[] memory coeffs, uint256 claim)
+        internal
+        pure
+        returns (uint256 e, uint256 r_i)
+    {
+        // the linear term follows from poly(0) + poly(1) == claim
+        uint256 linear = frSub(claim, coeffs[0]);
+        for (uint256 j = 0; j < coeffs.length; j++) {
+            linear = frSub(linear, coeffs[j]);
+        }
+
+        t.absorb("p", KeccakTranscript.scalarsBytes(coeffs));
+        r_i = t.squeeze("c");
+
+        uint256 power = frMul(r_i, r_i);
+        e = frAdd(coeffs[0], frMul(linear, r_i));
+        for (uint256 j = 1; j < coeffs.length; j++) {
+            e = frAdd(e, frMul(coeffs[j], power));
+            power = frMul(power, r_i);
+        }
+    }
+
+    /**
+     * @notice  Evaluates eq(a[aOffset..aOffset + len], b[bOffset..bOffset + len]).
+     */
+    function evalEq(uint256[] memory a, uint256 aOffset, uint256[] memory b, uint256 bOffset, uint256 len)
+        internal
+        pure
+        returns (uint256 result)
+    {
+        result = 1;
+        for (uint256 i = 0; i < len; i++) {
+            uint256 a_i = a[aOffset + i];
+            uint256 b_i = b[bOffset + i];
+            result = frMul(result, frAdd(frMul(a_i, b_i), frMul(frSub(1, a_i), frSub(1, b_i))));
+        }
+    }
+
+    /**
+     * @notice  Evaluates eq(r, x) at every point x of the boolean hypercube, the first coordinate of r being
+     *          the most significant bit of the index.
+     */
+    function evalsFromPoints(uint256[] memory r, uint256 offset) internal pure returns (uint256[] memory evals) {
+        uint256 ell = r.length - offset;
+        evals = new uint256[](1 << ell);
+        evals[0] = 1;
+        uint256 size = 1;
+        for (uint256 i = r.length; i > offset; i--) {
+            uint256 r_i = r[i - 1];
+            for (uint256 j = 0; j < size; j++) {
+                uint256 y = frMul(evals[j], r_i);
+                evals[j + size] = y;
+                evals[j] = frSub(evals[j], y);
+            }
+            size *= 2;
+        }
+    }
+
+    function outerSumcheck(
+        KeccakTranscript.Transcript memory t,
+        uint256[{{ instance_len }}] calldata instance,
+        uint256[{{ proof_len }}] calldata proof
+    ) internal pure returns (uint256[] memory r_x) {
+        uint256 tau = t.squeeze("t");
+
+        uint256 claim_outer_final;
+        (claim_outer_final, r_x) = sumcheck(t, proof, SC_OUTER, NUM_ROUNDS_X, 3, 0);
+
+        uint256[] memory claims = new uint256[](4);
+        claims[0] = proof[CLAIMS_OUTER];
+        claims[1] = proof[CLAIMS_OUTER + 1];
+        claims[2] = proof[CLAIMS_OUTER + 2];
+        claims[3] = proof[EVAL_E];
+
+        // eq(tau, r_x) * (Az * Bz - u * Cz - E), where tau = [tau, tau^2, tau^4, ...]
+        uint256 expected = frSub(frSub(frMul(claims[0], claims[1]), frMul(instance[U], claims[2])), claims[3]);
+        for (uint256 i = 0; i < NUM_ROUNDS_X; i++) {
+            expected = frMul(expected, frAdd(frMul(tau, r_x[i]), frMul(frSub(1, tau), frSub(1, r_x[i]))));
+            tau = frMul(tau, tau);
+        }
+        require(claim_outer_final == expected, "Spartan: outer sum-check failed");
+
+        t.absorb("claims_outer", KeccakTranscript.scalarsBytes(claims));
+    }
+
+    function innerSumcheck(
+        KeccakTranscript.Transcript memory t,
+        uint256[{{ instance_len }}] calldata instance,
+        uint256[{{ proof_len }}] calldata proof,
+        uint256[{{ matrices_len }}] calldata matrices,
+        uint256[] memory r_x
+    ) internal pure returns (uint256[] memory r_y) {
+        uint256 r = t.squeeze("r");
+
+        uint256 claim_inner_final;
+        (claim_inner_final, r_y) = sumcheck(
+            t,
+            proof,
+            SC_INNER,
+            NUM_ROUNDS_Y,
+            2,
+            frAdd(proof[CLAIMS_OUTER], frMul(r, frAdd(proof[CLAIMS_OUTER + 1], frMul(r, proof[CLAIMS_OUTER + 2]))))
+        );
+
+        uint256 expected = frMul(evalMatrices(matrices, r_x, r_y, r), evalZ(instance, proof, r_y));
+        require(claim_inner_final == expected, "Spartan: inner sum-check failed");
+    }
+
+    /**
+     * @notice  Evaluates Z = (W, u, X) at r_y, the public IO (u, X) being a sparse polynomial over r_y[1..].
+     */
+    function evalZ(
+        uint256[{{ instance_len }}] calldata instance,
+        uint256[{{ proof_len }}] calldata proof,
+        uint256[] memory r_y
+    ) internal pure returns (uint256) {
+        uint256[] memory chis = evalsFromPoints(r_y, 1 + X_NUM_COMMON);
+        uint256 eval_X = frMul(chis[0], instance[U]);
+        for (uint256 i = 0; i < NUM_IO; i++) {
+            eval_X = frAdd(eval_X, frMul(chis[i + 1], instance[X + i]));
+        }
+        for (uint256 i = 0; i < X_NUM_COMMON; i++) {
+            eval_X = frMul(eval_X, frSub(1, r_y[1 + i]));
+        }
+        return frAdd(frMul(frSub(1, r_y[0]), proof[EVAL_W]), frMul(r_y[0], eval_X));
+    }
+
+    /**
+     * @notice  Evaluates A(r_x, r_y) + r * B(r_x, r_y) + r^2 * C(r_x, r_y).
+     */
+    function evalMatrices(
+        uint256[{{ matrices_len }}] calldata matrices,
+        uint256[] memory r_x,
+        uint256[] memory r_y,
+        uint256 r
+    ) internal pure returns (uint256 result) {
+        uint256[] memory T_x = evalsFromPoints(r_x, 0);
+        uint256[] memory T_y = evalsFromPoints(r_y, 0);
+        uint256 weight = 1;
+        for (uint256 i = 0; i < NNZ_A + NNZ_B + NNZ_C; i++) {
+            if (i == NNZ_A || i == NNZ_A + NNZ_B) {
+                weight = frMul(weight, r);
+            }
+            uint256 entry = matrices[2 * i];
+            uint256 term = frMul(T_x[entry >> 128], T_y[entry & type(uint128).max]);
+            result = frAdd(result, frMul(weight, frMul(term, matrices[2 * i + 1])));
+        }
+    }
+
+    function batchedOpening(
+        KeccakTranscript.Transcript memory t,
+        uint256[{{ instance_len }}] calldata instance,
+        uint256[{{ proof_len }}] calldata proof,
+        uint256[] memory r_x,
+        uint256[] memory r_y
+    ) internal view returns (bool) {
+        // claims about W at r_y[1..] and about E at r_x, padded to NUM_ROUNDS_BATCH variables
+        uint256 pad_W = NUM_ROUNDS_BATCH - (NUM_ROUNDS_Y - 1);
+        uint256 pad_E = NUM_ROUNDS_BATCH - NUM_ROUNDS_X;
+
+        uint256 rho = t.squeeze("r");
+        uint256[] memory r;
+        {
+            uint256 claim_batch_final;
+            (claim_batch_final, r) = sumcheck(
+                t,
+                proof,
+                SC_BATCH,
+                NUM_ROUNDS_BATCH,
+                2,
+                frAdd(frMul(1 << pad_W, proof[EVAL_W]), frMul(rho, frMul(1 << pad_E, proof[EVAL_E])))
+            );
+            uint256 expected = frAdd(
+                frMul(evalEq(r, pad_W, r_y, 1, NUM_ROUNDS_Y - 1), proof[EVALS_BATCH]),
+                frMul(rho, frMul(evalEq(r, pad_E, r_x, 0, NUM_ROUNDS_X), proof[EVALS_BATCH + 1]))
+            );
+            require(claim_batch_final == expected, "Spartan: batching sum-check failed");
+        }
+
+        uint256 gamma;
+        {
+            uint256[] memory evals_batch = new uint256[](2);
+            evals_batch[0] = proof[EVALS_BATCH];
+            evals_batch[1] = proof[EVALS_BATCH + 1];
+            t.absorb("l", KeccakTranscript.scalarsBytes(evals_batch));
+            gamma = t.squeeze("g");
+        }
+
+        // C = comm_W + gamma * comm_E, rescaling each evaluation by the first Lagrange polynomial of its padding
+        uint256[2] memory C = ecAdd(
+            [instance[COMM_W], instance[COMM_W + 1]],
+            ecMul([instance[COMM_E], instance[COMM_E + 1]], gamma)
+        );
+        uint256 eval = frAdd(
+            frMul(evalLagrangeZero(r, pad_W), proof[EVALS_BATCH]),
+            frMul(gamma, frMul(evalLagrangeZero(r, pad_E), proof[EVALS_BATCH + 1]))
+        );
+
+        return verifyHyperKZG(t, C, r, eval, hyperKZGProof(proof));
+    }
+
+    /**
+     * @notice  Evaluates the first Lagrange polynomial over r[..len], i.e. the product of (1 - r_i).
+     */
+    function evalLagrangeZero(uint256[] memory r, uint256 len) internal pure returns (uint256 result) {
+        result = 1;
+        for (uint256 i = 0; i < len; i++) {
+            result = frMul(result, frSub(1, r[i]));
+        }
+    }
+
+    function hyperKZGProof(uint256[{{ proof_len }}] calldata proof) internal pure returns (HyperKZGProof memory pi) {
+        pi.comms = new uint256[2][](NUM_ROUNDS_BATCH - 1);
+        for (uint256 i = 0; i < NUM_ROUNDS_BATCH - 1; i++) {
+            pi.comms[i] = [proof[PCS_COMMS + 2 * i], proof[PCS_COMMS + 2 * i + 1]];
+        }
+        for (uint256 i = 0; i < 3; i++) {
+            pi.evals[i] = new uint256[](NUM_ROUNDS_BATCH);
+            for (uint256 j = 0; j < NUM_ROUNDS_BATCH; j++) {
+                pi.evals[i][j] = proof[PCS_EVALS + i * NUM_ROUNDS_BATCH + j];
+            }
+        }
+        pi.R_x = [proof[PCS_R_X], proof[PCS_R_X + 1], proof[PCS_R_X + 2]];
+        pi.C_Q = [proof[PCS_C_Q], proof[PCS_C_Q + 1]];
+        pi.C_H = [proof[PCS_C_H], proof[PCS_C_H + 1]];
+    }
+}
@@ -0,0 +1,496 @@
+//! Asset-Class Exposure Circuit
+//!
+//! Proves that the exposure of the portfolio to each asset class (or sector) stays within the
+//! bounds of the policy, e.g. at most 30% real estate and at least 50% government securities,
+//! without revealing the holdings.
+//!
+//! The class of each asset is read from a committed classification map: a Merkle tree whose
+//! leaves are Poseidon(asset_id, class_id) and whose nodes are Poseidon(left, right), checked with
+//! the Merkle gadgets of the whitelist circuit. The hash is collision resistant, so that a leaf
+//! cannot be opened at another class, whatever the siblings chosen by the prover.
+//!
+//! Public Inputs:
+//! - classification_root: Merkle root of the classification map
+//! - For each class: min_percentage and max_percentage
+//!
+//! Private Inputs:
+//! - For each asset: asset_id, class_id, value, and the Merkle path of its leaf
+//!
+//! Constraints:
+//! - Each leaf Poseidon(asset_id, class_id) is in the classification map
+//! - class_id is one-hot encoded over the classes, so that each class total is the sum of
+//!   value * is_class over the assets
+//! - min_percentage * total <= class_total * 100 <= max_percentage * total
+//!
+//! The one-hot accumulation costs num_assets * num_classes constraints, which stays small for the
+//! handful of classes of a fund policy. arecibo's lookup gadget (`gadgets/lookup.rs`) would route
+//! the class totals through a read-write table instead, which only pays off with many classes, and
+//! it is not exported by arecibo's `gadgets` module.
+
+use arecibo::frontend::gadgets::poseidon::PoseidonConstants;
+use bellpepper_core::{Circuit, ConstraintSystem, LinearCombination, SynthesisError, Variable};
+use ff::PrimeField;
+use generic_array::typenum::U2;
+use serde::{Deserialize, Serialize};
+
+use crate::position_limit::MAX_ASSETS;
+use crate::range_proof::{self, RANGE_PROOF_BITS};
+use crate::poseidon::poseidon_hash;
+use crate::utils::{
+    self, alloc_num, alloc_one_hot, alloc_percentage, enforce_le, mul_total, Num, PERCENTAGE_BITS,
+};
+use crate::whitelist::compute_poseidon_merkle_root;
+
+/// Maximum number of asset classes supported in a single proof
+pub const MAX_CLASSES: usize = 16;
+
+/// Number of bits of the weighted values (class_total * 100 and percentage * total), enough for
+/// MAX_ASSETS assets of RANGE_PROOF_BITS bits
+const WEIGHTED_BITS: usize = RANGE_PROOF_BITS + 4 + PERCENTAGE_BITS;
+
+/// Bounds on the weight of an asset class in the portfolio, in percent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassLimit {
+    pub min_pct: u64,
+    pub max_pct: u64,
+}
+
+impl ClassLimit {
+    /// No bound on the weight of the class
+    pub const UNBOUNDED: Self = Self {
+        min_pct: 0,
+        max_pct: 100,
+    };
+
+    pub const fn at_most(max_pct: u64) -> Self {
+        Self {
+            min_pct: 0,
+            max_pct,
+        }
+    }
+
+    pub const fn at_least(min_pct: u64) -> Self {
+        Self {
+            min_pct,
+            max_pct: 100,
+        }
+    }
+}
+
+/// Holding of the portfolio, together with its entry in the classification map
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClassifiedAsset<F: PrimeField> {
+    pub asset_id: F,
+    pub class_id: u64,
+    pub value: u64,
+
+    /// Path from the leaf Poseidon(asset_id, class_id) to the classification root
+    pub merkle_path: Vec<F>,
+    pub path_indices: Vec<bool>,
+}
+
+/// Leaf of the classification map for `asset_id`
+pub fn classification_leaf<F: PrimeField>(asset_id: F, class_id: u64) -> F {
+    poseidon_hash(&[asset_id, F::from(class_id)])
+}
+
+/// Parent of two nodes of the classification map
+pub fn classification_node<F: PrimeField>(left: F, right: F) -> F {
+    poseidon_hash(&[left, right])
+}
+
+/// Allocate the class and the value of a holding, enforcing that Poseidon(asset_id, class_id) is
+/// in the classification map of root `root_var` and that the value is in range
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn alloc_classified_asset<F, CS>(
+    cs: &mut CS,
+    root_var: Variable,
+    asset_id: Option<F>,
+    class_id: Option<u64>,
+    value: Option<u64>,
+    merkle_path: &[Option<F>],
+    path_indices: &[Option<bool>],
+    constants: &PoseidonConstants<F, U2>,
+) -> Result<(Num, Num), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let asset_id_var = cs.alloc(
+        || "asset_id",
+        || asset_id.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    let class_id = alloc_num(cs.namespace(|| "class_id"), class_id.map(u128::from))?;
+    let value = alloc_num(cs.namespace(|| "value"), value.map(u128::from))?;
+    range_proof::decompose_allocated_value(
+        cs.namespace(|| "value_range_proof"),
+        value.var,
+        value.value.map(F::from_u128),
+        RANGE_PROOF_BITS,
+        "value_bits",
+    )?;
+
+    // Enforce: Poseidon(asset_id, class_id) is in the classification map
+    let leaf = utils::poseidon_hash(
+        cs.namespace(|| "leaf"),
+        &[
+            (asset_id_var, asset_id),
+            (class_id.var, class_id.value.map(F::from_u128)),
+        ],
+        constants,
+    )?;
+    let (root, _) = compute_poseidon_merkle_root(
+        cs.namespace(|| "merkle_path"),
+        leaf,
+        merkle_path,
+        path_indices,
+        constants,
+    )?;
+    cs.enforce(
+        || "root_constraint",
+        |lc| lc + CS::one(),
+        |lc| lc + root,
+        |lc| lc + root_var,
+    );
+
+    Ok((class_id, value))
+}
+
+/// Allocate the exposure of a holding to each class, i.e. value * is_class where is_class is the
+/// one-hot encoding of class_id (exactly one is_class bit is set, at index class_id)
+pub(crate) fn alloc_class_exposures<F, CS>(
+    mut cs: CS,
+    class_id: &Num,
+    value: &Num,
+    num_classes: usize,
+) -> Result<Vec<Num>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let is_class = alloc_one_hot(cs.namespace(|| "is_class"), class_id, num_classes)?;
+    let mut exposures = Vec::with_capacity(num_classes);
+    for (j, (is_class, is_class_val)) in is_class.into_iter().enumerate() {
+        let exposure = alloc_num(
+            cs.namespace(|| format!("exposure_{}", j)),
+            is_class_val
+                .zip(value.value)
+                .map(|(b, v)| if b { v } else { 0 }),
+        )?;
+        cs.enforce(
+            || format!("exposure_{}_constraint", j),
+            |lc| lc + is_class,
+            |lc| lc + value.var,
+            |lc| lc + exposure.var,
+        );
+        exposures.push(exposure);
+    }
+    Ok(exposures)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AssetClassCircuit<F: PrimeField> {
+    /// Merkle root of the classification map (public input)
+    pub classification_root: Option<F>,
+
+    /// Bounds of each class, whose number is fixed by the circuit (public inputs)
+    pub limits: Vec<Option<ClassLimit>>,
+
+    /// Holdings and their classification (private witness)
+    pub asset_ids: Vec<Option<F>>,
+    pub class_ids: Vec<Option<u64>>,
+    pub values: Vec<Option<u64>>,
+    pub merkle_paths: Vec<Vec<Option<F>>>,
+    pub path_indices: Vec<Vec<Option<bool>>>,
+}
+
+impl<F: PrimeField> AssetClassCircuit<F> {
+    /// Create a new asset-class exposure circuit
+    pub fn new(
+        classification_root: F,
+        limits: Vec<ClassLimit>,
+        assets: Vec<ClassifiedAsset<F>>,
+    ) -> Self {
+        for asset in &assets {
+            assert_eq!(
+                asset.merkle_path.len(),
+                asset.path_indices.len(),
+                "Path and indices must have same length"
+            );
+        }
+
+        Self {
+            classification_root: Some(classification_root),
+            limits: limits.into_iter().map(Some).collect(),
+            asset_ids: assets.iter().map(|a| Some(a.asset_id)).collect(),
+            class_ids: assets.iter().map(|a| Some(a.class_id)).collect(),
+            values: assets.iter().map(|a| Some(a.value)).collect(),
+            merkle_paths: assets
+                .iter()
+                .map(|a| a.merkle_path.iter().copied().map(Some).collect())
+                .collect(),
+            path_indices: assets
+                .iter()
+                .map(|a| a.path_indices.iter().copied().map(Some).collect())
+                .collect(),
+        }
+    }
+
+    /// Create an empty circuit for setup (no witness data)
+    pub fn empty(num_classes: usize, num_assets: usize, depth: usize) -> Self {
+        Self {
+            classification_root: None,
+            limits: vec![None; num_classes],
+            asset_ids: vec![None; num_assets],
+            class_ids: vec![None; num_assets],
+            values: vec![None; num_assets],
+            merkle_paths: vec![vec![None; depth]; num_assets],
+            path_indices: vec![vec![None; depth]; num_assets],
+        }
+    }
+
+    /// Number of asset classes of the policy
+    pub fn num_classes(&self) -> usize {
+        self.limits.len()
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for AssetClassCircuit<F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let num_classes = self.num_classes();
+        let num_assets = self.values.len();
+        if num_classes == 0 || num_classes > MAX_CLASSES || num_assets > MAX_ASSETS {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let hundred = F::from(100u64);
+        let constants = PoseidonConstants::<F, U2>::new();
+
+        // Allocate public inputs: classification root and class bounds
+        let root_var = cs.alloc_input(
+            || "classification_root",
+            || {
+                self.classification_root
+                    .ok_or(SynthesisError::AssignmentMissing)
+            },
+        )?;
+        let mut limits = Vec::with_capacity(num_classes);
+        for (j, limit) in self.limits.iter().enumerate() {
+            let min = alloc_percentage(
+                cs.namespace(|| format!("class_{}_min_percentage", j)),
+                limit.map(|l| l.min_pct),
+            )?;
+            let max = alloc_percentage(
+                cs.namespace(|| format!("class_{}_max_percentage", j)),
+                limit.map(|l| l.max_pct),
+            )?;
+            limits.push((min, max));
+        }
+
+        // Accumulate the total and the class totals, weighted by 100
+        let mut total = LinearCombination::zero();
+        let mut total_value = Some(0u128);
+        let mut class_totals = vec![LinearCombination::zero(); num_classes];
+        let mut class_values = vec![Some(0u128); num_classes];
+
+        for i in 0..num_assets {
+            let mut cs = cs.namespace(|| format!("asset_{}", i));
+
+            let (class_id, value) = alloc_classified_asset(
+                &mut cs,
+                root_var,
+                self.asset_ids[i],
+                self.class_ids[i],
+                self.values[i],
+                &self.merkle_paths[i],
+                &self.path_indices[i],
+                &constants,
+            )?;
+            total = total + value.var;
+            total_value = total_value.zip(value.value).map(|(t, v)| t + v);
+
+            let exposures = alloc_class_exposures(
+                cs.namespace(|| "exposures"),
+                &class_id,
+                &value,
+                num_classes,
+            )?;
+            for (j, exposure) in exposures.into_iter().enumerate() {
+                class_totals[j] = class_totals[j].clone() + (hundred, exposure.var);
+                class_values[j] = class_values[j]
+                    .zip(exposure.value)
+                    .map(|(s, e)| s + 100 * e);
+            }
+        }
+
+        // Enforce: total >= 1
+        enforce_le(
+            cs.namespace(|| "total_positive"),
+            LinearCombination::zero() + CS::one(),
+            total.clone(),
+            Some(1),
+            total_value,
+            WEIGHTED_BITS,
+        )?;
+
+        // Enforce: min_percentage * total <= class_total * 100 <= max_percentage * total
+        for (j, ((min, max), (class_total, class_value))) in limits
+            .iter()
+            .zip(class_totals.into_iter().zip(class_values))
+            .enumerate()
+        {
+            let mut cs = cs.namespace(|| format!("class_{}", j));
+            let min_value = mul_total(cs.namespace(|| "min_value"), min, &total, total_value)?;
+            let max_value = mul_total(cs.namespace(|| "max_value"), max, &total, total_value)?;
+            enforce_le(
+                cs.namespace(|| "min_limit"),
+                LinearCombination::zero() + min_value.var,
+                class_total.clone(),
+                min_value.value,
+                class_value,
+                WEIGHTED_BITS,
+            )?;
+            enforce_le(
+                cs.namespace(|| "max_limit"),
+                class_total,
+                LinearCombination::zero() + max_value.var,
+                class_value,
+                max_value.value,
+                WEIGHTED_BITS,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use pasta_curves::Fp;
+
+    const GOVERNMENT: u64 = 0;
+    const REAL_ESTATE: u64 = 1;
+    const EQUITIES: u64 = 2;
+
+    /// ≥ 50% government securities, ≤ 30% real estate, equities unbounded
+    fn limits() -> Vec<ClassLimit> {
+        vec![
+            ClassLimit::at_least(50),
+            ClassLimit::at_most(30),
+            ClassLimit::UNBOUNDED,
+        ]
+    }
+
+    /// Classification map of 4 assets, with the Merkle path of each leaf
+    fn classification_map(entries: &[(Fp, u64); 4]) -> (Fp, Vec<(Vec<Fp>, Vec<bool>)>) {
+        let leaves = entries.map(|(asset_id, class_id)| classification_leaf(asset_id, class_id));
+        let nodes = [
+            classification_node(leaves[0], leaves[1]),
+            classification_node(leaves[2], leaves[3]),
+        ];
+        let root = classification_node(nodes[0], nodes[1]);
+        let paths = (0..4)
+            .map(|i| {
+                (
+                    vec![leaves[i ^ 1], nodes[1 - i / 2]],
+                    vec![i % 2 == 1, i / 2 == 1],
+                )
+            })
+            .collect();
+        (root, paths)
+    }
+
+    fn circuit(classes: [u64; 4], values: [u64; 4], claimed: [u64; 4]) -> AssetClassCircuit<Fp> {
+        let asset_ids = [1001u64, 1002, 1003, 1004].map(Fp::from);
+        let entries = [0, 1, 2, 3].map(|i| (asset_ids[i], classes[i]));
+        let (root, paths) = classification_map(&entries);
+        let assets = paths
+            .into_iter()
+            .enumerate()
+            .map(|(i, (merkle_path, path_indices))| ClassifiedAsset {
+                asset_id: asset_ids[i],
+                class_id: claimed[i],
+                value: values[i],
+                merkle_path,
+                path_indices,
+            })
+            .collect();
+        AssetClassCircuit::new(root, limits(), assets)
+    }
+
+    fn is_satisfied(circuit: AssetClassCircuit<Fp>) -> bool {
+        let mut cs = TestConstraintSystem::<Fp>::new();
+        circuit.synthesize(&mut cs).unwrap();
+        cs.is_satisfied()
+    }
+
+    const CLASSES: [u64; 4] = [GOVERNMENT, REAL_ESTATE, EQUITIES, GOVERNMENT];
+
+    #[test]
+    fn test_asset_class_circuit_valid() {
+        // Government 55%, real estate 25%, equities 20%
+        let mut cs = TestConstraintSystem::<Fp>::new();
+        circuit(CLASSES, [300, 250, 200, 250], CLASSES)
+            .synthesize(&mut cs)
+            .unwrap();
+        assert!(cs.is_satisfied());
+        println!(
+            "Asset class circuit - Num constraints: {}",
+            cs.num_constraints()
+        );
+    }
+
+    #[test]
+    fn test_asset_class_circuit_violations() {
+        // Real estate 35% > 30%
+        assert!(!is_satisfied(circuit(
+            CLASSES,
+            [300, 350, 100, 250],
+            CLASSES
+        )));
+        // Government securities 40% < 50%
+        assert!(!is_satisfied(circuit(
+            CLASSES,
+            [200, 250, 350, 200],
+            CLASSES
+        )));
+    }
+
+    #[test]
+    fn test_asset_class_circuit_misclassified() {
+        // Real estate claimed as equities, which is not the committed class
+        let claimed = [GOVERNMENT, EQUITIES, EQUITIES, GOVERNMENT];
+        assert!(!is_satisfied(circuit(
+            CLASSES,
+            [300, 350, 100, 250],
+            claimed
+        )));
+    }
+
+    #[test]
+    fn test_asset_class_circuit_adversarial_siblings() {
+        // Real estate claimed as equities, with the siblings that would open the forged leaf
+        // against an additive map: leaf' + sibling' = leaf + sibling at the first level
+        let mut circuit = circuit(CLASSES, [300, 350, 100, 250], CLASSES);
+        let asset_id = circuit.asset_ids[1].unwrap();
+        let sibling = circuit.merkle_paths[1][0].unwrap();
+        circuit.class_ids[1] = Some(EQUITIES);
+        circuit.merkle_paths[1][0] = Some(
+            classification_leaf(asset_id, REAL_ESTATE) + sibling
+                - classification_leaf(asset_id, EQUITIES),
+        );
+        assert!(!is_satisfied(circuit));
+    }
+
+    #[test]
+    fn test_asset_class_circuit_unknown_class() {
+        // Class 3 is committed in the map, but the policy has 3 classes
+        let classes = [GOVERNMENT, REAL_ESTATE, 3, GOVERNMENT];
+        assert!(!is_satisfied(circuit(
+            classes,
+            [300, 250, 200, 250],
+            classes
+        )));
+    }
+}
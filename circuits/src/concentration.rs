@@ -0,0 +1,434 @@
+//! Concentration Limit Circuit
+//!
+//! Proves that the holdings of the portfolio comply with a concentration policy, such as the
+//! UCITS 5/10/40 rule, without revealing them.
+//!
+//! Public Inputs:
+//! - max_single_percentage: Maximum weight of each holding
+//! - large_threshold_percentage: Weight above which a holding is large
+//! - max_large_percentage: Maximum aggregate weight of the large holdings
+//! - max_top_k_percentage: Maximum aggregate weight of the top_k largest holdings
+//!
+//! Private Inputs:
+//! - holdings: Values of the holdings, in any order
+//!
+//! Constraints:
+//! - The holdings are sorted in decreasing order by an odd-even transposition network, each
+//!   comparator proving with a range proof that its outputs are ordered
+//! - largest * 100 <= max_single_percentage * total
+//! - sum(large holdings) * 100 <= max_large_percentage * total, where a holding is large iff
+//!   holding * 100 > large_threshold_percentage * total
+//! - sum(top_k largest holdings) * 100 <= max_top_k_percentage * total
+
+use std::marker::PhantomData;
+
+use bellpepper_core::{Circuit, ConstraintSystem, LinearCombination, SynthesisError};
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+
+use crate::range_proof::{self, RANGE_PROOF_BITS};
+use crate::utils::{alloc_num, alloc_percentage, enforce_le, mul_total, Num, PERCENTAGE_BITS};
+
+/// Maximum number of holdings supported in a single proof
+pub const MAX_HOLDINGS: usize = 64;
+
+/// Number of bits of the weighted values (holding * 100 and percentage * total), enough for
+/// MAX_HOLDINGS holdings of RANGE_PROOF_BITS bits
+const WEIGHTED_BITS: usize = RANGE_PROOF_BITS + 6 + PERCENTAGE_BITS;
+
+/// Limits on the weights of the holdings in the portfolio, in percent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcentrationPolicy {
+    /// Maximum weight of each holding
+    pub max_single_pct: u64,
+    /// Holdings weighing more than `large_threshold_pct` together weigh at most `max_large_pct`
+    pub large_threshold_pct: u64,
+    pub max_large_pct: u64,
+    /// The `top_k` largest holdings together weigh at most `max_top_k_pct`
+    pub top_k: usize,
+    pub max_top_k_pct: u64,
+}
+
+impl ConcentrationPolicy {
+    /// UCITS 5/10/40 rule: each holding weighs at most 10%, and the holdings weighing more than 5%
+    /// together weigh at most 40%
+    pub const UCITS_5_10_40: Self = Self {
+        max_single_pct: 10,
+        large_threshold_pct: 5,
+        max_large_pct: 40,
+        top_k: 0,
+        max_top_k_pct: 100,
+    };
+
+    /// Limits the aggregate weight of the `k` largest holdings to `max_pct`
+    pub const fn top_k(k: usize, max_pct: u64) -> Self {
+        Self {
+            max_single_pct: 100,
+            large_threshold_pct: 100,
+            max_large_pct: 100,
+            top_k: k,
+            max_top_k_pct: max_pct,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConcentrationCircuit<F: PrimeField> {
+    /// Concentration policy, whose percentages are public inputs
+    pub policy: Option<ConcentrationPolicy>,
+
+    /// Number of largest holdings limited by the policy (fixed by the circuit)
+    pub top_k: usize,
+
+    /// Values of the holdings (private witness)
+    pub holdings: Vec<Option<u64>>,
+
+    #[serde(skip)]
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> ConcentrationCircuit<F> {
+    /// Create a new concentration limit circuit
+    pub fn new(policy: ConcentrationPolicy, holdings: Vec<u64>) -> Self {
+        Self {
+            policy: Some(policy),
+            top_k: policy.top_k,
+            holdings: holdings.into_iter().map(Some).collect(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create an empty circuit for setup (no witness data)
+    pub fn empty(num_holdings: usize, top_k: usize) -> Self {
+        Self {
+            policy: None,
+            top_k,
+            holdings: vec![None; num_holdings],
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Comparator of the sorting network: returns (max(a, b), min(a, b))
+fn compare_swap<F, CS>(mut cs: CS, a: &Num, b: &Num) -> Result<(Num, Num), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let swap_value = a.value.zip(b.value).map(|(a, b)| b > a);
+    let swap = range_proof::alloc_boolean(cs.namespace(|| "swap"), swap_value, "swap")?;
+
+    // hi = a + swap * (b - a), lo = a + b - hi
+    let hi = alloc_num(
+        cs.namespace(|| "hi"),
+        a.value.zip(b.value).map(|(a, b)| a.max(b)),
+    )?;
+    let lo = alloc_num(
+        cs.namespace(|| "lo"),
+        a.value.zip(b.value).map(|(a, b)| a.min(b)),
+    )?;
+    cs.enforce(
+        || "hi_constraint",
+        |lc| lc + swap,
+        |lc| lc + b.var - a.var,
+        |lc| lc + hi.var - a.var,
+    );
+    cs.enforce(
+        || "lo_constraint",
+        |lc| lc + CS::one(),
+        |lc| lc + lo.var,
+        |lc| lc + a.var + b.var - hi.var,
+    );
+
+    // lo <= hi, so that hi is the maximum
+    range_proof::enforce_less_than_or_equal(
+        cs.namespace(|| "lo_le_hi"),
+        lo.var,
+        hi.var,
+        lo.value.map(F::from_u128),
+        hi.value.map(F::from_u128),
+        RANGE_PROOF_BITS,
+    )?;
+
+    Ok((hi, lo))
+}
+
+impl<F: PrimeField> Circuit<F> for ConcentrationCircuit<F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let n = self.holdings.len();
+        if n > MAX_HOLDINGS {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let hundred = F::from(100u64);
+
+        // Allocate public inputs: the percentages of the policy
+        let policy = self.policy;
+        let max_single = alloc_percentage(
+            cs.namespace(|| "max_single_percentage"),
+            policy.map(|p| p.max_single_pct),
+        )?;
+        let large_threshold = alloc_percentage(
+            cs.namespace(|| "large_threshold_percentage"),
+            policy.map(|p| p.large_threshold_pct),
+        )?;
+        let max_large = alloc_percentage(
+            cs.namespace(|| "max_large_percentage"),
+            policy.map(|p| p.max_large_pct),
+        )?;
+        let max_top_k = alloc_percentage(
+            cs.namespace(|| "max_top_k_percentage"),
+            policy.map(|p| p.max_top_k_pct),
+        )?;
+
+        // Allocate holdings, each in [0, 2^RANGE_PROOF_BITS), and compute their sum
+        let mut sorted = Vec::with_capacity(n);
+        let mut total = LinearCombination::zero();
+        let mut total_value = Some(0u128);
+        for (i, holding) in self.holdings.iter().enumerate() {
+            let holding = alloc_num(
+                cs.namespace(|| format!("holding_{}", i)),
+                holding.map(u128::from),
+            )?;
+            range_proof::decompose_allocated_value(
+                cs.namespace(|| format!("holding_{}_range_proof", i)),
+                holding.var,
+                holding.value.map(F::from_u128),
+                RANGE_PROOF_BITS,
+                &format!("holding_{}_bits", i),
+            )?;
+            total = total + holding.var;
+            total_value = total_value.zip(holding.value).map(|(t, h)| t + h);
+            sorted.push(holding);
+        }
+
+        // Enforce: total >= 1
+        enforce_le(
+            cs.namespace(|| "total_positive"),
+            LinearCombination::zero() + CS::one(),
+            total.clone(),
+            Some(1),
+            total_value,
+            WEIGHTED_BITS,
+        )?;
+
+        // Sort the holdings in decreasing order: after n rounds of the odd-even transposition
+        // network, sorted[i] >= sorted[i + 1]
+        for round in 0..n {
+            for i in (round % 2..n.saturating_sub(1)).step_by(2) {
+                let (hi, lo) = compare_swap(
+                    cs.namespace(|| format!("round_{}_comparator_{}", round, i)),
+                    &sorted[i],
+                    &sorted[i + 1],
+                )?;
+                sorted[i] = hi;
+                sorted[i + 1] = lo;
+            }
+        }
+
+        // Enforce: largest * 100 <= max_single_percentage * total
+        if let Some(largest) = sorted.first() {
+            let max_value = mul_total(
+                cs.namespace(|| "max_single_value"),
+                &max_single,
+                &total,
+                total_value,
+            )?;
+            enforce_le(
+                cs.namespace(|| "single_limit"),
+                LinearCombination::zero() + (hundred, largest.var),
+                LinearCombination::zero() + max_value.var,
+                largest.value.map(|v| 100 * v),
+                max_value.value,
+                WEIGHTED_BITS,
+            )?;
+        }
+
+        // Sum the large holdings: is_large = 1 iff holding * 100 > threshold
+        let threshold = mul_total(
+            cs.namespace(|| "large_threshold_value"),
+            &large_threshold,
+            &total,
+            total_value,
+        )?;
+        let mut large = LinearCombination::zero();
+        let mut large_value = Some(0u128);
+        for (i, holding) in sorted.iter().enumerate() {
+            let mut cs = cs.namespace(|| format!("large_{}", i));
+            let weighted = holding.value.map(|v| 100 * v);
+            let is_large_value = weighted.zip(threshold.value).map(|(w, t)| w > t);
+            let is_large = range_proof::alloc_boolean(
+                cs.namespace(|| "is_large"),
+                is_large_value,
+                "is_large",
+            )?;
+
+            // is_large * (holding * 100 - threshold - 1) >= 0
+            let excess_value = weighted.zip(threshold.value).map(|(w, t)| {
+                if w > t {
+                    F::from_u128(w - t - 1)
+                } else {
+                    F::ZERO
+                }
+            });
+            let excess = cs.alloc(
+                || "excess",
+                || excess_value.ok_or(SynthesisError::AssignmentMissing),
+            )?;
+            cs.enforce(
+                || "excess_constraint",
+                |lc| lc + is_large,
+                |lc| lc + (hundred, holding.var) - threshold.var - CS::one(),
+                |lc| lc + excess,
+            );
+            range_proof::decompose_allocated_value(
+                cs.namespace(|| "excess_range_proof"),
+                excess,
+                excess_value,
+                WEIGHTED_BITS,
+                "excess_bits",
+            )?;
+
+            // (1 - is_large) * (threshold - holding * 100) >= 0
+            let slack_value = weighted.zip(threshold.value).map(|(w, t)| {
+                if w > t {
+                    F::ZERO
+                } else {
+                    F::from_u128(t - w)
+                }
+            });
+            let slack = cs.alloc(
+                || "slack",
+                || slack_value.ok_or(SynthesisError::AssignmentMissing),
+            )?;
+            cs.enforce(
+                || "slack_constraint",
+                |lc| lc + CS::one() - is_large,
+                |lc| lc + threshold.var - (hundred, holding.var),
+                |lc| lc + slack,
+            );
+            range_proof::decompose_allocated_value(
+                cs.namespace(|| "slack_range_proof"),
+                slack,
+                slack_value,
+                WEIGHTED_BITS,
+                "slack_bits",
+            )?;
+
+            // large_holding = is_large * holding
+            let large_holding = alloc_num(
+                cs.namespace(|| "large_holding"),
+                is_large_value
+                    .zip(holding.value)
+                    .map(|(is_large, v)| if is_large { v } else { 0 }),
+            )?;
+            cs.enforce(
+                || "large_holding_constraint",
+                |lc| lc + is_large,
+                |lc| lc + holding.var,
+                |lc| lc + large_holding.var,
+            );
+            large = large + (hundred, large_holding.var);
+            large_value = large_value
+                .zip(large_holding.value)
+                .map(|(s, v)| s + 100 * v);
+        }
+
+        // Enforce: sum(large holdings) * 100 <= max_large_percentage * total
+        let max_large_value = mul_total(
+            cs.namespace(|| "max_large_value"),
+            &max_large,
+            &total,
+            total_value,
+        )?;
+        enforce_le(
+            cs.namespace(|| "large_limit"),
+            large,
+            LinearCombination::zero() + max_large_value.var,
+            large_value,
+            max_large_value.value,
+            WEIGHTED_BITS,
+        )?;
+
+        // Enforce: sum(top_k largest holdings) * 100 <= max_top_k_percentage * total
+        let mut top_k = LinearCombination::zero();
+        let mut top_k_value = Some(0u128);
+        for holding in sorted.iter().take(self.top_k) {
+            top_k = top_k + (hundred, holding.var);
+            top_k_value = top_k_value.zip(holding.value).map(|(s, v)| s + 100 * v);
+        }
+        let max_top_k_value = mul_total(
+            cs.namespace(|| "max_top_k_value"),
+            &max_top_k,
+            &total,
+            total_value,
+        )?;
+        enforce_le(
+            cs.namespace(|| "top_k_limit"),
+            top_k,
+            LinearCombination::zero() + max_top_k_value.var,
+            top_k_value,
+            max_top_k_value.value,
+            WEIGHTED_BITS,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use pasta_curves::Fp;
+
+    fn is_satisfied(policy: ConcentrationPolicy, holdings: &[u64]) -> bool {
+        let mut cs = TestConstraintSystem::<Fp>::new();
+        let circuit = ConcentrationCircuit::new(policy, holdings.to_vec());
+        circuit.synthesize(&mut cs).unwrap();
+        cs.is_satisfied()
+    }
+
+    #[test]
+    fn test_ucits_5_10_40_valid() {
+        // 16 holdings, out of order: four of 10% (large) and twelve of 5% (not large)
+        // Large holdings weigh exactly 40%
+        let holdings = [
+            50, 100, 50, 50, 100, 50, 50, 50, 100, 50, 50, 50, 50, 100, 50, 50,
+        ];
+
+        let mut cs = TestConstraintSystem::<Fp>::new();
+        let circuit =
+            ConcentrationCircuit::new(ConcentrationPolicy::UCITS_5_10_40, holdings.to_vec());
+        circuit.synthesize(&mut cs).unwrap();
+        assert!(cs.is_satisfied());
+        println!("Num constraints: {}", cs.num_constraints());
+    }
+
+    #[test]
+    fn test_ucits_5_10_40_large_violation() {
+        // Holdings above 5%: 4 * 10% + 6% = 46% > 40%
+        let holdings = [&[100, 100, 100, 100, 60][..], &[50; 10], &[40]].concat();
+        assert!(!is_satisfied(ConcentrationPolicy::UCITS_5_10_40, &holdings));
+    }
+
+    #[test]
+    fn test_ucits_5_10_40_single_violation() {
+        // 11% > 10%
+        let holdings = [&[110, 90][..], &[50; 16]].concat();
+        assert!(!is_satisfied(ConcentrationPolicy::UCITS_5_10_40, &holdings));
+    }
+
+    #[test]
+    fn test_top_k() {
+        let policy = ConcentrationPolicy::top_k(3, 75);
+        // Top 3: 30 + 25 + 20 = 75%
+        assert!(is_satisfied(policy, &[10, 30, 5, 20, 10, 25]));
+        // Top 3: 30 + 25 + 21 = 76 out of 101
+        assert!(!is_satisfied(policy, &[10, 30, 5, 25, 10, 21]));
+    }
+
+    #[test]
+    fn test_empty_portfolio() {
+        assert!(!is_satisfied(ConcentrationPolicy::UCITS_5_10_40, &[0, 0]));
+    }
+}
@@ -0,0 +1,575 @@
+//! Leverage Circuit
+//!
+//! Proves that the gross and net exposures of a portfolio holding long and short positions,
+//! including derivatives, stay within the leverage limits, without revealing the positions.
+//!
+//! Each position is signed (a sign bit and a range-checked magnitude), and carries a delta factor
+//! so that derivatives count for their delta-adjusted notional:
+//! exposure = notional * delta_bps / 10_000, with delta_bps = 10_000 for cash positions.
+//!
+//! Public Inputs:
+//! - max_gross_percentage: Maximum gross exposure, in percent of the NAV (e.g., 200 for 2x)
+//! - min_net_percentage, max_net_percentage: Bounds of the net exposure, in percent of the NAV,
+//!   possibly negative for net-short funds
+//! - nav: Net asset value of the fund, as attested to the verifier. The limits scale with the NAV,
+//!   so that a NAV chosen by the prover could be inflated to fit any portfolio.
+//!
+//! Private Inputs:
+//! - positions: Sign, notional and delta of each position
+//!
+//! Constraints:
+//! - gross = sum(exposure) <= max_gross_percentage * nav / 100
+//! - min_net_percentage * nav / 100 <= net = sum(±exposure) <= max_net_percentage * nav / 100
+
+use std::marker::PhantomData;
+
+use bellpepper_core::{Circuit, ConstraintSystem, LinearCombination, SynthesisError, Variable};
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+
+use crate::range_proof::{self, RANGE_PROOF_BITS};
+
+/// Maximum number of positions supported in a single proof
+pub const MAX_POSITIONS: usize = 32;
+
+/// Delta factors are expressed in basis points
+pub const BASIS_POINTS: u64 = 10_000;
+
+/// Number of bits of a delta factor (up to 6.5x)
+const DELTA_BITS: usize = 16;
+
+/// Number of bits of the leverage percentages
+const PERCENTAGE_BITS: usize = 16;
+
+/// Number of bits of the exposures and limits, scaled by BASIS_POINTS: enough for
+/// MAX_POSITIONS positions of RANGE_PROOF_BITS bits with their delta, and for
+/// percentage * nav * 100
+const EXPOSURE_BITS: usize = RANGE_PROOF_BITS + DELTA_BITS + 8;
+
+/// Signed position of the portfolio
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub is_short: bool,
+    pub notional: u64,
+    /// Delta factor of the position, in basis points
+    pub delta_bps: u64,
+}
+
+impl Position {
+    pub fn long(notional: u64) -> Self {
+        Self {
+            is_short: false,
+            notional,
+            delta_bps: BASIS_POINTS,
+        }
+    }
+
+    pub fn short(notional: u64) -> Self {
+        Self {
+            is_short: true,
+            notional,
+            delta_bps: BASIS_POINTS,
+        }
+    }
+
+    /// Derivative position, whose exposure is its notional adjusted by its delta
+    pub fn derivative(is_short: bool, notional: u64, delta_bps: u64) -> Self {
+        Self {
+            is_short,
+            notional,
+            delta_bps,
+        }
+    }
+
+    /// Delta-adjusted exposure, scaled by BASIS_POINTS
+    pub fn exposure(&self) -> u128 {
+        self.notional as u128 * self.delta_bps as u128
+    }
+
+    /// Signed delta-adjusted exposure, scaled by BASIS_POINTS
+    pub fn signed_exposure(&self) -> i128 {
+        if self.is_short {
+            -(self.exposure() as i128)
+        } else {
+            self.exposure() as i128
+        }
+    }
+}
+
+/// Leverage limits, in percent of the NAV
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExposureLimits {
+    pub max_gross_pct: u64,
+    pub min_net_pct: i64,
+    pub max_net_pct: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LeverageCircuit<F: PrimeField> {
+    /// Leverage limits (public inputs)
+    pub limits: Option<ExposureLimits>,
+
+    /// Signed positions (private witness)
+    pub positions: Vec<Option<Position>>,
+
+    /// Net asset value (public input)
+    pub nav: Option<u64>,
+
+    #[serde(skip)]
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> LeverageCircuit<F> {
+    /// Create a new leverage circuit
+    pub fn new(limits: ExposureLimits, positions: Vec<Position>, nav: u64) -> Self {
+        Self {
+            limits: Some(limits),
+            positions: positions.into_iter().map(Some).collect(),
+            nav: Some(nav),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create an empty circuit for setup (no witness data)
+    pub fn empty(num_positions: usize) -> Self {
+        Self {
+            limits: None,
+            positions: vec![None; num_positions],
+            nav: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Map a signed integer into the field
+fn field_from_i128<F: PrimeField>(value: i128) -> F {
+    let magnitude = F::from_u128(value.unsigned_abs());
+    if value < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Allocate a signed percentage as a public input, range checked to [-2^15, 2^15)
+fn alloc_signed_percentage<F, CS>(
+    mut cs: CS,
+    value: Option<i64>,
+) -> Result<Variable, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let offset = 1i128 << (PERCENTAGE_BITS - 1);
+    let var = cs.alloc_input(
+        || "percentage",
+        || {
+            value
+                .map(|v| field_from_i128(v as i128))
+                .ok_or(SynthesisError::AssignmentMissing)
+        },
+    )?;
+
+    // percentage + 2^15 is in [0, 2^16)
+    let shifted_val = value.map(|v| field_from_i128(v as i128 + offset));
+    let shifted = cs.alloc(
+        || "shifted_percentage",
+        || shifted_val.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    cs.enforce(
+        || "shifted_percentage_constraint",
+        |lc| lc + CS::one(),
+        |lc| lc + var + (F::from_u128(offset as u128), CS::one()),
+        |lc| lc + shifted,
+    );
+    range_proof::decompose_allocated_value(
+        cs.namespace(|| "range_proof"),
+        shifted,
+        shifted_val,
+        PERCENTAGE_BITS,
+        "shifted_percentage_bits",
+    )?;
+    Ok(var)
+}
+
+/// Allocated signed position: the sign bit and the delta-adjusted exposure
+struct SignedExposure {
+    is_short: Variable,
+    exposure: Variable,
+}
+
+/// Allocate a signed position, range checking its notional and delta, and compute its exposure
+fn alloc_signed_position<F, CS>(
+    mut cs: CS,
+    position: Option<Position>,
+) -> Result<SignedExposure, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let is_short = range_proof::alloc_boolean(
+        cs.namespace(|| "is_short"),
+        position.map(|p| p.is_short),
+        "is_short",
+    )?;
+
+    let notional_val = position.map(|p| F::from(p.notional));
+    let notional = cs.alloc(
+        || "notional",
+        || notional_val.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    range_proof::decompose_allocated_value(
+        cs.namespace(|| "notional_range_proof"),
+        notional,
+        notional_val,
+        RANGE_PROOF_BITS,
+        "notional_bits",
+    )?;
+
+    let delta_val = position.map(|p| F::from(p.delta_bps));
+    let delta = cs.alloc(
+        || "delta",
+        || delta_val.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    range_proof::decompose_allocated_value(
+        cs.namespace(|| "delta_range_proof"),
+        delta,
+        delta_val,
+        DELTA_BITS,
+        "delta_bits",
+    )?;
+
+    // Enforce: exposure = notional * delta
+    let exposure = cs.alloc(
+        || "exposure",
+        || {
+            position
+                .map(|p| F::from_u128(p.exposure()))
+                .ok_or(SynthesisError::AssignmentMissing)
+        },
+    )?;
+    cs.enforce(
+        || "exposure_constraint",
+        |lc| lc + notional,
+        |lc| lc + delta,
+        |lc| lc + exposure,
+    );
+
+    Ok(SignedExposure { is_short, exposure })
+}
+
+/// Allocate percentage * nav * 100, the limit at `percentage` scaled like the exposures
+fn alloc_scaled_limit<F, CS>(
+    mut cs: CS,
+    percentage: Variable,
+    percentage_val: Option<i128>,
+    nav: Variable,
+    nav_val: Option<u64>,
+) -> Result<(Variable, Option<F>), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let value = percentage_val
+        .zip(nav_val)
+        .map(|(p, n)| field_from_i128(p * n as i128 * 100));
+    let limit = cs.alloc(
+        || "limit",
+        || value.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    cs.enforce(
+        || "limit_constraint",
+        |lc| lc + percentage,
+        |lc| lc + (F::from(100u64), nav),
+        |lc| lc + limit,
+    );
+    Ok((limit, value))
+}
+
+impl<F: PrimeField> Circuit<F> for LeverageCircuit<F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        if self.positions.len() > MAX_POSITIONS {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        // Allocate public inputs: the leverage limits
+        let max_gross_val = self.limits.map(|l| F::from(l.max_gross_pct));
+        let max_gross = cs.alloc_input(
+            || "max_gross_percentage",
+            || max_gross_val.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        range_proof::decompose_allocated_value(
+            cs.namespace(|| "max_gross_range_proof"),
+            max_gross,
+            max_gross_val,
+            PERCENTAGE_BITS,
+            "max_gross_bits",
+        )?;
+        let min_net = alloc_signed_percentage(
+            cs.namespace(|| "min_net_percentage"),
+            self.limits.map(|l| l.min_net_pct),
+        )?;
+        let max_net = alloc_signed_percentage(
+            cs.namespace(|| "max_net_percentage"),
+            self.limits.map(|l| l.max_net_pct),
+        )?;
+
+        // Allocate public input: nav, in [1, 2^RANGE_PROOF_BITS)
+        let nav_val = self.nav.map(F::from);
+        let nav = cs.alloc_input(
+            || "nav",
+            || nav_val.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let nav_minus_one_val = self.nav.map(|n| F::from(n) - F::ONE);
+        let nav_minus_one = cs.alloc(
+            || "nav_minus_one",
+            || nav_minus_one_val.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        cs.enforce(
+            || "nav_minus_one_constraint",
+            |lc| lc + CS::one(),
+            |lc| lc + nav - CS::one(),
+            |lc| lc + nav_minus_one,
+        );
+        range_proof::decompose_allocated_value(
+            cs.namespace(|| "nav_range_proof"),
+            nav_minus_one,
+            nav_minus_one_val,
+            RANGE_PROOF_BITS,
+            "nav_bits",
+        )?;
+
+        // Compute gross = sum(exposure) and net = sum(exposure - 2 * is_short * exposure)
+        let mut gross_lc = LinearCombination::zero();
+        let mut net_lc = LinearCombination::zero();
+        for (i, position) in self.positions.iter().enumerate() {
+            let signed =
+                alloc_signed_position(cs.namespace(|| format!("position_{}", i)), *position)?;
+
+            // short_exposure = is_short * exposure
+            let short_exposure = cs.alloc(
+                || format!("short_exposure_{}", i),
+                || {
+                    position
+                        .map(|p| if p.is_short { p.exposure() } else { 0 })
+                        .map(F::from_u128)
+                        .ok_or(SynthesisError::AssignmentMissing)
+                },
+            )?;
+            cs.enforce(
+                || format!("short_exposure_{}_constraint", i),
+                |lc| lc + signed.is_short,
+                |lc| lc + signed.exposure,
+                |lc| lc + short_exposure,
+            );
+
+            gross_lc = gross_lc + signed.exposure;
+            net_lc = net_lc + signed.exposure - (F::from(2u64), short_exposure);
+        }
+
+        let positions = self
+            .positions
+            .iter()
+            .copied()
+            .collect::<Option<Vec<Position>>>();
+        let gross_val = positions
+            .as_ref()
+            .map(|p| F::from_u128(p.iter().map(Position::exposure).sum()));
+        let net_val = positions
+            .as_ref()
+            .map(|p| field_from_i128(p.iter().map(Position::signed_exposure).sum()));
+        let gross = cs.alloc(
+            || "gross_exposure",
+            || gross_val.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        cs.enforce(
+            || "gross_exposure_constraint",
+            |lc| lc + CS::one(),
+            |_| gross_lc,
+            |lc| lc + gross,
+        );
+        let net = cs.alloc(
+            || "net_exposure",
+            || net_val.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        cs.enforce(
+            || "net_exposure_constraint",
+            |lc| lc + CS::one(),
+            |_| net_lc,
+            |lc| lc + net,
+        );
+
+        // Limits scaled like the exposures: percentage * nav * BASIS_POINTS / 100
+        let limits = self.limits;
+        let (max_gross_limit, max_gross_limit_val) = alloc_scaled_limit(
+            cs.namespace(|| "max_gross_limit"),
+            max_gross,
+            limits.map(|l| l.max_gross_pct as i128),
+            nav,
+            self.nav,
+        )?;
+        let (min_net_limit, min_net_limit_val) = alloc_scaled_limit(
+            cs.namespace(|| "min_net_limit"),
+            min_net,
+            limits.map(|l| l.min_net_pct as i128),
+            nav,
+            self.nav,
+        )?;
+        let (max_net_limit, max_net_limit_val) = alloc_scaled_limit(
+            cs.namespace(|| "max_net_limit"),
+            max_net,
+            limits.map(|l| l.max_net_pct as i128),
+            nav,
+            self.nav,
+        )?;
+
+        // Enforce: gross <= max_gross_limit
+        range_proof::enforce_less_than_or_equal(
+            cs.namespace(|| "gross_limit"),
+            gross,
+            max_gross_limit,
+            gross_val,
+            max_gross_limit_val,
+            EXPOSURE_BITS,
+        )?;
+
+        // Enforce: min_net_limit <= net <= max_net_limit
+        range_proof::enforce_less_than_or_equal(
+            cs.namespace(|| "min_net_limit"),
+            min_net_limit,
+            net,
+            min_net_limit_val,
+            net_val,
+            EXPOSURE_BITS,
+        )?;
+        range_proof::enforce_less_than_or_equal(
+            cs.namespace(|| "max_net_limit"),
+            net,
+            max_net_limit,
+            net_val,
+            max_net_limit_val,
+            EXPOSURE_BITS,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use pasta_curves::Fp;
+
+    fn is_satisfied(limits: ExposureLimits, positions: &[Position], nav: u64) -> bool {
+        let mut cs = TestConstraintSystem::<Fp>::new();
+        let circuit = LeverageCircuit::new(limits, positions.to_vec(), nav);
+        circuit.synthesize(&mut cs).unwrap();
+        cs.is_satisfied()
+    }
+
+    /// Public inputs of the circuit: the limits, then the NAV
+    fn public_inputs(limits: ExposureLimits, nav: u64) -> Vec<Fp> {
+        vec![
+            Fp::from(limits.max_gross_pct),
+            field_from_i128(limits.min_net_pct as i128),
+            field_from_i128(limits.max_net_pct as i128),
+            Fp::from(nav),
+        ]
+    }
+
+    /// 130/30 fund: gross 160%, net 100%
+    fn long_short_positions() -> Vec<Position> {
+        vec![
+            Position::long(800),
+            Position::short(300),
+            Position::long(500),
+        ]
+    }
+
+    #[test]
+    fn test_leverage_circuit_valid() {
+        let limits = ExposureLimits {
+            max_gross_pct: 200,
+            min_net_pct: 50,
+            max_net_pct: 150,
+        };
+
+        let mut cs = TestConstraintSystem::<Fp>::new();
+        let circuit = LeverageCircuit::new(limits, long_short_positions(), 1000);
+        circuit.synthesize(&mut cs).unwrap();
+        assert!(cs.is_satisfied());
+        assert!(cs.verify(&public_inputs(limits, 1000)));
+        println!(
+            "Leverage circuit - Num constraints: {}",
+            cs.num_constraints()
+        );
+    }
+
+    #[test]
+    fn test_leverage_circuit_gross_violation() {
+        // Gross 160% > 150%
+        let limits = ExposureLimits {
+            max_gross_pct: 150,
+            min_net_pct: 50,
+            max_net_pct: 150,
+        };
+        assert!(!is_satisfied(limits, &long_short_positions(), 1000));
+    }
+
+    #[test]
+    fn test_leverage_circuit_inflated_nav() {
+        // Gross 160% > 150% of the attested NAV, but 80% of twice the NAV
+        let limits = ExposureLimits {
+            max_gross_pct: 150,
+            min_net_pct: 50,
+            max_net_pct: 150,
+        };
+        let mut cs = TestConstraintSystem::<Fp>::new();
+        let circuit = LeverageCircuit::new(limits, long_short_positions(), 2000);
+        circuit.synthesize(&mut cs).unwrap();
+        assert!(cs.is_satisfied());
+        assert!(!cs.verify(&public_inputs(limits, 1000)));
+    }
+
+    #[test]
+    fn test_leverage_circuit_net_short() {
+        // Net exposure within [-50%, 0%]
+        let limits = ExposureLimits {
+            max_gross_pct: 200,
+            min_net_pct: -50,
+            max_net_pct: 0,
+        };
+        // Net -30%
+        assert!(is_satisfied(
+            limits,
+            &[Position::long(200), Position::short(500)],
+            1000
+        ));
+        // Net -60% < -50%
+        assert!(!is_satisfied(
+            limits,
+            &[Position::long(200), Position::short(800)],
+            1000
+        ));
+        // Net +10% > 0%
+        assert!(!is_satisfied(
+            limits,
+            &[Position::long(600), Position::short(500)],
+            1000
+        ));
+    }
+
+    #[test]
+    fn test_leverage_circuit_derivatives() {
+        // Call option on 1000 of notional with a delta of 0.5: gross and net 110%
+        let positions = [Position::long(600), Position::derivative(false, 1000, 5000)];
+        let limits = |max_gross_pct| ExposureLimits {
+            max_gross_pct,
+            min_net_pct: 0,
+            max_net_pct: 120,
+        };
+        assert!(is_satisfied(limits(120), &positions, 1000));
+        assert!(!is_satisfied(limits(100), &positions, 1000));
+    }
+}
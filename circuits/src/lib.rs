@@ -6,11 +6,18 @@
 pub mod position_limit;
 pub mod liquidity_reserve;
 pub mod whitelist;
+pub mod concentration;
+pub mod asset_class;
+pub mod leverage;
+pub mod redemption;
 pub mod range_proof;
+pub mod poseidon;
 pub mod utils;
 
 // Nova-compatible circuits using BN254
 pub mod nova_circuits;
+pub mod turnover;
+pub mod reserves;
 
 // Composite circuit that combines all checks
 pub mod composite_circuit;
@@ -18,5 +25,11 @@ pub mod composite_circuit;
 pub use position_limit::PositionLimitCircuit;
 pub use liquidity_reserve::LiquidityReserveCircuit;
 pub use whitelist::WhitelistCircuit;
+pub use concentration::{ConcentrationCircuit, ConcentrationPolicy};
+pub use asset_class::{AssetClassCircuit, ClassLimit, ClassifiedAsset};
+pub use leverage::{ExposureLimits, LeverageCircuit, Position};
+pub use redemption::{RedemptionCoverageCircuit, RedemptionRequest};
 pub use nova_circuits::{NovaLiquidityCircuit, NovaPositionLimitCircuit};
+pub use turnover::NovaTurnoverCircuit;
+pub use reserves::{InclusionProof, LiabilitiesTree, NovaReservesCircuit};
 pub use composite_circuit::{FundComplianceCircuit, FundComplianceParams};
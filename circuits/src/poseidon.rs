@@ -0,0 +1,67 @@
+//! Poseidon hash shared by the circuits
+//!
+//! `poseidon_hash` and `poseidon_hash_gadget` absorb their inputs in a sponge of width 3 and
+//! squeeze a single element. The bellpepper circuits use the gadget through
+//! `utils::poseidon_hash`.
+
+use arecibo::frontend::{
+    gadgets::poseidon::{
+        Elt, IOPattern, PoseidonConstants, Simplex, Sponge, SpongeAPI, SpongeCircuit, SpongeOp,
+        SpongeTrait,
+    },
+    num::AllocatedNum,
+    ConstraintSystem, SynthesisError,
+};
+use ff::PrimeField;
+use generic_array::typenum::U2;
+
+/// Poseidon hash of `inputs`, absorbed by a sponge of width 3
+pub fn poseidon_hash<F: PrimeField>(inputs: &[F]) -> F {
+    let constants = PoseidonConstants::<F, U2>::new();
+    let parameter = IOPattern(vec![
+        SpongeOp::Absorb(inputs.len() as u32),
+        SpongeOp::Squeeze(1u32),
+    ]);
+
+    let mut sponge = Sponge::new_with_constants(&constants, Simplex);
+    let acc = &mut ();
+    sponge.start(parameter, None, acc);
+    SpongeAPI::absorb(&mut sponge, inputs.len() as u32, inputs, acc);
+    let hash = SpongeAPI::squeeze(&mut sponge, 1, acc);
+    sponge.finish(acc).unwrap();
+    hash[0]
+}
+
+/// Hash allocated inputs, matching `poseidon_hash`
+pub fn poseidon_hash_gadget<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    inputs: &[AllocatedNum<F>],
+    constants: &PoseidonConstants<F, U2>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    let parameter = IOPattern(vec![
+        SpongeOp::Absorb(inputs.len() as u32),
+        SpongeOp::Squeeze(1u32),
+    ]);
+    let mut ns = cs.namespace(|| "sponge");
+
+    let hash = {
+        let mut sponge = SpongeCircuit::new_with_constants(constants, Simplex);
+        let acc = &mut ns;
+
+        sponge.start(parameter, None, acc);
+        SpongeAPI::absorb(
+            &mut sponge,
+            inputs.len() as u32,
+            &inputs
+                .iter()
+                .map(|x| Elt::Allocated(x.clone()))
+                .collect::<Vec<_>>(),
+            acc,
+        );
+        let output = SpongeAPI::squeeze(&mut sponge, 1, acc);
+        sponge.finish(acc).unwrap();
+        output
+    };
+
+    Elt::ensure_allocated(&hash[0], &mut ns.namespace(|| "ensure allocated"), true)
+}
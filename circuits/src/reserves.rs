@@ -0,0 +1,562 @@
+//! Proof of Reserves Circuit (BN254 version)
+//!
+//! Proves that the fund is solvent: sum(investor shares) * nav_per_share <= attested_assets,
+//! without revealing the individual balances.
+//!
+//! The liabilities are committed as a Merkle-sum tree: each leaf is Poseidon(investor_id, shares)
+//! and carries the shares, and each node is Poseidon(left.hash, left.sum, right.hash, right.sum)
+//! and carries left.sum + right.sum. Every investor gets an inclusion proof of their leaf against
+//! the published root, so that a balance left out of the tree would be noticed.
+//!
+//! The IVC folds the tree in chunks of 2^chunk_depth leaves: each step rebuilds the subtree of one
+//! chunk, opens it at the position chunk_index against the root, and adds its sum to the total. The
+//! shares and the sums are range-checked, so that no negative balance can offset the total. After
+//! the last step, chunk_index equals the number of chunks, so every leaf was counted exactly once.
+//!
+//! State: [liabilities_root, chunk_index, total_shares, nav_per_share, attested_assets]
+
+use arecibo::{
+    frontend::{
+        gadgets::poseidon::PoseidonConstants, num::AllocatedNum, AllocatedBit, ConstraintSystem,
+        SynthesisError,
+    },
+    nebula::rs::StepCircuit,
+};
+use ff::{Field, PrimeField};
+use generic_array::typenum::U2;
+use halo2curves::bn256::Fr;
+
+use crate::poseidon::{poseidon_hash, poseidon_hash_gadget};
+use crate::utils::{increment, range_check};
+
+/// Maximum depth of the liabilities tree (supports up to 2^32 investors)
+pub const MAX_TREE_DEPTH: usize = 32;
+
+/// Number of bits of the shares of an investor
+const SHARES_BITS: usize = 64;
+
+/// Number of bits of the sum of a subtree
+const SUM_BITS: usize = SHARES_BITS + MAX_TREE_DEPTH;
+
+/// Number of bits of a NAV per share
+const NAV_BITS: usize = 64;
+
+/// Number of bits of the attested assets, and of the liabilities (total_shares * nav_per_share)
+const ASSETS_BITS: usize = SUM_BITS + NAV_BITS;
+
+/// Shares held by an investor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Liability {
+    pub investor_id: Fr,
+    pub shares: u64,
+}
+
+impl Liability {
+    /// Padding leaf of the tree, which holds no shares
+    pub fn empty() -> Self {
+        Self {
+            investor_id: Fr::ZERO,
+            shares: 0,
+        }
+    }
+}
+
+/// Node of the Merkle-sum tree: the hash of the subtree, and the sum of its shares
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MerkleSumNode {
+    pub hash: Fr,
+    pub sum: u128,
+}
+
+impl MerkleSumNode {
+    pub fn leaf(liability: &Liability) -> Self {
+        Self {
+            hash: poseidon_hash(&[liability.investor_id, Fr::from(liability.shares)]),
+            sum: u128::from(liability.shares),
+        }
+    }
+
+    pub fn parent(left: &Self, right: &Self) -> Self {
+        Self {
+            hash: poseidon_hash(&[
+                left.hash,
+                Fr::from_u128(left.sum),
+                right.hash,
+                Fr::from_u128(right.sum),
+            ]),
+            sum: left.sum + right.sum,
+        }
+    }
+}
+
+/// Proof that the shares of an investor are counted in the liabilities tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub liability: Liability,
+    pub index: usize,
+    /// Siblings from the leaf up to the root
+    pub siblings: Vec<MerkleSumNode>,
+}
+
+impl InclusionProof {
+    /// Check the proof against the published root. The sums of the siblings can't be negative, so
+    /// the shares of the investor are at most the total of the tree
+    pub fn verify(&self, root: &MerkleSumNode) -> bool {
+        let mut node = MerkleSumNode::leaf(&self.liability);
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            if sibling.sum >= 1 << SUM_BITS {
+                return false;
+            }
+            node = if (self.index >> level) & 1 == 1 {
+                MerkleSumNode::parent(sibling, &node)
+            } else {
+                MerkleSumNode::parent(&node, sibling)
+            };
+        }
+        node == *root
+    }
+}
+
+/// Merkle-sum tree of the liabilities of the fund, built natively by the fund administrator
+#[derive(Clone, Debug)]
+pub struct LiabilitiesTree {
+    liabilities: Vec<Liability>,
+    /// Nodes of each level, from the leaves up to the root
+    levels: Vec<Vec<MerkleSumNode>>,
+}
+
+impl LiabilitiesTree {
+    /// Build a tree of depth `depth`, padded with empty leaves
+    pub fn new(liabilities: Vec<Liability>, depth: usize) -> Self {
+        assert!(depth <= MAX_TREE_DEPTH, "Tree depth exceeds MAX_TREE_DEPTH");
+        assert!(
+            liabilities.len() <= 1 << depth,
+            "Too many liabilities for the tree depth"
+        );
+
+        let mut liabilities = liabilities;
+        liabilities.resize(1 << depth, Liability::empty());
+        let mut levels = vec![liabilities
+            .iter()
+            .map(MerkleSumNode::leaf)
+            .collect::<Vec<_>>()];
+        for _ in 0..depth {
+            let level = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| MerkleSumNode::parent(&pair[0], &pair[1]))
+                .collect();
+            levels.push(level);
+        }
+        Self {
+            liabilities,
+            levels,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    pub fn root(&self) -> MerkleSumNode {
+        self.levels[self.depth()][0]
+    }
+
+    /// Inclusion proof of the leaf at `index`, to be handed to its investor
+    pub fn inclusion_proof(&self, index: usize) -> InclusionProof {
+        InclusionProof {
+            liability: self.liabilities[index],
+            index,
+            siblings: self.siblings(0, index),
+        }
+    }
+
+    /// Siblings of the node at `index` of `level`, up to the root
+    fn siblings(&self, level: usize, index: usize) -> Vec<MerkleSumNode> {
+        (level..self.depth())
+            .map(|l| self.levels[l][(index >> (l - level)) ^ 1])
+            .collect()
+    }
+
+    /// Initial state of the IVC folding the tree
+    pub fn initial_state(&self, nav_per_share: u64, attested_assets: u128) -> Vec<Fr> {
+        vec![
+            self.root().hash,
+            Fr::ZERO,
+            Fr::ZERO,
+            Fr::from(nav_per_share),
+            Fr::from_u128(attested_assets),
+        ]
+    }
+
+    /// Step circuits folding the tree in chunks of 2^chunk_depth leaves, in order
+    pub fn step_circuits(&self, chunk_depth: usize) -> Vec<NovaReservesCircuit> {
+        assert!(chunk_depth <= self.depth(), "Chunks exceed the tree");
+        let chunk_size = 1 << chunk_depth;
+        self.liabilities
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(chunk_index, chunk)| NovaReservesCircuit {
+                tree_depth: self.depth(),
+                chunk: chunk.to_vec(),
+                siblings: self.siblings(chunk_depth, chunk_index),
+            })
+            .collect()
+    }
+}
+
+/// Allocated node of the Merkle-sum tree
+struct MerkleSumNodeVar {
+    hash: AllocatedNum<Fr>,
+    sum: AllocatedNum<Fr>,
+}
+
+/// Parent of two allocated nodes, matching `MerkleSumNode::parent`
+fn parent_gadget<CS: ConstraintSystem<Fr>>(
+    mut cs: CS,
+    left: &MerkleSumNodeVar,
+    right: &MerkleSumNodeVar,
+    constants: &PoseidonConstants<Fr, U2>,
+) -> Result<MerkleSumNodeVar, SynthesisError> {
+    let hash = poseidon_hash_gadget(
+        cs.namespace(|| "hash"),
+        &[
+            left.hash.clone(),
+            left.sum.clone(),
+            right.hash.clone(),
+            right.sum.clone(),
+        ],
+        constants,
+    )?;
+    let sum = left.sum.add(cs.namespace(|| "sum"), &right.sum)?;
+    range_check(cs.namespace(|| "sum_range"), &sum, SUM_BITS)?;
+    Ok(MerkleSumNodeVar { hash, sum })
+}
+
+/// Order `node` and `sibling` as (left, right), where `is_right` tells whether `node` is the right
+/// child
+fn conditional_swap<CS: ConstraintSystem<Fr>>(
+    mut cs: CS,
+    node: &AllocatedNum<Fr>,
+    sibling: &AllocatedNum<Fr>,
+    is_right: &AllocatedBit,
+) -> Result<(AllocatedNum<Fr>, AllocatedNum<Fr>), SynthesisError> {
+    let swap = |a: &AllocatedNum<Fr>, b: &AllocatedNum<Fr>| {
+        a.get_value()
+            .zip(b.get_value())
+            .zip(is_right.get_value())
+            .map(|((a, b), is_right)| if is_right { b } else { a })
+            .ok_or(SynthesisError::AssignmentMissing)
+    };
+    let left = AllocatedNum::alloc(cs.namespace(|| "left"), || swap(node, sibling))?;
+    let right = AllocatedNum::alloc(cs.namespace(|| "right"), || swap(sibling, node))?;
+
+    // Enforce: is_right * (sibling - node) = left - node
+    cs.enforce(
+        || "left_selection",
+        |lc| lc + is_right.get_variable(),
+        |lc| lc + sibling.get_variable() - node.get_variable(),
+        |lc| lc + left.get_variable() - node.get_variable(),
+    );
+    // Enforce: left + right = node + sibling
+    cs.enforce(
+        || "right_selection",
+        |lc| lc + CS::one(),
+        |lc| lc + left.get_variable() + right.get_variable(),
+        |lc| lc + node.get_variable() + sibling.get_variable(),
+    );
+    Ok((left, right))
+}
+
+/// Proof of Reserves Circuit (BN254 version)
+///
+/// Folds one chunk of the liabilities tree into the total, and checks solvency of the running
+/// total, which is enough since the total only grows.
+///
+/// State: [liabilities_root, chunk_index, total_shares, nav_per_share, attested_assets]
+#[derive(Clone, Debug)]
+pub struct NovaReservesCircuit {
+    /// Depth of the liabilities tree
+    pub tree_depth: usize,
+    /// Leaves of the chunk (private input)
+    pub chunk: Vec<Liability>,
+    /// Siblings from the root of the chunk up to the root of the tree (private input)
+    pub siblings: Vec<MerkleSumNode>,
+}
+
+impl StepCircuit<Fr> for NovaReservesCircuit {
+    fn arity(&self) -> usize {
+        // State: [liabilities_root, chunk_index, total_shares, nav_per_share, attested_assets]
+        5
+    }
+
+    fn synthesize<CS: ConstraintSystem<Fr>>(
+        &self,
+        cs: &mut CS,
+        z_in: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+        let (root, chunk_index, total) = (&z_in[0], &z_in[1], &z_in[2]);
+        let (nav_per_share, attested_assets) = (&z_in[3], &z_in[4]);
+        let constants = PoseidonConstants::<Fr, U2>::new();
+        let chunk_depth = self.tree_depth - self.siblings.len();
+        if self.tree_depth > MAX_TREE_DEPTH || self.chunk.len() != 1 << chunk_depth {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        // Rebuild the subtree of the chunk from its leaves
+        let mut level = Vec::with_capacity(self.chunk.len());
+        for (i, liability) in self.chunk.iter().enumerate() {
+            let mut cs = cs.namespace(|| format!("leaf_{}", i));
+            let investor_id =
+                AllocatedNum::alloc(cs.namespace(|| "investor_id"), || Ok(liability.investor_id))?;
+            let shares =
+                AllocatedNum::alloc(cs.namespace(|| "shares"), || Ok(Fr::from(liability.shares)))?;
+            range_check(cs.namespace(|| "shares_range"), &shares, SHARES_BITS)?;
+            let hash = poseidon_hash_gadget(
+                cs.namespace(|| "hash"),
+                &[investor_id, shares.clone()],
+                &constants,
+            )?;
+            level.push(MerkleSumNodeVar { hash, sum: shares });
+        }
+        for depth in 0..chunk_depth {
+            level = level
+                .chunks(2)
+                .enumerate()
+                .map(|(i, pair)| {
+                    parent_gadget(
+                        cs.namespace(|| format!("chunk_node_{}_{}", depth, i)),
+                        &pair[0],
+                        &pair[1],
+                        &constants,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+        let chunk_root = level.pop().ok_or(SynthesisError::Unsatisfiable)?;
+
+        // Open the chunk at position chunk_index against the root of the tree
+        let index_bits = range_check(
+            cs.namespace(|| "chunk_index_bits"),
+            chunk_index,
+            self.siblings.len(),
+        )?;
+        let mut node = MerkleSumNodeVar {
+            hash: chunk_root.hash,
+            sum: chunk_root.sum.clone(),
+        };
+        for (i, (sibling, is_right)) in self.siblings.iter().zip(&index_bits).enumerate() {
+            let mut cs = cs.namespace(|| format!("path_{}", i));
+            let sibling_hash =
+                AllocatedNum::alloc(cs.namespace(|| "sibling_hash"), || Ok(sibling.hash))?;
+            let sibling_sum = AllocatedNum::alloc(cs.namespace(|| "sibling_sum"), || {
+                Ok(Fr::from_u128(sibling.sum))
+            })?;
+            range_check(cs.namespace(|| "sibling_sum_range"), &sibling_sum, SUM_BITS)?;
+
+            let (left_hash, right_hash) = conditional_swap(
+                cs.namespace(|| "hash_order"),
+                &node.hash,
+                &sibling_hash,
+                is_right,
+            )?;
+            let (left_sum, right_sum) = conditional_swap(
+                cs.namespace(|| "sum_order"),
+                &node.sum,
+                &sibling_sum,
+                is_right,
+            )?;
+            node = parent_gadget(
+                cs.namespace(|| "parent"),
+                &MerkleSumNodeVar {
+                    hash: left_hash,
+                    sum: left_sum,
+                },
+                &MerkleSumNodeVar {
+                    hash: right_hash,
+                    sum: right_sum,
+                },
+                &constants,
+            )?;
+        }
+        cs.enforce(
+            || "root_match",
+            |lc| lc + node.hash.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc + root.get_variable(),
+        );
+
+        // Add the chunk to the total
+        let new_total = total.add(cs.namespace(|| "total_update"), &chunk_root.sum)?;
+        range_check(cs.namespace(|| "total_range"), &new_total, SUM_BITS)?;
+
+        // Check: new_total * nav_per_share <= attested_assets
+        range_check(cs.namespace(|| "nav_range"), nav_per_share, NAV_BITS)?;
+        range_check(
+            cs.namespace(|| "assets_range"),
+            attested_assets,
+            ASSETS_BITS,
+        )?;
+        let liabilities = new_total.mul(cs.namespace(|| "liabilities"), nav_per_share)?;
+        let surplus = AllocatedNum::alloc(cs.namespace(|| "surplus"), || {
+            attested_assets
+                .get_value()
+                .zip(liabilities.get_value())
+                .map(|(assets, liabilities)| assets - liabilities)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+            || "solvency_check",
+            |lc| lc + attested_assets.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc + liabilities.get_variable() + surplus.get_variable(),
+        );
+        range_check(cs.namespace(|| "surplus_range"), &surplus, ASSETS_BITS)?;
+
+        // Increment chunk index, so that no chunk can be skipped
+        let new_chunk_index = increment(cs.namespace(|| "increment"), chunk_index)?;
+
+        Ok(vec![
+            root.clone(),
+            new_chunk_index,
+            new_total,
+            nav_per_share.clone(),
+            attested_assets.clone(),
+        ])
+    }
+
+    fn non_deterministic_advice(&self) -> Vec<Fr> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::enforce_increment;
+    use arecibo::frontend::test_cs::TestConstraintSystem;
+
+    const TREE_DEPTH: usize = 3;
+    const CHUNK_DEPTH: usize = 1;
+    const NAV_PER_SHARE: u64 = 105;
+
+    /// 6 investors holding 1000 shares in total, in a tree of 8 leaves
+    fn tree() -> LiabilitiesTree {
+        let shares = [100u64, 250, 50, 300, 200, 100];
+        let liabilities = shares
+            .iter()
+            .enumerate()
+            .map(|(i, &shares)| Liability {
+                investor_id: Fr::from(i as u64 + 1),
+                shares,
+            })
+            .collect();
+        LiabilitiesTree::new(liabilities, TREE_DEPTH)
+    }
+
+    /// Runs one step, returning whether it is satisfied together with the output state
+    fn run_step(circuit: &NovaReservesCircuit, z_in: &[Fr]) -> (bool, Vec<Fr>) {
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let z_in = z_in
+            .iter()
+            .enumerate()
+            .map(|(i, z)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("z_in_{}", i)), || Ok(*z)).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let z_out = circuit.synthesize(&mut cs, &z_in).unwrap();
+        (
+            cs.is_satisfied(),
+            z_out.iter().map(|z| z.get_value().unwrap()).collect(),
+        )
+    }
+
+    /// Folds all the chunks, returning whether every step is satisfied and the final state
+    fn fold(tree: &LiabilitiesTree, attested_assets: u128) -> (bool, Vec<Fr>) {
+        let mut z = tree.initial_state(NAV_PER_SHARE, attested_assets);
+        let mut all_satisfied = true;
+        for circuit in tree.step_circuits(CHUNK_DEPTH) {
+            let (satisfied, z_out) = run_step(&circuit, &z);
+            all_satisfied &= satisfied;
+            z = z_out;
+        }
+        (all_satisfied, z)
+    }
+
+    #[test]
+    fn test_inclusion_proofs() {
+        let tree = tree();
+        assert_eq!(tree.root().sum, 1000);
+        for index in 0..1 << TREE_DEPTH {
+            assert!(tree.inclusion_proof(index).verify(&tree.root()));
+        }
+
+        // An investor whose balance was understated detects it
+        let mut proof = tree.inclusion_proof(1);
+        proof.liability.shares = 260;
+        assert!(!proof.verify(&tree.root()));
+    }
+
+    #[test]
+    fn test_reserves_solvent() {
+        // Liabilities 1000 * 105 = 105_000 <= 110_000
+        let tree = tree();
+        let (satisfied, z_out) = fold(&tree, 110_000);
+        assert!(satisfied, "Constraints should be satisfied");
+        assert_eq!(
+            z_out,
+            vec![
+                tree.root().hash,
+                Fr::from(4u64),
+                Fr::from(1000u64),
+                Fr::from(NAV_PER_SHARE),
+                Fr::from(110_000u64),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reserves_insolvent() {
+        // Liabilities 105_000 > 100_000
+        let (satisfied, _) = fold(&tree(), 100_000);
+        assert!(
+            !satisfied,
+            "Circuit should fail when liabilities exceed assets"
+        );
+    }
+
+    #[test]
+    fn test_reserves_chunk_not_in_tree() {
+        // A chunk understating the shares of an investor doesn't open the root
+        let tree = tree();
+        let mut circuit = tree.step_circuits(CHUNK_DEPTH).remove(1);
+        circuit.chunk[1].shares = 0;
+        let mut z_in = tree.initial_state(NAV_PER_SHARE, 110_000);
+        z_in[1] = Fr::ONE;
+        let (satisfied, _) = run_step(&circuit, &z_in);
+        assert!(!satisfied, "Circuit should fail with a tampered chunk");
+    }
+
+    #[test]
+    fn test_reserves_chunk_skipped() {
+        // Each step moves to the next chunk
+        let tree = tree();
+        let z_in = tree.initial_state(NAV_PER_SHARE, 110_000);
+        let (satisfied, z_out) = run_step(&tree.step_circuits(CHUNK_DEPTH)[0], &z_in);
+        assert!(satisfied, "Constraints should be satisfied");
+        assert_eq!(z_out[1], Fr::ONE);
+
+        // An output state skipping chunk 1 is rejected
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let chunk_index =
+            AllocatedNum::alloc(cs.namespace(|| "chunk_index"), || Ok(z_in[1])).unwrap();
+        let skipped = AllocatedNum::alloc(cs.namespace(|| "new_chunk_index"), || {
+            Ok(z_in[1] + Fr::from(2u64))
+        })
+        .unwrap();
+        enforce_increment(cs.namespace(|| "increment"), &chunk_index, &skipped);
+        assert!(
+            !cs.is_satisfied(),
+            "Circuit should fail with a skipped chunk"
+        );
+    }
+}
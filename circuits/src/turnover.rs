@@ -0,0 +1,468 @@
+//! Turnover Circuit (BN254 version)
+//!
+//! Proves that each rebalance of the portfolio trades at most `max_turnover_bps` of the NAV:
+//! sum(|w_new - w_old|) <= max_turnover_bps, where the weights w are in basis points of the NAV.
+//!
+//! The weights are carried in the IVC state as a Poseidon commitment: each step opens the previous
+//! weights against the commitment left by the previous step, and commits to the new ones. Each
+//! commitment is blinded by a private random salt, since the weights range over a small domain that
+//! could otherwise be searched exhaustively from the public state. The
+//! turnover bucket of the rebalance (turnover / TURNOVER_BUCKET_BPS) is output publicly, together
+//! with the largest bucket over the folded rebalances.
+//!
+//! State: [holdings_commitment, rebalance_counter, turnover_bucket, max_turnover_bucket]
+
+use arecibo::{
+    frontend::{
+        gadgets::poseidon::PoseidonConstants, num::AllocatedNum, AllocatedBit, ConstraintSystem,
+        LinearCombination, SynthesisError,
+    },
+    nebula::rs::StepCircuit,
+};
+use ff::{Field, PrimeField};
+use generic_array::typenum::U2;
+use halo2curves::bn256::Fr;
+
+pub use crate::poseidon::{poseidon_hash, poseidon_hash_gadget};
+pub use crate::utils::range_check;
+
+use crate::utils::increment;
+
+/// Weights are expressed in basis points of the NAV, and sum to BASIS_POINTS
+pub const BASIS_POINTS: u64 = 10_000;
+
+/// Width of the public turnover buckets (5% of the NAV)
+pub const TURNOVER_BUCKET_BPS: u64 = 500;
+
+/// Number of bits of a weight, or of the difference of two weights
+const WEIGHT_BITS: usize = 14;
+
+/// Number of bits of a turnover, at most 2 * BASIS_POINTS
+const TURNOVER_BITS: usize = 16;
+
+/// Poseidon commitment to the weights of the holdings, blinded by `salt`: Poseidon(salt, weights)
+pub fn holdings_commitment(weights: &[u64], salt: Fr) -> Fr {
+    let inputs = std::iter::once(salt)
+        .chain(weights.iter().map(|w| Fr::from(*w)))
+        .collect::<Vec<_>>();
+    poseidon_hash(&inputs)
+}
+
+/// Turnover of a rebalance from `old_weights` to `new_weights`: sum(|w_new - w_old|)
+pub fn turnover(old_weights: &[u64], new_weights: &[u64]) -> u64 {
+    old_weights
+        .iter()
+        .zip(new_weights)
+        .map(|(old, new)| old.abs_diff(*new))
+        .sum()
+}
+
+/// Commit to allocated weights, matching `holdings_commitment`
+pub fn holdings_commitment_gadget<CS: ConstraintSystem<Fr>>(
+    cs: CS,
+    weights: &[AllocatedNum<Fr>],
+    salt: &AllocatedNum<Fr>,
+    constants: &PoseidonConstants<Fr, U2>,
+) -> Result<AllocatedNum<Fr>, SynthesisError> {
+    let inputs = std::iter::once(salt)
+        .chain(weights)
+        .cloned()
+        .collect::<Vec<_>>();
+    poseidon_hash_gadget(cs, &inputs, constants)
+}
+
+/// Signed difference of `a` and `b`, as a sign bit and a magnitude in [0, 2^num_bits):
+/// a - b = magnitude - 2 * is_negative * magnitude
+pub fn signed_difference<CS: ConstraintSystem<Fr>>(
+    mut cs: CS,
+    a: &AllocatedNum<Fr>,
+    b: &AllocatedNum<Fr>,
+    a_val: Option<u64>,
+    b_val: Option<u64>,
+    num_bits: usize,
+) -> Result<(AllocatedBit, AllocatedNum<Fr>), SynthesisError> {
+    let values = a_val.zip(b_val);
+    let is_negative =
+        AllocatedBit::alloc(cs.namespace(|| "is_negative"), values.map(|(a, b)| a < b))?;
+    let magnitude = AllocatedNum::alloc(cs.namespace(|| "magnitude"), || {
+        values
+            .map(|(a, b)| Fr::from(a.abs_diff(b)))
+            .ok_or(SynthesisError::AssignmentMissing)
+    })?;
+
+    // Enforce: is_negative * (2 * magnitude) = magnitude - (a - b)
+    cs.enforce(
+        || "signed_difference",
+        |lc| lc + is_negative.get_variable(),
+        |lc| lc + (Fr::from(2u64), magnitude.get_variable()),
+        |lc| lc + magnitude.get_variable() - a.get_variable() + b.get_variable(),
+    );
+    range_check(cs.namespace(|| "magnitude_range"), &magnitude, num_bits)?;
+
+    Ok((is_negative, magnitude))
+}
+
+/// Low 64 bits of a field element
+fn fr_to_u64(value: Fr) -> u64 {
+    let repr = value.to_repr();
+    u64::from_le_bytes(repr.as_ref()[..8].try_into().unwrap())
+}
+
+/// Turnover Circuit (BN254 version)
+///
+/// Proves that a rebalance from the committed weights to new weights stays within the turnover
+/// limit.
+///
+/// State: [holdings_commitment, rebalance_counter, turnover_bucket, max_turnover_bucket]
+#[derive(Clone, Debug)]
+pub struct NovaTurnoverCircuit {
+    /// Maximum turnover of a rebalance, in basis points (e.g., 2000 for 20%)
+    pub max_turnover_bps: u64,
+    /// Weights before the rebalance and their salt, opening the committed state (private input)
+    pub old_weights: Vec<u64>,
+    pub old_salt: Fr,
+    /// Weights after the rebalance and the fresh salt of their commitment (private input)
+    pub new_weights: Vec<u64>,
+    pub new_salt: Fr,
+}
+
+impl NovaTurnoverCircuit {
+    pub fn new(
+        max_turnover_bps: u64,
+        (old_weights, old_salt): (Vec<u64>, Fr),
+        (new_weights, new_salt): (Vec<u64>, Fr),
+    ) -> Self {
+        assert_eq!(
+            old_weights.len(),
+            new_weights.len(),
+            "Old and new weights must have the same length"
+        );
+        Self {
+            max_turnover_bps,
+            old_weights,
+            old_salt,
+            new_weights,
+            new_salt,
+        }
+    }
+}
+
+impl StepCircuit<Fr> for NovaTurnoverCircuit {
+    fn arity(&self) -> usize {
+        // State: [holdings_commitment, rebalance_counter, turnover_bucket, max_turnover_bucket]
+        4
+    }
+
+    fn synthesize<CS: ConstraintSystem<Fr>>(
+        &self,
+        cs: &mut CS,
+        z_in: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+        let (commitment, counter, max_bucket) = (&z_in[0], &z_in[1], &z_in[3]);
+        let constants = PoseidonConstants::<Fr, U2>::new();
+
+        // Allocate the weights before and after the rebalance
+        let alloc_weights = |cs: &mut CS, label: &str, weights: &[u64]| {
+            weights
+                .iter()
+                .enumerate()
+                .map(|(i, w)| {
+                    AllocatedNum::alloc(cs.namespace(|| format!("{}_{}", label, i)), || {
+                        Ok(Fr::from(*w))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let old_weights = alloc_weights(cs, "old_weight", &self.old_weights)?;
+        let new_weights = alloc_weights(cs, "new_weight", &self.new_weights)?;
+        let old_salt = AllocatedNum::alloc(cs.namespace(|| "old_salt"), || Ok(self.old_salt))?;
+        let new_salt = AllocatedNum::alloc(cs.namespace(|| "new_salt"), || Ok(self.new_salt))?;
+
+        // Enforce: the old weights open the commitment of the previous step
+        let old_commitment = holdings_commitment_gadget(
+            cs.namespace(|| "old_commitment"),
+            &old_weights,
+            &old_salt,
+            &constants,
+        )?;
+        cs.enforce(
+            || "old_commitment_match",
+            |lc| lc + old_commitment.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc + commitment.get_variable(),
+        );
+
+        // Enforce: the new weights are in range and sum to BASIS_POINTS
+        let mut weight_sum = LinearCombination::zero();
+        for (i, weight) in new_weights.iter().enumerate() {
+            range_check(
+                cs.namespace(|| format!("new_weight_{}_range", i)),
+                weight,
+                WEIGHT_BITS,
+            )?;
+            weight_sum = weight_sum + weight.get_variable();
+        }
+        cs.enforce(
+            || "weights_sum_to_basis_points",
+            |lc| lc + CS::one(),
+            |_| weight_sum,
+            |lc| lc + (Fr::from(BASIS_POINTS), CS::one()),
+        );
+
+        // Compute: turnover = sum(|w_new - w_old|)
+        let mut turnover_lc = LinearCombination::zero();
+        for (i, (old, new)) in old_weights.iter().zip(&new_weights).enumerate() {
+            let (_, magnitude) = signed_difference(
+                cs.namespace(|| format!("weight_{}_difference", i)),
+                new,
+                old,
+                Some(self.new_weights[i]),
+                Some(self.old_weights[i]),
+                WEIGHT_BITS,
+            )?;
+            turnover_lc = turnover_lc + magnitude.get_variable();
+        }
+        let turnover_value = turnover(&self.old_weights, &self.new_weights);
+        let turnover =
+            AllocatedNum::alloc(cs.namespace(|| "turnover"), || Ok(Fr::from(turnover_value)))?;
+        cs.enforce(
+            || "turnover_calculation",
+            |lc| lc + CS::one(),
+            |_| turnover_lc,
+            |lc| lc + turnover.get_variable(),
+        );
+
+        // Check: turnover <= max_turnover_bps
+        // Compute diff = max_turnover_bps - turnover (must be in range)
+        let diff = AllocatedNum::alloc(cs.namespace(|| "diff"), || {
+            Ok(Fr::from(self.max_turnover_bps) - Fr::from(turnover_value))
+        })?;
+        cs.enforce(
+            || "turnover_limit_check",
+            |lc| lc + (Fr::from(self.max_turnover_bps), CS::one()),
+            |lc| lc + CS::one(),
+            |lc| lc + turnover.get_variable() + diff.get_variable(),
+        );
+        range_check(cs.namespace(|| "diff_range"), &diff, TURNOVER_BITS)?;
+
+        // Compute: bucket = turnover / TURNOVER_BUCKET_BPS, with remainder < TURNOVER_BUCKET_BPS
+        let bucket = AllocatedNum::alloc(cs.namespace(|| "turnover_bucket"), || {
+            Ok(Fr::from(turnover_value / TURNOVER_BUCKET_BPS))
+        })?;
+        let remainder = AllocatedNum::alloc(cs.namespace(|| "remainder"), || {
+            Ok(Fr::from(turnover_value % TURNOVER_BUCKET_BPS))
+        })?;
+        cs.enforce(
+            || "bucket_calculation",
+            |lc| lc + bucket.get_variable(),
+            |lc| lc + (Fr::from(TURNOVER_BUCKET_BPS), CS::one()),
+            |lc| lc + turnover.get_variable() - remainder.get_variable(),
+        );
+        range_check(cs.namespace(|| "bucket_range"), &bucket, TURNOVER_BITS)?;
+        let remainder_slack = AllocatedNum::alloc(cs.namespace(|| "remainder_slack"), || {
+            Ok(Fr::from(
+                TURNOVER_BUCKET_BPS - 1 - turnover_value % TURNOVER_BUCKET_BPS,
+            ))
+        })?;
+        cs.enforce(
+            || "remainder_slack_calculation",
+            |lc| lc + (Fr::from(TURNOVER_BUCKET_BPS - 1), CS::one()),
+            |lc| lc + CS::one(),
+            |lc| lc + remainder.get_variable() + remainder_slack.get_variable(),
+        );
+        range_check(
+            cs.namespace(|| "remainder_slack_range"),
+            &remainder_slack,
+            TURNOVER_BITS,
+        )?;
+
+        // Compute: new_max_bucket = max(bucket, max_bucket)
+        let bucket_value = turnover_value / TURNOVER_BUCKET_BPS;
+        let max_bucket_value = max_bucket.get_value().map(fr_to_u64);
+        let (is_below_max, _) = signed_difference(
+            cs.namespace(|| "bucket_vs_max"),
+            &bucket,
+            max_bucket,
+            Some(bucket_value),
+            max_bucket_value,
+            TURNOVER_BITS,
+        )?;
+        let new_max_bucket = AllocatedNum::alloc(cs.namespace(|| "new_max_bucket"), || {
+            max_bucket_value
+                .map(|max| Fr::from(max.max(bucket_value)))
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+            || "new_max_bucket_selection",
+            |lc| lc + is_below_max.get_variable(),
+            |lc| lc + max_bucket.get_variable() - bucket.get_variable(),
+            |lc| lc + new_max_bucket.get_variable() - bucket.get_variable(),
+        );
+
+        // Commit to the new weights for the next step
+        let new_commitment = holdings_commitment_gadget(
+            cs.namespace(|| "new_commitment"),
+            &new_weights,
+            &new_salt,
+            &constants,
+        )?;
+
+        // Increment counter
+        let new_counter = increment(cs.namespace(|| "increment"), counter)?;
+
+        Ok(vec![new_commitment, new_counter, bucket, new_max_bucket])
+    }
+
+    fn non_deterministic_advice(&self) -> Vec<Fr> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::enforce_increment;
+    use arecibo::frontend::test_cs::TestConstraintSystem;
+
+    const OLD_WEIGHTS: [u64; 4] = [4000, 3000, 2000, 1000];
+
+    /// Salts of the commitments before and after the rebalance
+    const OLD_SALT: u64 = 0x5a17;
+    const NEW_SALT: u64 = 0xb10d;
+
+    fn old_holdings() -> (Vec<u64>, Fr) {
+        (OLD_WEIGHTS.to_vec(), Fr::from(OLD_SALT))
+    }
+
+    fn new_holdings(weights: Vec<u64>) -> (Vec<u64>, Fr) {
+        (weights, Fr::from(NEW_SALT))
+    }
+
+    /// Runs one step from the state [commitment, counter, bucket, max_bucket], returning whether
+    /// it is satisfied together with the output state
+    fn run_step(circuit: &NovaTurnoverCircuit, z_in: [Fr; 4]) -> (bool, Vec<Fr>) {
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let z_in = z_in
+            .iter()
+            .enumerate()
+            .map(|(i, z)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("z_in_{}", i)), || Ok(*z)).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let z_out = circuit.synthesize(&mut cs, &z_in).unwrap();
+        (
+            cs.is_satisfied(),
+            z_out.iter().map(|z| z.get_value().unwrap()).collect(),
+        )
+    }
+
+    fn initial_state() -> [Fr; 4] {
+        [
+            holdings_commitment(&OLD_WEIGHTS, Fr::from(OLD_SALT)),
+            Fr::ZERO,
+            Fr::ZERO,
+            Fr::ZERO,
+        ]
+    }
+
+    #[test]
+    fn test_turnover_within_limit() {
+        // Sell 5% of asset 0, buy 5% of asset 2: turnover 10%
+        let new_weights = vec![3500, 3000, 2500, 1000];
+        let circuit =
+            NovaTurnoverCircuit::new(2000, old_holdings(), new_holdings(new_weights.clone()));
+
+        let (satisfied, z_out) = run_step(&circuit, initial_state());
+        assert!(satisfied, "Constraints should be satisfied");
+        assert_eq!(
+            z_out,
+            vec![
+                holdings_commitment(&new_weights, Fr::from(NEW_SALT)),
+                Fr::ONE,
+                Fr::from(2u64),
+                Fr::from(2u64),
+            ]
+        );
+
+        // A smaller rebalance afterwards keeps the largest bucket
+        let circuit = NovaTurnoverCircuit::new(
+            2000,
+            new_holdings(new_weights),
+            (vec![3400, 3000, 2600, 1000], Fr::from(0xc0deu64)),
+        );
+        let (satisfied, z_out) = run_step(&circuit, z_out.try_into().unwrap());
+        assert!(satisfied, "Constraints should be satisfied");
+        assert_eq!(z_out[2..], [Fr::ZERO, Fr::from(2u64)]);
+    }
+
+    #[test]
+    fn test_turnover_exceeds_limit() {
+        // Turnover 30% > 20%
+        let circuit = NovaTurnoverCircuit::new(
+            2000,
+            old_holdings(),
+            new_holdings(vec![2500, 3000, 3500, 1000]),
+        );
+        let (satisfied, _) = run_step(&circuit, initial_state());
+        assert!(!satisfied, "Circuit should fail with excessive turnover");
+    }
+
+    #[test]
+    fn test_turnover_wrong_previous_weights() {
+        // The previous weights don't open the committed state
+        let circuit = NovaTurnoverCircuit::new(
+            2000,
+            (vec![3500, 3000, 2500, 1000], Fr::from(OLD_SALT)),
+            new_holdings(vec![3500, 3000, 2500, 1000]),
+        );
+        let (satisfied, _) = run_step(&circuit, initial_state());
+        assert!(!satisfied, "Circuit should fail with uncommitted weights");
+    }
+
+    #[test]
+    fn test_turnover_wrong_salt() {
+        // The committed weights, opened with another salt
+        let circuit = NovaTurnoverCircuit::new(
+            2000,
+            (OLD_WEIGHTS.to_vec(), Fr::from(OLD_SALT + 1)),
+            new_holdings(vec![3500, 3000, 2500, 1000]),
+        );
+        let (satisfied, _) = run_step(&circuit, initial_state());
+        assert!(!satisfied, "Circuit should fail with the wrong salt");
+
+        // The same weights are committed to differently under fresh salts
+        assert_ne!(
+            holdings_commitment(&OLD_WEIGHTS, Fr::from(OLD_SALT)),
+            holdings_commitment(&OLD_WEIGHTS, Fr::from(NEW_SALT))
+        );
+    }
+
+    #[test]
+    fn test_turnover_counter_increment() {
+        // The counter only advances by one rebalance per step
+        for (new_counter, expected) in [(1u64, true), (2, false), (0, false)] {
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            let counter = AllocatedNum::alloc(cs.namespace(|| "counter"), || Ok(Fr::ZERO)).unwrap();
+            let new_counter =
+                AllocatedNum::alloc(cs.namespace(|| "new_counter"), || Ok(Fr::from(new_counter)))
+                    .unwrap();
+            enforce_increment(cs.namespace(|| "increment"), &counter, &new_counter);
+            assert_eq!(cs.is_satisfied(), expected);
+        }
+    }
+
+    #[test]
+    fn test_turnover_weights_not_normalized() {
+        // The new weights sum to 90%
+        let circuit = NovaTurnoverCircuit::new(
+            2000,
+            old_holdings(),
+            new_holdings(vec![3000, 3000, 2000, 1000]),
+        );
+        let (satisfied, _) = run_step(&circuit, initial_state());
+        assert!(
+            !satisfied,
+            "Circuit should fail when weights don't sum to 100%"
+        );
+    }
+}
@@ -1,7 +1,16 @@
 //! Utility functions for circuit operations
 
-use bellpepper_core::{ConstraintSystem, SynthesisError, Variable};
-use ff::PrimeField;
+use arecibo::frontend::{self, gadgets::poseidon::PoseidonConstants, num::AllocatedNum, Index};
+use bellpepper_core::{ConstraintSystem, LinearCombination, SynthesisError, Variable};
+use ff::{Field, PrimeField};
+use generic_array::typenum::U2;
+use halo2curves::bn256::Fr;
+
+use crate::poseidon::poseidon_hash_gadget;
+use crate::range_proof;
+
+/// Number of bits of the percentages of the policies
+pub(crate) const PERCENTAGE_BITS: usize = 16;
 
 /// Allocate a variable representing a value that might not be known yet
 pub fn alloc_option<F, CS>(
@@ -20,10 +29,379 @@ where
     Ok(var)
 }
 
+/// Allocated value, together with its assignment
+#[derive(Clone, Copy)]
+pub(crate) struct Num {
+    pub var: Variable,
+    pub value: Option<u128>,
+}
+
+pub(crate) fn alloc_num<F, CS>(mut cs: CS, value: Option<u128>) -> Result<Num, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let var = cs.alloc(
+        || "value",
+        || {
+            value
+                .map(F::from_u128)
+                .ok_or(SynthesisError::AssignmentMissing)
+        },
+    )?;
+    Ok(Num { var, value })
+}
+
+/// Allocate a percentage of the policy as a public input
+pub(crate) fn alloc_percentage<F, CS>(mut cs: CS, value: Option<u64>) -> Result<Num, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let value = value.map(u128::from);
+    let var = cs.alloc_input(
+        || "percentage",
+        || {
+            value
+                .map(F::from_u128)
+                .ok_or(SynthesisError::AssignmentMissing)
+        },
+    )?;
+    range_proof::decompose_allocated_value(
+        cs.namespace(|| "range_proof"),
+        var,
+        value.map(F::from_u128),
+        PERCENTAGE_BITS,
+        "percentage_bits",
+    )?;
+    Ok(Num { var, value })
+}
+
+/// Allocate the one-hot encoding of `index` over `len` slots: exactly one bit is set, at `index`
+pub(crate) fn alloc_one_hot<F, CS>(
+    mut cs: CS,
+    index: &Num,
+    len: usize,
+) -> Result<Vec<(Variable, Option<bool>)>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let mut bits = Vec::with_capacity(len);
+    let mut sum = LinearCombination::zero();
+    let mut weighted_sum = LinearCombination::zero();
+    for j in 0..len {
+        let bit_val = index.value.map(|i| i == j as u128);
+        let bit =
+            range_proof::alloc_boolean(cs.namespace(|| format!("bit_{}", j)), bit_val, "bit")?;
+        sum = sum + bit;
+        weighted_sum = weighted_sum + (F::from(j as u64), bit);
+        bits.push((bit, bit_val));
+    }
+    cs.enforce(
+        || "one_hot_sum",
+        |lc| lc + CS::one(),
+        |lc| lc + CS::one(),
+        |_| sum,
+    );
+    cs.enforce(
+        || "one_hot_index",
+        |lc| lc + CS::one(),
+        |lc| lc + index.var,
+        |_| weighted_sum,
+    );
+    Ok(bits)
+}
+
+/// Allocate percentage * total
+pub(crate) fn mul_total<F, CS>(
+    mut cs: CS,
+    percentage: &Num,
+    total: &LinearCombination<F>,
+    total_value: Option<u128>,
+) -> Result<Num, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let product = alloc_num(
+        cs.namespace(|| "product"),
+        percentage.value.zip(total_value).map(|(p, t)| p * t),
+    )?;
+    cs.enforce(
+        || "product_constraint",
+        |lc| lc + percentage.var,
+        |_| total.clone(),
+        |lc| lc + product.var,
+    );
+    Ok(product)
+}
+
+/// Prove that a <= b for linear combinations, by proving (b - a) is in valid range
+pub(crate) fn enforce_le<F, CS>(
+    mut cs: CS,
+    a: LinearCombination<F>,
+    b: LinearCombination<F>,
+    a_val: Option<u128>,
+    b_val: Option<u128>,
+    num_bits: usize,
+) -> Result<(), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let diff_val = a_val
+        .zip(b_val)
+        .map(|(a, b)| F::from_u128(b) - F::from_u128(a));
+    let diff = cs.alloc(
+        || "difference",
+        || diff_val.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+
+    // Enforce: diff = b - a
+    cs.enforce(
+        || "diff_equals_b_minus_a",
+        |lc| lc + CS::one(),
+        |lc| lc + diff,
+        |_| b - &a,
+    );
+
+    range_proof::decompose_allocated_value(
+        cs.namespace(|| "range_proof"),
+        diff,
+        diff_val,
+        num_bits,
+        "diff_bits",
+    )?;
+    Ok(())
+}
+
+/// Prove that `num` is in [0, 2^num_bits) by decomposing it into bits, returning the bits in
+/// little-endian order. This is the arecibo counterpart of `range_proof::decompose_allocated_value`,
+/// for the BN254 circuits.
+pub fn range_check<CS: frontend::ConstraintSystem<Fr>>(
+    mut cs: CS,
+    num: &AllocatedNum<Fr>,
+    num_bits: usize,
+) -> Result<Vec<frontend::AllocatedBit>, frontend::SynthesisError> {
+    let repr = num.get_value().map(|v| v.to_repr());
+    let mut bits = Vec::with_capacity(num_bits);
+    let mut recomposition = frontend::LinearCombination::zero();
+    let mut coeff = Fr::ONE;
+    for i in 0..num_bits {
+        let bit = frontend::AllocatedBit::alloc(
+            cs.namespace(|| format!("bit_{}", i)),
+            repr.as_ref()
+                .map(|r| (r.as_ref()[i / 8] >> (i % 8)) & 1 == 1),
+        )?;
+        recomposition = recomposition + (coeff, bit.get_variable());
+        coeff = coeff.double();
+        bits.push(bit);
+    }
+
+    // Enforce: sum(bit_i * 2^i) = num
+    cs.enforce(
+        || "recomposition",
+        |lc| lc + CS::one(),
+        |lc| lc + num.get_variable(),
+        |_| recomposition,
+    );
+    Ok(bits)
+}
+
+/// Allocate `num + 1`, incremented by the constant one so that a step counter cannot be advanced
+/// by more than one step at a time
+pub fn increment<CS: frontend::ConstraintSystem<Fr>>(
+    mut cs: CS,
+    num: &AllocatedNum<Fr>,
+) -> Result<AllocatedNum<Fr>, frontend::SynthesisError> {
+    let incremented = AllocatedNum::alloc(cs.namespace(|| "incremented"), || {
+        num.get_value()
+            .map(|num| num + Fr::ONE)
+            .ok_or(frontend::SynthesisError::AssignmentMissing)
+    })?;
+    enforce_increment(cs, num, &incremented);
+    Ok(incremented)
+}
+
+/// Enforce `incremented = num + 1`
+pub(crate) fn enforce_increment<CS: frontend::ConstraintSystem<Fr>>(
+    mut cs: CS,
+    num: &AllocatedNum<Fr>,
+    incremented: &AllocatedNum<Fr>,
+) {
+    cs.enforce(
+        || "increment",
+        |lc| lc + num.get_variable() + CS::one(),
+        |lc| lc + CS::one(),
+        |lc| lc + incremented.get_variable(),
+    );
+}
+
+/// arecibo constraint system recording the assignment of its variables and its constraints, so
+/// that the arecibo gadgets can be replayed into a bellpepper constraint system
+struct RecordingCS<F: PrimeField> {
+    aux: Vec<F>,
+    constraints: Vec<[frontend::LinearCombination<F>; 3]>,
+}
+
+impl<F: PrimeField> frontend::ConstraintSystem<F> for RecordingCS<F> {
+    type Root = Self;
+
+    fn alloc<V, A, AR>(
+        &mut self,
+        _: A,
+        f: V,
+    ) -> Result<frontend::Variable, frontend::SynthesisError>
+    where
+        V: FnOnce() -> Result<F, frontend::SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.aux.push(f()?);
+        Ok(frontend::Variable::new_unchecked(Index::Aux(
+            self.aux.len() - 1,
+        )))
+    }
+
+    fn alloc_precommitted<V, A, AR>(
+        &mut self,
+        _: A,
+        _: V,
+        _: frontend::Split,
+    ) -> Result<frontend::Variable, frontend::SynthesisError>
+    where
+        V: FnOnce() -> Result<F, frontend::SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Err(frontend::SynthesisError::Unsatisfiable)
+    }
+
+    fn alloc_input<V, A, AR>(
+        &mut self,
+        _: A,
+        _: V,
+    ) -> Result<frontend::Variable, frontend::SynthesisError>
+    where
+        V: FnOnce() -> Result<F, frontend::SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Err(frontend::SynthesisError::Unsatisfiable)
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(frontend::LinearCombination<F>) -> frontend::LinearCombination<F>,
+        LB: FnOnce(frontend::LinearCombination<F>) -> frontend::LinearCombination<F>,
+        LC: FnOnce(frontend::LinearCombination<F>) -> frontend::LinearCombination<F>,
+    {
+        self.constraints.push([
+            a(frontend::LinearCombination::zero()),
+            b(frontend::LinearCombination::zero()),
+            c(frontend::LinearCombination::zero()),
+        ]);
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+/// Translate a recorded linear combination, whose auxiliary variable i is `vars[i]`
+fn replay_lc<F, CS>(
+    recorded: &frontend::LinearCombination<F>,
+    vars: &[Variable],
+) -> LinearCombination<F>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    recorded
+        .iter()
+        .fold(LinearCombination::zero(), |lc, (var, coeff)| {
+            match var.get_unchecked() {
+                Index::Input(0) => lc + (*coeff, CS::one()),
+                Index::Aux(i) => lc + (*coeff, vars[i]),
+                index => unreachable!("variable {:?} is not allocated by the recorder", index),
+            }
+        })
+}
+
+/// Poseidon hash of allocated inputs, matching `poseidon::poseidon_hash`
+///
+/// The constraints of `poseidon::poseidon_hash_gadget` are recorded on an arecibo constraint system
+/// and replayed into `cs`, with the recorded inputs bound to `inputs`. The arecibo sponge needs the
+/// values of its inputs, so that zeros stand in for them during setup: the constraints of the
+/// permutation do not depend on the values.
+pub(crate) fn poseidon_hash<F, CS>(
+    mut cs: CS,
+    inputs: &[(Variable, Option<F>)],
+    constants: &PoseidonConstants<F, U2>,
+) -> Result<(Variable, Option<F>), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let known = inputs.iter().all(|(_, value)| value.is_some());
+    let mut recorder = RecordingCS {
+        aux: Vec::new(),
+        constraints: Vec::new(),
+    };
+    let recorded = (|| {
+        let nums = inputs
+            .iter()
+            .map(|(_, value)| AllocatedNum::alloc(&mut recorder, || Ok(value.unwrap_or(F::ZERO))))
+            .collect::<Result<Vec<_>, _>>()?;
+        poseidon_hash_gadget(&mut recorder, &nums, constants)
+    })()
+    .map_err(|_| SynthesisError::Unsatisfiable)?;
+
+    // The recorded inputs are the given variables, the other variables are allocated in `cs`
+    let mut vars = inputs.iter().map(|(var, _)| *var).collect::<Vec<_>>();
+    for (i, value) in recorder.aux.iter().enumerate().skip(inputs.len()) {
+        vars.push(cs.alloc(
+            || format!("aux_{}", i),
+            || {
+                known
+                    .then_some(*value)
+                    .ok_or(SynthesisError::AssignmentMissing)
+            },
+        )?);
+    }
+    for (i, [a, b, c]) in recorder.constraints.iter().enumerate() {
+        cs.enforce(
+            || format!("constraint_{}", i),
+            |_| replay_lc::<F, CS>(a, &vars),
+            |_| replay_lc::<F, CS>(b, &vars),
+            |_| replay_lc::<F, CS>(c, &vars),
+        );
+    }
+
+    let hash = match recorded.get_variable().get_unchecked() {
+        Index::Aux(i) => vars[i],
+        _ => return Err(SynthesisError::Unsatisfiable),
+    };
+    Ok((hash, recorded.get_value().filter(|_| known)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use bellpepper_core::test_cs::TestConstraintSystem;
+    use ff::Field;
     use pasta_curves::Fp;
 
     #[test]
@@ -33,4 +411,37 @@ mod tests {
         let var = alloc_option(cs.namespace(|| "test"), value, "test_var").unwrap();
         assert!(cs.is_satisfied());
     }
+
+    /// Allocate the inputs and their hash, enforcing that the hash equals `expected`
+    fn hash_equals(inputs: &[Fp], expected: Fp) -> TestConstraintSystem<Fp> {
+        let constants = PoseidonConstants::<Fp, U2>::new();
+        let mut cs = TestConstraintSystem::<Fp>::new();
+        let expected = cs.alloc_input(|| "expected", || Ok(expected)).unwrap();
+        let inputs = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let x = Some(*x);
+                let var = alloc_option(cs.namespace(|| format!("input_{}", i)), x, "x").unwrap();
+                (var, x)
+            })
+            .collect::<Vec<_>>();
+        let (hash, _) = poseidon_hash(cs.namespace(|| "hash"), &inputs, &constants).unwrap();
+        cs.enforce(
+            || "hash_constraint",
+            |lc| lc + TestConstraintSystem::<Fp>::one(),
+            |lc| lc + hash,
+            |lc| lc + expected,
+        );
+        cs
+    }
+
+    #[test]
+    fn test_poseidon_hash() {
+        let inputs = [Fp::from(7u64), Fp::from(11u64), Fp::from(13u64)];
+        let expected = crate::poseidon::poseidon_hash(&inputs);
+
+        assert!(hash_equals(&inputs, expected).is_satisfied());
+        assert!(!hash_equals(&inputs, expected + Fp::ONE).is_satisfied());
+    }
 }
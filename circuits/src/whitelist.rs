@@ -12,13 +12,21 @@
 //! - path_indices: Left/right indicators for path (0 = left, 1 = right)
 //!
 //! Constraints:
-//! - Recompute Merkle root from asset_hash and path
+//! - Recompute Merkle root from asset_hash and path, ordering each pair of nodes by the
+//!   path index bit
 //! - Computed root must equal public merkle_root
 
-use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use arecibo::frontend::gadgets::poseidon::PoseidonConstants;
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError, Variable};
 use ff::PrimeField;
+use generic_array::typenum::U2;
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "poseidon")]
+use crate::poseidon;
+use crate::range_proof;
+use crate::utils::poseidon_hash;
+
 /// Maximum depth of Merkle tree (supports up to 2^20 = 1M assets)
 pub const MAX_MERKLE_DEPTH: usize = 20;
 
@@ -72,16 +80,16 @@ impl<F: PrimeField> WhitelistCircuit<F> {
 
 // Merkle hash function used by the circuit
 // Default (no feature): simple addition-based hash for demo/testing
-// Feature `poseidon`: placeholder for Poseidon gadget wiring
+// Feature `poseidon`: Poseidon hash of the two nodes, see `compute_poseidon_merkle_root`
 
 #[cfg(not(feature = "poseidon"))]
-fn merkle_hash<F: PrimeField, CS: ConstraintSystem<F>>(
+pub(crate) fn merkle_hash<F: PrimeField, CS: ConstraintSystem<F>>(
     mut cs: CS,
-    left: bellpepper_core::Variable,
-    right: bellpepper_core::Variable,
+    left: Variable,
+    right: Variable,
     left_val: Option<F>,
     right_val: Option<F>,
-) -> Result<bellpepper_core::Variable, SynthesisError> {
+) -> Result<Variable, SynthesisError> {
     let hash = cs.alloc(
         || "merkle_hash_addition",
         || {
@@ -101,17 +109,139 @@ fn merkle_hash<F: PrimeField, CS: ConstraintSystem<F>>(
     Ok(hash)
 }
 
+/// Native counterpart of `merkle_hash`
+#[cfg(not(feature = "poseidon"))]
+pub(crate) fn merkle_hash_value<F: PrimeField>(left: F, right: F) -> F {
+    left + right
+}
+
+/// Native counterpart of `merkle_hash`
 #[cfg(feature = "poseidon")]
-fn merkle_hash<F: PrimeField, CS: ConstraintSystem<F>>(
-    _cs: CS,
-    _left: bellpepper_core::Variable,
-    _right: bellpepper_core::Variable,
-    _left_val: Option<F>,
-    _right_val: Option<F>,
-) -> Result<bellpepper_core::Variable, SynthesisError> {
-    // Placeholder: Poseidon gadget not wired in this crate yet.
-    // Implement using a bellpepper-compatible Poseidon gadget or bridge.
-    Err(SynthesisError::Unsatisfiable)
+pub(crate) fn merkle_hash_value<F: PrimeField>(left: F, right: F) -> F {
+    poseidon::poseidon_hash(&[left, right])
+}
+
+/// Order the current node and its sibling as (left, right) by the path index: the sibling is on
+/// the left when `is_right` is set. The index is a constrained bit, so that the constraints do not
+/// depend on the witness.
+#[allow(clippy::type_complexity)]
+fn order_nodes<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    current: (Variable, Option<F>),
+    sibling: (Variable, Option<F>),
+    is_right: Option<bool>,
+) -> Result<((Variable, Option<F>), (Variable, Option<F>)), SynthesisError> {
+    let bit = range_proof::alloc_boolean(cs.namespace(|| "is_right"), is_right, "bit")?;
+    let (left_val, right_val) = match (current.1, sibling.1, is_right) {
+        (Some(cur), Some(sib), Some(true)) => (Some(sib), Some(cur)),
+        (Some(cur), Some(sib), Some(false)) => (Some(cur), Some(sib)),
+        _ => (None, None),
+    };
+    let left = cs.alloc(
+        || "left",
+        || left_val.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    let right = cs.alloc(
+        || "right",
+        || right_val.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+
+    // Enforce: left = current + is_right * (sibling - current)
+    cs.enforce(
+        || "left_constraint",
+        |lc| lc + bit,
+        |lc| lc + sibling.0 - current.0,
+        |lc| lc + left - current.0,
+    );
+    // Enforce: left + right = current + sibling
+    cs.enforce(
+        || "right_constraint",
+        |lc| lc + CS::one(),
+        |lc| lc + left + right,
+        |lc| lc + current.0 + sibling.0,
+    );
+
+    Ok(((left, left_val), (right, right_val)))
+}
+
+/// Allocate the sibling at `level` of a Merkle path
+fn alloc_sibling<F: PrimeField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    level: usize,
+    sibling: Option<F>,
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    let var = cs.alloc(
+        || format!("sibling_{}", level),
+        || sibling.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    Ok((var, sibling))
+}
+
+/// Recompute the Merkle root from an allocated leaf and the path to the root, returning the
+/// root variable and its value
+#[cfg(not(feature = "poseidon"))]
+pub(crate) fn compute_merkle_root<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    leaf: Variable,
+    leaf_val: Option<F>,
+    merkle_path: &[Option<F>],
+    path_indices: &[Option<bool>],
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    let mut current = (leaf, leaf_val);
+
+    for (i, (sibling, is_right)) in merkle_path.iter().zip(path_indices.iter()).enumerate() {
+        let sibling = alloc_sibling(&mut cs, i, *sibling)?;
+        let (left, right) = order_nodes(
+            cs.namespace(|| format!("order_{}", i)),
+            current,
+            sibling,
+            *is_right,
+        )?;
+
+        // Compute parent hash
+        let hash = merkle_hash(
+            cs.namespace(|| format!("hash_{}", i)),
+            left.0,
+            right.0,
+            left.1,
+            right.1,
+        )?;
+        current = (
+            hash,
+            left.1.zip(right.1).map(|(l, r)| merkle_hash_value(l, r)),
+        );
+    }
+
+    Ok(current)
+}
+
+/// Recompute the root of a Merkle tree hashed with Poseidon, whatever the `poseidon` feature,
+/// from an allocated leaf and the path to the root
+pub(crate) fn compute_poseidon_merkle_root<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    leaf: (Variable, Option<F>),
+    merkle_path: &[Option<F>],
+    path_indices: &[Option<bool>],
+    constants: &PoseidonConstants<F, U2>,
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    let mut current = leaf;
+
+    for (i, (sibling, is_right)) in merkle_path.iter().zip(path_indices.iter()).enumerate() {
+        let sibling = alloc_sibling(&mut cs, i, *sibling)?;
+        let (left, right) = order_nodes(
+            cs.namespace(|| format!("order_{}", i)),
+            current,
+            sibling,
+            *is_right,
+        )?;
+        current = poseidon_hash(
+            cs.namespace(|| format!("hash_{}", i)),
+            &[left, right],
+            constants,
+        )?;
+    }
+
+    Ok(current)
 }
 
 impl<F: PrimeField> Circuit<F> for WhitelistCircuit<F> {
@@ -129,55 +259,28 @@ impl<F: PrimeField> Circuit<F> for WhitelistCircuit<F> {
         )?;
 
         // Allocate private input: asset_hash (leaf)
-        let mut current_hash = cs.alloc(
+        let current_hash = cs.alloc(
             || "asset_hash",
             || self.asset_hash.ok_or(SynthesisError::AssignmentMissing),
         )?;
-        let mut current_val = self.asset_hash;
 
         // Traverse up the Merkle tree
-        for (i, (sibling_opt, index_opt)) in self
-            .merkle_path
-            .iter()
-            .zip(self.path_indices.iter())
-            .enumerate()
-        {
-            // Allocate sibling hash
-            let sibling = cs.alloc(
-                || format!("sibling_{}", i),
-                || sibling_opt.ok_or(SynthesisError::AssignmentMissing),
-            )?;
-
-            // Compute left and right directly based on index
-            // No complex conditional constraints - just compute both orderings
-            let (left_val, right_val, left_var, right_var) =
-                match (current_val, *sibling_opt, *index_opt) {
-                    (Some(cur), Some(sib), Some(is_right)) => {
-                        if is_right {
-                            // Current is on the right
-                            (Some(sib), Some(cur), sibling, current_hash)
-                        } else {
-                            // Current is on the left
-                            (Some(cur), Some(sib), current_hash, sibling)
-                        }
-                    }
-                    _ => (None, None, current_hash, sibling), // Placeholder
-                };
-
-            // Compute parent hash
-            current_hash = merkle_hash(
-                cs.namespace(|| format!("hash_{}", i)),
-                left_var,
-                right_var,
-                left_val,
-                right_val,
-            )?;
-
-            current_val = match (left_val, right_val) {
-                (Some(l), Some(r)) => Some(l + r),
-                _ => None,
-            };
-        }
+        #[cfg(not(feature = "poseidon"))]
+        let (current_hash, _) = compute_merkle_root(
+            cs.namespace(|| "merkle_path"),
+            current_hash,
+            self.asset_hash,
+            &self.merkle_path,
+            &self.path_indices,
+        )?;
+        #[cfg(feature = "poseidon")]
+        let (current_hash, _) = compute_poseidon_merkle_root(
+            cs.namespace(|| "merkle_path"),
+            (current_hash, self.asset_hash),
+            &self.merkle_path,
+            &self.path_indices,
+            &PoseidonConstants::<F, U2>::new(),
+        )?;
 
         // Enforce: computed root equals public merkle_root
         cs.enforce(
@@ -231,7 +334,7 @@ mod tests {
                 } else {
                     Fp::ZERO
                 };
-                next_level.push(left + right); // Simple hash
+                next_level.push(merkle_hash_value(left, right));
             }
 
             current_index /= 2;
@@ -3,6 +3,7 @@ pragma solidity ^0.8.20;
 
 import "forge-std/Script.sol";
 import "../src/TokenizedFundManager.sol";
+import "../src/Poseidon.sol";
 
 contract DeployFundManager is Script {
     function run() external {
@@ -15,19 +16,30 @@ contract DeployFundManager is Script {
         // Example whitelist root (mock for now)
         bytes32 whitelistRoot = keccak256("arc_approved_assets_v1");
 
-        // NovaDecider verifier address (single folded proof)
-        address novaVerifier = vm.envOr(
-            "NOVA_VERIFIER_ADDRESS",
-            address(0x076E915833620074669Eccd70aD8836EfA143A7B)
-        );
+        // NovaDecider verifier of the reported circuit (5-element state, uint256[36] proofs), as
+        // generated by the fund_compliance_full_flow example
+        address novaVerifier = vm.envAddress("NOVA_VERIFIER_ADDRESS");
+
+        // Poseidon hasher over BN254 with the canonical sponge configuration of the circuits,
+        // deployed below if not given
+        address poseidon = vm.envOr("POSEIDON_ADDRESS", address(0));
+
+        // Account of the pricing oracle, publishing the commitments to the prices it signs
+        address pricingOracle = vm.envOr("PRICING_ORACLE", vm.addr(deployerPrivateKey));
 
         vm.startBroadcast(deployerPrivateKey);
 
+        if (poseidon == address(0)) {
+            poseidon = address(new Poseidon());
+        }
+
         // Deploy TokenizedFundManager
         TokenizedFundManager fundManager = new TokenizedFundManager(
             initialAgent,
             whitelistRoot,
-            novaVerifier
+            novaVerifier,
+            poseidon,
+            pricingOracle
         );
 
         console.log("===========================================");
@@ -41,6 +53,8 @@ contract DeployFundManager is Script {
         console.log("Min liquidity:", fundManager.MIN_LIQUIDITY(), "%");
         console.log("Whitelist root:", vm.toString(whitelistRoot));
         console.log("Nova verifier:", address(fundManager.novaVerifier()));
+        console.log("Poseidon:", address(fundManager.poseidon()));
+        console.log("Pricing oracle:", fundManager.pricingOracle());
         console.log("===========================================");
 
         vm.stopBroadcast();
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {IPoseidon} from "./TokenizedFundManager.sol";
+
+/// @title Credential Decider Interface
+/// @notice Interface for the deployed NovaDecider verifier of the credential circuit, whose
+/// state has 4 elements
+interface ICredentialDecider {
+    function verifyOpaqueNovaProof(uint256[34] calldata proof) external view returns (bool);
+}
+
+/// @title Investor Registry
+/// @notice Publishes the root of the registry of investor credentials and checks the folded
+/// credential proofs of the transfer batches against it
+/// @dev The credential circuit folds one receiver per step over the state
+/// [now, registryRoot, transfersHash, transfers], chaining the receivers into
+/// transfersHash = Poseidon(transfersHash, receiver). The registry recomputes that chain from the
+/// receivers of the batch, so a proof only covers the transfers it is checked with
+contract InvestorRegistry {
+    /*//////////////////////////////////////////////////////////////
+                                 ERRORS
+    //////////////////////////////////////////////////////////////*/
+
+    error Unauthorized();
+    error InvalidProof();
+    error ProofVerificationFailed();
+    error RegistryMismatch(uint256 registryRoot);
+    error StaleProof(uint256 provenAt);
+    error ReceiversMismatch(uint256 transfersHash);
+
+    /*//////////////////////////////////////////////////////////////
+                                 EVENTS
+    //////////////////////////////////////////////////////////////*/
+
+    event RegistryRootUpdated(uint256 indexed registryRoot);
+
+    /*//////////////////////////////////////////////////////////////
+                                STORAGE
+    //////////////////////////////////////////////////////////////*/
+
+    /// Layout of the folded proof (uint256[34]): i, z_0[4], z_i[4], then the decider proof
+    uint256 private constant NOVA_PROOF_WORDS = 34;
+    uint256 private constant STATE_LEN = 4;
+    uint256 private constant Z_0_OFFSET = 1;
+    uint256 private constant Z_I_OFFSET = Z_0_OFFSET + STATE_LEN;
+
+    /// Maximum age of the time at which the credentials were checked
+    uint256 public constant MAX_PROOF_AGE = 1 days;
+
+    /// Registry admin, registering the investors
+    address public immutable admin;
+
+    /// NovaDecider verifier of the credential circuit
+    ICredentialDecider public immutable credentialVerifier;
+
+    /// Poseidon hasher, to chain the receivers of a batch
+    IPoseidon public immutable poseidon;
+
+    /// Root of the Poseidon Merkle tree of the registered credentials
+    uint256 public registryRoot;
+
+    /*//////////////////////////////////////////////////////////////
+                              CONSTRUCTOR
+    //////////////////////////////////////////////////////////////*/
+
+    constructor(address _credentialVerifier, address _poseidon, uint256 _registryRoot) {
+        admin = msg.sender;
+        credentialVerifier = ICredentialDecider(_credentialVerifier);
+        poseidon = IPoseidon(_poseidon);
+        registryRoot = _registryRoot;
+
+        emit RegistryRootUpdated(_registryRoot);
+    }
+
+    /*//////////////////////////////////////////////////////////////
+                            CORE FUNCTIONS
+    //////////////////////////////////////////////////////////////*/
+
+    /// @notice Check that every receiver of a batch of transfers holds a valid credential
+    /// @dev With signed credentials the circuit ignores the registry root of the state, which
+    /// must still match `registryRoot`
+    /// @param foldedProof Folded credential proof, encoded as uint256[34]
+    /// @param receivers Receivers of the shares, in folding order
+    /// @return provenAt Time at which the credentials were checked (unix timestamp)
+    function verifyReceivers(bytes calldata foldedProof, address[] calldata receivers)
+        external
+        view
+        returns (uint256 provenAt)
+    {
+        if (foldedProof.length != NOVA_PROOF_WORDS * 32) {
+            revert InvalidProof();
+        }
+        uint256[34] memory novaProof = abi.decode(foldedProof, (uint256[34]));
+
+        // The time and the registry root are carried unchanged, and the chain starts empty
+        provenAt = novaProof[Z_0_OFFSET];
+        if (
+            novaProof[Z_I_OFFSET] != provenAt
+                || novaProof[Z_I_OFFSET + 1] != novaProof[Z_0_OFFSET + 1]
+                || novaProof[Z_0_OFFSET + 2] != 0 || novaProof[Z_0_OFFSET + 3] != 0
+        ) {
+            revert InvalidProof();
+        }
+        if (novaProof[Z_0_OFFSET + 1] != registryRoot) {
+            revert RegistryMismatch(novaProof[Z_0_OFFSET + 1]);
+        }
+        if (provenAt > block.timestamp || block.timestamp - provenAt > MAX_PROOF_AGE) {
+            revert StaleProof(provenAt);
+        }
+
+        // One step per receiver
+        if (
+            receivers.length == 0 || novaProof[0] != receivers.length
+                || novaProof[Z_I_OFFSET + 3] != receivers.length
+        ) {
+            revert InvalidProof();
+        }
+        uint256 chained = _chainReceivers(receivers);
+        if (chained != novaProof[Z_I_OFFSET + 2]) {
+            revert ReceiversMismatch(chained);
+        }
+
+        if (!credentialVerifier.verifyOpaqueNovaProof(novaProof)) {
+            revert ProofVerificationFailed();
+        }
+    }
+
+    /*//////////////////////////////////////////////////////////////
+                           ADMIN FUNCTIONS
+    //////////////////////////////////////////////////////////////*/
+
+    /// @notice Publish the root of the registry after registering or removing investors
+    function updateRegistryRoot(uint256 newRoot) external {
+        if (msg.sender != admin) revert Unauthorized();
+        registryRoot = newRoot;
+        emit RegistryRootUpdated(newRoot);
+    }
+
+    /*//////////////////////////////////////////////////////////////
+                          INTERNAL HELPERS
+    //////////////////////////////////////////////////////////////*/
+
+    /// @dev Recomputes the chain of the circuit from the empty state:
+    /// transfersHash = Poseidon(transfersHash, receiver) for each receiver, as a field element
+    function _chainReceivers(address[] calldata receivers) internal view returns (uint256 chained) {
+        for (uint256 i = 0; i < receivers.length; i++) {
+            uint256[] memory link = new uint256[](2);
+            link[0] = chained;
+            link[1] = uint256(uint160(receivers[i]));
+            chained = poseidon.hash(link);
+        }
+    }
+}
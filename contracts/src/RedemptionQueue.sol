@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// @title Queue Hasher Interface
+/// @notice Interface for a deployed hasher of the Poseidon sponge of the bellpepper circuits over
+/// BN254 (`circuits::poseidon::poseidon_hash`: width 3, absorbing all the inputs then squeezing one
+/// element, with the IO pattern domain separation of the sponge)
+interface IQueueHasher {
+    function hash(uint256[] calldata inputs) external view returns (uint256);
+}
+
+/// @title Redemption Queue
+/// @notice Records the pending redemptions of the fund and maintains the commitment to them that
+/// the redemption coverage circuit takes as its public input
+/// @dev The queue is a Merkle-sum tree of 2^depth slots, matching `queue_commitment` of the
+/// circuits crate: each leaf is Poseidon(requestId, amount) and carries the amount, each node is
+/// Poseidon(left.hash, left.sum, right.hash, right.sum) and carries left.sum + right.sum, and free
+/// slots hold the empty request (0, 0). Adding or fulfilling a request rehashes the path of its
+/// slot, so the root is always the commitment of the recorded requests, in slot order.
+contract RedemptionQueue {
+    /*//////////////////////////////////////////////////////////////
+                                 ERRORS
+    //////////////////////////////////////////////////////////////*/
+
+    error Unauthorized();
+    error QueueFull();
+    error InvalidAmount();
+    error UnknownRequest(uint256 slot);
+
+    /*//////////////////////////////////////////////////////////////
+                                 EVENTS
+    //////////////////////////////////////////////////////////////*/
+
+    event RedemptionRequested(
+        uint256 indexed requestId,
+        address indexed investor,
+        uint256 slot,
+        uint64 amount
+    );
+
+    event RedemptionFulfilled(uint256 indexed requestId, uint256 slot);
+
+    /*//////////////////////////////////////////////////////////////
+                                STORAGE
+    //////////////////////////////////////////////////////////////*/
+
+    struct Node {
+        uint256 hash;
+        uint256 sum;
+    }
+
+    struct Request {
+        uint256 requestId;
+        address investor;
+        uint64 amount;
+    }
+
+    /// Fund operator, fulfilling the redemptions
+    address public immutable operator;
+
+    /// Hasher of the circuits' Poseidon sponge
+    IQueueHasher public immutable hasher;
+
+    /// Depth of the tree: the circuit is set up for a queue of 2^depth requests
+    uint8 public immutable depth;
+
+    /// Root of the Merkle-sum tree of the pending redemptions
+    uint256 public queueCommitment;
+
+    /// Last request id issued; ids start at 1 so that no request is the empty leaf
+    uint256 public lastRequestId;
+
+    /// Pending request of each slot
+    mapping(uint256 => Request) public requests;
+
+    /// Nodes of the tree, by level (0 for the leaves) and index within the level
+    mapping(uint256 => mapping(uint256 => Node)) internal nodes;
+
+    /// Node of each level of a subtree holding only free slots
+    Node[] internal emptyNodes;
+
+    /// Slots freed by fulfilled requests, reused before fresh ones
+    uint256[] internal freeSlots;
+
+    /// Next never used slot
+    uint256 public nextSlot;
+
+    /*//////////////////////////////////////////////////////////////
+                              CONSTRUCTOR
+    //////////////////////////////////////////////////////////////*/
+
+    constructor(address _operator, address _hasher, uint8 _depth) {
+        operator = _operator;
+        hasher = IQueueHasher(_hasher);
+        depth = _depth;
+
+        Node memory node = Node(_hashLeaf(0, 0), 0);
+        emptyNodes.push(node);
+        for (uint256 level = 0; level < _depth; level++) {
+            node = _hashNode(node, node);
+            emptyNodes.push(node);
+        }
+        queueCommitment = node.hash;
+    }
+
+    /*//////////////////////////////////////////////////////////////
+                               REDEMPTIONS
+    //////////////////////////////////////////////////////////////*/
+
+    /// @notice Queue a redemption of `amount`
+    /// @dev Amounts are range checked to 64 bits by the circuit, hence the `uint64`
+    /// @return requestId Identifier of the request, committed in its leaf
+    function requestRedemption(uint64 amount) external returns (uint256 requestId) {
+        if (amount == 0) revert InvalidAmount();
+
+        uint256 slot;
+        if (freeSlots.length > 0) {
+            slot = freeSlots[freeSlots.length - 1];
+            freeSlots.pop();
+        } else {
+            if (nextSlot == uint256(1) << depth) revert QueueFull();
+            slot = nextSlot++;
+        }
+
+        requestId = ++lastRequestId;
+        requests[slot] = Request(requestId, msg.sender, amount);
+        _updateSlot(slot, Node(_hashLeaf(requestId, amount), amount));
+
+        emit RedemptionRequested(requestId, msg.sender, slot, amount);
+    }
+
+    /// @notice Remove the fulfilled request of `slot` from the queue
+    function fulfillRedemption(uint256 slot) external {
+        if (msg.sender != operator) revert Unauthorized();
+        uint256 requestId = requests[slot].requestId;
+        if (requestId == 0) revert UnknownRequest(slot);
+
+        delete requests[slot];
+        freeSlots.push(slot);
+        _updateSlot(slot, emptyNodes[0]);
+
+        emit RedemptionFulfilled(requestId, slot);
+    }
+
+    /// @notice Total amount of the pending redemptions
+    function totalPending() external view returns (uint256) {
+        return _node(depth, 0).sum;
+    }
+
+    /*//////////////////////////////////////////////////////////////
+                            INTERNAL FUNCTIONS
+    //////////////////////////////////////////////////////////////*/
+
+    /// @dev Set the leaf of `slot` and rehash its path up to the root
+    function _updateSlot(uint256 slot, Node memory leaf) internal {
+        nodes[0][slot] = leaf;
+        Node memory node = leaf;
+        uint256 index = slot;
+        for (uint256 level = 0; level < depth; level++) {
+            node = index % 2 == 0
+                ? _hashNode(node, _node(level, index + 1))
+                : _hashNode(_node(level, index - 1), node);
+            index /= 2;
+            nodes[level + 1][index] = node;
+        }
+        queueCommitment = node.hash;
+    }
+
+    /// @dev Stored node, or the empty node of its level if it was never written
+    function _node(uint256 level, uint256 index) internal view returns (Node memory node) {
+        node = nodes[level][index];
+        if (node.hash == 0) {
+            node = emptyNodes[level];
+        }
+    }
+
+    function _hashLeaf(uint256 requestId, uint256 amount) internal view returns (uint256) {
+        uint256[] memory inputs = new uint256[](2);
+        inputs[0] = requestId;
+        inputs[1] = amount;
+        return hasher.hash(inputs);
+    }
+
+    function _hashNode(Node memory left, Node memory right) internal view returns (Node memory) {
+        uint256[] memory inputs = new uint256[](4);
+        inputs[0] = left.hash;
+        inputs[1] = left.sum;
+        inputs[2] = right.hash;
+        inputs[3] = right.sum;
+        return Node(hasher.hash(inputs), left.sum + right.sum);
+    }
+}
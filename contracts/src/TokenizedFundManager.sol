@@ -4,7 +4,16 @@ pragma solidity ^0.8.20;
 /// @title NovaDecider Interface
 /// @notice Interface for the deployed NovaDecider verifier contract
 interface INovaDecider {
-    function verifyOpaqueNovaProof(uint256[28] calldata proof) external view returns (bool);
+    function verifyOpaqueNovaProof(uint256[36] calldata proof) external view returns (bool);
+}
+
+/// @title Poseidon Interface
+/// @notice Interface for a deployed Poseidon hasher over BN254, with the canonical sponge
+/// configuration of the circuits (rate 4, capacity 1, alpha 5, 8 full and 60 partial rounds), such
+/// as `Poseidon`, generated from that configuration by solidity-verifiers
+interface IPoseidon {
+    /// @notice Absorbs `inputs` into a fresh sponge and squeezes one element
+    function hash(uint256[] calldata inputs) external view returns (uint256);
 }
 
 /// @title Tokenized Fund Manager with Zero-Knowledge Proofs
@@ -20,6 +29,10 @@ contract TokenizedFundManager {
     error InvalidProof();
     error InsufficientBalance();
     error PolicyViolation(string reason);
+    error CoverageMismatch(uint256 firstPeriod, uint256 lastPeriod);
+    error ReportsMismatch(uint256 reportsHash);
+    error PricesMismatch(uint256 pricesHash);
+    error UnpublishedPrices(uint256 commitment);
 
     /*//////////////////////////////////////////////////////////////
                                  EVENTS
@@ -35,6 +48,8 @@ contract TokenizedFundManager {
 
     event AgentAuthorized(address indexed agent, bool status);
 
+    event PricesPublished(uint256 indexed commitment);
+
     /*//////////////////////////////////////////////////////////////
                                 STORAGE
     //////////////////////////////////////////////////////////////*/
@@ -55,6 +70,15 @@ contract TokenizedFundManager {
     /// NovaDecider verifier contract (single folded proof)
     INovaDecider public immutable novaVerifier;
 
+    /// Poseidon hasher, to match the reports and the prices in the metadata against the proof
+    IPoseidon public immutable poseidon;
+
+    /// Pricing oracle, publishing the Poseidon commitments to the price vectors it signs
+    address public immutable pricingOracle;
+
+    /// Commitments to the price vectors published by the pricing oracle
+    mapping(uint256 => bool) public publishedPrices;
+
     /// Audit trail
     struct Transaction {
         bytes32 txHash;
@@ -64,6 +88,29 @@ contract TokenizedFundManager {
 
     Transaction[] public auditTrail;
 
+    /// Time window covered by a folded proof: the consecutive, gap-free periods
+    /// [firstPeriod, lastPeriod) (unix timestamps), split into `periods` steps
+    struct ComplianceWindow {
+        uint256 firstPeriod;
+        uint256 lastPeriod;
+        uint256 periods;
+    }
+
+    /// Report of a period, encrypted to the view key of the regulator: the ephemeral point
+    /// R = r·B on BabyJubJub and the encrypted holdings
+    struct Ciphertext {
+        uint256 ephemeralX;
+        uint256 ephemeralY;
+        uint256[] payload;
+    }
+
+    /// Layout of the folded proof (uint256[36]): i, z_0[5], z_i[5], then the decider proof.
+    /// The IVC state is [firstPeriod, lastPeriod, periods, pricesHash, reportsHash]
+    uint256 private constant NOVA_PROOF_WORDS = 36;
+    uint256 private constant STATE_LEN = 5;
+    uint256 private constant Z_0_OFFSET = 1;
+    uint256 private constant Z_I_OFFSET = Z_0_OFFSET + STATE_LEN;
+
     /// Daily tracking (for rate limits)
     uint256 public currentDay;
     uint256 public dailyRebalanceCount;
@@ -76,12 +123,16 @@ contract TokenizedFundManager {
     constructor(
         address _agent,
         bytes32 _whitelistRoot,
-        address _novaVerifier
+        address _novaVerifier,
+        address _poseidon,
+        address _pricingOracle
     ) {
         admin = msg.sender;
         authorizedAgents[_agent] = true;
         assetWhitelistRoot = _whitelistRoot;
         novaVerifier = INovaDecider(_novaVerifier);
+        poseidon = IPoseidon(_poseidon);
+        pricingOracle = _pricingOracle;
 
         emit AgentAuthorized(_agent, true);
     }
@@ -100,13 +151,28 @@ contract TokenizedFundManager {
         _;
     }
 
+    modifier onlyPricingOracle() {
+        if (msg.sender != pricingOracle) revert Unauthorized();
+        _;
+    }
+
     /*//////////////////////////////////////////////////////////////
                             CORE FUNCTIONS
     //////////////////////////////////////////////////////////////*/
 
     /// @notice Execute a fund rebalancing with ZK proof verification
     /// @param foldedProof Single folded Nova proof attesting to all compliance checks
-    /// @param metadata Encrypted transaction metadata
+    /// @param metadata Reports of the folded periods encrypted to the regulator, and the
+    /// commitments to the prices of the folded periods, encoded as (Ciphertext[], uint256[]) in
+    /// folding order. They must chain to the reportsHash and the pricesHash of the proof, and the
+    /// prices must be published by the pricing oracle
+    /// @dev Recomputing the chains costs 3 Poseidon hashes per folded period: the hash of the
+    /// ciphertext, of 2 + 5 * assets elements, its link into reportsHash, and the link of the
+    /// prices into pricesHash. A hash of n elements runs max(1, ceil(n / 4)) permutations, so a
+    /// period of the 4-asset demo fund costs 6 + 1 + 1 = 8 permutations, each estimated at around
+    /// 100k gas (the cross-check test of the generated `Poseidon` prints the measured cost). A
+    /// quarter of daily periods (92 * 8 = 736 permutations) therefore does not fit in a block, and
+    /// proofs should cover shorter windows, eg. a week (56 permutations)
     /// @return success Whether the rebalancing was executed
     function executeRebalance(
         bytes calldata foldedProof,
@@ -121,7 +187,14 @@ contract TokenizedFundManager {
         }
 
         // Verify folded proof once (all constraints folded together)
-        _verifyFoldedProof(foldedProof);
+        (, uint256 pricesHash, uint256 reportsHash) = _verifyFoldedProof(foldedProof);
+
+        // The metadata must be the reports encrypted by the circuit, and the published prices
+        // that the circuit valued the holdings at
+        (Ciphertext[] memory reports, uint256[] memory prices) =
+            abi.decode(metadata, (Ciphertext[], uint256[]));
+        _verifyReports(reports, reportsHash);
+        _verifyPrices(prices, pricesHash);
 
         // If all proofs valid, record the transaction
         bytes32 txHash = keccak256(
@@ -143,6 +216,30 @@ contract TokenizedFundManager {
         return true;
     }
 
+    /// @notice Check that a folded proof covers exactly the window [periodStart, periodEnd)
+    /// @dev The circuit enforces that the folded periods are consecutive, gap-free and at most
+    /// `max_period_len` long. The cap is a parameter of the circuit, and hence fixed by the verifier
+    /// this contract calls: with the default of one day (`MAX_PERIOD_LEN`), a proof covering e.g.
+    /// [Jul 1, Oct 1) folds at least one period for every day of Q3, weekends and holidays
+    /// included. A fund reporting on business days only raises it, eg. to 4 days, at the cost of
+    /// a period spanning the whole long weekend. The prices of the proof are only checked by
+    /// `executeRebalance`
+    /// @param foldedProof Single folded Nova proof, encoded as uint256[36]
+    /// @param periodStart Start of the required window (unix timestamp)
+    /// @param periodEnd End of the required window (unix timestamp, exclusive)
+    /// @return periods Number of periods folded in the proof
+    function verifyCoverage(
+        bytes calldata foldedProof,
+        uint256 periodStart,
+        uint256 periodEnd
+    ) external view returns (uint256 periods) {
+        (ComplianceWindow memory window, , ) = _verifyFoldedProof(foldedProof);
+        if (window.firstPeriod != periodStart || window.lastPeriod != periodEnd) {
+            revert CoverageMismatch(window.firstPeriod, window.lastPeriod);
+        }
+        return window.periods;
+    }
+
     /// @notice Generate a compliance report for auditors
     /// @param fromIndex Starting index in audit trail
     /// @param toIndex Ending index in audit trail
@@ -171,19 +268,45 @@ contract TokenizedFundManager {
 
     /// @notice Verify folded Nova proof (all constraints)
     /// @dev Calls the deployed NovaDecider verifier contract once
-    function _verifyFoldedProof(bytes memory proof) internal view {
+    /// @return window Time window covered by the proof
+    /// @return pricesHash Hash chaining the commitments to the prices of the folded periods
+    /// @return reportsHash Hash chaining the encrypted reports of the folded periods
+    function _verifyFoldedProof(bytes memory proof)
+        internal
+        view
+        returns (ComplianceWindow memory window, uint256 pricesHash, uint256 reportsHash)
+    {
         if (proof.length == 0) {
             revert ProofVerificationFailed();
         }
 
-        // For Nova proofs, we expect exactly 28 uint256 values (900 bytes when ABI-encoded)
-        // The proof should be pre-formatted as uint256[28]
-        if (proof.length != 28 * 32) {
+        // For Nova proofs, we expect exactly 36 uint256 values (1152 bytes when ABI-encoded)
+        // The proof should be pre-formatted as uint256[36]
+        if (proof.length != NOVA_PROOF_WORDS * 32) {
             revert InvalidProof();
         }
 
         // Decode and verify
-        uint256[28] memory novaProof = abi.decode(proof, (uint256[28]));
+        uint256[36] memory novaProof = abi.decode(proof, (uint256[36]));
+
+        // The folding must start from the empty window, otherwise the covered window is not
+        // enforced by the circuit
+        for (uint256 j = 0; j < STATE_LEN; j++) {
+            if (novaProof[Z_0_OFFSET + j] != 0) {
+                revert InvalidProof();
+            }
+        }
+        window = ComplianceWindow({
+            firstPeriod: novaProof[Z_I_OFFSET],
+            lastPeriod: novaProof[Z_I_OFFSET + 1],
+            periods: novaProof[Z_I_OFFSET + 2]
+        });
+        pricesHash = novaProof[Z_I_OFFSET + 3];
+        reportsHash = novaProof[Z_I_OFFSET + 4];
+        if (window.periods == 0 || window.periods != novaProof[0]) {
+            revert InvalidProof();
+        }
+
         bool verified = novaVerifier.verifyOpaqueNovaProof(novaProof);
 
         if (!verified) {
@@ -191,6 +314,52 @@ contract TokenizedFundManager {
         }
     }
 
+    /// @notice Check that the reports chain to the reportsHash of a verified proof
+    /// @dev Recomputes the chain of the circuit from the empty state:
+    /// reportsHash = Poseidon(reportsHash, Poseidon(R.x, R.y, payload)) for each report
+    function _verifyReports(Ciphertext[] memory reports, uint256 reportsHash) internal view {
+        uint256 chained = 0;
+        for (uint256 i = 0; i < reports.length; i++) {
+            uint256[] memory ciphertext = new uint256[](2 + reports[i].payload.length);
+            ciphertext[0] = reports[i].ephemeralX;
+            ciphertext[1] = reports[i].ephemeralY;
+            for (uint256 j = 0; j < reports[i].payload.length; j++) {
+                ciphertext[2 + j] = reports[i].payload[j];
+            }
+
+            uint256[] memory link = new uint256[](2);
+            link[0] = chained;
+            link[1] = poseidon.hash(ciphertext);
+            chained = poseidon.hash(link);
+        }
+
+        if (chained != reportsHash) {
+            revert ReportsMismatch(chained);
+        }
+    }
+
+    /// @notice Check that the prices chain to the pricesHash of a verified proof, and that each
+    /// of them was published by the pricing oracle
+    /// @dev Recomputes the chain of the circuit from the empty state:
+    /// pricesHash = Poseidon(pricesHash, commitment) for each price vector
+    function _verifyPrices(uint256[] memory prices, uint256 pricesHash) internal view {
+        uint256 chained = 0;
+        for (uint256 i = 0; i < prices.length; i++) {
+            if (!publishedPrices[prices[i]]) {
+                revert UnpublishedPrices(prices[i]);
+            }
+
+            uint256[] memory link = new uint256[](2);
+            link[0] = chained;
+            link[1] = prices[i];
+            chained = poseidon.hash(link);
+        }
+
+        if (chained != pricesHash) {
+            revert PricesMismatch(chained);
+        }
+    }
+
     /*//////////////////////////////////////////////////////////////
                            ADMIN FUNCTIONS
     //////////////////////////////////////////////////////////////*/
@@ -204,6 +373,13 @@ contract TokenizedFundManager {
         emit AgentAuthorized(agent, status);
     }
 
+    /// @notice Publish the commitment to a price vector signed by the pricing oracle, so that
+    /// proofs valued at these prices are accepted
+    function publishPrices(uint256 commitment) external onlyPricingOracle {
+        publishedPrices[commitment] = true;
+        emit PricesPublished(commitment);
+    }
+
     /// @notice Update asset whitelist root
     function updateWhitelistRoot(bytes32 newRoot) external onlyAdmin {
         assetWhitelistRoot = newRoot;
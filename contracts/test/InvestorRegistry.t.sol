@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "forge-std/Test.sol";
+import "../src/InvestorRegistry.sol";
+
+/// @notice Mock NovaDecider of the credential circuit for testing
+contract MockCredentialDecider {
+    bool public shouldPass = true;
+
+    function setShouldPass(bool _shouldPass) external {
+        shouldPass = _shouldPass;
+    }
+
+    function verifyOpaqueNovaProof(uint256[34] calldata) external view returns (bool) {
+        return shouldPass;
+    }
+}
+
+/// @notice Mock Poseidon hasher for testing (keccak reduced to the BN254 scalar field)
+contract MockRegistryPoseidon {
+    uint256 constant FIELD_MODULUS =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+
+    function hash(uint256[] calldata inputs) external pure returns (uint256) {
+        return uint256(keccak256(abi.encode(inputs))) % FIELD_MODULUS;
+    }
+}
+
+contract InvestorRegistryTest is Test {
+    InvestorRegistry public registry;
+    MockCredentialDecider public mockVerifier;
+    MockRegistryPoseidon public mockPoseidon;
+
+    uint256 constant REGISTRY_ROOT = 0x1234;
+    uint256 constant NOW = 1751328000;
+
+    function setUp() public {
+        vm.warp(NOW + 1 hours);
+        mockVerifier = new MockCredentialDecider();
+        mockPoseidon = new MockRegistryPoseidon();
+        registry = new InvestorRegistry(address(mockVerifier), address(mockPoseidon), REGISTRY_ROOT);
+    }
+
+    function testVerifyReceivers() public {
+        address[] memory receivers = _receivers();
+        uint256 provenAt = registry.verifyReceivers(_proof(receivers, REGISTRY_ROOT, NOW), receivers);
+        assertEq(provenAt, NOW);
+    }
+
+    function testReceiversMismatch() public {
+        address[] memory receivers = _receivers();
+        bytes memory proof = _proof(receivers, REGISTRY_ROOT, NOW);
+
+        // Same number of receivers, one of them swapped
+        receivers[1] = address(0xBAD);
+        vm.expectPartialRevert(InvestorRegistry.ReceiversMismatch.selector);
+        registry.verifyReceivers(proof, receivers);
+    }
+
+    function testRegistryMismatch() public {
+        address[] memory receivers = _receivers();
+        bytes memory proof = _proof(receivers, REGISTRY_ROOT + 1, NOW);
+        vm.expectRevert(
+            abi.encodeWithSelector(InvestorRegistry.RegistryMismatch.selector, REGISTRY_ROOT + 1)
+        );
+        registry.verifyReceivers(proof, receivers);
+
+        // Accepted once the admin publishes the new root
+        registry.updateRegistryRoot(REGISTRY_ROOT + 1);
+        registry.verifyReceivers(proof, receivers);
+    }
+
+    function testStaleProof() public {
+        address[] memory receivers = _receivers();
+        bytes memory proof = _proof(receivers, REGISTRY_ROOT, NOW);
+        vm.warp(NOW + 2 days);
+        vm.expectRevert(abi.encodeWithSelector(InvestorRegistry.StaleProof.selector, NOW));
+        registry.verifyReceivers(proof, receivers);
+    }
+
+    function testInvalidNovaProof() public {
+        address[] memory receivers = _receivers();
+        bytes memory proof = _proof(receivers, REGISTRY_ROOT, NOW);
+        mockVerifier.setShouldPass(false);
+        vm.expectRevert(InvestorRegistry.ProofVerificationFailed.selector);
+        registry.verifyReceivers(proof, receivers);
+    }
+
+    function testOnlyAdminUpdatesRoot() public {
+        vm.prank(address(0x1));
+        vm.expectRevert(InvestorRegistry.Unauthorized.selector);
+        registry.updateRegistryRoot(0);
+    }
+
+    function _receivers() internal pure returns (address[] memory receivers) {
+        receivers = new address[](3);
+        receivers[0] = address(0xA11CE);
+        receivers[1] = address(0xB0B);
+        receivers[2] = address(0xCA201);
+    }
+
+    /// Folded proof of the receivers, laid out as i, z_0[4], z_i[4], then the decider proof
+    function _proof(address[] memory receivers, uint256 registryRoot, uint256 provenAt)
+        internal
+        view
+        returns (bytes memory)
+    {
+        uint256 chained = 0;
+        for (uint256 i = 0; i < receivers.length; i++) {
+            uint256[] memory link = new uint256[](2);
+            link[0] = chained;
+            link[1] = uint256(uint160(receivers[i]));
+            chained = mockPoseidon.hash(link);
+        }
+
+        uint256[34] memory proof;
+        proof[0] = receivers.length;
+        proof[1] = provenAt;
+        proof[2] = registryRoot;
+        proof[5] = provenAt;
+        proof[6] = registryRoot;
+        proof[7] = chained;
+        proof[8] = receivers.length;
+        return abi.encode(proof);
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "forge-std/Test.sol";
+import "../src/Poseidon.sol";
+
+/// @dev The expected hashes are those of `PoseidonSponge` with `poseidon_canonical_config`, pinned
+/// by `poseidon_test_vectors` in solidity-verifiers, whose `poseidon_hasher_matches_sponge` also
+/// checks the generated contract against the sponge on random inputs
+contract PoseidonTest is Test {
+    Poseidon public poseidon;
+
+    uint256 constant FIELD_MODULUS =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+
+    function setUp() public {
+        poseidon = new Poseidon();
+    }
+
+    function testEmptyInput() public {
+        assertEq(
+            poseidon.hash(new uint256[](0)),
+            18299911814700648280742410696351220515353834127246957628534618031453646577154
+        );
+    }
+
+    function testFullRate() public {
+        assertEq(
+            poseidon.hash(_sequence(4)),
+            7817711165059374331357136443537800893307845083525445872661165200086166013245
+        );
+    }
+
+    function testSeveralPermutations() public {
+        assertEq(
+            poseidon.hash(_sequence(9)),
+            3402111777366377077762529803335651546215060635698385595629854299130918549031
+        );
+    }
+
+    function testRejectsNonFieldInputs() public {
+        uint256[] memory inputs = _sequence(2);
+        inputs[1] = FIELD_MODULUS;
+        vm.expectRevert("Poseidon: input is not a field element");
+        poseidon.hash(inputs);
+    }
+
+    /// @dev [1, 2, ..., n]
+    function _sequence(uint256 n) internal pure returns (uint256[] memory inputs) {
+        inputs = new uint256[](n);
+        for (uint256 i = 0; i < n; i++) {
+            inputs[i] = i + 1;
+        }
+    }
+}
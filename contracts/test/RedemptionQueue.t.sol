@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "forge-std/Test.sol";
+import "../src/RedemptionQueue.sol";
+
+/// @notice Mock queue hasher for testing (keccak reduced to the BN254 scalar field)
+contract MockQueueHasher {
+    uint256 constant FIELD_MODULUS =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+
+    function hash(uint256[] calldata inputs) external pure returns (uint256) {
+        return uint256(keccak256(abi.encode(inputs))) % FIELD_MODULUS;
+    }
+}
+
+contract RedemptionQueueTest is Test {
+    RedemptionQueue public queue;
+    MockQueueHasher public hasher;
+    address public operator;
+    address public investor;
+
+    uint8 constant DEPTH = 3;
+
+    function setUp() public {
+        operator = address(0x1);
+        investor = address(0x2);
+        hasher = new MockQueueHasher();
+        queue = new RedemptionQueue(operator, address(hasher), DEPTH);
+    }
+
+    function testEmptyQueueCommitment() public {
+        assertEq(queue.queueCommitment(), _commitment());
+        assertEq(queue.totalPending(), 0);
+    }
+
+    function testRequestsUpdateCommitment() public {
+        vm.startPrank(investor);
+        queue.requestRedemption(100);
+        queue.requestRedemption(250);
+        queue.requestRedemption(75);
+        vm.stopPrank();
+
+        assertEq(queue.queueCommitment(), _commitment());
+        assertEq(queue.totalPending(), 425);
+    }
+
+    function testFulfillmentFreesSlot() public {
+        vm.startPrank(investor);
+        queue.requestRedemption(100);
+        queue.requestRedemption(250);
+        vm.stopPrank();
+
+        vm.prank(operator);
+        queue.fulfillRedemption(0);
+        assertEq(queue.queueCommitment(), _commitment());
+        assertEq(queue.totalPending(), 250);
+
+        // The freed slot is reused by the next request
+        vm.prank(investor);
+        uint256 requestId = queue.requestRedemption(40);
+        (uint256 storedId,,) = queue.requests(0);
+        assertEq(storedId, requestId);
+        assertEq(queue.queueCommitment(), _commitment());
+        assertEq(queue.totalPending(), 290);
+    }
+
+    function testQueueFull() public {
+        vm.startPrank(investor);
+        for (uint256 i = 0; i < uint256(1) << DEPTH; i++) {
+            queue.requestRedemption(1);
+        }
+        vm.expectRevert(RedemptionQueue.QueueFull.selector);
+        queue.requestRedemption(1);
+        vm.stopPrank();
+    }
+
+    function testOnlyOperatorFulfills() public {
+        vm.prank(investor);
+        queue.requestRedemption(100);
+
+        vm.prank(investor);
+        vm.expectRevert(RedemptionQueue.Unauthorized.selector);
+        queue.fulfillRedemption(0);
+
+        vm.prank(operator);
+        vm.expectRevert(abi.encodeWithSelector(RedemptionQueue.UnknownRequest.selector, 1));
+        queue.fulfillRedemption(1);
+    }
+
+    /// Commitment of the recorded requests, rebuilt from scratch like `queue_commitment`
+    function _commitment() internal view returns (uint256) {
+        uint256 size = uint256(1) << DEPTH;
+        uint256[] memory hashes = new uint256[](size);
+        uint256[] memory sums = new uint256[](size);
+        for (uint256 slot = 0; slot < size; slot++) {
+            (uint256 requestId,, uint64 amount) = queue.requests(slot);
+            uint256[] memory leaf = new uint256[](2);
+            leaf[0] = requestId;
+            leaf[1] = amount;
+            hashes[slot] = hasher.hash(leaf);
+            sums[slot] = amount;
+        }
+        for (; size > 1; size /= 2) {
+            for (uint256 i = 0; i < size / 2; i++) {
+                uint256[] memory node = new uint256[](4);
+                node[0] = hashes[2 * i];
+                node[1] = sums[2 * i];
+                node[2] = hashes[2 * i + 1];
+                node[3] = sums[2 * i + 1];
+                hashes[i] = hasher.hash(node);
+                sums[i] = sums[2 * i] + sums[2 * i + 1];
+            }
+        }
+        return hashes[0];
+    }
+}
@@ -3,6 +3,7 @@ pragma solidity ^0.8.20;
 
 import "forge-std/Test.sol";
 import "../src/TokenizedFundManager.sol";
+import "../src/Poseidon.sol";
 
 /// @notice Mock NovaDecider for testing
 contract MockNovaDecider {
@@ -12,36 +13,69 @@ contract MockNovaDecider {
         shouldPass = _shouldPass;
     }
 
-    function verifyOpaqueNovaProof(uint256[28] calldata) external view returns (bool) {
+    function verifyOpaqueNovaProof(uint256[36] calldata) external view returns (bool) {
         return shouldPass;
     }
 }
 
+/// @notice Mock Poseidon hasher for testing (keccak reduced to the BN254 scalar field)
+contract MockPoseidon {
+    uint256 constant FIELD_MODULUS =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+
+    function hash(uint256[] calldata inputs) external pure returns (uint256) {
+        return uint256(keccak256(abi.encode(inputs))) % FIELD_MODULUS;
+    }
+}
+
 contract TokenizedFundManagerTest is Test {
     TokenizedFundManager public fundManager;
     MockNovaDecider public mockVerifier;
+    MockPoseidon public mockPoseidon;
+    /// Hasher with which the helpers chain the reports and the prices
+    IPoseidon internal hasher;
     address public admin;
     address public agent;
+    address public pricingOracle;
     bytes32 public whitelistRoot;
 
+    /// Q3 2025: [2025-07-01, 2025-10-01), 92 daily periods
+    uint256 constant Q3_START = 1751328000;
+    uint256 constant Q3_END = 1759276800;
+    uint256 constant Q3_DAYS = 92;
+
     function setUp() public {
         admin = address(this);
         agent = address(0x1);
+        pricingOracle = address(0x4);
         whitelistRoot = keccak256("mock_whitelist_root");
 
         // Deploy mock verifier and fund manager (single folded proof)
         mockVerifier = new MockNovaDecider();
+        mockPoseidon = new MockPoseidon();
+        hasher = IPoseidon(address(mockPoseidon));
         fundManager = new TokenizedFundManager(
             agent,
             whitelistRoot,
-            address(mockVerifier)
+            address(mockVerifier),
+            address(mockPoseidon),
+            pricingOracle
         );
+
+        // The pricing oracle publishes the prices of Q3
+        uint256[] memory prices = _prices(Q3_DAYS);
+        vm.startPrank(pricingOracle);
+        for (uint256 i = 0; i < prices.length; i++) {
+            fundManager.publishPrices(prices[i]);
+        }
+        vm.stopPrank();
     }
 
     function testInitialSetup() public {
         assertEq(fundManager.admin(), admin);
         assertTrue(fundManager.authorizedAgents(agent));
         assertEq(fundManager.assetWhitelistRoot(), whitelistRoot);
+        assertEq(fundManager.pricingOracle(), pricingOracle);
     }
 
     function testGetPolicyParameters() public {
@@ -50,24 +84,156 @@ contract TokenizedFundManagerTest is Test {
         assertEq(minLiquidity, 10);
     }
 
-    function testExecuteRebalanceWithMockProofs() public {
-        vm.prank(agent);
+    /// @dev Builds `periods` dummy encrypted reports
+    function _reports(uint256 periods)
+        internal
+        pure
+        returns (TokenizedFundManager.Ciphertext[] memory reports)
+    {
+        reports = new TokenizedFundManager.Ciphertext[](periods);
+        for (uint256 i = 0; i < periods; i++) {
+            reports[i].ephemeralX = 2 * i + 1;
+            reports[i].ephemeralY = 2 * i + 2;
+            reports[i].payload = new uint256[](2);
+            reports[i].payload[0] = 1000 + i;
+            reports[i].payload[1] = 2000 + i;
+        }
+    }
+
+    /// @dev Chains the hashes of the reports, as done by the circuit
+    function _reportsHash(TokenizedFundManager.Ciphertext[] memory reports)
+        internal
+        view
+        returns (uint256 reportsHash)
+    {
+        for (uint256 i = 0; i < reports.length; i++) {
+            uint256[] memory ciphertext = new uint256[](2 + reports[i].payload.length);
+            ciphertext[0] = reports[i].ephemeralX;
+            ciphertext[1] = reports[i].ephemeralY;
+            for (uint256 j = 0; j < reports[i].payload.length; j++) {
+                ciphertext[2 + j] = reports[i].payload[j];
+            }
+            uint256[] memory link = new uint256[](2);
+            link[0] = reportsHash;
+            link[1] = hasher.hash(ciphertext);
+            reportsHash = hasher.hash(link);
+        }
+    }
 
-        // Create folded proof (28 * 32 bytes, encoded as uint256[28])
-        uint256[28] memory folded;
-        for (uint256 i = 0; i < 28; i++) {
-            folded[i] = i + 1;
+    /// @dev Builds `periods` dummy commitments to price vectors
+    function _prices(uint256 periods) internal pure returns (uint256[] memory prices) {
+        prices = new uint256[](periods);
+        for (uint256 i = 0; i < periods; i++) {
+            prices[i] = 3000 + i;
         }
-        bytes memory proofBundle = abi.encode(folded);
+    }
 
-        bytes memory metadata = abi.encode("encrypted_transaction_data");
+    /// @dev Chains the commitments to the prices, as done by the circuit
+    function _pricesHash(uint256[] memory prices) internal view returns (uint256 pricesHash) {
+        for (uint256 i = 0; i < prices.length; i++) {
+            uint256[] memory link = new uint256[](2);
+            link[0] = pricesHash;
+            link[1] = prices[i];
+            pricesHash = hasher.hash(link);
+        }
+    }
 
+    /// @dev Builds a folded proof (36 * 32 bytes, encoded as uint256[36]) covering
+    /// [firstPeriod, lastPeriod) in `periods` steps, with dummy decider proof values
+    function _foldedProof(
+        uint256 firstPeriod,
+        uint256 lastPeriod,
+        uint256 periods,
+        uint256 pricesHash,
+        uint256 reportsHash,
+        uint256 seed
+    ) internal pure returns (uint256[36] memory folded) {
+        for (uint256 i = 11; i < 36; i++) {
+            folded[i] = seed + i;
+        }
+        folded[0] = periods;
+        // z_0 = [0, 0, 0, 0, 0]
+        folded[6] = firstPeriod;
+        folded[7] = lastPeriod;
+        folded[8] = periods;
+        folded[9] = pricesHash;
+        folded[10] = reportsHash;
+    }
+
+    function _q3Metadata() internal pure returns (bytes memory) {
+        return abi.encode(_reports(Q3_DAYS), _prices(Q3_DAYS));
+    }
+
+    function _q3Proof(uint256 seed) internal view returns (bytes memory) {
+        return abi.encode(
+            _foldedProof(
+                Q3_START,
+                Q3_END,
+                Q3_DAYS,
+                _pricesHash(_prices(Q3_DAYS)),
+                _reportsHash(_reports(Q3_DAYS)),
+                seed
+            )
+        );
+    }
+
+    function testExecuteRebalanceWithMockProofs() public {
+        bytes memory proofBundle = _q3Proof(1);
+
+        bytes memory metadata = _q3Metadata();
+
+        vm.prank(agent);
         bool success = fundManager.executeRebalance(proofBundle, metadata);
         assertTrue(success);
 
         assertEq(fundManager.getAuditTrailLength(), 1);
     }
 
+    /// @dev One week of reports of the 4-asset demo fund (ciphertexts of 2 + 20 elements), checked
+    /// with the generated Poseidon hasher
+    function testExecuteRebalanceWithPoseidon() public {
+        Poseidon poseidon = new Poseidon();
+        TokenizedFundManager manager = new TokenizedFundManager(
+            agent,
+            whitelistRoot,
+            address(mockVerifier),
+            address(poseidon),
+            pricingOracle
+        );
+        hasher = IPoseidon(address(poseidon));
+
+        uint256 periods = 7;
+        TokenizedFundManager.Ciphertext[] memory reports = _reports(periods);
+        for (uint256 i = 0; i < periods; i++) {
+            reports[i].payload = new uint256[](20);
+            for (uint256 j = 0; j < 20; j++) {
+                reports[i].payload[j] = 1000 * i + j;
+            }
+        }
+        uint256[] memory prices = _prices(periods);
+        vm.startPrank(pricingOracle);
+        for (uint256 i = 0; i < periods; i++) {
+            manager.publishPrices(prices[i]);
+        }
+        vm.stopPrank();
+
+        bytes memory proofBundle = abi.encode(
+            _foldedProof(
+                Q3_START,
+                Q3_START + periods * 1 days,
+                periods,
+                _pricesHash(prices),
+                _reportsHash(reports),
+                1
+            )
+        );
+
+        vm.prank(agent);
+        uint256 gasBefore = gasleft();
+        assertTrue(manager.executeRebalance(proofBundle, abi.encode(reports, prices)));
+        emit log_named_uint("executeRebalance gas, one week", gasBefore - gasleft());
+    }
+
     function testUnauthorizedAgentCannotRebalance() public {
         address unauthorizedAgent = address(0x2);
         vm.prank(unauthorizedAgent);
@@ -104,12 +270,8 @@ contract TokenizedFundManagerTest is Test {
 
         // Execute multiple rebalances with valid folded proof
         for (uint256 i = 0; i < 3; i++) {
-            uint256[28] memory folded2;
-            for (uint256 j = 0; j < 28; j++) {
-                folded2[j] = 100 + i * 28 + j;
-            }
-            bytes memory proofBundle2 = abi.encode(folded2);
-            fundManager.executeRebalance(proofBundle2, abi.encode("metadata", i));
+            bytes memory proofBundle2 = _q3Proof(100 + i * 32);
+            fundManager.executeRebalance(proofBundle2, _q3Metadata());
         }
 
         vm.stopPrank();
@@ -123,15 +285,11 @@ contract TokenizedFundManagerTest is Test {
         vm.startPrank(agent);
 
         // Create valid folded proof
-        uint256[28] memory folded3;
-        for (uint256 i = 0; i < 28; i++) {
-            folded3[i] = i + 1;
-        }
-        bytes memory proofBundle = abi.encode(folded3);
+        bytes memory proofBundle = _q3Proof(1);
 
         // Execute up to limit
         for (uint256 i = 0; i < 10; i++) {
-            fundManager.executeRebalance(proofBundle, abi.encode("metadata", i));
+            fundManager.executeRebalance(proofBundle, _q3Metadata());
         }
 
         // 11th should fail
@@ -141,23 +299,18 @@ contract TokenizedFundManagerTest is Test {
                 "Daily rebalance limit exceeded"
             )
         );
-        fundManager.executeRebalance(proofBundle, abi.encode("metadata", 11));
+        fundManager.executeRebalance(proofBundle, _q3Metadata());
 
         vm.stopPrank();
     }
 
     function testNovaVerifierIntegration() public {
-        // Create folded proof with valid Nova proof format (28 uint256 values)
-        uint256[28] memory validNovaProof;
-        for (uint256 i = 0; i < 28; i++) {
-            validNovaProof[i] = i + 100; // Dummy values
-        }
-
-        bytes memory proofBundle = abi.encode(validNovaProof);
+        // Create folded proof with valid Nova proof format (36 uint256 values)
+        bytes memory proofBundle = _q3Proof(100); // Dummy values
 
         // Should succeed with mock verifier returning true
         vm.prank(agent);
-        bool success = fundManager.executeRebalance(proofBundle, abi.encode("metadata"));
+        bool success = fundManager.executeRebalance(proofBundle, _q3Metadata());
         assertTrue(success);
 
         // Now make verifier fail
@@ -166,7 +319,7 @@ contract TokenizedFundManagerTest is Test {
         // Should revert with ProofVerificationFailed
         vm.prank(agent);
         vm.expectRevert(TokenizedFundManager.ProofVerificationFailed.selector);
-        fundManager.executeRebalance(proofBundle, abi.encode("metadata2"));
+        fundManager.executeRebalance(proofBundle, _q3Metadata());
     }
 
     // Position/Whitelist verifier integration now folded into a single proof path
@@ -183,4 +336,108 @@ contract TokenizedFundManagerTest is Test {
     }
 
     // Invalid length for folded proof already tested above
+
+    function testRebalanceRejectsTamperedReports() public {
+        bytes memory proofBundle = _q3Proof(1);
+
+        // A report not encrypted by the circuit does not match the reportsHash of the proof
+        TokenizedFundManager.Ciphertext[] memory reports = _reports(Q3_DAYS);
+        reports[0].payload[0] += 1;
+        uint256 reportsHash = _reportsHash(reports);
+
+        vm.prank(agent);
+        vm.expectRevert(
+            abi.encodeWithSelector(TokenizedFundManager.ReportsMismatch.selector, reportsHash)
+        );
+        fundManager.executeRebalance(proofBundle, abi.encode(reports, _prices(Q3_DAYS)));
+    }
+
+    function testRebalanceRejectsMissingReports() public {
+        bytes memory proofBundle = _q3Proof(1);
+
+        // Dropping the report of the last period breaks the chain
+        TokenizedFundManager.Ciphertext[] memory reports = _reports(Q3_DAYS - 1);
+        uint256 reportsHash = _reportsHash(reports);
+
+        vm.prank(agent);
+        vm.expectRevert(
+            abi.encodeWithSelector(TokenizedFundManager.ReportsMismatch.selector, reportsHash)
+        );
+        fundManager.executeRebalance(proofBundle, abi.encode(reports, _prices(Q3_DAYS)));
+    }
+
+    function testRebalanceRejectsUnpublishedPrices() public {
+        // Prices signed with the key of the circuit but never published by the pricing oracle
+        uint256[] memory prices = _prices(Q3_DAYS);
+        prices[0] = 42;
+        bytes memory proofBundle = abi.encode(
+            _foldedProof(
+                Q3_START, Q3_END, Q3_DAYS, _pricesHash(prices), _reportsHash(_reports(Q3_DAYS)), 1
+            )
+        );
+
+        vm.prank(agent);
+        vm.expectRevert(
+            abi.encodeWithSelector(TokenizedFundManager.UnpublishedPrices.selector, 42)
+        );
+        fundManager.executeRebalance(proofBundle, abi.encode(_reports(Q3_DAYS), prices));
+    }
+
+    function testRebalanceRejectsMismatchedPrices() public {
+        bytes memory proofBundle = _q3Proof(1);
+
+        // Published prices of other periods do not match the pricesHash of the proof
+        uint256[] memory prices = _prices(Q3_DAYS);
+        (prices[0], prices[1]) = (prices[1], prices[0]);
+        uint256 pricesHash = _pricesHash(prices);
+
+        vm.prank(agent);
+        vm.expectRevert(
+            abi.encodeWithSelector(TokenizedFundManager.PricesMismatch.selector, pricesHash)
+        );
+        fundManager.executeRebalance(proofBundle, abi.encode(_reports(Q3_DAYS), prices));
+    }
+
+    function testOnlyPricingOracleCanPublishPrices() public {
+        vm.prank(agent);
+        vm.expectRevert(TokenizedFundManager.Unauthorized.selector);
+        fundManager.publishPrices(42);
+        assertFalse(fundManager.publishedPrices(42));
+    }
+
+    function testVerifyCoverage() public {
+        uint256 periods = fundManager.verifyCoverage(_q3Proof(1), Q3_START, Q3_END);
+        assertEq(periods, Q3_DAYS);
+    }
+
+    function testVerifyCoverageMismatch() public {
+        // The proof stops one day before the end of Q3
+        bytes memory proofBundle =
+            abi.encode(_foldedProof(Q3_START, Q3_END - 1 days, Q3_DAYS - 1, 0, 0, 1));
+
+        vm.expectRevert(
+            abi.encodeWithSelector(
+                TokenizedFundManager.CoverageMismatch.selector,
+                Q3_START,
+                Q3_END - 1 days
+            )
+        );
+        fundManager.verifyCoverage(proofBundle, Q3_START, Q3_END);
+    }
+
+    function testVerifyCoverageRejectsNonEmptyInitialState() public {
+        // A proof folded from a non-empty initial state does not attest its first period
+        uint256[36] memory folded = _foldedProof(Q3_START, Q3_END, Q3_DAYS, 0, 0, 1);
+        folded[1] = Q3_START;
+
+        vm.expectRevert(TokenizedFundManager.InvalidProof.selector);
+        fundManager.verifyCoverage(abi.encode(folded), Q3_START, Q3_END);
+    }
+
+    function testVerifyCoverageFailsWithInvalidProof() public {
+        mockVerifier.setShouldPass(false);
+
+        vm.expectRevert(TokenizedFundManager.ProofVerificationFailed.selector);
+        fundManager.verifyCoverage(_q3Proof(1), Q3_START, Q3_END);
+    }
 }
@@ -0,0 +1,361 @@
+//! 256-bit token amounts with decimals, as held by ERC-20 contracts, and their in-circuit
+//! representation as `NonNativeUintVar`s.
+//!
+//! Amounts of different tokens are compared after being normalised to `NORMALIZED_DECIMALS`
+//! decimals. In-circuit, all the arithmetic is done over the integers: the limbs of a
+//! `NonNativeUintVar` carry an upper bound which grows with each operation, so sums and products
+//! never wrap around the field modulus (operations whose bound would exceed it fail with
+//! `SynthesisError::Unsatisfiable` instead).
+use ark_ff::{BigInt, BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::borrow::Borrow;
+use folding_schemes::folding::circuits::nonnative::uint::NonNativeUintVar;
+use num_bigint::BigUint;
+
+/// Unsigned 256-bit integer, the type of ERC-20 balances
+pub type U256 = BigInt<4>;
+
+/// Number of bits of raw token amounts
+pub const AMOUNT_BITS: usize = 256;
+/// Number of decimals of normalised amounts, the most used by ERC-20 tokens
+pub const NORMALIZED_DECIMALS: u8 = 18;
+
+/// Rounding of the results of divisions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards zero
+    #[default]
+    Down,
+    /// Away from zero
+    Up,
+}
+
+/// Returns `x / d`, rounded according to `rounding`
+pub fn div_round(x: &BigUint, d: &BigUint, rounding: Rounding) -> BigUint {
+    match rounding {
+        Rounding::Down => x / d,
+        Rounding::Up => (x + d - 1u8) / d,
+    }
+}
+
+/// Amount of a token, in units of `10^-decimals` tokens
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TokenAmount {
+    pub value: U256,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn new(value: U256, decimals: u8) -> Self {
+        Self { value, decimals }
+    }
+
+    /// Returns the amount of `units` whole tokens, or `None` if it doesn't fit in 256 bits
+    pub fn from_units(units: u64, decimals: u8) -> Option<Self> {
+        let value = BigUint::from(units) * BigUint::from(10u8).pow(decimals as u32);
+        Some(Self::new(U256::try_from(value).ok()?, decimals))
+    }
+
+    /// Returns the number of whole tokens, rounded down
+    pub fn units(&self) -> BigUint {
+        BigUint::from(self.value) / BigUint::from(10u8).pow(self.decimals as u32)
+    }
+
+    /// Returns the amount with `NORMALIZED_DECIMALS` decimals, or `None` if the token has more
+    /// decimals than that. The result may not fit in 256 bits.
+    pub fn normalized(&self) -> Option<BigUint> {
+        let shift = NORMALIZED_DECIMALS.checked_sub(self.decimals)?;
+        Some(BigUint::from(self.value) * BigUint::from(10u8).pow(shift as u32))
+    }
+
+    /// Returns `self + other`, or `None` if the amounts have different decimals or if the sum
+    /// doesn't fit in 256 bits
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        if self.decimals != other.decimals {
+            return None;
+        }
+        let value = BigUint::from(self.value) + BigUint::from(other.value);
+        Some(Self::new(U256::try_from(value).ok()?, self.decimals))
+    }
+
+    /// Returns `self · k`, or `None` if the product doesn't fit in 256 bits
+    pub fn checked_mul(&self, k: u64) -> Option<Self> {
+        let value = BigUint::from(self.value) * BigUint::from(k);
+        Some(Self::new(U256::try_from(value).ok()?, self.decimals))
+    }
+}
+
+/// Returns the constant `x` as a `NonNativeUintVar` whose limbs are bounded by `x`'s bit length
+fn constant<F: PrimeField>(x: &BigUint) -> Result<NonNativeUintVar<F>, SynthesisError> {
+    // `0` still gets a limb, so that products with it are well-formed
+    let bits = (0..x.bits().max(1)).map(|i| x.bit(i)).collect::<Vec<_>>();
+    Ok(NonNativeUintVar::from(Vec::<Boolean<F>>::new_constant(
+        ConstraintSystemRef::None,
+        bits,
+    )?))
+}
+
+/// Allocates the `length` low bits of `x` as witnesses, or as constants if `cs` is `None`
+pub(crate) fn hint_bits<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    x: impl FnOnce() -> BigUint,
+    length: u64,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let mode = if cs.is_none() {
+        AllocationMode::Constant
+    } else {
+        AllocationMode::Witness
+    };
+    Vec::<Boolean<F>>::new_variable(
+        cs,
+        || {
+            let x = x();
+            Ok((0..length).map(|i| x.bit(i)).collect::<Vec<_>>())
+        },
+        mode,
+    )
+}
+
+/// In-circuit non-negative integer, starting as a 256-bit token amount and growing as it is added
+/// to or multiplied with other amounts. The decimals are not part of the variable, as they are a
+/// property of the token fixed by the circuit rather than chosen by the prover.
+#[derive(Clone, Debug)]
+pub struct AmountVar<F: PrimeField>(pub NonNativeUintVar<F>);
+
+impl<F: PrimeField> AmountVar<F> {
+    /// Builds the amount from its little-endian bits
+    pub fn from_bits(bits: &[Boolean<F>]) -> Self {
+        Self(NonNativeUintVar::from(bits))
+    }
+
+    pub fn constant(x: &BigUint) -> Result<Self, SynthesisError> {
+        Ok(Self(constant(x)?))
+    }
+
+    /// Converts an amount of a token with `decimals` decimals to `NORMALIZED_DECIMALS` decimals
+    pub fn normalize(&self, decimals: u8) -> Result<Self, SynthesisError> {
+        let shift = NORMALIZED_DECIMALS
+            .checked_sub(decimals)
+            .ok_or(SynthesisError::Unsatisfiable)?;
+        self.mul_constant(&BigUint::from(10u8).pow(shift as u32))
+    }
+
+    pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self(self.0.add_no_align(&other.0)?))
+    }
+
+    pub fn mul(&self, other: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self(self.0.mul_no_align(&other.0)?))
+    }
+
+    pub fn mul_constant(&self, k: &BigUint) -> Result<Self, SynthesisError> {
+        Ok(Self(self.0.mul_no_align(&constant(k)?)?))
+    }
+
+    /// Returns `self / d`, rounded according to `rounding`, by witnessing the quotient `q` and
+    /// the remainder `r` such that `self = q·d + r` and `r < d` (`self + d - 1` when rounding up).
+    /// Fails with `SynthesisError::Unsatisfiable` if `d` is zero.
+    pub fn div_constant(&self, d: &BigUint, rounding: Rounding) -> Result<Self, SynthesisError> {
+        if d.bits() == 0 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let d_minus_one = Self::constant(&(d - 1u8))?;
+        let dividend = match rounding {
+            Rounding::Down => self.clone(),
+            Rounding::Up => self.add(&d_minus_one)?,
+        };
+
+        let cs = dividend.cs();
+        let value = || dividend.value().unwrap_or_default();
+        let q = Self::from_bits(&hint_bits(
+            cs.clone(),
+            || value() / d,
+            dividend.0.ubound().bits().max(1),
+        )?);
+        let r = Self::from_bits(&hint_bits(cs, || value() % d, d.bits())?);
+
+        r.enforce_le(&d_minus_one)?;
+        q.mul_constant(d)?.add(&r)?.enforce_equal(&dividend)?;
+        Ok(q)
+    }
+
+    /// Returns the little-endian bits of the amount, as many as the bits of its upper bound
+    pub fn to_bits_le(&self) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        let bits = hint_bits(
+            self.cs(),
+            || self.value().unwrap_or_default(),
+            self.0.ubound().bits(),
+        )?;
+        Self::from_bits(&bits).enforce_equal(self)?;
+        Ok(bits)
+    }
+
+    /// Enforces that `self == other` as integers
+    pub fn enforce_equal(&self, other: &Self) -> Result<(), SynthesisError> {
+        // `enforce_equal_unaligned` treats the extra limbs of its second operand as the final
+        // carry, so the operand with more limbs goes last
+        if self.0 .0.len() > other.0 .0.len() {
+            other.0.enforce_equal_unaligned(&self.0)
+        } else {
+            self.0.enforce_equal_unaligned(&other.0)
+        }
+    }
+
+    /// Enforces that `self <= other`, by enforcing that `other - self` has a bit decomposition
+    pub fn enforce_le(&self, other: &Self) -> Result<(), SynthesisError> {
+        let diff = hint_bits(
+            self.cs().or(other.cs()),
+            || {
+                let (x, y) = (
+                    self.value().unwrap_or_default(),
+                    other.value().unwrap_or_default(),
+                );
+                if y > x {
+                    y - x
+                } else {
+                    BigUint::default()
+                }
+            },
+            other.0.ubound().bits(),
+        )?;
+        self.add(&Self::from_bits(&diff))?.enforce_equal(other)
+    }
+}
+
+impl<F: PrimeField> GR1CSVar<F> for AmountVar<F> {
+    type Value = BigUint;
+
+    fn cs(&self) -> ConstraintSystemRef<F> {
+        self.0.cs()
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        self.0.value()
+    }
+}
+
+/// Allocates a 256-bit amount, enforcing its range when allocated as a witness
+impl<F: PrimeField> AllocVar<U256, F> for AmountVar<F> {
+    fn new_variable<T: Borrow<U256>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into().cs();
+        let bits = Vec::<Boolean<F>>::new_variable(cs, || Ok(f()?.borrow().to_bits_le()), mode)?;
+        Ok(Self::from_bits(&bits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_relations::gr1cs::ConstraintSystem;
+
+    use super::*;
+
+    #[test]
+    fn test_token_amount() {
+        // $100M of an 18-decimals stablecoin doesn't fit in a u64
+        let amount = TokenAmount::from_units(100_000_000, 18).unwrap();
+        assert!(BigUint::from(amount.value) > BigUint::from(u64::MAX));
+        assert_eq!(amount.units(), BigUint::from(100_000_000u64));
+
+        let usdc = TokenAmount::from_units(100_000_000, 6).unwrap();
+        assert_eq!(usdc.normalized(), amount.normalized());
+        assert_eq!(TokenAmount::new(U256::from(1u64), 24).normalized(), None);
+
+        let max = TokenAmount::new(
+            U256::try_from((BigUint::from(1u8) << 256) - 1u8).unwrap(),
+            0,
+        );
+        assert_eq!(
+            max.checked_add(&TokenAmount::new(U256::from(1u64), 0)),
+            None
+        );
+        assert_eq!(max.checked_mul(2), None);
+        assert_eq!(usdc.checked_add(&amount), None);
+        assert_eq!(
+            usdc.checked_add(&usdc),
+            TokenAmount::from_units(200_000_000, 6)
+        );
+    }
+
+    #[test]
+    fn test_amount_gadget() -> Result<(), SynthesisError> {
+        let max = U256::try_from((BigUint::from(1u8) << 256) - 1u8).unwrap();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = AmountVar::new_witness(cs.clone(), || Ok(max))?;
+        let y = AmountVar::new_witness(cs.clone(), || Ok(max))?;
+
+        // no overflow, neither in the sum nor in the product
+        let sum = x.add(&y)?;
+        let product = x.mul(&y)?.mul_constant(&BigUint::from(100u8))?;
+        assert_eq!(sum.value()?, BigUint::from(max) * 2u8);
+        assert_eq!(
+            product.value()?,
+            BigUint::from(max) * BigUint::from(max) * 100u8
+        );
+        x.enforce_le(&sum)?;
+        sum.enforce_le(&product)?;
+        let bits = product.to_bits_le()?;
+        assert_eq!(AmountVar::from_bits(&bits).value()?, product.value()?);
+        assert!(cs.is_satisfied()?);
+
+        product.enforce_le(&sum)?;
+        assert!(!cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_div_gadget() -> Result<(), SynthesisError> {
+        let d = BigUint::from(10u8).pow(18);
+        for x in [
+            BigUint::default(),
+            d.clone(),
+            &d * 7u8 + 1u8,
+            (BigUint::from(1u8) << 256) - 1u8,
+        ] {
+            for rounding in [Rounding::Down, Rounding::Up] {
+                let cs = ConstraintSystem::<Fr>::new_ref();
+                let value = U256::try_from(x.clone()).unwrap();
+                let x_var = AmountVar::new_witness(cs.clone(), || Ok(value))?;
+                let q = x_var.div_constant(&d, rounding)?;
+                assert!(cs.is_satisfied()?);
+                assert_eq!(q.value()?, div_round(&x, &d, rounding));
+            }
+        }
+        assert_eq!(
+            div_round(&BigUint::from(7u8), &BigUint::from(2u8), Rounding::Up),
+            BigUint::from(4u8)
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = AmountVar::new_witness(cs, || Ok(U256::from(1u64)))?;
+        assert!(x.div_constant(&BigUint::default(), Rounding::Down).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_gadget() -> Result<(), SynthesisError> {
+        for (usdc, dai, expected) in [(100u64, 100u64, true), (100, 99, false)] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let usdc = TokenAmount::from_units(usdc, 6).unwrap();
+            let dai = TokenAmount::from_units(dai, 18).unwrap();
+            let usdc = AmountVar::new_witness(cs.clone(), || Ok(usdc.value))?.normalize(6)?;
+            let dai = AmountVar::new_witness(cs.clone(), || Ok(dai.value))?.normalize(18)?;
+            usdc.enforce_le(&dai)?;
+            assert_eq!(cs.is_satisfied()?, expected);
+        }
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = AmountVar::new_witness(cs, || Ok(U256::from(1u64)))?;
+        assert!(x.normalize(24).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+//! Balance attestations signed by an oracle (eg. the fund's custodian), so that the balances used
+//! by the compliance circuits are bound to the oracle's public key instead of being chosen by the
+//! prover.
+//!
+//! Attestations are signed with EdDSA over BabyJubJub (see `crate::eddsa`). Oracles that can only
+//! sign with secp256k1 keys sign `BalanceAttestation::digest` with ECDSA instead, which is
+//! verified by `crate::ecdsa::verify_gadget` at a much higher cost.
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    fields::fp::FpVar,
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{Namespace, SynthesisError};
+use ark_std::borrow::Borrow;
+
+use crate::{
+    amount::{AmountVar, TokenAmount, AMOUNT_BITS},
+    eddsa::{self, PublicKeyVar, Signature, SignatureVar, SigningKey},
+    gadgets::enforce_bit_length,
+};
+
+/// Number of bits of the attested timestamps
+pub const TIMESTAMP_BITS: usize = 64;
+/// Number of bits of the halves of the balance in the signed message, which fit in a field element
+const BALANCE_WORD_BITS: usize = AMOUNT_BITS / 2;
+/// Number of bits of the decimals of the balance
+const DECIMALS_BITS: usize = 8;
+
+/// Balance of `account` at `timestamp` (unix time, in seconds), as attested by an oracle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BalanceAttestation {
+    /// Identifier of the attested account (eg. the hash of its address)
+    pub account: Fr,
+    pub balance: TokenAmount,
+    pub timestamp: u64,
+}
+
+impl BalanceAttestation {
+    /// Returns the signed message, `[account, balance_lo, balance_hi, decimals, timestamp]`, where
+    /// `balance_lo` and `balance_hi` are the low and high 128 bits of the balance.
+    pub fn to_field_elements(&self) -> Vec<Fr> {
+        let bits = self.balance.value.to_bits_le();
+        let [lo, hi] = [&bits[..BALANCE_WORD_BITS], &bits[BALANCE_WORD_BITS..]]
+            .map(|word| Fr::from_bigint(<Fr as PrimeField>::BigInt::from_bits_le(word)).unwrap());
+        vec![
+            self.account,
+            lo,
+            hi,
+            Fr::from(self.balance.decimals),
+            Fr::from(self.timestamp),
+        ]
+    }
+
+    /// Returns the Poseidon hash of the attestation, to be signed with ECDSA
+    pub fn digest(&self, poseidon_config: &PoseidonConfig<Fr>) -> Fr {
+        let mut sponge = PoseidonSponge::new(poseidon_config);
+        sponge.absorb(&self.to_field_elements());
+        sponge.squeeze_field_elements(1)[0]
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SignedAttestation {
+    pub attestation: BalanceAttestation,
+    pub signature: Signature,
+}
+
+impl SignedAttestation {
+    pub fn sign(
+        oracle: &SigningKey,
+        poseidon_config: &PoseidonConfig<Fr>,
+        attestation: BalanceAttestation,
+    ) -> Self {
+        Self {
+            attestation,
+            signature: oracle.sign(poseidon_config, &attestation.to_field_elements()),
+        }
+    }
+
+    /// Verifies natively the signature of the attestation under the oracle's public key
+    pub fn verify(&self, poseidon_config: &PoseidonConfig<Fr>, oracle: &eddsa::PublicKey) -> bool {
+        eddsa::verify(
+            poseidon_config,
+            oracle,
+            &self.attestation.to_field_elements(),
+            &self.signature,
+        )
+    }
+}
+
+/// In-circuit representation of a `BalanceAttestation`. The balance is allocated from its 256 bits,
+/// and the decimals and the timestamp are enforced to fit in 8 and `TIMESTAMP_BITS` bits when
+/// allocated as witnesses.
+#[derive(Clone, Debug)]
+pub struct BalanceAttestationVar {
+    pub account: FpVar<Fr>,
+    pub balance: AmountVar<Fr>,
+    /// Low and high 128 bits of the balance, as absorbed by the signature
+    balance_words: [FpVar<Fr>; 2],
+    pub decimals: FpVar<Fr>,
+    pub timestamp: FpVar<Fr>,
+}
+
+impl BalanceAttestationVar {
+    pub fn to_field_elements(&self) -> Vec<FpVar<Fr>> {
+        let [lo, hi] = self.balance_words.clone();
+        vec![
+            self.account.clone(),
+            lo,
+            hi,
+            self.decimals.clone(),
+            self.timestamp.clone(),
+        ]
+    }
+
+    /// Computes in-circuit the Poseidon hash of the attestation
+    pub fn digest(
+        &self,
+        poseidon_config: &PoseidonConfig<Fr>,
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        let message = self.to_field_elements();
+        let mut sponge = PoseidonSpongeVar::new(message.cs(), poseidon_config);
+        sponge.absorb(&message)?;
+        Ok(sponge.squeeze_field_elements(1)?.remove(0))
+    }
+}
+
+impl AllocVar<BalanceAttestation, Fr> for BalanceAttestationVar {
+    fn new_variable<T: Borrow<BalanceAttestation>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|attestation| {
+            let cs = cs.into().cs();
+            let attestation = attestation.borrow();
+
+            let account = FpVar::new_variable(cs.clone(), || Ok(attestation.account), mode)?;
+            let bits = Vec::<Boolean<Fr>>::new_variable(
+                cs.clone(),
+                || Ok(attestation.balance.value.to_bits_le()),
+                mode,
+            )?;
+            let balance_words = [
+                Boolean::le_bits_to_fp(&bits[..BALANCE_WORD_BITS])?,
+                Boolean::le_bits_to_fp(&bits[BALANCE_WORD_BITS..])?,
+            ];
+            let decimals = FpVar::new_variable(
+                cs.clone(),
+                || Ok(Fr::from(attestation.balance.decimals)),
+                mode,
+            )?;
+            let timestamp = FpVar::new_variable(cs, || Ok(Fr::from(attestation.timestamp)), mode)?;
+            if mode == AllocationMode::Witness {
+                enforce_bit_length(&decimals, DECIMALS_BITS)?;
+                enforce_bit_length(&timestamp, TIMESTAMP_BITS)?;
+            }
+
+            Ok(Self {
+                account,
+                balance: AmountVar::from_bits(&bits),
+                balance_words,
+                decimals,
+                timestamp,
+            })
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SignedAttestationVar {
+    pub attestation: BalanceAttestationVar,
+    pub signature: SignatureVar,
+}
+
+impl SignedAttestationVar {
+    /// Enforces that the attestation is signed by the oracle
+    pub fn verify(
+        &self,
+        poseidon_config: &PoseidonConfig<Fr>,
+        oracle: &PublicKeyVar,
+    ) -> Result<(), SynthesisError> {
+        eddsa::verify_gadget(
+            poseidon_config,
+            oracle,
+            &self.attestation.to_field_elements(),
+            &self.signature,
+        )
+    }
+}
+
+impl AllocVar<SignedAttestation, Fr> for SignedAttestationVar {
+    fn new_variable<T: Borrow<SignedAttestation>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|signed| {
+            let cs = cs.into().cs();
+            let signed = signed.borrow();
+
+            Ok(Self {
+                attestation: BalanceAttestationVar::new_variable(
+                    cs.clone(),
+                    || Ok(signed.attestation),
+                    mode,
+                )?,
+                signature: SignatureVar::new_variable(cs, || Ok(signed.signature), mode)?,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::{test_rng, UniformRand};
+    use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+
+    use super::*;
+    use crate::ecdsa;
+
+    #[test]
+    fn test_signed_attestation() -> Result<(), SynthesisError> {
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let oracle = SigningKey::rand(&mut rng);
+        let attestation = BalanceAttestation {
+            account: Fr::rand(&mut rng),
+            balance: TokenAmount::from_units(10_000_000, 6).unwrap(),
+            timestamp: 1_751_328_000,
+        };
+        let signed = SignedAttestation::sign(&oracle, &poseidon_config, attestation);
+        assert!(signed.verify(&poseidon_config, &oracle.public_key()));
+
+        let check = |signed: SignedAttestation| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let oracle = PublicKeyVar::new_constant(cs.clone(), oracle.public_key())?;
+            let signed = SignedAttestationVar::new_witness(cs.clone(), || Ok(signed))?;
+            signed.verify(&poseidon_config, &oracle)?;
+            cs.is_satisfied()
+        };
+        assert!(check(signed)?);
+
+        // the prover can't change the attested balance
+        let mut tampered = signed;
+        tampered.attestation.balance.value = TokenAmount::from_units(10_000_001, 6).unwrap().value;
+        assert!(!signed.verify(&poseidon_config, &SigningKey::rand(&mut rng).public_key()));
+        assert!(!tampered.verify(&poseidon_config, &oracle.public_key()));
+        assert!(!check(tampered)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_digest_gadget() -> Result<(), SynthesisError> {
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let attestation = BalanceAttestation {
+            account: Fr::rand(&mut rng),
+            balance: TokenAmount::from_units(u64::MAX, 18).unwrap(),
+            timestamp: 1_751_328_000,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let attestation_var = BalanceAttestationVar::new_witness(cs.clone(), || Ok(attestation))?;
+        let digest = attestation_var.digest(&poseidon_config)?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(digest.value()?, attestation.digest(&poseidon_config));
+
+        // the digest is what secp256k1 oracles sign
+        let oracle = ecdsa::SigningKey::rand(&mut rng);
+        let z = ecdsa::digest_to_scalar(attestation.digest(&poseidon_config));
+        assert!(ecdsa::verify(
+            &oracle.public_key(),
+            z,
+            &oracle.sign(z, &mut rng)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,816 @@
+//! Composite fund compliance circuit, folded with Nova over consecutive reporting periods.
+//!
+//! Each step proves, for the period `[period_start, period_end)`:
+//! 1. Position limit: `value(asset) / NAV ≤ max_position_pct / 100` for each asset but the liquid
+//!    one
+//! 2. Liquidity: `value(liquid_asset) / NAV ≥ min_liquidity_pct / 100`
+//! 3. Whitelist: `asset_hash` is in the Poseidon Merkle tree with root `merkle_root` (see
+//!    `crate::merkle`)
+//!
+//! The values are not chosen by the prover: the holdings of each asset come with a
+//! `SignedAttestation` of the oracle whose public key is fixed in the circuit params, for the
+//! expected account and a timestamp within the period, and the prices with a `SignedPrices` of the
+//! pricing oracle. The value of each asset and the NAV are then derived in-circuit (see
+//! `crate::nav`).
+//!
+//! The folded state `[first_period, last_period, count, prices_hash]` tells which time window the
+//! proof covers, as each period must start where the previous one ended, and lasts at most
+//! `CompositeFundParams::max_period_len`. `prices_hash` chains the commitments to the price vectors
+//! of the periods (see `chain_prices`), so that the contract can check that the prices are the ones
+//! the pricing oracle published, and not only that they are signed by the key of the circuit.
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::borrow::Borrow;
+use folding_schemes::{frontend::FCircuit, transcript::poseidon::poseidon_canonical_config, Error};
+use num_bigint::BigUint;
+
+use crate::{
+    amount::{AmountVar, TokenAmount},
+    attestation::{BalanceAttestation, SignedAttestation, SignedAttestationVar, TIMESTAMP_BITS},
+    eddsa::{self, PublicKeyVar, SigningKey},
+    gadgets::{enforce_bit_length, enforce_le},
+    merkle::{MerkleProof, MerkleProofVar},
+    nav::{net_asset_value_gadget, PriceVector, SignedPrices, SignedPricesVar},
+};
+
+/// Default maximum length of a reporting period (one day), so that a proof covering a window of
+/// `n` days folds at least `n` periods, each checked against the holdings of its own day
+pub const MAX_PERIOD_LEN: u64 = 86_400;
+
+/// Depth of the whitelist of assets
+pub const WHITELIST_DEPTH: usize = 1;
+
+/// Account holding one of the fund's assets, whose balance is attested by the oracle
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AttestedAccount {
+    /// Identifier of the account, as signed by the oracle
+    pub id: Fr,
+    /// Decimals of the token held by the account
+    pub decimals: u8,
+}
+
+/// Composite Fund Compliance Circuit Parameters for a fund holding `N` assets
+/// Combines all three compliance checks in a single circuit
+#[derive(Clone, Copy, Debug)]
+pub struct CompositeFundParams<const N: usize> {
+    // Position limit check, in percent (at most 100), for each asset but the liquid one
+    pub max_position_pct: u64,
+
+    // Liquidity check, in percent (at most 100)
+    pub min_liquidity_pct: u64,
+
+    // Whitelist check: membership proof of `asset_hash` in the tree with root `merkle_root`
+    pub asset_hash: Fr,
+    pub whitelist_proof: MerkleProof<WHITELIST_DEPTH>,
+    pub merkle_root: Fr,
+
+    // Oracle attesting the holdings of the fund's accounts, one per asset
+    pub oracle: eddsa::PublicKey,
+    pub accounts: [AttestedAccount; N],
+    // Index in `accounts` of the liquid asset (eg. USDC)
+    pub liquid_asset: usize,
+
+    // Oracle publishing the prices of the assets
+    pub pricing_oracle: eddsa::PublicKey,
+
+    // Maximum length of a reporting period, in seconds, eg. `MAX_PERIOD_LEN`. A fund reporting on
+    // business days only can allow e.g. 4 days, so that a single period spans a long weekend
+    pub max_period_len: u64,
+}
+
+/// Holdings of the fund during a reporting period, each in the token of its account, and the
+/// prices of the assets in USD with `PRICE_DECIMALS` decimals
+#[derive(Clone, Copy, Debug)]
+pub struct FundSnapshot<const N: usize> {
+    pub holdings: [TokenAmount; N],
+    pub prices: [u128; N],
+}
+
+/// External inputs of a step: the reporting period, the oracle-signed holdings and the prices
+/// signed by the pricing oracle
+#[derive(Clone, Copy, Debug)]
+pub struct CompositeStepInputs<const N: usize> {
+    pub period_start: u64,
+    pub period_end: u64,
+    pub holdings: [SignedAttestation; N],
+    pub prices: SignedPrices<N>,
+}
+
+impl<const N: usize> Default for CompositeStepInputs<N> {
+    fn default() -> Self {
+        Self {
+            period_start: 0,
+            period_end: 0,
+            holdings: [SignedAttestation::default(); N],
+            prices: SignedPrices::default(),
+        }
+    }
+}
+
+impl<const N: usize> CompositeStepInputs<N> {
+    /// Builds the inputs of the period `[period_start, period_end)`, where the holdings of the
+    /// `snapshot` are attested by `oracle` and its prices by `pricing_oracle` at `period_start`.
+    pub fn attest(
+        oracle: &SigningKey,
+        pricing_oracle: &SigningKey,
+        poseidon_config: &PoseidonConfig<Fr>,
+        accounts: &[AttestedAccount; N],
+        [period_start, period_end]: [u64; 2],
+        snapshot: &FundSnapshot<N>,
+    ) -> Self {
+        let sign = |account: &AttestedAccount, balance| {
+            SignedAttestation::sign(
+                oracle,
+                poseidon_config,
+                BalanceAttestation {
+                    account: account.id,
+                    balance,
+                    timestamp: period_start,
+                },
+            )
+        };
+        Self {
+            period_start,
+            period_end,
+            holdings: core::array::from_fn(|i| sign(&accounts[i], snapshot.holdings[i])),
+            prices: SignedPrices::sign(
+                pricing_oracle,
+                poseidon_config,
+                PriceVector {
+                    prices: snapshot.prices,
+                    timestamp: period_start,
+                },
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CompositeStepInputsVar {
+    pub period_start: FpVar<Fr>,
+    pub period_end: FpVar<Fr>,
+    pub holdings: Vec<SignedAttestationVar>,
+    pub prices: SignedPricesVar,
+}
+
+impl CompositeStepInputsVar {
+    /// Enforces that the period `[period_start, period_end)` is non-empty, lasts at most
+    /// `max_period_len`, and follows the window
+    /// `[first_period, last_period)` of the state `z_i = [first_period, last_period, count]`, which
+    /// is empty if `count` is zero. Returns the first period of the extended window.
+    pub fn enforce_period(
+        &self,
+        z_i: &[FpVar<Fr>],
+        max_period_len: u64,
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        let (first_period, last_period, counter) = (&z_i[0], &z_i[1], &z_i[2]);
+        let (period_start, period_end) = (&self.period_start, &self.period_end);
+
+        enforce_bit_length(period_start, TIMESTAMP_BITS)?;
+        enforce_bit_length(period_end, TIMESTAMP_BITS)?;
+        // period_end > period_start, ie. period_end - period_start - 1 does not wrap around
+        enforce_bit_length(&(period_end - period_start - FpVar::one()), TIMESTAMP_BITS)?;
+        enforce_le(
+            &(period_end - period_start),
+            &FpVar::constant(Fr::from(max_period_len)),
+            TIMESTAMP_BITS,
+        )?;
+
+        // The first period opens the window, the next ones must start where the last one ended
+        let is_first = counter.is_zero()?;
+        period_start.conditional_enforce_equal(last_period, &!&is_first)?;
+        is_first.select(period_start, first_period)
+    }
+
+    /// Enforces that the holdings of the `accounts` are attested by `oracle`, and that the prices
+    /// are published by `pricing_oracle`, both during the period. Returns the commitment to the
+    /// prices.
+    pub fn verify(
+        &self,
+        poseidon_config: &PoseidonConfig<Fr>,
+        oracle: &PublicKeyVar,
+        accounts: &[AttestedAccount],
+        pricing_oracle: &PublicKeyVar,
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        // period_start <= timestamp < period_end
+        let enforce_within_period = |timestamp: &FpVar<Fr>| {
+            enforce_le(&self.period_start, timestamp, TIMESTAMP_BITS)?;
+            enforce_le(
+                &(timestamp + FpVar::one()),
+                &self.period_end,
+                TIMESTAMP_BITS,
+            )
+        };
+
+        if self.holdings.len() != accounts.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        for (signed, account) in self.holdings.iter().zip(accounts) {
+            signed.verify(poseidon_config, oracle)?;
+            let attestation = &signed.attestation;
+            attestation
+                .account
+                .enforce_equal(&FpVar::constant(account.id))?;
+            attestation
+                .decimals
+                .enforce_equal(&FpVar::constant(Fr::from(account.decimals)))?;
+            enforce_within_period(&attestation.timestamp)?;
+        }
+
+        let prices_commitment = self.prices.verify(poseidon_config, pricing_oracle)?;
+        enforce_within_period(&self.prices.prices.timestamp)?;
+        Ok(prices_commitment)
+    }
+
+    /// Returns the values of the holdings of the `accounts` and their sum, the NAV, which is
+    /// enforced to be positive
+    pub fn valuation(
+        &self,
+        accounts: &[AttestedAccount],
+    ) -> Result<(Vec<AmountVar<Fr>>, AmountVar<Fr>), SynthesisError> {
+        let (values, total) = net_asset_value_gadget(
+            &self
+                .holdings
+                .iter()
+                .map(|signed| signed.attestation.balance.clone())
+                .collect::<Vec<_>>(),
+            &accounts
+                .iter()
+                .map(|account| account.decimals)
+                .collect::<Vec<_>>(),
+            &self.prices.prices.prices,
+        )?;
+        // total > 0
+        AmountVar::constant(&BigUint::from(1u8))?.enforce_le(&total)?;
+        Ok((values, total))
+    }
+}
+
+impl<const N: usize> AllocVar<CompositeStepInputs<N>, Fr> for CompositeStepInputsVar {
+    fn new_variable<T: Borrow<CompositeStepInputs<N>>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|inputs| {
+            let cs = cs.into().cs();
+            let inputs = inputs.borrow();
+
+            Ok(Self {
+                period_start: FpVar::new_variable(
+                    cs.clone(),
+                    || Ok(Fr::from(inputs.period_start)),
+                    mode,
+                )?,
+                period_end: FpVar::new_variable(
+                    cs.clone(),
+                    || Ok(Fr::from(inputs.period_end)),
+                    mode,
+                )?,
+                holdings: inputs
+                    .holdings
+                    .iter()
+                    .map(|signed| {
+                        SignedAttestationVar::new_variable(cs.clone(), || Ok(signed), mode)
+                    })
+                    .collect::<Result<_, _>>()?,
+                prices: SignedPricesVar::new_variable(cs, || Ok(inputs.prices), mode)?,
+            })
+        })
+    }
+}
+
+/// Chains the commitment to the prices of a period into the hash of the prices,
+/// `Poseidon(prices_hash, prices_commitment)`
+pub fn chain_prices(
+    poseidon_config: &PoseidonConfig<Fr>,
+    prices_hash: Fr,
+    prices_commitment: Fr,
+) -> Fr {
+    let mut sponge = PoseidonSponge::new(poseidon_config);
+    sponge.absorb(&[prices_hash, prices_commitment]);
+    sponge.squeeze_field_elements(1)[0]
+}
+
+/// Computes in-circuit `chain_prices`
+pub fn chain_prices_gadget(
+    poseidon_config: &PoseidonConfig<Fr>,
+    prices_hash: &FpVar<Fr>,
+    prices_commitment: &FpVar<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::new(prices_hash.cs(), poseidon_config);
+    sponge.absorb(&[prices_hash.clone(), prices_commitment.clone()])?;
+    Ok(sponge.squeeze_field_elements(1)?.remove(0))
+}
+
+/// Composite Fund Compliance Circuit
+///
+/// Nova folds this circuit over N steps, proving N consecutive compliant periods. Each step
+/// receives its period `[period_start, period_end)`, the oracle-signed holdings and the signed
+/// prices as external inputs, and the circuit enforces that periods are non-empty and gap-free.
+#[derive(Clone, Debug)]
+pub struct CompositeFundCircuit<const N: usize> {
+    params: CompositeFundParams<N>,
+    poseidon_config: PoseidonConfig<Fr>,
+}
+
+impl<const N: usize> FCircuit<Fr> for CompositeFundCircuit<N> {
+    type Params = CompositeFundParams<N>;
+    type ExternalInputs = CompositeStepInputs<N>;
+    type ExternalInputsVar = CompositeStepInputsVar;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        if params.liquid_asset >= N || params.max_period_len == 0 {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(Self {
+            params,
+            poseidon_config: poseidon_canonical_config::<Fr>(),
+        })
+    }
+    fn state_len(&self) -> usize {
+        // State: [first_period, last_period, compliance_counter, prices_hash]
+        // The counter increments each time all checks pass
+        4
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<Fr>,
+        _i: usize,
+        z_i: Vec<FpVar<Fr>>,
+        external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        // Input state: covered time window, compliance counter and hash of the prices
+        let (counter, prices_hash) = (&z_i[2], &z_i[3]);
+
+        // ========================================
+        // PERIOD: [period_start, period_end) follows the covered window
+        // ========================================
+
+        let new_first_period = external_inputs.enforce_period(&z_i, self.params.max_period_len)?;
+
+        // ========================================
+        // ATTESTATIONS: holdings and prices signed by the oracles during the period
+        // ========================================
+
+        let oracle = PublicKeyVar::new_constant(cs.clone(), self.params.oracle)?;
+        let pricing_oracle = PublicKeyVar::new_constant(cs.clone(), self.params.pricing_oracle)?;
+        let accounts = &self.params.accounts;
+        let prices_commitment =
+            external_inputs.verify(&self.poseidon_config, &oracle, accounts, &pricing_oracle)?;
+
+        // ========================================
+        // NAV: value of each asset, and their sum
+        // ========================================
+
+        let (values, total) = external_inputs.valuation(accounts)?;
+
+        // ========================================
+        // CHECK 1: Position Limit (≤ max_position_pct)
+        // ========================================
+
+        // value * 100 ≤ max_pct * total for each asset but the liquid one, over the integers
+        let hundred = BigUint::from(100u8);
+        let max_value = total.mul_constant(&self.params.max_position_pct.into())?;
+        for (i, value) in values.iter().enumerate() {
+            if i != self.params.liquid_asset {
+                value.mul_constant(&hundred)?.enforce_le(&max_value)?;
+            }
+        }
+
+        // ========================================
+        // CHECK 2: Liquidity Reserve (≥ min_liquidity_pct)
+        // ========================================
+
+        // min_pct * total ≤ liquid_value * 100, over the integers
+        total
+            .mul_constant(&self.params.min_liquidity_pct.into())?
+            .enforce_le(&values[self.params.liquid_asset].mul_constant(&hundred)?)?;
+
+        // ========================================
+        // CHECK 3: Whitelist Membership (Merkle proof)
+        // ========================================
+
+        let leaf = FpVar::new_witness(cs.clone(), || Ok(self.params.asset_hash))?;
+        let proof = MerkleProofVar::new_witness(cs.clone(), || Ok(self.params.whitelist_proof))?;
+        proof
+            .root(&self.poseidon_config, &leaf)?
+            .enforce_equal(&FpVar::constant(self.params.merkle_root))?;
+
+        // ========================================
+        // ALL CHECKS PASSED - Increment Counter
+        // ========================================
+
+        // Output: extended time window, incremented counter and chained prices
+        Ok(vec![
+            new_first_period,
+            external_inputs.period_end,
+            counter + FpVar::one(),
+            chain_prices_gadget(&self.poseidon_config, prices_hash, &prices_commitment)?,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ed_on_bn254::Fr as EdFr;
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::{test_rng, UniformRand};
+
+    use super::*;
+    use crate::{amount::U256, merkle::MerkleTree, nav::PRICE_DECIMALS};
+
+    /// Length of each reporting period (one day)
+    const PERIOD_LEN: u64 = 86_400;
+    /// Start of the first reporting period (2025-07-01 00:00:00 UTC)
+    const FIRST_PERIOD_START: u64 = 1_751_328_000;
+
+    /// USDC, WETH, WBTC and a tokenized T-bill fund
+    const N_ASSETS: usize = 4;
+    const DECIMALS: [u8; N_ASSETS] = [6, 18, 8, 18];
+    const USDC: usize = 0;
+    const WETH: usize = 1;
+
+    /// Price of `dollars` USD
+    fn usd(dollars: u64) -> u128 {
+        dollars as u128 * 10u128.pow(PRICE_DECIMALS as u32)
+    }
+
+    /// Holdings of `units` whole tokens of each asset
+    fn holdings(units: [u64; N_ASSETS]) -> [TokenAmount; N_ASSETS] {
+        let mut holdings = [TokenAmount::default(); N_ASSETS];
+        for ((holding, units), decimals) in holdings.iter_mut().zip(units).zip(DECIMALS) {
+            *holding = TokenAmount::from_units(units, decimals).unwrap();
+        }
+        holdings
+    }
+
+    /// $100M fund: $10M of USDC (10%), $35M of WETH (35%), $25M of WBTC and $30M of T-bills
+    fn snapshot() -> FundSnapshot<N_ASSETS> {
+        FundSnapshot {
+            holdings: holdings([10_000_000, 14_000, 250, 300_000]),
+            prices: [usd(1), usd(2_500), usd(100_000), usd(100)],
+        }
+    }
+
+    struct Setup {
+        circuit: CompositeFundCircuit<N_ASSETS>,
+        oracle: SigningKey,
+        pricing_oracle: SigningKey,
+        accounts: [AttestedAccount; N_ASSETS],
+    }
+
+    impl Setup {
+        fn new() -> Result<Self, Error> {
+            let mut rng = test_rng();
+            let oracle = SigningKey::rand(&mut rng);
+            let pricing_oracle = SigningKey::rand(&mut rng);
+            let accounts = DECIMALS.map(|decimals| AttestedAccount {
+                id: Fr::rand(&mut rng),
+                decimals,
+            });
+            let whitelist = MerkleTree::<WHITELIST_DEPTH>::new(
+                &poseidon_canonical_config::<Fr>(),
+                &[Fr::from(100u32), Fr::from(200u32)],
+            );
+            let circuit = CompositeFundCircuit::new(CompositeFundParams {
+                max_position_pct: 40,
+                min_liquidity_pct: 10,
+                asset_hash: Fr::from(100u32),
+                whitelist_proof: whitelist.proof(0),
+                merkle_root: whitelist.root(),
+                oracle: oracle.public_key(),
+                accounts,
+                liquid_asset: USDC,
+                pricing_oracle: pricing_oracle.public_key(),
+                max_period_len: MAX_PERIOD_LEN,
+            })?;
+            Ok(Self {
+                circuit,
+                oracle,
+                pricing_oracle,
+                accounts,
+            })
+        }
+
+        fn inputs(
+            &self,
+            period: [u64; 2],
+            snapshot: &FundSnapshot<N_ASSETS>,
+        ) -> CompositeStepInputs<N_ASSETS> {
+            CompositeStepInputs::attest(
+                &self.oracle,
+                &self.pricing_oracle,
+                &self.circuit.poseidon_config,
+                &self.accounts,
+                period,
+                snapshot,
+            )
+        }
+
+        /// Runs the step circuit over the given inputs, returning whether all steps are
+        /// satisfied together with the final state
+        fn run_steps(
+            &self,
+            inputs: &[CompositeStepInputs<N_ASSETS>],
+        ) -> Result<(bool, Vec<Fr>), Error> {
+            let mut z_i = vec![Fr::from(0u32); 4];
+            for step_inputs in inputs {
+                let cs = ConstraintSystem::<Fr>::new_ref();
+                let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone()))?;
+                let inputs_var =
+                    CompositeStepInputsVar::new_witness(cs.clone(), || Ok(*step_inputs))?;
+                let z_i1_var =
+                    self.circuit
+                        .generate_step_constraints(cs.clone(), 0, z_i_var, inputs_var)?;
+                if !cs.is_satisfied()? {
+                    return Ok((false, z_i));
+                }
+                z_i = z_i1_var.value()?;
+            }
+            Ok((true, z_i))
+        }
+
+        fn run_periods(&self, periods: &[[u64; 2]]) -> Result<(bool, Vec<Fr>), Error> {
+            let inputs = periods
+                .iter()
+                .map(|period| self.inputs(*period, &snapshot()))
+                .collect::<Vec<_>>();
+            self.run_steps(&inputs)
+        }
+    }
+
+    /// Returns the `i`-th daily reporting period
+    fn daily_period(i: u64) -> [u64; 2] {
+        let start = FIRST_PERIOD_START + i * PERIOD_LEN;
+        [start, start + PERIOD_LEN]
+    }
+
+    #[test]
+    fn test_consecutive_periods() -> Result<(), Error> {
+        let setup = Setup::new()?;
+        let periods = (0..3).map(daily_period).collect::<Vec<_>>();
+        let (satisfied, z_n) = setup.run_periods(&periods)?;
+        assert!(satisfied);
+        let poseidon_config = &setup.circuit.poseidon_config;
+        let prices_hash = periods.iter().fold(Fr::from(0u32), |prices_hash, period| {
+            let prices = setup.inputs(*period, &snapshot()).prices.prices;
+            chain_prices(
+                poseidon_config,
+                prices_hash,
+                prices.commitment(poseidon_config),
+            )
+        });
+        assert_eq!(
+            z_n,
+            vec![
+                Fr::from(periods[0][0]),
+                Fr::from(periods[2][1]),
+                Fr::from(3u32),
+                prices_hash
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_periods() -> Result<(), Error> {
+        let setup = Setup::new()?;
+
+        // gap between the first and the second period
+        let (satisfied, _) = setup.run_periods(&[daily_period(0), daily_period(2)])?;
+        assert!(!satisfied);
+
+        // overlapping periods
+        let (satisfied, _) = setup.run_periods(&[daily_period(1), daily_period(0)])?;
+        assert!(!satisfied);
+
+        // empty and reversed periods
+        let start = FIRST_PERIOD_START;
+        let (satisfied, _) = setup.run_periods(&[[start, start]])?;
+        assert!(!satisfied);
+        let (satisfied, _) = setup.run_periods(&[[start, start - PERIOD_LEN]])?;
+        assert!(!satisfied);
+
+        // a single period covering the whole quarter
+        let (satisfied, _) = setup.run_periods(&[[start, start + 92 * PERIOD_LEN]])?;
+        assert!(!satisfied);
+        // periods of at most a day are accepted
+        let (satisfied, _) = setup.run_periods(&[[start, start + PERIOD_LEN / 2]])?;
+        assert!(satisfied);
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_period_len() -> Result<(), Error> {
+        let mut setup = Setup::new()?;
+        // Friday to Monday, over a weekend
+        let weekend = [FIRST_PERIOD_START, FIRST_PERIOD_START + 3 * PERIOD_LEN];
+        let (satisfied, _) = setup.run_periods(&[weekend])?;
+        assert!(!satisfied);
+
+        let mut params = setup.circuit.params;
+        params.max_period_len = 4 * PERIOD_LEN;
+        setup.circuit = CompositeFundCircuit::new(params)?;
+        let (satisfied, _) = setup.run_periods(&[weekend, daily_period(3)])?;
+        assert!(satisfied);
+        let (satisfied, _) = setup.run_periods(&[[weekend[0], weekend[0] + 5 * PERIOD_LEN]])?;
+        assert!(!satisfied);
+
+        params.max_period_len = 0;
+        assert!(CompositeFundCircuit::new(params).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unlisted_asset() -> Result<(), Error> {
+        let mut setup = Setup::new()?;
+        let mut params = setup.circuit.params;
+        params.asset_hash = Fr::from(300u32);
+        setup.circuit = CompositeFundCircuit::new(params)?;
+        let (satisfied, _) = setup.run_periods(&[daily_period(0)])?;
+        assert!(!satisfied);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        let mut params = Setup::new().unwrap().circuit.params;
+        params.liquid_asset = N_ASSETS;
+        assert!(CompositeFundCircuit::new(params).is_err());
+    }
+
+    #[test]
+    fn test_invalid_attestations() -> Result<(), Error> {
+        let setup = Setup::new()?;
+        let inputs = setup.inputs(daily_period(0), &snapshot());
+        assert!(setup.run_steps(&[inputs])?.0);
+
+        // holding changed by the prover
+        let mut tampered = inputs;
+        tampered.holdings[USDC].attestation.balance =
+            TokenAmount::from_units(20_000_000, 6).unwrap();
+        assert!(!setup.run_steps(&[tampered])?.0);
+
+        // holding signed with the decimals of another token
+        let mut wrong_decimals = snapshot();
+        wrong_decimals.holdings[USDC] = TokenAmount::from_units(10_000_000, 18).unwrap();
+        assert!(
+            !setup
+                .run_steps(&[setup.inputs(daily_period(0), &wrong_decimals)])?
+                .0
+        );
+
+        // holdings signed by another key
+        let other_oracle = SigningKey::new(EdFr::from(42u64));
+        let forged = CompositeStepInputs::attest(
+            &other_oracle,
+            &setup.pricing_oracle,
+            &setup.circuit.poseidon_config,
+            &setup.accounts,
+            daily_period(0),
+            &snapshot(),
+        );
+        assert!(!setup.run_steps(&[forged])?.0);
+
+        // attestations swapped between accounts
+        let mut swapped = inputs;
+        swapped.holdings.swap(USDC, WETH);
+        assert!(!setup.run_steps(&[swapped])?.0);
+
+        // attestation of another period
+        let stale = CompositeStepInputs {
+            period_start: daily_period(1)[0],
+            period_end: daily_period(1)[1],
+            ..inputs
+        };
+        assert!(!setup.run_steps(&[stale])?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_prices() -> Result<(), Error> {
+        let setup = Setup::new()?;
+        let inputs = setup.inputs(daily_period(0), &snapshot());
+
+        // price changed by the prover
+        let mut tampered = inputs;
+        tampered.prices.prices.prices[WETH] = usd(2_000);
+        assert!(!setup.run_steps(&[tampered])?.0);
+
+        // prices signed by the balance oracle instead of the pricing oracle
+        let forged = CompositeStepInputs::attest(
+            &setup.oracle,
+            &setup.oracle,
+            &setup.circuit.poseidon_config,
+            &setup.accounts,
+            daily_period(0),
+            &snapshot(),
+        );
+        assert!(!setup.run_steps(&[forged])?.0);
+
+        // prices of another period
+        let stale = CompositeStepInputs {
+            prices: setup.inputs(daily_period(1), &snapshot()).prices,
+            ..inputs
+        };
+        assert!(!setup.run_steps(&[stale])?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_compliant_snapshots() -> Result<(), Error> {
+        let setup = Setup::new()?;
+        // WETH worth $40M out of $100M, plus one wei (ie. $2,500·10^-18)
+        let mut above_limit = FundSnapshot {
+            holdings: holdings([10_000_000, 16_000, 250, 250_000]),
+            ..snapshot()
+        };
+        assert!(
+            setup
+                .run_steps(&[setup.inputs(daily_period(0), &above_limit)])?
+                .0
+        );
+        above_limit.holdings[WETH].value =
+            U256::try_from(BigUint::from(above_limit.holdings[WETH].value) + 1u8).unwrap();
+
+        for (snapshot, expected) in [
+            (above_limit, false),
+            // with $12M of USDC, WETH rallies to $3,000: $42M out of $109M
+            (
+                FundSnapshot {
+                    holdings: holdings([12_000_000, 14_000, 250, 300_000]),
+                    prices: [usd(1), usd(3_000), usd(100_000), usd(100)],
+                },
+                true,
+            ),
+            // with $12M of USDC, WETH rallies to $3,500: $49M out of $116M
+            (
+                FundSnapshot {
+                    holdings: holdings([12_000_000, 14_000, 250, 300_000]),
+                    prices: [usd(1), usd(3_500), usd(100_000), usd(100)],
+                },
+                false,
+            ),
+            // the liquid asset is not subject to the position limit, but to the 10% minimum
+            (
+                FundSnapshot {
+                    holdings: holdings([50_000_000, 8_000, 200, 100_000]),
+                    ..snapshot()
+                },
+                true,
+            ),
+            (
+                FundSnapshot {
+                    holdings: holdings([9_999_999, 14_000, 250, 300_000]),
+                    ..snapshot()
+                },
+                false,
+            ),
+            // amounts that overflow a u64 are handled
+            (
+                FundSnapshot {
+                    holdings: holdings([
+                        u64::MAX,
+                        u64::MAX / 10_000,
+                        u64::MAX / 400_000,
+                        u64::MAX / 400,
+                    ]),
+                    ..snapshot()
+                },
+                true,
+            ),
+            // the NAV must be positive
+            (
+                FundSnapshot {
+                    holdings: holdings([0; N_ASSETS]),
+                    ..snapshot()
+                },
+                false,
+            ),
+            (
+                FundSnapshot {
+                    prices: [0; N_ASSETS],
+                    ..snapshot()
+                },
+                false,
+            ),
+        ] {
+            let inputs = setup.inputs(daily_period(0), &snapshot);
+            assert_eq!(setup.run_steps(&[inputs])?.0, expected);
+        }
+        Ok(())
+    }
+}
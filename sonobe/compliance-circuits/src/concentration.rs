@@ -0,0 +1,437 @@
+//! Concentration limits on the holdings of the fund, such as the UCITS 5/10/40 rule: each holding
+//! weighs at most 10% of the NAV, and the holdings weighing more than 5% together weigh at most 40%.
+//!
+//! The limits on the largest holdings are checked on a copy of the values sorted by the prover. A
+//! grand-product argument proves that it is a permutation of the values: with challenges `r` and
+//! `s` squeezed from Poseidon over both vectors, `∏ (r - h_s(value_i)) = ∏ (r - h_s(sorted_i))`,
+//! where `h_s` compresses the words of a value into a field element. Each sorted value is then
+//! enforced to be at most the previous one, so that the `k` largest holdings are the first `k`.
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig},
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{ConstraintSystemRef, SynthesisError};
+use folding_schemes::{frontend::FCircuit, transcript::poseidon::poseidon_canonical_config, Error};
+use num_bigint::BigUint;
+
+use crate::{
+    amount::{hint_bits, AmountVar},
+    composite::{
+        chain_prices_gadget, AttestedAccount, CompositeStepInputs, CompositeStepInputsVar,
+    },
+    eddsa::{self, PublicKeyVar},
+};
+
+/// Number of bits of the words absorbed by the permutation argument, which fit in a field element
+const WORD_BITS: usize = (Fr::MODULUS_BIT_SIZE - 1) as usize;
+
+/// Limits on the weights of the holdings in the NAV, in percent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConcentrationPolicy {
+    /// Maximum weight of each holding
+    pub max_single_pct: u64,
+    /// Holdings weighing more than `large_threshold_pct` together weigh at most `max_large_pct`
+    pub large_threshold_pct: u64,
+    pub max_large_pct: u64,
+    /// The `top_k` largest holdings together weigh at most `max_top_k_pct`
+    pub top_k: usize,
+    pub max_top_k_pct: u64,
+}
+
+impl ConcentrationPolicy {
+    /// UCITS 5/10/40 rule: each holding weighs at most 10%, and the holdings weighing more than 5%
+    /// together weigh at most 40%
+    pub const UCITS_5_10_40: Self = Self {
+        max_single_pct: 10,
+        large_threshold_pct: 5,
+        max_large_pct: 40,
+        top_k: 0,
+        max_top_k_pct: 100,
+    };
+
+    /// Limits the aggregate weight of the `k` largest holdings to `max_pct`
+    pub const fn top_k(k: usize, max_pct: u64) -> Self {
+        Self {
+            max_single_pct: 100,
+            large_threshold_pct: 100,
+            max_large_pct: 100,
+            top_k: k,
+            max_top_k_pct: max_pct,
+        }
+    }
+
+    /// Returns whether holdings worth `values` comply with the policy
+    pub fn is_compliant(&self, values: &[BigUint]) -> bool {
+        let total = values.iter().sum::<BigUint>();
+        let weighs_at_most = |value: &BigUint, pct: u64| value * 100u8 <= &total * pct;
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| b.cmp(a));
+        let large = sorted
+            .iter()
+            .filter(|value| !weighs_at_most(*value, self.large_threshold_pct))
+            .sum::<BigUint>();
+        let top_k = sorted.iter().take(self.top_k).sum::<BigUint>();
+
+        total != BigUint::default()
+            && sorted
+                .first()
+                .map_or(true, |largest| weighs_at_most(largest, self.max_single_pct))
+            && weighs_at_most(&large, self.max_large_pct)
+            && weighs_at_most(&top_k, self.max_top_k_pct)
+    }
+}
+
+/// Returns the `values` sorted in decreasing order, enforcing that they are a permutation of them
+pub fn sort_gadget(
+    poseidon_config: &PoseidonConfig<Fr>,
+    values: &[AmountVar<Fr>],
+) -> Result<Vec<AmountVar<Fr>>, SynthesisError> {
+    let cs = values
+        .iter()
+        .fold(ConstraintSystemRef::None, |cs, value| cs.or(value.cs()));
+    let length = values
+        .iter()
+        .map(|value| value.0.ubound().bits())
+        .max()
+        .unwrap_or_default();
+
+    // Both vectors are decomposed into `length` bits, so that equal values have equal words
+    let value_bits = values
+        .iter()
+        .map(|value| {
+            let mut bits = value.to_bits_le()?;
+            bits.resize(length as usize, Boolean::FALSE);
+            Ok(bits)
+        })
+        .collect::<Result<Vec<_>, SynthesisError>>()?;
+    let mut sorted_values = values
+        .iter()
+        .map(|value| value.value().unwrap_or_default())
+        .collect::<Vec<_>>();
+    sorted_values.sort_by(|a, b| b.cmp(a));
+    let sorted_bits = sorted_values
+        .into_iter()
+        .map(|value| hint_bits(cs.clone(), || value, length))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let to_words = |bits: &[Vec<Boolean<Fr>>]| {
+        bits.iter()
+            .map(|bits| {
+                bits.chunks(WORD_BITS)
+                    .map(Boolean::le_bits_to_fp)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let (value_words, sorted_words) = (to_words(&value_bits)?, to_words(&sorted_bits)?);
+
+    let mut sponge = PoseidonSpongeVar::new(cs, poseidon_config);
+    sponge.absorb(&value_words.concat())?;
+    sponge.absorb(&sorted_words.concat())?;
+    let challenges = sponge.squeeze_field_elements(2)?;
+    let (r, s) = (&challenges[0], &challenges[1]);
+
+    // ∏ (r - h_s(words)), where h_s(words) = Σ words_j · s^j
+    let grand_product = |words: &[Vec<FpVar<Fr>>]| {
+        words.iter().fold(FpVar::one(), |product, words| {
+            let h = words
+                .iter()
+                .rev()
+                .fold(FpVar::zero(), |h, word| h * s + word);
+            product * (r - h)
+        })
+    };
+    grand_product(&value_words).enforce_equal(&grand_product(&sorted_words))?;
+
+    let sorted = sorted_bits
+        .iter()
+        .map(|bits| AmountVar::from_bits(bits))
+        .collect::<Vec<_>>();
+    for pair in sorted.windows(2) {
+        pair[1].enforce_le(&pair[0])?;
+    }
+    Ok(sorted)
+}
+
+/// Enforces that holdings worth `values`, whose sum is `total`, comply with the `policy`
+pub fn enforce_concentration(
+    poseidon_config: &PoseidonConfig<Fr>,
+    policy: &ConcentrationPolicy,
+    values: &[AmountVar<Fr>],
+    total: &AmountVar<Fr>,
+) -> Result<(), SynthesisError> {
+    let sorted = sort_gadget(poseidon_config, values)?;
+    let hundred = BigUint::from(100u8);
+    // value * 100 ≤ pct * total, over the integers
+    let weighs_at_most = |value: &AmountVar<Fr>, pct: u64| {
+        value
+            .mul_constant(&hundred)?
+            .enforce_le(&total.mul_constant(&pct.into())?)
+    };
+
+    // The largest holding is the first one
+    if let Some(largest) = sorted.first() {
+        weighs_at_most(largest, policy.max_single_pct)?;
+    }
+
+    // A holding is large iff value * 100 > large_threshold_pct * total
+    let threshold = total.mul_constant(&policy.large_threshold_pct.into())?;
+    let above_threshold = threshold.add(&AmountVar::constant(&BigUint::from(1u8))?)?;
+    let mut large = AmountVar::constant(&BigUint::default())?;
+    for value in &sorted {
+        let weighted = value.mul_constant(&hundred)?;
+        let is_large = hint_bits(
+            total.cs().or(value.cs()),
+            || {
+                let is_large =
+                    weighted.value().unwrap_or_default() > threshold.value().unwrap_or_default();
+                BigUint::from(is_large as u8)
+            },
+            1,
+        )?;
+        let is_small = [!&is_large[0]];
+        let (is_large, is_small) = (
+            AmountVar::from_bits(&is_large),
+            AmountVar::from_bits(&is_small),
+        );
+        above_threshold.mul(&is_large)?.enforce_le(&weighted)?;
+        weighted.mul(&is_small)?.enforce_le(&threshold)?;
+        large = large.add(&value.mul(&is_large)?)?;
+    }
+    weighs_at_most(&large, policy.max_large_pct)?;
+
+    // The `top_k` largest holdings are the first ones
+    let top_k = sorted
+        .iter()
+        .take(policy.top_k)
+        .try_fold(AmountVar::constant(&BigUint::default())?, |sum, value| {
+            sum.add(value)
+        })?;
+    weighs_at_most(&top_k, policy.max_top_k_pct)
+}
+
+/// Concentration Fund Compliance Circuit Parameters for a fund holding `N` assets
+#[derive(Clone, Copy, Debug)]
+pub struct ConcentrationFundParams<const N: usize> {
+    pub policy: ConcentrationPolicy,
+
+    // Oracle attesting the holdings of the fund's accounts, one per asset
+    pub oracle: eddsa::PublicKey,
+    pub accounts: [AttestedAccount; N],
+
+    // Oracle publishing the prices of the assets
+    pub pricing_oracle: eddsa::PublicKey,
+
+    // Maximum length of a reporting period, in seconds, see `CompositeFundParams::max_period_len`
+    pub max_period_len: u64,
+}
+
+/// Concentration Fund Compliance Circuit
+///
+/// Nova folds this circuit over consecutive periods like `CompositeFundCircuit`, from the same
+/// external inputs, proving that the holdings comply with a `ConcentrationPolicy` in each period.
+#[derive(Clone, Debug)]
+pub struct ConcentrationFundCircuit<const N: usize> {
+    params: ConcentrationFundParams<N>,
+    poseidon_config: PoseidonConfig<Fr>,
+}
+
+impl<const N: usize> FCircuit<Fr> for ConcentrationFundCircuit<N> {
+    type Params = ConcentrationFundParams<N>;
+    type ExternalInputs = CompositeStepInputs<N>;
+    type ExternalInputsVar = CompositeStepInputsVar;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        if params.max_period_len == 0 {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(Self {
+            params,
+            poseidon_config: poseidon_canonical_config::<Fr>(),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        // State: [first_period, last_period, compliance_counter, prices_hash], see
+        // `CompositeFundCircuit`
+        4
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<Fr>,
+        _i: usize,
+        z_i: Vec<FpVar<Fr>>,
+        external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        let new_first_period = external_inputs.enforce_period(&z_i, self.params.max_period_len)?;
+
+        let oracle = PublicKeyVar::new_constant(cs.clone(), self.params.oracle)?;
+        let pricing_oracle = PublicKeyVar::new_constant(cs, self.params.pricing_oracle)?;
+        let accounts = &self.params.accounts;
+        let prices_commitment =
+            external_inputs.verify(&self.poseidon_config, &oracle, accounts, &pricing_oracle)?;
+        let (values, total) = external_inputs.valuation(accounts)?;
+
+        enforce_concentration(&self.poseidon_config, &self.params.policy, &values, &total)?;
+
+        Ok(vec![
+            new_first_period,
+            external_inputs.period_end,
+            &z_i[2] + FpVar::one(),
+            chain_prices_gadget(&self.poseidon_config, &z_i[3], &prices_commitment)?,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::{test_rng, UniformRand};
+
+    use super::*;
+    use crate::{
+        amount::{TokenAmount, U256},
+        composite::{chain_prices, FundSnapshot, MAX_PERIOD_LEN},
+        eddsa::SigningKey,
+        nav::PRICE_DECIMALS,
+    };
+
+    /// Checks the policy over holdings worth `values`, both natively and in-circuit
+    fn check(policy: &ConcentrationPolicy, values: &[u64]) -> Result<bool, SynthesisError> {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let value_vars = values
+            .iter()
+            .map(|value| AmountVar::new_witness(cs.clone(), || Ok(U256::from(*value))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let total = value_vars
+            .iter()
+            .try_fold(AmountVar::constant(&BigUint::default())?, |sum, value| {
+                sum.add(value)
+            })?;
+        enforce_concentration(&poseidon_config, policy, &value_vars, &total)?;
+
+        let values = values
+            .iter()
+            .map(|value| BigUint::from(*value))
+            .collect::<Vec<_>>();
+        assert_eq!(cs.is_satisfied()?, policy.is_compliant(&values));
+        cs.is_satisfied()
+    }
+
+    #[test]
+    fn test_sort_gadget() -> Result<(), SynthesisError> {
+        let values = [25u64, 40, 10, 25, 0];
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let value_vars = values
+            .iter()
+            .map(|value| AmountVar::new_witness(cs.clone(), || Ok(U256::from(*value))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let sorted = sort_gadget(&poseidon_canonical_config::<Fr>(), &value_vars)?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(
+            sorted
+                .iter()
+                .map(|v| v.value())
+                .collect::<Result<Vec<_>, _>>()?,
+            [40u8, 25, 25, 10, 0].map(BigUint::from)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ucits_5_10_40() -> Result<(), SynthesisError> {
+        let policy = ConcentrationPolicy::UCITS_5_10_40;
+        // four holdings of 10% and twelve of 5%, in any order
+        let mut values = [[50u64; 12].as_slice(), &[100; 4]].concat();
+        values.rotate_left(5);
+        assert!(check(&policy, &values)?);
+
+        // one holding above 10%
+        assert!(!check(&policy, &[&[110, 90][..], &[50; 16]].concat())?);
+        // the holdings above 5% weigh 46%
+        assert!(!check(
+            &policy,
+            &[&[100, 100, 100, 100, 60][..], &[50; 10], &[40]].concat()
+        )?);
+        // a single holding weighs 100%
+        assert!(!check(&policy, &[1])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_k() -> Result<(), SynthesisError> {
+        let policy = ConcentrationPolicy::top_k(3, 75);
+        assert!(check(&policy, &[10, 30, 5, 20, 10, 25])?);
+        assert!(!check(&policy, &[10, 30, 5, 25, 10, 21])?);
+        // fewer holdings than k
+        assert!(!check(&ConcentrationPolicy::top_k(8, 99), &[10, 30])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_concentration_circuit() -> Result<(), Error> {
+        const N: usize = 4;
+        let mut rng = test_rng();
+        let oracle = SigningKey::rand(&mut rng);
+        let pricing_oracle = SigningKey::rand(&mut rng);
+        let accounts: [_; N] = core::array::from_fn(|_| AttestedAccount {
+            id: Fr::rand(&mut rng),
+            decimals: 18,
+        });
+        let circuit = ConcentrationFundCircuit::new(ConcentrationFundParams {
+            policy: ConcentrationPolicy::top_k(2, 70),
+            oracle: oracle.public_key(),
+            accounts,
+            pricing_oracle: pricing_oracle.public_key(),
+            max_period_len: MAX_PERIOD_LEN,
+        })?;
+
+        let period = [1_751_328_000, 1_751_414_400];
+        for (units, expected) in [([40u64, 25, 20, 15], true), ([15, 40, 20, 35], false)] {
+            let snapshot = FundSnapshot {
+                holdings: units.map(|units| TokenAmount::from_units(units, 18).unwrap()),
+                prices: [10u128.pow(PRICE_DECIMALS as u32); N],
+            };
+            let inputs = CompositeStepInputs::attest(
+                &oracle,
+                &pricing_oracle,
+                &circuit.poseidon_config,
+                &accounts,
+                period,
+                &snapshot,
+            );
+
+            let prices_commitment = inputs.prices.prices.commitment(&circuit.poseidon_config);
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_i = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(vec![Fr::from(0u8); 4]))?;
+            let inputs = CompositeStepInputsVar::new_witness(cs.clone(), || Ok(inputs))?;
+            let z_i1 = circuit.generate_step_constraints(cs.clone(), 0, z_i, inputs)?;
+            assert_eq!(cs.is_satisfied()?, expected);
+            if expected {
+                assert_eq!(
+                    z_i1.value()?,
+                    vec![
+                        Fr::from(period[0]),
+                        Fr::from(period[1]),
+                        Fr::from(1u8),
+                        chain_prices(&circuit.poseidon_config, Fr::from(0u8), prices_commitment)
+                    ]
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,518 @@
+//! Investor credentials, which restrict the holders of the fund to KYC'd or accredited investors
+//! from allowed jurisdictions.
+//!
+//! A credential binds the hash of the identity of an investor to the address receiving the shares,
+//! together with their jurisdiction, accreditation level and expiry. It is either signed by an
+//! issuer (eg. a KYC provider) with EdDSA, or registered by the fund as a leaf of a Poseidon Merkle
+//! tree (see `crate::merkle`, which also backs the whitelist of the composite circuit) whose root
+//! is published on-chain.
+//!
+//! `CredentialCircuit` is folded over the transfers of a batch: each step proves that the receiver
+//! holds a valid and unexpired credential from an allowed jurisdiction, without revealing the
+//! investor, their jurisdiction nor their accreditation. The receivers are chained into a hash that
+//! the `InvestorRegistry` contract recomputes from the receivers of the batch.
+//!
+//! The root of the registry is carried in the state rather than fixed in the circuit, so that
+//! registering an investor doesn't require a new setup: `InvestorRegistry` matches the root of
+//! `z_0` against the one it publishes, and the time of `z_0` against the block time.
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::borrow::Borrow;
+use folding_schemes::{frontend::FCircuit, transcript::poseidon::poseidon_canonical_config, Error};
+
+use crate::{
+    attestation::TIMESTAMP_BITS,
+    eddsa::{self, PublicKeyVar, Signature, SignatureVar, SigningKey},
+    gadgets::{enforce_bit_length, enforce_le},
+    merkle::{MerkleProof, MerkleProofVar},
+};
+
+/// Number of bits of a jurisdiction, an ISO 3166-1 numeric country code
+pub const JURISDICTION_BITS: usize = 16;
+/// Number of bits of an accreditation level
+pub const ACCREDITATION_BITS: usize = 8;
+
+/// Credential of the investor `investor_id`, allowing `holder` to receive shares until `expiry`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Credential {
+    /// Hash of the identity of the investor, as checked by the issuer
+    pub investor_id: Fr,
+    /// Address receiving the shares, as a field element (`uint256(uint160(receiver))` on-chain)
+    pub holder: Fr,
+    /// ISO 3166-1 numeric code of the jurisdiction of the investor
+    pub jurisdiction: u16,
+    /// Accreditation level, eg. 0 for retail, 1 for accredited and 2 for qualified investors
+    pub accreditation: u8,
+    /// Unix time (in seconds) at which the credential expires
+    pub expiry: u64,
+}
+
+impl Credential {
+    /// Returns the signed message, `[investor_id, holder, jurisdiction, accreditation, expiry]`
+    pub fn to_field_elements(&self) -> Vec<Fr> {
+        vec![
+            self.investor_id,
+            self.holder,
+            Fr::from(self.jurisdiction),
+            Fr::from(self.accreditation),
+            Fr::from(self.expiry),
+        ]
+    }
+
+    /// Returns the Poseidon hash of the credential, registered as a leaf of the registry
+    pub fn leaf(&self, poseidon_config: &PoseidonConfig<Fr>) -> Fr {
+        let mut sponge = PoseidonSponge::new(poseidon_config);
+        sponge.absorb(&self.to_field_elements());
+        sponge.squeeze_field_elements(1)[0]
+    }
+}
+
+/// Source of the valid credentials
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CredentialIssuer {
+    /// Credentials signed by the issuer with this public key
+    Signed(eddsa::PublicKey),
+    /// Credentials registered in the Merkle tree whose root is carried in the state
+    Registered,
+}
+
+/// Credential of a receiver, together with its proof of issuance. Only the part matching the
+/// `CredentialIssuer` of the circuit is checked, the other one can be left to its default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IssuedCredential<const D: usize> {
+    pub credential: Credential,
+    pub signature: Signature,
+    pub membership: MerkleProof<D>,
+}
+
+impl<const D: usize> IssuedCredential<D> {
+    pub fn sign(
+        issuer: &SigningKey,
+        poseidon_config: &PoseidonConfig<Fr>,
+        credential: Credential,
+    ) -> Self {
+        Self {
+            credential,
+            signature: issuer.sign(poseidon_config, &credential.to_field_elements()),
+            membership: MerkleProof::default(),
+        }
+    }
+
+    pub fn registered(credential: Credential, membership: MerkleProof<D>) -> Self {
+        Self {
+            credential,
+            signature: Signature::default(),
+            membership,
+        }
+    }
+
+    /// Verifies natively the issuance of the credential, against `registry_root` for registered
+    /// credentials
+    pub fn verify(
+        &self,
+        poseidon_config: &PoseidonConfig<Fr>,
+        issuer: &CredentialIssuer,
+        registry_root: Fr,
+    ) -> bool {
+        match issuer {
+            CredentialIssuer::Signed(pk) => eddsa::verify(
+                poseidon_config,
+                pk,
+                &self.credential.to_field_elements(),
+                &self.signature,
+            ),
+            CredentialIssuer::Registered => {
+                self.membership
+                    .root(poseidon_config, self.credential.leaf(poseidon_config))
+                    == registry_root
+            }
+        }
+    }
+}
+
+/// Rules on the holders of the fund
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialPolicy {
+    /// Jurisdictions whose investors may hold shares
+    pub allowed_jurisdictions: Vec<u16>,
+    /// Minimum accreditation level of the holders
+    pub min_accreditation: u8,
+}
+
+impl CredentialPolicy {
+    /// Checks natively that the credential complies with the policy at time `now`
+    pub fn allows(&self, credential: &Credential, now: u64) -> bool {
+        self.allowed_jurisdictions
+            .contains(&credential.jurisdiction)
+            && credential.accreditation >= self.min_accreditation
+            && now < credential.expiry
+    }
+}
+
+/// In-circuit representation of a `Credential`. The jurisdiction, the accreditation and the expiry
+/// are enforced to fit in `JURISDICTION_BITS`, `ACCREDITATION_BITS` and `TIMESTAMP_BITS` bits when
+/// allocated as witnesses.
+#[derive(Clone, Debug)]
+pub struct CredentialVar {
+    pub investor_id: FpVar<Fr>,
+    pub holder: FpVar<Fr>,
+    pub jurisdiction: FpVar<Fr>,
+    pub accreditation: FpVar<Fr>,
+    pub expiry: FpVar<Fr>,
+}
+
+impl CredentialVar {
+    pub fn to_field_elements(&self) -> Vec<FpVar<Fr>> {
+        vec![
+            self.investor_id.clone(),
+            self.holder.clone(),
+            self.jurisdiction.clone(),
+            self.accreditation.clone(),
+            self.expiry.clone(),
+        ]
+    }
+
+    /// Computes in-circuit the Poseidon hash of the credential
+    pub fn leaf(&self, poseidon_config: &PoseidonConfig<Fr>) -> Result<FpVar<Fr>, SynthesisError> {
+        let message = self.to_field_elements();
+        let mut sponge = PoseidonSpongeVar::new(message.cs(), poseidon_config);
+        sponge.absorb(&message)?;
+        Ok(sponge.squeeze_field_elements(1)?.remove(0))
+    }
+
+    /// Enforces that the credential complies with the policy at time `now`
+    pub fn enforce_policy(
+        &self,
+        policy: &CredentialPolicy,
+        now: &FpVar<Fr>,
+    ) -> Result<(), SynthesisError> {
+        // the jurisdiction is a root of ∏ (X - allowed)
+        policy
+            .allowed_jurisdictions
+            .iter()
+            .fold(FpVar::one(), |product, allowed| {
+                product * (&self.jurisdiction - Fr::from(*allowed))
+            })
+            .enforce_equal(&FpVar::zero())?;
+        enforce_le(
+            &FpVar::constant(Fr::from(policy.min_accreditation)),
+            &self.accreditation,
+            ACCREDITATION_BITS,
+        )?;
+        // now < expiry
+        enforce_le(&(now + FpVar::one()), &self.expiry, TIMESTAMP_BITS)
+    }
+}
+
+impl AllocVar<Credential, Fr> for CredentialVar {
+    fn new_variable<T: Borrow<Credential>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|credential| {
+            let cs = cs.into().cs();
+            let credential = credential.borrow();
+
+            let investor_id = FpVar::new_variable(cs.clone(), || Ok(credential.investor_id), mode)?;
+            let holder = FpVar::new_variable(cs.clone(), || Ok(credential.holder), mode)?;
+            let jurisdiction =
+                FpVar::new_variable(cs.clone(), || Ok(Fr::from(credential.jurisdiction)), mode)?;
+            let accreditation =
+                FpVar::new_variable(cs.clone(), || Ok(Fr::from(credential.accreditation)), mode)?;
+            let expiry = FpVar::new_variable(cs, || Ok(Fr::from(credential.expiry)), mode)?;
+            if mode == AllocationMode::Witness {
+                enforce_bit_length(&jurisdiction, JURISDICTION_BITS)?;
+                enforce_bit_length(&accreditation, ACCREDITATION_BITS)?;
+                enforce_bit_length(&expiry, TIMESTAMP_BITS)?;
+            }
+
+            Ok(Self {
+                investor_id,
+                holder,
+                jurisdiction,
+                accreditation,
+                expiry,
+            })
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IssuedCredentialVar {
+    pub credential: CredentialVar,
+    pub signature: SignatureVar,
+    pub membership: MerkleProofVar,
+}
+
+impl IssuedCredentialVar {
+    /// Enforces that the credential was issued by `issuer`, against `registry_root` for registered
+    /// credentials
+    pub fn verify(
+        &self,
+        poseidon_config: &PoseidonConfig<Fr>,
+        issuer: &CredentialIssuer,
+        registry_root: &FpVar<Fr>,
+    ) -> Result<(), SynthesisError> {
+        let cs = self.credential.holder.cs();
+        match issuer {
+            CredentialIssuer::Signed(pk) => eddsa::verify_gadget(
+                poseidon_config,
+                &PublicKeyVar::new_constant(cs, pk)?,
+                &self.credential.to_field_elements(),
+                &self.signature,
+            ),
+            CredentialIssuer::Registered => self
+                .membership
+                .root(poseidon_config, &self.credential.leaf(poseidon_config)?)?
+                .enforce_equal(registry_root),
+        }
+    }
+}
+
+impl<const D: usize> AllocVar<IssuedCredential<D>, Fr> for IssuedCredentialVar {
+    fn new_variable<T: Borrow<IssuedCredential<D>>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|issued| {
+            let cs = cs.into().cs();
+            let issued = issued.borrow();
+
+            Ok(Self {
+                credential: CredentialVar::new_variable(
+                    cs.clone(),
+                    || Ok(issued.credential),
+                    mode,
+                )?,
+                signature: SignatureVar::new_variable(cs.clone(), || Ok(issued.signature), mode)?,
+                membership: MerkleProofVar::new_variable(cs, || Ok(issued.membership), mode)?,
+            })
+        })
+    }
+}
+
+/// Chains the receiver `holder` of a transfer into the hash of the batch, `Poseidon(hash, holder)`
+pub fn chain_transfer(poseidon_config: &PoseidonConfig<Fr>, transfers_hash: Fr, holder: Fr) -> Fr {
+    let mut sponge = PoseidonSponge::new(poseidon_config);
+    sponge.absorb(&[transfers_hash, holder]);
+    sponge.squeeze_field_elements(1)[0]
+}
+
+#[derive(Clone, Debug)]
+pub struct CredentialParams {
+    pub issuer: CredentialIssuer,
+    pub policy: CredentialPolicy,
+}
+
+/// Credential Circuit
+///
+/// Nova folds this circuit over the transfers of a batch, one receiver per step, proving that each
+/// receiver holds a credential issued by `issuer` and complying with `policy` at time `now`. `D`
+/// is the depth of the registry, and is unused for signed credentials, as is the registry root of
+/// the state.
+#[derive(Clone, Debug)]
+pub struct CredentialCircuit<const D: usize> {
+    params: CredentialParams,
+    poseidon_config: PoseidonConfig<Fr>,
+}
+
+impl<const D: usize> FCircuit<Fr> for CredentialCircuit<D> {
+    type Params = CredentialParams;
+    type ExternalInputs = IssuedCredential<D>;
+    type ExternalInputsVar = IssuedCredentialVar;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            params,
+            poseidon_config: poseidon_canonical_config::<Fr>(),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        // State: [now, registry_root, transfers_hash, transfer_counter]
+        4
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<Fr>,
+        _i: usize,
+        z_i: Vec<FpVar<Fr>>,
+        external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        let (now, registry_root, transfers_hash, counter) = (&z_i[0], &z_i[1], &z_i[2], &z_i[3]);
+
+        external_inputs.verify(&self.poseidon_config, &self.params.issuer, registry_root)?;
+        let credential = &external_inputs.credential;
+        credential.enforce_policy(&self.params.policy, now)?;
+
+        // Output: the receiver chained into the hash of the batch
+        let mut sponge = PoseidonSpongeVar::new(transfers_hash.cs(), &self.poseidon_config);
+        sponge.absorb(&[transfers_hash.clone(), credential.holder.clone()])?;
+        let new_transfers_hash = sponge.squeeze_field_elements(1)?.remove(0);
+
+        Ok(vec![
+            now.clone(),
+            registry_root.clone(),
+            new_transfers_hash,
+            counter + FpVar::one(),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::{test_rng, UniformRand};
+
+    use super::*;
+    use crate::merkle::MerkleTree;
+
+    const NOW: u64 = 1_751_328_000;
+    const FRANCE: u16 = 250;
+    const SINGAPORE: u16 = 702;
+    const UNITED_STATES: u16 = 840;
+
+    fn policy() -> CredentialPolicy {
+        CredentialPolicy {
+            allowed_jurisdictions: vec![FRANCE, SINGAPORE],
+            min_accreditation: 1,
+        }
+    }
+
+    fn credential(rng: &mut impl ark_std::rand::RngCore) -> Credential {
+        Credential {
+            investor_id: Fr::rand(rng),
+            holder: Fr::rand(rng),
+            jurisdiction: FRANCE,
+            accreditation: 1,
+            expiry: NOW + 86_400,
+        }
+    }
+
+    /// Runs one step from the initial state with the registry `registry_root`, returning whether it
+    /// is satisfied and the next state
+    fn step<const D: usize>(
+        circuit: &CredentialCircuit<D>,
+        registry_root: Fr,
+        issued: IssuedCredential<D>,
+    ) -> Result<(bool, Vec<Fr>), Error> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || {
+            Ok(vec![
+                Fr::from(NOW),
+                registry_root,
+                Fr::from(0u8),
+                Fr::from(0u8),
+            ])
+        })?;
+        let inputs = IssuedCredentialVar::new_witness(cs.clone(), || Ok(issued))?;
+        let z_i1 = circuit.generate_step_constraints(cs.clone(), 0, z_i, inputs)?;
+        Ok((cs.is_satisfied()?, z_i1.value()?))
+    }
+
+    #[test]
+    fn test_signed_credential() -> Result<(), Error> {
+        let mut rng = test_rng();
+        let issuer = SigningKey::rand(&mut rng);
+        let circuit = CredentialCircuit::<0>::new(CredentialParams {
+            issuer: CredentialIssuer::Signed(issuer.public_key()),
+            policy: policy(),
+        })?;
+        let poseidon_config = &circuit.poseidon_config;
+        let sign = |credential| IssuedCredential::sign(&issuer, poseidon_config, credential);
+
+        let valid = credential(&mut rng);
+        let (satisfied, z_i1) = step(&circuit, Fr::from(0u8), sign(valid))?;
+        assert!(satisfied);
+        assert_eq!(
+            z_i1,
+            vec![
+                Fr::from(NOW),
+                Fr::from(0u8),
+                chain_transfer(poseidon_config, Fr::from(0u8), valid.holder),
+                Fr::from(1u8)
+            ]
+        );
+
+        for credential in [
+            Credential {
+                expiry: NOW,
+                ..valid
+            },
+            Credential {
+                jurisdiction: UNITED_STATES,
+                ..valid
+            },
+            Credential {
+                accreditation: 0,
+                ..valid
+            },
+        ] {
+            assert!(!policy().allows(&credential, NOW));
+            assert!(!step(&circuit, Fr::from(0u8), sign(credential))?.0);
+        }
+
+        // the prover can't change the jurisdiction of a signed credential
+        let mut tampered = sign(Credential {
+            jurisdiction: UNITED_STATES,
+            ..valid
+        });
+        tampered.credential.jurisdiction = SINGAPORE;
+        assert!(!tampered.verify(poseidon_config, &circuit.params.issuer, Fr::from(0u8)));
+        assert!(!step(&circuit, Fr::from(0u8), tampered)?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_registered_credential() -> Result<(), Error> {
+        const D: usize = 2;
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let credentials = (0..3).map(|_| credential(&mut rng)).collect::<Vec<_>>();
+        let registry = MerkleTree::<D>::new(
+            &poseidon_config,
+            &credentials
+                .iter()
+                .map(|credential| credential.leaf(&poseidon_config))
+                .collect::<Vec<_>>(),
+        );
+        let circuit = CredentialCircuit::<D>::new(CredentialParams {
+            issuer: CredentialIssuer::Registered,
+            policy: policy(),
+        })?;
+        let issuer = &circuit.params.issuer;
+
+        for (index, credential) in credentials.iter().enumerate() {
+            let issued = IssuedCredential::registered(*credential, registry.proof(index));
+            assert!(issued.verify(&poseidon_config, issuer, registry.root()));
+            let (satisfied, z_i1) = step(&circuit, registry.root(), issued)?;
+            assert!(satisfied);
+            assert_eq!(z_i1[1], registry.root());
+        }
+
+        // a credential that was never registered
+        let issued = IssuedCredential::registered(credential(&mut rng), registry.proof(3));
+        assert!(!issued.verify(&poseidon_config, issuer, registry.root()));
+        assert!(!step(&circuit, registry.root(), issued)?.0);
+
+        // a registered credential, against a root published before its registration
+        let issued = IssuedCredential::registered(credentials[0], registry.proof(0));
+        let outdated = MerkleTree::<D>::new(&poseidon_config, &[]).root();
+        assert!(!step(&circuit, outdated, issued)?.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! Demo fund used by the examples. The oracle keys are fixed so that all the examples build the
+//! same circuit, and can therefore share the persisted Nova and Decider parameters.
+//!
+//! The oracles' secret keys are public, so the attestations produced here don't bind anything: a
+//! real deployment sets `CompositeFundParams::oracle` to the custodian's public key, and
+//! `CompositeFundParams::pricing_oracle` to the one of its pricing provider. Likewise, anyone can
+//! decrypt the reports encrypted to the demo regulator.
+use ark_bn254::Fr;
+use ark_ed_on_bn254::Fr as EdFr;
+use ark_ff::PrimeField;
+use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+
+use crate::{
+    amount::TokenAmount,
+    composite::{
+        AttestedAccount, CompositeFundCircuit, CompositeFundParams, CompositeStepInputs,
+        FundSnapshot, MAX_PERIOD_LEN, WHITELIST_DEPTH,
+    },
+    eddsa::SigningKey,
+    encryption::DecryptionKey,
+    merkle::MerkleTree,
+    nav::PRICE_DECIMALS,
+    reporting::{ReportedFundCircuit, ReportedFundParams, ReportedStepInputs},
+};
+
+/// Number of assets of the demo fund: USDC, WETH, WBTC and a tokenized T-bill fund
+pub const N_ASSETS: usize = 4;
+/// Index of USDC, the liquid asset
+pub const USDC: usize = 0;
+
+/// Compliance circuit of the demo fund
+pub type FundCircuit = CompositeFundCircuit<N_ASSETS>;
+/// External inputs of the compliance circuit of the demo fund
+pub type FundStepInputs = CompositeStepInputs<N_ASSETS>;
+/// Compliance circuit of the demo fund, also encrypting the checked holdings to the regulator
+pub type ReportedCircuit = ReportedFundCircuit<N_ASSETS>;
+/// External inputs of the reported compliance circuit of the demo fund
+pub type ReportedInputs = ReportedStepInputs<N_ASSETS>;
+
+/// Key of the demo oracle, attesting the holdings of the fund
+pub fn oracle() -> SigningKey {
+    SigningKey::new(EdFr::from_le_bytes_mod_order(
+        b"arc-fund-manager demo oracle",
+    ))
+}
+
+/// Key of the demo pricing oracle, publishing the prices of the assets
+pub fn pricing_oracle() -> SigningKey {
+    SigningKey::new(EdFr::from_le_bytes_mod_order(
+        b"arc-fund-manager demo pricing oracle",
+    ))
+}
+
+/// Key of the demo regulator, decrypting the reports of the fund
+pub fn regulator() -> DecryptionKey {
+    DecryptionKey::new(EdFr::from_le_bytes_mod_order(
+        b"arc-fund-manager demo regulator",
+    ))
+}
+
+/// Accounts of the demo fund, holding USDC (6 decimals), WETH (18), WBTC (8) and T-bills (18)
+pub fn accounts() -> [AttestedAccount; N_ASSETS] {
+    let account = |id: &[u8], decimals| AttestedAccount {
+        id: Fr::from_le_bytes_mod_order(id),
+        decimals,
+    };
+    [
+        account(b"fund:usdc", 6),
+        account(b"fund:weth", 18),
+        account(b"fund:wbtc", 8),
+        account(b"fund:tbill", 18),
+    ]
+}
+
+/// Compliance rules of the demo fund: position ≤ 40%, liquidity ≥ 10%, and a one-level whitelist
+pub fn params() -> CompositeFundParams<N_ASSETS> {
+    let whitelist = MerkleTree::<WHITELIST_DEPTH>::new(
+        &poseidon_canonical_config::<Fr>(),
+        &[Fr::from(100u32), Fr::from(200u32)],
+    );
+    CompositeFundParams {
+        max_position_pct: 40,
+        min_liquidity_pct: 10,
+        asset_hash: Fr::from(100u32),
+        whitelist_proof: whitelist.proof(0),
+        merkle_root: whitelist.root(),
+        oracle: oracle().public_key(),
+        accounts: accounts(),
+        liquid_asset: USDC,
+        pricing_oracle: pricing_oracle().public_key(),
+        max_period_len: MAX_PERIOD_LEN,
+    }
+}
+
+/// Compliance rules of the demo fund, reporting the checked holdings to the demo regulator
+pub fn reported_params() -> ReportedFundParams<N_ASSETS> {
+    ReportedFundParams {
+        compliance: params(),
+        regulator: regulator().public_key(),
+    }
+}
+
+/// $100M fund: $10M of USDC (10%), 14,000 WETH at $2,500 ($35M, the largest position), 250 WBTC at
+/// $100,000 ($25M) and 300,000 T-bills at $100 ($30M)
+pub fn snapshot() -> FundSnapshot<N_ASSETS> {
+    let units = [10_000_000, 14_000, 250, 300_000];
+    let dollars: [u64; N_ASSETS] = [1, 2_500, 100_000, 100];
+
+    let accounts = accounts();
+    FundSnapshot {
+        holdings: core::array::from_fn(|i| {
+            TokenAmount::from_units(units[i], accounts[i].decimals).expect("fits in 256 bits")
+        }),
+        prices: dollars.map(|dollars| dollars as u128 * 10u128.pow(PRICE_DECIMALS as u32)),
+    }
+}
@@ -0,0 +1,416 @@
+//! ECDSA signatures over secp256k1, verified in-circuit with the non-native gadgets of
+//! `folding_schemes::folding::circuits::nonnative`.
+//!
+//! The signed digest `z` is an element of the constraint field (eg. the Poseidon hash of the
+//! attested data), interpreted as an integer. The circuit computes `R = u1·G + u2·Q`, where
+//! `u1 = z/s` and `u2 = r/s`, and checks that `R.x ≡ r (mod n)`.
+//!
+//! Points are kept in affine coordinates and added with incomplete formulas, whose exceptional
+//! cases are ruled out by enforcing that the added points have distinct x coordinates. So that
+//! honest provers don't hit these cases, the double scalar multiplication starts from an offset
+//! point which is removed at the end. The verification costs a few million constraints, so it is
+//! meant for attestations of custodians that can only sign with secp256k1 keys, EdDSA being much
+//! cheaper otherwise.
+use ark_ec::{short_weierstrass::SWCurveConfig, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    convert::ToBitsGadget,
+    fields::fp::FpVar,
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_secp256k1::{Affine, Config, Fq, Fr as Scalar};
+use ark_std::{borrow::Borrow, rand::RngCore, UniformRand};
+use folding_schemes::folding::circuits::nonnative::uint::NonNativeUintVar;
+use num_bigint::BigUint;
+
+/// Number of bits of the scalars `u1` and `u2`
+const SCALAR_BITS: usize = 256;
+
+pub type PublicKey = Affine;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Signature {
+    pub r: Scalar,
+    pub s: Scalar,
+}
+
+/// Maps an element of `S` to an element of `T`, reducing it modulo `|T|`
+fn convert<S: PrimeField, T: PrimeField>(x: S) -> T {
+    T::from_le_bytes_mod_order(&x.into_bigint().to_bytes_le())
+}
+
+/// Maps a digest in the field `F` to a scalar of secp256k1, as done in-circuit
+pub fn digest_to_scalar<F: PrimeField>(z: F) -> Scalar {
+    convert(z)
+}
+
+#[derive(Clone, Debug)]
+pub struct SigningKey {
+    secret: Scalar,
+    public: PublicKey,
+}
+
+impl SigningKey {
+    pub fn new(secret: Scalar) -> Self {
+        Self {
+            secret,
+            public: (Affine::generator() * secret).into_affine(),
+        }
+    }
+
+    pub fn rand(rng: &mut impl RngCore) -> Self {
+        Self::new(Scalar::rand(rng))
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.public
+    }
+
+    /// Signs the digest `z` with a random nonce
+    pub fn sign(&self, z: Scalar, rng: &mut impl RngCore) -> Signature {
+        loop {
+            let k = Scalar::rand(rng);
+            let r = convert((Affine::generator() * k).into_affine().x);
+            let s = k
+                .inverse()
+                .map(|k_inv| k_inv * (z + r * self.secret))
+                .unwrap_or_default();
+            if !r.is_zero() && !s.is_zero() {
+                return Signature { r, s };
+            }
+        }
+    }
+}
+
+/// Verifies natively the signature `sig` of the digest `z` under the public key `pk`
+pub fn verify(pk: &PublicKey, z: Scalar, sig: &Signature) -> bool {
+    let Some(w) = sig.s.inverse() else {
+        return false;
+    };
+    let p = (Affine::generator() * (z * w) + *pk * (sig.r * w)).into_affine();
+    !sig.r.is_zero() && !p.is_zero() && convert::<Fq, Scalar>(p.x) == sig.r
+}
+
+/// Allocates the hint `v`, as a witness unless all the operands are constants
+fn hint<F: PrimeField, G: folding_schemes::Field>(
+    cs: &ConstraintSystemRef<F>,
+    v: G,
+) -> Result<NonNativeUintVar<F>, SynthesisError> {
+    let mode = if cs.is_none() {
+        AllocationMode::Constant
+    } else {
+        AllocationMode::Witness
+    };
+    NonNativeUintVar::new_variable(cs.clone(), || Ok(v), mode)
+}
+
+/// Affine point of secp256k1, whose coordinates are non-native integers congruent to (but not
+/// necessarily reduced to) the actual coordinates. The point at infinity is not representable.
+#[derive(Clone, Debug)]
+struct PointVar<F: PrimeField> {
+    x: NonNativeUintVar<F>,
+    y: NonNativeUintVar<F>,
+}
+
+impl<F: PrimeField> PointVar<F> {
+    fn constant(p: Affine) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: NonNativeUintVar::new_constant(ConstraintSystemRef::None, p.x)?,
+            y: NonNativeUintVar::new_constant(ConstraintSystemRef::None, p.y)?,
+        })
+    }
+
+    fn cs(&self) -> ConstraintSystemRef<F> {
+        self.x.cs().or(self.y.cs())
+    }
+
+    fn native(&self) -> (Fq, Fq) {
+        (
+            Fq::from(self.x.value().unwrap_or_default()),
+            Fq::from(self.y.value().unwrap_or_default()),
+        )
+    }
+
+    /// Computes `self + other`, enforcing that `self.x != other.x`
+    fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let cs = self.cs().or(other.cs());
+        let ((x1, y1), (x2, y2)) = (self.native(), other.native());
+        let inv = (x2 - x1).inverse().unwrap_or_default();
+        let lambda = (y2 - y1) * inv;
+        let x3 = lambda.square() - x1 - x2;
+        let y3 = lambda * (x1 - x3) - y1;
+
+        let inv = hint(&cs, inv)?;
+        let lambda = hint(&cs, lambda)?;
+        let x3 = hint(&cs, x3)?;
+        let y3 = hint(&cs, y3)?;
+        let one = NonNativeUintVar::new_constant(ConstraintSystemRef::None, Fq::one())?;
+
+        // `inv·(x2 - x1) = 1`, ie. `x2 - x1` is invertible
+        inv.mul_no_align(&other.x)?
+            .enforce_congruent::<Fq>(&inv.mul_no_align(&self.x)?.add_no_align(&one)?)?;
+        // `λ·(x2 - x1) = y2 - y1`
+        lambda
+            .mul_no_align(&other.x)?
+            .add_no_align(&self.y)?
+            .enforce_congruent::<Fq>(&lambda.mul_no_align(&self.x)?.add_no_align(&other.y)?)?;
+        // `x3 = λ² - x1 - x2`
+        x3.add_no_align(&self.x)?
+            .add_no_align(&other.x)?
+            .enforce_congruent::<Fq>(&lambda.mul_no_align(&lambda)?)?;
+        // `y3 = λ·(x1 - x3) - y1`
+        y3.add_no_align(&self.y)?
+            .add_no_align(&lambda.mul_no_align(&x3)?)?
+            .enforce_congruent::<Fq>(&lambda.mul_no_align(&self.x)?)?;
+
+        Ok(Self { x: x3, y: y3 })
+    }
+
+    /// Computes `2·self`. secp256k1 has no point of order 2, so the tangent is never vertical.
+    fn double(&self) -> Result<Self, SynthesisError> {
+        let cs = self.cs();
+        let (x1, y1) = self.native();
+        let lambda = x1.square() * Fq::from(3u64) * y1.double().inverse().unwrap_or_default();
+        let x3 = lambda.square() - x1.double();
+        let y3 = lambda * (x1 - x3) - y1;
+
+        let lambda = hint(&cs, lambda)?;
+        let x3 = hint(&cs, x3)?;
+        let y3 = hint(&cs, y3)?;
+
+        // `λ·2y1 = 3·x1²`
+        let x1_squared = self.x.mul_no_align(&self.x)?;
+        lambda
+            .mul_no_align(&self.y.add_no_align(&self.y)?)?
+            .enforce_congruent::<Fq>(
+                &x1_squared
+                    .add_no_align(&x1_squared)?
+                    .add_no_align(&x1_squared)?,
+            )?;
+        // `x3 = λ² - 2·x1`
+        x3.add_no_align(&self.x)?
+            .add_no_align(&self.x)?
+            .enforce_congruent::<Fq>(&lambda.mul_no_align(&lambda)?)?;
+        // `y3 = λ·(x1 - x3) - y1`
+        y3.add_no_align(&self.y)?
+            .add_no_align(&lambda.mul_no_align(&x3)?)?
+            .enforce_congruent::<Fq>(&lambda.mul_no_align(&self.x)?)?;
+
+        Ok(Self { x: x3, y: y3 })
+    }
+
+    fn select(
+        cond: &Boolean<F>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: cond.select(&true_value.x, &false_value.x)?,
+            y: cond.select(&true_value.y, &false_value.y)?,
+        })
+    }
+}
+
+/// Starting point of the double scalar multiplication: the first point whose x coordinate is
+/// `2^128 + i`. Knowing its discrete logarithm does not help forging signatures, it only makes the
+/// exceptional cases of the addition likely to happen.
+fn offset_point() -> Affine {
+    let base = Fq::from(BigUint::one() << 128);
+    (0u64..)
+        .find_map(|i| Affine::get_point_from_x_unchecked(base + Fq::from(i), false))
+        .expect("half of the x coordinates are on the curve")
+}
+
+/// In-circuit public key, whose coordinates are enforced to be on the curve when allocated as
+/// witnesses.
+#[derive(Clone, Debug)]
+pub struct PublicKeyVar<F: PrimeField>(PointVar<F>);
+
+impl<F: PrimeField> AllocVar<PublicKey, F> for PublicKeyVar<F> {
+    fn new_variable<T: Borrow<PublicKey>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|pk| {
+            let cs = cs.into().cs();
+            let pk = pk.borrow();
+
+            let point = PointVar {
+                x: NonNativeUintVar::new_variable(cs.clone(), || Ok(pk.x), mode)?,
+                y: NonNativeUintVar::new_variable(cs.clone(), || Ok(pk.y), mode)?,
+            };
+            if mode != AllocationMode::Constant {
+                // `y² = x³ + b`, which also rules out the point at infinity, encoded as `(0, 0)`
+                let b = NonNativeUintVar::new_constant(ConstraintSystemRef::None, Config::COEFF_B)?;
+                point.y.mul_no_align(&point.y)?.enforce_congruent::<Fq>(
+                    &point
+                        .x
+                        .mul_no_align(&point.x)?
+                        .mul_no_align(&point.x)?
+                        .add_no_align(&b)?,
+                )?;
+            }
+
+            Ok(Self(point))
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SignatureVar<F: PrimeField> {
+    pub r: NonNativeUintVar<F>,
+    pub s: NonNativeUintVar<F>,
+}
+
+impl<F: PrimeField> AllocVar<Signature, F> for SignatureVar<F> {
+    fn new_variable<T: Borrow<Signature>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|sig| {
+            let cs = cs.into().cs();
+            let sig = sig.borrow();
+
+            Ok(Self {
+                r: NonNativeUintVar::new_variable(cs.clone(), || Ok(sig.r), mode)?,
+                s: NonNativeUintVar::new_variable(cs, || Ok(sig.s), mode)?,
+            })
+        })
+    }
+}
+
+/// Enforces that `sig` is a valid signature of the digest `z` under the public key `pk`
+pub fn verify_gadget<F: PrimeField>(
+    pk: &PublicKeyVar<F>,
+    z: &FpVar<F>,
+    sig: &SignatureVar<F>,
+) -> Result<(), SynthesisError> {
+    let cs = pk.0.cs().or(z.cs()).or(sig.r.cs()).or(sig.s.cs());
+    let mode = if cs.is_none() {
+        AllocationMode::Constant
+    } else {
+        AllocationMode::Witness
+    };
+
+    // Compute `w = 1/s`, `u1 = z·w` and `u2 = r·w` outside the circuit and provide them as hints
+    let r = Scalar::from(sig.r.value().unwrap_or_default());
+    let s = Scalar::from(sig.s.value().unwrap_or_default());
+    let w = s.inverse().unwrap_or_default();
+    let u1 = digest_to_scalar(z.value().unwrap_or_default()) * w;
+    let u2 = r * w;
+
+    let r_inv = hint(&cs, r.inverse().unwrap_or_default())?;
+    let w = hint(&cs, w)?;
+    let u1_bits =
+        Vec::<Boolean<F>>::new_variable(cs.clone(), || Ok(u1.into_bigint().to_bits_le()), mode)?;
+    let u2_bits =
+        Vec::<Boolean<F>>::new_variable(cs.clone(), || Ok(u2.into_bigint().to_bits_le()), mode)?;
+    let one = NonNativeUintVar::new_constant(ConstraintSystemRef::None, Scalar::one())?;
+
+    // `r` and `s` are invertible modulo `n`, ie. they are non-zero
+    sig.r
+        .mul_no_align(&r_inv)?
+        .enforce_congruent::<Scalar>(&one)?;
+    sig.s.mul_no_align(&w)?.enforce_congruent::<Scalar>(&one)?;
+    // `u1 = z/s` and `u2 = r/s`
+    NonNativeUintVar::from(&u1_bits)
+        .enforce_congruent::<Scalar>(&NonNativeUintVar::from(z.to_bits_le()?).mul_no_align(&w)?)?;
+    NonNativeUintVar::from(&u2_bits).enforce_congruent::<Scalar>(&sig.r.mul_no_align(&w)?)?;
+
+    // `R = H' + u1·G + u2·Q`, where `H' = 2^SCALAR_BITS·H` is the accumulated offset
+    let offset = offset_point();
+    let g = PointVar::constant(Affine::generator())?;
+    let mut acc = PointVar::constant(offset)?;
+    for (b1, b2) in u1_bits.iter().zip(&u2_bits).rev() {
+        acc = acc.double()?;
+        acc = PointVar::select(b1, &acc.add(&g)?, &acc)?;
+        acc = PointVar::select(b2, &acc.add(&pk.0)?, &acc)?;
+    }
+    let offset = (offset * Scalar::from(2u64).pow([SCALAR_BITS as u64])).into_affine();
+    let r_point = acc.add(&PointVar::constant(-offset)?)?;
+
+    // `R.x mod q ≡ r (mod n)`
+    r_point
+        .x
+        .modulo::<Fq>()?
+        .enforce_congruent::<Scalar>(&sig.r)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = test_rng();
+        let key = SigningKey::rand(&mut rng);
+        let z = digest_to_scalar(Fr::rand(&mut rng));
+
+        let sig = key.sign(z, &mut rng);
+        assert!(verify(&key.public_key(), z, &sig));
+        assert!(!verify(&key.public_key(), z + Scalar::one(), &sig));
+        assert!(!verify(&SigningKey::rand(&mut rng).public_key(), z, &sig));
+    }
+
+    #[test]
+    fn test_point_ops() -> Result<(), SynthesisError> {
+        let mut rng = test_rng();
+        let (p, q) = (
+            (Affine::generator() * Scalar::rand(&mut rng)).into_affine(),
+            (Affine::generator() * Scalar::rand(&mut rng)).into_affine(),
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let p_var = PublicKeyVar::new_witness(cs.clone(), || Ok(p))?.0;
+        let q_var = PublicKeyVar::new_witness(cs.clone(), || Ok(q))?.0;
+        let sum = p_var.add(&q_var)?.native();
+        let double = p_var.double()?.native();
+        assert!(cs.is_satisfied()?);
+        assert_eq!(sum, (p + q).into_affine().xy().unwrap());
+        assert_eq!(double, (p + p).into_affine().xy().unwrap());
+
+        // adding a point to itself is rejected
+        p_var.add(&p_var)?;
+        assert!(!cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_public_key_on_curve() -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let not_on_curve = Affine::new_unchecked(Fq::one(), Fq::one());
+        PublicKeyVar::<Fr>::new_witness(cs.clone(), || Ok(not_on_curve))?;
+        assert!(!cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_gadget() -> Result<(), SynthesisError> {
+        let mut rng = test_rng();
+        let key = SigningKey::rand(&mut rng);
+        let z = Fr::rand(&mut rng);
+        let sig = key.sign(digest_to_scalar(z), &mut rng);
+
+        let check = |z: Fr| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let pk = PublicKeyVar::new_constant(cs.clone(), key.public_key())?;
+            let z = FpVar::new_witness(cs.clone(), || Ok(z))?;
+            let sig = SignatureVar::new_witness(cs.clone(), || Ok(sig))?;
+            verify_gadget(&pk, &z, &sig)?;
+            cs.is_satisfied()
+        };
+
+        assert!(check(z)?);
+        assert!(!check(z + Fr::one())?);
+        Ok(())
+    }
+}
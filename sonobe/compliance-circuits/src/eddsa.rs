@@ -0,0 +1,239 @@
+//! EdDSA signatures over BabyJubJub, the twisted Edwards curve defined over BN254's scalar field
+//! (`ark-ed-on-bn254`), so that signatures can be verified in-circuit with native arithmetic.
+//!
+//! A signature `(R, s)` of the message `m` under the public key `A = sk·B` is valid iff
+//! `s·B = R + c·A`, where `c = Poseidon(R.x, R.y, A.x, A.y, m)` is computed with the given
+//! Poseidon config. The curve is the `a = 1` representation used by arkworks, which is isomorphic
+//! to (but not encoded as) the one of circomlib.
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ed_on_bn254::{constraints::EdwardsVar, EdwardsAffine, Fr as EdFr};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    convert::ToBitsGadget,
+    eq::EqGadget,
+    fields::fp::FpVar,
+    groups::CurveVar,
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{Namespace, SynthesisError};
+use ark_std::{borrow::Borrow, rand::RngCore, UniformRand};
+
+pub type PublicKey = EdwardsAffine;
+pub type PublicKeyVar = EdwardsVar;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub r: EdwardsAffine,
+    pub s: EdFr,
+}
+
+impl Default for Signature {
+    fn default() -> Self {
+        Self {
+            r: EdwardsAffine::zero(),
+            s: EdFr::zero(),
+        }
+    }
+}
+
+/// Maps an element of the base field to the scalar field, reducing it modulo the group order.
+fn to_scalar(x: Fr) -> EdFr {
+    EdFr::from_le_bytes_mod_order(&x.into_bigint().to_bytes_le())
+}
+
+/// Computes the challenge `c = Poseidon(R.x, R.y, A.x, A.y, m)`
+pub fn challenge(
+    poseidon_config: &PoseidonConfig<Fr>,
+    r: &EdwardsAffine,
+    pk: &PublicKey,
+    msg: &[Fr],
+) -> Fr {
+    let mut sponge = PoseidonSponge::new(poseidon_config);
+    sponge.absorb(&[&[r.x, r.y, pk.x, pk.y], msg].concat());
+    sponge.squeeze_field_elements(1)[0]
+}
+
+#[derive(Clone, Debug)]
+pub struct SigningKey {
+    secret: EdFr,
+    public: PublicKey,
+}
+
+impl SigningKey {
+    pub fn new(secret: EdFr) -> Self {
+        Self {
+            secret,
+            public: (EdwardsAffine::generator() * secret).into_affine(),
+        }
+    }
+
+    pub fn rand(rng: &mut impl RngCore) -> Self {
+        Self::new(EdFr::rand(rng))
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.public
+    }
+
+    /// Signs `msg`, with a nonce derived deterministically from the secret key and the message.
+    pub fn sign(&self, poseidon_config: &PoseidonConfig<Fr>, msg: &[Fr]) -> Signature {
+        let nonce = {
+            let mut sponge = PoseidonSponge::new(poseidon_config);
+            // the secret key is smaller than the modulus of `Fr`, so it is embedded as is
+            let secret = Fr::from_le_bytes_mod_order(&self.secret.into_bigint().to_bytes_le());
+            sponge.absorb(&[&[secret], msg].concat());
+            to_scalar(sponge.squeeze_field_elements(1)[0])
+        };
+        let r = (EdwardsAffine::generator() * nonce).into_affine();
+        let c = challenge(poseidon_config, &r, &self.public, msg);
+        Signature {
+            r,
+            s: nonce + to_scalar(c) * self.secret,
+        }
+    }
+}
+
+/// Verifies natively the signature `sig` of `msg` under the public key `pk`
+pub fn verify(
+    poseidon_config: &PoseidonConfig<Fr>,
+    pk: &PublicKey,
+    msg: &[Fr],
+    sig: &Signature,
+) -> bool {
+    let c = challenge(poseidon_config, &sig.r, pk, msg);
+    EdwardsAffine::generator() * sig.s == sig.r.into_group() + *pk * to_scalar(c)
+}
+
+/// In-circuit representation of a `Signature`, where `s` is kept as its little-endian bits.
+#[derive(Clone, Debug)]
+pub struct SignatureVar {
+    pub r: EdwardsVar,
+    pub s: Vec<Boolean<Fr>>,
+}
+
+impl AllocVar<Signature, Fr> for SignatureVar {
+    fn new_variable<T: Borrow<Signature>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|sig| {
+            let cs = cs.into().cs();
+            let sig = sig.borrow();
+
+            let r = EdwardsVar::new_variable(cs.clone(), || Ok(sig.r), mode)?;
+            // `s` is a scalar, ie. it fits in `EdFr::MODULUS_BIT_SIZE` bits. Bigger values would
+            // only make the signature malleable, as `s·B` is computed in a group of order `|EdFr|`
+            let s = Vec::new_variable(
+                cs,
+                || Ok(sig.s.into_bigint().to_bits_le()[..EdFr::MODULUS_BIT_SIZE as usize].to_vec()),
+                mode,
+            )?;
+
+            Ok(Self { r, s })
+        })
+    }
+}
+
+/// Computes in-circuit the challenge `c = Poseidon(R.x, R.y, A.x, A.y, m)`
+pub fn challenge_gadget(
+    poseidon_config: &PoseidonConfig<Fr>,
+    r: &EdwardsVar,
+    pk: &PublicKeyVar,
+    msg: &[FpVar<Fr>],
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let cs = r.cs().or(pk.cs()).or(msg.cs());
+    let mut sponge = PoseidonSpongeVar::new(cs, poseidon_config);
+    sponge.absorb(&[&[r.x.clone(), r.y.clone(), pk.x.clone(), pk.y.clone()], msg].concat())?;
+    Ok(sponge.squeeze_field_elements(1)?.remove(0))
+}
+
+/// Enforces that `sig` is a valid signature of `msg` under the public key `pk`
+pub fn verify_gadget(
+    poseidon_config: &PoseidonConfig<Fr>,
+    pk: &PublicKeyVar,
+    msg: &[FpVar<Fr>],
+    sig: &SignatureVar,
+) -> Result<(), SynthesisError> {
+    let c = challenge_gadget(poseidon_config, &sig.r, pk, msg)?;
+    // `pk` has prime order, so multiplying it by `c` or by `c mod |EdFr|` (as done natively) gives
+    // the same point. This also forces `R` to have prime order.
+    let lhs = EdwardsVar::constant(EdwardsAffine::generator().into_group())
+        .scalar_mul_le(sig.s.iter())?;
+    let rhs = sig.r.clone() + pk.scalar_mul_le(c.to_bits_le()?.iter())?;
+    lhs.enforce_equal(&rhs)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::test_rng;
+    use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+
+    use super::*;
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let key = SigningKey::rand(&mut rng);
+        let msg = (0..3).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+
+        let sig = key.sign(&poseidon_config, &msg);
+        assert!(verify(&poseidon_config, &key.public_key(), &msg, &sig));
+
+        let other_msg = [msg[0], msg[1], msg[2] + Fr::from(1u64)];
+        assert!(!verify(
+            &poseidon_config,
+            &key.public_key(),
+            &other_msg,
+            &sig
+        ));
+        let other_key = SigningKey::rand(&mut rng);
+        assert!(!verify(
+            &poseidon_config,
+            &other_key.public_key(),
+            &msg,
+            &sig
+        ));
+    }
+
+    #[test]
+    fn test_verify_gadget() -> Result<(), SynthesisError> {
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let key = SigningKey::rand(&mut rng);
+        let msg = (0..3).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let sig = key.sign(&poseidon_config, &msg);
+
+        let check = |pk: PublicKey, msg: &[Fr], sig: Signature| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let pk = PublicKeyVar::new_constant(cs.clone(), pk)?;
+            let msg = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(msg.to_vec()))?;
+            let sig = SignatureVar::new_witness(cs.clone(), || Ok(sig))?;
+            verify_gadget(&poseidon_config, &pk, &msg, &sig)?;
+            cs.is_satisfied()
+        };
+
+        assert!(check(key.public_key(), &msg, sig)?);
+
+        let other_msg = [msg[0] + Fr::from(1u64), msg[1], msg[2]];
+        assert!(!check(key.public_key(), &other_msg, sig)?);
+        let other_key = SigningKey::rand(&mut rng);
+        assert!(!check(other_key.public_key(), &msg, sig)?);
+        let forged = Signature {
+            s: sig.s + EdFr::from(1u64),
+            ..sig
+        };
+        assert!(!check(key.public_key(), &msg, forged)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+//! Hybrid ElGamal encryption over BabyJubJub, with a Poseidon key stream, so that data witnessed by
+//! a circuit can be encrypted in-circuit with native arithmetic over BN254.
+//!
+//! A message `m` is encrypted to the public key `P = sk·B` with a nonce `r` as `(R, c)`, where
+//! `R = r·B` and `c_i = m_i + k_i` for the key stream `k = Poseidon(S.x, S.y)` squeezed to the
+//! length of the message, from the shared point `S = r·P = sk·R`. The nonce must be fresh and
+//! random for each message, otherwise the key stream would be reused.
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ed_on_bn254::{constraints::EdwardsVar, EdwardsAffine, Fr as EdFr};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{boolean::Boolean, fields::fp::FpVar, groups::CurveVar, GR1CSVar};
+use ark_relations::gr1cs::SynthesisError;
+use ark_std::{rand::RngCore, UniformRand};
+
+pub type EncryptionKey = EdwardsAffine;
+pub type EncryptionKeyVar = EdwardsVar;
+
+#[derive(Clone, Debug)]
+pub struct DecryptionKey {
+    secret: EdFr,
+    public: EncryptionKey,
+}
+
+impl DecryptionKey {
+    pub fn new(secret: EdFr) -> Self {
+        Self {
+            secret,
+            public: (EdwardsAffine::generator() * secret).into_affine(),
+        }
+    }
+
+    pub fn rand(rng: &mut impl RngCore) -> Self {
+        Self::new(EdFr::rand(rng))
+    }
+
+    pub fn public_key(&self) -> EncryptionKey {
+        self.public
+    }
+
+    /// Decrypts the ciphertext, with the shared point `S = sk·R`
+    pub fn decrypt(
+        &self,
+        poseidon_config: &PoseidonConfig<Fr>,
+        ciphertext: &Ciphertext,
+    ) -> Vec<Fr> {
+        let shared = (ciphertext.ephemeral * self.secret).into_affine();
+        key_stream(poseidon_config, &shared, ciphertext.payload.len())
+            .into_iter()
+            .zip(&ciphertext.payload)
+            .map(|(k, c)| *c - k)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ciphertext {
+    /// Ephemeral point `R = r·B`
+    pub ephemeral: EdwardsAffine,
+    pub payload: Vec<Fr>,
+}
+
+impl Ciphertext {
+    /// Returns the hash `Poseidon(R.x, R.y, c)` of the ciphertext
+    pub fn hash(&self, poseidon_config: &PoseidonConfig<Fr>) -> Fr {
+        let mut sponge = PoseidonSponge::new(poseidon_config);
+        sponge.absorb(&[&[self.ephemeral.x, self.ephemeral.y], &self.payload[..]].concat());
+        sponge.squeeze_field_elements(1)[0]
+    }
+}
+
+/// Derives the key stream of `len` elements from the shared point
+fn key_stream(poseidon_config: &PoseidonConfig<Fr>, shared: &EdwardsAffine, len: usize) -> Vec<Fr> {
+    let mut sponge = PoseidonSponge::new(poseidon_config);
+    sponge.absorb(&[shared.x, shared.y]);
+    sponge.squeeze_field_elements(len)
+}
+
+/// Encrypts natively `msg` to the public key `pk` with the nonce `r`
+pub fn encrypt(
+    poseidon_config: &PoseidonConfig<Fr>,
+    pk: &EncryptionKey,
+    nonce: EdFr,
+    msg: &[Fr],
+) -> Ciphertext {
+    let shared = (*pk * nonce).into_affine();
+    Ciphertext {
+        ephemeral: (EdwardsAffine::generator() * nonce).into_affine(),
+        payload: key_stream(poseidon_config, &shared, msg.len())
+            .into_iter()
+            .zip(msg)
+            .map(|(k, m)| *m + k)
+            .collect(),
+    }
+}
+
+/// Returns the little-endian bits of the nonce, as allocated by the circuits
+pub fn nonce_to_bits(nonce: EdFr) -> Vec<bool> {
+    nonce.into_bigint().to_bits_le()[..EdFr::MODULUS_BIT_SIZE as usize].to_vec()
+}
+
+/// In-circuit representation of a `Ciphertext`
+#[derive(Clone, Debug)]
+pub struct CiphertextVar {
+    pub ephemeral: EdwardsVar,
+    pub payload: Vec<FpVar<Fr>>,
+}
+
+impl CiphertextVar {
+    /// Computes in-circuit the hash `Poseidon(R.x, R.y, c)` of the ciphertext
+    pub fn hash(&self, poseidon_config: &PoseidonConfig<Fr>) -> Result<FpVar<Fr>, SynthesisError> {
+        let mut sponge = PoseidonSpongeVar::new(self.payload.cs(), poseidon_config);
+        sponge.absorb(
+            &[
+                &[self.ephemeral.x.clone(), self.ephemeral.y.clone()],
+                &self.payload[..],
+            ]
+            .concat(),
+        )?;
+        Ok(sponge.squeeze_field_elements(1)?.remove(0))
+    }
+}
+
+/// Encrypts in-circuit `msg` to the public key `pk`, with the nonce given by its little-endian bits
+pub fn encrypt_gadget(
+    poseidon_config: &PoseidonConfig<Fr>,
+    pk: &EncryptionKeyVar,
+    nonce: &[Boolean<Fr>],
+    msg: &[FpVar<Fr>],
+) -> Result<CiphertextVar, SynthesisError> {
+    let ephemeral = EdwardsVar::constant(EdwardsAffine::generator().into_group())
+        .scalar_mul_le(nonce.iter())?;
+    let shared = pk.scalar_mul_le(nonce.iter())?;
+
+    let mut sponge = PoseidonSpongeVar::new(msg.cs().or(shared.cs()), poseidon_config);
+    sponge.absorb(&[shared.x, shared.y])?;
+    let key_stream = sponge.squeeze_field_elements(msg.len())?;
+
+    Ok(CiphertextVar {
+        ephemeral,
+        payload: key_stream.iter().zip(msg).map(|(k, m)| m + k).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_r1cs_std::alloc::AllocVar;
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::test_rng;
+    use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let regulator = DecryptionKey::rand(&mut rng);
+        let msg = (0..5).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+
+        let ciphertext = encrypt(
+            &poseidon_config,
+            &regulator.public_key(),
+            EdFr::rand(&mut rng),
+            &msg,
+        );
+        assert_ne!(ciphertext.payload, msg);
+        assert_eq!(regulator.decrypt(&poseidon_config, &ciphertext), msg);
+        assert_ne!(
+            DecryptionKey::rand(&mut rng).decrypt(&poseidon_config, &ciphertext),
+            msg
+        );
+    }
+
+    #[test]
+    fn test_encrypt_gadget() -> Result<(), SynthesisError> {
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let regulator = DecryptionKey::rand(&mut rng);
+        let msg = (0..5).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let nonce = EdFr::rand(&mut rng);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let pk = EncryptionKeyVar::new_constant(cs.clone(), regulator.public_key())?;
+        let nonce_var = Vec::<Boolean<Fr>>::new_witness(cs.clone(), || Ok(nonce_to_bits(nonce)))?;
+        let msg_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(msg.clone()))?;
+        let ciphertext = encrypt_gadget(&poseidon_config, &pk, &nonce_var, &msg_var)?;
+        let hash = ciphertext.hash(&poseidon_config)?;
+        assert!(cs.is_satisfied()?);
+
+        let expected = encrypt(&poseidon_config, &regulator.public_key(), nonce, &msg);
+        assert_eq!(
+            ciphertext.ephemeral.value()?.into_affine(),
+            expected.ephemeral
+        );
+        assert_eq!(ciphertext.payload.value()?, expected.payload);
+        assert_eq!(hash.value()?, expected.hash(&poseidon_config));
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! Helper gadgets shared by the compliance circuits.
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::fp::FpVar,
+    GR1CSVar,
+};
+use ark_relations::gr1cs::SynthesisError;
+
+/// Enforces that `x` fits in `length` bits, returning its little-endian bit decomposition.
+///
+/// `length` should be smaller than `F::MODULUS_BIT_SIZE`, so that the decomposition is unique and
+/// negative values (ie. `x > (|F| - 1) / 2`) are rejected.
+pub fn enforce_bit_length<F: PrimeField>(
+    x: &FpVar<F>,
+    length: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let cs = x.cs();
+    let mode = if cs.is_none() {
+        AllocationMode::Constant
+    } else {
+        AllocationMode::Witness
+    };
+
+    let bits = (0..length)
+        .map(|i| {
+            Boolean::new_variable(
+                cs.clone(),
+                || Ok(x.value().unwrap_or_default().into_bigint().get_bit(i)),
+                mode,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp(&bits)?.enforce_equal(x)?;
+
+    Ok(bits)
+}
+
+/// Enforces that `x <= y`, where both `x` and `y` fit in `length` bits.
+pub fn enforce_le<F: PrimeField>(
+    x: &FpVar<F>,
+    y: &FpVar<F>,
+    length: usize,
+) -> Result<(), SynthesisError> {
+    enforce_bit_length(&(y - x), length)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_relations::gr1cs::ConstraintSystem;
+
+    use super::*;
+
+    #[test]
+    fn test_enforce_bit_length() -> Result<(), SynthesisError> {
+        for (x, length, expected) in [
+            (Fr::from(0u64), 8, true),
+            (Fr::from(255u64), 8, true),
+            (Fr::from(256u64), 8, false),
+            (-Fr::from(1u64), 64, false),
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let x = FpVar::new_witness(cs.clone(), || Ok(x))?;
+            enforce_bit_length(&x, length)?;
+            assert_eq!(cs.is_satisfied()?, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_enforce_le() -> Result<(), SynthesisError> {
+        for (x, y, expected) in [(3u64, 5u64, true), (5, 5, true), (6, 5, false)] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let x = FpVar::new_witness(cs.clone(), || Ok(Fr::from(x)))?;
+            let y = FpVar::new_witness(cs.clone(), || Ok(Fr::from(y)))?;
+            enforce_le(&x, &y, 64)?;
+            assert_eq!(cs.is_satisfied()?, expected);
+        }
+        Ok(())
+    }
+}
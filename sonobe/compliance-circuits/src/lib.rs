@@ -0,0 +1,32 @@
+//! Compliance circuits of the tokenized fund, as sonobe `FCircuit`s, together with the gadgets
+//! they are built from.
+//!
+//! - `composite`: the step circuit folded by Nova, checking the position, liquidity and whitelist
+//!   rules over consecutive reporting periods
+//! - `concentration`: concentration limits on the largest holdings, such as UCITS 5/10/40
+//! - `credentials`: KYC and accreditation credentials of the receivers of the shares, checked
+//!   over a batch of transfers
+//! - `reporting`: the composite circuit, additionally encrypting the checked holdings to the view
+//!   key of a regulator
+//! - `amount`: 256-bit token amounts with decimals, and their in-circuit arithmetic
+//! - `nav`: the net asset value, derived from the holdings and a signed price vector
+//! - `attestation`: balances signed by an oracle, so that the prover can't invent them
+//! - `eddsa`: EdDSA over BabyJubJub, cheap to verify in-circuit over BN254
+//! - `encryption`: ElGamal encryption over BabyJubJub with a Poseidon key stream, in-circuit
+//! - `ecdsa`: ECDSA over secp256k1, verified with non-native arithmetic
+//! - `gadgets`: range checks shared by the circuits
+//! - `merkle`: Poseidon Merkle trees and their membership proofs
+//! - `demo`: the demo fund used by the examples
+pub mod amount;
+pub mod attestation;
+pub mod composite;
+pub mod concentration;
+pub mod credentials;
+pub mod demo;
+pub mod ecdsa;
+pub mod eddsa;
+pub mod encryption;
+pub mod gadgets;
+pub mod merkle;
+pub mod nav;
+pub mod reporting;
@@ -0,0 +1,204 @@
+//! Binary Merkle trees hashed with Poseidon, whose membership proofs are checked in-circuit with
+//! native arithmetic: the whitelist of assets of `CompositeFundCircuit` and the registry of
+//! credentials both use these gadgets. As in the whitelist of `arc-fund-circuits`, a leaf is opened
+//! by its siblings from the bottom up, and by the bits of its index, which tell on which side it
+//! lies.
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_ff::Zero;
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    fields::fp::FpVar,
+    select::CondSelectGadget,
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{Namespace, SynthesisError};
+use ark_std::borrow::Borrow;
+
+/// Computes the parent `Poseidon(left, right)` of two nodes
+pub fn hash_node(poseidon_config: &PoseidonConfig<Fr>, left: Fr, right: Fr) -> Fr {
+    let mut sponge = PoseidonSponge::new(poseidon_config);
+    sponge.absorb(&[left, right]);
+    sponge.squeeze_field_elements(1)[0]
+}
+
+/// Computes in-circuit the parent `Poseidon(left, right)` of two nodes
+pub fn hash_node_gadget(
+    poseidon_config: &PoseidonConfig<Fr>,
+    left: &FpVar<Fr>,
+    right: &FpVar<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::new(left.cs().or(right.cs()), poseidon_config);
+    sponge.absorb(&[left.clone(), right.clone()])?;
+    Ok(sponge.squeeze_field_elements(1)?.remove(0))
+}
+
+/// Merkle tree of depth `D`, whose missing leaves are zero
+#[derive(Clone, Debug)]
+pub struct MerkleTree<const D: usize> {
+    /// Nodes of each level, from the leaves up to the root
+    levels: Vec<Vec<Fr>>,
+}
+
+impl<const D: usize> MerkleTree<D> {
+    pub fn new(poseidon_config: &PoseidonConfig<Fr>, leaves: &[Fr]) -> Self {
+        assert!(leaves.len() <= 1 << D, "Too many leaves for the tree depth");
+        let mut level = leaves.to_vec();
+        level.resize(1 << D, Fr::zero());
+        let mut levels = vec![level];
+        for depth in 0..D {
+            let level = levels[depth]
+                .chunks(2)
+                .map(|pair| hash_node(poseidon_config, pair[0], pair[1]))
+                .collect();
+            levels.push(level);
+        }
+        Self { levels }
+    }
+
+    pub fn root(&self) -> Fr {
+        self.levels[D][0]
+    }
+
+    /// Returns the membership proof of the leaf at `index`
+    pub fn proof(&self, index: usize) -> MerkleProof<D> {
+        MerkleProof {
+            siblings: core::array::from_fn(|depth| self.levels[depth][(index >> depth) ^ 1]),
+            index: index as u64,
+        }
+    }
+}
+
+/// Membership proof of a leaf in a Merkle tree of depth `D`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MerkleProof<const D: usize> {
+    /// Siblings from the leaf up to the root
+    pub siblings: [Fr; D],
+    pub index: u64,
+}
+
+impl<const D: usize> Default for MerkleProof<D> {
+    fn default() -> Self {
+        Self {
+            siblings: [Fr::zero(); D],
+            index: 0,
+        }
+    }
+}
+
+impl<const D: usize> MerkleProof<D> {
+    /// Computes natively the root of the tree from `leaf`
+    pub fn root(&self, poseidon_config: &PoseidonConfig<Fr>, leaf: Fr) -> Fr {
+        self.siblings
+            .iter()
+            .enumerate()
+            .fold(leaf, |node, (depth, sibling)| {
+                if (self.index >> depth) & 1 == 1 {
+                    hash_node(poseidon_config, *sibling, node)
+                } else {
+                    hash_node(poseidon_config, node, *sibling)
+                }
+            })
+    }
+}
+
+/// In-circuit representation of a `MerkleProof`, where the index is kept as its `D` little-endian
+/// bits
+#[derive(Clone, Debug)]
+pub struct MerkleProofVar {
+    pub siblings: Vec<FpVar<Fr>>,
+    pub index_bits: Vec<Boolean<Fr>>,
+}
+
+impl MerkleProofVar {
+    /// Computes in-circuit the root of the tree from `leaf`
+    pub fn root(
+        &self,
+        poseidon_config: &PoseidonConfig<Fr>,
+        leaf: &FpVar<Fr>,
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        self.siblings.iter().zip(&self.index_bits).try_fold(
+            leaf.clone(),
+            |node, (sibling, is_right)| {
+                let left = FpVar::conditionally_select(is_right, sibling, &node)?;
+                let right = FpVar::conditionally_select(is_right, &node, sibling)?;
+                hash_node_gadget(poseidon_config, &left, &right)
+            },
+        )
+    }
+}
+
+impl<const D: usize> AllocVar<MerkleProof<D>, Fr> for MerkleProofVar {
+    fn new_variable<T: Borrow<MerkleProof<D>>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|proof| {
+            let cs = cs.into().cs();
+            let proof = proof.borrow();
+
+            let siblings = Vec::new_variable(cs.clone(), || Ok(proof.siblings.to_vec()), mode)?;
+            let index_bits = Vec::new_variable(
+                cs,
+                || {
+                    Ok((0..D)
+                        .map(|i| (proof.index >> i) & 1 == 1)
+                        .collect::<Vec<_>>())
+                },
+                mode,
+            )?;
+
+            Ok(Self {
+                siblings,
+                index_bits,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::{test_rng, UniformRand};
+    use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+
+    use super::*;
+
+    #[test]
+    fn test_merkle_proof() -> Result<(), SynthesisError> {
+        const D: usize = 3;
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let leaves = (0..6).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let tree = MerkleTree::<D>::new(&poseidon_config, &leaves);
+
+        let check = |leaf: Fr, proof: MerkleProof<D>| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let leaf = FpVar::new_witness(cs.clone(), || Ok(leaf))?;
+            let proof = MerkleProofVar::new_witness(cs.clone(), || Ok(proof))?;
+            let root = proof.root(&poseidon_config, &leaf)?;
+            Ok::<_, SynthesisError>(cs.is_satisfied()? && root.value()? == tree.root())
+        };
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.proof(index);
+            assert_eq!(proof.root(&poseidon_config, *leaf), tree.root());
+            assert!(check(*leaf, proof)?);
+        }
+
+        // a leaf opened at the position of another one
+        let proof = MerkleProof {
+            index: 1,
+            ..tree.proof(0)
+        };
+        assert_ne!(proof.root(&poseidon_config, leaves[0]), tree.root());
+        assert!(!check(leaves[0], proof)?);
+        Ok(())
+    }
+}
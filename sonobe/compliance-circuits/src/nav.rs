@@ -0,0 +1,381 @@
+//! Net asset value (NAV) of the fund, derived from its holdings and the prices of its assets.
+//!
+//! Holdings are token amounts (see `crate::amount`), and prices are in USD per whole token with
+//! `PRICE_DECIMALS` decimals. As these are the decimals of normalised amounts, a holding of a token
+//! with `d` decimals is worth `holding · price / 10^d` USD with `NORMALIZED_DECIMALS` decimals,
+//! rounded down so that the NAV never overstates the holdings. The NAV is the sum of these values.
+//!
+//! The prices are published by a pricing oracle, which commits to the price vector of each period
+//! with Poseidon and signs the commitment. The circuits recompute the commitment from the prices
+//! they use, and verify its signature under the pricing oracle's public key. The commitment is
+//! also returned, so that the folded state can expose the prices that were used to the contract.
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    fields::fp::FpVar,
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{Namespace, SynthesisError};
+use ark_std::borrow::Borrow;
+use num_bigint::BigUint;
+
+use crate::{
+    amount::{div_round, AmountVar, Rounding, TokenAmount, NORMALIZED_DECIMALS},
+    attestation::TIMESTAMP_BITS,
+    eddsa::{self, PublicKeyVar, Signature, SignatureVar, SigningKey},
+    gadgets::enforce_bit_length,
+};
+
+/// Number of decimals of prices, the same as normalised amounts
+pub const PRICE_DECIMALS: u8 = NORMALIZED_DECIMALS;
+/// Number of bits of prices
+pub const PRICE_BITS: usize = 128;
+/// Rounding of the values of the holdings
+const ROUNDING: Rounding = Rounding::Down;
+
+/// Returns `10^decimals`, the divisor of the product of a holding and its price
+fn scale(decimals: u8) -> BigUint {
+    BigUint::from(10u8).pow(decimals as u32)
+}
+
+/// Returns the value of `holding` at `price`, in USD with `NORMALIZED_DECIMALS` decimals
+pub fn position_value(holding: &TokenAmount, price: u128) -> BigUint {
+    div_round(
+        &(BigUint::from(holding.value) * price),
+        &scale(holding.decimals),
+        ROUNDING,
+    )
+}
+
+/// Returns the NAV of the `holdings` at `prices`, in USD with `NORMALIZED_DECIMALS` decimals
+pub fn net_asset_value(holdings: &[TokenAmount], prices: &[u128]) -> BigUint {
+    holdings
+        .iter()
+        .zip(prices)
+        .map(|(holding, price)| position_value(holding, *price))
+        .sum()
+}
+
+/// Computes in-circuit the value of `holding`, an amount of a token with `decimals` decimals
+pub fn position_value_gadget<F: PrimeField>(
+    holding: &AmountVar<F>,
+    decimals: u8,
+    price: &AmountVar<F>,
+) -> Result<AmountVar<F>, SynthesisError> {
+    holding.mul(price)?.div_constant(&scale(decimals), ROUNDING)
+}
+
+/// Computes in-circuit the values of the `holdings`, whose tokens have the given `decimals`, and
+/// their sum, the NAV
+pub fn net_asset_value_gadget<F: PrimeField>(
+    holdings: &[AmountVar<F>],
+    decimals: &[u8],
+    prices: &[AmountVar<F>],
+) -> Result<(Vec<AmountVar<F>>, AmountVar<F>), SynthesisError> {
+    if holdings.len() != decimals.len() || holdings.len() != prices.len() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    let values = holdings
+        .iter()
+        .zip(decimals)
+        .zip(prices)
+        .map(|((holding, decimals), price)| position_value_gadget(holding, *decimals, price))
+        .collect::<Result<Vec<_>, _>>()?;
+    let nav = values
+        .iter()
+        .try_fold(AmountVar::constant(&BigUint::default())?, |nav, value| {
+            nav.add(value)
+        })?;
+    Ok((values, nav))
+}
+
+/// Prices of the fund's `N` assets at `timestamp` (unix time, in seconds), in USD per whole token
+/// with `PRICE_DECIMALS` decimals
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceVector<const N: usize> {
+    pub prices: [u128; N],
+    pub timestamp: u64,
+}
+
+impl<const N: usize> Default for PriceVector<N> {
+    fn default() -> Self {
+        Self {
+            prices: [0; N],
+            timestamp: 0,
+        }
+    }
+}
+
+impl<const N: usize> PriceVector<N> {
+    /// Returns the committed message, `[timestamp, prices...]`
+    pub fn to_field_elements(&self) -> Vec<Fr> {
+        [Fr::from(self.timestamp)]
+            .into_iter()
+            .chain(self.prices.iter().map(|price| Fr::from(*price)))
+            .collect()
+    }
+
+    /// Returns the Poseidon commitment to the prices, as published by the pricing oracle
+    pub fn commitment(&self, poseidon_config: &PoseidonConfig<Fr>) -> Fr {
+        let mut sponge = PoseidonSponge::new(poseidon_config);
+        sponge.absorb(&self.to_field_elements());
+        sponge.squeeze_field_elements(1)[0]
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SignedPrices<const N: usize> {
+    pub prices: PriceVector<N>,
+    /// Signature of the commitment to the prices
+    pub signature: Signature,
+}
+
+impl<const N: usize> SignedPrices<N> {
+    pub fn sign(
+        oracle: &SigningKey,
+        poseidon_config: &PoseidonConfig<Fr>,
+        prices: PriceVector<N>,
+    ) -> Self {
+        Self {
+            prices,
+            signature: oracle.sign(poseidon_config, &[prices.commitment(poseidon_config)]),
+        }
+    }
+
+    /// Verifies natively the signature of the prices under the pricing oracle's public key
+    pub fn verify(&self, poseidon_config: &PoseidonConfig<Fr>, oracle: &eddsa::PublicKey) -> bool {
+        eddsa::verify(
+            poseidon_config,
+            oracle,
+            &[self.prices.commitment(poseidon_config)],
+            &self.signature,
+        )
+    }
+}
+
+/// In-circuit representation of a `PriceVector`. Each price is allocated from its `PRICE_BITS`
+/// bits, and the timestamp is enforced to fit in `TIMESTAMP_BITS` bits when allocated as a witness.
+#[derive(Clone, Debug)]
+pub struct PriceVectorVar {
+    pub prices: Vec<AmountVar<Fr>>,
+    /// Prices as field elements, as absorbed by the commitment
+    price_words: Vec<FpVar<Fr>>,
+    pub timestamp: FpVar<Fr>,
+}
+
+impl PriceVectorVar {
+    pub fn to_field_elements(&self) -> Vec<FpVar<Fr>> {
+        [self.timestamp.clone()]
+            .into_iter()
+            .chain(self.price_words.iter().cloned())
+            .collect()
+    }
+
+    /// Computes in-circuit the Poseidon commitment to the prices
+    pub fn commitment(
+        &self,
+        poseidon_config: &PoseidonConfig<Fr>,
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        let message = self.to_field_elements();
+        let mut sponge = PoseidonSpongeVar::new(message.cs(), poseidon_config);
+        sponge.absorb(&message)?;
+        Ok(sponge.squeeze_field_elements(1)?.remove(0))
+    }
+}
+
+impl<const N: usize> AllocVar<PriceVector<N>, Fr> for PriceVectorVar {
+    fn new_variable<T: Borrow<PriceVector<N>>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|prices| {
+            let cs = cs.into().cs();
+            let prices = prices.borrow();
+
+            let mut price_words = Vec::with_capacity(N);
+            let mut price_vars = Vec::with_capacity(N);
+            for price in prices.prices {
+                let bits = Vec::<Boolean<Fr>>::new_variable(
+                    cs.clone(),
+                    || {
+                        Ok((0..PRICE_BITS)
+                            .map(|i| price >> i & 1 == 1)
+                            .collect::<Vec<_>>())
+                    },
+                    mode,
+                )?;
+                price_words.push(Boolean::le_bits_to_fp(&bits)?);
+                price_vars.push(AmountVar::from_bits(&bits));
+            }
+            let timestamp = FpVar::new_variable(cs, || Ok(Fr::from(prices.timestamp)), mode)?;
+            if mode == AllocationMode::Witness {
+                enforce_bit_length(&timestamp, TIMESTAMP_BITS)?;
+            }
+
+            Ok(Self {
+                prices: price_vars,
+                price_words,
+                timestamp,
+            })
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SignedPricesVar {
+    pub prices: PriceVectorVar,
+    pub signature: SignatureVar,
+}
+
+impl SignedPricesVar {
+    /// Enforces that the commitment to the prices is signed by the pricing oracle, and returns the
+    /// commitment
+    pub fn verify(
+        &self,
+        poseidon_config: &PoseidonConfig<Fr>,
+        oracle: &PublicKeyVar,
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        let commitment = self.prices.commitment(poseidon_config)?;
+        eddsa::verify_gadget(
+            poseidon_config,
+            oracle,
+            &[commitment.clone()],
+            &self.signature,
+        )?;
+        Ok(commitment)
+    }
+}
+
+impl<const N: usize> AllocVar<SignedPrices<N>, Fr> for SignedPricesVar {
+    fn new_variable<T: Borrow<SignedPrices<N>>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|signed| {
+            let cs = cs.into().cs();
+            let signed = signed.borrow();
+
+            Ok(Self {
+                prices: PriceVectorVar::new_variable(cs.clone(), || Ok(signed.prices), mode)?,
+                signature: SignatureVar::new_variable(cs, || Ok(signed.signature), mode)?,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::test_rng;
+    use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+
+    use super::*;
+    use crate::amount::U256;
+
+    /// Returns `price` as a 256-bit integer
+    fn to_u256(price: u128) -> U256 {
+        U256::try_from(BigUint::from(price)).unwrap()
+    }
+
+    /// Price of `dollars` USD
+    fn usd(dollars: u64) -> u128 {
+        dollars as u128 * 10u128.pow(PRICE_DECIMALS as u32)
+    }
+
+    #[test]
+    fn test_net_asset_value() -> Result<(), SynthesisError> {
+        // 10M USDC at $1, 14k WETH at $2,500 and 250 WBTC at $100,000
+        let holdings = [
+            TokenAmount::from_units(10_000_000, 6).unwrap(),
+            TokenAmount::from_units(14_000, 18).unwrap(),
+            TokenAmount::from_units(250, 8).unwrap(),
+        ];
+        let prices = [usd(1), usd(2_500), usd(100_000)];
+        let nav = net_asset_value(&holdings, &prices);
+        assert_eq!(
+            nav,
+            TokenAmount::from_units(70_000_000, 18)
+                .unwrap()
+                .normalized()
+                .unwrap()
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let holding_vars = holdings
+            .iter()
+            .map(|holding| AmountVar::new_witness(cs.clone(), || Ok(holding.value)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let price_vars = prices
+            .iter()
+            .map(|price| AmountVar::new_witness(cs.clone(), || Ok(to_u256(*price))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let decimals = holdings.map(|holding| holding.decimals);
+        let (values, nav_var) = net_asset_value_gadget(&holding_vars, &decimals, &price_vars)?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(nav_var.value()?, nav);
+        for ((value, holding), price) in values.iter().zip(&holdings).zip(prices) {
+            assert_eq!(value.value()?, position_value(holding, price));
+        }
+
+        assert!(net_asset_value_gadget(&holding_vars, &decimals[1..], &price_vars).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_position_value_rounding() -> Result<(), SynthesisError> {
+        // 1 wei of a token priced at $0.5 is worth 0.5·10^-18 USD, rounded down to 0
+        let holding = TokenAmount::new(1u64.into(), 18);
+        let price = usd(1) / 2;
+        assert_eq!(position_value(&holding, price), BigUint::default());
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let holding_var = AmountVar::new_witness(cs.clone(), || Ok(holding.value))?;
+        let price_var = AmountVar::new_witness(cs.clone(), || Ok(to_u256(price)))?;
+        let value = position_value_gadget(&holding_var, 18, &price_var)?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(value.value()?, BigUint::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_prices() -> Result<(), SynthesisError> {
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let oracle = SigningKey::rand(&mut rng);
+        let prices = PriceVector {
+            prices: [usd(1), usd(2_500), usd(100_000)],
+            timestamp: 1_751_328_000,
+        };
+        let signed = SignedPrices::sign(&oracle, &poseidon_config, prices);
+        assert!(signed.verify(&poseidon_config, &oracle.public_key()));
+
+        let check = |signed: SignedPrices<3>| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let oracle = PublicKeyVar::new_constant(cs.clone(), oracle.public_key())?;
+            let signed_var = SignedPricesVar::new_witness(cs.clone(), || Ok(signed))?;
+            let commitment = signed_var.verify(&poseidon_config, &oracle)?;
+            assert_eq!(
+                commitment.value()?,
+                signed.prices.commitment(&poseidon_config)
+            );
+            cs.is_satisfied()
+        };
+        assert!(check(signed)?);
+
+        // the prover can't change the published prices
+        let mut tampered = signed;
+        tampered.prices.prices[1] = usd(2_600);
+        assert!(!tampered.verify(&poseidon_config, &oracle.public_key()));
+        assert!(!check(tampered)?);
+        Ok(())
+    }
+}
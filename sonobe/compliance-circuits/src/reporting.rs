@@ -0,0 +1,327 @@
+//! Regulatory reporting: the composite compliance circuit, which additionally encrypts the holdings
+//! it checked to the view key of a regulator (see `crate::encryption`).
+//!
+//! Each step encrypts the oracle-signed holdings of its period, ie. `[account, balance_lo,
+//! balance_hi, decimals, timestamp]` for each asset, and chains the hash of the ciphertext into
+//! the state, after the state of `CompositeFundCircuit`. The ciphertexts are published with the
+//! proof in the `metadata` of `executeRebalance`, together with the commitments to the prices of
+//! the periods. The contract recomputes both chains and requires them to match the state: the
+//! public only learns that the ciphertexts encrypt the checked holdings, while the regulator
+//! decrypts the exact positions.
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_ed_on_bn254::Fr as EdFr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    fields::fp::FpVar,
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::{borrow::Borrow, rand::RngCore, UniformRand};
+use folding_schemes::{frontend::FCircuit, transcript::poseidon::poseidon_canonical_config, Error};
+
+use crate::{
+    composite::{
+        CompositeFundCircuit, CompositeFundParams, CompositeStepInputs, CompositeStepInputsVar,
+    },
+    encryption::{self, Ciphertext, EncryptionKey, EncryptionKeyVar},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct ReportedFundParams<const N: usize> {
+    pub compliance: CompositeFundParams<N>,
+    // View key of the regulator
+    pub regulator: EncryptionKey,
+}
+
+/// External inputs of a step: those of the compliance circuit, and the nonce of the encryption
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReportedStepInputs<const N: usize> {
+    pub inputs: CompositeStepInputs<N>,
+    pub nonce: EdFr,
+}
+
+impl<const N: usize> ReportedStepInputs<N> {
+    /// Inputs of a step, encrypting its report with a fresh nonce
+    pub fn new(inputs: CompositeStepInputs<N>, rng: &mut impl RngCore) -> Self {
+        Self {
+            inputs,
+            nonce: EdFr::rand(rng),
+        }
+    }
+
+    /// Returns the reported holdings, `[account, balance_lo, balance_hi, decimals, timestamp]` for
+    /// each asset
+    pub fn report(&self) -> Vec<Fr> {
+        self.inputs
+            .holdings
+            .iter()
+            .flat_map(|signed| signed.attestation.to_field_elements())
+            .collect()
+    }
+
+    /// Encrypts natively the report to the regulator, as done by the circuit
+    pub fn ciphertext(
+        &self,
+        poseidon_config: &PoseidonConfig<Fr>,
+        regulator: &EncryptionKey,
+    ) -> Ciphertext {
+        encryption::encrypt(poseidon_config, regulator, self.nonce, &self.report())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReportedStepInputsVar {
+    pub inputs: CompositeStepInputsVar,
+    /// Little-endian bits of the nonce
+    pub nonce: Vec<Boolean<Fr>>,
+}
+
+impl<const N: usize> AllocVar<ReportedStepInputs<N>, Fr> for ReportedStepInputsVar {
+    fn new_variable<T: Borrow<ReportedStepInputs<N>>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|inputs| {
+            let cs = cs.into().cs();
+            let inputs = inputs.borrow();
+
+            Ok(Self {
+                inputs: CompositeStepInputsVar::new_variable(
+                    cs.clone(),
+                    || Ok(inputs.inputs),
+                    mode,
+                )?,
+                nonce: Vec::new_variable(cs, || Ok(encryption::nonce_to_bits(inputs.nonce)), mode)?,
+            })
+        })
+    }
+}
+
+/// Chains the hash of the ciphertext of a period into the hash of the reports,
+/// `Poseidon(reports_hash, ciphertext_hash)`
+pub fn chain_report(
+    poseidon_config: &PoseidonConfig<Fr>,
+    reports_hash: Fr,
+    ciphertext_hash: Fr,
+) -> Fr {
+    let mut sponge = PoseidonSponge::new(poseidon_config);
+    sponge.absorb(&[reports_hash, ciphertext_hash]);
+    sponge.squeeze_field_elements(1)[0]
+}
+
+/// Returns the `metadata` of `TokenizedFundManager.executeRebalance`: the abi encoding of
+/// `(Ciphertext[], uint256[])`, with the reports encrypted by the circuit and the commitments to
+/// the prices of the folded periods, in folding order
+pub fn rebalance_metadata(reports: &[Ciphertext], prices: &[Fr]) -> Vec<u8> {
+    let word = |x: &Fr| x.into_bigint().to_bytes_be();
+    let uint = |n: usize| word(&Fr::from(n as u64));
+
+    // Each Ciphertext is encoded as (R.x, R.y, offset of the payload, payload length, payload)
+    let reports = reports
+        .iter()
+        .map(|c| {
+            [
+                word(&c.ephemeral.x),
+                word(&c.ephemeral.y),
+                uint(3 * 32),
+                uint(c.payload.len()),
+                c.payload.iter().flat_map(word).collect(),
+            ]
+            .concat()
+        })
+        .collect::<Vec<_>>();
+    let mut encoded_reports = uint(reports.len());
+    let mut offset = reports.len() * 32;
+    for report in &reports {
+        encoded_reports.extend(uint(offset));
+        offset += report.len();
+    }
+    encoded_reports.extend(reports.concat());
+    let encoded_prices = [uint(prices.len()), prices.iter().flat_map(word).collect()].concat();
+
+    [
+        uint(2 * 32),
+        uint(2 * 32 + encoded_reports.len()),
+        encoded_reports,
+        encoded_prices,
+    ]
+    .concat()
+}
+
+/// Reported Fund Compliance Circuit
+///
+/// Runs the checks of `CompositeFundCircuit` over the inputs of each period, and encrypts the
+/// checked holdings to the regulator.
+#[derive(Clone, Debug)]
+pub struct ReportedFundCircuit<const N: usize> {
+    compliance: CompositeFundCircuit<N>,
+    regulator: EncryptionKey,
+    poseidon_config: PoseidonConfig<Fr>,
+}
+
+impl<const N: usize> FCircuit<Fr> for ReportedFundCircuit<N> {
+    type Params = ReportedFundParams<N>;
+    type ExternalInputs = ReportedStepInputs<N>;
+    type ExternalInputsVar = ReportedStepInputsVar;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            compliance: CompositeFundCircuit::new(params.compliance)?,
+            regulator: params.regulator,
+            poseidon_config: poseidon_canonical_config::<Fr>(),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        // State: [first_period, last_period, compliance_counter, prices_hash, reports_hash]
+        5
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<Fr>,
+        i: usize,
+        z_i: Vec<FpVar<Fr>>,
+        external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        let reports_hash = &z_i[4];
+        let report = external_inputs
+            .inputs
+            .holdings
+            .iter()
+            .flat_map(|signed| signed.attestation.to_field_elements())
+            .collect::<Vec<_>>();
+
+        let mut z_i1 = self.compliance.generate_step_constraints(
+            cs.clone(),
+            i,
+            z_i[..4].to_vec(),
+            external_inputs.inputs,
+        )?;
+
+        // Encrypt the checked holdings, and chain the hash of the ciphertext
+        let regulator = EncryptionKeyVar::new_constant(cs, self.regulator)?;
+        let ciphertext = encryption::encrypt_gadget(
+            &self.poseidon_config,
+            &regulator,
+            &external_inputs.nonce,
+            &report,
+        )?;
+        let mut sponge = PoseidonSpongeVar::new(reports_hash.cs(), &self.poseidon_config);
+        sponge.absorb(&[
+            reports_hash.clone(),
+            ciphertext.hash(&self.poseidon_config)?,
+        ])?;
+        z_i1.push(sponge.squeeze_field_elements(1)?.remove(0));
+
+        Ok(z_i1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::{test_rng, UniformRand};
+
+    use super::*;
+    use crate::{composite::chain_prices, demo, encryption::DecryptionKey};
+
+    #[test]
+    fn test_reported_fund_circuit() -> Result<(), Error> {
+        let mut rng = test_rng();
+        let regulator = DecryptionKey::rand(&mut rng);
+        let circuit = ReportedFundCircuit::new(ReportedFundParams {
+            compliance: demo::params(),
+            regulator: regulator.public_key(),
+        })?;
+        let poseidon_config = &circuit.poseidon_config;
+
+        let period = [1_751_328_000, 1_751_414_400];
+        let inputs = ReportedStepInputs {
+            inputs: CompositeStepInputs::attest(
+                &demo::oracle(),
+                &demo::pricing_oracle(),
+                poseidon_config,
+                &demo::accounts(),
+                period,
+                &demo::snapshot(),
+            ),
+            nonce: EdFr::rand(&mut rng),
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(vec![Fr::from(0u8); 5]))?;
+        let inputs_var = ReportedStepInputsVar::new_witness(cs.clone(), || Ok(inputs))?;
+        let z_i1 = circuit.generate_step_constraints(cs.clone(), 0, z_i, inputs_var)?;
+        assert!(cs.is_satisfied()?);
+
+        // the published ciphertext matches the state, and the regulator decrypts the holdings
+        let ciphertext = inputs.ciphertext(poseidon_config, &regulator.public_key());
+        assert_eq!(
+            z_i1.value()?,
+            vec![
+                Fr::from(period[0]),
+                Fr::from(period[1]),
+                Fr::from(1u8),
+                chain_prices(
+                    poseidon_config,
+                    Fr::from(0u8),
+                    inputs.inputs.prices.prices.commitment(poseidon_config)
+                ),
+                chain_report(
+                    poseidon_config,
+                    Fr::from(0u8),
+                    ciphertext.hash(poseidon_config)
+                ),
+            ]
+        );
+        assert_eq!(
+            regulator.decrypt(poseidon_config, &ciphertext),
+            inputs.report()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_rebalance_metadata() {
+        let mut rng = test_rng();
+        let ciphertext = encryption::encrypt(
+            &poseidon_canonical_config(),
+            &DecryptionKey::rand(&mut rng).public_key(),
+            EdFr::rand(&mut rng),
+            &[Fr::from(7u8)],
+        );
+        let metadata = rebalance_metadata(&[ciphertext.clone()], &[Fr::from(9u8)]);
+
+        // offsets of both arrays, one report at offset 32 after its length, and one price
+        let words = metadata
+            .chunks(32)
+            .map(Fr::from_be_bytes_mod_order)
+            .collect::<Vec<_>>();
+        let uint = |n: u64| Fr::from(n);
+        assert_eq!(
+            words,
+            vec![
+                uint(64),
+                uint(288),
+                uint(1),
+                uint(32),
+                ciphertext.ephemeral.x,
+                ciphertext.ephemeral.y,
+                uint(96),
+                uint(1),
+                ciphertext.payload[0],
+                uint(1),
+                uint(9),
+            ]
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! WebAssembly bindings of the [`compliance_verifier`], so that compliance proofs can be verified
+//! in the browser (or in Node.js) without calling the chain.
+//!
+//! The inputs are JS-friendly: verifier keys are passed as the bytes of their files (eg.
+//! `decider_vp.bin`) and calldata as hex strings, and the verdicts are returned as plain JS
+//! objects. Malformed inputs throw an `Error`, while proofs that are well-formed but invalid
+//! return a verdict with `valid: false`.
+use compliance_verifier::{
+    calldata::{decode_hex, parse_calldata, parse_groth16_calldata},
+    read_groth16_verifying_key, read_verifier_param, verify, verify_groth16, ComplianceProof,
+};
+use wasm_bindgen::prelude::*;
+
+/// Verifies the calldata of a call to the `NovaDecider` contract, in any of its verification
+/// modes, against the serialized decider verifier params. Returns a `Verdict` with the public
+/// outputs, the number of steps and the policy hash.
+#[wasm_bindgen(js_name = verifyNovaCalldata)]
+pub fn verify_nova_calldata(decider_vp: &[u8], calldata: &str) -> Result<JsValue, JsError> {
+    let vp = read_verifier_param(decider_vp)?;
+    let proof = parse_calldata(&decode_hex(calldata)?)?;
+    Ok(serde_wasm_bindgen::to_value(&verify(&vp, &proof))?)
+}
+
+/// Verifies a serialized `ComplianceProof` against the serialized decider verifier params
+#[wasm_bindgen(js_name = verifyNovaProof)]
+pub fn verify_nova_proof(decider_vp: &[u8], proof: &[u8]) -> Result<JsValue, JsError> {
+    let vp = read_verifier_param(decider_vp)?;
+    let proof = ComplianceProof::from_bytes(proof)?;
+    Ok(serde_wasm_bindgen::to_value(&verify(&vp, &proof))?)
+}
+
+/// Verifies the calldata of a call to the Groth16 verifier contract of the compliance circuit
+/// against its serialized verifying key (`verifying_key.bin`)
+#[wasm_bindgen(js_name = verifyGroth16Calldata)]
+pub fn verify_groth16_calldata(vk: &[u8], calldata: &str) -> Result<JsValue, JsError> {
+    let vk = read_groth16_verifying_key(vk)?;
+    let (proof, public_inputs) = parse_groth16_calldata(&decode_hex(calldata)?)?;
+    Ok(serde_wasm_bindgen::to_value(&verify_groth16(
+        &vk,
+        &proof,
+        &public_inputs,
+    ))?)
+}
@@ -0,0 +1,125 @@
+//! Run with `wasm-pack test --node`
+#![cfg(target_arch = "wasm32")]
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::Groth16;
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
+use ark_relations::gr1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use serde::Deserialize;
+use wasm_bindgen::{JsError, JsValue};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use compliance_verifier_wasm::{verify_groth16_calldata, verify_nova_calldata};
+
+const DECIDER_VP: &[u8] = include_bytes!("../../persisted_params/decider_vp.bin");
+const NOVA_CALLDATA: &[u8] = include_bytes!("../../composite-proof.calldata");
+
+#[derive(Debug, Deserialize)]
+struct Verdict {
+    valid: bool,
+    steps: String,
+    public_outputs: Vec<String>,
+    policy_hash: String,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Groth16Verdict {
+    valid: bool,
+    public_inputs: Vec<String>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!(
+        "0x{}",
+        bytes.iter().map(|b| format!("{b:02x}")).collect::<String>()
+    )
+}
+
+fn from_js<T: for<'de> Deserialize<'de>>(value: Result<JsValue, JsError>) -> T {
+    serde_wasm_bindgen::from_value(value.map_err(JsValue::from).unwrap()).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn verifies_persisted_nova_calldata() {
+    let verdict: Verdict = from_js(verify_nova_calldata(DECIDER_VP, &to_hex(NOVA_CALLDATA)));
+    assert!(verdict.valid, "{:?}", verdict.error);
+    assert_eq!(verdict.steps, "3");
+    assert_eq!(verdict.public_outputs, vec!["3"]);
+    assert!(verdict.policy_hash.starts_with("0x"));
+
+    // changing the public output invalidates the proof, z_i is placed between bytes 68 - 100
+    let mut invalid_calldata = NOVA_CALLDATA.to_vec();
+    invalid_calldata[99] += 1;
+    let verdict: Verdict = from_js(verify_nova_calldata(DECIDER_VP, &to_hex(&invalid_calldata)));
+    assert!(!verdict.valid);
+    assert_eq!(verdict.public_outputs, vec!["4"]);
+
+    // malformed inputs are errors
+    assert!(verify_nova_calldata(DECIDER_VP, "0x1234").is_err());
+    assert!(verify_nova_calldata(&DECIDER_VP[1..], &to_hex(NOVA_CALLDATA)).is_err());
+}
+
+/// Proves the knowledge of a factorization of the public input
+#[derive(Clone, Copy)]
+struct FactorCircuit {
+    x: Fr,
+    y: Fr,
+}
+
+impl ConstraintSynthesizer<Fr> for FactorCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let z = FpVar::new_input(cs.clone(), || Ok(self.x * self.y))?;
+        let x = FpVar::new_witness(cs.clone(), || Ok(self.x))?;
+        let y = FpVar::new_witness(cs, || Ok(self.y))?;
+        (x * y).enforce_equal(&z)
+    }
+}
+
+/// Encodes the proof and the public input as the calldata of
+/// `verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[1])`
+fn groth16_calldata(proof: &ark_groth16::Proof<Bn254>, public_input: Fr) -> Vec<u8> {
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInteger, PrimeField};
+
+    let (a_x, a_y) = proof.a.xy().unwrap();
+    let (b_x, b_y) = proof.b.xy().unwrap();
+    let (c_x, c_y) = proof.c.xy().unwrap();
+    [
+        &[0x43, 0x75, 0x3b, 0x4d][..],
+        &a_x.into_bigint().to_bytes_be(),
+        &a_y.into_bigint().to_bytes_be(),
+        &b_x.c1.into_bigint().to_bytes_be(),
+        &b_x.c0.into_bigint().to_bytes_be(),
+        &b_y.c1.into_bigint().to_bytes_be(),
+        &b_y.c0.into_bigint().to_bytes_be(),
+        &c_x.into_bigint().to_bytes_be(),
+        &c_y.into_bigint().to_bytes_be(),
+        &public_input.into_bigint().to_bytes_be(),
+    ]
+    .concat()
+}
+
+#[wasm_bindgen_test]
+fn verifies_groth16_calldata() {
+    let mut rng = ark_std::test_rng();
+    let circuit = FactorCircuit {
+        x: Fr::from(3u32),
+        y: Fr::from(7u32),
+    };
+    let (pk, vk) = Groth16::<Bn254>::setup(circuit, &mut rng).unwrap();
+    let proof = Groth16::<Bn254>::prove(&pk, circuit, &mut rng).unwrap();
+    let mut vk_bytes = vec![];
+    vk.serialize_compressed(&mut vk_bytes).unwrap();
+
+    let calldata = groth16_calldata(&proof, Fr::from(21u32));
+    let verdict: Groth16Verdict = from_js(verify_groth16_calldata(&vk_bytes, &to_hex(&calldata)));
+    assert!(verdict.valid);
+    assert_eq!(verdict.public_inputs, vec!["21"]);
+
+    let calldata = groth16_calldata(&proof, Fr::from(22u32));
+    let verdict: Groth16Verdict = from_js(verify_groth16_calldata(&vk_bytes, &to_hex(&calldata)));
+    assert!(!verdict.valid);
+}
@@ -0,0 +1,216 @@
+//! Decoding of the calldata of the Solidity `NovaDecider` and Groth16 verifier contracts.
+//!
+//! The three verification modes of the contract (`verifyNovaProof`, `verifyOpaqueNovaProof` and
+//! `verifyOpaqueNovaProofWithInputs`) only differ in the function selector, the arguments are
+//! always the same sequence of 32-byte big-endian words:
+//!
+//! | `i` | `z_0` | `z_i` | `U.cmW`, `U.cmE`, `u.cmW` | `cmT` | `r` | `pA`, `pB`, `pC` | KZG challenges | KZG evals | KZG proofs |
+//! |-----|-------|-------|---------------------------|-------|-----|------------------|----------------|-----------|------------|
+//! | 1   | `L`   | `L`   | 6                         | 2     | 1   | 8                | 2              | 2         | 4          |
+//!
+//! where `L` is the length of the IVC state, G1 points are encoded as `(x, y)` with the identity
+//! being `(0, 0)`, and the coordinates of G2 points are encoded as `(c1, c0)`. The Groth16 verifier
+//! uses the same encoding for `pA`, `pB` and `pC`, followed by the public inputs.
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G1Projective as G1, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{PrimeField, Zero};
+use ark_groth16::Proof as Groth16Proof;
+use folding_schemes::{commitment::kzg::Proof as KZGProof, folding::nova::decider_eth::Proof};
+use num_bigint::BigUint;
+use solidity_verifiers::calldata::{get_function_selector, keccak_selector, NovaVerificationMode};
+
+use crate::{ComplianceProof, Error};
+
+/// Size of the function selector
+const SELECTOR_LEN: usize = 4;
+/// Size of an EVM word
+const WORD_LEN: usize = 32;
+/// Number of words besides the IVC states `z_0` and `z_i`
+const FIXED_WORDS: usize = 26;
+
+/// Number of words of a Groth16 proof `(pA, pB, pC)`
+const GROTH16_PROOF_WORDS: usize = 8;
+
+/// Returns the function selectors of the three verification modes for the given state length
+fn selectors(state_len: usize) -> [[u8; SELECTOR_LEN]; 3] {
+    [
+        NovaVerificationMode::Explicit,
+        NovaVerificationMode::Opaque,
+        NovaVerificationMode::OpaqueWithInputs,
+    ]
+    .map(|mode| get_function_selector(mode, state_len))
+}
+
+/// Splits the calldata into its function selector and its arguments, checking that the arguments
+/// are made of at least `min_words` words
+fn split_calldata(calldata: &[u8], min_words: usize) -> Result<(&[u8], Words<'_>, usize), Error> {
+    if calldata.len() < SELECTOR_LEN || (calldata.len() - SELECTOR_LEN) % WORD_LEN != 0 {
+        return Err(Error::InvalidCalldata(format!(
+            "unexpected calldata size {}",
+            calldata.len()
+        )));
+    }
+    let n_words = (calldata.len() - SELECTOR_LEN) / WORD_LEN;
+    if n_words < min_words {
+        return Err(Error::InvalidCalldata(format!(
+            "unexpected number of words {n_words}"
+        )));
+    }
+    let (selector, args) = calldata.split_at(SELECTOR_LEN);
+    Ok((selector, Words(args.chunks_exact(WORD_LEN)), n_words))
+}
+
+fn unknown_selector(selector: &[u8]) -> Error {
+    Error::InvalidCalldata(format!(
+        "unknown function selector 0x{}",
+        encode_hex(selector)
+    ))
+}
+
+/// Encodes the given bytes as hex, without `0x` prefix
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Decodes hex encoded calldata, with or without `0x` prefix, as printed by most Ethereum tooling
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    let hex = hex.trim();
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() % 2 != 0 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(Error::InvalidCalldata("invalid hex encoding".to_string()));
+    }
+    Ok((0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("checked hex digits"))
+        .collect())
+}
+
+/// Sequential reader of the calldata words
+struct Words<'a>(core::slice::ChunksExact<'a, u8>);
+
+impl Words<'_> {
+    fn field<F: PrimeField>(&mut self) -> Result<F, Error> {
+        let word = self.0.next().ok_or(Error::InvalidCalldata(
+            "unexpected end of calldata".to_string(),
+        ))?;
+        F::BigInt::try_from(BigUint::from_bytes_be(word))
+            .ok()
+            .and_then(F::from_bigint)
+            .ok_or(Error::InvalidCalldata(
+                "field element is not reduced".to_string(),
+            ))
+    }
+
+    fn fields<F: PrimeField>(&mut self, n: usize) -> Result<Vec<F>, Error> {
+        (0..n).map(|_| self.field()).collect()
+    }
+
+    fn g1(&mut self) -> Result<G1, Error> {
+        let (x, y) = (self.field::<Fq>()?, self.field::<Fq>()?);
+        if x.is_zero() && y.is_zero() {
+            return Ok(G1::zero());
+        }
+        let p = G1Affine::new_unchecked(x, y);
+        if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(Error::InvalidCalldata("invalid G1 point".to_string()));
+        }
+        Ok(p.into_group())
+    }
+
+    fn g2(&mut self) -> Result<G2Affine, Error> {
+        let x_c1 = self.field::<Fq>()?;
+        let x = Fq2::new(self.field()?, x_c1);
+        let y_c1 = self.field::<Fq>()?;
+        let y = Fq2::new(self.field()?, y_c1);
+        if x.is_zero() && y.is_zero() {
+            return Ok(G2Affine::zero());
+        }
+        let p = G2Affine::new_unchecked(x, y);
+        if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(Error::InvalidCalldata("invalid G2 point".to_string()));
+        }
+        Ok(p)
+    }
+
+    fn groth16_proof(&mut self) -> Result<Groth16Proof<Bn254>, Error> {
+        Ok(Groth16Proof {
+            a: self.g1()?.into(),
+            b: self.g2()?,
+            c: self.g1()?.into(),
+        })
+    }
+}
+
+/// Decodes the calldata of a call to the `NovaDecider` contract, in any of its verification
+/// modes. The IVC state length is inferred from the calldata size and checked against the
+/// function selector.
+pub fn parse_calldata(calldata: &[u8]) -> Result<ComplianceProof, Error> {
+    let (selector, mut words, n_words) = split_calldata(calldata, FIXED_WORDS)?;
+    if (n_words - FIXED_WORDS) % 2 != 0 {
+        return Err(Error::InvalidCalldata(format!(
+            "unexpected number of words {n_words}"
+        )));
+    }
+    let state_len = (n_words - FIXED_WORDS) / 2;
+    if !selectors(state_len).iter().any(|s| s == selector) {
+        return Err(unknown_selector(selector));
+    }
+
+    let i = words.field()?;
+    let z_0 = words.fields(state_len)?;
+    let z_i = words.fields(state_len)?;
+    let running_commitments = vec![words.g1()?, words.g1()?];
+    // the cmE of the incoming instance is always zero, hence it is not part of the calldata
+    let incoming_commitments = vec![words.g1()?, G1::zero()];
+    let cm_t = words.g1()?;
+    let r = words.field()?;
+    let snark_proof = words.groth16_proof()?;
+    let kzg_challenges = [words.field()?, words.field()?];
+    let evals: [Fr; 2] = [words.field()?, words.field()?];
+    // the onchain decider uses non-hiding KZG commitments, so the proofs don't carry a blinding
+    let kzg_proofs = [
+        KZGProof {
+            eval: evals[0],
+            proof: words.g1()?,
+            random_v: Fr::zero(),
+        },
+        KZGProof {
+            eval: evals[1],
+            proof: words.g1()?,
+            random_v: Fr::zero(),
+        },
+    ];
+
+    Ok(ComplianceProof {
+        i,
+        z_0,
+        z_i,
+        running_commitments,
+        incoming_commitments,
+        proof: Proof::new(snark_proof, kzg_proofs, cm_t, r, kzg_challenges),
+    })
+}
+
+/// Decodes the calldata of a call to the Solidity Groth16 verifier, ie. to
+/// `verifyProof(pA, pB, pC, pubSignals)`, returning the proof and its public inputs. Verifiers of
+/// circuits without public inputs may omit the `pubSignals` argument.
+pub fn parse_groth16_calldata(calldata: &[u8]) -> Result<(Groth16Proof<Bn254>, Vec<Fr>), Error> {
+    let (selector, mut words, n_words) = split_calldata(calldata, GROTH16_PROOF_WORDS)?;
+    let n_inputs = n_words - GROTH16_PROOF_WORDS;
+    let mut signatures = vec![format!(
+        "verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{n_inputs}])"
+    )];
+    if n_inputs == 0 {
+        signatures.push("verifyProof(uint256[2],uint256[2][2],uint256[2])".to_string());
+    }
+    if !signatures
+        .iter()
+        .any(|signature| selector == keccak_selector(signature))
+    {
+        return Err(unknown_selector(selector));
+    }
+
+    let proof = words.groth16_proof()?;
+    let public_inputs = words.fields(n_inputs)?;
+    Ok((proof, public_inputs))
+}
@@ -0,0 +1,437 @@
+//! Off-chain verifier for the compliance proofs produced by Nova + CycleFold and the onchain
+//! [`Decider`](folding_schemes::folding::nova::decider_eth::Decider).
+//!
+//! It checks the same proofs as the Solidity `NovaDecider` contract, but without an EVM, and only
+//! needs the serialized decider verifier params (`decider_vp.bin`): neither the prover params nor
+//! the folded circuit are required. Proofs can be provided either as the calldata sent to the
+//! contract (in any of its verification modes) or as a [`ComplianceProof`] file.
+//!
+//! The Groth16 compliance proofs, which are verified by a plain Groth16 verifier contract, are
+//! supported too through [`verify_groth16`].
+
+use ark_bn254::{Bn254, Fr, G1Projective as G1};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_groth16::{Groth16, VerifyingKey as Groth16VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_snark::SNARK;
+use folding_schemes::{
+    commitment::kzg::KZG,
+    folding::{
+        nova::decider_eth::{self, Proof, VerifierParam},
+        traits::InputizeNonNative,
+    },
+};
+use serde::Serialize;
+use thiserror::Error;
+
+pub mod calldata;
+
+use calldata::encode_hex;
+
+/// Verifier params of the onchain decider, as stored in `decider_vp.bin`
+pub type DeciderVerifierParam =
+    VerifierParam<G1, ark_poly_commit::kzg10::VerifierKey<Bn254>, Groth16VerifyingKey<Bn254>>;
+
+/// Proof of the onchain decider
+pub type DeciderProof = Proof<G1, KZG<'static, Bn254>, Groth16<Bn254>>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Invalid calldata: {0}")]
+    InvalidCalldata(String),
+    #[error(transparent)]
+    Serialization(#[from] SerializationError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Everything needed to verify a compliance proof besides the [`DeciderVerifierParam`], ie. the
+/// public inputs of the decider and its proof. This is the content of a proof file, serialized
+/// with [`CanonicalSerialize`].
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct ComplianceProof {
+    /// number of folded steps
+    pub i: Fr,
+    /// initial state of the IVC
+    pub z_0: Vec<Fr>,
+    /// final state of the IVC, ie. the public outputs
+    pub z_i: Vec<Fr>,
+    /// commitments `[cmW, cmE]` of the running instance
+    pub running_commitments: Vec<G1>,
+    /// commitments `[cmW, cmE]` of the incoming instance
+    pub incoming_commitments: Vec<G1>,
+    pub proof: DeciderProof,
+}
+
+impl ComplianceProof {
+    /// Reads a proof file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::deserialize_compressed(bytes)?)
+    }
+
+    /// Writes the proof as a proof file
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        self.serialize_compressed(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+/// Result of the verification of a [`ComplianceProof`]. The public values are reported even when
+/// the proof is invalid, so that it is clear which statement was rejected.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Verdict {
+    pub valid: bool,
+    /// number of folded steps, in decimal
+    pub steps: String,
+    /// initial state of the IVC, in decimal
+    pub initial_state: Vec<String>,
+    /// final state of the IVC, in decimal
+    pub public_outputs: Vec<String>,
+    /// hash of the public params that the proof is bound to, as 0x-prefixed hex. It identifies
+    /// the circuit (and therefore the policy) that was proven.
+    pub policy_hash: String,
+    /// reason why the proof was rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of the verification of a Groth16 compliance proof
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Groth16Verdict {
+    pub valid: bool,
+    /// public inputs of the proof, in decimal
+    pub public_inputs: Vec<String>,
+    /// reason why the proof was rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Reads the decider verifier params from the content of `decider_vp.bin`
+pub fn read_verifier_param(bytes: &[u8]) -> Result<DeciderVerifierParam, Error> {
+    Ok(DeciderVerifierParam::deserialize_compressed(bytes)?)
+}
+
+/// Reads a Groth16 verifying key, eg. the content of `groth16_params/verifying_key.bin`
+pub fn read_groth16_verifying_key(bytes: &[u8]) -> Result<Groth16VerifyingKey<Bn254>, Error> {
+    Ok(Groth16VerifyingKey::deserialize_compressed(bytes)?)
+}
+
+fn to_decimal(x: &[Fr]) -> Vec<String> {
+    x.iter().map(|x| x.into_bigint().to_string()).collect()
+}
+
+/// Length of the IVC state of the proofs verified with the given params, read from the number of
+/// public inputs of the decider SNARK
+pub fn state_len(vp: &DeciderVerifierParam) -> Option<usize> {
+    // besides z_0 and z_i, the public inputs are pp_hash, i, the folded commitments [cmW, cmE],
+    // the two KZG challenges and evaluations, and cmT
+    let fixed = 2 + 3 * G1::zero().inputize_nonnative().len() + 4;
+    let n_states = vp.snark_vp.gamma_abc_g1.len().checked_sub(1 + fixed)?;
+    (n_states % 2 == 0).then_some(n_states / 2)
+}
+
+/// Checks the shape of the proof, since the decider indexes the commitments and builds its public
+/// inputs from the states without checking their lengths
+fn check_lengths(vp: &DeciderVerifierParam, proof: &ComplianceProof) -> Result<(), String> {
+    if proof.running_commitments.len() != 2 || proof.incoming_commitments.len() != 2 {
+        return Err(format!(
+            "expected 2 running and 2 incoming commitments, got {} and {}",
+            proof.running_commitments.len(),
+            proof.incoming_commitments.len()
+        ));
+    }
+    let state_len =
+        state_len(vp).ok_or("unexpected number of public inputs in the verifier params")?;
+    if proof.z_0.len() != state_len || proof.z_i.len() != state_len {
+        return Err(format!(
+            "expected states of length {state_len}, got {} and {}",
+            proof.z_0.len(),
+            proof.z_i.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Verifies the given proof against the decider verifier params
+pub fn verify(vp: &DeciderVerifierParam, proof: &ComplianceProof) -> Verdict {
+    let result = check_lengths(vp, proof).and_then(|()| {
+        decider_eth::verify::<G1, KZG<'static, Bn254>, Groth16<Bn254>>(
+            vp.clone(),
+            proof.i,
+            proof.z_0.clone(),
+            proof.z_i.clone(),
+            &proof.running_commitments,
+            &proof.incoming_commitments,
+            &proof.proof,
+        )
+        .map_err(|e| e.to_string())
+    });
+    let (valid, error) = match result {
+        Ok(valid) => (valid, None),
+        Err(e) => (false, Some(e)),
+    };
+    Verdict {
+        valid,
+        steps: proof.i.into_bigint().to_string(),
+        initial_state: to_decimal(&proof.z_0),
+        public_outputs: to_decimal(&proof.z_i),
+        policy_hash: format!("0x{}", encode_hex(&vp.pp_hash.into_bigint().to_bytes_be())),
+        error,
+    }
+}
+
+/// Verifies a Groth16 compliance proof with the given public inputs
+pub fn verify_groth16(
+    vk: &Groth16VerifyingKey<Bn254>,
+    proof: &ark_groth16::Proof<Bn254>,
+    public_inputs: &[Fr],
+) -> Groth16Verdict {
+    let (valid, error) = match Groth16::<Bn254>::verify(vk, public_inputs, proof) {
+        Ok(true) => (true, None),
+        Ok(false) => (false, Some("invalid Groth16 proof".to_string())),
+        Err(e) => (false, Some(e.to_string())),
+    };
+    Groth16Verdict {
+        valid,
+        public_inputs: to_decimal(public_inputs),
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_grumpkin::Projective as G2;
+    use ark_r1cs_std::eq::EqGadget;
+    use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar};
+    use ark_relations::gr1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+    use ark_snark::CircuitSpecificSetupSNARK;
+    use folding_schemes::{
+        commitment::pedersen::Pedersen,
+        folding::{
+            nova::{decider_eth::Decider as DeciderEth, Nova, PreprocessorParam},
+            traits::CommittedInstanceOps,
+        },
+        frontend::FCircuit,
+        transcript::poseidon::poseidon_canonical_config,
+        Decider, Error as FoldingError, FoldingScheme,
+    };
+    use solidity_verifiers::{
+        calldata::{
+            keccak_selector, prepare_calldata_for_nova_cyclefold_verifier, NovaVerificationMode,
+        },
+        utils::eth::ToEth,
+    };
+
+    use super::*;
+
+    /// Adds 1 to a counter at each step
+    #[derive(Clone, Copy, Debug)]
+    struct CounterFCircuit;
+    impl FCircuit<Fr> for CounterFCircuit {
+        type Params = ();
+        type ExternalInputs = ();
+        type ExternalInputsVar = ();
+
+        fn new(_params: Self::Params) -> Result<Self, FoldingError> {
+            Ok(Self)
+        }
+        fn state_len(&self) -> usize {
+            1
+        }
+        fn generate_step_constraints(
+            &self,
+            cs: ConstraintSystemRef<Fr>,
+            _i: usize,
+            z_i: Vec<FpVar<Fr>>,
+            _external_inputs: Self::ExternalInputsVar,
+        ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+            let one = FpVar::new_constant(cs, Fr::from(1u32))?;
+            Ok(vec![&z_i[0] + one])
+        }
+    }
+
+    type N = Nova<G1, G2, CounterFCircuit, KZG<'static, Bn254>, Pedersen<G2>, false>;
+    type D =
+        DeciderEth<G1, G2, CounterFCircuit, KZG<'static, Bn254>, Pedersen<G2>, Groth16<Bn254>, N>;
+
+    #[test]
+    fn test_verify_calldata_and_proof_file() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = ark_std::rand::rngs::OsRng;
+        let f_circuit = CounterFCircuit::new(())?;
+        let prep_param = PreprocessorParam::new(poseidon_canonical_config::<Fr>(), f_circuit);
+        let nova_params = N::preprocess(&mut rng, &prep_param)?;
+        let (decider_pp, decider_vp) =
+            D::preprocess(&mut rng, (nova_params.clone(), f_circuit.state_len()))?;
+
+        let mut nova = N::init(&nova_params, f_circuit, vec![Fr::from(3u32)])?;
+        for _ in 0..3 {
+            nova.prove_step(&mut rng, (), None)?;
+        }
+        let proof = D::prove(rng, decider_pp, nova.clone())?;
+
+        // the verifier only gets the serialized decider verifier params
+        let mut vp_bytes = vec![];
+        decider_vp.serialize_compressed(&mut vp_bytes)?;
+        let vp = read_verifier_param(&vp_bytes)?;
+
+        let compliance_proof = ComplianceProof {
+            i: nova.i,
+            z_0: nova.z_0.clone(),
+            z_i: nova.z_i.clone(),
+            running_commitments: nova.U_i.get_commitments(),
+            incoming_commitments: nova.u_i.get_commitments(),
+            proof,
+        };
+        let verdict = verify(&vp, &compliance_proof);
+        assert!(verdict.valid, "{:?}", verdict.error);
+        assert_eq!(verdict.steps, "3");
+        assert_eq!(verdict.initial_state, vec!["3"]);
+        assert_eq!(verdict.public_outputs, vec!["6"]);
+        assert_eq!(state_len(&vp), Some(1));
+
+        // proof files roundtrip
+        let proof_file = compliance_proof.to_bytes()?;
+        assert_eq!(
+            verify(&vp, &ComplianceProof::from_bytes(&proof_file)?),
+            verdict
+        );
+
+        // the calldata of every verification mode yields the same verdict
+        for mode in [
+            NovaVerificationMode::Explicit,
+            NovaVerificationMode::Opaque,
+            NovaVerificationMode::OpaqueWithInputs,
+        ] {
+            let calldata = prepare_calldata_for_nova_cyclefold_verifier(
+                mode,
+                nova.i,
+                nova.z_0.clone(),
+                nova.z_i.clone(),
+                &nova.U_i,
+                &nova.u_i,
+                &compliance_proof.proof,
+            )?;
+            let decoded = calldata::parse_calldata(&calldata)?;
+            assert_eq!(decoded.to_bytes()?, proof_file);
+            assert_eq!(verify(&vp, &decoded), verdict);
+
+            // tampering with the public outputs is detected
+            let mut invalid_calldata = calldata.clone();
+            invalid_calldata[99] += 1;
+            let invalid_verdict = verify(&vp, &calldata::parse_calldata(&invalid_calldata)?);
+            assert!(!invalid_verdict.valid);
+            assert_eq!(invalid_verdict.public_outputs, vec!["7"]);
+            assert!(invalid_verdict.error.is_some());
+
+            // malformed calldata is rejected before verifying
+            assert!(calldata::parse_calldata(&calldata[..calldata.len() - 32]).is_err());
+            let mut invalid_selector = calldata.clone();
+            invalid_selector[0] ^= 1;
+            assert!(calldata::parse_calldata(&invalid_selector).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_malformed_proof() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = ark_std::rand::rngs::OsRng;
+        let f_circuit = CounterFCircuit::new(())?;
+        let prep_param = PreprocessorParam::new(poseidon_canonical_config::<Fr>(), f_circuit);
+        let nova_params = N::preprocess(&mut rng, &prep_param)?;
+        let (decider_pp, vp) =
+            D::preprocess(&mut rng, (nova_params.clone(), f_circuit.state_len()))?;
+
+        let mut nova = N::init(&nova_params, f_circuit, vec![Fr::from(3u32)])?;
+        for _ in 0..2 {
+            nova.prove_step(&mut rng, (), None)?;
+        }
+        let proof = ComplianceProof {
+            i: nova.i,
+            z_0: nova.z_0.clone(),
+            z_i: nova.z_i.clone(),
+            running_commitments: nova.U_i.get_commitments(),
+            incoming_commitments: nova.u_i.get_commitments(),
+            proof: D::prove(rng, decider_pp, nova.clone())?,
+        };
+        assert!(verify(&vp, &proof).valid);
+
+        // missing commitments are rejected instead of panicking
+        let mut missing_commitment = ComplianceProof::from_bytes(&proof.to_bytes()?)?;
+        missing_commitment.running_commitments.pop();
+        let verdict = verify(&vp, &missing_commitment);
+        assert!(!verdict.valid);
+        assert!(verdict.error.is_some());
+        let mut missing_commitment = ComplianceProof::from_bytes(&proof.to_bytes()?)?;
+        missing_commitment.incoming_commitments.clear();
+        assert!(!verify(&vp, &missing_commitment).valid);
+
+        // states of another length are rejected, so that the boundary between z_0 and z_i cannot
+        // be moved
+        let mut shifted_states = ComplianceProof::from_bytes(&proof.to_bytes()?)?;
+        shifted_states.z_0.append(&mut shifted_states.z_i);
+        let verdict = verify(&vp, &shifted_states);
+        assert!(!verdict.valid);
+        assert_eq!(verdict.initial_state, vec!["3", "5"]);
+        assert!(verdict.public_outputs.is_empty());
+        assert!(verdict.error.is_some());
+        Ok(())
+    }
+
+    /// Proves the knowledge of a factorization of the public input
+    #[derive(Clone, Copy)]
+    struct FactorCircuit {
+        x: Fr,
+        y: Fr,
+    }
+    impl ConstraintSynthesizer<Fr> for FactorCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let z = FpVar::new_input(cs.clone(), || Ok(self.x * self.y))?;
+            let x = FpVar::new_witness(cs.clone(), || Ok(self.x))?;
+            let y = FpVar::new_witness(cs, || Ok(self.y))?;
+            (x * y).enforce_equal(&z)
+        }
+    }
+
+    #[test]
+    fn test_verify_groth16_calldata() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = ark_std::test_rng();
+        let circuit = FactorCircuit {
+            x: Fr::from(3u32),
+            y: Fr::from(7u32),
+        };
+        let (pk, vk) = Groth16::<Bn254>::setup(circuit, &mut rng)?;
+        let proof = Groth16::<Bn254>::prove(&pk, circuit, &mut rng)?;
+
+        let mut vk_bytes = vec![];
+        vk.serialize_compressed(&mut vk_bytes)?;
+        let vk = read_groth16_verifying_key(&vk_bytes)?;
+
+        let selector =
+            keccak_selector("verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[1])");
+        let calldata = [&selector[..], &proof.to_eth(), &Fr::from(21u32).to_eth()].concat();
+        let (decoded_proof, public_inputs) = calldata::parse_groth16_calldata(&calldata)?;
+        assert_eq!(decoded_proof, proof);
+        let verdict = verify_groth16(&vk, &decoded_proof, &public_inputs);
+        assert!(verdict.valid, "{:?}", verdict.error);
+        assert_eq!(verdict.public_inputs, vec!["21"]);
+
+        // the hex encoding is accepted too
+        let hex = format!("0x{}", encode_hex(&calldata));
+        assert_eq!(
+            calldata::parse_groth16_calldata(&calldata::decode_hex(&hex)?)?,
+            (decoded_proof, public_inputs)
+        );
+
+        // a wrong public input is rejected
+        let mut invalid_calldata = calldata.clone();
+        invalid_calldata[calldata.len() - 1] += 1;
+        let (proof, public_inputs) = calldata::parse_groth16_calldata(&invalid_calldata)?;
+        assert!(!verify_groth16(&vk, &proof, &public_inputs).valid);
+
+        // the selector must match the number of public inputs
+        assert!(calldata::parse_groth16_calldata(&calldata[..calldata.len() - 32]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use clap::{ArgGroup, Parser};
+use compliance_verifier::{
+    calldata::{decode_hex, parse_calldata},
+    read_verifier_param, verify, ComplianceProof,
+};
+use std::{fs, path::PathBuf, process::ExitCode};
+
+/// Verifies compliance proofs off-chain, using only the decider verifier params. Prints the verdict
+/// as JSON and exits with a non-zero code if the proof is invalid.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+#[command(group(ArgGroup::new("input").required(true).args(["calldata", "proof"])))]
+struct Cli {
+    /// Path to the serialized decider verifier params (`decider_vp.bin`)
+    #[arg(short = 'k', long)]
+    vk: PathBuf,
+    /// Path to the calldata sent to the `NovaDecider` contract, either raw or hex encoded
+    #[arg(short, long)]
+    calldata: Option<PathBuf>,
+    /// Path to a proof file
+    #[arg(short, long)]
+    proof: Option<PathBuf>,
+}
+
+/// Accepts both raw calldata and its hex encoding
+fn decode_calldata(bytes: Vec<u8>) -> Vec<u8> {
+    std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|hex| decode_hex(hex).ok())
+        .filter(|calldata| !calldata.is_empty())
+        .unwrap_or(bytes)
+}
+
+fn run(cli: Cli) -> Result<bool, Box<dyn std::error::Error>> {
+    let vp = read_verifier_param(&fs::read(cli.vk)?)?;
+    let proof = match (cli.calldata, cli.proof) {
+        (Some(path), _) => parse_calldata(&decode_calldata(fs::read(path)?))?,
+        (None, Some(path)) => ComplianceProof::from_bytes(&fs::read(path)?)?,
+        (None, None) => unreachable!("clap requires one of the inputs"),
+    };
+    let verdict = verify(&vp, &proof);
+    println!("{}", serde_json::to_string_pretty(&verdict)?);
+    Ok(verdict.valid)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
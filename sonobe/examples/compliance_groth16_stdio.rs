@@ -6,16 +6,26 @@
 // - ~800-1200 R1CS constraints
 // - Memory: ~4-8GB for setup (vs 20GB+ for Nova+Decider)
 // - Proof size: ~288 bytes
+// - Public inputs: policy thresholds, whitelist root, snapshot commitment and period
 
 use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::crh::{
+    poseidon::{
+        constraints::{CRHGadget, CRHParametersVar},
+        CRH,
+    },
+    CRHScheme, CRHSchemeGadget,
+};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ff::PrimeField;
 use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
-use ark_snark::SNARK;
 use ark_r1cs_std::alloc::AllocVar;
 use ark_r1cs_std::eq::EqGadget;
 use ark_r1cs_std::fields::fp::FpVar;
 use ark_r1cs_std::fields::FieldVar;
 use ark_relations::gr1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
 use ark_std::rand::rngs::StdRng;
 use ark_std::rand::SeedableRng;
 use std::fs;
@@ -23,7 +33,10 @@ use std::io::{BufRead, Write as IoWrite};
 use std::path::Path;
 use std::time::Instant;
 
+use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+
 // For Solidity verifier generation
+use solidity_verifiers::calldata::prepare_calldata_for_groth16_verifier;
 use solidity_verifiers::{Groth16VerifierKey, ProtocolVerifierKey};
 
 const PARAMS_DIR: &str = "./groth16_params";
@@ -44,6 +57,75 @@ pub struct ComplianceParams {
     pub asset_hash: u64,
     pub sibling: u64,
     pub merkle_root: u64,
+
+    // Snapshot of the fund that the proof refers to
+    pub period: u64,
+    pub snapshot_salt: u64,
+}
+
+impl ComplianceParams {
+    /// Poseidon commitment to the private values of the snapshot, bound to its period
+    pub fn snapshot_commitment(&self, poseidon_config: &PoseidonConfig<Fr>) -> Fr {
+        let snapshot = [
+            self.period,
+            self.total_value,
+            self.largest_asset_value,
+            self.usdc_balance,
+            self.asset_hash,
+            self.snapshot_salt,
+        ]
+        .map(Fr::from);
+        CRH::<Fr>::evaluate(poseidon_config, snapshot).unwrap()
+    }
+
+    pub fn public_inputs(&self, poseidon_config: &PoseidonConfig<Fr>) -> CompliancePublicInputs {
+        CompliancePublicInputs {
+            max_position_pct: self.max_position_pct,
+            min_liquidity_pct: self.min_liquidity_pct,
+            whitelist_root: Fr::from(self.merkle_root),
+            snapshot_commitment: self.snapshot_commitment(poseidon_config),
+            period: self.period,
+        }
+    }
+}
+
+/// Number of public inputs of the compliance circuit
+pub const NUM_PUBLIC_INPUTS: usize = 5;
+
+/// Public inputs of the compliance circuit. They are allocated in the order of the fields, which
+/// is also the order of the `_pubSignals` of the Solidity verifier:
+///
+/// | index | public input          |
+/// |-------|-----------------------|
+/// | 0     | `max_position_pct`    |
+/// | 1     | `min_liquidity_pct`   |
+/// | 2     | `whitelist_root`      |
+/// | 3     | `snapshot_commitment` |
+/// | 4     | `period`              |
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompliancePublicInputs {
+    /// Maximum size of a position, in percentage of the total value
+    pub max_position_pct: u64,
+    /// Minimum USDC reserve, in percentage of the total value
+    pub min_liquidity_pct: u64,
+    /// Root of the whitelist of assets
+    pub whitelist_root: Fr,
+    /// Commitment to the private snapshot, see [`ComplianceParams::snapshot_commitment`]
+    pub snapshot_commitment: Fr,
+    /// Reporting period of the snapshot
+    pub period: u64,
+}
+
+impl CompliancePublicInputs {
+    pub fn to_field_elements(&self) -> [Fr; NUM_PUBLIC_INPUTS] {
+        [
+            Fr::from(self.max_position_pct),
+            Fr::from(self.min_liquidity_pct),
+            self.whitelist_root,
+            self.snapshot_commitment,
+            Fr::from(self.period),
+        ]
+    }
 }
 
 /// Compliance Circuit
@@ -51,19 +133,34 @@ pub struct ComplianceParams {
 /// 1. Position limit: largest_asset / total ≤ 40%
 /// 2. Liquidity: usdc / total ≥ 10%
 /// 3. Whitelist: Merkle proof verification
+///
+/// for the snapshot committed in the public inputs, see [`CompliancePublicInputs`].
 #[derive(Clone)]
 pub struct ComplianceCircuit {
     params: ComplianceParams,
+    poseidon_config: PoseidonConfig<Fr>,
 }
 
 impl ComplianceCircuit {
     pub fn new(params: ComplianceParams) -> Self {
-        Self { params }
+        Self {
+            params,
+            poseidon_config: poseidon_canonical_config::<Fr>(),
+        }
+    }
+
+    pub fn public_inputs(&self) -> CompliancePublicInputs {
+        self.params.public_inputs(&self.poseidon_config)
     }
 }
 
 impl ConstraintSynthesizer<Fr> for ComplianceCircuit {
     fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        // Public inputs, allocated in the order of `CompliancePublicInputs`
+        let public_inputs = self.public_inputs().to_field_elements();
+        let [max_pct, min_pct, expected_root, snapshot_commitment, period] =
+            <[FpVar<Fr>; NUM_PUBLIC_INPUTS]>::new_input(cs.clone(), || Ok(public_inputs))?;
+
         // Constants
         let hundred = FpVar::<Fr>::new_constant(cs.clone(), Fr::from(100u64))?;
 
@@ -75,8 +172,6 @@ impl ConstraintSynthesizer<Fr> for ComplianceCircuit {
         let largest = FpVar::new_witness(cs.clone(), || {
             Ok(Fr::from(self.params.largest_asset_value))
         })?;
-        let max_pct =
-            FpVar::new_constant(cs.clone(), Fr::from(self.params.max_position_pct))?;
 
         // Compute asset_pct = (largest * 100) / total
         let largest_times_100 = &largest * &hundred;
@@ -109,8 +204,6 @@ impl ConstraintSynthesizer<Fr> for ComplianceCircuit {
         // ========================================
 
         let usdc = FpVar::new_witness(cs.clone(), || Ok(Fr::from(self.params.usdc_balance)))?;
-        let min_pct =
-            FpVar::new_constant(cs.clone(), Fr::from(self.params.min_liquidity_pct))?;
 
         // Compute usdc_pct = (usdc * 100) / total
         let usdc_times_100 = &usdc * &hundred;
@@ -147,11 +240,22 @@ impl ConstraintSynthesizer<Fr> for ComplianceCircuit {
 
         // Simple Merkle verification (demo - production would use Poseidon)
         let computed_root = &leaf + &sibling;
-        let expected_root = FpVar::new_constant(cs.clone(), Fr::from(self.params.merkle_root))?;
 
         // Enforce: computed_root == expected_root
         computed_root.enforce_equal(&expected_root)?;
 
+        // ========================================
+        // Snapshot commitment
+        // ========================================
+
+        let salt = FpVar::new_witness(cs.clone(), || Ok(Fr::from(self.params.snapshot_salt)))?;
+        let crh_params = CRHParametersVar::<Fr>::new_constant(cs.clone(), self.poseidon_config)?;
+        let computed_commitment = CRHGadget::<Fr>::evaluate(
+            &crh_params,
+            &[period, total, largest, usdc, leaf, salt],
+        )?;
+        computed_commitment.enforce_equal(&snapshot_commitment)?;
+
         Ok(())
     }
 }
@@ -189,44 +293,29 @@ fn generate_proof(
 fn verify_proof(
     vk: &VerifyingKey<Bn254>,
     proof: &Proof<Bn254>,
+    public_inputs: &CompliancePublicInputs,
 ) -> Result<bool, Box<dyn std::error::Error>> {
     eprintln!("✅ Verifying proof...");
     let start = Instant::now();
 
-    // Public inputs (empty for this circuit - all private)
-    let public_inputs = vec![];
-    let valid = Groth16::<Bn254>::verify(vk, &public_inputs, proof)?;
+    let valid = Groth16::<Bn254>::verify(vk, &public_inputs.to_field_elements(), proof)?;
 
     eprintln!("   Verification completed: {:?}", start.elapsed());
     Ok(valid)
 }
 
-/// Export proof as calldata for on-chain verification
-fn export_calldata(proof: &Proof<Bn254>, _vk: &VerifyingKey<Bn254>) -> Vec<u8> {
-    use ark_ec::AffineRepr;
-    use ark_ff::{BigInteger, PrimeField};
-
-    // Function selector for verifyProof(uint256[2],uint256[2][2],uint256[2])
-    const FUNCTION_SELECTOR: [u8; 4] = [0x66, 0x68, 0xa9, 0xfa];
-
-    // Extract proof points in affine coordinates
-    let (a_x, a_y) = proof.a.xy().unwrap();
-    let (b_x, b_y) = proof.b.xy().unwrap();
-    let (c_x, c_y) = proof.c.xy().unwrap();
-
-    // ABI encode proof (selector + pA + pB + pC)
-    [
-        &FUNCTION_SELECTOR[..],
-        &a_x.into_bigint().to_bytes_be(),
-        &a_y.into_bigint().to_bytes_be(),
-        &b_x.c1.into_bigint().to_bytes_be(),
-        &b_x.c0.into_bigint().to_bytes_be(),
-        &b_y.c1.into_bigint().to_bytes_be(),
-        &b_y.c0.into_bigint().to_bytes_be(),
-        &c_x.into_bigint().to_bytes_be(),
-        &c_y.into_bigint().to_bytes_be(),
-    ]
-    .concat()
+/// Export proof as calldata for on-chain verification, ie. for
+/// `verifyProof(pA, pB, pC, pubSignals)` of the verifier rendered from `vk`
+fn export_calldata(
+    proof: &Proof<Bn254>,
+    public_inputs: &CompliancePublicInputs,
+    vk: &VerifyingKey<Bn254>,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    Ok(prepare_calldata_for_groth16_verifier(
+        vk,
+        proof,
+        &public_inputs.to_field_elements(),
+    )?)
 }
 
 /// Save parameters to disk
@@ -256,6 +345,13 @@ fn save_params(
     // Generate and save Solidity verifier
     eprintln!("📝 Generating Solidity verifier contract...");
     let g16_vk = Groth16VerifierKey::from(vk.clone());
+
+    // Also save the key in the format of `solidity-verifiers-cli -p groth16 -k <path>`, so that
+    // the verifier can be rendered again without the proving key
+    let protocol_vk_path = Path::new(PARAMS_DIR).join("protocol_vk.bin");
+    let mut protocol_vk_file = fs::File::create(&protocol_vk_path)?;
+    g16_vk.serialize_protocol_verifier_key(&mut protocol_vk_file)?;
+
     let verifier_code = g16_vk.render_as_template(None);
 
     let verifier_path = "./ComplianceGroth16Verifier.sol";
@@ -289,6 +385,16 @@ fn load_params(
     let vk_file = fs::File::open(&vk_path)?;
     let vk = VerifyingKey::<Bn254>::deserialize_compressed(vk_file)?;
 
+    // Parameters generated for a previous version of the circuit can't verify its public inputs
+    if vk.gamma_abc_g1.len() != NUM_PUBLIC_INPUTS + 1 {
+        eprintln!(
+            "⚠️  Parameters have {} public inputs instead of {}, regenerating them",
+            vk.gamma_abc_g1.len() - 1,
+            NUM_PUBLIC_INPUTS
+        );
+        return Ok(None);
+    }
+
     eprintln!("   Parameters loaded: {:?}", start.elapsed());
     Ok(Some((pk, vk)))
 }
@@ -308,9 +414,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         asset_hash: 100,
         sibling: 200,
         merkle_root: 300,
+        period: 20250930,                 // reporting day, as YYYYMMDD
+        snapshot_salt: 42,
     };
 
     let circuit = ComplianceCircuit::new(params);
+    let public_inputs = circuit.public_inputs();
 
     // Try to load existing parameters
     let (pk, vk) = match load_params()? {
@@ -351,16 +460,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                 match generate_proof(&pk, circuit.clone()) {
                     Ok(proof) => {
-                        match verify_proof(&vk, &proof) {
+                        match verify_proof(&vk, &proof, &public_inputs) {
                             Ok(true) => {
-                                let calldata = export_calldata(&proof, &vk);
+                                let calldata = export_calldata(&proof, &public_inputs, &vk)?;
 
                                 // Save calldata
                                 fs::write("./compliance-proof.calldata", &calldata)?;
 
+                                let public_inputs = public_inputs
+                                    .to_field_elements()
+                                    .iter()
+                                    .map(|x| format!("\"{}\"", x.into_bigint()))
+                                    .collect::<Vec<_>>()
+                                    .join(",");
                                 println!(
-                                    "{{\"status\":\"success\",\"verified\":true,\"proof_size\":{}}}",
-                                    calldata.len()
+                                    "{{\"status\":\"success\",\"verified\":true,\"proof_size\":{},\"public_inputs\":[{}]}}",
+                                    calldata.len(),
+                                    public_inputs
                                 );
                             }
                             Ok(false) => {
@@ -393,3 +509,70 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// cargo test --example compliance_groth16_stdio
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::gr1cs::ConstraintSystem;
+
+    fn params() -> ComplianceParams {
+        ComplianceParams {
+            max_position_pct: 40,
+            largest_asset_value: 35_000_000,
+            total_value: 100_000_000,
+            min_liquidity_pct: 10,
+            usdc_balance: 10_000_000,
+            asset_hash: 100,
+            sibling: 200,
+            merkle_root: 300,
+            period: 20250930,
+            snapshot_salt: 42,
+        }
+    }
+
+    #[test]
+    fn test_public_inputs_layout() -> Result<(), SynthesisError> {
+        let circuit = ComplianceCircuit::new(params());
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.clone().generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+
+        // the instance starts with the constant `1`, followed by the public inputs in order
+        let public_inputs = circuit.public_inputs().to_field_elements();
+        assert_eq!(&cs.borrow().unwrap().instance_assignment()?[1..], &public_inputs);
+        Ok(())
+    }
+
+    #[test]
+    fn test_calldata_and_proof_verification() -> Result<(), Box<dyn std::error::Error>> {
+        let circuit = ComplianceCircuit::new(params());
+        let public_inputs = circuit.public_inputs();
+        let (pk, vk) = setup_groth16(circuit.clone())?;
+        let proof = generate_proof(&pk, circuit)?;
+        assert!(verify_proof(&vk, &proof, &public_inputs)?);
+
+        // selector + pA, pB, pC + pubSignals
+        let calldata = export_calldata(&proof, &public_inputs, &vk)?;
+        assert_eq!(calldata.len(), 4 + 32 * (8 + NUM_PUBLIC_INPUTS));
+
+        // the proof is bound to the period and to the snapshot
+        let other_period = CompliancePublicInputs {
+            period: public_inputs.period + 1,
+            ..public_inputs
+        };
+        assert!(!verify_proof(&vk, &proof, &other_period)?);
+        let other_snapshot = ComplianceParams {
+            usdc_balance: 20_000_000,
+            ..params()
+        };
+        let other_snapshot = CompliancePublicInputs {
+            snapshot_commitment: ComplianceCircuit::new(other_snapshot)
+                .public_inputs()
+                .snapshot_commitment,
+            ..public_inputs
+        };
+        assert!(!verify_proof(&vk, &proof, &other_snapshot)?);
+        Ok(())
+    }
+}
@@ -4,6 +4,8 @@
 //
 // Architecture: Nova IVC with 3-step folding
 // - Step 1-3: Each proves Position ≤40% AND Liquidity ≥10% AND Whitelisted
+//   for one daily period, the 3 periods being consecutive and gap-free, and
+//   encrypts the checked holdings to the regulator
 // - Decider: Compresses recursive proof for on-chain verification
 //
 // Optimization Strategy (Option 1 - Modified):
@@ -13,18 +15,16 @@
 // - Total load time: ~60s (same as before, but simplified code)
 
 use ark_bn254::{Bn254, Fr, G1Projective as G1};
-use ark_ff::PrimeField;
 use ark_groth16::Groth16;
 use ark_grumpkin::Projective as G2;
-use ark_r1cs_std::alloc::AllocVar;
-use ark_r1cs_std::fields::fp::FpVar;
-use ark_r1cs_std::eq::EqGadget;
-use ark_r1cs_std::fields::FieldVar;
-use ark_relations::gr1cs::{ConstraintSystemRef, SynthesisError};
 use ark_serialize::CanonicalDeserialize;
-use std::marker::PhantomData;
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
+use compliance_circuits::{
+    composite::CompositeStepInputs,
+    demo::{self, ReportedCircuit, ReportedInputs},
+    reporting::rebalance_metadata,
+};
 use folding_schemes::{
     commitment::{kzg::KZG, pedersen::Pedersen, CommitmentScheme},
     folding::nova::{decider_eth::Decider as DeciderEth, Nova, PreprocessorParam},
@@ -41,118 +41,32 @@ use std::path::Path;
 
 const PARAMS_DIR: &str = "./persisted_params";
 const N_STEPS: usize = 3;
-
-/// Composite Fund Compliance Circuit Parameters
-#[derive(Clone, Copy, Debug)]
-pub struct CompositeFundParams {
-    pub max_position_pct: u64,
-    pub largest_asset_value: u64,
-    pub min_liquidity_pct: u64,
-    pub usdc_balance: u64,
-    pub asset_hash: u64,
-    pub sibling: u64,
-    pub merkle_root: u64,
-    pub total_value: u64,
-}
-
-/// Composite Fund Compliance Circuit
-/// Proves ALL THREE compliance requirements in a single circuit per fold step
-#[derive(Clone, Copy, Debug)]
-pub struct CompositeFundCircuit<F: PrimeField> {
-    params: CompositeFundParams,
-    _f: PhantomData<F>,
-}
-
-impl<F: PrimeField> FCircuit<F> for CompositeFundCircuit<F> {
-    type Params = CompositeFundParams;
-    type ExternalInputs = ();
-    type ExternalInputsVar = ();
-
-    fn new(params: Self::Params) -> Result<Self, Error> {
-        Ok(Self {
-            params,
-            _f: PhantomData,
-        })
-    }
-
-    fn state_len(&self) -> usize {
-        1 // compliance_counter
-    }
-
-    fn generate_step_constraints(
-        &self,
-        cs: ConstraintSystemRef<F>,
-        _i: usize,
-        z_i: Vec<FpVar<F>>,
-        _external_inputs: Self::ExternalInputsVar,
-    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
-        let hundred = FpVar::new_constant(cs.clone(), F::from(100u64))?;
-
-        // CHECK 1: Position Limit
-        let total = FpVar::new_witness(cs.clone(), || Ok(F::from(self.params.total_value)))?;
-        let largest = FpVar::new_witness(cs.clone(), || Ok(F::from(self.params.largest_asset_value)))?;
-        let max_pct = FpVar::new_constant(cs.clone(), F::from(self.params.max_position_pct))?;
-
-        let largest_times_100 = &largest * &hundred;
-        let asset_pct = FpVar::new_witness(cs.clone(), || {
-            let pct = (self.params.largest_asset_value * 100) / self.params.total_value;
-            Ok(F::from(pct))
-        })?;
-
-        asset_pct.mul_equals(&total, &largest_times_100)?;
-
-        let position_diff = FpVar::new_witness(cs.clone(), || {
-            let pct = (self.params.largest_asset_value * 100) / self.params.total_value;
-            Ok(F::from(self.params.max_position_pct - pct))
-        })?;
-
-        let max_check = &asset_pct + &position_diff;
-        max_pct.enforce_equal(&max_check)?;
-
-        // CHECK 2: Liquidity
-        let usdc = FpVar::new_witness(cs.clone(), || Ok(F::from(self.params.usdc_balance)))?;
-        let min_pct = FpVar::new_constant(cs.clone(), F::from(self.params.min_liquidity_pct))?;
-
-        let usdc_times_100 = &usdc * &hundred;
-        let usdc_pct = FpVar::new_witness(cs.clone(), || {
-            let pct = (self.params.usdc_balance * 100) / self.params.total_value;
-            Ok(F::from(pct))
-        })?;
-
-        usdc_pct.mul_equals(&total, &usdc_times_100)?;
-
-        let liquidity_diff = FpVar::new_witness(cs.clone(), || {
-            let pct = (self.params.usdc_balance * 100) / self.params.total_value;
-            Ok(F::from(pct - self.params.min_liquidity_pct))
-        })?;
-
-        let min_check = &min_pct + &liquidity_diff;
-        usdc_pct.enforce_equal(&min_check)?;
-
-        // CHECK 3: Whitelist (simplified)
-        let leaf = FpVar::new_witness(cs.clone(), || Ok(F::from(self.params.asset_hash)))?;
-        let sibling = FpVar::new_witness(cs.clone(), || Ok(F::from(self.params.sibling)))?;
-
-        let computed_root = &leaf + &sibling;
-        let expected_root = FpVar::new_constant(cs, F::from(self.params.merkle_root))?;
-
-        computed_root.enforce_equal(&expected_root)?;
-
-        // Increment counter
-        Ok(vec![&z_i[0] + &FpVar::one()])
-    }
+/// Length of each reporting period (one day)
+const PERIOD_LEN: u64 = 86_400;
+
+/// Returns the last `N_STEPS` full daily periods, ending at the start of the current UTC day
+fn last_daily_periods() -> Vec<(u64, u64)> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let end = now - now % PERIOD_LEN;
+    (0..N_STEPS as u64)
+        .rev()
+        .map(|i| (end - (i + 1) * PERIOD_LEN, end - i * PERIOD_LEN))
+        .collect()
 }
 
 // Type aliases
-type N = Nova<G1, G2, CompositeFundCircuit<Fr>, KZG<'static, Bn254>, Pedersen<G2>, false>;
-type D = DeciderEth<G1, G2, CompositeFundCircuit<Fr>, KZG<'static, Bn254>, Pedersen<G2>, Groth16<Bn254>, N>;
+type N = Nova<G1, G2, ReportedCircuit, KZG<'static, Bn254>, Pedersen<G2>, false>;
+type D = DeciderEth<G1, G2, ReportedCircuit, KZG<'static, Bn254>, Pedersen<G2>, Groth16<Bn254>, N>;
 
 /// Fast parameter loading (Option 1 optimization)
-fn load_params_fast(f_circuit: CompositeFundCircuit<Fr>) -> Result<
+fn load_params_fast(f_circuit: ReportedCircuit) -> Result<
     Option<(
-        <N as FoldingScheme<G1, G2, CompositeFundCircuit<Fr>>>::ProverParam,
-        <N as FoldingScheme<G1, G2, CompositeFundCircuit<Fr>>>::VerifierParam,
-        <D as Decider<G1, G2, CompositeFundCircuit<Fr>, N>>::ProverParam,
+        <N as FoldingScheme<G1, G2, ReportedCircuit>>::ProverParam,
+        <N as FoldingScheme<G1, G2, ReportedCircuit>>::VerifierParam,
+        <D as Decider<G1, G2, ReportedCircuit, N>>::ProverParam,
     )>,
     Error,
 > {
@@ -177,7 +91,7 @@ fn load_params_fast(f_circuit: CompositeFundCircuit<Fr>) -> Result<
     eprintln!("   📁 Read nova_prover_params.bin: {:?}", start.elapsed());
 
     let start = Instant::now();
-    let nova_prover_param = <N as FoldingScheme<G1, G2, CompositeFundCircuit<Fr>>>::ProverParam::deserialize_compressed(&nova_pp_data[..])
+    let nova_prover_param = <N as FoldingScheme<G1, G2, ReportedCircuit>>::ProverParam::deserialize_compressed(&nova_pp_data[..])
         .map_err(|e| Error::Other(format!("Failed to deserialize nova_prover_params: {}", e)))?;
     eprintln!("   🔓 Deserialize nova_prover_params: {:?}", start.elapsed());
 
@@ -220,7 +134,7 @@ fn load_params_fast(f_circuit: CompositeFundCircuit<Fr>) -> Result<
     eprintln!("   📁 Read decider_pp.bin: {:?}", start.elapsed());
 
     let start = Instant::now();
-    let decider_pp = <D as Decider<G1, G2, CompositeFundCircuit<Fr>, N>>::ProverParam::deserialize_compressed(&pp_data[..])
+    let decider_pp = <D as Decider<G1, G2, ReportedCircuit, N>>::ProverParam::deserialize_compressed(&pp_data[..])
         .map_err(|e| Error::Other(format!("Failed to deserialize decider_pp: {}", e)))?;
     eprintln!("   🔓 Deserialize decider_pp: {:?} (slow but necessary)", start.elapsed());
 
@@ -235,19 +149,14 @@ fn main() -> Result<(), Error> {
     eprintln!("🚀 Arc Compliance Service (Nova Folding - Fast Mode) Starting...\n");
     eprintln!("════════════════════════════════════════════════════════════");
 
-    // Example compliance parameters (same for all 3 days in demo)
-    let params = CompositeFundParams {
-        max_position_pct: 40,
-        largest_asset_value: 35_000_000,  // $35M
-        total_value: 100_000_000,         // $100M
-        min_liquidity_pct: 10,
-        usdc_balance: 10_000_000,         // $10M
-        asset_hash: 100,
-        sibling: 200,
-        merkle_root: 300,
-    };
+    // Demo fund, whose holdings and prices (same for all 3 days) are signed by the demo oracles
+    let oracle = demo::oracle();
+    let pricing_oracle = demo::pricing_oracle();
+    let accounts = demo::accounts();
+    let regulator = demo::regulator().public_key();
+    let poseidon_config = poseidon_canonical_config::<Fr>();
 
-    let f_circuit = CompositeFundCircuit::new(params)?;
+    let f_circuit = ReportedCircuit::new(demo::reported_params())?;
 
     // Load parameters
     let (nova_prover_param, nova_verifier_param, decider_pp) = match load_params_fast(f_circuit.clone())? {
@@ -285,8 +194,9 @@ fn main() -> Result<(), Error> {
                 println!("{{\"status\":\"initializing\",\"message\":\"Starting Nova prover...\"}}");
                 stdout.flush().map_err(|e| Error::Other(e.to_string()))?;
 
-                // Initial state (counter starts at 0)
-                let z_0 = vec![Fr::from(0u32)];
+                // Initial state (empty time window, prices and reports, counter starts at 0)
+                let z_0 = vec![Fr::from(0u32); f_circuit.state_len()];
+                let periods = last_daily_periods();
 
                 // Initialize Nova
                 let mut rng = ark_std::rand::rngs::OsRng;
@@ -296,7 +206,9 @@ fn main() -> Result<(), Error> {
                     Ok(mut nova) => {
                         // Fold N_STEPS times
                         let mut all_success = true;
-                        for i in 0..N_STEPS {
+                        let mut reports = Vec::new();
+                        let mut prices = Vec::new();
+                        for (i, (period_start, period_end)) in periods.iter().enumerate() {
                             println!(
                                 "{{\"status\":\"folding\",\"message\":\"Folding compliance check {} (all 3 requirements)...\",\"step\":{},\"total_steps\":{}}}",
                                 i + 1,
@@ -306,7 +218,20 @@ fn main() -> Result<(), Error> {
                             stdout.flush().map_err(|e| Error::Other(e.to_string()))?;
 
                             let start = Instant::now();
-                            match nova.prove_step(&mut rng, (), None) {
+                            let inputs = ReportedInputs::new(
+                                CompositeStepInputs::attest(
+                                    &oracle,
+                                    &pricing_oracle,
+                                    &poseidon_config,
+                                    &accounts,
+                                    [*period_start, *period_end],
+                                    &demo::snapshot(),
+                                ),
+                                &mut rng,
+                            );
+                            reports.push(inputs.ciphertext(&poseidon_config, &regulator));
+                            prices.push(inputs.inputs.prices.prices.commitment(&poseidon_config));
+                            match nova.prove_step(&mut rng, inputs, None) {
                                 Ok(_) => {
                                     let elapsed = start.elapsed().as_millis();
                                     eprintln!("   ✅ Step {} completed in {}ms", i + 1, elapsed);
@@ -357,10 +282,16 @@ fn main() -> Result<(), Error> {
 
                                 eprintln!("   💾 Saved calldata ({} bytes)", calldata.len());
 
+                                // Save the reports and price commitments, the metadata of executeRebalance
+                                fs::write("./composite-proof.metadata", rebalance_metadata(&reports, &prices))
+                                    .map_err(|e| Error::Other(format!("Failed to save metadata: {}", e)))?;
+
                                 println!(
-                                    "{{\"status\":\"success\",\"verified\":true,\"proof_size\":{},\"periods_proven\":{}}}",
+                                    "{{\"status\":\"success\",\"verified\":true,\"proof_size\":{},\"periods_proven\":{},\"first_period\":{},\"last_period\":{}}}",
                                     calldata.len(),
-                                    N_STEPS
+                                    N_STEPS,
+                                    periods[0].0,
+                                    periods[N_STEPS - 1].1
                                 );
                                 stdout.flush().map_err(|e| Error::Other(e.to_string()))?;
                             }
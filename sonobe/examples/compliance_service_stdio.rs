@@ -12,22 +12,22 @@
 /// - One circuit checks: Position ≤ 40%, Liquidity ≥ 10%, Whitelist membership
 /// - Nova folds this circuit over N steps (e.g., N days of compliance)
 /// - Final proof: "Fund was compliant for N consecutive periods"
+/// - The checked holdings are encrypted to the regulator, and the ciphertexts and price commitments
+///   are saved as the `metadata` of `executeRebalance`
 /// - On-chain cost: $0.02 (single verification)
 ///
 use ark_bn254::{Bn254, Fr, G1Projective as G1};
-use ark_ff::PrimeField;
 use ark_groth16::Groth16;
 use ark_grumpkin::Projective as G2;
-use ark_r1cs_std::alloc::AllocVar;
-use ark_r1cs_std::eq::EqGadget;
-use ark_r1cs_std::fields::fp::FpVar;
-use ark_r1cs_std::fields::FieldVar;
-use ark_relations::gr1cs::{ConstraintSystemRef, SynthesisError};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use std::marker::PhantomData;
 use std::path::Path;
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
+use compliance_circuits::{
+    composite::CompositeStepInputs,
+    demo::{self, ReportedCircuit, ReportedInputs},
+    reporting::rebalance_metadata,
+};
 use folding_schemes::{
     commitment::{kzg::KZG, pedersen::Pedersen, CommitmentScheme},
     folding::{
@@ -44,182 +44,17 @@ use solidity_verifiers::{
 
 const PARAMS_DIR: &str = "./persisted_params";
 
-/// Fund Compliance Circuit Parameters
-/// Daily compliance proof for on-chain verification (no folding)
-#[derive(Clone, Copy, Debug)]
-pub struct CompositeFundParams {
-    // Position limit check
-    pub max_position_pct: u64,        // 40% limit
-    pub largest_asset_value: u64,      // e.g., $35M
-    pub total_value: u64,              // e.g., $100M
-
-    // Liquidity requirement check
-    pub min_liquidity_pct: u64,        // 10% minimum
-    pub usdc_balance: u64,             // e.g., $10M
-
-    // Whitelist verification (Merkle proof)
-    pub asset_hash: u64,               // Hash of asset address
-    pub sibling: u64,                  // Merkle sibling
-    pub merkle_root: u64,              // Whitelist root
-}
-
-/// Fund Compliance Circuit (Daily Verification)
-///
-/// Proves THREE compliance requirements in a single Groth16 proof:
-/// 1. Position Limit: largest_asset / total_value ≤ 40%
-/// 2. Liquidity Reserve: usdc_balance / total_value ≥ 10%
-/// 3. Asset Whitelist: Merkle proof verification
-///
-/// Architecture: Single daily proof (no folding)
-/// - Each day: generate new proof for that day's compliance
-/// - Submit to Arc testnet for on-chain verification
-/// - ~800-1200 R1CS constraints, Groth16 proof ~288 bytes
-#[derive(Clone, Copy, Debug)]
-pub struct CompositeFundCircuit<F: PrimeField> {
-    params: CompositeFundParams,
-    _f: PhantomData<F>,
-}
-
-impl<F: PrimeField> FCircuit<F> for CompositeFundCircuit<F> {
-    type Params = CompositeFundParams;
-    type ExternalInputs = ();
-    type ExternalInputsVar = ();
-
-    fn new(params: Self::Params) -> Result<Self, Error> {
-        Ok(Self {
-            params,
-            _f: PhantomData,
-        })
-    }
-
-    fn state_len(&self) -> usize {
-        // State: [compliance_counter]
-        // Increments each time all checks pass
-        1
-    }
-
-    fn generate_step_constraints(
-        &self,
-        cs: ConstraintSystemRef<F>,
-        _i: usize,
-        z_i: Vec<FpVar<F>>,
-        _external_inputs: Self::ExternalInputsVar,
-    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
-        // Input state: compliance counter
-        let counter = &z_i[0];
-
-        // ========================================
-        // CHECK 1: Position Limit (≤ 40%)
-        // ========================================
-
-        let asset = FpVar::<F>::new_witness(cs.clone(), || {
-            Ok(F::from(self.params.largest_asset_value))
-        })?;
-
-        let total = FpVar::<F>::new_witness(cs.clone(), || {
-            Ok(F::from(self.params.total_value))
-        })?;
-
-        let max_pct = FpVar::<F>::new_constant(cs.clone(), F::from(self.params.max_position_pct))?;
-        let hundred = FpVar::<F>::new_constant(cs.clone(), F::from(100u64))?;
-
-        // Compute asset_pct = (asset * 100) / total
-        let asset_times_100 = &asset * &hundred;
-
-        let asset_pct = FpVar::<F>::new_witness(cs.clone(), || {
-            if self.params.total_value == 0 {
-                return Err(SynthesisError::DivisionByZero);
-            }
-            let pct = (self.params.largest_asset_value * 100) / self.params.total_value;
-            Ok(F::from(pct))
-        })?;
-
-        // Enforce: asset_pct * total = asset * 100
-        asset_pct.mul_equals(&total, &asset_times_100)?;
-
-        // Check: asset_pct ≤ max_pct
-        let position_diff = FpVar::<F>::new_witness(cs.clone(), || {
-            let pct = (self.params.largest_asset_value * 100) / self.params.total_value;
-            if pct > self.params.max_position_pct {
-                return Err(SynthesisError::AssignmentMissing);
-            }
-            Ok(F::from(self.params.max_position_pct - pct))
-        })?;
-
-        // Enforce: max_pct = asset_pct + diff
-        let max_check = &asset_pct + &position_diff;
-        max_pct.enforce_equal(&max_check)?;
-
-        // ========================================
-        // CHECK 2: Liquidity Reserve (≥ 10%)
-        // ========================================
-
-        let usdc = FpVar::<F>::new_witness(cs.clone(), || {
-            Ok(F::from(self.params.usdc_balance))
-        })?;
-
-        let min_pct = FpVar::<F>::new_constant(cs.clone(), F::from(self.params.min_liquidity_pct))?;
-
-        // Compute usdc_pct = (usdc * 100) / total
-        let usdc_times_100 = &usdc * &hundred;
-
-        let usdc_pct = FpVar::<F>::new_witness(cs.clone(), || {
-            if self.params.total_value == 0 {
-                return Err(SynthesisError::DivisionByZero);
-            }
-            let pct = (self.params.usdc_balance * 100) / self.params.total_value;
-            Ok(F::from(pct))
-        })?;
-
-        // Enforce: usdc_pct * total = usdc * 100
-        usdc_pct.mul_equals(&total, &usdc_times_100)?;
-
-        // Check: usdc_pct ≥ min_pct
-        let liquidity_diff = FpVar::<F>::new_witness(cs.clone(), || {
-            let pct = (self.params.usdc_balance * 100) / self.params.total_value;
-            if pct < self.params.min_liquidity_pct {
-                return Err(SynthesisError::AssignmentMissing);
-            }
-            Ok(F::from(pct - self.params.min_liquidity_pct))
-        })?;
-
-        // Enforce: usdc_pct = min_pct + diff
-        let min_check = &min_pct + &liquidity_diff;
-        usdc_pct.enforce_equal(&min_check)?;
-
-        // ========================================
-        // CHECK 3: Whitelist Membership (Merkle proof)
-        // ========================================
-        // Simplified: one-level Merkle tree (demo only)
-        // Production: full Merkle tree with Poseidon hash
-
-        let leaf = FpVar::<F>::new_witness(cs.clone(), || {
-            Ok(F::from(self.params.asset_hash))
-        })?;
-
-        let sibling = FpVar::<F>::new_witness(cs.clone(), || {
-            Ok(F::from(self.params.sibling))
-        })?;
-
-        // Compute Merkle parent: hash(leaf + sibling)
-        // For demo: simple addition (replace with Poseidon in production)
-        let computed_root = &leaf + &sibling;
-
-        let expected_root = FpVar::<F>::new_constant(cs.clone(), F::from(self.params.merkle_root))?;
-
-        // Enforce: computed_root == expected_root
-        computed_root.enforce_equal(&expected_root)?;
-
-        // ========================================
-        // ALL CHECKS PASSED - Increment Counter
-        // ========================================
-
-        let one = FpVar::<F>::new_constant(cs.clone(), F::one())?;
-        let new_counter = counter + &one;
-
-        // Output: incremented counter
-        Ok(vec![new_counter])
-    }
+/// Length of each reporting period (one day)
+const PERIOD_LEN: u64 = 86_400;
+
+/// Returns the last full daily period, ending at the start of the current UTC day
+fn last_daily_period() -> (u64, u64) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let end = now - now % PERIOD_LEN;
+    (end - PERIOD_LEN, end)
 }
 
 use std::io::{self, BufRead, Write};
@@ -232,26 +67,20 @@ fn main() -> Result<(), Error> {
     eprintln!("📂 Loading cryptographic parameters from disk...");
     let load_start = Instant::now();
 
-    // Full compliance circuit parameters - all 3 checks
-    let params = CompositeFundParams {
-        max_position_pct: 40,
-        largest_asset_value: 35_000_000,  // $35M
-        min_liquidity_pct: 10,
-        usdc_balance: 10_000_000,         // $10M
-        asset_hash: 100,
-        sibling: 200,
-        merkle_root: 300,
-        total_value: 100_000_000,         // $100M
-    };
+    // Demo fund, whose holdings and prices are signed by the demo oracles
+    let oracle = demo::oracle();
+    let pricing_oracle = demo::pricing_oracle();
+    let accounts = demo::accounts();
+    let regulator = demo::regulator().public_key();
 
-    let f_circuit = CompositeFundCircuit::<Fr>::new(params)?;
+    let f_circuit = ReportedCircuit::new(demo::reported_params())?;
 
     // Define types for Nova and Decider
-    pub type N = Nova<G1, G2, CompositeFundCircuit<Fr>, KZG<'static, Bn254>, Pedersen<G2>, false>;
+    pub type N = Nova<G1, G2, ReportedCircuit, KZG<'static, Bn254>, Pedersen<G2>, false>;
     pub type D = DeciderEth<
         G1,
         G2,
-        CompositeFundCircuit<Fr>,
+        ReportedCircuit,
         KZG<'static, Bn254>,
         Pedersen<G2>,
         Groth16<Bn254>,
@@ -284,7 +113,7 @@ fn main() -> Result<(), Error> {
         println!("   📁 Read nova_prover_params.bin: {:?}", start.elapsed());
 
         let start = Instant::now();
-        let nova_prover_param = <N as FoldingScheme<G1, G2, CompositeFundCircuit<Fr>>>::ProverParam
+        let nova_prover_param = <N as FoldingScheme<G1, G2, ReportedCircuit>>::ProverParam
             ::deserialize_compressed(&nova_pp_data[..])
             .map_err(|e| Error::Other(format!("Failed to deserialize nova_prover_params: {}", e)))?;
         println!("   🔓 Deserialize nova_prover_params: {:?}", start.elapsed());
@@ -317,7 +146,7 @@ fn main() -> Result<(), Error> {
         let start = Instant::now();
         let nova_preprocess_params = PreprocessorParam {
             poseidon_config: poseidon_config.clone(),
-            F: f_circuit,
+            F: f_circuit.clone(),
             cs_pp: Some(nova_prover_param.cs_pp.clone()),
             cs_vp: Some(nova_cs_vp),
             cf_cs_pp: Some(nova_prover_param.cf_cs_pp.clone()),
@@ -335,7 +164,7 @@ fn main() -> Result<(), Error> {
         println!("   📁 Read decider_pp.bin: {:?}", start.elapsed());
 
         let start = Instant::now();
-        let decider_pp = <D as Decider<G1, G2, CompositeFundCircuit<Fr>, N>>::ProverParam
+        let decider_pp = <D as Decider<G1, G2, ReportedCircuit, N>>::ProverParam
             ::deserialize_compressed(&pp_data[..])
             .map_err(|e| Error::Other(format!("Failed to deserialize decider_pp: {}", e)))?;
         println!("   🔓 Deserialize decider_pp: {:?}", start.elapsed());
@@ -347,7 +176,7 @@ fn main() -> Result<(), Error> {
         println!("   📁 Read decider_vp.bin: {:?}", start.elapsed());
 
         let start = Instant::now();
-        let decider_vp = <D as Decider<G1, G2, CompositeFundCircuit<Fr>, N>>::VerifierParam
+        let decider_vp = <D as Decider<G1, G2, ReportedCircuit, N>>::VerifierParam
             ::deserialize_compressed(&vp_data[..])
             .map_err(|e| Error::Other(format!("Failed to deserialize decider_vp: {}", e)))?;
         println!("   🔓 Deserialize decider_vp: {:?}", start.elapsed());
@@ -363,7 +192,7 @@ fn main() -> Result<(), Error> {
 
         println!("   ⚙️  Producing Nova public parameters...");
         let start = Instant::now();
-        let nova_preprocess_params = PreprocessorParam::new(poseidon_config.clone(), f_circuit);
+        let nova_preprocess_params = PreprocessorParam::new(poseidon_config.clone(), f_circuit.clone());
         let (nova_prover_param, nova_verifier_param) = N::preprocess(&mut rng, &nova_preprocess_params)?;
 
         // Extract CS verifier params before moving nova_verifier_param
@@ -455,12 +284,26 @@ fn main() -> Result<(), Error> {
             let proof_start = Instant::now();
 
             // Generate proof using pre-loaded params
-            let z_0 = vec![Fr::from(0u32)];
-            let mut nova = N::init(&nova_params, f_circuit, z_0)?;
+            let z_0 = vec![Fr::from(0u32); f_circuit.state_len()];
+            let mut nova = N::init(&nova_params, f_circuit.clone(), z_0)?;
 
-            // Fold 1 step (simplified for demo to reduce memory usage)
+            // Fold 1 step (simplified for demo to reduce memory usage), covering the last day
+            let (period_start, period_end) = last_daily_period();
             let step_start = Instant::now();
-            nova.prove_step(rng, (), None)?;
+            let inputs = ReportedInputs::new(
+                CompositeStepInputs::attest(
+                    &oracle,
+                    &pricing_oracle,
+                    &poseidon_config,
+                    &accounts,
+                    [period_start, period_end],
+                    &demo::snapshot(),
+                ),
+                &mut rng,
+            );
+            let reports = [inputs.ciphertext(&poseidon_config, &regulator)];
+            let prices = [inputs.inputs.prices.prices.commitment(&poseidon_config)];
+            nova.prove_step(rng, inputs, None)?;
             eprintln!("   Step 1: {:?}", step_start.elapsed());
 
             // Generate Decider proof (final compression for on-chain verification)
@@ -503,6 +346,12 @@ fn main() -> Result<(), Error> {
                 .map_err(|e| Error::Other(format!("Failed to write calldata: {}", e)))?;
             eprintln!("   💾 Calldata saved to {}", calldata_path);
 
+            // Write the reports and price commitments, the metadata of executeRebalance
+            let metadata_path = "composite-proof.metadata";
+            std::fs::write(metadata_path, rebalance_metadata(&reports, &prices))
+                .map_err(|e| Error::Other(format!("Failed to write metadata: {}", e)))?;
+            eprintln!("   💾 Metadata saved to {}", metadata_path);
+
             // Estimate gas (Nova verification is more expensive than Groth16)
             let gas_estimate = calldata.len() * 16 + 21000 + 800000; // Higher gas for Nova
             eprintln!("   ⛽ Estimated gas: {}\n", gas_estimate);
@@ -510,8 +359,8 @@ fn main() -> Result<(), Error> {
             eprintln!("✅ Total time: {:?}", proof_start.elapsed());
 
             // Return proof result
-            println!("{{\"success\":true,\"duration_ms\":{},\"verified\":{},\"gas_estimate\":{}}}",
-                proof_start.elapsed().as_millis(), verified, gas_estimate);
+            println!("{{\"success\":true,\"duration_ms\":{},\"verified\":{},\"gas_estimate\":{},\"first_period\":{},\"last_period\":{}}}",
+                proof_start.elapsed().as_millis(), verified, gas_estimate, period_start, period_end);
             io::stdout().flush().unwrap();
 
         } else {
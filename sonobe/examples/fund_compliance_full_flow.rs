@@ -12,22 +12,30 @@
 /// - One circuit checks: Position ≤ 40%, Liquidity ≥ 10%, Whitelist membership
 /// - Nova folds this circuit over N steps (e.g., N days of compliance)
 /// - Final proof: "Fund was compliant for N consecutive periods"
+/// - Each step takes its reporting period `[period_start, period_end)` as external inputs, and the
+///   folded state `[first_period, last_period, count, prices_hash, reports_hash]` tells which time
+///   window the proof covers, and chains the prices and the encrypted reports of its periods
+/// - The holdings of each step are signed by an oracle and the prices by a pricing oracle, whose
+///   public keys are fixed in the circuit, and the NAV is derived in-circuit from both
+/// - The checked holdings of each step are encrypted to the view key of a regulator, and the
+///   ciphertexts and the price commitments are the `metadata` of `executeRebalance`
 /// - On-chain cost: $0.02 (single verification)
 ///
 use ark_bn254::{Bn254, Fr, G1Projective as G1};
-use ark_ff::PrimeField;
 use ark_groth16::Groth16;
 use ark_grumpkin::Projective as G2;
-use ark_r1cs_std::alloc::AllocVar;
-use ark_r1cs_std::eq::EqGadget;
-use ark_r1cs_std::fields::fp::FpVar;
-use ark_r1cs_std::fields::FieldVar;
-use ark_relations::gr1cs::{ConstraintSystemRef, SynthesisError};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use std::marker::PhantomData;
+use num_bigint::BigUint;
 use std::path::Path;
 use std::time::Instant;
 
+use compliance_circuits::{
+    amount::NORMALIZED_DECIMALS,
+    composite::CompositeStepInputs,
+    demo::{self, ReportedCircuit, ReportedInputs},
+    nav::{net_asset_value, position_value},
+    reporting::rebalance_metadata,
+};
 use folding_schemes::{
     commitment::{kzg::KZG, pedersen::Pedersen, CommitmentScheme},
     folding::{
@@ -47,181 +55,15 @@ use solidity_verifiers::{
 
 const PARAMS_DIR: &str = "./persisted_params";
 
-/// Composite Fund Compliance Circuit Parameters
-/// Combines all three compliance checks in a single circuit
-#[derive(Clone, Copy, Debug)]
-pub struct CompositeFundParams {
-    // Position limit check
-    pub max_position_pct: u64,
-    pub largest_asset_value: u64,
-
-    // Liquidity check
-    pub min_liquidity_pct: u64,
-    pub usdc_balance: u64,
-
-    // Whitelist check (simplified Merkle proof for demo)
-    pub asset_hash: u64,
-    pub sibling: u64,
-    pub merkle_root: u64,
-
-    // Shared parameter
-    pub total_value: u64,
-}
+/// Length of each reporting period (one day)
+const PERIOD_LEN: u64 = 86_400;
+/// Start of the first reporting period (2025-07-01 00:00:00 UTC)
+const FIRST_PERIOD_START: u64 = 1_751_328_000;
 
-/// Composite Fund Compliance Circuit
-///
-/// This circuit proves ALL THREE compliance requirements in a single circuit:
-/// 1. Position Limit: largest_asset_value / total_value ≤ max_position_pct (40%)
-/// 2. Liquidity: usdc_balance / total_value ≥ min_liquidity_pct (10%)
-/// 3. Whitelist: asset_hash is in Merkle tree with root = merkle_root
-///
-/// Nova will fold this circuit over N steps, proving N consecutive compliant periods
-#[derive(Clone, Copy, Debug)]
-pub struct CompositeFundCircuit<F: PrimeField> {
-    params: CompositeFundParams,
-    _f: PhantomData<F>,
-}
-
-impl<F: PrimeField> FCircuit<F> for CompositeFundCircuit<F> {
-    type Params = CompositeFundParams;
-    type ExternalInputs = ();
-    type ExternalInputsVar = ();
-
-    fn new(params: Self::Params) -> Result<Self, Error> {
-        Ok(Self {
-            params,
-            _f: PhantomData,
-        })
-    }
-
-    fn state_len(&self) -> usize {
-        // State: [compliance_counter]
-        // Increments each time all checks pass
-        1
-    }
-
-    fn generate_step_constraints(
-        &self,
-        cs: ConstraintSystemRef<F>,
-        _i: usize,
-        z_i: Vec<FpVar<F>>,
-        _external_inputs: Self::ExternalInputsVar,
-    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
-        // Input state: compliance counter
-        let counter = &z_i[0];
-
-        // ========================================
-        // CHECK 1: Position Limit (≤ 40%)
-        // ========================================
-
-        let asset = FpVar::<F>::new_witness(cs.clone(), || {
-            Ok(F::from(self.params.largest_asset_value))
-        })?;
-
-        let total = FpVar::<F>::new_witness(cs.clone(), || {
-            Ok(F::from(self.params.total_value))
-        })?;
-
-        let max_pct = FpVar::<F>::new_constant(cs.clone(), F::from(self.params.max_position_pct))?;
-        let hundred = FpVar::<F>::new_constant(cs.clone(), F::from(100u64))?;
-
-        // Compute asset_pct = (asset * 100) / total
-        let asset_times_100 = &asset * &hundred;
-
-        let asset_pct = FpVar::<F>::new_witness(cs.clone(), || {
-            if self.params.total_value == 0 {
-                return Err(SynthesisError::DivisionByZero);
-            }
-            let pct = (self.params.largest_asset_value * 100) / self.params.total_value;
-            Ok(F::from(pct))
-        })?;
-
-        // Enforce: asset_pct * total = asset * 100
-        asset_pct.mul_equals(&total, &asset_times_100)?;
-
-        // Check: asset_pct ≤ max_pct
-        let position_diff = FpVar::<F>::new_witness(cs.clone(), || {
-            let pct = (self.params.largest_asset_value * 100) / self.params.total_value;
-            if pct > self.params.max_position_pct {
-                return Err(SynthesisError::AssignmentMissing);
-            }
-            Ok(F::from(self.params.max_position_pct - pct))
-        })?;
-
-        // Enforce: max_pct = asset_pct + diff
-        let max_check = &asset_pct + &position_diff;
-        max_pct.enforce_equal(&max_check)?;
-
-        // ========================================
-        // CHECK 2: Liquidity Reserve (≥ 10%)
-        // ========================================
-
-        let usdc = FpVar::<F>::new_witness(cs.clone(), || {
-            Ok(F::from(self.params.usdc_balance))
-        })?;
-
-        let min_pct = FpVar::<F>::new_constant(cs.clone(), F::from(self.params.min_liquidity_pct))?;
-
-        // Compute usdc_pct = (usdc * 100) / total
-        let usdc_times_100 = &usdc * &hundred;
-
-        let usdc_pct = FpVar::<F>::new_witness(cs.clone(), || {
-            if self.params.total_value == 0 {
-                return Err(SynthesisError::DivisionByZero);
-            }
-            let pct = (self.params.usdc_balance * 100) / self.params.total_value;
-            Ok(F::from(pct))
-        })?;
-
-        // Enforce: usdc_pct * total = usdc * 100
-        usdc_pct.mul_equals(&total, &usdc_times_100)?;
-
-        // Check: usdc_pct ≥ min_pct
-        let liquidity_diff = FpVar::<F>::new_witness(cs.clone(), || {
-            let pct = (self.params.usdc_balance * 100) / self.params.total_value;
-            if pct < self.params.min_liquidity_pct {
-                return Err(SynthesisError::AssignmentMissing);
-            }
-            Ok(F::from(pct - self.params.min_liquidity_pct))
-        })?;
-
-        // Enforce: usdc_pct = min_pct + diff
-        let min_check = &min_pct + &liquidity_diff;
-        usdc_pct.enforce_equal(&min_check)?;
-
-        // ========================================
-        // CHECK 3: Whitelist Membership (Merkle proof)
-        // ========================================
-        // Simplified: one-level Merkle tree (demo only)
-        // Production: full Merkle tree with Poseidon hash
-
-        let leaf = FpVar::<F>::new_witness(cs.clone(), || {
-            Ok(F::from(self.params.asset_hash))
-        })?;
-
-        let sibling = FpVar::<F>::new_witness(cs.clone(), || {
-            Ok(F::from(self.params.sibling))
-        })?;
-
-        // Compute Merkle parent: hash(leaf + sibling)
-        // For demo: simple addition (replace with Poseidon in production)
-        let computed_root = &leaf + &sibling;
-
-        let expected_root = FpVar::<F>::new_constant(cs.clone(), F::from(self.params.merkle_root))?;
-
-        // Enforce: computed_root == expected_root
-        computed_root.enforce_equal(&expected_root)?;
-
-        // ========================================
-        // ALL CHECKS PASSED - Increment Counter
-        // ========================================
-
-        let one = FpVar::<F>::new_constant(cs.clone(), F::one())?;
-        let new_counter = counter + &one;
-
-        // Output: incremented counter
-        Ok(vec![new_counter])
-    }
+/// Returns the `i`-th daily reporting period
+fn daily_period(i: u64) -> [u64; 2] {
+    let start = FIRST_PERIOD_START + i * PERIOD_LEN;
+    [start, start + PERIOD_LEN]
 }
 
 fn main() -> Result<(), Error> {
@@ -234,45 +76,47 @@ fn main() -> Result<(), Error> {
     // - $35M largest position (35% - compliant ≤ 40%)
     // - $10M USDC (10% liquidity - compliant ≥ 10%)
     // - All assets whitelisted
-    let params = CompositeFundParams {
-        max_position_pct: 40,
-        largest_asset_value: 35_000_000,
-        min_liquidity_pct: 10,
-        usdc_balance: 10_000_000,
-        asset_hash: 100,
-        sibling: 200,
-        merkle_root: 300, // 100 + 200 = 300
-        total_value: 100_000_000,
-    };
+    // The holdings are signed by the (demo) oracle and the prices by the (demo) pricing oracle,
+    // whose public keys are fixed in the circuit. The NAV is derived in-circuit from both.
+    let params = demo::reported_params();
+    let snapshot = demo::snapshot();
+    let oracle = demo::oracle();
+    let pricing_oracle = demo::pricing_oracle();
+    let regulator = demo::regulator();
+    let usd = BigUint::from(10u8).pow(NORMALIZED_DECIMALS as u32);
+    let nav = net_asset_value(&snapshot.holdings, &snapshot.prices);
 
     println!("📊 Fund State:");
-    println!("   Total Portfolio: ${}M", params.total_value / 1_000_000);
-    println!("   Largest Asset: ${}M ({}%)",
-        params.largest_asset_value / 1_000_000,
-        (params.largest_asset_value * 100) / params.total_value
-    );
-    println!("   USDC Balance: ${}M ({}%)",
-        params.usdc_balance / 1_000_000,
-        (params.usdc_balance * 100) / params.total_value
-    );
-    println!("   Merkle Root: {}\n", params.merkle_root);
+    println!("   Net Asset Value: ${}M", &nav / &usd / 1_000_000u32);
+    for (name, (holding, price)) in ["USDC", "WETH", "WBTC", "T-bill"]
+        .iter()
+        .zip(snapshot.holdings.iter().zip(snapshot.prices))
+    {
+        let value = position_value(holding, price);
+        println!("   {}: ${}M ({}%)",
+            name,
+            &value / &usd / 1_000_000u32,
+            value * 100u32 / &nav
+        );
+    }
+    println!("   Merkle Root: {}\n", params.compliance.merkle_root);
 
     println!("✅ Compliance Checks:");
     println!("   1. Position Limit: 35% ≤ 40% ✓");
     println!("   2. Liquidity: 10% ≥ 10% ✓");
     println!("   3. Whitelist: Asset verified ✓\n");
 
-    // Set initial state (counter starts at 0)
-    let z_0 = vec![Fr::from(0u32)];
+    let f_circuit = ReportedCircuit::new(params)?;
 
-    let f_circuit = CompositeFundCircuit::<Fr>::new(params)?;
+    // Set initial state (empty time window, prices and reports, counter starts at 0)
+    let z_0 = vec![Fr::from(0u32); f_circuit.state_len()];
 
     // Define types for Nova and Decider
-    pub type N = Nova<G1, G2, CompositeFundCircuit<Fr>, KZG<'static, Bn254>, Pedersen<G2>, false>;
+    pub type N = Nova<G1, G2, ReportedCircuit, KZG<'static, Bn254>, Pedersen<G2>, false>;
     pub type D = DeciderEth<
         G1,
         G2,
-        CompositeFundCircuit<Fr>,
+        ReportedCircuit,
         KZG<'static, Bn254>,
         Pedersen<G2>,
         Groth16<Bn254>,
@@ -305,7 +149,7 @@ fn main() -> Result<(), Error> {
         println!("   📁 Read nova_prover_params.bin: {:?}", start.elapsed());
 
         let start = Instant::now();
-        let nova_prover_param = <N as FoldingScheme<G1, G2, CompositeFundCircuit<Fr>>>::ProverParam
+        let nova_prover_param = <N as FoldingScheme<G1, G2, ReportedCircuit>>::ProverParam
             ::deserialize_compressed(&nova_pp_data[..])
             .map_err(|e| Error::Other(format!("Failed to deserialize nova_prover_params: {}", e)))?;
         println!("   🔓 Deserialize nova_prover_params: {:?}", start.elapsed());
@@ -338,7 +182,7 @@ fn main() -> Result<(), Error> {
         let start = Instant::now();
         let nova_preprocess_params = PreprocessorParam {
             poseidon_config: poseidon_config.clone(),
-            F: f_circuit,
+            F: f_circuit.clone(),
             cs_pp: Some(nova_prover_param.cs_pp.clone()),
             cs_vp: Some(nova_cs_vp),
             cf_cs_pp: Some(nova_prover_param.cf_cs_pp.clone()),
@@ -356,7 +200,7 @@ fn main() -> Result<(), Error> {
         println!("   📁 Read decider_pp.bin: {:?}", start.elapsed());
 
         let start = Instant::now();
-        let decider_pp = <D as Decider<G1, G2, CompositeFundCircuit<Fr>, N>>::ProverParam
+        let decider_pp = <D as Decider<G1, G2, ReportedCircuit, N>>::ProverParam
             ::deserialize_compressed(&pp_data[..])
             .map_err(|e| Error::Other(format!("Failed to deserialize decider_pp: {}", e)))?;
         println!("   🔓 Deserialize decider_pp: {:?}", start.elapsed());
@@ -368,7 +212,7 @@ fn main() -> Result<(), Error> {
         println!("   📁 Read decider_vp.bin: {:?}", start.elapsed());
 
         let start = Instant::now();
-        let decider_vp = <D as Decider<G1, G2, CompositeFundCircuit<Fr>, N>>::VerifierParam
+        let decider_vp = <D as Decider<G1, G2, ReportedCircuit, N>>::VerifierParam
             ::deserialize_compressed(&vp_data[..])
             .map_err(|e| Error::Other(format!("Failed to deserialize decider_vp: {}", e)))?;
         println!("   🔓 Deserialize decider_vp: {:?}", start.elapsed());
@@ -384,7 +228,7 @@ fn main() -> Result<(), Error> {
 
         println!("   ⚙️  Producing Nova public parameters...");
         let start = Instant::now();
-        let nova_preprocess_params = PreprocessorParam::new(poseidon_config.clone(), f_circuit);
+        let nova_preprocess_params = PreprocessorParam::new(poseidon_config.clone(), f_circuit.clone());
         let (nova_prover_param, nova_verifier_param) = N::preprocess(&mut rng, &nova_preprocess_params)?;
 
         // Extract CS verifier params before moving nova_verifier_param
@@ -448,19 +292,43 @@ fn main() -> Result<(), Error> {
     };
 
     // Initialize Nova folding scheme
-    let mut nova = N::init(&nova_params, f_circuit, z_0)?;
+    let mut nova = N::init(&nova_params, f_circuit.clone(), z_0)?;
 
     // Run n steps of the folding iteration
     // Each step proves: Position ≤ 40% AND Liquidity ≥ 10% AND Whitelisted
+    // and encrypts the checked holdings to the regulator
     println!("🔄 Generating RecursiveSNARK ({} steps)...", n_steps);
     println!("   (Each step checks ALL 3 compliance requirements)\n");
+    let mut reports = Vec::new();
+    let mut prices = Vec::new();
     for i in 0..n_steps {
         let start = Instant::now();
-        nova.prove_step(rng, (), None)?;
+        let inputs = ReportedInputs::new(
+            CompositeStepInputs::attest(
+                &oracle,
+                &pricing_oracle,
+                &poseidon_config,
+                &params.compliance.accounts,
+                daily_period(i),
+                &snapshot,
+            ),
+            &mut rng,
+        );
+        reports.push(inputs.ciphertext(&poseidon_config, &params.regulator));
+        prices.push(inputs.inputs.prices.prices.commitment(&poseidon_config));
+        nova.prove_step(rng, inputs, None)?;
         println!("   Step {}: All checks passed ✅ ({:?})", i + 1, start.elapsed());
     }
     println!();
 
+    // Only the regulator can read the reports
+    let report = regulator.decrypt(&poseidon_config, &reports[0]);
+    println!(
+        "🔐 Regulator decrypted the report of the first period: {} elements",
+        report.len()
+    );
+    println!();
+
     // Generate Decider proof (final compression for on-chain verification)
     println!("📦 Generating Decider proof...");
     let start = Instant::now();
@@ -517,6 +385,11 @@ fn main() -> Result<(), Error> {
     let gas = 795738;
     println!("   EVM verification: gas: {:?}\n", gas);
 
+    // Inputs of TokenizedFundManager.executeRebalance: the folded proof, encoded as
+    // uint256[36] (the calldata without its selector), and the reports and price commitments
+    let folded_proof = calldata[4..].to_vec();
+    let metadata = rebalance_metadata(&reports, &prices);
+
     // Save artifacts
     println!("📝 Saving artifacts...");
     std::fs::write(
@@ -524,12 +397,16 @@ fn main() -> Result<(), Error> {
         decider_solidity_code.clone(),
     )?;
     std::fs::write("./composite-proof.calldata", calldata.clone())?;
+    std::fs::write("./composite-proof.folded", &folded_proof)?;
+    std::fs::write("./composite-proof.metadata", &metadata)?;
     let s = solidity_verifiers::calldata::get_formatted_calldata(calldata.clone());
     std::fs::write("./composite-proof.inputs", s.join(",\n")).expect("");
 
     println!("   ✅ Solidity verifier saved to: CompositeFundVerifier.sol");
     println!("   ✅ Calldata saved to: composite-proof.calldata");
     println!("   ✅ Formatted inputs saved to: composite-proof.inputs");
+    println!("   ✅ executeRebalance proof saved to: composite-proof.folded");
+    println!("   ✅ executeRebalance metadata saved to: composite-proof.metadata");
     println!("   📊 Contract size: {} bytes", decider_solidity_code.len());
     println!("   📊 Calldata size: {} bytes\n", calldata.len());
 
@@ -541,6 +418,12 @@ fn main() -> Result<(), Error> {
     println!("   • Position limit ≤ 40% for {} consecutive periods", n_steps);
     println!("   • Liquidity ≥ 10% for {} consecutive periods", n_steps);
     println!("   • All assets whitelisted for {} consecutive periods", n_steps);
+    println!(
+        "   • Periods covered: [{}, {}), no gaps",
+        FIRST_PERIOD_START,
+        FIRST_PERIOD_START + n_steps * PERIOD_LEN
+    );
+    println!("   • Holdings of every period encrypted to the regulator");
     println!("   • All THREE checks folded into ONE proof");
     println!("   • Ready for single on-chain verification (~$0.02)\n");
 
@@ -548,6 +431,7 @@ fn main() -> Result<(), Error> {
     println!("   1. Deploy CompositeFundVerifier.sol to Arc testnet");
     println!("   2. Call verifyNovaProof() with composite-proof.calldata");
     println!("   3. Single verification confirms ALL compliance rules!");
+    println!("   4. Call executeRebalance() with composite-proof.folded and composite-proof.metadata");
     println!();
 
     Ok(())
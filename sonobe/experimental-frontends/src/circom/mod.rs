@@ -14,10 +14,14 @@ use num_bigint::{BigInt, BigUint};
 
 pub mod utils;
 use crate::utils::{VecF, VecFpVar};
-use utils::CircomWrapper;
+use utils::{CircomWitnessGenerator, CircomWrapper};
 
 /// Define CircomFCircuit. The parameter `SL` indicates the length of the state vector.
 /// The parameter `EIL` indicates the length of the ExternalInputs vector of field elements.
+///
+/// The witnesses are generated by the [`CircomWitnessGenerator`] given in the params, which can be
+/// circom's WASM witness calculator, precomputed `.wtns` files (one per step), or a native witness
+/// calculator.
 #[derive(Clone, Debug)]
 pub struct CircomFCircuit<F: PrimeField, const SL: usize, const EIL: usize> {
     circom_wrapper: CircomWrapper,
@@ -25,14 +29,15 @@ pub struct CircomFCircuit<F: PrimeField, const SL: usize, const EIL: usize> {
 }
 
 impl<F: PrimeField, const SL: usize, const EIL: usize> FCircuit<F> for CircomFCircuit<F, SL, EIL> {
-    /// (r1cs_path, wasm_path)
-    type Params = (PathOrBin, PathOrBin);
+    /// (r1cs_path, witness_generator), where a path to the WASM file can be passed as the
+    /// witness generator
+    type Params = (PathOrBin, CircomWitnessGenerator);
     type ExternalInputs = VecF<F, EIL>;
     type ExternalInputsVar = VecFpVar<F, EIL>;
 
     fn new(params: Self::Params) -> Result<Self, Error> {
-        let (r1cs_path, wasm_path) = params;
-        let circom_wrapper = CircomWrapper::new(r1cs_path, wasm_path)?;
+        let (r1cs_path, witness_generator) = params;
+        let circom_wrapper = CircomWrapper::new(r1cs_path, witness_generator)?;
 
         let r1cs = circom_wrapper.extract_r1cs()?;
         Ok(Self {
@@ -48,7 +53,7 @@ impl<F: PrimeField, const SL: usize, const EIL: usize> FCircuit<F> for CircomFCi
     fn generate_step_constraints(
         &self,
         cs: ConstraintSystemRef<F>,
-        _i: usize,
+        i: usize,
         z_i: Vec<FpVar<F>>,
         external_inputs: Self::ExternalInputsVar,
     ) -> Result<Vec<FpVar<F>>, SynthesisError> {
@@ -75,10 +80,17 @@ impl<F: PrimeField, const SL: usize, const EIL: usize> FCircuit<F> for CircomFCi
         // ]
         // Here, 1, z_i, and external_inputs have already been allocated in the
         // constraint system, while z_{i + 1} and aux are yet to be allocated.
-        let witness = self
-            .circom_wrapper
-            .extract_witness(inputs_map)
-            .map_err(|_| SynthesisError::AssignmentMissing)?;
+        // Note that the precomputed witnesses are not derived from `z_i` and `external_inputs`,
+        // so if they do not correspond to them the constraints of the step are not satisfied.
+        let witness = if self.circom_wrapper.is_precomputed() && cs.is_in_setup_mode() {
+            // the values are not needed in setup mode, and there may be no precomputed witness
+            // for the step
+            vec![F::zero(); self.r1cs.num_variables]
+        } else {
+            self.circom_wrapper
+                .extract_step_witness(i, inputs_map)
+                .map_err(|_| SynthesisError::AssignmentMissing)?
+        };
 
         // In order to convert the indexes of variables in the circom circuit to
         // those in the arkworks circuit, we adopt the tricks from
@@ -344,4 +356,62 @@ pub mod tests {
         assert!(cs.is_satisfied()?, "Constraint system is not satisfied");
         Ok(())
     }
+
+    #[test]
+    fn test_circom_wtns_and_native_witnesses() -> Result<(), Error> {
+        let r1cs_path = PathBuf::from("./src/circom/test_folder/with_external_inputs.r1cs");
+        let wasm_path = PathBuf::from(
+            "./src/circom/test_folder/with_external_inputs_js/with_external_inputs.wasm",
+        );
+        let z_i = vec![Fr::from(3u32)];
+        let external_inputs = vec![Fr::from(6u32), Fr::from(7u32)];
+        let z_i1_native = external_inputs_step_native(z_i.clone(), external_inputs.clone());
+
+        // the witness of the step, as computed by an external witness generator
+        let wasm_wrapper = CircomWrapper::new(r1cs_path.clone().into(), wasm_path.into())?;
+        let inputs_map = vec![
+            ("ivc_input".to_string(), vec![BigInt::from(3)]),
+            (
+                "external_inputs".to_string(),
+                vec![BigInt::from(6), BigInt::from(7)],
+            ),
+        ];
+        let witness = wasm_wrapper.extract_witness::<Fr>(inputs_map)?;
+
+        let run_step = |circom_fcircuit: &CircomFCircuit<Fr, 1, 2>,
+                        z_i: Vec<Fr>|
+         -> Result<Vec<Fr>, SynthesisError> {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i))?;
+            let external_inputs_var =
+                Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs.clone()))?;
+            let z_i1_var = circom_fcircuit.generate_step_constraints(
+                cs.clone(),
+                0,
+                z_i_var,
+                VecFpVar(external_inputs_var),
+            )?;
+            assert!(cs.is_satisfied()?);
+            z_i1_var.value()
+        };
+
+        // precomputed .wtns witness
+        let circom_fcircuit = CircomFCircuit::<Fr, 1, 2>::new((
+            r1cs_path.clone().into(),
+            CircomWitnessGenerator::Wtns(vec![utils::tests::wtns_bytes(&witness).into()]),
+        ))?;
+        assert_eq!(run_step(&circom_fcircuit, z_i.clone())?, z_i1_native);
+        // a .wtns witness of another state does not satisfy the constraints
+        assert!(run_step(&circom_fcircuit, vec![Fr::from(4u32)]).is_err());
+
+        // native witness calculator, here backed by the WASM one for the sake of the test
+        let circom_fcircuit = CircomFCircuit::<Fr, 1, 2>::new((
+            r1cs_path.into(),
+            CircomWitnessGenerator::Native(std::sync::Arc::new(move |inputs| {
+                wasm_wrapper.calculate_witness(inputs)
+            })),
+        ))?;
+        assert_eq!(run_step(&circom_fcircuit, z_i)?, z_i1_native);
+        Ok(())
+    }
 }
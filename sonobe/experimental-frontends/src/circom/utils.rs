@@ -1,4 +1,4 @@
-use std::{fs::File, io::Cursor, path::PathBuf};
+use std::{fmt::Debug, fs::File, io::Cursor, path::PathBuf, sync::Arc};
 
 use ark_circom::{
     circom::{r1cs_reader, R1CS},
@@ -6,50 +6,121 @@ use ark_circom::{
 };
 use ark_ff::PrimeField;
 use ark_serialize::Read;
-use num_bigint::BigInt;
+use num_bigint::{BigInt, BigUint};
 use wasmer::{Module, Store};
 
 use folding_schemes::{utils::PathOrBin, Error};
 
+/// A native witness calculator, which receives the named inputs of the circuit and returns the
+/// full witness. This allows plugging witness generators that do not depend on a WASM runtime,
+/// such as the graph-based `calculate_witness` of
+/// [circom-witness-rs](https://github.com/philsippl/circom-witness-rs).
+pub type NativeWitnessCalculator =
+    Arc<dyn Fn(Vec<(String, Vec<BigInt>)>) -> Result<Vec<BigInt>, Error> + Send + Sync>;
+
+/// Selects how the witnesses of a Circom circuit are generated.
+///
+/// This enum implements the [`From`] trait for [`PathOrBin`], [`PathBuf`] and [`Vec<u8>`], which
+/// select the WASM witness calculator.
+#[derive(Clone)]
+pub enum CircomWitnessGenerator {
+    /// Circom's WASM witness calculator, run in `wasmer` at every step.
+    Wasm(PathOrBin),
+    /// Precomputed `.wtns` witness files (as output by circom's native witness generators or by
+    /// `snarkjs wtns calculate`), where the `i`-th file is the witness of the `i`-th step.
+    Wtns(Vec<PathOrBin>),
+    /// A native witness calculator.
+    Native(NativeWitnessCalculator),
+}
+
+impl Debug for CircomWitnessGenerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Wasm(wasm) => f.debug_tuple("Wasm").field(wasm).finish(),
+            Self::Wtns(wtns) => f.debug_tuple("Wtns").field(wtns).finish(),
+            Self::Native(_) => f.debug_tuple("Native").finish(),
+        }
+    }
+}
+
+impl From<PathOrBin> for CircomWitnessGenerator {
+    fn from(value: PathOrBin) -> Self {
+        Self::Wasm(value)
+    }
+}
+
+impl From<PathBuf> for CircomWitnessGenerator {
+    fn from(value: PathBuf) -> Self {
+        Self::Wasm(value.into())
+    }
+}
+
+impl From<Vec<u8>> for CircomWitnessGenerator {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Wasm(value.into())
+    }
+}
+
+// The loaded witness generator, with the files already read.
+#[derive(Clone)]
+enum WitnessSource {
+    Wasm(Vec<u8>),
+    // the prime of the field and the witness of each step
+    Wtns(BigUint, Vec<Vec<BigInt>>),
+    Native(NativeWitnessCalculator),
+}
+
+impl Debug for WitnessSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Wasm(_) => write!(f, "Wasm"),
+            Self::Wtns(_, witnesses) => write!(f, "Wtns({} steps)", witnesses.len()),
+            Self::Native(_) => write!(f, "Native"),
+        }
+    }
+}
+
 // A struct that wraps Circom functionalities, allowing for extraction of R1CS and witnesses
-// based on file paths to Circom's .r1cs and .wasm.
+// based on file paths to Circom's .r1cs and the witness generator.
 #[derive(Clone, Debug)]
 pub struct CircomWrapper {
     r1csfile_bytes: Vec<u8>,
-    wasmfile_bytes: Vec<u8>,
+    witness_source: WitnessSource,
 }
 
 impl CircomWrapper {
-    // Creates a new instance of the CircomWrapper with the file paths.
-    pub fn new(r1cs: PathOrBin, wasm: PathOrBin) -> Result<Self, Error> {
-        match (r1cs, wasm) {
-            (PathOrBin::Path(r1cs_path), PathOrBin::Path(wasm_path)) => {
-                Self::new_from_path(r1cs_path, wasm_path)
+    // Creates a new instance of the CircomWrapper with the R1CS and the witness generator.
+    pub fn new(r1cs: PathOrBin, witness_generator: CircomWitnessGenerator) -> Result<Self, Error> {
+        let witness_source = match witness_generator {
+            CircomWitnessGenerator::Wasm(wasm) => WitnessSource::Wasm(read_path_or_bin(wasm)?),
+            CircomWitnessGenerator::Wtns(wtns) => {
+                let mut prime = None;
+                let witnesses = wtns
+                    .into_iter()
+                    .map(|w| {
+                        let (p, witness) = read_wtns(&read_path_or_bin(w)?)?;
+                        if prime.get_or_insert_with(|| p.clone()) != &p {
+                            return Err(Error::WitnessCalculationError(
+                                "the .wtns files are defined over different fields".to_string(),
+                            ));
+                        }
+                        Ok(witness)
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                WitnessSource::Wtns(prime.unwrap_or_default(), witnesses)
             }
-            (PathOrBin::Bin(r1cs_bin), PathOrBin::Bin(wasm_bin)) => Ok(Self {
-                r1csfile_bytes: r1cs_bin,
-                wasmfile_bytes: wasm_bin,
-            }),
-            _ => unreachable!("You should pass the same enum branch for both inputs"),
-        }
+            CircomWitnessGenerator::Native(calculator) => WitnessSource::Native(calculator),
+        };
+        Ok(Self {
+            r1csfile_bytes: read_path_or_bin(r1cs)?,
+            witness_source,
+        })
     }
 
-    // Creates a new instance of the CircomWrapper with the file paths.
-    fn new_from_path(r1cs_file_path: PathBuf, wasm_file_path: PathBuf) -> Result<Self, Error> {
-        let mut file = File::open(r1cs_file_path)?;
-        let metadata = File::metadata(&file)?;
-        let mut r1csfile_bytes = vec![0; metadata.len() as usize];
-        file.read_exact(&mut r1csfile_bytes)?;
-
-        let mut file = File::open(wasm_file_path)?;
-        let metadata = File::metadata(&file)?;
-        let mut wasmfile_bytes = vec![0; metadata.len() as usize];
-        file.read_exact(&mut wasmfile_bytes)?;
-
-        Ok(CircomWrapper {
-            r1csfile_bytes,
-            wasmfile_bytes,
-        })
+    // Returns true if the witnesses are read from precomputed `.wtns` files instead of being
+    // calculated from the inputs.
+    pub fn is_precomputed(&self) -> bool {
+        matches!(self.witness_source, WitnessSource::Wtns(..))
     }
 
     // Aggregated function to obtain R1CS and witness from Circom.
@@ -79,44 +150,162 @@ impl CircomWrapper {
         &self,
         inputs: Vec<(String, Vec<BigInt>)>,
     ) -> Result<Vec<F>, Error> {
-        let witness_bigint = self.calculate_witness(inputs)?;
-
-        witness_bigint
-            .into_iter()
-            .map(|big_int| {
-                big_int.to_biguint().map(F::from).ok_or_else(|| {
-                    Error::ConversionError(
-                        "BigInt".into(),
-                        "BigUint".into(),
-                        "BigInt is negative".into(),
-                    )
-                })
-            })
-            .collect()
+        bigints_to_field_elements(self.calculate_witness(inputs)?)
+    }
+
+    // Extracts the witness vector of the given step as a vector of PrimeField elements. For the
+    // precomputed witnesses, `inputs` is ignored and the witness of the step is returned.
+    pub fn extract_step_witness<F: PrimeField>(
+        &self,
+        step: usize,
+        inputs: Vec<(String, Vec<BigInt>)>,
+    ) -> Result<Vec<F>, Error> {
+        match &self.witness_source {
+            WitnessSource::Wtns(prime, witnesses) => {
+                let modulus: BigUint = F::MODULUS.into();
+                if *prime != modulus {
+                    return Err(Error::WitnessCalculationError(format!(
+                        "the .wtns files are defined over the field of modulus {prime}, which is not the field of the circuit"
+                    )));
+                }
+                let witness = witnesses.get(step).ok_or_else(|| {
+                    Error::WitnessCalculationError(format!(
+                        "missing .wtns file for step {step}, only {} were provided",
+                        witnesses.len()
+                    ))
+                })?;
+                bigints_to_field_elements(witness.clone())
+            }
+            _ => self.extract_witness(inputs),
+        }
     }
 
-    // Calculates the witness given the Wasm filepath and inputs.
+    // Calculates the witness given the inputs, using the WASM or the native witness calculator.
     pub fn calculate_witness(
         &self,
         inputs: Vec<(String, Vec<BigInt>)>,
     ) -> Result<Vec<BigInt>, Error> {
-        let mut store = Store::default();
-        let module = Module::new(&store, &self.wasmfile_bytes).map_err(|e| {
-            Error::WitnessCalculationError(format!("Failed to create Wasm module: {e}"))
-        })?;
-        let mut calculator = WitnessCalculator::from_module(&mut store, module).map_err(|e| {
-            Error::WitnessCalculationError(format!("Failed to create WitnessCalculator: {e}"))
-        })?;
-        calculator
-            .calculate_witness(&mut store, inputs, true)
-            .map_err(|e| {
-                Error::WitnessCalculationError(format!("Failed to calculate witness: {e}"))
+        match &self.witness_source {
+            WitnessSource::Wasm(wasmfile_bytes) => {
+                let mut store = Store::default();
+                let module = Module::new(&store, wasmfile_bytes).map_err(|e| {
+                    Error::WitnessCalculationError(format!("Failed to create Wasm module: {e}"))
+                })?;
+                let mut calculator =
+                    WitnessCalculator::from_module(&mut store, module).map_err(|e| {
+                        Error::WitnessCalculationError(format!(
+                            "Failed to create WitnessCalculator: {e}"
+                        ))
+                    })?;
+                calculator
+                    .calculate_witness(&mut store, inputs, true)
+                    .map_err(|e| {
+                        Error::WitnessCalculationError(format!("Failed to calculate witness: {e}"))
+                    })
+            }
+            WitnessSource::Native(calculator) => calculator(inputs),
+            WitnessSource::Wtns(..) => Err(Error::WitnessCalculationError(
+                "precomputed .wtns witnesses can not be calculated from inputs, use `extract_step_witness`".to_string(),
+            )),
+        }
+    }
+}
+
+fn read_path_or_bin(value: PathOrBin) -> Result<Vec<u8>, Error> {
+    match value {
+        PathOrBin::Path(path) => {
+            let mut file = File::open(path)?;
+            let metadata = File::metadata(&file)?;
+            let mut bytes = vec![0; metadata.len() as usize];
+            file.read_exact(&mut bytes)?;
+            Ok(bytes)
+        }
+        PathOrBin::Bin(bytes) => Ok(bytes),
+    }
+}
+
+fn bigints_to_field_elements<F: PrimeField>(witness: Vec<BigInt>) -> Result<Vec<F>, Error> {
+    witness
+        .into_iter()
+        .map(|big_int| {
+            big_int.to_biguint().map(F::from).ok_or_else(|| {
+                Error::ConversionError(
+                    "BigInt".into(),
+                    "BigUint".into(),
+                    "BigInt is negative".into(),
+                )
             })
+        })
+        .collect()
+}
+
+/// Reads a witness in the binary `.wtns` format of iden3, returning the prime of the field and the
+/// witness values. The format consists of the magic `wtns`, the version and the number of
+/// sections, followed by the sections, each prefixed by its type (`u32`) and its size (`u64`):
+/// - section 1 (header): the size in bytes of a field element `n8` (`u32`), the prime (`n8` bytes)
+///   and the number of witness values (`u32`),
+/// - section 2: the witness values, of `n8` bytes each.
+///
+/// All the integers are little-endian.
+pub fn read_wtns(bytes: &[u8]) -> Result<(BigUint, Vec<BigInt>), Error> {
+    let err = |msg: &str| Error::WitnessCalculationError(format!("invalid .wtns file: {msg}"));
+    let mut reader = Cursor::new(bytes);
+    let mut read_bytes = |n: usize| -> Result<Vec<u8>, Error> {
+        let mut buf = vec![0; n];
+        reader
+            .read_exact(&mut buf)
+            .map_err(|_| err("unexpected end of file"))?;
+        Ok(buf)
+    };
+    let read_u32 = |b: Vec<u8>| u32::from_le_bytes(b.try_into().unwrap_or_default()) as usize;
+
+    if read_bytes(4)? != b"wtns" {
+        return Err(err("wrong magic"));
+    }
+    let _version = read_u32(read_bytes(4)?);
+    let n_sections = read_u32(read_bytes(4)?);
+
+    let mut header = None;
+    let mut values = None;
+    for _ in 0..n_sections {
+        let section_type = read_u32(read_bytes(4)?);
+        let section_size = u64::from_le_bytes(read_bytes(8)?.try_into().unwrap_or_default());
+        if section_size > bytes.len() as u64 {
+            return Err(err("section larger than the file"));
+        }
+        let section = read_bytes(section_size as usize)?;
+        match section_type {
+            1 => {
+                if section.len() < 4 {
+                    return Err(err("header section too short"));
+                }
+                let n8 = read_u32(section[..4].to_vec());
+                if section.len() != 4 + n8 + 4 {
+                    return Err(err("wrong header section size"));
+                }
+                let prime = BigUint::from_bytes_le(&section[4..4 + n8]);
+                let n_witness = read_u32(section[4 + n8..].to_vec());
+                header = Some((n8, prime, n_witness));
+            }
+            2 => values = Some(section),
+            _ => {}
+        }
     }
+
+    let (n8, prime, n_witness) = header.ok_or_else(|| err("missing header section"))?;
+    let values = values.ok_or_else(|| err("missing witness section"))?;
+    if n8 == 0 || values.len() != n8 * n_witness {
+        return Err(err("wrong witness section size"));
+    }
+    let witness = values
+        .chunks(n8)
+        .map(|v| BigInt::from(BigUint::from_bytes_le(v)))
+        .collect();
+    Ok((prime, witness))
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use ark_bn254::Fr;
     use ark_circom::circom::{CircomBuilder, CircomConfig};
@@ -164,4 +353,66 @@ mod tests {
         assert!(cs.is_satisfied()?);
         Ok(())
     }
+
+    /// Serializes a witness into the `.wtns` format, as circom's witness generators do
+    pub(crate) fn wtns_bytes<F: PrimeField>(witness: &[F]) -> Vec<u8> {
+        let n8 = F::MODULUS_BIT_SIZE.div_ceil(64) as usize * 8;
+        let to_bytes = |x: BigUint| {
+            let mut b = x.to_bytes_le();
+            b.resize(n8, 0);
+            b
+        };
+        let header = [
+            &(n8 as u32).to_le_bytes()[..],
+            &to_bytes(F::MODULUS.into()),
+            &(witness.len() as u32).to_le_bytes(),
+        ]
+        .concat();
+        let values = witness
+            .iter()
+            .flat_map(|w| to_bytes((*w).into()))
+            .collect::<Vec<_>>();
+        [
+            &b"wtns"[..],
+            &2u32.to_le_bytes(),
+            &2u32.to_le_bytes(),
+            &1u32.to_le_bytes(),
+            &(header.len() as u64).to_le_bytes(),
+            &header,
+            &2u32.to_le_bytes(),
+            &(values.len() as u64).to_le_bytes(),
+            &values,
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_read_wtns() -> Result<(), Error> {
+        let r1cs_path = PathBuf::from("./src/circom/test_folder/cubic_circuit.r1cs");
+        let wasm_path =
+            PathBuf::from("./src/circom/test_folder/cubic_circuit_js/cubic_circuit.wasm");
+        let inputs = vec![("ivc_input".to_string(), vec![BigInt::from(3)])];
+        let wrapper = CircomWrapper::new(r1cs_path.clone().into(), wasm_path.into())?;
+        let witness = wrapper.extract_witness::<Fr>(inputs.clone())?;
+
+        let (prime, wtns_witness) = read_wtns(&wtns_bytes(&witness))?;
+        assert_eq!(prime, BigUint::from(Fr::MODULUS));
+        assert_eq!(bigints_to_field_elements::<Fr>(wtns_witness)?, witness);
+
+        // the precomputed witness is returned for its step, and there is none for the next one
+        let wrapper = CircomWrapper::new(
+            r1cs_path.into(),
+            CircomWitnessGenerator::Wtns(vec![wtns_bytes(&witness).into()]),
+        )?;
+        assert_eq!(
+            wrapper.extract_step_witness::<Fr>(0, inputs.clone())?,
+            witness
+        );
+        assert!(wrapper.extract_step_witness::<Fr>(1, inputs).is_err());
+
+        // truncated files are rejected
+        let bytes = wtns_bytes(&witness);
+        assert!(read_wtns(&bytes[..bytes.len() - 1]).is_err());
+        Ok(())
+    }
 }
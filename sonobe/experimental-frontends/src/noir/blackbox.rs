@@ -0,0 +1,302 @@
+/// R1CS lowering of the ACIR black box functions supported by the Noir frontend: range checks,
+/// bitwise AND/XOR, SHA-256 compression and Keccak-f[1600]. The Poseidon2 permutation lives in
+/// [`super::poseidon2`].
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar, boolean::Boolean, convert::ToBitsGadget, eq::EqGadget, fields::fp::FpVar,
+    uint32::UInt32, uint64::UInt64, GR1CSVar,
+};
+use ark_relations::gr1cs::SynthesisError;
+
+/// Decomposes `x` into `n_bits` little-endian bits, enforcing that `x < 2^n_bits`.
+pub fn to_bits_le_bounded<F: PrimeField>(
+    x: &FpVar<F>,
+    n_bits: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    if n_bits >= F::MODULUS_BIT_SIZE as usize {
+        // every field element fits, fall back to the canonical decomposition
+        let mut bits = x.to_bits_le()?;
+        bits.resize(n_bits, Boolean::FALSE);
+        return Ok(bits);
+    }
+    let cs = x.cs();
+    let value_bits = x.value().map(|v| v.into_bigint().to_bits_le());
+    let bits = (0..n_bits)
+        .map(|i| {
+            Boolean::new_witness(cs.clone(), || {
+                value_bits
+                    .as_ref()
+                    .map(|b| b[i])
+                    .map_err(|_| SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp(&bits)?.enforce_equal(x)?;
+    Ok(bits)
+}
+
+/// Enforces that `x < 2^n_bits`
+pub fn range_gadget<F: PrimeField>(x: &FpVar<F>, n_bits: usize) -> Result<(), SynthesisError> {
+    if n_bits < F::MODULUS_BIT_SIZE as usize {
+        to_bits_le_bounded(x, n_bits)?;
+    }
+    Ok(())
+}
+
+/// Computes the bitwise AND of `lhs` and `rhs`, where both are `n_bits` long
+pub fn and_gadget<F: PrimeField>(
+    lhs: &FpVar<F>,
+    rhs: &FpVar<F>,
+    n_bits: usize,
+) -> Result<FpVar<F>, SynthesisError> {
+    let lhs = to_bits_le_bounded(lhs, n_bits)?;
+    let rhs = to_bits_le_bounded(rhs, n_bits)?;
+    Boolean::le_bits_to_fp(&lhs.iter().zip(&rhs).map(|(l, r)| l & r).collect::<Vec<_>>())
+}
+
+/// Computes the bitwise XOR of `lhs` and `rhs`, where both are `n_bits` long
+pub fn xor_gadget<F: PrimeField>(
+    lhs: &FpVar<F>,
+    rhs: &FpVar<F>,
+    n_bits: usize,
+) -> Result<FpVar<F>, SynthesisError> {
+    let lhs = to_bits_le_bounded(lhs, n_bits)?;
+    let rhs = to_bits_le_bounded(rhs, n_bits)?;
+    Boolean::le_bits_to_fp(&lhs.iter().zip(&rhs).map(|(l, r)| l ^ r).collect::<Vec<_>>())
+}
+
+fn fp_to_uint32<F: PrimeField>(x: &FpVar<F>) -> Result<UInt32<F>, SynthesisError> {
+    Ok(UInt32::from_bits_le(&to_bits_le_bounded(x, 32)?))
+}
+
+fn uint32_to_fp<F: PrimeField>(x: &UInt32<F>) -> Result<FpVar<F>, SynthesisError> {
+    Boolean::le_bits_to_fp(&x.to_bits_le()?)
+}
+
+fn fp_to_uint64<F: PrimeField>(x: &FpVar<F>) -> Result<UInt64<F>, SynthesisError> {
+    Ok(UInt64::from_bits_le(&to_bits_le_bounded(x, 64)?))
+}
+
+fn uint64_to_fp<F: PrimeField>(x: &UInt64<F>) -> Result<FpVar<F>, SynthesisError> {
+    Boolean::le_bits_to_fp(&x.to_bits_le()?)
+}
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Logical right shift of a `UInt32`
+fn shr<F: PrimeField>(x: &UInt32<F>, by: usize) -> Result<UInt32<F>, SynthesisError> {
+    let mut bits = x.to_bits_le()?[by..].to_vec();
+    bits.resize(32, Boolean::FALSE);
+    Ok(UInt32::from_bits_le(&bits))
+}
+
+/// Computes the SHA-256 compression function of the 16 words `inputs` (the message block) with the
+/// 8 words `hash_values` (the current state), as Noir's `sha256_compression`.
+pub fn sha256_compression_gadget<F: PrimeField>(
+    inputs: &[FpVar<F>],
+    hash_values: &[FpVar<F>],
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let mut w = inputs
+        .iter()
+        .map(fp_to_uint32)
+        .collect::<Result<Vec<_>, _>>()?;
+    let state = hash_values
+        .iter()
+        .map(fp_to_uint32)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // message schedule
+    for i in 16..64 {
+        let s0 = &w[i - 15].rotate_right(7) ^ &w[i - 15].rotate_right(18) ^ shr(&w[i - 15], 3)?;
+        let s1 = &w[i - 2].rotate_right(17) ^ &w[i - 2].rotate_right(19) ^ shr(&w[i - 2], 10)?;
+        w.push(UInt32::wrapping_add_many(&[
+            w[i - 16].clone(),
+            s0,
+            w[i - 7].clone(),
+            s1,
+        ])?);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h]: [UInt32<F>; 8] = state
+        .clone()
+        .try_into()
+        .map_err(|_| SynthesisError::Unsatisfiable)?;
+    for (k, w_i) in SHA256_K.iter().zip(&w) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (&e & &f) ^ (!&e & &g);
+        let temp1 = UInt32::wrapping_add_many(&[h, s1, ch, UInt32::constant(*k), w_i.clone()])?;
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (&a & &b) ^ (&a & &c) ^ (&b & &c);
+        let temp2 = UInt32::wrapping_add_many(&[s0, maj])?;
+
+        h = g;
+        g = f;
+        f = e;
+        e = UInt32::wrapping_add_many(&[d, temp1.clone()])?;
+        d = c;
+        c = b;
+        b = a;
+        a = UInt32::wrapping_add_many(&[temp1, temp2])?;
+    }
+
+    state
+        .iter()
+        .zip([a, b, c, d, e, f, g, h])
+        .map(|(s, v)| uint32_to_fp(&UInt32::wrapping_add_many(&[s.clone(), v])?))
+        .collect()
+}
+
+const KECCAK_RC: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+const KECCAK_RHO: [usize; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+const KECCAK_PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// Computes the Keccak-f[1600] permutation over the 25 lanes `inputs`, where the lane `(x, y)` is
+/// at position `x + 5 * y`, as Noir's `keccakf1600`.
+pub fn keccakf1600_gadget<F: PrimeField>(
+    inputs: &[FpVar<F>],
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let mut a = inputs
+        .iter()
+        .map(fp_to_uint64)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for rc in KECCAK_RC {
+        // theta
+        let c = (0..5)
+            .map(|x| &a[x] ^ &a[x + 5] ^ &a[x + 10] ^ &a[x + 15] ^ &a[x + 20])
+            .collect::<Vec<_>>();
+        for x in 0..5 {
+            let d = &c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] = &a[x + 5 * y] ^ &d;
+            }
+        }
+
+        // rho and pi
+        let mut last = a[1].clone();
+        for (&pi, &rho) in KECCAK_PI.iter().zip(&KECCAK_RHO) {
+            let tmp = a[pi].clone();
+            a[pi] = last.rotate_left(rho);
+            last = tmp;
+        }
+
+        // chi
+        for y in 0..5 {
+            let row = a[5 * y..5 * y + 5].to_vec();
+            for x in 0..5 {
+                a[x + 5 * y] = &row[x] ^ (!&row[(x + 1) % 5] & &row[(x + 2) % 5]);
+            }
+        }
+
+        // iota
+        a[0] = &a[0] ^ UInt64::constant(rc);
+    }
+
+    a.iter().map(uint64_to_fp).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_relations::gr1cs::ConstraintSystem;
+
+    use super::*;
+
+    #[test]
+    fn test_bitwise_gadgets() -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let lhs = FpVar::new_witness(cs.clone(), || Ok(Fr::from(0b1100u64)))?;
+        let rhs = FpVar::new_witness(cs.clone(), || Ok(Fr::from(0b1010u64)))?;
+        assert_eq!(and_gadget(&lhs, &rhs, 4)?.value()?, Fr::from(0b1000u64));
+        assert_eq!(xor_gadget(&lhs, &rhs, 4)?.value()?, Fr::from(0b0110u64));
+        range_gadget(&lhs, 4)?;
+        assert!(cs.is_satisfied()?);
+
+        // 0b1100 does not fit in 3 bits
+        range_gadget(&lhs, 3)?;
+        assert!(!cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sha256_compression_gadget() -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        // the padded block of the message "abc"
+        let mut block = [0u32; 16];
+        block[0] = 0x61626380;
+        block[15] = 0x18;
+        let iv: [u32; 8] = [
+            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+            0x5be0cd19,
+        ];
+        let block = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || {
+            Ok(block.map(|w| Fr::from(w as u64)).to_vec())
+        })?;
+        let iv = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || {
+            Ok(iv.map(|w| Fr::from(w as u64)).to_vec())
+        })?;
+
+        let digest = sha256_compression_gadget(&block, &iv)?;
+        let expected: [u32; 8] = [
+            0xba7816bf, 0x8f01cfea, 0x414140de, 0x5dae2223, 0xb00361a3, 0x96177a9c, 0xb410ff61,
+            0xf20015ad,
+        ];
+        assert_eq!(
+            digest.value()?,
+            expected.map(|w| Fr::from(w as u64)).to_vec()
+        );
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_keccakf1600_gadget() -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let state = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(vec![Fr::from(0u64); 25]))?;
+
+        let output = keccakf1600_gadget(&state)?;
+        // first lanes of Keccak-f[1600] applied to the all-zero state
+        assert_eq!(output[0].value()?, Fr::from(0xf1258f7940e1dde7u64));
+        assert_eq!(output[1].value()?, Fr::from(0x84d5ccf933c0478au64));
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+}
@@ -4,26 +4,36 @@ use std::convert::TryInto;
 
 use acvm::acir::{
     acir_field::GenericFieldElement,
-    circuit::{Circuit, Opcode, PublicInputs},
+    circuit::{
+        opcodes::{BlackBoxFuncCall, BlockId, ConstantOrWitnessEnum, FunctionInput, MemOp},
+        Circuit, Opcode, PublicInputs,
+    },
     native_types::{Expression, Witness, WitnessMap},
 };
 use ark_ff::{Field, PrimeField};
-use ark_r1cs_std::alloc::AllocVar;
-use ark_r1cs_std::fields::fp::FpVar;
-use ark_relations::{
-    gr1cs::{
-        ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable,
-    },
-    lc,
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    GR1CSVar,
 };
+use ark_relations::gr1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use folding_schemes::Error;
 
-// AcirCircuit and AcirArithGate are structs that arkworks can synthesise.
+use super::blackbox::{
+    and_gadget, keccakf1600_gadget, range_gadget, sha256_compression_gadget, xor_gadget,
+};
+use super::poseidon2::{poseidon2_permutation_gadget, Poseidon2Params, POSEIDON2_WIDTH};
+
+// AcirCircuit is a struct that arkworks can synthesise.
 //
-// The difference between these structures and the ACIR structure that the compiler uses is the following:
+// The difference between this structure and the ACIR structure that the compiler uses is the
+// following:
 // - The compilers ACIR struct is currently fixed to bn254
-// - These structures only support arithmetic gates, while the compiler has other
-// gate types. These can be added later once the backend knows how to deal with things like XOR
-// or once ACIR is taught how to do convert these black box functions to Arithmetic gates.
+// - This structure lowers to R1CS the arithmetic gates, the memory opcodes and a subset of the
+// black box functions (see `check_supported_opcodes`). Brillig calls are unconstrained hints, so
+// they don't add any constraint. Any other opcode is rejected.
 //
 // XXX: Ideally we want to implement `ConstraintSynthesizer` on ACIR however
 // this does not seem possible since ACIR is juts a description of the constraint system and the API Asks for prover values also.
@@ -34,12 +44,246 @@ use ark_relations::{
 // - verify(index_vk, verifier, rng)
 #[derive(Clone)]
 pub struct AcirCircuitSonobe<'a, F: Field + PrimeField> {
-    pub(crate) gates: Vec<Expression<GenericFieldElement<F>>>,
+    pub(crate) opcodes: Vec<Opcode<GenericFieldElement<F>>>,
     pub(crate) public_inputs: PublicInputs,
     pub(crate) values: BTreeMap<Witness, F>,
     pub already_assigned_witnesses: HashMap<Witness, &'a FpVar<F>>,
 }
 
+/// Checks that all the opcodes of the given circuit can be lowered to R1CS by
+/// [`AcirCircuitSonobe`], returning an error pointing to the first one that can't.
+pub(crate) fn check_supported_opcodes<F: PrimeField>(
+    circuit: &Circuit<GenericFieldElement<F>>,
+) -> Result<(), Error> {
+    for opcode in &circuit.opcodes {
+        match opcode {
+            Opcode::AssertZero(_)
+            | Opcode::MemoryInit { .. }
+            | Opcode::MemoryOp { .. }
+            | Opcode::BrilligCall { .. } => {}
+            Opcode::BlackBoxFuncCall(call) => match call {
+                BlackBoxFuncCall::RANGE { .. }
+                | BlackBoxFuncCall::AND { .. }
+                | BlackBoxFuncCall::XOR { .. }
+                | BlackBoxFuncCall::Sha256Compression { .. }
+                | BlackBoxFuncCall::Keccakf1600 { .. } => {}
+                BlackBoxFuncCall::Poseidon2Permutation { len, .. } => {
+                    if *len as usize != POSEIDON2_WIDTH {
+                        return Err(Error::NotSupported(format!(
+                            "Poseidon2 permutation with a state of {len} elements"
+                        )));
+                    }
+                }
+                _ => {
+                    return Err(Error::NotSupported(format!(
+                        "ACIR black box function {}",
+                        call.name()
+                    )))
+                }
+            },
+            _ => return Err(Error::NotSupported(format!("ACIR opcode {opcode}"))),
+        }
+    }
+    Ok(())
+}
+
+fn get_witness<F: PrimeField>(
+    witnesses: &BTreeMap<Witness, FpVar<F>>,
+    witness: &Witness,
+) -> Result<FpVar<F>, SynthesisError> {
+    witnesses
+        .get(witness)
+        .cloned()
+        .ok_or(SynthesisError::AssignmentMissing)
+}
+
+fn get_input<F: PrimeField>(
+    witnesses: &BTreeMap<Witness, FpVar<F>>,
+    input: &FunctionInput<GenericFieldElement<F>>,
+) -> Result<FpVar<F>, SynthesisError> {
+    match input.input_ref() {
+        ConstantOrWitnessEnum::Constant(c) => Ok(FpVar::constant(c.into_repr())),
+        ConstantOrWitnessEnum::Witness(w) => get_witness(witnesses, w),
+    }
+}
+
+/// Returns the linear part (constant term and linear combinations) of the given expression
+fn linear_part<F: PrimeField>(
+    witnesses: &BTreeMap<Witness, FpVar<F>>,
+    expr: &Expression<GenericFieldElement<F>>,
+) -> Result<FpVar<F>, SynthesisError> {
+    expr.linear_combinations
+        .iter()
+        .try_fold(FpVar::constant(expr.q_c.into_repr()), |acc, (coeff, w)| {
+            Ok(acc + get_witness(witnesses, w)? * coeff.into_repr())
+        })
+}
+
+/// Evaluates the given expression in-circuit, allocating one constraint per multiplication term
+fn expression_to_fp<F: PrimeField>(
+    witnesses: &BTreeMap<Witness, FpVar<F>>,
+    expr: &Expression<GenericFieldElement<F>>,
+) -> Result<FpVar<F>, SynthesisError> {
+    expr.mul_terms
+        .iter()
+        .try_fold(linear_part(witnesses, expr)?, |acc, (coeff, l, r)| {
+            Ok(acc + get_witness(witnesses, l)? * coeff.into_repr() * get_witness(witnesses, r)?)
+        })
+}
+
+/// Enforces that the given expression is zero. The common case of a single multiplication term is
+/// handled with a single constraint.
+fn enforce_assert_zero<F: PrimeField>(
+    witnesses: &BTreeMap<Witness, FpVar<F>>,
+    expr: &Expression<GenericFieldElement<F>>,
+) -> Result<(), SynthesisError> {
+    match expr.mul_terms.as_slice() {
+        [(coeff, l, r)] => (get_witness(witnesses, l)? * coeff.into_repr()).mul_equals(
+            &get_witness(witnesses, r)?,
+            &linear_part(witnesses, expr)?.negate()?,
+        ),
+        _ => expression_to_fp(witnesses, expr)?.enforce_equal(&FpVar::zero()),
+    }
+}
+
+/// Returns the one-hot encoding of `index` over a memory block of `len` elements, enforcing that
+/// `index < len`.
+fn index_selectors<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    index: &FpVar<F>,
+    len: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let index_value = index.value();
+    let selectors = (0..len)
+        .map(|j| {
+            Boolean::new_witness(cs.clone(), || {
+                index_value
+                    .as_ref()
+                    .map(|v| *v == F::from(j as u64))
+                    .map_err(|_| SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // exactly one selector is set, and it is the one at position `index`
+    selectors
+        .iter()
+        .map(|s| FpVar::from(s.clone()))
+        .sum::<FpVar<F>>()
+        .enforce_equal(&FpVar::one())?;
+    selectors
+        .iter()
+        .enumerate()
+        .map(|(j, s)| FpVar::from(s.clone()) * F::from(j as u64))
+        .sum::<FpVar<F>>()
+        .enforce_equal(index)?;
+    Ok(selectors)
+}
+
+/// Lowers a memory operation over `block`. As in the ACVM, when the predicate is false the
+/// operation is done at index 0, and writes leave the block unchanged.
+fn memory_op<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    witnesses: &BTreeMap<Witness, FpVar<F>>,
+    block: &mut [FpVar<F>],
+    op: &MemOp<GenericFieldElement<F>>,
+    predicate: &Option<Expression<GenericFieldElement<F>>>,
+) -> Result<(), SynthesisError> {
+    if !op.operation.is_const() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    let is_write = op.operation.q_c.into_repr().is_one();
+
+    let predicate = match predicate {
+        Some(predicate) => expression_to_fp(witnesses, predicate)?,
+        None => FpVar::one(),
+    };
+    let index = expression_to_fp(witnesses, &op.index)? * &predicate;
+    let selectors = index_selectors(cs, &index, block.len())?;
+    let value = expression_to_fp(witnesses, &op.value)?;
+
+    if is_write {
+        for (cell, s) in block.iter_mut().zip(&selectors) {
+            let enabled = FpVar::from(s.clone()) * &predicate;
+            *cell = &*cell + enabled * (&value - &*cell);
+        }
+    } else {
+        let read = block
+            .iter()
+            .zip(&selectors)
+            .map(|(cell, s)| FpVar::from(s.clone()) * cell)
+            .sum::<FpVar<F>>();
+        read.enforce_equal(&value)?;
+    }
+    Ok(())
+}
+
+fn black_box_call<F: PrimeField>(
+    witnesses: &BTreeMap<Witness, FpVar<F>>,
+    poseidon2_params: &mut Option<Poseidon2Params<F>>,
+    call: &BlackBoxFuncCall<GenericFieldElement<F>>,
+) -> Result<(), SynthesisError> {
+    let get_inputs = |inputs: &[FunctionInput<GenericFieldElement<F>>]| {
+        inputs
+            .iter()
+            .map(|input| get_input(witnesses, input))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let enforce_outputs = |outputs: &[Witness], values: Vec<FpVar<F>>| {
+        if outputs.len() != values.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        outputs
+            .iter()
+            .zip(values)
+            .try_for_each(|(w, v)| get_witness(witnesses, w)?.enforce_equal(&v))
+    };
+
+    match call {
+        BlackBoxFuncCall::RANGE { input } => {
+            range_gadget(&get_input(witnesses, input)?, input.num_bits() as usize)
+        }
+        BlackBoxFuncCall::AND { lhs, rhs, output } => enforce_outputs(
+            &[*output],
+            vec![and_gadget(
+                &get_input(witnesses, lhs)?,
+                &get_input(witnesses, rhs)?,
+                lhs.num_bits() as usize,
+            )?],
+        ),
+        BlackBoxFuncCall::XOR { lhs, rhs, output } => enforce_outputs(
+            &[*output],
+            vec![xor_gadget(
+                &get_input(witnesses, lhs)?,
+                &get_input(witnesses, rhs)?,
+                lhs.num_bits() as usize,
+            )?],
+        ),
+        BlackBoxFuncCall::Poseidon2Permutation {
+            inputs, outputs, ..
+        } => {
+            // the round constants are only computed if the circuit uses Poseidon2
+            let params = poseidon2_params.get_or_insert_with(Poseidon2Params::default);
+            enforce_outputs(
+                &outputs[..],
+                poseidon2_permutation_gadget(params, &get_inputs(&inputs[..])?)?,
+            )
+        }
+        BlackBoxFuncCall::Sha256Compression {
+            inputs,
+            hash_values,
+            outputs,
+        } => enforce_outputs(
+            &outputs[..],
+            sha256_compression_gadget(&get_inputs(&inputs[..])?, &get_inputs(&hash_values[..])?)?,
+        ),
+        BlackBoxFuncCall::Keccakf1600 { inputs, outputs } => {
+            enforce_outputs(&outputs[..], keccakf1600_gadget(&get_inputs(&inputs[..])?)?)
+        }
+        // unreachable for circuits that passed `check_supported_opcodes`
+        _ => Err(SynthesisError::Unsatisfiable),
+    }
+}
+
 impl<'a, ConstraintF: Field + PrimeField> ConstraintSynthesizer<ConstraintF>
     for AcirCircuitSonobe<'a, ConstraintF>
 {
@@ -47,54 +291,52 @@ impl<'a, ConstraintF: Field + PrimeField> ConstraintSynthesizer<ConstraintF>
         self,
         cs: ConstraintSystemRef<ConstraintF>,
     ) -> Result<(), SynthesisError> {
-        let mut variables = Vec::with_capacity(self.values.len());
+        let mut witnesses = BTreeMap::new();
 
         // First create all of the witness indices by adding the values into the constraint system
         for (i, val) in self.values.iter() {
-            let var = if self.already_assigned_witnesses.contains_key(i) {
-                let var = self.already_assigned_witnesses.get(i).unwrap();
-                if let FpVar::Var(allocated) = var {
-                    allocated.variable
-                } else {
-                    return Err(SynthesisError::Unsatisfiable);
-                }
+            let var = if let Some(var) = self.already_assigned_witnesses.get(i) {
+                (*var).clone()
             } else if self.public_inputs.contains(i.0.try_into().unwrap()) {
-                cs.new_witness_variable(|| Ok(*val))?
+                FpVar::new_witness(cs.clone(), || Ok(*val))?
             } else {
-                cs.new_witness_variable(|| Ok(*val))?
+                FpVar::new_witness(cs.clone(), || Ok(*val))?
             };
-            variables.push(var);
+            witnesses.insert(*i, var);
         }
 
-        // Now iterate each gate and add it to the constraint system
-        for gate in self.gates {
-            let mut arith_gate = LinearCombination::<ConstraintF>::new();
-
-            // Process mul terms
-            for mul_term in gate.mul_terms {
-                let coeff = mul_term.0;
-                let left_val = self.values[&mul_term.1];
-                let right_val = self.values[&mul_term.2];
-
-                let out_val = left_val * right_val;
-                let out_var = FpVar::<ConstraintF>::new_witness(cs.clone(), || Ok(out_val))?;
-                // out var can't be a type different from FpVar::Var
-                if let FpVar::Var(allocated) = out_var {
-                    arith_gate += (coeff.into_repr(), allocated.variable);
-                }
-            }
+        let mut blocks: HashMap<BlockId, Vec<FpVar<ConstraintF>>> = HashMap::new();
+        let mut poseidon2_params = None;
 
-            // Process Add terms
-            for add_term in gate.linear_combinations {
-                let coeff = add_term.0;
-                let add_var = &variables[add_term.1.as_usize()];
-                arith_gate += (coeff.into_repr(), *add_var);
+        // Now iterate each opcode and add its constraints to the constraint system
+        for opcode in &self.opcodes {
+            match opcode {
+                Opcode::AssertZero(expr) => enforce_assert_zero(&witnesses, expr)?,
+                Opcode::MemoryInit { block_id, init, .. } => {
+                    let init = init
+                        .iter()
+                        .map(|w| get_witness(&witnesses, w))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    blocks.insert(*block_id, init);
+                }
+                Opcode::MemoryOp {
+                    block_id,
+                    op,
+                    predicate,
+                } => {
+                    let block = blocks
+                        .get_mut(block_id)
+                        .ok_or(SynthesisError::AssignmentMissing)?;
+                    memory_op(cs.clone(), &witnesses, block, op, predicate)?;
+                }
+                Opcode::BlackBoxFuncCall(call) => {
+                    black_box_call(&witnesses, &mut poseidon2_params, call)?
+                }
+                // Brillig calls are unconstrained, their outputs are constrained by other opcodes
+                Opcode::BrilligCall { .. } => {}
+                // unreachable for circuits that passed `check_supported_opcodes`
+                _ => return Err(SynthesisError::Unsatisfiable),
             }
-
-            // Process constant term
-            arith_gate += (gate.q_c.into_repr(), Variable::One);
-
-            cs.enforce_r1cs_constraint(|| lc!() + Variable::One, || arith_gate, || lc!())?;
         }
 
         Ok(())
@@ -113,22 +355,9 @@ impl<'a, F: PrimeField>
             WitnessMap<GenericFieldElement<F>>,
         ),
     ) -> AcirCircuitSonobe<'a, F> {
-        // Currently non-arithmetic gates are not supported
-        // so we extract all of the arithmetic gates only
         let (circuit, witness_map) = circ_val;
 
         let public_inputs = circuit.public_inputs();
-        let arith_gates: Vec<_> = circuit
-            .opcodes
-            .iter()
-            .filter_map(|opcode| {
-                if let Opcode::AssertZero(code) = opcode {
-                    Some(code.clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
 
         let num_variables: usize = circuit.num_vars().try_into().unwrap();
 
@@ -145,10 +374,68 @@ impl<'a, F: PrimeField>
             .collect();
 
         AcirCircuitSonobe {
-            gates: arith_gates,
+            opcodes: circuit.opcodes.clone(),
             values,
             public_inputs,
             already_assigned_witnesses: HashMap::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::circuit::opcodes::BlockType;
+    use ark_bn254::Fr;
+    use ark_relations::gr1cs::ConstraintSystem;
+
+    use super::*;
+
+    fn witness_expr(w: u32) -> Expression<GenericFieldElement<Fr>> {
+        Expression::from(Witness(w))
+    }
+
+    // builds a circuit over the witnesses `[a_0, a_1, a_2, i, v]` which initializes the memory
+    // block `[a_0, a_1, a_2]`, writes `v` at index `i` and reads it back into `v`
+    fn memory_circuit(values: [u64; 5]) -> AcirCircuitSonobe<'static, Fr> {
+        let block_id = BlockId(0);
+        AcirCircuitSonobe {
+            opcodes: vec![
+                Opcode::MemoryInit {
+                    block_id,
+                    init: (0..3).map(Witness).collect(),
+                    block_type: BlockType::Memory,
+                },
+                Opcode::MemoryOp {
+                    block_id,
+                    op: MemOp::write_to_mem_index(witness_expr(3), witness_expr(4)),
+                    predicate: None,
+                },
+                Opcode::MemoryOp {
+                    block_id,
+                    op: MemOp::read_at_mem_index(witness_expr(3), Witness(4)),
+                    predicate: None,
+                },
+            ],
+            public_inputs: PublicInputs::default(),
+            values: values
+                .iter()
+                .enumerate()
+                .map(|(i, &v)| (Witness(i as u32), Fr::from(v)))
+                .collect(),
+            already_assigned_witnesses: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_memory_opcodes() -> Result<(), SynthesisError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        memory_circuit([1, 2, 3, 1, 5]).generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+
+        // out of bounds index
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        memory_circuit([1, 2, 3, 3, 5]).generate_constraints(cs.clone())?;
+        assert!(!cs.is_satisfied()?);
+        Ok(())
+    }
+}
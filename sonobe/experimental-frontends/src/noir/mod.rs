@@ -1,11 +1,10 @@
 use acvm::{
     acir::{
         acir_field::GenericFieldElement,
-        circuit::{Circuit, Program},
+        circuit::{brillig::BrilligBytecode, Circuit, Program},
         native_types::{Witness as AcvmWitness, WitnessMap},
     },
-    blackbox_solver::StubbedBlackBoxSolver,
-    pwg::ACVM,
+    pwg::{ACVMStatus, ACVM},
 };
 use ark_ff::PrimeField;
 use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, GR1CSVar};
@@ -13,15 +12,22 @@ use ark_relations::gr1cs::{ConstraintSynthesizer, ConstraintSystemRef, Synthesis
 use serde::{self, Deserialize, Serialize};
 use std::collections::HashMap;
 
-use self::bridge::AcirCircuitSonobe;
+use self::bridge::{check_supported_opcodes, AcirCircuitSonobe};
+use self::solver::SonobeBlackBoxSolver;
 use crate::utils::{VecF, VecFpVar};
 use folding_schemes::{frontend::FCircuit, utils::PathOrBin, Error};
 
+mod blackbox;
 mod bridge;
+mod poseidon2;
+mod solver;
 
 #[derive(Clone, Debug)]
 pub struct NoirFCircuit<F: PrimeField, const SL: usize, const EIL: usize> {
     pub circuit: Circuit<GenericFieldElement<F>>,
+    /// Brillig functions called by the circuit, needed by the ACVM to compute the witness
+    pub unconstrained_functions: Vec<BrilligBytecode<GenericFieldElement<F>>>,
+    solver: SonobeBlackBoxSolver<F>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -49,6 +55,9 @@ impl<F: PrimeField, const SL: usize, const EIL: usize> FCircuit<F> for NoirFCirc
         let program: ProgramArtifactGeneric<F> = serde_json::from_slice(&input_string)
             .map_err(|err| Error::JSONSerdeError(err.to_string()))?;
         let circuit: Circuit<GenericFieldElement<F>> = program.bytecode.functions[0].clone();
+        // reject the circuits that use opcodes that can't be lowered to R1CS, instead of proving
+        // a different relation
+        check_supported_opcodes(&circuit)?;
         let ivc_input_length = circuit.public_parameters.0.len();
         let ivc_return_length = circuit.return_values.0.len();
 
@@ -61,7 +70,11 @@ impl<F: PrimeField, const SL: usize, const EIL: usize> FCircuit<F> for NoirFCirc
             ));
         }
 
-        Ok(NoirFCircuit { circuit })
+        Ok(NoirFCircuit {
+            circuit,
+            unconstrained_functions: program.bytecode.unconstrained_functions,
+            solver: SonobeBlackBoxSolver::default(),
+        })
     }
 
     fn state_len(&self) -> usize {
@@ -76,10 +89,10 @@ impl<F: PrimeField, const SL: usize, const EIL: usize> FCircuit<F> for NoirFCirc
         external_inputs: Self::ExternalInputsVar, // inputs that are not part of the state
     ) -> Result<Vec<FpVar<F>>, SynthesisError> {
         let mut acvm = ACVM::new(
-            &StubbedBlackBoxSolver,
+            &self.solver,
             &self.circuit.opcodes,
             WitnessMap::new(),
-            &[],
+            &self.unconstrained_functions,
             &[],
         );
 
@@ -108,8 +121,12 @@ impl<F: PrimeField, const SL: usize, const EIL: usize> FCircuit<F> for NoirFCirc
             acvm.overwrite_witness(witness, f);
         });
 
-        // computes the witness
-        let _ = acvm.solve();
+        // computes the witness. In setup mode the inputs are not assigned, so the solver may fail,
+        // but the values are not used anyway
+        let status = acvm.solve();
+        if !cs.is_in_setup_mode() && !matches!(status, ACVMStatus::Solved) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
         let witness_map = acvm.finalize();
 
         // get the z_{i+1} output state
@@ -0,0 +1,175 @@
+/// Poseidon2 permutation as used by Noir's `Poseidon2Permutation` black box function, both natively
+/// (to solve the witness) and in-circuit (to lower the black box call to R1CS).
+///
+/// The parameters are the ones of [Poseidon2](https://eprint.iacr.org/2023/323.pdf) over BN254's
+/// scalar field with a state of width 4, as used by Noir and Barretenberg.
+use ark_crypto_primitives::sponge::poseidon::find_poseidon_ark_and_mds;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_relations::gr1cs::SynthesisError;
+use num_bigint::BigUint;
+
+/// Width of the Poseidon2 state supported by Noir
+pub const POSEIDON2_WIDTH: usize = 4;
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 56;
+
+/// Diagonal of the internal matrix minus the identity, i.e. the internal matrix is
+/// `diag(INTERNAL_MATRIX_DIAGONAL) + 1`
+const INTERNAL_MATRIX_DIAGONAL: [&str; POSEIDON2_WIDTH] = [
+    "10dc6e9c006ea38b04b1e03b4bd9490c0d03f98929ca1d7fb56821fd19d3b6e7",
+    "0c28145b6a44df3e0149b3d0a30b3bb599df9756d4dd9b84a86b38cfb45a740b",
+    "00544b8338791518b2c7645a50392798b21f75bb60e3596170067d00141cac15",
+    "222c01175718386f2e2e82eb122789e352e105a3b8fa852613bc534433ee428b",
+];
+
+#[derive(Clone, Debug)]
+pub struct Poseidon2Params<F: PrimeField> {
+    /// round constants, one vector of `POSEIDON2_WIDTH` elements per round. In the partial rounds
+    /// only the first element is used.
+    pub round_constants: Vec<Vec<F>>,
+    pub internal_matrix_diagonal: [F; POSEIDON2_WIDTH],
+}
+
+impl<F: PrimeField> Default for Poseidon2Params<F> {
+    fn default() -> Self {
+        // Poseidon2's round constants are sampled with the same Grain LFSR as Poseidon's
+        let (round_constants, _) = find_poseidon_ark_and_mds::<F>(
+            F::MODULUS_BIT_SIZE as u64,
+            POSEIDON2_WIDTH - 1,
+            FULL_ROUNDS as u64,
+            PARTIAL_ROUNDS as u64,
+            0,
+        );
+        let internal_matrix_diagonal = INTERNAL_MATRIX_DIAGONAL
+            .map(|d| F::from(BigUint::parse_bytes(d.as_bytes(), 16).expect("valid hex constant")));
+        Self {
+            round_constants,
+            internal_matrix_diagonal,
+        }
+    }
+}
+
+/// Computes the Poseidon2 permutation natively
+pub fn poseidon2_permutation<F: PrimeField>(params: &Poseidon2Params<F>, inputs: &[F]) -> Vec<F> {
+    permutation(
+        params,
+        inputs.to_vec(),
+        |x| Ok(x.pow([5])),
+        |x| x.iter().sum(),
+    )
+    .expect("the native permutation does not fail")
+}
+
+/// Computes the Poseidon2 permutation in-circuit
+pub fn poseidon2_permutation_gadget<F: PrimeField>(
+    params: &Poseidon2Params<F>,
+    inputs: &[FpVar<F>],
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    permutation(
+        params,
+        inputs.to_vec(),
+        |x| {
+            let x2 = x.square()?;
+            Ok(x2.square()? * x)
+        },
+        |x| x.iter().sum(),
+    )
+}
+
+/// Generic implementation of the permutation, shared by the native and the in-circuit versions,
+/// which only differ in how the S-box and the sum of the state are computed.
+fn permutation<F, T>(
+    params: &Poseidon2Params<F>,
+    mut state: Vec<T>,
+    sbox: impl Fn(&T) -> Result<T, SynthesisError>,
+    sum: impl Fn(&[T]) -> T,
+) -> Result<Vec<T>, SynthesisError>
+where
+    F: PrimeField,
+    T: Clone
+        + core::ops::Add<Output = T>
+        + core::ops::Add<F, Output = T>
+        + core::ops::Mul<F, Output = T>,
+{
+    let external_layer = |s: &mut Vec<T>| {
+        // multiplication by the 4x4 MDS matrix
+        // [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]]
+        let t0 = s[0].clone() + s[1].clone();
+        let t1 = s[2].clone() + s[3].clone();
+        let t2 = s[1].clone() * F::from(2u64) + t1.clone();
+        let t3 = s[3].clone() * F::from(2u64) + t0.clone();
+        let t4 = t1 * F::from(4u64) + t3.clone();
+        let t5 = t0 * F::from(4u64) + t2.clone();
+        let t6 = t3 + t5.clone();
+        let t7 = t2 + t4.clone();
+        *s = vec![t6, t5, t7, t4];
+    };
+
+    let half_full_rounds = FULL_ROUNDS / 2;
+
+    external_layer(&mut state);
+    for (r, rc) in params.round_constants.iter().enumerate() {
+        if r < half_full_rounds || r >= half_full_rounds + PARTIAL_ROUNDS {
+            // full round
+            state = state
+                .iter()
+                .zip(rc)
+                .map(|(s, &c)| sbox(&(s.clone() + c)))
+                .collect::<Result<_, _>>()?;
+            external_layer(&mut state);
+        } else {
+            // partial round
+            state[0] = sbox(&(state[0].clone() + rc[0]))?;
+            let s = sum(&state);
+            state = state
+                .iter()
+                .zip(&params.internal_matrix_diagonal)
+                .map(|(x, &d)| x.clone() * d + s.clone())
+                .collect();
+        }
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, GR1CSVar};
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    #[test]
+    fn test_poseidon2_permutation() {
+        let params = Poseidon2Params::<Fr>::default();
+        let inputs = (0..4).map(Fr::from).collect::<Vec<_>>();
+        let expected = [
+            "01bd538c2ee014ed5141b29e9ae240bf8db3fe5b9a38629a9647cf8d76c01737",
+            "239b62e7db98aa3a2a8f6a0d2fa1709e7a35959aa6c7034814d9daa90cbac662",
+            "04cbb44c61d928ed06808456bf758cbf0c18d1e15a7b6dbc8245fa7515d5e3cb",
+            "2e11c5cff2a22c64d01304b778d78f6998eff1ab73163a35603f54794c30847a",
+        ]
+        .map(|h| Fr::from(BigUint::parse_bytes(h.as_bytes(), 16).unwrap()));
+        assert_eq!(poseidon2_permutation(&params, &inputs), expected);
+    }
+
+    #[test]
+    fn test_poseidon2_permutation_gadget() -> Result<(), SynthesisError> {
+        let mut rng = ark_std::test_rng();
+        let params = Poseidon2Params::<Fr>::default();
+        let inputs = (0..4).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let inputs_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(inputs.clone()))?;
+        let outputs_var = poseidon2_permutation_gadget(&params, &inputs_var)?;
+        assert_eq!(
+            outputs_var.value()?,
+            poseidon2_permutation(&params, &inputs)
+        );
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+/// Black box function solver used by the ACVM to compute the witness of Noir circuits.
+///
+/// The ACVM solves the range checks, the bitwise operations, SHA-256 and Keccak by itself, and
+/// relies on the solver for the remaining black box functions. Among those, only the Poseidon2
+/// permutation is supported, matching the black box functions that can be lowered to R1CS.
+use acvm::{
+    acir::{acir_field::GenericFieldElement, BlackBoxFunc},
+    blackbox_solver::{BlackBoxFunctionSolver, BlackBoxResolutionError},
+};
+use ark_ff::PrimeField;
+
+use super::poseidon2::{poseidon2_permutation, Poseidon2Params, POSEIDON2_WIDTH};
+
+#[derive(Clone, Debug, Default)]
+pub struct SonobeBlackBoxSolver<F: PrimeField> {
+    poseidon2_params: Poseidon2Params<F>,
+}
+
+fn unsupported<T>(func: BlackBoxFunc) -> Result<T, BlackBoxResolutionError> {
+    Err(BlackBoxResolutionError::Failed(
+        func,
+        format!(
+            "{} is not supported by the Sonobe Noir frontend",
+            func.name()
+        ),
+    ))
+}
+
+impl<F: PrimeField> BlackBoxFunctionSolver<GenericFieldElement<F>> for SonobeBlackBoxSolver<F> {
+    fn schnorr_verify(
+        &self,
+        _public_key_x: &GenericFieldElement<F>,
+        _public_key_y: &GenericFieldElement<F>,
+        _signature: &[u8; 64],
+        _message: &[u8],
+    ) -> Result<bool, BlackBoxResolutionError> {
+        unsupported(BlackBoxFunc::SchnorrVerify)
+    }
+
+    fn multi_scalar_mul(
+        &self,
+        _points: &[GenericFieldElement<F>],
+        _scalars_lo: &[GenericFieldElement<F>],
+        _scalars_hi: &[GenericFieldElement<F>],
+    ) -> Result<
+        (
+            GenericFieldElement<F>,
+            GenericFieldElement<F>,
+            GenericFieldElement<F>,
+        ),
+        BlackBoxResolutionError,
+    > {
+        unsupported(BlackBoxFunc::MultiScalarMul)
+    }
+
+    fn ec_add(
+        &self,
+        _input1_x: &GenericFieldElement<F>,
+        _input1_y: &GenericFieldElement<F>,
+        _input1_infinite: &GenericFieldElement<F>,
+        _input2_x: &GenericFieldElement<F>,
+        _input2_y: &GenericFieldElement<F>,
+        _input2_infinite: &GenericFieldElement<F>,
+    ) -> Result<
+        (
+            GenericFieldElement<F>,
+            GenericFieldElement<F>,
+            GenericFieldElement<F>,
+        ),
+        BlackBoxResolutionError,
+    > {
+        unsupported(BlackBoxFunc::EmbeddedCurveAdd)
+    }
+
+    fn poseidon2_permutation(
+        &self,
+        inputs: &[GenericFieldElement<F>],
+        len: u32,
+    ) -> Result<Vec<GenericFieldElement<F>>, BlackBoxResolutionError> {
+        if len as usize != POSEIDON2_WIDTH || inputs.len() != POSEIDON2_WIDTH {
+            return Err(BlackBoxResolutionError::Failed(
+                BlackBoxFunc::Poseidon2Permutation,
+                format!(
+                    "expected a state of {} elements, got {} (len = {})",
+                    POSEIDON2_WIDTH,
+                    inputs.len(),
+                    len
+                ),
+            ));
+        }
+        let inputs = inputs.iter().map(|x| x.into_repr()).collect::<Vec<_>>();
+        Ok(poseidon2_permutation(&self.poseidon2_params, &inputs)
+            .into_iter()
+            .map(GenericFieldElement::from_repr)
+            .collect())
+    }
+}
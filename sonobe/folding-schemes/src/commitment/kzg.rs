@@ -5,6 +5,16 @@
 /// vectors indistinctly, and the arkworks KZG10 implementation contains all the methods under the
 /// same trait, which requires the Pairing trait, where the prover does not need access to the
 /// Pairing but only to G1.
+///
+/// When `H = true` the commitments are hiding, as in arkworks' `KZG10::commit` with a hiding bound:
+/// a commitment to `v` with the blinding polynomial `r(X)` is `Σ v_i β^i G + Σ r_i γβ^i G`. An
+/// opening at `z` is blinded with the quotient `(r(X) - r(z)) / (X - z)` and carries `r(z)`, so
+/// that the verifier can remove it. The commitments of the `CommitmentScheme` trait are blinded by
+/// the constant `r(X) = blind`, which is what folds linearly with the blinding factors of the
+/// folding schemes, and an opening of such a commitment reveals `blind`. Commitments that are
+/// opened must thus first be blinded with a random polynomial of degree `HIDING_BOUND`, by adding
+/// `commit_blinding_polynomial` of its higher coefficients, and opened with `open` (as the
+/// zero-knowledge Nova decider does for the randomized folded instance).
 use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
 use ark_ff::PrimeField;
 use ark_poly::{
@@ -32,6 +42,9 @@ use crate::{Curve, Error};
 pub struct ProverKey<'a, C: Curve> {
     /// Group elements of the form `β^i G`, for different values of `i`.
     pub powers_of_g: Cow<'a, [C::Affine]>,
+    /// Group elements of the form `γβ^i G`, for `i` up to `HIDING_BOUND`, used to commit to the
+    /// blinding polynomials.
+    pub powers_of_gamma_g: Cow<'a, [C::Affine]>,
 }
 
 impl<'a, C: Curve> CanonicalSerialize for ProverKey<'a, C> {
@@ -40,33 +53,37 @@ impl<'a, C: Curve> CanonicalSerialize for ProverKey<'a, C> {
         mut writer: W,
         compress: ark_serialize::Compress,
     ) -> Result<(), ark_serialize::SerializationError> {
-        self.powers_of_g.serialize_with_mode(&mut writer, compress)
+        self.powers_of_g
+            .serialize_with_mode(&mut writer, compress)?;
+        self.powers_of_gamma_g
+            .serialize_with_mode(&mut writer, compress)
     }
 
     fn serialized_size(&self, compress: ark_serialize::Compress) -> usize {
         self.powers_of_g.serialized_size(compress)
+            + self.powers_of_gamma_g.serialized_size(compress)
     }
 }
 
 impl<'a, C: Curve> CanonicalDeserialize for ProverKey<'a, C> {
     fn deserialize_with_mode<R: std::io::prelude::Read>(
-        reader: R,
+        mut reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
     ) -> Result<Self, ark_serialize::SerializationError> {
-        let powers_of_g_vec = Vec::deserialize_with_mode(reader, compress, validate)?;
+        let powers_of_g_vec = Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        let powers_of_gamma_g_vec = Vec::deserialize_with_mode(&mut reader, compress, validate)?;
         Ok(ProverKey {
             powers_of_g: ark_std::borrow::Cow::Owned(powers_of_g_vec),
+            powers_of_gamma_g: ark_std::borrow::Cow::Owned(powers_of_gamma_g_vec),
         })
     }
 }
 
 impl<'a, C: Curve> Valid for ProverKey<'a, C> {
     fn check(&self) -> Result<(), ark_serialize::SerializationError> {
-        match self.powers_of_g.clone() {
-            Cow::Borrowed(powers) => powers.to_vec().check(),
-            Cow::Owned(powers) => powers.check(),
-        }
+        self.powers_of_g.to_vec().check()?;
+        self.powers_of_gamma_g.to_vec().check()
     }
 }
 
@@ -74,8 +91,16 @@ impl<'a, C: Curve> Valid for ProverKey<'a, C> {
 pub struct Proof<C: Curve> {
     pub eval: C::ScalarField,
     pub proof: C,
+    /// evaluation of the blinding polynomial at the challenge, zero when the commitment is not
+    /// hiding
+    pub random_v: C::ScalarField,
 }
 
+/// Degree of the blinding polynomials of the hiding commitments that are opened. An opening
+/// reveals one evaluation of the blinding polynomial, so a degree of 1 hides the committed vector
+/// from a single opening.
+pub const HIDING_BOUND: usize = 1;
+
 /// KZG implements the CommitmentScheme trait for the KZG commitment scheme.
 #[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct KZG<'a, E: Pairing, const H: bool = false> {
@@ -108,31 +133,35 @@ impl<'a, E: Pairing<G1: Curve>, const H: bool> CommitmentScheme<E::G1, H> for KZ
             KZG10::<E, DensePolynomial<E::ScalarField>>::setup(len, false, &mut rng)
                 .expect("Setup failed");
         let powers_of_g = universal_params.powers_of_g[..=len].to_vec();
-        let powers = ProverKey::<E::G1> {
-            powers_of_g: ark_std::borrow::Cow::Owned(powers_of_g),
-        };
+        let powers_of_gamma_g = (0..=HIDING_BOUND)
+            .map(|i| universal_params.powers_of_gamma_g[&i])
+            .collect::<Vec<_>>();
         let vk = VerifierKey {
             g: universal_params.powers_of_g[0],
-            gamma_g: universal_params.powers_of_gamma_g[&0],
+            gamma_g: powers_of_gamma_g[0],
             h: universal_params.h,
             beta_h: universal_params.beta_h,
             prepared_h: universal_params.prepared_h.clone(),
             prepared_beta_h: universal_params.prepared_beta_h.clone(),
         };
+        let powers = ProverKey::<E::G1> {
+            powers_of_g: ark_std::borrow::Cow::Owned(powers_of_g),
+            powers_of_gamma_g: ark_std::borrow::Cow::Owned(powers_of_gamma_g),
+        };
         Ok((powers, vk))
     }
 
     /// commit implements the CommitmentScheme commit interface, adapting the implementation from
     /// https://github.com/arkworks-rs/poly-commit/tree/c724fa666e935bbba8db5a1421603bab542e15ab/poly-commit/src/kzg10/mod.rs#L178
-    /// with the main difference being the removal of the blinding factors and the no-dependency to
-    /// the Pairing trait.
+    /// with the main differences being that the blinding polynomial is the constant `blind`, and the
+    /// no-dependency to the Pairing trait.
     fn commit(
         params: &Self::ProverParams,
         v: &[E::ScalarField],
-        _blind: &E::ScalarField,
+        blind: &E::ScalarField,
     ) -> Result<E::G1, Error> {
-        if !_blind.is_zero() || H {
-            return Err(Error::NotSupportedYet("hiding".to_string()));
+        if !H && !blind.is_zero() {
+            return Err(Error::BlindingNotZero);
         }
 
         let polynomial = poly_from_vec(v.to_vec())?;
@@ -144,69 +173,40 @@ impl<'a, E: Pairing<G1: Curve>, const H: bool> CommitmentScheme<E::G1, H> for KZ
             &params.powers_of_g[num_leading_zeros..],
             &plain_coeffs,
         );
-        Ok(commitment)
+        if !H {
+            return Ok(commitment);
+        }
+        Ok(commitment + commit_blinding_polynomial(params, &[*blind])?)
     }
 
     /// prove implements the CommitmentScheme prove interface, adapting the implementation from
     /// https://github.com/arkworks-rs/poly-commit/tree/c724fa666e935bbba8db5a1421603bab542e15ab/poly-commit/src/kzg10/mod.rs#L307
-    /// with the main difference being the removal of the blinding factors and the no-dependency to
-    /// the Pairing trait.
+    /// with the main differences being that the blinding polynomial is the constant `blind`, and the
+    /// no-dependency to the Pairing trait.
     fn prove(
         params: &Self::ProverParams,
         transcript: &mut impl Transcript<E::ScalarField>,
         cm: &E::G1,
         v: &[E::ScalarField],
-        _blind: &E::ScalarField,
+        blind: &E::ScalarField,
         _rng: Option<&mut dyn RngCore>,
     ) -> Result<Self::Proof, Error> {
         transcript.absorb_nonnative(cm);
         let challenge = transcript.get_challenge();
-        Self::prove_with_challenge(params, challenge, v, _blind, _rng)
+        Self::prove_with_challenge(params, challenge, v, blind, _rng)
     }
 
     fn prove_with_challenge(
         params: &Self::ProverParams,
         challenge: Self::ProverChallenge,
         v: &[E::ScalarField],
-        _blind: &E::ScalarField,
+        blind: &E::ScalarField,
         _rng: Option<&mut dyn RngCore>,
     ) -> Result<Self::Proof, Error> {
-        if !_blind.is_zero() || H {
-            return Err(Error::NotSupportedYet("hiding".to_string()));
+        if !H && !blind.is_zero() {
+            return Err(Error::BlindingNotZero);
         }
-
-        let polynomial = poly_from_vec(v.to_vec())?;
-        check_degree_is_too_large(polynomial.degree(), params.powers_of_g.len())?;
-
-        // Compute q(x) = (p(x) - p(z)) / (x-z). Observe that this quotient does not change with z
-        // because p(z) is the remainder term. We can therefore omit p(z) when computing the
-        // quotient.
-        let divisor = DensePolynomial::<E::ScalarField>::from_coefficients_vec(vec![
-            -challenge,
-            E::ScalarField::one(),
-        ]);
-        let (witness_poly, remainder_poly) = DenseOrSparsePolynomial::from(&polynomial)
-            .divide_with_q_and_r(&DenseOrSparsePolynomial::from(&divisor))
-            // the panic inside `divide_with_q_and_r` should never be reached, since the divisor
-            // polynomial is constructed right before and is set to not be zero. And the `.unwrap`
-            // should not give an error.
-            .unwrap();
-
-        let eval = if remainder_poly.is_zero() {
-            E::ScalarField::zero()
-        } else {
-            remainder_poly[0]
-        };
-
-        check_degree_is_too_large(witness_poly.degree(), params.powers_of_g.len())?;
-        let (num_leading_zeros, witness_coeffs) =
-            skip_first_zero_coeffs_and_convert_to_bigints(&witness_poly);
-        let proof = <E::G1 as VariableBaseMSM>::msm_bigint(
-            &params.powers_of_g[num_leading_zeros..],
-            &witness_coeffs,
-        );
-
-        Ok(Proof { eval, proof })
+        open(params, challenge, v, &[*blind])
     }
 
     fn verify(
@@ -226,19 +226,15 @@ impl<'a, E: Pairing<G1: Curve>, const H: bool> CommitmentScheme<E::G1, H> for KZ
         cm: &E::G1,
         proof: &Self::Proof,
     ) -> Result<(), Error> {
-        if H {
-            return Err(Error::NotSupportedYet("hiding".to_string()));
-        }
-
         // verify the KZG proof using arkworks method
         let v = KZG10::<E, DensePolynomial<E::ScalarField>>::check(
-            params, // vk
+            params,
             &KZG10Commitment(cm.into_affine()),
             challenge,
             proof.eval,
             &KZG10Proof::<E> {
                 w: proof.proof.into_affine(),
-                random_v: None,
+                random_v: H.then_some(proof.random_v),
             },
         )?;
         if !v {
@@ -248,6 +244,83 @@ impl<'a, E: Pairing<G1: Curve>, const H: bool> CommitmentScheme<E::G1, H> for KZ
     }
 }
 
+/// Commits to the blinding polynomial with coefficients `r`, of degree at most `HIDING_BOUND`, as
+/// `Σ r_i γβ^i G`. Adding the commitment to the higher coefficients of `r` to a hiding commitment
+/// with blinding factor `r_0` blinds it with `r(X)`.
+pub fn commit_blinding_polynomial<C: Curve>(
+    params: &ProverKey<C>,
+    r: &[C::ScalarField],
+) -> Result<C, Error> {
+    if r.len() > params.powers_of_gamma_g.len() {
+        return Err(Error::NotExpectedLength(
+            r.len(),
+            params.powers_of_gamma_g.len(),
+        ));
+    }
+    Ok(<C as VariableBaseMSM>::msm_bigint(
+        &params.powers_of_gamma_g[..r.len()],
+        &convert_to_bigints(r),
+    ))
+}
+
+/// Opens at `challenge` the commitment to `v` blinded with the polynomial with coefficients `r`,
+/// adapting the implementation from
+/// https://github.com/arkworks-rs/poly-commit/tree/c724fa666e935bbba8db5a1421603bab542e15ab/poly-commit/src/kzg10/mod.rs#L307
+/// The proof is blinded with the quotient of `r(X)` by `X - challenge`, and carries `r(challenge)`.
+pub fn open<C: Curve>(
+    params: &ProverKey<C>,
+    challenge: C::ScalarField,
+    v: &[C::ScalarField],
+    r: &[C::ScalarField],
+) -> Result<Proof<C>, Error> {
+    let polynomial = poly_from_vec(v.to_vec())?;
+    check_degree_is_too_large(polynomial.degree(), params.powers_of_g.len())?;
+
+    // Compute q(x) = (p(x) - p(z)) / (x-z). Observe that this quotient does not change with z
+    // because p(z) is the remainder term. We can therefore omit p(z) when computing the
+    // quotient.
+    let (witness_poly, eval) = divide_by_linear(&polynomial, challenge);
+
+    check_degree_is_too_large(witness_poly.degree(), params.powers_of_g.len())?;
+    let (num_leading_zeros, witness_coeffs) =
+        skip_first_zero_coeffs_and_convert_to_bigints(&witness_poly);
+    let mut proof = <C as VariableBaseMSM>::msm_bigint(
+        &params.powers_of_g[num_leading_zeros..],
+        &witness_coeffs,
+    );
+
+    // Same for the blinding polynomial, whose quotient blinds the proof
+    let blinding_poly = DensePolynomial::from_coefficients_slice(r);
+    let (random_witness_poly, random_v) = divide_by_linear(&blinding_poly, challenge);
+    proof += commit_blinding_polynomial(params, random_witness_poly.coeffs())?;
+
+    Ok(Proof {
+        eval,
+        proof,
+        random_v,
+    })
+}
+
+/// Returns the quotient and the remainder of the division of `p(X)` by `X - z`
+fn divide_by_linear<F: PrimeField>(p: &DensePolynomial<F>, z: F) -> (DensePolynomial<F>, F) {
+    if p.is_zero() {
+        return (DensePolynomial::zero(), F::zero());
+    }
+    let divisor = DensePolynomial::<F>::from_coefficients_vec(vec![-z, F::one()]);
+    let (quotient, remainder) = DenseOrSparsePolynomial::from(p)
+        .divide_with_q_and_r(&DenseOrSparsePolynomial::from(&divisor))
+        // the panic inside `divide_with_q_and_r` should never be reached, since the divisor
+        // polynomial is constructed right before and is set to not be zero. And the `.unwrap`
+        // should not give an error.
+        .unwrap();
+    let remainder = if remainder.is_zero() {
+        F::zero()
+    } else {
+        remainder[0]
+    };
+    (quotient, remainder)
+}
+
 fn check_degree_is_too_large(
     degree: usize,
     num_powers: usize,
@@ -308,4 +381,74 @@ mod tests {
         KZG::<Bn254>::verify(&vk, transcript_v, &cm, &proof)?;
         Ok(())
     }
+
+    #[test]
+    fn test_hiding_kzg_commitment_scheme() -> Result<(), Error> {
+        let mut rng = &mut test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+
+        let n = 10;
+        let (pk, vk): (ProverKey<G1>, VerifierKey<Bn254>) = KZG::<Bn254, true>::setup(&mut rng, n)?;
+
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng)).take(n).collect();
+        let blind = Fr::rand(rng);
+        let cm = KZG::<Bn254, true>::commit(&pk, &v, &blind)?;
+        // the blinding factor must change the commitment
+        assert_ne!(cm, KZG::<Bn254>::commit(&pk, &v, &Fr::zero())?);
+        // and is not accepted by the non-hiding scheme
+        assert!(KZG::<Bn254>::commit(&pk, &v, &blind).is_err());
+
+        let proof = KZG::<Bn254, true>::prove(&pk, transcript_p, &cm, &v, &blind, None)?;
+        assert_eq!(proof.random_v, blind);
+
+        // verify the proof:
+        KZG::<Bn254, true>::verify(&vk, &mut transcript_v.clone(), &cm, &proof)?;
+
+        // a proof with a wrong blinding evaluation must not verify
+        let mut wrong_proof = proof.clone();
+        wrong_proof.random_v += Fr::one();
+        assert!(KZG::<Bn254, true>::verify(&vk, transcript_v, &cm, &wrong_proof).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hiding_kzg_opening() -> Result<(), Error> {
+        let mut rng = &mut test_rng();
+        let n = 10;
+        let (pk, vk): (ProverKey<G1>, VerifierKey<Bn254>) = KZG::<Bn254, true>::setup(&mut rng, n)?;
+        // the blinding generator is the one of the setup
+        assert_eq!(vk.gamma_g, pk.powers_of_gamma_g[0]);
+
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng)).take(n).collect();
+        let r: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng))
+            .take(HIDING_BOUND + 1)
+            .collect();
+        // blind the commitment of the trait with the higher coefficients of r(X)
+        let cm = KZG::<Bn254, true>::commit(&pk, &v, &r[0])?
+            + commit_blinding_polynomial(&pk, &[&[Fr::zero()], &r[1..]].concat())?;
+        assert_eq!(
+            cm,
+            KZG::<Bn254>::commit(&pk, &v, &Fr::zero())? + commit_blinding_polynomial(&pk, &r)?
+        );
+
+        let challenge = Fr::rand(rng);
+        let proof = open(&pk, challenge, &v, &r)?;
+        // the opening carries r(z), not the blinding factor
+        assert_eq!(
+            proof.random_v,
+            DensePolynomial::from_coefficients_slice(&r).evaluate(&challenge)
+        );
+        assert_ne!(proof.random_v, r[0]);
+        KZG::<Bn254, true>::verify_with_challenge(&vk, challenge, &cm, &proof)?;
+
+        // opening with the constant blinding factor only does not verify
+        let proof = open(&pk, challenge, &v, &r[..1])?;
+        assert!(KZG::<Bn254, true>::verify_with_challenge(&vk, challenge, &cm, &proof).is_err());
+
+        // the degree of the blinding polynomial is bounded by the prover key
+        assert!(commit_blinding_polynomial(&pk, &vec![Fr::one(); HIDING_BOUND + 2]).is_err());
+        Ok(())
+    }
 }
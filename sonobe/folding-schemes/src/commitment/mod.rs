@@ -77,7 +77,7 @@ mod tests {
     };
     use ark_poly_commit::kzg10::VerifierKey;
     use ark_std::Zero;
-    use ark_std::{test_rng, UniformRand};
+    use ark_std::{rand::RngCore, test_rng, UniformRand};
 
     use super::ipa::IPA;
     use super::kzg::{ProverKey, KZG};
@@ -98,10 +98,12 @@ mod tests {
 
         // setup params for Pedersen & KZG
         let (pedersen_params, _) = Pedersen::<G1>::setup(&mut rng, n)?;
-        let (kzg_pk, kzg_vk): (ProverKey<G1>, VerifierKey<Bn254>) = KZG::<Bn254>::setup(rng, n)?;
+        let (kzg_pk, kzg_vk): (ProverKey<G1>, VerifierKey<Bn254>) =
+            KZG::<Bn254>::setup(&mut rng, n)?;
 
         // test with Pedersen
-        let _ = test_homomorphic_property_using_Commitment_trait_opt::<G1, Pedersen<G1>>(
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, Pedersen<G1>, false>(
+            rng,
             &poseidon_config,
             &pedersen_params,
             &pedersen_params,
@@ -110,7 +112,8 @@ mod tests {
             &v_2,
         )?;
         // test with IPA
-        let _ = test_homomorphic_property_using_Commitment_trait_opt::<G1, IPA<G1>>(
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, IPA<G1>, false>(
+            rng,
             &poseidon_config,
             &pedersen_params,
             &pedersen_params,
@@ -119,7 +122,39 @@ mod tests {
             &v_2,
         )?;
         // test with KZG
-        let _ = test_homomorphic_property_using_Commitment_trait_opt::<G1, KZG<Bn254>>(
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, KZG<Bn254>, false>(
+            rng,
+            &poseidon_config,
+            &kzg_pk,
+            &kzg_vk,
+            r,
+            &v_1,
+            &v_2,
+        )?;
+
+        // test with hiding Pedersen
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, Pedersen<G1, true>, true>(
+            rng,
+            &poseidon_config,
+            &pedersen_params,
+            &pedersen_params,
+            r,
+            &v_1,
+            &v_2,
+        )?;
+        // test with hiding IPA
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, IPA<G1, true>, true>(
+            rng,
+            &poseidon_config,
+            &pedersen_params,
+            &pedersen_params,
+            r,
+            &v_1,
+            &v_2,
+        )?;
+        // test with hiding KZG, which uses the same params as the non-hiding KZG
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, KZG<Bn254, true>, true>(
+            rng,
             &poseidon_config,
             &kzg_pk,
             &kzg_vk,
@@ -130,7 +165,12 @@ mod tests {
         Ok(())
     }
 
-    fn test_homomorphic_property_using_Commitment_trait_opt<C: Curve, CS: CommitmentScheme<C>>(
+    fn test_homomorphic_property_using_Commitment_trait_opt<
+        C: Curve,
+        CS: CommitmentScheme<C, H>,
+        const H: bool,
+    >(
+        rng: &mut impl RngCore,
         poseidon_config: &PoseidonConfig<C::ScalarField>,
         prover_params: &CS::ProverParams,
         verifier_params: &CS::VerifierParams,
@@ -138,13 +178,22 @@ mod tests {
         v_1: &[C::ScalarField],
         v_2: &[C::ScalarField],
     ) -> Result<(), Error> {
+        // set the blinding factors, which are zero if the scheme is not hiding
+        let (blind_1, blind_2) = if H {
+            (C::ScalarField::rand(rng), C::ScalarField::rand(rng))
+        } else {
+            (C::ScalarField::zero(), C::ScalarField::zero())
+        };
+
         // compute the commitment of the two vectors using the given CommitmentScheme
-        let cm_1 = CS::commit(prover_params, v_1, &C::ScalarField::zero())?;
-        let cm_2 = CS::commit(prover_params, v_2, &C::ScalarField::zero())?;
+        let cm_1 = CS::commit(prover_params, v_1, &blind_1)?;
+        let cm_2 = CS::commit(prover_params, v_2, &blind_2)?;
 
-        // random linear combination of the commitments and their witnesses (vectors v_i)
+        // random linear combination of the commitments and their witnesses (vectors v_i and
+        // blinding factors)
         let cm_3 = cm_1 + cm_2.mul(r);
         let v_3: Vec<C::ScalarField> = v_1.iter().zip(v_2).map(|(a, b)| *a + (r * b)).collect();
+        let blind_3 = blind_1 + r * blind_2;
 
         // compute the proof of the cm_3
         let transcript_p = &mut PoseidonSponge::<C::ScalarField>::new(poseidon_config);
@@ -153,8 +202,8 @@ mod tests {
             transcript_p,
             &cm_3,
             &v_3,
-            &C::ScalarField::zero(),
-            None,
+            &blind_3,
+            Some(rng),
         )?;
 
         // verify the opening proof
@@ -0,0 +1,404 @@
+/// Checkpointing of long-lived IVCs, so that the folding can be resumed after the prover process
+/// restarts instead of folding again from `z_0`.
+///
+/// A checkpoint contains the [`FoldingScheme::IVCProof`] of the last step, the index of the next
+/// step and a fingerprint of the params bundle it was computed with. It is written to a temporary
+/// file in the same directory, synced to disk and renamed over the previous checkpoint, so that a
+/// crash while writing leaves the previous checkpoint untouched.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{marker::PhantomData, rand::RngCore};
+use sha3::{Digest, Sha3_256};
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{frontend::FCircuit, Curve, Error, FoldingScheme};
+
+const CHECKPOINT_MAGIC: &[u8; 8] = b"sonobeck";
+const CHECKPOINT_VERSION: u32 = 1;
+
+/// Fingerprint of a params bundle (the prover and verifier params of a folding scheme).
+pub type ParamsFingerprint = [u8; 32];
+
+/// Returns the fingerprint of the given params bundle, which is the hash of their serialization.
+pub fn params_fingerprint<PP: CanonicalSerialize, VP: CanonicalSerialize>(
+    params: &(PP, VP),
+) -> Result<ParamsFingerprint, Error> {
+    let mut bytes = vec![];
+    params.0.serialize_compressed(&mut bytes)?;
+    params.1.serialize_compressed(&mut bytes)?;
+    Ok(Sha3_256::digest(&bytes).into())
+}
+
+/// A checkpoint of an IVC.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint<P> {
+    pub params_fingerprint: ParamsFingerprint,
+    /// number of steps folded so far, ie. the index of the next step
+    pub step: u64,
+    pub ivc_proof: P,
+}
+
+impl<P: CanonicalSerialize + CanonicalDeserialize> Checkpoint<P> {
+    /// Serializes the checkpoint as `magic || version || fingerprint || step || ivc_proof ||
+    /// H(...)`, where the trailing hash allows to detect corrupted checkpoints.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = CHECKPOINT_MAGIC.to_vec();
+        bytes.extend_from_slice(&CHECKPOINT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.params_fingerprint);
+        bytes.extend_from_slice(&self.step.to_le_bytes());
+        self.ivc_proof.serialize_compressed(&mut bytes)?;
+        let checksum = Sha3_256::digest(&bytes);
+        bytes.extend_from_slice(&checksum);
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let corrupted = |msg: &str| Error::Checkpoint(format!("corrupted checkpoint: {msg}"));
+        let header_len = CHECKPOINT_MAGIC.len() + 4 + 32 + 8;
+        if bytes.len() < header_len + 32 {
+            return Err(corrupted("too short"));
+        }
+        let (content, checksum) = bytes.split_at(bytes.len() - 32);
+        if Sha3_256::digest(content).as_slice() != checksum {
+            return Err(corrupted("wrong checksum"));
+        }
+        let (magic, rest) = content.split_at(CHECKPOINT_MAGIC.len());
+        if magic != CHECKPOINT_MAGIC {
+            return Err(corrupted("wrong magic"));
+        }
+        let (version, rest) = rest.split_at(4);
+        let version = u32::from_le_bytes(version.try_into().map_err(|_| corrupted("version"))?);
+        if version != CHECKPOINT_VERSION {
+            return Err(Error::Checkpoint(format!(
+                "unsupported checkpoint version {version}, expected {CHECKPOINT_VERSION}"
+            )));
+        }
+        let (params_fingerprint, rest) = rest.split_at(32);
+        let (step, mut ivc_proof) = rest.split_at(8);
+        Ok(Self {
+            params_fingerprint: params_fingerprint
+                .try_into()
+                .map_err(|_| corrupted("fingerprint"))?,
+            step: u64::from_le_bytes(step.try_into().map_err(|_| corrupted("step"))?),
+            ivc_proof: P::deserialize_compressed(&mut ivc_proof)?,
+        })
+    }
+}
+
+/// Stores the checkpoint of an IVC in a file.
+#[derive(Clone, Debug)]
+pub struct CheckpointStore {
+    path: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// returns true if a checkpoint has been stored
+    pub fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        let mut file_name = self.path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".tmp");
+        self.path.with_file_name(file_name)
+    }
+
+    /// Atomically replaces the stored checkpoint by the given one.
+    pub fn save<P: CanonicalSerialize + CanonicalDeserialize>(
+        &self,
+        checkpoint: &Checkpoint<P>,
+    ) -> Result<(), Error> {
+        let bytes = checkpoint.to_bytes()?;
+
+        // write the new checkpoint into a temporary file and flush it to disk before renaming it,
+        // since otherwise after a crash the rename could be persisted but not the content
+        let tmp_path = self.tmp_path();
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp_path, &self.path)?;
+
+        // persist the rename itself. Directories can not be opened on every platform, in which
+        // case the rename is persisted by the OS at some later point.
+        #[cfg(unix)]
+        {
+            let dir = match self.path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// Loads the stored checkpoint, if any. Leftovers of interrupted writes are ignored.
+    pub fn load<P: CanonicalSerialize + CanonicalDeserialize>(
+        &self,
+    ) -> Result<Option<Checkpoint<P>>, Error> {
+        if !self.exists() {
+            return Ok(None);
+        }
+        Ok(Some(Checkpoint::from_bytes(&fs::read(&self.path)?)?))
+    }
+}
+
+/// A folding scheme whose IVC proof is checkpointed into a [`CheckpointStore`] after each step.
+#[derive(Clone, Debug)]
+pub struct CheckpointedIVC<C1, C2, FC, FS>
+where
+    C1: Curve<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: Curve,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>,
+{
+    _c1: PhantomData<C1>,
+    _c2: PhantomData<C2>,
+    _fc: PhantomData<FC>,
+    pub folding_scheme: FS,
+    store: CheckpointStore,
+    params_fingerprint: ParamsFingerprint,
+    step: u64,
+}
+
+impl<C1, C2, FC, FS> CheckpointedIVC<C1, C2, FC, FS>
+where
+    C1: Curve<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: Curve,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>,
+{
+    /// Starts a new IVC from `z_0`. Any checkpoint already in the store is overwritten after the
+    /// first step.
+    pub fn init(
+        params: &(FS::ProverParam, FS::VerifierParam),
+        step_circuit: FC,
+        z_0: Vec<C1::ScalarField>,
+        store: CheckpointStore,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            _c1: PhantomData,
+            _c2: PhantomData,
+            _fc: PhantomData,
+            params_fingerprint: params_fingerprint(params)?,
+            folding_scheme: FS::init(params, step_circuit, z_0)?,
+            store,
+            step: 0,
+        })
+    }
+
+    /// Resumes the IVC from the checkpoint in the store. The checkpoint must have been computed
+    /// with the given params, and its IVC proof must be valid for them and be at the step of the
+    /// checkpoint.
+    pub fn resume(
+        params: (FS::ProverParam, FS::VerifierParam),
+        fcircuit_params: FC::Params,
+        store: CheckpointStore,
+    ) -> Result<Self, Error> {
+        let checkpoint = store
+            .load::<FS::IVCProof>()?
+            .ok_or_else(|| Error::Checkpoint(format!("no checkpoint at {:?}", store.path())))?;
+
+        let params_fingerprint = params_fingerprint(&params)?;
+        if checkpoint.params_fingerprint != params_fingerprint {
+            return Err(Error::Checkpoint(
+                "the checkpoint was computed with different params".to_string(),
+            ));
+        }
+        FS::verify(params.1.clone(), checkpoint.ivc_proof.clone())?;
+
+        let folding_scheme = FS::from_ivc_proof(checkpoint.ivc_proof, fcircuit_params, params)?;
+        if folding_scheme.steps() != C1::ScalarField::from(checkpoint.step) {
+            return Err(Error::Checkpoint(format!(
+                "the checkpoint is at step {}, but its IVC proof is not",
+                checkpoint.step
+            )));
+        }
+
+        Ok(Self {
+            _c1: PhantomData,
+            _c2: PhantomData,
+            _fc: PhantomData,
+            folding_scheme,
+            store,
+            params_fingerprint,
+            step: checkpoint.step,
+        })
+    }
+
+    /// Proves the next step, and checkpoints the resulting IVC proof.
+    pub fn prove_step(
+        &mut self,
+        rng: impl RngCore,
+        external_inputs: FC::ExternalInputs,
+        other_instances: Option<FS::MultiCommittedInstanceWithWitness>,
+    ) -> Result<(), Error> {
+        self.folding_scheme
+            .prove_step(rng, external_inputs, other_instances)?;
+        self.step += 1;
+        self.store.save(&Checkpoint {
+            params_fingerprint: self.params_fingerprint,
+            step: self.step,
+            ivc_proof: self.folding_scheme.ivc_proof(),
+        })
+    }
+
+    /// returns the number of steps folded so far
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    pub fn store(&self) -> &CheckpointStore {
+        &self.store
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ark_pallas::{Fr, Projective as G1};
+    use ark_vesta::Projective as G2;
+
+    use super::*;
+    use crate::commitment::pedersen::Pedersen;
+    use crate::folding::{
+        hypernova::HyperNova,
+        nova::{Nova, PreprocessorParam as NovaPreprocessorParam},
+        protogalaxy::ProtoGalaxy,
+    };
+    use crate::frontend::utils::CubicFCircuit;
+    use crate::transcript::poseidon::poseidon_canonical_config;
+
+    /// tests killing and resuming the IVC for the 3 implemented IVCs: Nova, HyperNova and
+    /// ProtoGalaxy.
+    #[test]
+    fn test_checkpoint_resume_nova_hypernova_protogalaxy() -> Result<(), Error> {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        type FC = CubicFCircuit<Fr>;
+        let f_circuit = FC::new(())?;
+
+        // test Nova
+        type N = Nova<G1, G2, FC, Pedersen<G1>, Pedersen<G2>, false>;
+        let prep_param = NovaPreprocessorParam::new(poseidon_config.clone(), f_circuit);
+        test_checkpoint_resume_opt::<G1, G2, FC, N>("nova", prep_param.clone())?;
+
+        // test HyperNova
+        type HN = HyperNova<G1, G2, FC, Pedersen<G1>, Pedersen<G2>, 1, 1, false>;
+        test_checkpoint_resume_opt::<G1, G2, FC, HN>("hypernova", prep_param)?;
+
+        // test ProtoGalaxy
+        type P = ProtoGalaxy<G1, G2, FC, Pedersen<G1>, Pedersen<G2>>;
+        let prep_param = (poseidon_config, f_circuit);
+        test_checkpoint_resume_opt::<G1, G2, FC, P>("protogalaxy", prep_param)?;
+        Ok(())
+    }
+
+    fn test_checkpoint_resume_opt<
+        C1: Curve<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+        C2: Curve,
+        FC: FCircuit<C1::ScalarField, Params = ()>,
+        FS: FoldingScheme<C1, C2, FC>,
+    >(
+        name: &str,
+        prep_param: FS::PreprocessorParam,
+    ) -> Result<(), Error> {
+        let mut rng = ark_std::test_rng();
+        let F_circuit = FC::new(())?;
+        let fs_params = FS::preprocess(&mut rng, &prep_param)?;
+        let z_0 = vec![C1::ScalarField::from(3_u32)];
+
+        let path =
+            std::env::temp_dir().join(format!("sonobe-checkpoint-{}-{}", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        let store = CheckpointStore::new(&path);
+
+        // reference run, without interruptions
+        let mut reference_fs = FS::init(&fs_params, F_circuit.clone(), z_0.clone())?;
+        for _ in 0..5 {
+            reference_fs.prove_step(&mut rng, FC::ExternalInputs::default(), None)?;
+        }
+
+        // checkpointed run, killed after 3 steps
+        let mut ivc = CheckpointedIVC::<C1, C2, FC, FS>::init(
+            &fs_params,
+            F_circuit.clone(),
+            z_0.clone(),
+            store.clone(),
+        )?;
+        for _ in 0..3 {
+            ivc.prove_step(&mut rng, FC::ExternalInputs::default(), None)?;
+        }
+        drop(ivc);
+
+        // simulate a crash in the middle of writing the checkpoint of the 4th step, which leaves a
+        // torn temporary file
+        let bytes = fs::read(&path)?;
+        fs::write(store.tmp_path(), &bytes[..bytes.len() / 2])?;
+
+        // resume from the last complete checkpoint, and finish the run
+        let mut ivc =
+            CheckpointedIVC::<C1, C2, FC, FS>::resume(fs_params.clone(), (), store.clone())?;
+        assert_eq!(ivc.step(), 3);
+        for _ in 3..5 {
+            ivc.prove_step(&mut rng, FC::ExternalInputs::default(), None)?;
+        }
+        assert_eq!(ivc.step(), 5);
+        assert_eq!(ivc.folding_scheme.ivc_proof(), reference_fs.ivc_proof());
+
+        // the stored checkpoint is the one of the last step
+        let checkpoint = store.load::<FS::IVCProof>()?.unwrap();
+        assert_eq!(checkpoint.step, 5);
+        FS::verify(fs_params.1.clone(), checkpoint.ivc_proof.clone())?;
+
+        // resuming with other params must fail
+        let other_params = FS::preprocess(&mut rng, &prep_param)?;
+        assert!(
+            CheckpointedIVC::<C1, C2, FC, FS>::resume(other_params, (), store.clone()).is_err()
+        );
+
+        // resuming from a checkpoint whose step does not match its IVC proof must fail, even if
+        // its checksum is valid
+        store.save(&Checkpoint {
+            step: 4,
+            ..checkpoint.clone()
+        })?;
+        assert!(matches!(
+            CheckpointedIVC::<C1, C2, FC, FS>::resume(fs_params.clone(), (), store.clone()),
+            Err(Error::Checkpoint(_))
+        ));
+        store.save(&checkpoint)?;
+
+        // resuming from a checkpoint torn on disk (eg. on a filesystem where the rename is not
+        // atomic) must fail
+        let bytes = fs::read(&path)?;
+        for len in [0, 16, bytes.len() / 2, bytes.len() - 1] {
+            fs::write(&path, &bytes[..len])?;
+            assert!(matches!(
+                CheckpointedIVC::<C1, C2, FC, FS>::resume(fs_params.clone(), (), store.clone()),
+                Err(Error::Checkpoint(_))
+            ));
+        }
+        fs::write(&path, &bytes)?;
+
+        // resuming from a corrupted checkpoint must fail
+        let mut bytes = fs::read(&path)?;
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&path, bytes)?;
+        assert!(CheckpointedIVC::<C1, C2, FC, FS>::resume(fs_params, (), store.clone()).is_err());
+
+        fs::remove_file(&path)?;
+        let _ = fs::remove_file(store.tmp_path());
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
+pub mod checkpoint;
 pub mod circuits;
 pub mod hypernova;
+pub mod mova;
 pub mod nova;
+pub mod ova;
 pub mod protogalaxy;
 pub mod traits;
 
@@ -0,0 +1,341 @@
+/// contains [Mova](https://eprint.iacr.org/2024/1220.pdf) related circuits
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    GR1CSVar,
+};
+use ark_relations::gr1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::{fmt::Debug, marker::PhantomData, Zero};
+
+use super::{CommittedInstance, CommittedInstanceVar, MovaCycleFoldConfig, Proof};
+use crate::folding::circuits::{
+    cyclefold::{
+        CycleFoldAugmentationGadget, CycleFoldCommittedInstance, CycleFoldCommittedInstanceVar,
+        CycleFoldConfig,
+    },
+    nonnative::affine::NonNativeAffineVar,
+    CF1,
+};
+use crate::folding::nova::nifs::{
+    mova_circuits::{NIFSGadget, ProofVar},
+    NIFSGadgetTrait,
+};
+use crate::folding::traits::{CommittedInstanceVarOps, Dummy};
+use crate::frontend::FCircuit;
+use crate::transcript::{Transcript, TranscriptVar};
+use crate::Curve;
+
+/// `EvalPointGadget` computes the evaluation point `rE` of an incoming instance.
+///
+/// The incoming instance claims that the MLE of its error term `E` (which is zero) evaluates to
+/// zero at `rE`. If the prover could choose `rE` freely, the claim could be satisfied by a
+/// non-zero `E`, so `rE` is derived from the instance's public inputs and its commitment to the
+/// witness.
+pub struct EvalPointGadget<C: Curve> {
+    _c: PhantomData<C>,
+}
+
+impl<C: Curve> EvalPointGadget<C> {
+    pub fn get_eval_point_native<T: Transcript<CF1<C>>>(
+        sponge: &T,
+        x: &[CF1<C>],
+        cmW: &C,
+        n_vars: usize,
+    ) -> Vec<CF1<C>> {
+        let mut sponge = sponge.clone();
+        sponge.absorb(&x);
+        sponge.absorb_nonnative(cmW);
+        sponge.squeeze_field_elements(n_vars)
+    }
+
+    pub fn get_eval_point_gadget<S: CryptographicSponge, T: TranscriptVar<CF1<C>, S>>(
+        sponge: &T,
+        x: &[FpVar<CF1<C>>],
+        cmW: &NonNativeAffineVar<C>,
+        n_vars: usize,
+    ) -> Result<Vec<FpVar<CF1<C>>>, SynthesisError> {
+        let mut sponge = sponge.clone();
+        sponge.absorb(&x)?;
+        sponge.absorb_nonnative(cmW)?;
+        sponge.squeeze_field_elements(n_vars)
+    }
+}
+
+/// `AugmentedFCircuit` enhances the original step function `F`, so that it can
+/// be used in recursive arguments such as IVC.
+///
+/// It follows the same structure as Nova's `AugmentedFCircuit`, but the
+/// primary instances are Mova's `CommittedInstance`s, which replace the
+/// commitment to `E` by the evaluation `mleE` of its MLE at the point `rE`.
+/// Hence, a single CycleFold circuit is needed to fold the primary commitments
+/// on the other curve.
+#[derive(Debug, Clone)]
+pub struct AugmentedFCircuit<C1: Curve, C2: Curve, FC: FCircuit<CF1<C1>>> {
+    pub(super) poseidon_config: PoseidonConfig<CF1<C1>>,
+    pub(super) pp_hash: Option<CF1<C1>>,
+    pub(super) i: Option<CF1<C1>>,
+    pub(super) i_usize: Option<usize>,
+    pub(super) z_0: Option<Vec<C1::ScalarField>>,
+    pub(super) z_i: Option<Vec<C1::ScalarField>>,
+    pub(super) external_inputs: Option<FC::ExternalInputs>,
+    pub(super) u_i_cmW: Option<C1>,
+    pub(super) U_i: Option<CommittedInstance<C1>>,
+    pub(super) U_i1_cmW: Option<C1>,
+    pub(super) proof: Option<Proof<C1>>,
+    pub(super) F: FC, // F circuit
+    // number of variables of the MLE of `E`, i.e. the length of `rE`
+    pub(super) n_vars: usize,
+
+    // cyclefold verifier on C1
+    pub(super) cf_u_i_cmW: Option<C2>, // input
+    pub(super) cf_U_i: Option<CycleFoldCommittedInstance<C2>>, // input
+    pub(super) cf_cmT: Option<C2>,
+}
+
+impl<C1: Curve, C2: Curve, FC: FCircuit<CF1<C1>>> AugmentedFCircuit<C1, C2, FC> {
+    pub fn empty(poseidon_config: &PoseidonConfig<CF1<C1>>, F_circuit: FC, n_vars: usize) -> Self {
+        Self {
+            poseidon_config: poseidon_config.clone(),
+            pp_hash: None,
+            i: None,
+            i_usize: None,
+            z_0: None,
+            z_i: None,
+            external_inputs: None,
+            u_i_cmW: None,
+            U_i: None,
+            U_i1_cmW: None,
+            proof: None,
+            F: F_circuit,
+            n_vars,
+            // cyclefold values
+            cf_u_i_cmW: None,
+            cf_U_i: None,
+            cf_cmT: None,
+        }
+    }
+}
+
+impl<C1, C2, FC> AugmentedFCircuit<C1, C2, FC>
+where
+    C1: Curve<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: Curve,
+    FC: FCircuit<CF1<C1>>,
+{
+    pub fn compute_next_state(
+        self,
+        cs: ConstraintSystemRef<CF1<C1>>,
+    ) -> Result<Vec<FpVar<CF1<C1>>>, SynthesisError> {
+        let pp_hash = FpVar::<CF1<C1>>::new_witness(cs.clone(), || {
+            Ok(self.pp_hash.unwrap_or_else(CF1::<C1>::zero))
+        })?;
+        let i = FpVar::<CF1<C1>>::new_witness(cs.clone(), || {
+            Ok(self.i.unwrap_or_else(CF1::<C1>::zero))
+        })?;
+        let z_0 = Vec::<FpVar<CF1<C1>>>::new_witness(cs.clone(), || {
+            Ok(self
+                .z_0
+                .unwrap_or(vec![CF1::<C1>::zero(); self.F.state_len()]))
+        })?;
+        let z_i = Vec::<FpVar<CF1<C1>>>::new_witness(cs.clone(), || {
+            Ok(self
+                .z_i
+                .unwrap_or(vec![CF1::<C1>::zero(); self.F.state_len()]))
+        })?;
+        let external_inputs = FC::ExternalInputsVar::new_witness(cs.clone(), || {
+            Ok(self.external_inputs.unwrap_or_default())
+        })?;
+
+        let u_dummy = CommittedInstance {
+            rE: vec![CF1::<C1>::zero(); self.n_vars],
+            mleE: CF1::<C1>::zero(),
+            u: CF1::<C1>::zero(),
+            cmW: C1::zero(),
+            x: vec![CF1::<C1>::zero(); 2],
+        };
+        let U_i = CommittedInstanceVar::<C1>::new_witness(cs.clone(), || {
+            Ok(self.U_i.unwrap_or(u_dummy.clone()))
+        })?;
+        let U_i1_cmW = NonNativeAffineVar::new_witness(cs.clone(), || {
+            Ok(self.U_i1_cmW.unwrap_or_else(C1::zero))
+        })?;
+        let proof = ProofVar::<C1>::new_witness(cs.clone(), || {
+            Ok(self.proof.unwrap_or_else(|| Proof::dummy(self.n_vars)))
+        })?;
+
+        let cf_u_dummy = CycleFoldCommittedInstance::dummy(MovaCycleFoldConfig::<C1>::IO_LEN);
+        let cf_U_i = CycleFoldCommittedInstanceVar::<C2>::new_witness(cs.clone(), || {
+            Ok(self.cf_U_i.unwrap_or(cf_u_dummy.clone()))
+        })?;
+        let cf_cmT = C2::Var::new_witness(cs.clone(), || Ok(self.cf_cmT.unwrap_or_else(C2::zero)))?;
+
+        // `sponge` is for digest computation.
+        let sponge = PoseidonSpongeVar::<C1::ScalarField>::new_with_pp_hash(
+            &self.poseidon_config,
+            &pp_hash,
+        )?;
+        // `transcript` is for challenge generation.
+        let mut transcript = sponge.clone();
+
+        let is_basecase = i.is_zero()?;
+
+        // Primary Part
+        // P.1. Compute u_i.x
+        // u_i.x[0] = H(i, z_0, z_i, U_i)
+        let (u_i_x, U_i_vec) = U_i.clone().hash(&sponge, &i, &z_0, &z_i)?;
+        // u_i.x[1] = H(cf_U_i)
+        let (cf_u_i_x, _) = cf_U_i.clone().hash(&sponge)?;
+
+        // P.2. Construct u_i
+        let u_i_x = vec![u_i_x, cf_u_i_x];
+        // u_i.cmW is provided by the prover as witness
+        let u_i_cmW =
+            NonNativeAffineVar::new_witness(cs.clone(), || Ok(self.u_i_cmW.unwrap_or(C1::zero())))?;
+        let u_i = CommittedInstanceVar {
+            // u_i.rE is derived from u_i.x and u_i.cmW
+            rE: EvalPointGadget::<C1>::get_eval_point_gadget::<PoseidonSponge<CF1<C1>>, _>(
+                &sponge,
+                &u_i_x,
+                &u_i_cmW,
+                self.n_vars,
+            )?,
+            // u_i.mleE = 0, since E = 0 for incoming instances
+            mleE: FpVar::zero(),
+            // u_i.u = 1
+            u: FpVar::one(),
+            // u_i.x is computed in step 1
+            x: u_i_x,
+            cmW: u_i_cmW,
+        };
+
+        // P.3. nifs.verify, obtains U_{i+1} by folding u_i & U_i.
+        // Notice that NIFSGadget::verify does not fold cmW, we set `U_i1.cmW` to the unconstrained
+        // witness `U_i1_cmW`, whose correctness is checked on the other curve.
+        let (mut U_i1, r_bits) = NIFSGadget::<
+            C1,
+            PoseidonSponge<C1::ScalarField>,
+            PoseidonSpongeVar<C1::ScalarField>,
+        >::verify(
+            &mut transcript,
+            U_i.clone(),
+            U_i_vec,
+            u_i.clone(),
+            Some(proof),
+        )?;
+        U_i1.cmW = U_i1_cmW;
+
+        // P.4.a compute and check the first output of F'
+
+        // get z_{i+1} from the F circuit
+        let i_usize = self.i_usize.unwrap_or(0);
+        let z_i1 = self
+            .F
+            .generate_step_constraints(cs.clone(), i_usize, z_i, external_inputs)?;
+
+        // Base case: u_{i+1}.x[0] == H((i+1, z_0, z_{i+1}, U_{\bot})
+        // Non-base case: u_{i+1}.x[0] == H((i+1, z_0, z_{i+1}, U_{i+1})
+        let (u_i1_x, _) =
+            U_i1.clone()
+                .hash(&sponge, &(i + FpVar::<CF1<C1>>::one()), &z_0, &z_i1)?;
+        let (u_i1_x_base, _) = CommittedInstanceVar::new_constant(cs.clone(), u_dummy)?.hash(
+            &sponge,
+            &FpVar::<CF1<C1>>::one(),
+            &z_0,
+            &z_i1,
+        )?;
+        let x = is_basecase.select(&u_i1_x_base, &u_i1_x)?;
+        // This line "converts" `x` from a witness to a public input, see Nova's
+        // `AugmentedFCircuit` for details.
+        FpVar::new_input(cs.clone(), || x.value())?.enforce_equal(&x)?;
+
+        // CycleFold part
+        // C.1. Compute cf_u_i.x
+        // C.2. Construct `cf_u_i`
+        let cf_u_i = CycleFoldCommittedInstanceVar::new_incoming_from_components(
+            // `cf_u_i.cmW` is provided by the prover as witness.
+            C2::Var::new_witness(cs.clone(), || Ok(self.cf_u_i_cmW.unwrap_or(C2::zero())))?,
+            // To construct `cf_u_i.x`, we need to provide the randomness
+            // `r_bits` and the `cmW` component in committed instances `U_i`,
+            // `u_i`, and `U_{i+1}`.
+            &r_bits,
+            vec![U_i.cmW, u_i.cmW, U_i1.cmW],
+        )?;
+
+        // C.3. nifs.verify, obtains cf_U_{i+1} by folding cf_u_i into cf_U.
+        let cf_U_i1 = CycleFoldAugmentationGadget::fold_gadget(
+            &mut transcript,
+            cf_U_i,
+            vec![cf_u_i],
+            vec![cf_cmT],
+        )?;
+
+        // Back to Primary Part
+        // P.4.b compute and check the second output of F'
+        // Base case: u_{i+1}.x[1] == H(cf_U_{\bot})
+        // Non-base case: u_{i+1}.x[1] == H(cf_U_{i+1})
+        let (cf_u_i1_x, _) = cf_U_i1.clone().hash(&sponge)?;
+        let (cf_u_i1_x_base, _) =
+            CycleFoldCommittedInstanceVar::<C2>::new_constant(cs.clone(), cf_u_dummy)?
+                .hash(&sponge)?;
+        let cf_x = is_basecase.select(&cf_u_i1_x_base, &cf_u_i1_x)?;
+        // This line "converts" `cf_x` from a witness to a public input, see Nova's
+        // `AugmentedFCircuit` for details.
+        FpVar::new_input(cs.clone(), || cf_x.value())?.enforce_equal(&cf_x)?;
+
+        Ok(z_i1)
+    }
+}
+
+impl<C1, C2, FC> ConstraintSynthesizer<CF1<C1>> for AugmentedFCircuit<C1, C2, FC>
+where
+    C1: Curve<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: Curve,
+    FC: FCircuit<CF1<C1>>,
+{
+    fn generate_constraints(self, cs: ConstraintSystemRef<CF1<C1>>) -> Result<(), SynthesisError> {
+        self.compute_next_state(cs).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ark_pallas::{Fr, Projective};
+    use ark_relations::gr1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+
+    use super::*;
+    use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::Error;
+
+    // checks that the gadget and native implementations of the evaluation point derivation match
+    #[test]
+    fn test_eval_point_gadget() -> Result<(), Error> {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let pp_hash = Fr::rand(&mut rng);
+        let sponge = PoseidonSponge::<Fr>::new_with_pp_hash(&poseidon_config, pp_hash);
+
+        let n_vars = 5;
+        let x = vec![Fr::rand(&mut rng), Fr::rand(&mut rng)];
+        let cmW = Projective::rand(&mut rng);
+        let rE = EvalPointGadget::<Projective>::get_eval_point_native(&sponge, &x, &cmW, n_vars);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let pp_hashVar = FpVar::<Fr>::new_witness(cs.clone(), || Ok(pp_hash))?;
+        let spongeVar = PoseidonSpongeVar::<Fr>::new_with_pp_hash(&poseidon_config, &pp_hashVar)?;
+        let xVar = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(x))?;
+        let cmWVar = NonNativeAffineVar::<Projective>::new_witness(cs.clone(), || Ok(cmW))?;
+        let rEVar = EvalPointGadget::<Projective>::get_eval_point_gadget::<PoseidonSponge<Fr>, _>(
+            &spongeVar, &xVar, &cmWVar, n_vars,
+        )?;
+        assert!(cs.is_satisfied()?);
+        assert_eq!(rEVar.value()?, rE);
+        Ok(())
+    }
+}
@@ -0,0 +1,328 @@
+/// This file implements the Mova's onchain (Ethereum's EVM) decider. It follows the same
+/// approach as Nova's onchain decider, but since Mova's running instance only commits to the
+/// witness `W`, a single KZG opening is needed.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use ark_std::{
+    log2,
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    One, Zero,
+};
+
+pub use super::decider_eth_circuit::DeciderEthCircuit;
+use super::decider_eth_circuit::DeciderMovaGadget;
+use super::Mova;
+use crate::arith::Arith;
+use crate::folding::circuits::decider::DeciderEnabledNIFS;
+use crate::folding::traits::{Dummy, InputizeNonNative, WitnessOps};
+use crate::frontend::FCircuit;
+use crate::{
+    commitment::{kzg::Proof as KZGProof, pedersen::Params as PedersenParams, CommitmentScheme},
+    Curve, Error,
+};
+use crate::{Decider as DeciderTrait, FoldingScheme};
+
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Proof<C, CS, S>
+where
+    C: Curve,
+    CS: CommitmentScheme<C, ProverChallenge = C::ScalarField, Challenge = C::ScalarField>,
+    S: SNARK<C::ScalarField>,
+{
+    snark_proof: S::Proof,
+    kzg_proofs: [CS::Proof; 1],
+    // r is the randomness (`alpha`) of the last fold, U_{i+1}=NIFS.V(r, U_i, u_i), and it is
+    // checked in-circuit
+    r: C::ScalarField,
+    // the KZG challenges are provided by the prover, but in-circuit they are checked to match
+    // the in-circuit computed ones.
+    kzg_challenges: [C::ScalarField; 1],
+}
+
+impl<C, CS, S> Proof<C, CS, S>
+where
+    C: Curve,
+    CS: CommitmentScheme<C, ProverChallenge = C::ScalarField, Challenge = C::ScalarField>,
+    S: SNARK<C::ScalarField>,
+{
+    pub fn snark_proof(&self) -> &S::Proof {
+        &self.snark_proof
+    }
+
+    pub fn kzg_proofs(&self) -> &[CS::Proof; 1] {
+        &self.kzg_proofs
+    }
+
+    pub fn r(&self) -> C::ScalarField {
+        self.r
+    }
+
+    pub fn kzg_challenges(&self) -> [C::ScalarField; 1] {
+        self.kzg_challenges
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifierParam<C1, CS_VerifyingKey, S_VerifyingKey>
+where
+    C1: Curve,
+    CS_VerifyingKey: Clone + CanonicalSerialize + CanonicalDeserialize,
+    S_VerifyingKey: Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    pub pp_hash: C1::ScalarField,
+    pub snark_vp: S_VerifyingKey,
+    pub cs_vp: CS_VerifyingKey,
+}
+
+/// Onchain Decider, for ethereum use cases
+#[derive(Clone, Debug)]
+pub struct Decider<C1, C2, FC, CS1, CS2, S, FS> {
+    _c1: PhantomData<C1>,
+    _c2: PhantomData<C2>,
+    _fc: PhantomData<FC>,
+    _cs1: PhantomData<CS1>,
+    _cs2: PhantomData<CS2>,
+    _s: PhantomData<S>,
+    _fs: PhantomData<FS>,
+}
+
+impl<C1, C2, FC, CS1, CS2, S, FS> DeciderTrait<C1, C2, FC, FS>
+    for Decider<C1, C2, FC, CS1, CS2, S, FS>
+where
+    C1: Curve<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: Curve,
+    FC: FCircuit<C1::ScalarField>,
+    // CS1 is a KZG commitment, where challenge is C1::Fr elem
+    CS1: CommitmentScheme<
+        C1,
+        ProverChallenge = C1::ScalarField,
+        Challenge = C1::ScalarField,
+        Proof = KZGProof<C1>,
+    >,
+    // enforce that the CS2 is Pedersen commitment scheme, since we're at Ethereum's EVM decider
+    CS2: CommitmentScheme<C2, ProverParams = PedersenParams<C2>>,
+    S: SNARK<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>,
+    // constrain FS into Mova, since this is a Decider specifically for Mova
+    Mova<C1, C2, FC, CS1, CS2, false>: From<FS>,
+    crate::folding::nova::ProverParams<C1, C2, CS1, CS2, false>:
+        From<<FS as FoldingScheme<C1, C2, FC>>::ProverParam>,
+    crate::folding::nova::VerifierParams<C1, C2, CS1, CS2, false>:
+        From<<FS as FoldingScheme<C1, C2, FC>>::VerifierParam>,
+{
+    type PreprocessorParam = ((FS::ProverParam, FS::VerifierParam), usize);
+    type ProverParam = (S::ProvingKey, CS1::ProverParams);
+    type Proof = Proof<C1, CS1, S>;
+    type VerifierParam = VerifierParam<C1, CS1::VerifierParams, S::VerifyingKey>;
+    type PublicInput = Vec<C1::ScalarField>;
+    type CommittedInstance = Vec<C1>;
+
+    fn preprocess(
+        mut rng: impl RngCore + CryptoRng,
+        ((pp, vp), state_len): Self::PreprocessorParam,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
+        // get the FoldingScheme prover & verifier params from Mova
+        let mova_pp: <Mova<C1, C2, FC, CS1, CS2, false> as FoldingScheme<C1, C2, FC>>::ProverParam =
+            pp.into();
+        let mova_vp: <Mova<C1, C2, FC, CS1, CS2, false> as FoldingScheme<C1, C2, FC>>::VerifierParam =
+            vp.into();
+
+        let pp_hash = mova_vp.pp_hash()?;
+
+        // the dummy Mova proof depends on the number of variables of the MLE of `E`
+        let n_vars = log2(mova_vp.r1cs.n_constraints()) as usize;
+
+        let circuit = DeciderEthCircuit::<C1, C2>::dummy((
+            mova_vp.r1cs,
+            mova_vp.cf_r1cs,
+            mova_pp.cf_cs_pp,
+            mova_pp.poseidon_config,
+            n_vars,
+            (),
+            state_len,
+            1, // Mova's running CommittedInstance contains 1 commitment
+        ));
+
+        // get the Groth16 specific setup for the circuit
+        let (g16_pk, g16_vk) = S::circuit_specific_setup(circuit, &mut rng)
+            .map_err(|e| Error::SNARKSetupFail(e.to_string()))?;
+
+        let pp = (g16_pk, mova_pp.cs_pp);
+        let vp = Self::VerifierParam {
+            pp_hash,
+            snark_vp: g16_vk,
+            cs_vp: mova_vp.cs_vp,
+        };
+        Ok((pp, vp))
+    }
+
+    fn prove(
+        mut rng: impl RngCore + CryptoRng,
+        pp: Self::ProverParam,
+        folding_scheme: FS,
+    ) -> Result<Self::Proof, Error> {
+        let (snark_pk, cs_pk): (S::ProvingKey, CS1::ProverParams) = pp;
+
+        let circuit = DeciderEthCircuit::<C1, C2>::try_from(Mova::from(folding_scheme))?;
+
+        let r = circuit.randomness;
+
+        // get the challenges that have been already computed when preparing the circuit inputs in
+        // the above `try_from` call
+        let kzg_challenges = circuit.kzg_challenges.clone();
+
+        // generate KZG proofs
+        let kzg_proofs = circuit
+            .W_i1
+            .get_openings()
+            .iter()
+            .zip(&kzg_challenges)
+            .map(|((v, _), &c)| {
+                CS1::prove_with_challenge(&cs_pk, c, v, &C1::ScalarField::zero(), None)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let snark_proof =
+            S::prove(&snark_pk, circuit, &mut rng).map_err(|e| Error::Other(e.to_string()))?;
+
+        Ok(Self::Proof {
+            snark_proof,
+            r,
+            kzg_proofs: kzg_proofs
+                .try_into()
+                .map_err(|e: Vec<_>| Error::NotExpectedLength(e.len(), 1))?,
+            kzg_challenges: kzg_challenges
+                .try_into()
+                .map_err(|e: Vec<_>| Error::NotExpectedLength(e.len(), 1))?,
+        })
+    }
+
+    fn verify(
+        vp: Self::VerifierParam,
+        i: C1::ScalarField,
+        z_0: Vec<C1::ScalarField>,
+        z_i: Vec<C1::ScalarField>,
+        // we don't use the instances at the verifier level, since we check them in-circuit
+        running_commitments: &Self::CommittedInstance,
+        incoming_commitments: &Self::CommittedInstance,
+        proof: &Self::Proof,
+    ) -> Result<bool, Error> {
+        if i <= C1::ScalarField::one() {
+            return Err(Error::NotEnoughSteps);
+        }
+
+        let Self::VerifierParam {
+            pp_hash,
+            snark_vp,
+            cs_vp,
+        } = vp;
+
+        // 6.2. Fold the commitments
+        let U_final_commitments = DeciderMovaGadget::fold_group_elements_native(
+            running_commitments,
+            incoming_commitments,
+            None,
+            proof.r,
+        )?;
+
+        let public_input = [
+            &[pp_hash, i][..],
+            &z_0,
+            &z_i,
+            &U_final_commitments.inputize_nonnative(),
+            &proof.kzg_challenges,
+            &proof.kzg_proofs.iter().map(|p| p.eval).collect::<Vec<_>>(),
+            &[proof.r],
+        ]
+        .concat();
+
+        let snark_v = S::verify(&snark_vp, &public_input, &proof.snark_proof)
+            .map_err(|e| Error::Other(e.to_string()))?;
+        if !snark_v {
+            return Err(Error::SNARKVerificationFail);
+        }
+
+        // 7.3. Verify the KZG proofs
+        for ((cm, &c), pi) in U_final_commitments
+            .iter()
+            .zip(&proof.kzg_challenges)
+            .zip(&proof.kzg_proofs)
+        {
+            // we're at the Ethereum EVM case, so the CS1 is KZG commitments
+            CS1::verify_with_challenge(&cs_vp, c, cm, pi)?;
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ark_bn254::{Bn254, Fr, G1Projective as Projective};
+    use ark_groth16::Groth16;
+    use ark_grumpkin::Projective as Projective2;
+
+    use super::*;
+    use crate::commitment::{kzg::KZG, pedersen::Pedersen};
+    use crate::folding::nova::PreprocessorParam;
+    use crate::folding::traits::CommittedInstanceOps;
+    use crate::frontend::utils::CubicFCircuit;
+    use crate::transcript::poseidon::poseidon_canonical_config;
+
+    #[test]
+    fn test_decider() -> Result<(), Error> {
+        // use Mova as FoldingScheme
+        type M = Mova<
+            Projective,
+            Projective2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        type D = Decider<
+            Projective,
+            Projective2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            Groth16<Bn254>, // here we define the Snark to use in the decider
+            M,              // here we define the FoldingScheme to use
+        >;
+
+        let mut rng = rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(())?;
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let preprocessor_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let mova_params = M::preprocess(&mut rng, &preprocessor_param)?;
+
+        let mut mova = M::init(&mova_params, F_circuit, z_0)?;
+
+        // prepare the Decider prover & verifier params
+        let (decider_pp, decider_vp) =
+            D::preprocess(&mut rng, (mova_params, F_circuit.state_len()))?;
+
+        mova.prove_step(&mut rng, (), None)?;
+        mova.prove_step(&mut rng, (), None)?; // do a 2nd step
+
+        // decider proof generation
+        let proof = D::prove(rng, decider_pp, mova.clone())?;
+
+        // decider proof verification
+        let verified = D::verify(
+            decider_vp,
+            mova.i,
+            mova.z_0,
+            mova.z_i,
+            &mova.U_i.get_commitments(),
+            &mova.u_i.get_commitments(),
+            &proof,
+        )?;
+        assert!(verified);
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+/// This file implements the Mova's onchain (Ethereum's EVM) decider circuit. For non-ethereum use
+/// cases, other more efficient approaches can be used.
+use ark_crypto_primitives::sponge::poseidon::{constraints::PoseidonSpongeVar, PoseidonSponge};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar, prelude::Boolean, GR1CSVar};
+use ark_relations::gr1cs::SynthesisError;
+use ark_std::{log2, marker::PhantomData};
+
+use super::{
+    circuits::EvalPointGadget, CommittedInstance, CommittedInstanceVar, Mova, Proof, Witness,
+};
+use crate::{
+    arith::{
+        r1cs::{circuits::R1CSMatricesVar, R1CS},
+        Arith,
+    },
+    commitment::{pedersen::Params as PedersenParams, CommitmentScheme},
+    folding::{
+        circuits::{
+            decider::{
+                on_chain::GenericOnchainDeciderCircuit, DeciderEnabledNIFS, EvalGadget,
+                KZGChallengesGadget,
+            },
+            CF1,
+        },
+        nova::nifs::{
+            mova::NIFS,
+            mova_circuits::{NIFSGadget, ProofVar},
+            NIFSGadgetTrait, NIFSTrait,
+        },
+        traits::WitnessOps,
+    },
+    frontend::FCircuit,
+    transcript::Transcript,
+    Curve, Error,
+};
+
+pub type DeciderEthCircuit<C1, C2> = GenericOnchainDeciderCircuit<
+    C1,
+    C2,
+    CommittedInstance<C1>,
+    CommittedInstance<C1>,
+    Witness<C1>,
+    R1CS<CF1<C1>>,
+    R1CSMatricesVar<CF1<C1>, FpVar<CF1<C1>>>,
+    DeciderMovaGadget,
+>;
+
+/// returns an instance of the DeciderEthCircuit from the given Mova struct
+impl<
+        C1: Curve,
+        C2: Curve,
+        FC: FCircuit<C1::ScalarField>,
+        CS1: CommitmentScheme<C1, H>,
+        // enforce that the CS2 is Pedersen commitment scheme, since we're at Ethereum's EVM decider
+        CS2: CommitmentScheme<C2, H, ProverParams = PedersenParams<C2>>,
+        const H: bool,
+    > TryFrom<Mova<C1, C2, FC, CS1, CS2, H>> for DeciderEthCircuit<C1, C2>
+{
+    type Error = Error;
+
+    fn try_from(mova: Mova<C1, C2, FC, CS1, CS2, H>) -> Result<Self, Error> {
+        let mut transcript = PoseidonSponge::new_with_pp_hash(&mova.poseidon_config, mova.pp_hash);
+
+        // compute the U_{i+1}, W_{i+1}
+        let (W_i1, U_i1, proof, r_bits) =
+            NIFS::<C1, CS1, PoseidonSponge<C1::ScalarField>, H>::prove(
+                &mova.cs_pp,
+                &mova.r1cs,
+                &mut transcript,
+                &mova.W_i,
+                &mova.U_i,
+                &mova.w_i,
+                &mova.u_i,
+            )?;
+        let r_Fr = C1::ScalarField::from_bigint(BigInteger::from_bits_le(&r_bits))
+            .ok_or(Error::OutOfBounds)?;
+
+        // compute the KZG challenges used as inputs in the circuit
+        let kzg_challenges = KZGChallengesGadget::get_challenges_native(&mut transcript, &U_i1);
+
+        // get KZG evals
+        let kzg_evaluations = W_i1
+            .get_openings()
+            .iter()
+            .zip(&kzg_challenges)
+            .map(|((v, _), &c)| EvalGadget::evaluate_native(v, c))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            _avar: PhantomData,
+            arith: mova.r1cs,
+            cf_arith: mova.cf_r1cs,
+            cf_pedersen_params: mova.cf_cs_pp,
+            poseidon_config: mova.poseidon_config,
+            pp_hash: mova.pp_hash,
+            i: mova.i,
+            z_0: mova.z_0,
+            z_i: mova.z_i,
+            U_i: mova.U_i,
+            W_i: mova.W_i,
+            u_i: mova.u_i,
+            w_i: mova.w_i,
+            U_i1,
+            W_i1,
+            proof,
+            randomness: r_Fr,
+            cf_U_i: mova.cf_U_i,
+            cf_W_i: mova.cf_W_i,
+            kzg_challenges,
+            kzg_evaluations,
+        })
+    }
+}
+
+pub struct DeciderMovaGadget;
+
+impl<C: Curve>
+    DeciderEnabledNIFS<C, CommittedInstance<C>, CommittedInstance<C>, Witness<C>, R1CS<CF1<C>>>
+    for DeciderMovaGadget
+{
+    // the number of variables of the MLE of `E`
+    type ProofDummyCfg = usize;
+    type Proof = Proof<C>;
+    type RandomnessDummyCfg = ();
+    type Randomness = CF1<C>;
+
+    fn fold_field_elements_gadget(
+        arith: &R1CS<CF1<C>>,
+        transcript: &mut PoseidonSpongeVar<CF1<C>>,
+        U: CommittedInstanceVar<C>,
+        U_vec: Vec<FpVar<CF1<C>>>,
+        u: CommittedInstanceVar<C>,
+        proof: Proof<C>,
+        randomness: CF1<C>,
+    ) -> Result<CommittedInstanceVar<C>, SynthesisError> {
+        let cs = U.u.cs();
+        let alpha = FpVar::new_input(cs.clone(), || Ok(randomness))?;
+
+        // the evaluation point of the incoming instance must be the one derived from its public
+        // inputs and its commitment. At this point `transcript` has only absorbed `pp_hash`, so
+        // it is in the same state as the sponge used for the derivation in the IVC.
+        let n_vars = log2(arith.n_constraints()) as usize;
+        EvalPointGadget::<C>::get_eval_point_gadget::<PoseidonSponge<CF1<C>>, _>(
+            transcript, &u.x, &u.cmW, n_vars,
+        )?
+        .enforce_equal(&u.rE)?;
+
+        let proof = ProofVar::<C>::new_witness(cs.clone(), || Ok(proof))?;
+        let (new_U, r_bits) = NIFSGadget::verify(transcript, U, U_vec, u, Some(proof))?;
+        // the randomness used to fold the commitments outside of the circuit must be the
+        // in-circuit computed challenge
+        Boolean::le_bits_to_fp(&r_bits)?.enforce_equal(&alpha)?;
+        Ok(new_U)
+    }
+
+    fn fold_group_elements_native(
+        U_commitments: &[C],
+        u_commitments: &[C],
+        _: Option<Self::Proof>,
+        alpha: Self::Randomness,
+    ) -> Result<Vec<C>, Error> {
+        let U_cmW = U_commitments[0];
+        let u_cmW = u_commitments[0];
+        Ok(vec![U_cmW + u_cmW.mul(alpha)])
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ark_bn254::{Fr, G1Projective as Projective};
+    use ark_grumpkin::Projective as Projective2;
+    use ark_relations::gr1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+    use super::*;
+    use crate::commitment::pedersen::Pedersen;
+    use crate::folding::nova::PreprocessorParam;
+    use crate::frontend::utils::CubicFCircuit;
+    use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::FoldingScheme;
+
+    #[test]
+    fn test_decider_circuit() -> Result<(), Error> {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(())?;
+        let z_0 = vec![Fr::from(3_u32)];
+
+        type M = Mova<
+            Projective,
+            Projective2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let mova_params = M::preprocess(&mut rng, &prep_param)?;
+
+        // generate a Mova instance and do two steps of it, so that the running instance is not
+        // the dummy one anymore
+        let mut mova = M::init(&mova_params, F_circuit, z_0.clone())?;
+        mova.prove_step(&mut rng, (), None)?;
+        mova.prove_step(&mut rng, (), None)?;
+
+        let ivc_proof = mova.ivc_proof();
+        M::verify(mova_params.1, ivc_proof)?;
+
+        // load the DeciderEthCircuit from the Mova instance
+        let decider_circuit = DeciderEthCircuit::<Projective, Projective2>::try_from(mova)?;
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+
+        // generate the constraints and check that are satisfied by the inputs
+        decider_circuit.generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+}
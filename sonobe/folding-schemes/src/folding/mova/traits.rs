@@ -0,0 +1,35 @@
+use ark_r1cs_std::{eq::EqGadget, fields::fp::FpVar};
+use ark_relations::gr1cs::SynthesisError;
+
+use super::{CommittedInstanceVar, WitnessVar};
+use crate::arith::{r1cs::circuits::R1CSMatricesVar, ArithRelationGadget};
+use crate::utils::gadgets::{eval_mle, EquivalenceGadget, VectorGadget};
+use crate::Curve;
+
+/// In-circuit counterpart of Mova's [`crate::arith::ArithRelation`] implementation for R1CS.
+///
+/// Besides the relaxed R1CS check `Az ∘ Bz = u * Cz + E`, it enforces that the claimed evaluation
+/// `mleE` is the evaluation of the MLE of `E` at the point `rE`, since in Mova `E` is not
+/// committed.
+impl<C: Curve> ArithRelationGadget<WitnessVar<C>, CommittedInstanceVar<C>>
+    for R1CSMatricesVar<C::ScalarField, FpVar<C::ScalarField>>
+{
+    type Evaluation = (Vec<FpVar<C::ScalarField>>, Vec<FpVar<C::ScalarField>>);
+
+    fn eval_relation(
+        &self,
+        w: &WitnessVar<C>,
+        u: &CommittedInstanceVar<C>,
+    ) -> Result<Self::Evaluation, SynthesisError> {
+        self.eval_at_z(&[&[u.u.clone()][..], &u.x, &w.W].concat())
+    }
+
+    fn enforce_evaluation(
+        w: &WitnessVar<C>,
+        u: &CommittedInstanceVar<C>,
+        (AzBz, uCz): Self::Evaluation,
+    ) -> Result<(), SynthesisError> {
+        EquivalenceGadget::<C::ScalarField>::enforce_equivalent(&AzBz[..], &uCz.add(&w.E)?[..])?;
+        eval_mle(u.rE.len(), w.E.clone(), u.rE.clone()).enforce_equal(&u.mleE)
+    }
+}
@@ -6,18 +6,23 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_snark::SNARK;
 use ark_std::{
     rand::{CryptoRng, RngCore},
-    One, Zero,
+    One, UniformRand, Zero,
 };
 use core::marker::PhantomData;
 
-pub use super::decider_eth_circuit::DeciderEthCircuit;
-use super::decider_eth_circuit::DeciderNovaGadget;
+pub use super::decider_eth_circuit::{DeciderEthCircuit, DeciderEthZKCircuit};
+use super::decider_eth_circuit::{DeciderNovaGadget, DeciderNovaZKGadget, RandomizedFoldProof};
 use super::Nova;
+use crate::arith::Arith;
 use crate::folding::circuits::decider::DeciderEnabledNIFS;
-use crate::folding::traits::{InputizeNonNative, WitnessOps};
+use crate::folding::traits::{Inputize, InputizeNonNative, WitnessOps};
 use crate::frontend::FCircuit;
 use crate::{
-    commitment::{kzg::Proof as KZGProof, pedersen::Params as PedersenParams, CommitmentScheme},
+    commitment::{
+        kzg::{self, Proof as KZGProof, ProverKey as KZGProverKey},
+        pedersen::Params as PedersenParams,
+        CommitmentScheme,
+    },
     folding::traits::Dummy,
 };
 use crate::{Curve, Error};
@@ -47,6 +52,24 @@ where
     CS: CommitmentScheme<C, ProverChallenge = C::ScalarField, Challenge = C::ScalarField>,
     S: SNARK<C::ScalarField>,
 {
+    /// builds the proof from its parts, eg. when decoding it from the calldata of the onchain
+    /// verifier
+    pub fn new(
+        snark_proof: S::Proof,
+        kzg_proofs: [CS::Proof; 2],
+        cmT: C,
+        r: C::ScalarField,
+        kzg_challenges: [C::ScalarField; 2],
+    ) -> Self {
+        Self {
+            snark_proof,
+            kzg_proofs,
+            cmT,
+            r,
+            kzg_challenges,
+        }
+    }
+
     pub fn snark_proof(&self) -> &S::Proof {
         &self.snark_proof
     }
@@ -205,6 +228,304 @@ where
         })
     }
 
+    fn verify(
+        vp: Self::VerifierParam,
+        i: C1::ScalarField,
+        z_0: Vec<C1::ScalarField>,
+        z_i: Vec<C1::ScalarField>,
+        running_commitments: &Self::CommittedInstance,
+        incoming_commitments: &Self::CommittedInstance,
+        proof: &Self::Proof,
+    ) -> Result<bool, Error> {
+        verify::<C1, CS1, S>(
+            vp,
+            i,
+            z_0,
+            z_i,
+            running_commitments,
+            incoming_commitments,
+            proof,
+        )
+    }
+}
+
+/// Verifies a proof of the onchain [`Decider`]. Unlike [`DeciderTrait::verify`], it does not
+/// depend on the [`FCircuit`] nor on the [`FoldingScheme`], so that the proofs can be verified
+/// with only the [`VerifierParam`].
+pub fn verify<C1, CS1, S>(
+    vp: VerifierParam<C1, CS1::VerifierParams, S::VerifyingKey>,
+    i: C1::ScalarField,
+    z_0: Vec<C1::ScalarField>,
+    z_i: Vec<C1::ScalarField>,
+    // we don't use the instances at the verifier level, since we check them in-circuit
+    running_commitments: &[C1],
+    incoming_commitments: &[C1],
+    proof: &Proof<C1, CS1, S>,
+) -> Result<bool, Error>
+where
+    C1: Curve,
+    CS1: CommitmentScheme<
+        C1,
+        ProverChallenge = C1::ScalarField,
+        Challenge = C1::ScalarField,
+        Proof = KZGProof<C1>,
+    >,
+    S: SNARK<C1::ScalarField>,
+{
+    if i <= C1::ScalarField::one() {
+        return Err(Error::NotEnoughSteps);
+    }
+
+    let VerifierParam {
+        pp_hash,
+        snark_vp,
+        cs_vp,
+    } = vp;
+
+    // 6.2. Fold the commitments
+    let U_final_commitments = DeciderNovaGadget::fold_group_elements_native(
+        running_commitments,
+        incoming_commitments,
+        Some(proof.cmT),
+        proof.r,
+    )?;
+
+    let public_input = [
+        &[pp_hash, i][..],
+        &z_0,
+        &z_i,
+        &U_final_commitments.inputize_nonnative(),
+        &proof.kzg_challenges,
+        &proof.kzg_proofs.iter().map(|p| p.eval).collect::<Vec<_>>(),
+        &proof.cmT.inputize_nonnative(),
+    ]
+    .concat();
+
+    let snark_v = S::verify(&snark_vp, &public_input, &proof.snark_proof)
+        .map_err(|e| Error::Other(e.to_string()))?;
+    if !snark_v {
+        return Err(Error::SNARKVerificationFail);
+    }
+
+    // 7.3. Verify the KZG proofs
+    for ((cm, &c), pi) in U_final_commitments
+        .iter()
+        .zip(&proof.kzg_challenges)
+        .zip(&proof.kzg_proofs)
+    {
+        // we're at the Ethereum EVM case, so the CS1 is KZG commitments
+        CS1::verify_with_challenge(&cs_vp, c, cm, pi)?;
+    }
+
+    Ok(true)
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ZKProof<C, CS, S>
+where
+    C: Curve,
+    CS: CommitmentScheme<C, true, ProverChallenge = C::ScalarField, Challenge = C::ScalarField>,
+    S: SNARK<C::ScalarField>,
+{
+    snark_proof: S::Proof,
+    kzg_proofs: [CS::Proof; 2],
+    // cmT, U_r, cmT_r and r are values for the last two folds,
+    // U_{i+1}=NIFS.V(r[0], U_i, u_i, cmT) and U'_{i+1}=NIFS.V(r[1], U_{i+1}, U_r, cmT_r), and they
+    // are checked in-circuit
+    fold_proof: RandomizedFoldProof<C>,
+    r: [C::ScalarField; 2],
+    // the KZG challenges are provided by the prover, but in-circuit they are checked to match
+    // the in-circuit computed ones.
+    kzg_challenges: [C::ScalarField; 2],
+}
+
+impl<C, CS, S> ZKProof<C, CS, S>
+where
+    C: Curve,
+    CS: CommitmentScheme<C, true, ProverChallenge = C::ScalarField, Challenge = C::ScalarField>,
+    S: SNARK<C::ScalarField>,
+{
+    pub fn snark_proof(&self) -> &S::Proof {
+        &self.snark_proof
+    }
+
+    pub fn kzg_proofs(&self) -> &[CS::Proof; 2] {
+        &self.kzg_proofs
+    }
+
+    pub fn fold_proof(&self) -> &RandomizedFoldProof<C> {
+        &self.fold_proof
+    }
+
+    pub fn r(&self) -> [C::ScalarField; 2] {
+        self.r
+    }
+
+    pub fn kzg_challenges(&self) -> [C::ScalarField; 2] {
+        self.kzg_challenges
+    }
+}
+
+/// Zero-knowledge onchain Decider, for ethereum use cases. It decides a hiding Nova
+/// (`Nova<_, _, _, _, _, true>`) IVC proof through the [`DeciderEthZKCircuit`], which randomizes
+/// the folded instance before deciding it, and opens the (hiding) KZG commitments of the
+/// randomized instance, so that neither the proof nor its public inputs depend on the IVC
+/// witnesses beyond `i`, `z_0` and `z_i`. The commitments of the random instance are blinded with
+/// polynomials of degree `kzg::HIDING_BOUND`, so the openings do not reveal the blinding factors.
+/// Its proofs are verified onchain by the `NovaZKDecider` contract of the `solidity-verifiers` crate.
+#[derive(Clone, Debug)]
+pub struct ZKDecider<C1, C2, FC, CS1, CS2, S, FS> {
+    _c1: PhantomData<C1>,
+    _c2: PhantomData<C2>,
+    _fc: PhantomData<FC>,
+    _cs1: PhantomData<CS1>,
+    _cs2: PhantomData<CS2>,
+    _s: PhantomData<S>,
+    _fs: PhantomData<FS>,
+}
+
+impl<C1, C2, FC, CS1, CS2, S, FS> DeciderTrait<C1, C2, FC, FS>
+    for ZKDecider<C1, C2, FC, CS1, CS2, S, FS>
+where
+    C1: Curve<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: Curve,
+    FC: FCircuit<C1::ScalarField>,
+    // CS1 is a hiding KZG commitment, where challenge is C1::Fr elem
+    CS1: CommitmentScheme<
+        C1,
+        true,
+        ProverParams = KZGProverKey<'static, C1>,
+        ProverChallenge = C1::ScalarField,
+        Challenge = C1::ScalarField,
+        Proof = KZGProof<C1>,
+    >,
+    // enforce that the CS2 is Pedersen commitment scheme, since we're at Ethereum's EVM decider
+    CS2: CommitmentScheme<C2, true, ProverParams = PedersenParams<C2>>,
+    S: SNARK<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>,
+    // constrain FS into hiding Nova, since this is a Decider specifically for Nova
+    Nova<C1, C2, FC, CS1, CS2, true>: From<FS>,
+    crate::folding::nova::ProverParams<C1, C2, CS1, CS2, true>:
+        From<<FS as FoldingScheme<C1, C2, FC>>::ProverParam>,
+    crate::folding::nova::VerifierParams<C1, C2, CS1, CS2, true>:
+        From<<FS as FoldingScheme<C1, C2, FC>>::VerifierParam>,
+{
+    type PreprocessorParam = ((FS::ProverParam, FS::VerifierParam), usize);
+    type ProverParam = (S::ProvingKey, CS1::ProverParams);
+    type Proof = ZKProof<C1, CS1, S>;
+    type VerifierParam = VerifierParam<C1, CS1::VerifierParams, S::VerifyingKey>;
+    type PublicInput = Vec<C1::ScalarField>;
+    type CommittedInstance = Vec<C1>;
+
+    fn preprocess(
+        mut rng: impl RngCore + CryptoRng,
+        ((pp, vp), state_len): Self::PreprocessorParam,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
+        // get the FoldingScheme prover & verifier params from Nova
+        let nova_pp: <Nova<C1, C2, FC, CS1, CS2, true> as FoldingScheme<C1, C2, FC>>::ProverParam =
+            pp.into();
+        let nova_vp: <Nova<C1, C2, FC, CS1, CS2, true> as FoldingScheme<
+                    C1,
+                    C2,
+                    FC,
+                >>::VerifierParam = vp.into();
+
+        let pp_hash = nova_vp.pp_hash()?;
+        let io_len = nova_vp.r1cs.n_public_inputs();
+
+        let circuit = DeciderEthZKCircuit::<C1, C2>::dummy((
+            nova_vp.r1cs,
+            nova_vp.cf_r1cs,
+            nova_pp.cf_cs_pp,
+            nova_pp.poseidon_config,
+            io_len, // the random instance U_r has the same public inputs length as U_i
+            (),
+            state_len,
+            2, // Nova's running CommittedInstance contains 2 commitments
+        ));
+
+        // get the Groth16 specific setup for the circuit
+        let (g16_pk, g16_vk) = S::circuit_specific_setup(circuit, &mut rng)
+            .map_err(|e| Error::SNARKSetupFail(e.to_string()))?;
+
+        let pp = (g16_pk, nova_pp.cs_pp);
+        let vp = Self::VerifierParam {
+            pp_hash,
+            snark_vp: g16_vk,
+            cs_vp: nova_vp.cs_vp,
+        };
+        Ok((pp, vp))
+    }
+
+    fn prove(
+        mut rng: impl RngCore + CryptoRng,
+        pp: Self::ProverParam,
+        folding_scheme: FS,
+    ) -> Result<Self::Proof, Error> {
+        let (snark_pk, cs_pk): (S::ProvingKey, CS1::ProverParams) = pp;
+
+        // higher coefficients of the blinding polynomials of the random instance U_r
+        let U_r_blinding_coeffs = [
+            C1::ScalarField::rand(&mut rng),
+            C1::ScalarField::rand(&mut rng),
+        ];
+        let U_r_blinding = [
+            kzg::commit_blinding_polynomial(
+                &cs_pk,
+                &[C1::ScalarField::zero(), U_r_blinding_coeffs[0]],
+            )?,
+            kzg::commit_blinding_polynomial(
+                &cs_pk,
+                &[C1::ScalarField::zero(), U_r_blinding_coeffs[1]],
+            )?,
+        ];
+
+        let circuit = DeciderEthZKCircuit::<C1, C2>::try_from_randomized(
+            Nova::from(folding_scheme),
+            U_r_blinding,
+            &mut rng,
+        )?;
+
+        let fold_proof = circuit.proof.clone();
+        let r = circuit.randomness;
+
+        // U_r is folded in U'_{i+1} with the factor r_r for cmW and r_r^2 for cmE
+        let r_r = r[1];
+        let blinding_coeffs = [
+            r_r * U_r_blinding_coeffs[0],
+            r_r * r_r * U_r_blinding_coeffs[1],
+        ];
+
+        // get the challenges that have been already computed when preparing the circuit inputs in
+        // the above `try_from_randomized` call
+        let kzg_challenges = circuit.kzg_challenges.clone();
+
+        // generate KZG proofs, blinded with the blinding polynomials of the randomized witness
+        let kzg_proofs = circuit
+            .W_i1
+            .get_openings()
+            .iter()
+            .zip(&kzg_challenges)
+            .zip(&blinding_coeffs)
+            .map(|(((v, blind), &c), &coeff)| kzg::open(&cs_pk, c, v, &[*blind, coeff]))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let snark_proof =
+            S::prove(&snark_pk, circuit, &mut rng).map_err(|e| Error::Other(e.to_string()))?;
+
+        Ok(Self::Proof {
+            snark_proof,
+            fold_proof,
+            r,
+            kzg_proofs: kzg_proofs
+                .try_into()
+                .map_err(|e: Vec<_>| Error::NotExpectedLength(e.len(), 2))?,
+            kzg_challenges: kzg_challenges
+                .try_into()
+                .map_err(|e: Vec<_>| Error::NotExpectedLength(e.len(), 2))?,
+        })
+    }
+
     fn verify(
         vp: Self::VerifierParam,
         i: C1::ScalarField,
@@ -225,11 +546,11 @@ where
             cs_vp,
         } = vp;
 
-        // 6.2. Fold the commitments
-        let U_final_commitments = DeciderNovaGadget::fold_group_elements_native(
+        // 6.2. Fold the commitments, including the ones of the random instance
+        let U_final_commitments = DeciderNovaZKGadget::fold_group_elements_native(
             running_commitments,
             incoming_commitments,
-            Some(proof.cmT),
+            Some(proof.fold_proof.clone()),
             proof.r,
         )?;
 
@@ -240,7 +561,9 @@ where
             &U_final_commitments.inputize_nonnative(),
             &proof.kzg_challenges,
             &proof.kzg_proofs.iter().map(|p| p.eval).collect::<Vec<_>>(),
-            &proof.cmT.inputize_nonnative(),
+            &proof.fold_proof.cmT.inputize_nonnative(),
+            &proof.fold_proof.U_r.inputize(),
+            &proof.fold_proof.cmT_r.inputize_nonnative(),
         ]
         .concat();
 
@@ -354,6 +677,60 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_zk_decider() -> Result<(), Error> {
+        // use hiding Nova, with hiding KZG, as FoldingScheme
+        type N = Nova<
+            Projective,
+            Projective2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254, true>,
+            Pedersen<Projective2, true>,
+            true,
+        >;
+        type D = ZKDecider<
+            Projective,
+            Projective2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254, true>,
+            Pedersen<Projective2, true>,
+            Groth16<Bn254>,
+            N,
+        >;
+
+        let mut rng = rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(())?;
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let preprocessor_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let nova_params = N::preprocess(&mut rng, &preprocessor_param)?;
+        let mut nova = N::init(&nova_params, F_circuit, z_0)?;
+
+        let (decider_pp, decider_vp) =
+            D::preprocess(&mut rng, (nova_params, F_circuit.state_len()))?;
+
+        nova.prove_step(&mut rng, (), None)?;
+        nova.prove_step(&mut rng, (), None)?;
+
+        let proof = D::prove(rng, decider_pp, nova.clone())?;
+        // the KZG proofs open hiding commitments
+        assert!(proof.kzg_proofs.iter().all(|p| !p.random_v.is_zero()));
+
+        let verified = D::verify(
+            decider_vp,
+            nova.i,
+            nova.z_0.clone(),
+            nova.z_i.clone(),
+            &nova.U_i.get_commitments(),
+            &nova.u_i.get_commitments(),
+            &proof,
+        )?;
+        assert!(verified);
+        Ok(())
+    }
+
     // Test to check the serialization and deserialization of diverse Decider related parameters.
     // This test is the same test as `test_decider` but it serializes values and then uses the
     // deserialized values to continue the checks.
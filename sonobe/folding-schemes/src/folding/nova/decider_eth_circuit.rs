@@ -2,7 +2,10 @@
 /// other more efficient approaches can be used.
 /// More details can be found at the documentation page:
 /// https://privacy-scaling-explorations.github.io/sonobe-docs/design/nova-decider-onchain.html
-use ark_crypto_primitives::sponge::poseidon::{constraints::PoseidonSpongeVar, PoseidonSponge};
+use ark_crypto_primitives::sponge::{
+    constraints::AbsorbGadget,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonSponge},
+};
 use ark_ff::{BigInteger, PrimeField};
 use ark_r1cs_std::{
     alloc::{AllocVar, AllocationMode},
@@ -10,7 +13,8 @@ use ark_r1cs_std::{
     GR1CSVar,
 };
 use ark_relations::gr1cs::{Namespace, SynthesisError};
-use ark_std::{borrow::Borrow, marker::PhantomData};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{borrow::Borrow, marker::PhantomData, rand::RngCore};
 
 use super::{
     nifs::nova_circuits::{CommittedInstanceVar, NIFSGadget},
@@ -18,7 +22,10 @@ use super::{
     CommittedInstance, Nova, Witness,
 };
 use crate::{
-    arith::r1cs::{circuits::R1CSMatricesVar, R1CS},
+    arith::{
+        r1cs::{circuits::R1CSMatricesVar, R1CS},
+        ArithSampler,
+    },
     commitment::{pedersen::Params as PedersenParams, CommitmentScheme},
     folding::{
         circuits::{
@@ -29,7 +36,7 @@ use crate::{
             nonnative::affine::NonNativeAffineVar,
             CF1,
         },
-        traits::{WitnessOps, WitnessVarOps},
+        traits::{Dummy, WitnessOps, WitnessVarOps},
     },
     frontend::FCircuit,
     transcript::Transcript,
@@ -86,6 +93,19 @@ pub type DeciderEthCircuit<C1, C2> = GenericOnchainDeciderCircuit<
     DeciderNovaGadget,
 >;
 
+/// Zero-knowledge variant of [`DeciderEthCircuit`], where `U_{i+1}` is further folded with a
+/// random satisfying instance before being decided. See [`DeciderNovaZKGadget`].
+pub type DeciderEthZKCircuit<C1, C2> = GenericOnchainDeciderCircuit<
+    C1,
+    C2,
+    CommittedInstance<C1>,
+    CommittedInstance<C1>,
+    Witness<C1>,
+    R1CS<CF1<C1>>,
+    R1CSMatricesVar<CF1<C1>, FpVar<CF1<C1>>>,
+    DeciderNovaZKGadget,
+>;
+
 /// returns an instance of the DeciderEthCircuit from the given Nova struct
 impl<
         C1: Curve,
@@ -198,10 +218,195 @@ impl<C: Curve>
     }
 }
 
+/// Proof of the two folds performed by the [`DeciderEthZKCircuit`] prover:
+/// `U_{i+1} = NIFS.V(U_i, u_i, cmT)` followed by `U'_{i+1} = NIFS.V(U_{i+1}, U_r, cmT_r)`, where
+/// `U_r` is a random satisfying relaxed R1CS instance.
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RandomizedFoldProof<C: Curve> {
+    pub cmT: C,
+    pub U_r: CommittedInstance<C>,
+    pub cmT_r: C,
+}
+
+impl<C: Curve> Dummy<usize> for RandomizedFoldProof<C> {
+    fn dummy(io_len: usize) -> Self {
+        Self {
+            cmT: C::zero(),
+            U_r: CommittedInstance::dummy(io_len),
+            cmT_r: C::zero(),
+        }
+    }
+}
+
+/// returns an instance of the DeciderEthZKCircuit from the given hiding Nova struct
+impl<C1: Curve, C2: Curve> DeciderEthZKCircuit<C1, C2> {
+    /// Prepares the zero-knowledge decider circuit, implementing the Use-case-2 described in
+    /// [`super::zk`]: before deciding, the folded instance `U_{i+1}` is randomized by folding it
+    /// with a random satisfying instance `U_r`, as in appendix D.4 of
+    /// [HyperNova](https://eprint.iacr.org/2023/573.pdf). The witness `W'_{i+1}` that is opened by
+    /// the KZG proofs is thus masked by the random `W_r`, and the hiding commitments of `U_i` and
+    /// `u_i` are never opened.
+    ///
+    /// `U_r_blinding` is added to the commitments `(cmW, cmE)` of `U_r`: it commits to the higher
+    /// coefficients of their blinding polynomials (see `commitment::kzg`), so that the openings of
+    /// `U'_{i+1}` do not reveal its blinding factors.
+    pub fn try_from_randomized<
+        FC: FCircuit<C1::ScalarField>,
+        CS1: CommitmentScheme<C1, true>,
+        // enforce that the CS2 is Pedersen commitment scheme, since we're at Ethereum's EVM decider
+        CS2: CommitmentScheme<C2, true, ProverParams = PedersenParams<C2>>,
+    >(
+        nova: Nova<C1, C2, FC, CS1, CS2, true>,
+        U_r_blinding: [C1; 2],
+        mut rng: impl RngCore,
+    ) -> Result<Self, Error> {
+        let mut transcript = PoseidonSponge::new_with_pp_hash(&nova.poseidon_config, nova.pp_hash);
+
+        // compute the U_{i+1}, W_{i+1}
+        let (W_f, U_f, cmT, r_bits) =
+            NIFS::<C1, CS1, PoseidonSponge<C1::ScalarField>, true>::prove(
+                &nova.cs_pp,
+                &nova.r1cs,
+                &mut transcript,
+                &nova.W_i,
+                &nova.U_i,
+                &nova.w_i,
+                &nova.u_i,
+            )?;
+        let r_Fr = C1::ScalarField::from_bigint(BigInteger::from_bits_le(&r_bits))
+            .ok_or(Error::OutOfBounds)?;
+
+        // sample a random satisfying instance-witness pair (U_r, W_r)
+        let (W_r, mut U_r) = nova
+            .r1cs
+            .sample_witness_instance::<CS1>(&nova.cs_pp, &mut rng)?;
+        U_r.cmW += U_r_blinding[0];
+        U_r.cmE += U_r_blinding[1];
+
+        // compute U'_{i+1}, W'_{i+1}. The commitments of U_{i+1} are not available in-circuit, so
+        // they are not absorbed when computing the challenge; they are already bound by the
+        // transcript, which absorbed U_i, u_i and cmT.
+        let U_f_absorbed = CommittedInstance {
+            cmE: C1::zero(),
+            cmW: C1::zero(),
+            ..U_f.clone()
+        };
+        let (W_i1, _, cmT_r, r_r_bits) =
+            NIFS::<C1, CS1, PoseidonSponge<C1::ScalarField>, true>::prove(
+                &nova.cs_pp,
+                &nova.r1cs,
+                &mut transcript,
+                &W_f,
+                &U_f_absorbed,
+                &W_r,
+                &U_r,
+            )?;
+        let r_r_Fr = C1::ScalarField::from_bigint(BigInteger::from_bits_le(&r_r_bits))
+            .ok_or(Error::OutOfBounds)?;
+        let U_i1 = NIFS::<C1, CS1, PoseidonSponge<C1::ScalarField>, true>::fold_committed_instances(
+            r_r_Fr, &U_f, &U_r, &cmT_r,
+        );
+
+        // compute the KZG challenges used as inputs in the circuit
+        let kzg_challenges = KZGChallengesGadget::get_challenges_native(&mut transcript, &U_i1);
+
+        // get KZG evals
+        let kzg_evaluations = W_i1
+            .get_openings()
+            .iter()
+            .zip(&kzg_challenges)
+            .map(|((v, _), &c)| EvalGadget::evaluate_native(v, c))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            _avar: PhantomData,
+            arith: nova.r1cs,
+            cf_arith: nova.cf_r1cs,
+            cf_pedersen_params: nova.cf_cs_pp,
+            poseidon_config: nova.poseidon_config,
+            pp_hash: nova.pp_hash,
+            i: nova.i,
+            z_0: nova.z_0,
+            z_i: nova.z_i,
+            U_i: nova.U_i,
+            W_i: nova.W_i,
+            u_i: nova.u_i,
+            w_i: nova.w_i,
+            U_i1,
+            W_i1,
+            proof: RandomizedFoldProof { cmT, U_r, cmT_r },
+            randomness: [r_Fr, r_r_Fr],
+            cf_U_i: nova.cf_U_i,
+            cf_W_i: nova.cf_W_i,
+            kzg_challenges,
+            kzg_evaluations,
+        })
+    }
+}
+
+/// Same as [`DeciderNovaGadget`], but folding `U_{i+1}` with the random instance `U_r` of the
+/// [`RandomizedFoldProof`] before returning it.
+pub struct DeciderNovaZKGadget;
+
+impl<C: Curve>
+    DeciderEnabledNIFS<C, CommittedInstance<C>, CommittedInstance<C>, Witness<C>, R1CS<CF1<C>>>
+    for DeciderNovaZKGadget
+{
+    type ProofDummyCfg = usize;
+    type Proof = RandomizedFoldProof<C>;
+    type RandomnessDummyCfg = ();
+    type Randomness = [CF1<C>; 2];
+
+    fn fold_field_elements_gadget(
+        _arith: &R1CS<CF1<C>>,
+        transcript: &mut PoseidonSpongeVar<CF1<C>>,
+        U: CommittedInstanceVar<C>,
+        U_vec: Vec<FpVar<CF1<C>>>,
+        u: CommittedInstanceVar<C>,
+        proof: RandomizedFoldProof<C>,
+        _randomness: [CF1<C>; 2],
+    ) -> Result<CommittedInstanceVar<C>, SynthesisError> {
+        let cs = U.u.cs();
+        let cmT = NonNativeAffineVar::new_input(cs.clone(), || Ok(proof.cmT))?;
+        let U_r = CommittedInstanceVar::new_input(cs.clone(), || Ok(proof.U_r))?;
+        let cmT_r = NonNativeAffineVar::new_input(cs.clone(), || Ok(proof.cmT_r))?;
+
+        let (U_f, _) = NIFSGadget::verify(transcript, U, U_vec, u, Some(cmT))?;
+        // the commitments of `U_f` are constant zeros, matching the native prover, which does not
+        // absorb them either
+        let U_f_vec = U_f.to_sponge_field_elements()?;
+        let (new_U, _) = NIFSGadget::verify(transcript, U_f, U_f_vec, U_r, Some(cmT_r))?;
+        Ok(new_U)
+    }
+
+    fn fold_group_elements_native(
+        U_commitments: &[C],
+        u_commitments: &[C],
+        proof: Option<Self::Proof>,
+        randomness: Self::Randomness,
+    ) -> Result<Vec<C>, Error> {
+        let proof = proof.ok_or(Error::Empty)?;
+        let [r, r_r] = randomness;
+        let U_f_commitments = <DeciderNovaGadget as DeciderEnabledNIFS<
+            C,
+            CommittedInstance<C>,
+            CommittedInstance<C>,
+            Witness<C>,
+            R1CS<CF1<C>>,
+        >>::fold_group_elements_native(
+            U_commitments, u_commitments, Some(proof.cmT), r
+        )?;
+        let cmW = U_f_commitments[0] + proof.U_r.cmW.mul(r_r);
+        let cmE = U_f_commitments[1] + proof.cmT_r.mul(r_r) + proof.U_r.cmE.mul(r_r * r_r);
+        Ok(vec![cmW, cmE])
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use ark_pallas::{Fr, Projective};
     use ark_relations::gr1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::Zero;
     use ark_vesta::Projective as Projective2;
 
     use super::*;
@@ -255,4 +460,75 @@ pub mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_decider_zk_circuit() -> Result<(), Error> {
+        use crate::arith::ArithRelation;
+        use crate::folding::traits::CommittedInstanceOps;
+
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(())?;
+        let z_0 = vec![Fr::from(3_u32)];
+
+        type N = Nova<
+            Projective,
+            Projective2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective, true>,
+            Pedersen<Projective2, true>,
+            true,
+        >;
+
+        let prep_param = PreprocessorParam::<
+            Projective,
+            Projective2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective, true>,
+            Pedersen<Projective2, true>,
+            true,
+        >::new(poseidon_config, F_circuit);
+        let nova_params = N::preprocess(&mut rng, &prep_param)?;
+
+        // generate a hiding Nova instance and do a couple of steps of it
+        let mut nova = N::init(&nova_params, F_circuit, z_0.clone())?;
+        nova.prove_step(&mut rng, (), None)?;
+        nova.prove_step(&mut rng, (), None)?;
+        let ivc_proof = nova.ivc_proof();
+        N::verify(nova_params.1, ivc_proof)?;
+
+        // two randomizations of the same IVC proof decide different instances
+        let decider_circuit = DeciderEthZKCircuit::<Projective, Projective2>::try_from_randomized(
+            nova.clone(),
+            [Projective::zero(); 2],
+            &mut rng,
+        )?;
+        let other_decider_circuit =
+            DeciderEthZKCircuit::<Projective, Projective2>::try_from_randomized(
+                nova.clone(),
+                [Projective::zero(); 2],
+                &mut rng,
+            )?;
+        assert_ne!(decider_circuit.W_i1.W, other_decider_circuit.W_i1.W);
+        nova.r1cs
+            .check_relation(&decider_circuit.W_i1, &decider_circuit.U_i1)?;
+
+        // the verifier recovers the commitments of the randomized instance from the IVC ones
+        let U_i1_commitments = DeciderNovaZKGadget::fold_group_elements_native(
+            &nova.U_i.get_commitments(),
+            &nova.u_i.get_commitments(),
+            Some(decider_circuit.proof.clone()),
+            decider_circuit.randomness,
+        )?;
+        assert_eq!(U_i1_commitments, decider_circuit.U_i1.get_commitments());
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+
+        // generate the constraints and check that are satisfied by the inputs
+        decider_circuit.generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+
+        Ok(())
+    }
 }
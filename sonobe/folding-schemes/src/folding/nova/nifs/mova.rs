@@ -1,12 +1,13 @@
 /// This module contains the implementation the NIFSTrait for the
 /// [Mova](https://eprint.iacr.org/2024/1220.pdf) NIFS (Non-Interactive Folding Scheme).
 use ark_crypto_primitives::sponge::Absorb;
-use ark_ff::PrimeField;
+use ark_ff::{BigInteger, PrimeField};
 use ark_poly::Polynomial;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{log2, marker::PhantomData, rand::RngCore, One, UniformRand, Zero};
 
 use super::{
+    mova_circuits::{CommittedInstanceVar, WitnessVar},
     nova::NIFS as NovaNIFS,
     pointvsline::{PointVsLine, PointVsLineProof, PointvsLineEvaluationClaim},
     NIFSTrait,
@@ -14,7 +15,7 @@ use super::{
 use crate::arith::{r1cs::R1CS, Arith, ArithRelation};
 use crate::commitment::CommitmentScheme;
 use crate::folding::circuits::CF1;
-use crate::folding::traits::Dummy;
+use crate::folding::traits::{CommittedInstanceOps, Dummy, Inputize, WitnessOps};
 use crate::transcript::Transcript;
 use crate::utils::{
     mle::dense_vec_to_dense_mle,
@@ -59,6 +60,47 @@ impl<C: Curve> Dummy<usize> for CommittedInstance<C> {
     }
 }
 
+impl<C: Curve> Dummy<&R1CS<CF1<C>>> for CommittedInstance<C> {
+    /// The dummy instance has an evaluation point `rE` of `log2(n_constraints)` variables, which
+    /// is the number of variables of the MLE of `E`.
+    fn dummy(r1cs: &R1CS<CF1<C>>) -> Self {
+        Self {
+            rE: vec![C::ScalarField::zero(); log2(r1cs.n_constraints()) as usize],
+            mleE: C::ScalarField::zero(),
+            u: C::ScalarField::zero(),
+            cmW: C::zero(),
+            x: vec![C::ScalarField::zero(); r1cs.n_public_inputs()],
+        }
+    }
+}
+
+impl<C: Curve> CommittedInstanceOps<C> for CommittedInstance<C> {
+    type Var = CommittedInstanceVar<C>;
+
+    fn get_commitments(&self) -> Vec<C> {
+        vec![self.cmW]
+    }
+
+    fn is_incoming(&self) -> bool {
+        self.u == One::one() && self.mleE == Zero::zero()
+    }
+}
+
+impl<C: Curve> Inputize<CF1<C>> for CommittedInstance<C> {
+    /// Returns the internal representation in the same order as how the value
+    /// is allocated in `CommittedInstanceVar::new_input`.
+    fn inputize(&self) -> Vec<CF1<C>> {
+        [
+            &[self.u][..],
+            &self.x,
+            &self.rE,
+            &[self.mleE],
+            &self.cmW.inputize_nonnative(),
+        ]
+        .concat()
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct Witness<C: Curve> {
     pub E: Vec<C::ScalarField>,
@@ -76,6 +118,15 @@ impl<C: Curve> Dummy<&R1CS<C::ScalarField>> for Witness<C> {
     }
 }
 
+impl<C: Curve> WitnessOps<C::ScalarField> for Witness<C> {
+    type Var = WitnessVar<C>;
+
+    // `E` is not committed in Mova, hence the only opening is the one of `W`
+    fn get_openings(&self) -> Vec<(&[C::ScalarField], C::ScalarField)> {
+        vec![(&self.W, self.rW)]
+    }
+}
+
 impl<C: Curve> Witness<C> {
     pub fn new<const H: bool>(w: Vec<C::ScalarField>, e_len: usize, mut rng: impl RngCore) -> Self {
         let rW = if H {
@@ -122,6 +173,19 @@ pub struct Proof<C: Curve> {
     pub rE_prime: Vec<C::ScalarField>,
 }
 
+impl<C: Curve> Dummy<usize> for Proof<C> {
+    /// Returns a dummy proof for instances whose evaluation point `rE` has `n_vars` variables.
+    fn dummy(n_vars: usize) -> Self {
+        Self {
+            h_proof: PointVsLineProof::dummy(n_vars),
+            mleE1_prime: C::ScalarField::zero(),
+            mleE2_prime: C::ScalarField::zero(),
+            mleT: C::ScalarField::zero(),
+            rE_prime: vec![C::ScalarField::zero(); n_vars],
+        }
+    }
+}
+
 /// Implements the Non-Interactive Folding Scheme described in section 4 of
 /// [Mova](https://eprint.iacr.org/2024/1220.pdf).
 /// `H` specifies whether the NIFS will use a blinding factor
@@ -264,13 +328,7 @@ impl<C: Curve, CS: CommitmentScheme<C, H>, T: Transcript<C::ScalarField>, const
             mleT: mleT_evaluated,
             rE_prime,
         };
-        Ok((
-            w,
-            ci,
-            proof,
-            vec![], // r_bits, returned to be passed as inputs to the circuit, not used at the
-                    // current impl status
-        ))
+        Ok((w, ci, proof, Self::challenge_to_bits(alpha)))
     }
 
     /// [Mova](https://eprint.iacr.org/2024/1220.pdf)'s section 4. It verifies the results from the proof
@@ -310,7 +368,7 @@ impl<C: Curve, CS: CommitmentScheme<C, H>, T: Transcript<C::ScalarField>, const
                 &proof.mleE2_prime,
                 &proof.mleT,
             )?,
-            vec![],
+            Self::challenge_to_bits(alpha),
         ))
     }
 }
@@ -318,6 +376,15 @@ impl<C: Curve, CS: CommitmentScheme<C, H>, T: Transcript<C::ScalarField>, const
 impl<C: Curve, CS: CommitmentScheme<C, H>, T: Transcript<C::ScalarField>, const H: bool>
     NIFS<C, CS, T, H>
 {
+    /// Returns the little-endian bits of the folding challenge `alpha`. Unlike Nova, `alpha` is a
+    /// full field element, so its bit length is `MODULUS_BIT_SIZE`, which matches the output of
+    /// `FpVar::to_bits_le` in-circuit.
+    fn challenge_to_bits(alpha: C::ScalarField) -> Vec<bool> {
+        let mut bits = alpha.into_bigint().to_bits_le();
+        bits.resize(C::ScalarField::MODULUS_BIT_SIZE as usize, false);
+        bits
+    }
+
     // Protocol 7 - point 3 (15)
     fn fold_committed_instance(
         a: C::ScalarField,
@@ -362,10 +429,21 @@ impl<C: Curve> ArithRelation<Witness<C>, CommittedInstance<C>> for R1CS<CF1<C>>
 
     fn check_evaluation(
         w: &Witness<C>,
-        _u: &CommittedInstance<C>,
+        u: &CommittedInstance<C>,
         e: Self::Evaluation,
     ) -> Result<(), Error> {
-        (w.E == e).then_some(()).ok_or(Error::NotSatisfied)
+        (w.E == e).then_some(()).ok_or(Error::NotSatisfied)?;
+
+        // the claimed evaluation `mleE` must be the evaluation of the MLE of `E` at `rE`
+        let mut mleE = C::ScalarField::zero();
+        if !is_zero_vec::<C::ScalarField>(&w.E) {
+            let n_vars = log2(w.E.len()) as usize;
+            if u.rE.len() != n_vars {
+                return Err(Error::NotExpectedLength(u.rE.len(), n_vars));
+            }
+            mleE = dense_vec_to_dense_mle(n_vars, &w.E).evaluate(&u.rE);
+        }
+        (mleE == u.mleE).then_some(()).ok_or(Error::NotSatisfied)
     }
 }
 
@@ -0,0 +1,397 @@
+/// contains [Mova](https://eprint.iacr.org/2024/1220.pdf) NIFS related circuits
+use ark_crypto_primitives::sponge::{constraints::AbsorbGadget, CryptographicSponge};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    convert::ToBitsGadget,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    poly::polynomial::univariate::dense::DensePolynomialVar,
+    uint8::UInt8,
+};
+use ark_relations::gr1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use core::{borrow::Borrow, marker::PhantomData};
+
+use super::mova::{CommittedInstance, Proof, Witness};
+use super::pointvsline::padded_coeffs;
+use super::NIFSGadgetTrait;
+use crate::folding::traits::{CommittedInstanceVarOps, WitnessVarOps};
+use crate::transcript::TranscriptVar;
+use crate::Curve;
+use crate::{
+    folding::circuits::{nonnative::affine::NonNativeAffineVar, CF1},
+    transcript::AbsorbNonNativeGadget,
+};
+
+/// CommittedInstanceVar contains the u, x, rE and mleE values which are folded on the main Mova
+/// constraints field (E1::Fr, where E1 is the main curve), and the commitment cmW, which is
+/// represented non-natively over the constraint field.
+#[derive(Debug, Clone)]
+pub struct CommittedInstanceVar<C: Curve> {
+    pub u: FpVar<C::ScalarField>,
+    pub x: Vec<FpVar<C::ScalarField>>,
+    pub rE: Vec<FpVar<C::ScalarField>>,
+    pub mleE: FpVar<C::ScalarField>,
+    pub cmW: NonNativeAffineVar<C>,
+}
+
+impl<C: Curve> AllocVar<CommittedInstance<C>, CF1<C>> for CommittedInstanceVar<C> {
+    fn new_variable<T: Borrow<CommittedInstance<C>>>(
+        cs: impl Into<Namespace<CF1<C>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|val| {
+            let cs = cs.into();
+
+            let u = FpVar::<C::ScalarField>::new_variable(cs.clone(), || Ok(val.borrow().u), mode)?;
+            let x: Vec<FpVar<C::ScalarField>> =
+                Vec::new_variable(cs.clone(), || Ok(val.borrow().x.clone()), mode)?;
+            let rE: Vec<FpVar<C::ScalarField>> =
+                Vec::new_variable(cs.clone(), || Ok(val.borrow().rE.clone()), mode)?;
+            let mleE =
+                FpVar::<C::ScalarField>::new_variable(cs.clone(), || Ok(val.borrow().mleE), mode)?;
+
+            let cmW =
+                NonNativeAffineVar::<C>::new_variable(cs.clone(), || Ok(val.borrow().cmW), mode)?;
+
+            Ok(Self {
+                u,
+                x,
+                rE,
+                mleE,
+                cmW,
+            })
+        })
+    }
+}
+
+impl<C: Curve> AbsorbGadget<C::ScalarField> for CommittedInstanceVar<C> {
+    fn to_sponge_bytes(&self) -> Result<Vec<UInt8<C::ScalarField>>, SynthesisError> {
+        FpVar::batch_to_sponge_bytes(&self.to_sponge_field_elements()?)
+    }
+
+    fn to_sponge_field_elements(&self) -> Result<Vec<FpVar<C::ScalarField>>, SynthesisError> {
+        Ok([
+            vec![self.u.clone()],
+            self.x.clone(),
+            self.rE.clone(),
+            vec![self.mleE.clone()],
+            self.cmW.to_native_sponge_field_elements()?,
+        ]
+        .concat())
+    }
+}
+
+impl<C: Curve> CommittedInstanceVarOps<C> for CommittedInstanceVar<C> {
+    type PointVar = NonNativeAffineVar<C>;
+
+    fn get_commitments(&self) -> Vec<Self::PointVar> {
+        vec![self.cmW.clone()]
+    }
+
+    fn get_public_inputs(&self) -> &[FpVar<CF1<C>>] {
+        &self.x
+    }
+
+    fn enforce_incoming(&self) -> Result<(), SynthesisError> {
+        self.mleE.enforce_equal(&FpVar::zero())?;
+        self.u.enforce_equal(&FpVar::one())
+    }
+
+    fn enforce_partial_equal(&self, other: &Self) -> Result<(), SynthesisError> {
+        self.u.enforce_equal(&other.u)?;
+        self.x.enforce_equal(&other.x)?;
+        self.rE.enforce_equal(&other.rE)?;
+        self.mleE.enforce_equal(&other.mleE)
+    }
+}
+
+/// In-circuit representation of the Mova [`Witness`].
+#[derive(Debug, Clone)]
+pub struct WitnessVar<C: Curve> {
+    pub E: Vec<FpVar<C::ScalarField>>,
+    pub W: Vec<FpVar<C::ScalarField>>,
+    pub rW: FpVar<C::ScalarField>,
+}
+
+impl<C: Curve> AllocVar<Witness<C>, CF1<C>> for WitnessVar<C> {
+    fn new_variable<T: Borrow<Witness<C>>>(
+        cs: impl Into<Namespace<CF1<C>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|val| {
+            let cs = cs.into();
+
+            let E = Vec::new_variable(cs.clone(), || Ok(val.borrow().E.clone()), mode)?;
+            let W = Vec::new_variable(cs.clone(), || Ok(val.borrow().W.clone()), mode)?;
+            let rW = FpVar::new_variable(cs.clone(), || Ok(val.borrow().rW), mode)?;
+
+            Ok(Self { E, W, rW })
+        })
+    }
+}
+
+impl<C: Curve> WitnessVarOps<C::ScalarField> for WitnessVar<C> {
+    fn get_openings(&self) -> Vec<(&[FpVar<C::ScalarField>], FpVar<C::ScalarField>)> {
+        vec![(&self.W, self.rW.clone())]
+    }
+}
+
+/// In-circuit representation of the Mova [`Proof`]. The `rE_prime` of the native proof is not
+/// allocated, since it is recomputed by the in-circuit verifier.
+#[derive(Debug, Clone)]
+pub struct ProofVar<C: Curve> {
+    pub h1: DensePolynomialVar<C::ScalarField>,
+    pub h2: DensePolynomialVar<C::ScalarField>,
+    pub mleE1_prime: FpVar<C::ScalarField>,
+    pub mleE2_prime: FpVar<C::ScalarField>,
+    pub mleT: FpVar<C::ScalarField>,
+}
+
+impl<C: Curve> AllocVar<Proof<C>, CF1<C>> for ProofVar<C> {
+    fn new_variable<T: Borrow<Proof<C>>>(
+        cs: impl Into<Namespace<CF1<C>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let val = val.borrow();
+            // `h1` and `h2` have degree `n_vars`, where `n_vars` is the length of `rE_prime`
+            let n_vars = val.rE_prime.len();
+
+            let h1 = DensePolynomialVar::from_coefficients_vec(Vec::new_variable(
+                cs.clone(),
+                || Ok(padded_coeffs(&val.h_proof.h1, n_vars)),
+                mode,
+            )?);
+            let h2 = DensePolynomialVar::from_coefficients_vec(Vec::new_variable(
+                cs.clone(),
+                || Ok(padded_coeffs(&val.h_proof.h2, n_vars)),
+                mode,
+            )?);
+            let mleE1_prime = FpVar::new_variable(cs.clone(), || Ok(val.mleE1_prime), mode)?;
+            let mleE2_prime = FpVar::new_variable(cs.clone(), || Ok(val.mleE2_prime), mode)?;
+            let mleT = FpVar::new_variable(cs.clone(), || Ok(val.mleT), mode)?;
+
+            Ok(Self {
+                h1,
+                h2,
+                mleE1_prime,
+                mleE2_prime,
+                mleT,
+            })
+        })
+    }
+}
+
+/// Implements the circuit that does the checks of the Non-Interactive Folding Scheme Verifier
+/// described in section 4 of [Mova](https://eprint.iacr.org/2024/1220.pdf), including the
+/// Points-vs-Line verifier, where the cmW check is delegated to the CycleFold circuit.
+pub struct NIFSGadget<C: Curve, S: CryptographicSponge, T: TranscriptVar<CF1<C>, S>> {
+    _c: PhantomData<C>,
+    _s: PhantomData<S>,
+    _t: PhantomData<T>,
+}
+
+impl<C, S, T> NIFSGadgetTrait<C, S, T> for NIFSGadget<C, S, T>
+where
+    C: Curve,
+    S: CryptographicSponge,
+    T: TranscriptVar<CF1<C>, S>,
+{
+    type CommittedInstance = CommittedInstance<C>;
+    type CommittedInstanceVar = CommittedInstanceVar<C>;
+    type Proof = Proof<C>;
+    type ProofVar = ProofVar<C>;
+
+    fn verify(
+        transcript: &mut T,
+        U_i: Self::CommittedInstanceVar,
+        // U_i_vec is passed to reuse the already computed U_i_vec from previous methods
+        U_i_vec: Vec<FpVar<CF1<C>>>,
+        u_i: Self::CommittedInstanceVar,
+        proof: Option<Self::ProofVar>,
+    ) -> Result<(Self::CommittedInstanceVar, Vec<Boolean<CF1<C>>>), SynthesisError> {
+        let proof = proof.ok_or(SynthesisError::AssignmentMissing)?;
+
+        transcript.absorb(&U_i_vec)?;
+        transcript.absorb(&u_i)?;
+
+        // Points-vs-Line verification (protocol 6)
+        proof.h1.coeffs[0].enforce_equal(&U_i.mleE)?;
+        proof.h2.evaluate(&FpVar::one())?.enforce_equal(&u_i.mleE)?;
+
+        transcript.absorb(&proof.h1.coeffs)?;
+        transcript.absorb(&proof.h2.coeffs)?;
+        let beta_scalar = FpVar::constant(CF1::<C>::from_le_bytes_mod_order(b"beta"));
+        transcript.absorb(&beta_scalar)?;
+        let beta = transcript.get_challenge()?;
+
+        proof
+            .h1
+            .evaluate(&beta)?
+            .enforce_equal(&proof.mleE1_prime)?;
+        proof
+            .h2
+            .evaluate(&beta)?
+            .enforce_equal(&proof.mleE2_prime)?;
+
+        // rE' = l(beta) = rE_1 + beta * (rE_2 - rE_1)
+        let rE_prime = U_i
+            .rE
+            .iter()
+            .zip(&u_i.rE)
+            .map(|(r1, r2)| r1 + &beta * (r2 - r1))
+            .collect::<Vec<_>>();
+
+        // Folding (protocol 7)
+        transcript.absorb(&proof.mleE1_prime)?;
+        transcript.absorb(&proof.mleE2_prime)?;
+        transcript.absorb(&proof.mleT)?;
+        let alpha = transcript.get_challenge()?;
+        let alpha2 = alpha.square()?;
+
+        Ok((
+            Self::CommittedInstanceVar {
+                cmW: NonNativeAffineVar::new_constant(ConstraintSystemRef::None, C::zero())?,
+                rE: rE_prime,
+                // ci3.mleE = mleE1' + alpha * mleT + alpha^2 * mleE2'
+                mleE: &proof.mleE1_prime + &alpha * &proof.mleT + &alpha2 * &proof.mleE2_prime,
+                // ci3.u = U_i.u + alpha * u_i.u
+                u: U_i.u + &alpha * &u_i.u,
+                // ci3.x = U_i.x + alpha * u_i.x
+                x: U_i
+                    .x
+                    .iter()
+                    .zip(u_i.x)
+                    .map(|(a, b)| a + &alpha * &b)
+                    .collect::<Vec<FpVar<CF1<C>>>>(),
+            },
+            alpha.to_bits_le()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+    use ark_crypto_primitives::sponge::poseidon::PoseidonSponge;
+    use ark_pallas::{Fr, Projective};
+    use ark_r1cs_std::GR1CSVar;
+    use ark_std::{log2, UniformRand};
+
+    use crate::arith::{
+        r1cs::tests::{get_test_r1cs, get_test_z},
+        Arith,
+    };
+    use crate::commitment::{pedersen::Pedersen, CommitmentScheme};
+    use crate::folding::nova::nifs::{
+        mova::NIFS,
+        tests::{
+            test_committed_instance_hash_opt, test_committed_instance_to_sponge_preimage_opt,
+            test_nifs_gadget_opt,
+        },
+        NIFSTrait,
+    };
+    use crate::transcript::{poseidon::poseidon_canonical_config, Transcript};
+    use crate::Error;
+
+    fn random_committed_instance(
+        rng: &mut impl ark_std::rand::RngCore,
+    ) -> CommittedInstance<Projective> {
+        CommittedInstance::<Projective> {
+            rE: (0..3).map(|_| Fr::rand(rng)).collect(),
+            mleE: Fr::rand(rng),
+            u: Fr::rand(rng),
+            cmW: Projective::rand(rng),
+            x: vec![Fr::rand(rng); 1],
+        }
+    }
+
+    #[test]
+    fn test_nifs_gadget() -> Result<(), Error> {
+        type N = NIFS<Projective, Pedersen<Projective>, PoseidonSponge<Fr>>;
+
+        let mut rng = ark_std::test_rng();
+        // `test_nifs_gadget_opt` verifies the proof using a transcript initialized with the
+        // first random element of `test_rng`, so the proof is generated with the same one
+        let pp_hash = Fr::rand(&mut ark_std::test_rng());
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let mut transcript = PoseidonSponge::<Fr>::new_with_pp_hash(&poseidon_config, pp_hash);
+
+        let r1cs = get_test_r1cs();
+        let (pedersen_params, _) = Pedersen::<Projective>::setup(&mut rng, r1cs.n_witnesses())?;
+
+        // prepare a running instance with non-zero error term, by folding two fresh instances
+        let (w, x) = r1cs.split_z(&get_test_z(3));
+        let W_0 = N::new_witness(w, r1cs.n_constraints(), &mut rng);
+        let U_0 = N::new_instance(&mut rng, &pedersen_params, &W_0, x, vec![])?;
+        let (w, x) = r1cs.split_z(&get_test_z(4));
+        let w_0 = N::new_witness(w, r1cs.n_constraints(), &mut rng);
+        let u_0 = N::new_instance(&mut rng, &pedersen_params, &w_0, x, vec![])?;
+        let (W_i, U_i, _, _) = N::prove(
+            &pedersen_params,
+            &r1cs,
+            &mut transcript.clone(),
+            &W_0,
+            &U_0,
+            &w_0,
+            &u_0,
+        )?;
+
+        // prepare the incoming instance
+        let (w, x) = r1cs.split_z(&get_test_z(5));
+        let w_i = N::new_witness(w, r1cs.n_constraints(), &mut rng);
+        let u_i = N::new_instance(&mut rng, &pedersen_params, &w_i, x, vec![])?;
+        assert_eq!(u_i.rE.len(), log2(r1cs.n_constraints()) as usize);
+
+        let (_, _, proof, _) = N::prove(
+            &pedersen_params,
+            &r1cs,
+            &mut transcript,
+            &W_i,
+            &U_i,
+            &w_i,
+            &u_i,
+        )?;
+
+        let (ci_out, ciVar_out) = test_nifs_gadget_opt::<
+            N,
+            NIFSGadget<Projective, PoseidonSponge<Fr>, PoseidonSpongeVar<Fr>>,
+        >(vec![U_i, u_i], proof)?;
+        assert_eq!(ciVar_out.u.value()?, ci_out.u);
+        assert_eq!(ciVar_out.x.value()?, ci_out.x);
+        assert_eq!(ciVar_out.rE.value()?, ci_out.rE);
+        assert_eq!(ciVar_out.mleE.value()?, ci_out.mleE);
+        Ok(())
+    }
+
+    #[test]
+    fn test_committed_instance_to_sponge_preimage() -> Result<(), Error> {
+        let mut rng = ark_std::test_rng();
+        let ci = random_committed_instance(&mut rng);
+
+        test_committed_instance_to_sponge_preimage_opt::<
+            NIFS<Projective, Pedersen<Projective>, PoseidonSponge<Fr>>,
+            NIFSGadget<Projective, PoseidonSponge<Fr>, PoseidonSpongeVar<Fr>>,
+        >(ci)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_committed_instance_hash() -> Result<(), Error> {
+        let mut rng = ark_std::test_rng();
+        let ci = random_committed_instance(&mut rng);
+
+        test_committed_instance_hash_opt::<
+            NIFS<Projective, Pedersen<Projective>, PoseidonSponge<Fr>>,
+            NIFSGadget<Projective, PoseidonSponge<Fr>, PoseidonSpongeVar<Fr>>,
+        >(ci)?;
+        Ok(())
+    }
+}
@@ -9,14 +9,14 @@ use ark_std::{One, UniformRand, Zero};
 use std::marker::PhantomData;
 
 use super::nova::ChallengeGadget;
-use super::ova_circuits::CommittedInstanceVar;
+use super::ova_circuits::{CommittedInstanceVar, WitnessVar};
 use super::NIFSTrait;
 use crate::arith::{r1cs::R1CS, Arith};
 use crate::commitment::CommitmentScheme;
-use crate::folding::traits::{CommittedInstanceOps, Inputize};
+use crate::folding::traits::{CommittedInstanceOps, Inputize, WitnessOps};
 use crate::folding::{circuits::CF1, traits::Dummy};
 use crate::transcript::Transcript;
-use crate::utils::vec::{hadamard, mat_vec_mul, vec_scalar_mul, vec_sub};
+use crate::utils::vec::{hadamard, mat_vec_mul, vec_add, vec_scalar_mul, vec_sub};
 use crate::{Curve, Error};
 
 /// A CommittedInstance in [Ova](https://hackmd.io/V4838nnlRKal9ZiTHiGYzw) is represented by `W` or
@@ -31,6 +31,22 @@ pub struct CommittedInstance<C: Curve> {
     pub cmWE: C,
 }
 
+impl<C: Curve> Dummy<usize> for CommittedInstance<C> {
+    fn dummy(io_len: usize) -> Self {
+        Self {
+            u: C::ScalarField::zero(),
+            x: vec![C::ScalarField::zero(); io_len],
+            cmWE: C::zero(),
+        }
+    }
+}
+
+impl<C: Curve> Dummy<&R1CS<CF1<C>>> for CommittedInstance<C> {
+    fn dummy(r1cs: &R1CS<CF1<C>>) -> Self {
+        Self::dummy(r1cs.n_public_inputs())
+    }
+}
+
 impl<C: Curve> Absorb for CommittedInstance<C> {
     fn to_sponge_bytes(&self, dest: &mut Vec<u8>) {
         C::ScalarField::batch_to_sponge_bytes(&self.to_sponge_field_elements_as_vec(), dest);
@@ -105,14 +121,24 @@ impl<C: Curve> Witness<C> {
 }
 
 impl<C: Curve> Dummy<&R1CS<CF1<C>>> for Witness<C> {
+    /// The dummy witness holds `w` concatenated with `e`, since that is the vector that the
+    /// Ova IVC commits to and folds.
     fn dummy(r1cs: &R1CS<CF1<C>>) -> Self {
         Self {
-            w: vec![C::ScalarField::zero(); r1cs.n_witnesses()],
+            w: vec![C::ScalarField::zero(); r1cs.n_witnesses() + r1cs.n_constraints()],
             rW: C::ScalarField::zero(),
         }
     }
 }
 
+impl<C: Curve> WitnessOps<C::ScalarField> for Witness<C> {
+    type Var = WitnessVar<C>;
+
+    fn get_openings(&self) -> Vec<(&[C::ScalarField], C::ScalarField)> {
+        vec![(&self.w, self.rW)]
+    }
+}
+
 /// Implements the NIFS (Non-Interactive Folding Scheme) trait for Ova.
 pub struct NIFS<
     C: Curve,
@@ -247,6 +273,48 @@ pub fn compute_E<C: Curve>(
     vec_sub(&Az_Bz, &uCz)
 }
 
+/// Computes the cross term `t` that the incoming instance commits to together with its witness,
+/// so that folding it into the running instance `U_i` keeps the relaxed R1CS relation.
+///
+/// `W_i.w` is expected to hold the running witness concatenated with its error term `e`, while
+/// `x` and `w` are the public inputs and witness of the fresh incoming instance, whose `u` is
+/// always 1 and whose error term is zero. Then, for `z' = z_1 + r * z_2` and `u' = u_1 + r`,
+/// `A z' ∘ B z' - u' C z' = e + r * t`.
+pub fn compute_t<C: Curve>(
+    r1cs: &R1CS<C::ScalarField>,
+    U_i: &CommittedInstance<C>,
+    W_i: &Witness<C>,
+    x: &[C::ScalarField],
+    w: &[C::ScalarField],
+) -> Result<Vec<C::ScalarField>, Error> {
+    let n_witnesses = r1cs.n_witnesses();
+    if W_i.w.len() < n_witnesses {
+        return Err(Error::NotSameLength(
+            "W_i.w.len()".to_string(),
+            W_i.w.len(),
+            "r1cs.n_witnesses()".to_string(),
+            n_witnesses,
+        ));
+    }
+    let z1 = [&[U_i.u][..], &U_i.x, &W_i.w[..n_witnesses]].concat();
+    let z2 = [&[C::ScalarField::one()][..], x, w].concat();
+
+    let (Az1, Bz1, Cz1) = (
+        mat_vec_mul(&r1cs.A, &z1)?,
+        mat_vec_mul(&r1cs.B, &z1)?,
+        mat_vec_mul(&r1cs.C, &z1)?,
+    );
+    let (Az2, Bz2, Cz2) = (
+        mat_vec_mul(&r1cs.A, &z2)?,
+        mat_vec_mul(&r1cs.B, &z2)?,
+        mat_vec_mul(&r1cs.C, &z2)?,
+    );
+
+    // t = Az1 ∘ Bz2 + Az2 ∘ Bz1 - u1 * Cz2 - u2 * Cz1, where u2 = 1
+    let cross = vec_add(&hadamard(&Az1, &Bz2)?, &hadamard(&Az2, &Bz1)?)?;
+    vec_sub(&vec_sub(&cross, &vec_scalar_mul(&Cz2, &U_i.u))?, &Cz1)
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
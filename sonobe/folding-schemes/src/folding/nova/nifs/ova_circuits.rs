@@ -11,9 +11,9 @@ use ark_relations::gr1cs::{ConstraintSystemRef, Namespace, SynthesisError};
 use ark_std::fmt::Debug;
 use core::{borrow::Borrow, marker::PhantomData};
 
-use super::ova::CommittedInstance;
+use super::ova::{CommittedInstance, Witness};
 use super::NIFSGadgetTrait;
-use crate::folding::traits::CommittedInstanceVarOps;
+use crate::folding::traits::{CommittedInstanceVarOps, WitnessVarOps};
 use crate::transcript::TranscriptVar;
 use crate::{
     folding::circuits::{nonnative::affine::NonNativeAffineVar, CF1},
@@ -87,6 +87,37 @@ impl<C: Curve> CommittedInstanceVarOps<C> for CommittedInstanceVar<C> {
     }
 }
 
+/// In-circuit representation of the Ova [`Witness`], where `W` holds the witness concatenated
+/// with `t` or `e`.
+#[derive(Debug, Clone)]
+pub struct WitnessVar<C: Curve> {
+    pub W: Vec<FpVar<C::ScalarField>>,
+    pub rW: FpVar<C::ScalarField>,
+}
+
+impl<C: Curve> AllocVar<Witness<C>, CF1<C>> for WitnessVar<C> {
+    fn new_variable<T: Borrow<Witness<C>>>(
+        cs: impl Into<Namespace<CF1<C>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|val| {
+            let cs = cs.into();
+
+            let W = Vec::new_variable(cs.clone(), || Ok(val.borrow().w.clone()), mode)?;
+            let rW = FpVar::new_variable(cs.clone(), || Ok(val.borrow().rW), mode)?;
+
+            Ok(Self { W, rW })
+        })
+    }
+}
+
+impl<C: Curve> WitnessVarOps<C::ScalarField> for WitnessVar<C> {
+    fn get_openings(&self) -> Vec<(&[FpVar<C::ScalarField>], FpVar<C::ScalarField>)> {
+        vec![(&self.W, self.rW.clone())]
+    }
+}
+
 /// Implements the circuit that does the checks of the Non-Interactive Folding Scheme Verifier
 /// described of the Ova variant, where the cmWE check is delegated to the NIFSCycleFoldGadget.
 pub struct NIFSGadget<C: Curve, S: CryptographicSponge, T: TranscriptVar<CF1<C>, S>> {
@@ -5,6 +5,7 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{log2, Zero};
 
 use super::mova::{CommittedInstance, Witness};
+use crate::folding::traits::Dummy;
 use crate::transcript::Transcript;
 use crate::utils::mle::dense_vec_to_dense_mle;
 use crate::{Curve, Error};
@@ -24,6 +25,17 @@ pub struct PointVsLineProof<C: Curve> {
     pub h2: DensePolynomial<C::ScalarField>,
 }
 
+impl<C: Curve> Dummy<usize> for PointVsLineProof<C> {
+    // `h1` and `h2` are padded to `n_vars + 1` coefficients when they are allocated in-circuit,
+    // hence the zero polynomial is a valid dummy for any `n_vars`
+    fn dummy(_n_vars: usize) -> Self {
+        Self {
+            h1: DensePolynomial::zero(),
+            h2: DensePolynomial::zero(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct PointVsLine<C: Curve, T: Transcript<C::ScalarField>> {
     _phantom_C: std::marker::PhantomData<C>,
@@ -55,8 +67,8 @@ impl<C: Curve, T: Transcript<C::ScalarField>> PointVsLine<C, T> {
         let h1 = compute_h(&mleE1, &ci1.rE, &r2_sub_r1)?;
         let h2 = compute_h(&mleE2, &ci1.rE, &r2_sub_r1)?;
 
-        transcript.absorb(&h1.coeffs());
-        transcript.absorb(&h2.coeffs());
+        transcript.absorb(&padded_coeffs(&h1, n_vars));
+        transcript.absorb(&padded_coeffs(&h2, n_vars));
 
         let beta_scalar = C::ScalarField::from_le_bytes_mod_order(b"beta");
         transcript.absorb(&beta_scalar);
@@ -97,8 +109,9 @@ impl<C: Curve, T: Transcript<C::ScalarField>> PointVsLine<C, T> {
             return Err(Error::NotEqual);
         }
 
-        transcript.absorb(&proof.h1.coeffs());
-        transcript.absorb(&proof.h2.coeffs());
+        let n_vars = ci1.rE.len();
+        transcript.absorb(&padded_coeffs(&proof.h1, n_vars));
+        transcript.absorb(&padded_coeffs(&proof.h2, n_vars));
 
         let beta_scalar = C::ScalarField::from_le_bytes_mod_order(b"beta");
         transcript.absorb(&beta_scalar);
@@ -127,6 +140,15 @@ impl<C: Curve, T: Transcript<C::ScalarField>> PointVsLine<C, T> {
     }
 }
 
+/// Returns the coefficients of `h`, padded with zeros to the `n_vars + 1` coefficients of a
+/// polynomial of degree `n_vars`. `DensePolynomial` trims the leading zero coefficients, so the
+/// padding is needed to absorb a fixed amount of elements, as done by the in-circuit verifier.
+pub(crate) fn padded_coeffs<F: PrimeField>(h: &DensePolynomial<F>, n_vars: usize) -> Vec<F> {
+    let mut coeffs = h.coeffs().to_vec();
+    coeffs.resize(coeffs.len().max(n_vars + 1), F::zero());
+    coeffs
+}
+
 fn compute_h<F: PrimeField>(
     mle: &DenseMultilinearExtension<F>,
     r1: &[F],
@@ -31,7 +31,9 @@
 //! and implemented directly at the app level by folding the original instance with a randomized
 //! instance (steps 2,3,4 from section D.4 of the [HyperNova](https://eprint.iacr.org/2023/573.pdf)
 //! paper).
-//! And the Use-case-2 would require a modified version of the Decider circuits.
+//! The Use-case-2 is covered for the onchain Decider by the modified Decider circuit
+//! [`super::decider_eth_circuit::DeciderEthZKCircuit`], used by
+//! [`super::decider_eth::ZKDecider`].
 use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, PoseidonSponge};
 use ark_std::{rand::RngCore, One, Zero};
 
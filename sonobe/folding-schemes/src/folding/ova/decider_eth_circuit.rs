@@ -0,0 +1,201 @@
+/// This file implements the Ova's onchain (Ethereum's EVM) decider circuit. For non-ethereum use
+/// cases, other more efficient approaches can be used.
+use ark_crypto_primitives::sponge::poseidon::{constraints::PoseidonSpongeVar, PoseidonSponge};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar, prelude::Boolean, GR1CSVar};
+use ark_relations::gr1cs::SynthesisError;
+use ark_std::marker::PhantomData;
+
+use super::{CommittedInstance, CommittedInstanceVar, Ova, Witness};
+use crate::{
+    arith::r1cs::{circuits::R1CSMatricesVar, R1CS},
+    commitment::{pedersen::Params as PedersenParams, CommitmentScheme},
+    folding::{
+        circuits::{
+            decider::{
+                on_chain::GenericOnchainDeciderCircuit, DeciderEnabledNIFS, EvalGadget,
+                KZGChallengesGadget,
+            },
+            CF1,
+        },
+        nova::nifs::{ova::NIFS, ova_circuits::NIFSGadget, NIFSGadgetTrait, NIFSTrait},
+        traits::WitnessOps,
+    },
+    frontend::FCircuit,
+    transcript::Transcript,
+    Curve, Error,
+};
+
+pub type DeciderEthCircuit<C1, C2> = GenericOnchainDeciderCircuit<
+    C1,
+    C2,
+    CommittedInstance<C1>,
+    CommittedInstance<C1>,
+    Witness<C1>,
+    R1CS<CF1<C1>>,
+    R1CSMatricesVar<CF1<C1>, FpVar<CF1<C1>>>,
+    DeciderOvaGadget,
+>;
+
+/// returns an instance of the DeciderEthCircuit from the given Ova struct
+impl<
+        C1: Curve,
+        C2: Curve,
+        FC: FCircuit<C1::ScalarField>,
+        CS1: CommitmentScheme<C1, H>,
+        // enforce that the CS2 is Pedersen commitment scheme, since we're at Ethereum's EVM decider
+        CS2: CommitmentScheme<C2, H, ProverParams = PedersenParams<C2>>,
+        const H: bool,
+    > TryFrom<Ova<C1, C2, FC, CS1, CS2, H>> for DeciderEthCircuit<C1, C2>
+{
+    type Error = Error;
+
+    fn try_from(ova: Ova<C1, C2, FC, CS1, CS2, H>) -> Result<Self, Error> {
+        let mut transcript = PoseidonSponge::new_with_pp_hash(&ova.poseidon_config, ova.pp_hash);
+
+        // compute the U_{i+1}, W_{i+1}
+        let (W_i1, U_i1, proof, r_bits) =
+            NIFS::<C1, CS1, PoseidonSponge<C1::ScalarField>, H>::prove(
+                &ova.cs_pp,
+                &ova.r1cs,
+                &mut transcript,
+                &ova.W_i,
+                &ova.U_i,
+                &ova.w_i,
+                &ova.u_i,
+            )?;
+        let r_Fr = C1::ScalarField::from_bigint(BigInteger::from_bits_le(&r_bits))
+            .ok_or(Error::OutOfBounds)?;
+
+        // compute the KZG challenges used as inputs in the circuit
+        let kzg_challenges = KZGChallengesGadget::get_challenges_native(&mut transcript, &U_i1);
+
+        // get KZG evals
+        let kzg_evaluations = W_i1
+            .get_openings()
+            .iter()
+            .zip(&kzg_challenges)
+            .map(|((v, _), &c)| EvalGadget::evaluate_native(v, c))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            _avar: PhantomData,
+            arith: ova.r1cs,
+            cf_arith: ova.cf_r1cs,
+            cf_pedersen_params: ova.cf_cs_pp,
+            poseidon_config: ova.poseidon_config,
+            pp_hash: ova.pp_hash,
+            i: ova.i,
+            z_0: ova.z_0,
+            z_i: ova.z_i,
+            U_i: ova.U_i,
+            W_i: ova.W_i,
+            u_i: ova.u_i,
+            w_i: ova.w_i,
+            U_i1,
+            W_i1,
+            proof,
+            randomness: r_Fr,
+            cf_U_i: ova.cf_U_i,
+            cf_W_i: ova.cf_W_i,
+            kzg_challenges,
+            kzg_evaluations,
+        })
+    }
+}
+
+pub struct DeciderOvaGadget;
+
+impl<C: Curve>
+    DeciderEnabledNIFS<C, CommittedInstance<C>, CommittedInstance<C>, Witness<C>, R1CS<CF1<C>>>
+    for DeciderOvaGadget
+{
+    type ProofDummyCfg = ();
+    // unused in Ova, see `ova::NIFS::Proof`
+    type Proof = CF1<C>;
+    type RandomnessDummyCfg = ();
+    type Randomness = CF1<C>;
+
+    fn fold_field_elements_gadget(
+        _arith: &R1CS<CF1<C>>,
+        transcript: &mut PoseidonSpongeVar<CF1<C>>,
+        U: CommittedInstanceVar<C>,
+        U_vec: Vec<FpVar<CF1<C>>>,
+        u: CommittedInstanceVar<C>,
+        _proof: CF1<C>,
+        randomness: CF1<C>,
+    ) -> Result<CommittedInstanceVar<C>, SynthesisError> {
+        let cs = U.u.cs();
+        let r = FpVar::new_input(cs.clone(), || Ok(randomness))?;
+        let (new_U, r_bits) = NIFSGadget::verify(transcript, U, U_vec, u, None)?;
+        // the randomness used to fold the commitments outside of the circuit must be the
+        // in-circuit computed challenge
+        Boolean::le_bits_to_fp(&r_bits)?.enforce_equal(&r)?;
+        Ok(new_U)
+    }
+
+    fn fold_group_elements_native(
+        U_commitments: &[C],
+        u_commitments: &[C],
+        _: Option<Self::Proof>,
+        r: Self::Randomness,
+    ) -> Result<Vec<C>, Error> {
+        let U_cmWE = U_commitments[0];
+        let u_cmWE = u_commitments[0];
+        Ok(vec![U_cmWE + u_cmWE.mul(r)])
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ark_bn254::{Fr, G1Projective as Projective};
+    use ark_grumpkin::Projective as Projective2;
+    use ark_relations::gr1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+    use super::*;
+    use crate::commitment::pedersen::Pedersen;
+    use crate::folding::nova::PreprocessorParam;
+    use crate::frontend::utils::CubicFCircuit;
+    use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::FoldingScheme;
+
+    #[test]
+    fn test_decider_circuit() -> Result<(), Error> {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(())?;
+        let z_0 = vec![Fr::from(3_u32)];
+
+        type O = Ova<
+            Projective,
+            Projective2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let ova_params = O::preprocess(&mut rng, &prep_param)?;
+
+        // generate an Ova instance and do two steps of it, so that the running instance is not
+        // the dummy one anymore
+        let mut ova = O::init(&ova_params, F_circuit, z_0.clone())?;
+        ova.prove_step(&mut rng, (), None)?;
+        ova.prove_step(&mut rng, (), None)?;
+
+        let ivc_proof = ova.ivc_proof();
+        O::verify(ova_params.1, ivc_proof)?;
+
+        // load the DeciderEthCircuit from the Ova instance
+        let decider_circuit = DeciderEthCircuit::<Projective, Projective2>::try_from(ova)?;
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+
+        // generate the constraints and check that are satisfied by the inputs
+        decider_circuit.generate_constraints(cs.clone())?;
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+}
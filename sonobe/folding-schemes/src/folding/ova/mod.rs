@@ -0,0 +1,715 @@
+/// Implements the IVC of [Ova](https://hackmd.io/V4838nnlRKal9ZiTHiGYzw) together with
+/// [CycleFold](https://eprint.iacr.org/2023/1192.pdf), following the FoldingScheme trait.
+///
+/// Ova uses the NIFS from [`crate::folding::nova::nifs::ova`]. The prover commits to the witness
+/// `w` concatenated with the cross term `t` in a single commitment `cmWE`, so that there is no
+/// commitment to `E` nor to `T`, and only a single CycleFold circuit is needed per step.
+///
+/// The structure of the Ova code is the following:
+/// - IVC (this file) and its AugmentedFCircuit (circuits.rs)
+/// - [`crate::arith::ArithRelation`] implementations for Ova's witness layout (traits.rs)
+/// - onchain Decider (decider_eth.rs & decider_eth_circuit.rs)
+use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, PoseidonSponge};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{alloc::AllocVar, prelude::Boolean, GR1CSVar};
+use ark_relations::gr1cs::{ConstraintSystem, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{rand::RngCore, One, Zero};
+
+use crate::arith::{
+    r1cs::{extract_w_x, R1CS},
+    Arith, ArithRelation,
+};
+use crate::commitment::CommitmentScheme;
+use crate::constants::NOVA_N_BITS_RO;
+use crate::folding::{
+    circuits::{
+        cyclefold::{
+            CycleFoldAugmentationGadget, CycleFoldCircuit, CycleFoldCommittedInstance,
+            CycleFoldConfig, CycleFoldWitness,
+        },
+        CF1, CF2,
+    },
+    nova::{
+        get_r1cs_from_cs,
+        nifs::{ova::NIFS, NIFSTrait},
+        PreprocessorParam, ProverParams, VerifierParams,
+    },
+    traits::{CommittedInstanceOps, Dummy},
+};
+use crate::frontend::FCircuit;
+use crate::transcript::{poseidon::poseidon_canonical_config, Transcript};
+use crate::{Curve, Error, FoldingScheme};
+
+pub mod circuits;
+pub mod decider_eth;
+pub mod decider_eth_circuit;
+pub mod traits;
+
+use circuits::AugmentedFCircuit;
+
+pub use crate::folding::nova::nifs::ova::{compute_t, CommittedInstance, Witness};
+pub use crate::folding::nova::nifs::ova_circuits::{CommittedInstanceVar, WitnessVar};
+
+/// Configuration for Ova's CycleFold circuit
+pub struct OvaCycleFoldConfig<C: Curve> {
+    r: Vec<bool>,
+    points: Vec<C>,
+}
+
+impl<C: Curve> Default for OvaCycleFoldConfig<C> {
+    fn default() -> Self {
+        Self {
+            r: vec![false; NOVA_N_BITS_RO],
+            points: vec![C::zero(); 2],
+        }
+    }
+}
+
+impl<C: Curve> CycleFoldConfig<C> for OvaCycleFoldConfig<C> {
+    const RANDOMNESS_BIT_LENGTH: usize = NOVA_N_BITS_RO;
+    // Number of points to be folded in the CycleFold circuit, in Ova's case, this is a fixed
+    // amount:
+    // 2 points to be folded (`U_i.cmWE` and `u_i.cmWE`).
+    const N_INPUT_POINTS: usize = 2;
+    const N_UNIQUE_RANDOMNESSES: usize = 1;
+
+    fn alloc_points(&self, cs: ConstraintSystemRef<CF2<C>>) -> Result<Vec<C::Var>, SynthesisError> {
+        let points = Vec::new_witness(cs.clone(), || Ok(self.points.clone()))?;
+        for point in &points {
+            Self::mark_point_as_public(point)?;
+        }
+        Ok(points)
+    }
+
+    fn alloc_randomnesses(
+        &self,
+        cs: ConstraintSystemRef<CF2<C>>,
+    ) -> Result<Vec<Vec<Boolean<CF2<C>>>>, SynthesisError> {
+        let one = &CF1::<C>::one().into_bigint().to_bits_le()[..NOVA_N_BITS_RO];
+        let one_var = Vec::new_constant(cs.clone(), one)?;
+        let r_var = Vec::new_witness(cs.clone(), || Ok(self.r.clone()))?;
+        Self::mark_randomness_as_public(&r_var)?;
+        Ok(vec![one_var, r_var])
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct IVCProof<C1, C2>
+where
+    C1: Curve,
+    C2: Curve,
+{
+    // current step of the IVC
+    pub i: C1::ScalarField,
+    // initial state
+    pub z_0: Vec<C1::ScalarField>,
+    // current state
+    pub z_i: Vec<C1::ScalarField>,
+    // running instance
+    pub W_i: Witness<C1>,
+    pub U_i: CommittedInstance<C1>,
+    // incoming instance
+    pub w_i: Witness<C1>,
+    pub u_i: CommittedInstance<C1>,
+    // CycleFold instances
+    pub cf_W_i: CycleFoldWitness<C2>,
+    pub cf_U_i: CycleFoldCommittedInstance<C2>,
+}
+
+/// Implements Ova+CycleFold's IVC, following the FoldingScheme trait.
+/// The `H` const generic specifies whether the homorphic commitment scheme is blinding
+#[derive(Clone, Debug)]
+pub struct Ova<C1, C2, FC, CS1, CS2, const H: bool = false>
+where
+    C1: Curve,
+    C2: Curve,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+{
+    /// R1CS of the Augmented Function circuit
+    pub r1cs: R1CS<C1::ScalarField>,
+    /// R1CS of the CycleFold circuit
+    pub cf_r1cs: R1CS<C2::ScalarField>,
+    pub poseidon_config: PoseidonConfig<C1::ScalarField>,
+    /// CommitmentScheme::ProverParams over C1
+    pub cs_pp: CS1::ProverParams,
+    /// CycleFold CommitmentScheme::ProverParams, over C2
+    pub cf_cs_pp: CS2::ProverParams,
+    /// F circuit, the circuit that is being folded
+    pub F: FC,
+    /// public params hash
+    pub pp_hash: C1::ScalarField,
+    pub i: C1::ScalarField,
+    /// initial state
+    pub z_0: Vec<C1::ScalarField>,
+    /// current i-th state
+    pub z_i: Vec<C1::ScalarField>,
+    /// Ova instances, where `w_i.w` holds `w || t` and `W_i.w` holds `w || e`
+    pub w_i: Witness<C1>,
+    pub u_i: CommittedInstance<C1>,
+    pub W_i: Witness<C1>,
+    pub U_i: CommittedInstance<C1>,
+
+    /// CycleFold running instance
+    pub cf_W_i: CycleFoldWitness<C2>,
+    pub cf_U_i: CycleFoldCommittedInstance<C2>,
+}
+
+impl<C1, C2, FC, CS1, CS2, const H: bool> FoldingScheme<C1, C2, FC> for Ova<C1, C2, FC, CS1, CS2, H>
+where
+    C1: Curve,
+    C2: Curve,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    C1: Curve<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+{
+    type PreprocessorParam = PreprocessorParam<C1, C2, FC, CS1, CS2, H>;
+    type ProverParam = ProverParams<C1, C2, CS1, CS2, H>;
+    type VerifierParam = VerifierParams<C1, C2, CS1, CS2, H>;
+    type RunningInstance = (CommittedInstance<C1>, Witness<C1>);
+    type IncomingInstance = (CommittedInstance<C1>, Witness<C1>);
+    type MultiCommittedInstanceWithWitness = ();
+    type CFInstance = (CycleFoldCommittedInstance<C2>, CycleFoldWitness<C2>);
+    type IVCProof = IVCProof<C1, C2>;
+
+    fn pp_deserialize_with_mode<R: std::io::prelude::Read>(
+        reader: R,
+        compress: ark_serialize::Compress,
+        validate: ark_serialize::Validate,
+        _fc_params: FC::Params, // FCircuit params
+    ) -> Result<Self::ProverParam, Error> {
+        Ok(Self::ProverParam::deserialize_with_mode(
+            reader, compress, validate,
+        )?)
+    }
+
+    fn vp_deserialize_with_mode<R: std::io::prelude::Read>(
+        mut reader: R,
+        compress: ark_serialize::Compress,
+        validate: ark_serialize::Validate,
+        fc_params: FC::Params,
+    ) -> Result<Self::VerifierParam, Error> {
+        let poseidon_config = poseidon_canonical_config::<C1::ScalarField>();
+
+        // generate the r1cs & cf_r1cs needed for the VerifierParams. In this way we avoid needing
+        // to serialize them, saving significant space in the VerifierParams serialized size.
+        let (r1cs, cf_r1cs) = get_r1cs::<C1, C2, FC>(&poseidon_config, FC::new(fc_params)?)?;
+
+        let cs_vp = CS1::VerifierParams::deserialize_with_mode(&mut reader, compress, validate)?;
+        let cf_cs_vp = CS2::VerifierParams::deserialize_with_mode(&mut reader, compress, validate)?;
+
+        Ok(Self::VerifierParam {
+            poseidon_config,
+            r1cs,
+            cf_r1cs,
+            cs_vp,
+            cf_cs_vp,
+        })
+    }
+
+    fn preprocess(
+        mut rng: impl RngCore,
+        prep_param: &Self::PreprocessorParam,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
+        let (r1cs, cf_r1cs) =
+            get_r1cs::<C1, C2, FC>(&prep_param.poseidon_config, prep_param.F.clone())?;
+
+        // if cs params exist, use them, if not, generate new ones
+        let (cs_pp, cs_vp) = match (&prep_param.cs_pp, &prep_param.cs_vp) {
+            (Some(cs_pp), Some(cs_vp)) => (cs_pp.clone(), cs_vp.clone()),
+            _ => CS1::setup(
+                &mut rng,
+                // `CS1` is for committing to Ova's witness vector `w` concatenated with the cross
+                // term `t` (or the error term `e`), whose length is the number of constraints.
+                r1cs.n_witnesses() + r1cs.n_constraints(),
+            )?,
+        };
+        let (cf_cs_pp, cf_cs_vp) = match (&prep_param.cf_cs_pp, &prep_param.cf_cs_vp) {
+            (Some(cf_cs_pp), Some(cf_cs_vp)) => (cf_cs_pp.clone(), cf_cs_vp.clone()),
+            _ => CS2::setup(
+                &mut rng,
+                // `CS2` is for committing to CycleFold's witness vector `w` and
+                // error term `e`, where the length of `e` is the number of
+                // constraints, so we set `len` to the maximum of `e` and `w`'s
+                // lengths.
+                cf_r1cs.n_constraints().max(cf_r1cs.n_witnesses()),
+            )?,
+        };
+
+        let prover_params = ProverParams::<C1, C2, CS1, CS2, H> {
+            poseidon_config: prep_param.poseidon_config.clone(),
+            cs_pp,
+            cf_cs_pp,
+        };
+        let verifier_params = VerifierParams::<C1, C2, CS1, CS2, H> {
+            poseidon_config: prep_param.poseidon_config.clone(),
+            r1cs,
+            cf_r1cs,
+            cs_vp,
+            cf_cs_vp,
+        };
+
+        Ok((prover_params, verifier_params))
+    }
+
+    /// Initializes the Ova+CycleFold's IVC for the given parameters and initial state `z_0`.
+    fn init(
+        params: &(Self::ProverParam, Self::VerifierParam),
+        F: FC,
+        z_0: Vec<C1::ScalarField>,
+    ) -> Result<Self, Error> {
+        let (pp, vp) = params;
+
+        let (r1cs, cf_r1cs) = get_r1cs::<C1, C2, FC>(&pp.poseidon_config, F.clone())?;
+
+        // compute the public params hash
+        let pp_hash = vp.pp_hash()?;
+
+        // setup the dummy instances. W_dummy=W_0 is a 'dummy witness', all zeroes, but with the
+        // size corresponding to the R1CS that we're working with.
+        let (W_dummy, U_dummy) = (Witness::dummy(&r1cs), CommittedInstance::dummy(&r1cs));
+        let (cf_W_dummy, cf_U_dummy) = cf_r1cs.dummy_witness_instance();
+
+        Ok(Self {
+            r1cs,
+            cf_r1cs,
+            poseidon_config: pp.poseidon_config.clone(),
+            cs_pp: pp.cs_pp.clone(),
+            cf_cs_pp: pp.cf_cs_pp.clone(),
+            F,
+            pp_hash,
+            i: C1::ScalarField::zero(),
+            z_0: z_0.clone(),
+            z_i: z_0,
+            w_i: W_dummy.clone(),
+            u_i: U_dummy.clone(),
+            W_i: W_dummy,
+            U_i: U_dummy,
+            // cyclefold running instance
+            cf_W_i: cf_W_dummy,
+            cf_U_i: cf_U_dummy,
+        })
+    }
+
+    /// Implements IVC.P of Ova+CycleFold
+    fn prove_step(
+        &mut self,
+        mut rng: impl RngCore,
+        external_inputs: FC::ExternalInputs,
+        // Ova does not support multi-instances folding
+        _other_instances: Option<Self::MultiCommittedInstanceWithWitness>,
+    ) -> Result<(), Error> {
+        // ensure that commitments are blinding if user has specified so.
+        if H && self.i >= C1::ScalarField::one() {
+            let blinding_commitments = if self.i == C1::ScalarField::one() {
+                // blinding values of the running instances are zero at the first iteration
+                vec![self.w_i.rW]
+            } else {
+                vec![self.w_i.rW, self.W_i.rW]
+            };
+            if blinding_commitments.contains(&C1::ScalarField::zero()) {
+                return Err(Error::IncorrectBlinding(
+                    H,
+                    format!("{blinding_commitments:?}"),
+                ));
+            }
+        }
+        // `sponge` is for digest computation.
+        let sponge = PoseidonSponge::<C1::ScalarField>::new_with_pp_hash(
+            &self.poseidon_config,
+            self.pp_hash,
+        );
+        // `transcript` is for challenge generation.
+        let mut transcript = sponge.clone();
+
+        let augmented_F_circuit: AugmentedFCircuit<C1, C2, FC>;
+
+        if _other_instances.is_some() {
+            return Err(Error::NoMultiInstances);
+        }
+
+        if self.z_i.len() != self.F.state_len() {
+            return Err(Error::NotSameLength(
+                "z_i.len()".to_string(),
+                self.z_i.len(),
+                "F.state_len()".to_string(),
+                self.F.state_len(),
+            ));
+        }
+
+        if self.i > C1::ScalarField::from_le_bytes_mod_order(&usize::MAX.to_le_bytes()) {
+            return Err(Error::MaxStep);
+        }
+
+        let i_usize;
+
+        #[cfg(target_pointer_width = "64")]
+        {
+            let mut i_bytes: [u8; 8] = [0; 8];
+            i_bytes.copy_from_slice(&self.i.into_bigint().to_bytes_le()[..8]);
+            i_usize = usize::from_le_bytes(i_bytes);
+        }
+
+        #[cfg(target_pointer_width = "32")]
+        {
+            let mut i_bytes: [u8; 4] = [0; 4];
+            i_bytes.copy_from_slice(&self.i.into_bigint().to_bytes_le()[..4]);
+            i_usize = usize::from_le_bytes(i_bytes);
+        }
+
+        let (W_i1, U_i1) = if self.i == C1::ScalarField::zero() {
+            // base case: Ova's NIFS assumes that the incoming instance has `u = 1`, which does
+            // not hold for the dummy `u_0`. Since the circuit outputs `H(1, z_0, z_1, U_⊥)` at
+            // the base case, the next running instance is `U_1 = U_⊥`.
+            augmented_F_circuit = AugmentedFCircuit::<C1, C2, FC> {
+                poseidon_config: self.poseidon_config.clone(),
+                pp_hash: Some(self.pp_hash),
+                i: Some(C1::ScalarField::zero()), // = i=0
+                i_usize: Some(0),
+                z_0: Some(self.z_0.clone()), // = z_i
+                z_i: Some(self.z_i.clone()),
+                external_inputs: Some(external_inputs.clone()),
+                u_i_cmWE: Some(self.u_i.cmWE), // = dummy
+                U_i: Some(self.U_i.clone()),   // = dummy
+                U_i1_cmWE: Some(self.U_i.cmWE),
+                F: self.F.clone(),
+                cf_u_i_cmW: None,
+                cf_U_i: None,
+                cf_cmT: None,
+            };
+
+            (self.W_i.clone(), self.U_i.clone())
+        } else {
+            // fold Ova instances
+            let (W_i1, U_i1, _, r_bits) =
+                NIFS::<C1, CS1, PoseidonSponge<C1::ScalarField>, H>::prove(
+                    &self.cs_pp,
+                    &self.r1cs,
+                    &mut transcript,
+                    &self.W_i,
+                    &self.U_i,
+                    &self.w_i,
+                    &self.u_i,
+                )?;
+
+            // CycleFold part:
+            let (cf_w_i, cf_u_i) = OvaCycleFoldConfig {
+                r: r_bits,
+                points: vec![self.U_i.cmWE, self.u_i.cmWE],
+            }
+            .build_circuit()
+            .generate_incoming_instance_witness::<_, CS2, H>(&self.cf_cs_pp, &mut rng)?;
+
+            let (cf_W_i1, cf_U_i1, cf_cmTs) = CycleFoldAugmentationGadget::fold_native::<_, CS2, H>(
+                &mut transcript,
+                &self.cf_r1cs,
+                &self.cf_cs_pp,
+                self.cf_W_i.clone(),
+                self.cf_U_i.clone(),
+                vec![cf_w_i],
+                vec![cf_u_i.clone()],
+            )?;
+
+            augmented_F_circuit = AugmentedFCircuit::<C1, C2, FC> {
+                poseidon_config: self.poseidon_config.clone(),
+                pp_hash: Some(self.pp_hash),
+                i: Some(self.i),
+                i_usize: Some(i_usize),
+                z_0: Some(self.z_0.clone()),
+                z_i: Some(self.z_i.clone()),
+                external_inputs: Some(external_inputs.clone()),
+                u_i_cmWE: Some(self.u_i.cmWE),
+                U_i: Some(self.U_i.clone()),
+                U_i1_cmWE: Some(U_i1.cmWE),
+                F: self.F.clone(),
+                // cyclefold values
+                cf_u_i_cmW: Some(cf_u_i.cmW),
+                cf_U_i: Some(self.cf_U_i.clone()),
+                cf_cmT: Some(cf_cmTs[0]),
+            };
+
+            self.cf_W_i = cf_W_i1;
+            self.cf_U_i = cf_U_i1;
+
+            (W_i1, U_i1)
+        };
+
+        let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
+
+        let z_i1 = augmented_F_circuit
+            .compute_next_state(cs.clone())?
+            .value()?;
+
+        #[cfg(test)]
+        assert!(cs.is_satisfied()?);
+
+        let cs = cs.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
+        let (w_i1, x_i1) = extract_w_x::<C1::ScalarField>(&cs);
+
+        #[cfg(test)]
+        if x_i1.len() != 2 {
+            return Err(Error::NotExpectedLength(x_i1.len(), 2));
+        }
+
+        // the incoming instance commits to `w_{i+1} || t_{i+1}`, where `t_{i+1}` is the cross
+        // term with respect to the running instance `U_{i+1}` it will be folded into
+        let t_i1 = compute_t(&self.r1cs, &U_i1, &W_i1, &x_i1, &w_i1)?;
+
+        // set values for next iteration
+        self.i += C1::ScalarField::one();
+        self.z_i = z_i1;
+        self.w_i = Witness::<C1>::new::<H>([w_i1, t_i1].concat(), &mut rng);
+        self.u_i = self.w_i.commit::<CS1, H>(&self.cs_pp, x_i1, vec![])?;
+        self.W_i = W_i1;
+        self.U_i = U_i1;
+
+        #[cfg(test)]
+        {
+            self.u_i.check_incoming()?;
+            check_incoming_relation(&self.r1cs, &self.w_i, &self.u_i)?;
+            self.r1cs.check_relation(&self.W_i, &self.U_i)?;
+        }
+
+        Ok(())
+    }
+
+    fn state(&self) -> Vec<C1::ScalarField> {
+        self.z_i.clone()
+    }
+
+    fn steps(&self) -> C1::ScalarField {
+        self.i
+    }
+
+    fn ivc_proof(&self) -> Self::IVCProof {
+        Self::IVCProof {
+            i: self.i,
+            z_0: self.z_0.clone(),
+            z_i: self.z_i.clone(),
+            W_i: self.W_i.clone(),
+            U_i: self.U_i.clone(),
+            w_i: self.w_i.clone(),
+            u_i: self.u_i.clone(),
+            cf_W_i: self.cf_W_i.clone(),
+            cf_U_i: self.cf_U_i.clone(),
+        }
+    }
+
+    fn from_ivc_proof(
+        ivc_proof: IVCProof<C1, C2>,
+        fcircuit_params: FC::Params,
+        params: (Self::ProverParam, Self::VerifierParam),
+    ) -> Result<Self, Error> {
+        let IVCProof {
+            i,
+            z_0,
+            z_i,
+            W_i,
+            U_i,
+            w_i,
+            u_i,
+            cf_W_i,
+            cf_U_i,
+        } = ivc_proof;
+        let (pp, vp) = params;
+
+        let f_circuit = FC::new(fcircuit_params)?;
+        let (r1cs, cf_r1cs) = get_r1cs::<C1, C2, FC>(&pp.poseidon_config, f_circuit.clone())?;
+
+        Ok(Self {
+            r1cs,
+            cf_r1cs,
+            poseidon_config: pp.poseidon_config,
+            cs_pp: pp.cs_pp,
+            cf_cs_pp: pp.cf_cs_pp,
+            F: f_circuit,
+            pp_hash: vp.pp_hash()?,
+            i,
+            z_0,
+            z_i,
+            w_i,
+            u_i,
+            W_i,
+            U_i,
+            cf_W_i,
+            cf_U_i,
+        })
+    }
+
+    /// Implements IVC.V of Ova+CycleFold. Notice that this method does not include the
+    /// commitments verification, which is done in the Decider.
+    fn verify(vp: Self::VerifierParam, ivc_proof: Self::IVCProof) -> Result<(), Error> {
+        let Self::IVCProof {
+            i: num_steps,
+            z_0,
+            z_i,
+            W_i,
+            U_i,
+            w_i,
+            u_i,
+            cf_W_i,
+            cf_U_i,
+        } = ivc_proof;
+
+        let sponge =
+            PoseidonSponge::<C1::ScalarField>::new_with_pp_hash(&vp.poseidon_config, vp.pp_hash()?);
+
+        if num_steps == C1::ScalarField::zero() {
+            if z_0 != z_i {
+                return Err(Error::IVCVerificationFail);
+            }
+            return Ok(());
+        }
+
+        if u_i.x.len() != 2 || U_i.x.len() != 2 {
+            return Err(Error::IVCVerificationFail);
+        }
+
+        // check that u_i's output points to the running instance
+        // u_i.X[0] == H(i, z_0, z_i, U_i)
+        let expected_u_i_x = U_i.hash(&sponge, num_steps, &z_0, &z_i);
+        if expected_u_i_x != u_i.x[0] {
+            return Err(Error::IVCVerificationFail);
+        }
+        // u_i.X[1] == H(cf_U_i)
+        let expected_cf_u_i_x = cf_U_i.hash_cyclefold(&sponge);
+        if expected_cf_u_i_x != u_i.x[1] {
+            return Err(Error::IVCVerificationFail);
+        }
+
+        // check that `u_i` is an incoming instance and that `w_i` satisfies the plain R1CS
+        u_i.check_incoming()?;
+        check_incoming_relation(&vp.r1cs, &w_i, &u_i)?;
+        // check RelaxedR1CS satisfiability
+        vp.r1cs.check_relation(&W_i, &U_i)?;
+
+        // check CycleFold RelaxedR1CS satisfiability
+        vp.cf_r1cs.check_relation(&cf_W_i, &cf_U_i)?;
+
+        Ok(())
+    }
+}
+
+/// Checks that the incoming `w_i`, `u_i` satisfy the plain R1CS. Notice that `w_i.w` holds the
+/// witness concatenated with the cross term `t`, which is not part of the relation.
+fn check_incoming_relation<C: Curve>(
+    r1cs: &R1CS<C::ScalarField>,
+    w_i: &Witness<C>,
+    u_i: &CommittedInstance<C>,
+) -> Result<(), Error> {
+    let n_witnesses = r1cs.n_witnesses();
+    if w_i.w.len() != n_witnesses + r1cs.n_constraints() {
+        return Err(Error::NotSameLength(
+            "w_i.w.len()".to_string(),
+            w_i.w.len(),
+            "n_witnesses + n_constraints".to_string(),
+            n_witnesses + r1cs.n_constraints(),
+        ));
+    }
+    r1cs.check_relation(&w_i.w[..n_witnesses].to_vec(), &u_i.x)
+}
+
+/// helper method to get the R1CS for both the Ova's AugmentedFCircuit and the CycleFold circuit
+#[allow(clippy::type_complexity)]
+pub fn get_r1cs<C1, C2, FC>(
+    poseidon_config: &PoseidonConfig<C1::ScalarField>,
+    F_circuit: FC,
+) -> Result<(R1CS<C1::ScalarField>, R1CS<C2::ScalarField>), Error>
+where
+    C1: Curve,
+    C2: Curve,
+    FC: FCircuit<C1::ScalarField>,
+    C1: Curve<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+{
+    let augmented_F_circuit = AugmentedFCircuit::<C1, C2, FC>::empty(poseidon_config, F_circuit);
+    let cf_circuit = CycleFoldCircuit::<_, OvaCycleFoldConfig<C1>>::default();
+    let r1cs = get_r1cs_from_cs::<C1::ScalarField>(augmented_F_circuit)?;
+    let cf_r1cs = get_r1cs_from_cs::<C2::ScalarField>(cf_circuit)?;
+    Ok((r1cs, cf_r1cs))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ark_bn254::{Bn254, Fr, G1Projective as Projective};
+    use ark_grumpkin::Projective as Projective2;
+
+    use super::*;
+    use crate::commitment::{kzg::KZG, pedersen::Pedersen};
+    use crate::frontend::utils::CubicFCircuit;
+
+    /// This test tests the Ova+CycleFold IVC, and by consequence it is also testing the
+    /// AugmentedFCircuit
+    #[test]
+    fn test_ivc() -> Result<(), Error> {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(())?;
+
+        // run the test using Pedersen commitments on both sides of the curve cycle
+        test_ivc_opt::<Pedersen<Projective>, Pedersen<Projective2>, false>(
+            poseidon_config.clone(),
+            F_circuit,
+            3,
+        )?;
+
+        test_ivc_opt::<Pedersen<Projective, true>, Pedersen<Projective2, true>, true>(
+            poseidon_config.clone(),
+            F_circuit,
+            3,
+        )?;
+
+        // run the test using KZG for the commitments on the main curve, and Pedersen for the
+        // commitments on the secondary curve
+        test_ivc_opt::<KZG<Bn254>, Pedersen<Projective2>, false>(poseidon_config, F_circuit, 3)?;
+        Ok(())
+    }
+
+    // test_ivc allowing to choose the CommitmentSchemes
+    fn test_ivc_opt<
+        CS1: CommitmentScheme<Projective, H>,
+        CS2: CommitmentScheme<Projective2, H>,
+        const H: bool,
+    >(
+        poseidon_config: PoseidonConfig<Fr>,
+        F_circuit: CubicFCircuit<Fr>,
+        num_steps: usize,
+    ) -> Result<(), Error> {
+        type O<CS1, CS2, const H: bool> =
+            Ova<Projective, Projective2, CubicFCircuit<Fr>, CS1, CS2, H>;
+        let mut rng = ark_std::test_rng();
+
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let ova_params = O::<CS1, CS2, H>::preprocess(&mut rng, &prep_param)?;
+
+        let z_0 = vec![Fr::from(3_u32)];
+        let mut ova = O::<CS1, CS2, H>::init(&ova_params, F_circuit, z_0)?;
+
+        for _ in 0..num_steps {
+            ova.prove_step(&mut rng, (), None)?;
+        }
+        assert_eq!(Fr::from(num_steps as u32), ova.i);
+
+        // serialize and deserialize the verifier params and the IVCProof
+        let mut ova_vp_serialized = vec![];
+        ova_params.1.serialize_compressed(&mut ova_vp_serialized)?;
+        let ova_vp_deserialized = O::<CS1, CS2, H>::vp_deserialize_with_mode(
+            &mut ova_vp_serialized.as_slice(),
+            ark_serialize::Compress::Yes,
+            ark_serialize::Validate::Yes,
+            (), // fcircuit_params
+        )?;
+
+        let mut ivc_proof_serialized = vec![];
+        ova.ivc_proof()
+            .serialize_compressed(&mut ivc_proof_serialized)?;
+        let ivc_proof_deserialized = IVCProof::<Projective, Projective2>::deserialize_compressed(
+            ivc_proof_serialized.as_slice(),
+        )?;
+
+        O::<CS1, CS2, H>::verify(ova_vp_deserialized, ivc_proof_deserialized)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::gr1cs::SynthesisError;
+
+use super::{CommittedInstance, CommittedInstanceVar, Witness, WitnessVar};
+use crate::arith::{
+    r1cs::{circuits::R1CSMatricesVar, R1CS},
+    Arith, ArithRelation, ArithRelationGadget,
+};
+use crate::folding::circuits::CF1;
+use crate::utils::gadgets::{EquivalenceGadget, VectorGadget};
+use crate::{Curve, Error};
+
+/// Implements [`ArithRelation`] for R1CS, where the witness is of type [`Witness`], and the
+/// committed instance is of type [`CommittedInstance`].
+///
+/// In the Ova IVC, `Witness.w` is the vector committed in `cmWE`, i.e. the R1CS witness
+/// concatenated with the error term `e` (for running instances). Thus the relation splits
+/// `Witness.w` at `n_witnesses`, and checks that the tail matches the relaxed R1CS evaluation.
+impl<C: Curve> ArithRelation<Witness<C>, CommittedInstance<C>> for R1CS<CF1<C>> {
+    type Evaluation = Vec<CF1<C>>;
+
+    fn eval_relation(
+        &self,
+        w: &Witness<C>,
+        u: &CommittedInstance<C>,
+    ) -> Result<Self::Evaluation, Error> {
+        if w.w.len() != self.n_witnesses() + self.n_constraints() {
+            return Err(Error::NotSameLength(
+                "w.w.len()".to_string(),
+                w.w.len(),
+                "n_witnesses + n_constraints".to_string(),
+                self.n_witnesses() + self.n_constraints(),
+            ));
+        }
+        self.eval_at_z(&[&[u.u][..], &u.x, &w.w[..self.n_witnesses()]].concat())
+    }
+
+    fn check_evaluation(
+        w: &Witness<C>,
+        _u: &CommittedInstance<C>,
+        e: Self::Evaluation,
+    ) -> Result<(), Error> {
+        (w.w.len() >= e.len() && w.w[w.w.len() - e.len()..] == e)
+            .then_some(())
+            .ok_or(Error::NotSatisfied)
+    }
+}
+
+impl<C: Curve> ArithRelationGadget<WitnessVar<C>, CommittedInstanceVar<C>>
+    for R1CSMatricesVar<C::ScalarField, FpVar<C::ScalarField>>
+{
+    type Evaluation = (Vec<FpVar<C::ScalarField>>, Vec<FpVar<C::ScalarField>>);
+
+    fn eval_relation(
+        &self,
+        w: &WitnessVar<C>,
+        u: &CommittedInstanceVar<C>,
+    ) -> Result<Self::Evaluation, SynthesisError> {
+        // `w.W` is the witness concatenated with `e`, where `e` has one entry per constraint
+        let n_witnesses =
+            w.W.len()
+                .checked_sub(self.A.n_rows)
+                .ok_or(SynthesisError::Unsatisfiable)?;
+        self.eval_at_z(&[&[u.u.clone()][..], &u.x, &w.W[..n_witnesses]].concat())
+    }
+
+    fn enforce_evaluation(
+        w: &WitnessVar<C>,
+        _u: &CommittedInstanceVar<C>,
+        (AzBz, uCz): Self::Evaluation,
+    ) -> Result<(), SynthesisError> {
+        let e = &w.W[w.W.len() - AzBz.len()..];
+        EquivalenceGadget::<C::ScalarField>::enforce_equivalent(&AzBz[..], &uCz.add(e)?[..])
+    }
+}
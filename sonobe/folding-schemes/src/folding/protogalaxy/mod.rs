@@ -993,6 +993,10 @@ where
         self.z_i.clone()
     }
 
+    fn steps(&self) -> C1::ScalarField {
+        self.i
+    }
+
     fn ivc_proof(&self) -> Self::IVCProof {
         Self::IVCProof {
             i: self.i,
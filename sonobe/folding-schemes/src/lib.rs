@@ -134,6 +134,8 @@ pub enum Error {
     NoMultiInstances,
     #[error("Missing 'other' instances, since this is a multi-instances folding scheme. Expected number of instances, mu:{0}, nu:{1}")]
     MissingOtherInstances(usize, usize),
+    #[error("Checkpoint error: {0}")]
+    Checkpoint(String),
 }
 
 /// FoldingScheme defines trait that is implemented by the diverse folding schemes. It is defined
@@ -203,6 +205,9 @@ pub trait FoldingScheme<
     /// returns the state at the current step
     fn state(&self) -> Vec<C1::ScalarField>;
 
+    /// returns the number of steps folded so far, `i`
+    fn steps(&self) -> C1::ScalarField;
+
     /// returns the last IVC state proof, which can be verified in the `verify` method
     fn ivc_proof(&self) -> Self::IVCProof;
 
@@ -1,13 +1,12 @@
 use crate::utils::eth::ToEth;
 use ark_bn254::Bn254;
-use ark_groth16::Groth16;
-use crypto::digest::Digest;
-use crypto::sha3::Sha3;
+use ark_groth16::{Groth16, Proof as Groth16Proof, VerifyingKey as Groth16VerifyingKey};
 use folding_schemes::commitment::kzg::KZG;
-use folding_schemes::folding::nova::decider_eth::Proof;
+use folding_schemes::folding::nova::decider_eth::{Proof, ZKProof};
 use folding_schemes::folding::nova::CommittedInstance;
 use folding_schemes::Error;
 use num_bigint::BigUint;
+use sha3::{Digest, Keccak256};
 
 /// Specifies which API to use for a proof verification in a contract.
 #[derive(Copy, Clone, Debug, Default)]
@@ -65,9 +64,82 @@ pub fn prepare_calldata_for_nova_cyclefold_verifier(
     .concat())
 }
 
+/// Prepares solidity calldata for calling the `verifyNovaZKProof` function of the NovaZKDecider
+/// contract, which verifies the proofs of the `ZKDecider`
+pub fn prepare_calldata_for_nova_cyclefold_zk_verifier(
+    i: ark_bn254::Fr,
+    z_0: Vec<ark_bn254::Fr>,
+    z_i: Vec<ark_bn254::Fr>,
+    running_instance: &CommittedInstance<ark_bn254::G1Projective>,
+    incoming_instance: &CommittedInstance<ark_bn254::G1Projective>,
+    proof: &ZKProof<ark_bn254::G1Projective, KZG<Bn254, true>, Groth16<Bn254>>,
+) -> Result<Vec<u8>, Error> {
+    let fold_proof = proof.fold_proof();
+    let random_instance = &fold_proof.U_r;
+    let fn_sig = format!(
+        "verifyNovaZKProof(uint256[{}],uint256[4],uint256[2],uint256[6],uint256[{}],uint256[2],uint256[2][2],uint256[2],uint256[6],uint256[2][2])",
+        z_0.len() * 2 + 1,
+        random_instance.x.len() + 5
+    );
+
+    Ok([
+        keccak_selector(&fn_sig).to_eth(),
+        i.to_eth(),   // i
+        z_0.to_eth(), // z_0
+        z_i.to_eth(), // z_i
+        running_instance.cmW.to_eth(),
+        running_instance.cmE.to_eth(),
+        incoming_instance.cmW.to_eth(),
+        fold_proof.cmT.to_eth(),                 // cmT
+        fold_proof.cmT_r.to_eth(),               // cmT_r
+        proof.r().to_eth(),                      // r, r_r
+        random_instance.u.to_eth(),              // U_r.u
+        random_instance.x.to_eth(),              // U_r.x
+        random_instance.cmE.to_eth(),            // U_r.cmE
+        random_instance.cmW.to_eth(),            // U_r.cmW
+        proof.snark_proof().to_eth(),            // pA, pB, pC
+        proof.kzg_challenges().to_eth(),         // challenge_W, challenge_E
+        proof.kzg_proofs()[0].eval.to_eth(),     // eval W
+        proof.kzg_proofs()[1].eval.to_eth(),     // eval E
+        proof.kzg_proofs()[0].random_v.to_eth(), // random_v W
+        proof.kzg_proofs()[1].random_v.to_eth(), // random_v E
+        proof.kzg_proofs()[0].proof.to_eth(),    // W kzg_proof
+        proof.kzg_proofs()[1].proof.to_eth(),    // E kzg_proof
+    ]
+    .concat())
+}
+
+/// Prepares solidity calldata for calling the `verifyProof(pA, pB, pC, pubSignals)` function of the
+/// Groth16 verifier rendered from the given verifying key. The public inputs are expected in the
+/// order in which they were allocated in the circuit.
+pub fn prepare_calldata_for_groth16_verifier(
+    vk: &Groth16VerifyingKey<Bn254>,
+    proof: &Groth16Proof<Bn254>,
+    public_inputs: &[ark_bn254::Fr],
+) -> Result<Vec<u8>, Error> {
+    // `gamma_abc_g1` has one element per public input, plus the one for the constant `1`
+    if public_inputs.len() + 1 != vk.gamma_abc_g1.len() {
+        return Err(Error::NotExpectedLength(
+            public_inputs.len(),
+            vk.gamma_abc_g1.len().saturating_sub(1),
+        ));
+    }
+    let fn_sig = format!(
+        "verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{}])",
+        public_inputs.len()
+    );
+
+    Ok([
+        keccak_selector(&fn_sig).to_eth(),
+        proof.to_eth(),         // pA, pB, pC
+        public_inputs.to_eth(), // pubSignals
+    ]
+    .concat())
+}
+
 /// Computes the function selector for the nova cyclefold verifier.
 /// It is computed on the fly since it depends on the IVC state length.
-fn get_function_selector(mode: NovaVerificationMode, state_len: usize) -> [u8; 4] {
+pub fn get_function_selector(mode: NovaVerificationMode, state_len: usize) -> [u8; 4] {
     let fn_sig = match mode {
         NovaVerificationMode::Explicit =>
             format!(
@@ -80,9 +152,11 @@ fn get_function_selector(mode: NovaVerificationMode, state_len: usize) -> [u8; 4
             format!("verifyOpaqueNovaProofWithInputs(uint256,uint256[{state_len}],uint256[{state_len}],uint256[25])"),
     };
 
-    let mut hasher = Sha3::keccak256();
-    hasher.input_str(&fn_sig);
-    let hash = &mut [0u8; 32];
-    hasher.result(hash);
+    keccak_selector(&fn_sig)
+}
+
+/// Computes the function selector for the given function signature
+pub fn keccak_selector(fn_sig: &str) -> [u8; 4] {
+    let hash = Keccak256::digest(fn_sig.as_bytes());
     [hash[0], hash[1], hash[2], hash[3]]
 }
@@ -1,4 +1,5 @@
 pub mod calldata;
+#[cfg(feature = "evm")]
 pub mod evm;
 pub mod utils;
 pub mod verifiers;
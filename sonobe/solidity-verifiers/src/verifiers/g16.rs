@@ -88,6 +88,7 @@ mod tests {
     use askama::Template;
 
     use super::Groth16Verifier;
+    use crate::calldata::prepare_calldata_for_groth16_verifier;
     use crate::verifiers::tests::{setup, DEFAULT_SETUP_LEN};
 
     pub const FUNCTION_SELECTOR_GROTH16_VERIFY_PROOF: [u8; 4] = [0x43, 0x75, 0x3b, 0x4d];
@@ -142,4 +143,35 @@ mod tests {
         let (_, output) = evm.call(verifier_address, calldata);
         assert_eq!(*output.last().unwrap(), 0);
     }
+
+    #[test]
+    fn groth16_calldata_includes_public_inputs() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let (_, _, _, g16_pk, g16_vk, circuit) = setup(DEFAULT_SETUP_LEN);
+        let proof = Groth16::<Bn254>::prove(&g16_pk, circuit, &mut rng).unwrap();
+
+        let z = Fr::from(circuit.z);
+        let calldata = prepare_calldata_for_groth16_verifier(&g16_vk, &proof, &[z]).unwrap();
+        let (a_x, a_y) = proof.a.xy().unwrap();
+        let (b_x, b_y) = proof.b.xy().unwrap();
+        let (c_x, c_y) = proof.c.xy().unwrap();
+        let expected_calldata: Vec<u8> = [
+            &FUNCTION_SELECTOR_GROTH16_VERIFY_PROOF[..],
+            &a_x.into_bigint().to_bytes_be(),
+            &a_y.into_bigint().to_bytes_be(),
+            &b_x.c1.into_bigint().to_bytes_be(),
+            &b_x.c0.into_bigint().to_bytes_be(),
+            &b_y.c1.into_bigint().to_bytes_be(),
+            &b_y.c0.into_bigint().to_bytes_be(),
+            &c_x.into_bigint().to_bytes_be(),
+            &c_y.into_bigint().to_bytes_be(),
+            &BigInt::from(z).to_bytes_be(),
+        ]
+        .concat();
+        assert_eq!(calldata, expected_calldata);
+
+        // the number of public inputs must match the verifying key
+        assert!(prepare_calldata_for_groth16_verifier(&g16_vk, &proof, &[]).is_err());
+        assert!(prepare_calldata_for_groth16_verifier(&g16_vk, &proof, &[z, z]).is_err());
+    }
 }
@@ -14,6 +14,8 @@ use super::PRAGMA_KZG10_VERIFIER;
 pub struct KZG10Verifier {
     /// The generator of `G1`.
     pub(crate) g1: G1Repr,
+    /// The generator of `G1` used to blind hiding commitments.
+    pub(crate) gamma_g1: G1Repr,
     /// The generator of `G2`.
     pub(crate) g2: G2Repr,
     /// The verification key
@@ -28,6 +30,7 @@ impl From<KZG10VerifierKey> for KZG10Verifier {
     fn from(data: KZG10VerifierKey) -> Self {
         Self {
             g1: g1_to_fq_repr(data.vk.g),
+            gamma_g1: g1_to_fq_repr(data.vk.gamma_g),
             g2: g2_to_fq_repr(data.vk.h),
             vk: g2_to_fq_repr(data.vk.beta_h),
             g1_crs_len: data.g1_crs_batch_points.len(),
@@ -88,7 +91,10 @@ mod tests {
     use askama::Template;
 
     use folding_schemes::{
-        commitment::{kzg::KZG, CommitmentScheme},
+        commitment::{
+            kzg::{commit_blinding_polynomial, open, KZG},
+            CommitmentScheme,
+        },
         transcript::{poseidon::poseidon_canonical_config, Transcript},
     };
 
@@ -96,6 +102,7 @@ mod tests {
     use crate::verifiers::tests::{setup, DEFAULT_SETUP_LEN};
 
     const FUNCTION_SELECTOR_KZG10_CHECK: [u8; 4] = [0x9e, 0x78, 0xcc, 0xf7];
+    const FUNCTION_SELECTOR_KZG10_CHECK_HIDING: [u8; 4] = [0x4c, 0x67, 0xab, 0xea];
 
     #[test]
     fn kzg_vk_serde_roundtrip() {
@@ -180,4 +187,80 @@ mod tests {
         let (_, output) = evm.call(verifier_address, calldata);
         assert_eq!(*output.last().unwrap(), 0);
     }
+
+    #[test]
+    fn kzg_verifier_accepts_and_rejects_hiding_proofs() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+
+        let (_, kzg_pk, kzg_vk, _, _, _) = setup(DEFAULT_SETUP_LEN);
+        let kzg_vk = KZG10VerifierKey::from((kzg_vk.clone(), kzg_pk.powers_of_g[0..3].to_vec()));
+
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+            .take(DEFAULT_SETUP_LEN)
+            .collect();
+        // blinding polynomial of degree 1, whose constant term is the blinding factor
+        let r = [Fr::rand(&mut rng), Fr::rand(&mut rng)];
+        let cm = KZG::<Bn254, true>::commit(&kzg_pk, &v, &r[0]).unwrap()
+            + commit_blinding_polynomial(&kzg_pk, &[Fr::zero(), r[1]]).unwrap();
+        transcript_p.absorb_nonnative(&cm);
+        let proof = open(&kzg_pk, transcript_p.get_challenge(), &v, &r).unwrap();
+        let template = HeaderInclusion::<KZG10Verifier>::builder()
+            .template(kzg_vk)
+            .build()
+            .render()
+            .unwrap();
+
+        let kzg_verifier_bytecode = compile_solidity(template, "KZG10Verifier");
+        let mut evm = Evm::default();
+        let verifier_address = evm.create(kzg_verifier_bytecode);
+
+        let (cm_affine, proof_affine) = (cm.into_affine(), proof.proof.into_affine());
+        let (x_comm, y_comm) = cm_affine.xy().unwrap();
+        let (x_proof, y_proof) = proof_affine.xy().unwrap();
+        let y = proof.eval.into_bigint().to_bytes_be();
+        let random_v = proof.random_v.into_bigint().to_bytes_be();
+
+        transcript_v.absorb_nonnative(&cm);
+        let x = transcript_v.get_challenge();
+
+        let x = x.into_bigint().to_bytes_be();
+        let calldata_without_blinding: Vec<u8> = [
+            &x_comm.into_bigint().to_bytes_be()[..],
+            &y_comm.into_bigint().to_bytes_be(),
+            &x_proof.into_bigint().to_bytes_be(),
+            &y_proof.into_bigint().to_bytes_be(),
+            &x,
+            &y,
+        ]
+        .concat();
+        let mut calldata: Vec<u8> = [
+            &FUNCTION_SELECTOR_KZG10_CHECK_HIDING[..],
+            &calldata_without_blinding,
+            &random_v,
+        ]
+        .concat();
+
+        let (_, output) = evm.call(verifier_address, calldata.clone());
+        assert_eq!(*output.last().unwrap(), 1);
+
+        // the non-hiding check must reject the hiding commitment
+        let (_, output) = evm.call(
+            verifier_address,
+            [
+                &FUNCTION_SELECTOR_KZG10_CHECK[..],
+                &calldata_without_blinding,
+            ]
+            .concat(),
+        );
+        assert_eq!(*output.last().unwrap(), 0);
+
+        // change calldata to make it invalid
+        let last_calldata_element = calldata.last_mut().unwrap();
+        *last_calldata_element = last_calldata_element.wrapping_add(1);
+        let (_, output) = evm.call(verifier_address, calldata);
+        assert_eq!(*output.last().unwrap(), 0);
+    }
 }
@@ -4,6 +4,7 @@
 // Pragma statements for verifiers
 pub const PRAGMA_GROTH16_VERIFIER: &str = "pragma solidity >=0.7.0 <0.9.0;"; // from snarkjs, avoid changing
 pub const PRAGMA_KZG10_VERIFIER: &str = "pragma solidity >=0.8.1 <=0.8.4;";
+pub const PRAGMA_POSEIDON: &str = "pragma solidity ^0.8.20;";
 
 /// Default SDPX License identifier
 pub const GPL3_SDPX_IDENTIFIER: &str = "// SPDX-License-Identifier: GPL-3.0";
@@ -13,10 +14,15 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, Serializatio
 pub mod g16;
 pub mod kzg;
 pub mod nova_cyclefold;
+pub mod poseidon;
 
 pub use g16::Groth16VerifierKey;
 pub use kzg::KZG10VerifierKey;
-pub use nova_cyclefold::{get_decider_template_for_cyclefold_decider, NovaCycleFoldVerifierKey};
+pub use nova_cyclefold::{
+    get_decider_template_for_cyclefold_decider, get_decider_template_for_cyclefold_zk_decider,
+    NovaCycleFoldVerifierKey,
+};
+pub use poseidon::get_poseidon_hasher_template;
 
 pub trait ProtocolVerifierKey: CanonicalDeserialize + CanonicalSerialize {
     const PROTOCOL_NAME: &'static str;
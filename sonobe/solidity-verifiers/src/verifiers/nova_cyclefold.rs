@@ -26,6 +26,19 @@ pub fn get_decider_template_for_cyclefold_decider(
         .unwrap()
 }
 
+/// Renders the verifier contract of the `ZKDecider`, which decides hiding Nova IVC proofs. It uses
+/// the same verifier key as the non-hiding decider, whose KZG10 verifier also checks the openings
+/// of hiding commitments.
+pub fn get_decider_template_for_cyclefold_zk_decider(
+    nova_cyclefold_vk: NovaCycleFoldVerifierKey,
+) -> String {
+    HeaderInclusion::<NovaCycleFoldZKDecider>::builder()
+        .template(nova_cyclefold_vk)
+        .build()
+        .render()
+        .unwrap()
+}
+
 #[derive(Template, Default)]
 #[template(path = "nova_cyclefold_decider.askama.sol", ext = "sol")]
 pub struct NovaCycleFoldDecider {
@@ -56,6 +69,48 @@ impl From<NovaCycleFoldVerifierKey> for NovaCycleFoldDecider {
     }
 }
 
+#[derive(Template, Default)]
+#[template(path = "nova_cyclefold_zk_decider.askama.sol", ext = "sol")]
+pub struct NovaCycleFoldZKDecider {
+    pp_hash: Fr, // public params hash
+    groth16_verifier: Groth16Verifier,
+    kzg10_verifier: KZG10Verifier,
+    // z_len denotes the FCircuit state (z_i) length
+    z_len: usize,
+    // io_len denotes the length of the public inputs `x` of the random instance U_r
+    io_len: usize,
+    public_inputs_len: usize,
+    num_limbs: usize,
+    bits_per_limb: usize,
+}
+
+impl From<NovaCycleFoldVerifierKey> for NovaCycleFoldZKDecider {
+    fn from(value: NovaCycleFoldVerifierKey) -> Self {
+        let NovaCycleFoldDecider {
+            pp_hash,
+            groth16_verifier,
+            kzg10_verifier,
+            z_len,
+            public_inputs_len,
+            num_limbs,
+            bits_per_limb,
+        } = NovaCycleFoldDecider::from(value);
+        // the public inputs are [pp_hash, i, z_0, z_i, cmW, cmE, challenges, evals, cmT, U_r,
+        // cmT_r], where U_r = [u, x, cmE, cmW], and each commitment takes 2 * num_limbs elements
+        let io_len = public_inputs_len - 1 - (7 + 2 * z_len + 12 * num_limbs);
+        Self {
+            pp_hash,
+            groth16_verifier,
+            kzg10_verifier,
+            z_len,
+            io_len,
+            public_inputs_len,
+            num_limbs,
+            bits_per_limb,
+        }
+    }
+}
+
 #[derive(CanonicalDeserialize, CanonicalSerialize, PartialEq, Debug, Clone)]
 pub struct NovaCycleFoldVerifierKey {
     pp_hash: Fr,
@@ -152,19 +207,28 @@ mod tests {
 
     use super::{DeciderVerifierParam, NovaCycleFoldDecider};
     use crate::calldata::NovaVerificationMode::{Explicit, Opaque, OpaqueWithInputs};
-    use crate::calldata::{prepare_calldata_for_nova_cyclefold_verifier, NovaVerificationMode};
+    use crate::calldata::{
+        prepare_calldata_for_nova_cyclefold_verifier,
+        prepare_calldata_for_nova_cyclefold_zk_verifier, NovaVerificationMode,
+    };
     use crate::verifiers::tests::{setup, DEFAULT_SETUP_LEN};
     use crate::{
         evm::{compile_solidity, save_solidity, Evm},
         utils::HeaderInclusion,
-        verifiers::nova_cyclefold::get_decider_template_for_cyclefold_decider,
+        verifiers::nova_cyclefold::{
+            get_decider_template_for_cyclefold_decider,
+            get_decider_template_for_cyclefold_zk_decider,
+        },
         NovaCycleFoldVerifierKey, ProtocolVerifierKey,
     };
     use folding_schemes::folding::nova::decider_eth::Proof;
     use folding_schemes::{
         commitment::{kzg::KZG, pedersen::Pedersen},
         folding::{
-            nova::{decider_eth::Decider as DeciderEth, Nova, PreprocessorParam},
+            nova::{
+                decider_eth::{Decider as DeciderEth, ZKDecider},
+                Nova, PreprocessorParam,
+            },
             traits::CommittedInstanceOps,
         },
         frontend::FCircuit,
@@ -430,4 +494,67 @@ mod tests {
     fn nova_cyclefold_solidity_verifier_multi_input() {
         nova_cyclefold_solidity_verifier_test::<MultiInputsFCircuit<Fr>>(vec![Fr::from(1_u32); 5]);
     }
+
+    #[test]
+    fn nova_cyclefold_zk_solidity_verifier() {
+        type ZK_NOVA =
+            Nova<G1, G2, CubicFCircuit<Fr>, KZG<'static, Bn254, true>, Pedersen<G2, true>, true>;
+        type ZK_DECIDER = ZKDecider<
+            G1,
+            G2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254, true>,
+            Pedersen<G2, true>,
+            Groth16<Bn254>,
+            ZK_NOVA,
+        >;
+
+        let mut rng = ark_std::rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_config, f_circuit);
+        let nova_params = ZK_NOVA::preprocess(&mut rng, &prep_param).unwrap();
+        let (decider_pp, decider_vp) =
+            ZK_DECIDER::preprocess(&mut rng, (nova_params.clone(), f_circuit.state_len())).unwrap();
+
+        let mut nova = ZK_NOVA::init(&nova_params, f_circuit, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..3 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+        }
+        let proof = ZK_DECIDER::prove(rng, decider_pp, nova.clone()).unwrap();
+
+        let nova_cyclefold_vk = NovaCycleFoldVerifierKey::from((decider_vp, f_circuit.state_len()));
+        let decider_solidity_code =
+            get_decider_template_for_cyclefold_zk_decider(nova_cyclefold_vk);
+        let bytecode = compile_solidity(decider_solidity_code, "NovaZKDecider");
+
+        let mut evm = Evm::default();
+        let verifier_address = evm.create(bytecode);
+
+        let calldata = prepare_calldata_for_nova_cyclefold_zk_verifier(
+            nova.i,
+            nova.z_0.clone(),
+            nova.z_i.clone(),
+            &nova.U_i,
+            &nova.u_i,
+            &proof,
+        )
+        .unwrap();
+        let (_, output) = evm.call(verifier_address, calldata.clone());
+        assert_eq!(*output.last().unwrap(), 1);
+
+        // change z_i to make the EVM check fail, placed between bytes 68 - 100
+        let mut invalid_calldata = calldata.clone();
+        invalid_calldata[99] += 1;
+        let (_, output) = evm.call(verifier_address, invalid_calldata);
+        assert_eq!(*output.last().unwrap(), 0);
+
+        // change the blinding of the last KZG opening, placed before the two KZG proofs
+        let mut invalid_calldata = calldata;
+        let random_v_E_last_byte = invalid_calldata.len() - 4 * 32 - 1;
+        invalid_calldata[random_v_E_last_byte] ^= 1;
+        let (_, output) = evm.call(verifier_address, invalid_calldata);
+        assert_eq!(*output.last().unwrap(), 0);
+    }
 }
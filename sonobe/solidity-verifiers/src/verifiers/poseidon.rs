@@ -0,0 +1,167 @@
+//! Poseidon hasher contract, generated from the `PoseidonConfig` of the circuits, so that contracts
+//! can recompute the Poseidon hashes chained into the IVC state by the circuits.
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ff::{BigInteger, PrimeField};
+use askama::Template;
+
+use super::PRAGMA_POSEIDON;
+use crate::utils::HeaderInclusion;
+use crate::MIT_SDPX_IDENTIFIER;
+
+#[derive(Template, Default)]
+#[template(path = "poseidon.askama.sol", ext = "sol")]
+pub struct PoseidonHasher {
+    /// Number of elements absorbed per permutation.
+    pub(crate) rate: usize,
+    /// Number of elements of the state that are never absorbed into nor squeezed.
+    pub(crate) capacity: usize,
+    /// Number of elements of the state, `rate + capacity`.
+    pub(crate) width: usize,
+    pub(crate) full_rounds: usize,
+    pub(crate) partial_rounds: usize,
+    /// The round constants, as the hex string of their big-endian encodings, round by round.
+    pub(crate) ark: String,
+    /// The MDS matrix.
+    pub(crate) mds: Vec<Vec<Fr>>,
+}
+
+impl From<&PoseidonConfig<Fr>> for PoseidonHasher {
+    /// # Panics
+    /// Panics if the S-box of the configuration is not x^5, the only one supported by the template.
+    fn from(config: &PoseidonConfig<Fr>) -> Self {
+        assert_eq!(
+            config.alpha, 5,
+            "the Poseidon template only supports the x^5 S-box"
+        );
+        Self {
+            rate: config.rate,
+            capacity: config.capacity,
+            width: config.rate + config.capacity,
+            full_rounds: config.full_rounds,
+            partial_rounds: config.partial_rounds,
+            ark: config
+                .ark
+                .iter()
+                .flatten()
+                .flat_map(|c| c.into_bigint().to_bytes_be())
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+            mds: config.mds.clone(),
+        }
+    }
+}
+
+/// Renders the `Poseidon` contract, whose `hash` matches `PoseidonSponge` with `poseidon_config`.
+/// The contract shipped in `contracts/src/Poseidon.sol` is rendered from
+/// `poseidon_canonical_config`.
+pub fn get_poseidon_hasher_template(poseidon_config: &PoseidonConfig<Fr>) -> String {
+    HeaderInclusion::<PoseidonHasher>::builder()
+        .sdpx(MIT_SDPX_IDENTIFIER)
+        .pragma_version(PRAGMA_POSEIDON)
+        .template(poseidon_config)
+        .build()
+        .render()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+    use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, CryptographicSponge};
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_std::{test_rng, UniformRand};
+    use std::str::FromStr;
+
+    use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+
+    use super::get_poseidon_hasher_template;
+    use crate::{
+        calldata::keccak_selector,
+        evm::{compile_solidity, Evm},
+        utils::eth::ToEth,
+    };
+
+    /// Contract shipped with the fund contracts, which must be the rendering of the canonical
+    /// configuration
+    const SHIPPED_POSEIDON: &str = include_str!("../../../../contracts/src/Poseidon.sol");
+
+    fn sponge_hash(inputs: &[Fr]) -> Fr {
+        let mut sponge = PoseidonSponge::<Fr>::new(&poseidon_canonical_config());
+        sponge.absorb(&inputs);
+        sponge.squeeze_field_elements(1)[0]
+    }
+
+    /// Calldata of `hash(uint256[])`
+    fn hash_calldata(inputs: &[Fr]) -> Vec<u8> {
+        [
+            keccak_selector("hash(uint256[])").to_eth(),
+            Fr::from(32u8).to_eth(), // offset of the array
+            Fr::from(inputs.len() as u64).to_eth(),
+            inputs.to_eth(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn poseidon_hasher_matches_sponge() {
+        let mut rng = test_rng();
+        let bytecode = compile_solidity(
+            get_poseidon_hasher_template(&poseidon_canonical_config()),
+            "Poseidon",
+        );
+        let mut evm = Evm::default();
+        let address = evm.create(bytecode);
+
+        // empty input, partial and full rates, several permutations, and the 22 elements of a
+        // ciphertext of the demo fund
+        for len in [0, 1, 2, 3, 4, 5, 8, 9, 22] {
+            let inputs: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+                .take(len)
+                .collect();
+            let (gas, output) = evm.call(address, hash_calldata(&inputs));
+            println!("Poseidon hash of {len} elements: {gas} gas");
+            assert_eq!(output, sponge_hash(&inputs).into_bigint().to_bytes_be());
+        }
+    }
+
+    #[test]
+    fn poseidon_test_vectors() {
+        // Vectors checked by the Foundry tests of the shipped contract
+        let inputs = [1u8, 2, 3, 4].map(Fr::from);
+        assert_eq!(
+            sponge_hash(&inputs),
+            Fr::from_str(
+                "7817711165059374331357136443537800893307845083525445872661165200086166013245"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            sponge_hash(&[]),
+            Fr::from_str(
+                "18299911814700648280742410696351220515353834127246957628534618031453646577154"
+            )
+            .unwrap()
+        );
+        let inputs: Vec<Fr> = (1u8..=9).map(Fr::from).collect();
+        assert_eq!(
+            sponge_hash(&inputs),
+            Fr::from_str(
+                "3402111777366377077762529803335651546215060635698385595629854299130918549031"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn shipped_poseidon_contract_is_up_to_date() {
+        let rendered = get_poseidon_hasher_template(&poseidon_canonical_config());
+        assert!(
+            SHIPPED_POSEIDON
+                .split_whitespace()
+                .eq(rendered.split_whitespace()),
+            "contracts/src/Poseidon.sol is out of date, regenerate it with \
+             `get_poseidon_hasher_template(&poseidon_canonical_config())`"
+        );
+    }
+}
@@ -115,6 +115,11 @@ contract KZG10Verifier {
             {{ g1.0[0] }},
             {{ g1.0[1] }}
     ];
+    // generator used to blind the hiding KZG commitments, gamma_g of the setup
+    uint256[2] GAMMA_G_1 = [
+            {{ gamma_g1.0[0] }},
+            {{ gamma_g1.0[1] }}
+    ];
     uint256[2][2] G_2 = [
         [
             {{ g2.0[0][0] }},
@@ -185,6 +190,29 @@ contract KZG10Verifier {
         return pairing(pi, VK, rhs_pairing, G_2);
     }
 
+    /**
+     * @notice  Verifies a single point evaluation proof of a hiding commitment.
+     * @dev     Same as `check`, but the blinding `random_v * gamma_g1` is removed from the commitment, and
+     *          pi is blinded with the quotient of the blinding polynomial.
+     * @param   c  G_1 point hiding commitment to polynomial.
+     * @param   pi G_1 point proof.
+     * @param   x  Value to prove evaluation of polynomial at.
+     * @param   y  Evaluation poly(x).
+     * @param   random_v  Evaluation of the blinding polynomial at x.
+     * @return  result Indicates if KZG proof is correct.
+     */
+    function checkHiding(uint256[2] calldata c, uint256[2] calldata pi, uint256 x, uint256 y, uint256 random_v)
+        public
+        view
+        returns (bool result)
+    {
+        // e(pi, vk) * e(x * -pi - c + y * g1 + random_v * gamma_g1, g2) = 1
+        uint256[2] memory rhs_pairing = add(
+            mulScalar(negate(pi), x), add(negate(c), add(mulScalar(G_1, y), mulScalar(GAMMA_G_1, random_v)))
+        );
+        return pairing(pi, VK, rhs_pairing, G_2);
+    }
+
     function evalPolyAt(uint256[] memory _coefficients, uint256 _index) public pure returns (uint256) {
         uint256 m = BN254_SCALAR_FIELD;
         uint256 result = 0;
@@ -0,0 +1,169 @@
+/*
+    Sonobe's zero-knowledge Nova + CycleFold decider verifier.
+    Joint effort by 0xPARC & PSE.
+
+    More details at https://github.com/privacy-scaling-explorations/sonobe
+    Usage and design documentation at https://privacy-scaling-explorations.github.io/sonobe-docs/
+
+    Uses the https://github.com/iden3/snarkjs/blob/master/templates/verifier_groth16.sol.ejs
+    Groth16 verifier implementation and a KZG10 Solidity template adapted from
+    https://github.com/weijiekoh/libkzg.
+    Additionally we implement the NovaZKDecider contract, which combines the
+    Groth16 and (hiding) KZG10 verifiers to verify the zkSNARK proofs coming from
+    the ZKDecider of hiding Nova+CycleFold folding, where the folded instance is
+    randomized with a random instance U_r before being decided.
+*/
+
+
+/* =============================== */
+/* KZG10 verifier methods */
+{{ kzg10_verifier }}
+
+/* =============================== */
+/* Groth16 verifier methods */
+{{ groth16_verifier }}
+
+
+/* =============================== */
+/* Nova+CycleFold ZK Decider verifier */
+/**
+ * @notice  Computes the decomposition of a `uint256` into num_limbs limbs of bits_per_limb bits each.
+ * @dev     Compatible with sonobe::folding-schemes::folding::circuits::nonnative::nonnative_field_to_field_elements.
+ */
+library LimbsDecomposition {
+    function decompose(uint256 x) internal pure returns (uint256[{{num_limbs}}] memory) {
+        uint256[{{num_limbs}}] memory limbs;
+        for (uint8 i = 0; i < {{num_limbs}}; i++) {
+            limbs[i] = (x >> ({{bits_per_limb}} * i)) & ((1 << {{bits_per_limb}}) - 1);
+        }
+        return limbs;
+    }
+}
+
+/**
+ * @author  PSE & 0xPARC
+ * @title   NovaZKDecider contract, for verifying zero-knowledge Nova IVC SNARK proofs.
+ * @dev     This is an askama template which, when templated, features a Groth16 and KZG10 verifiers from which this contract inherits.
+ */
+contract NovaZKDecider is Groth16Verifier, KZG10Verifier {
+    /**
+     * @notice  Writes the limbs of the coordinates of the point `p` into `public_inputs`, starting at `offset`.
+     */
+    function inputizePoint(uint256[{{ public_inputs_len - 1 }}] memory public_inputs, uint256 offset, uint256[2] memory p)
+        internal
+        pure
+    {
+        uint256[{{num_limbs}}] memory x_limbs = LimbsDecomposition.decompose(p[0]);
+        uint256[{{num_limbs}}] memory y_limbs = LimbsDecomposition.decompose(p[1]);
+        for (uint8 k = 0; k < {{num_limbs}}; k++) {
+            public_inputs[offset + k] = x_limbs[k];
+            public_inputs[offset + {{num_limbs}} + k] = y_limbs[k];
+        }
+    }
+
+    /**
+     * @notice  Verifies a zero-knowledge nova cyclefold proof consisting of two hiding KZG proofs and of a groth16 proof.
+     * @dev     The selector of this function is "dynamic", since it depends on `z_len` and on the length of the
+     *          public inputs of the folded instances.
+     */
+    function verifyNovaZKProof(
+        // inputs are grouped to prevent errors due stack too deep
+        uint256[{{ 1 + z_len * 2 }}] calldata i_z0_zi, // [i, z0, zi] where |z0| == |zi|
+        uint256[4] calldata U_i_cmW_U_i_cmE, // [U_i_cmW[2], U_i_cmE[2]]
+        uint256[2] calldata u_i_cmW, // [u_i_cmW[2]]
+        uint256[6] calldata cmT_cmT_r_r, // [cmT[2], cmT_r[2], r, r_r]
+        uint256[{{ 5 + io_len }}] calldata U_r, // [u, x[{{ io_len }}], cmE[2], cmW[2]] of the random instance
+        uint256[2] calldata pA, // groth16
+        uint256[2][2] calldata pB, // groth16
+        uint256[2] calldata pC, // groth16
+        uint256[6] calldata challenge_W_challenge_E_kzg_evals_random_v, // [challenge_W, challenge_E, eval_W, eval_E, random_v_W, random_v_E]
+        uint256[2][2] calldata kzg_proof // [proof_W, proof_E]
+    ) public view returns (bool) {
+
+        require(i_z0_zi[0] >= 2, "Folding: the number of folded steps should be at least 2");
+
+        // from gamma_abc_len, we subtract 1.
+        uint256[{{ public_inputs_len - 1 }}] memory public_inputs;
+
+        public_inputs[0] = {{pp_hash}};
+        public_inputs[1] = i_z0_zi[0];
+
+        for (uint i = 0; i < {{ z_len * 2 }}; i++) {
+            public_inputs[2 + i] = i_z0_zi[1 + i];
+        }
+
+        {
+            // U_i.cmW + r * u_i.cmW + r_r * U_r.cmW
+            uint256[2] memory cmW = super.add(
+                [U_i_cmW_U_i_cmE[0], U_i_cmW_U_i_cmE[1]],
+                super.mulScalar([u_i_cmW[0], u_i_cmW[1]], cmT_cmT_r_r[4])
+            );
+            cmW = super.add(cmW, super.mulScalar([U_r[{{ 3 + io_len }}], U_r[{{ 4 + io_len }}]], cmT_cmT_r_r[5]));
+
+            inputizePoint(public_inputs, {{ z_len * 2 + 2 }}, cmW);
+
+            require(
+                this.checkHiding(
+                    cmW,
+                    kzg_proof[0],
+                    challenge_W_challenge_E_kzg_evals_random_v[0],
+                    challenge_W_challenge_E_kzg_evals_random_v[2],
+                    challenge_W_challenge_E_kzg_evals_random_v[4]
+                ),
+                "KZG: verifying proof for challenge W failed"
+            );
+        }
+
+        {
+            // U_i.cmE + r * cmT + r_r * cmT_r + r_r^2 * U_r.cmE
+            uint256 r_r = cmT_cmT_r_r[5];
+            uint256[2] memory cmE = super.add(
+                [U_i_cmW_U_i_cmE[2], U_i_cmW_U_i_cmE[3]],
+                super.mulScalar([cmT_cmT_r_r[0], cmT_cmT_r_r[1]], cmT_cmT_r_r[4])
+            );
+            cmE = super.add(cmE, super.mulScalar([cmT_cmT_r_r[2], cmT_cmT_r_r[3]], r_r));
+            cmE = super.add(
+                cmE,
+                super.mulScalar([U_r[{{ 1 + io_len }}], U_r[{{ 2 + io_len }}]], mulmod(r_r, r_r, BN254_SCALAR_FIELD))
+            );
+
+            inputizePoint(public_inputs, {{ z_len * 2 + 2 + num_limbs * 2 }}, cmE);
+
+            require(
+                this.checkHiding(
+                    cmE,
+                    kzg_proof[1],
+                    challenge_W_challenge_E_kzg_evals_random_v[1],
+                    challenge_W_challenge_E_kzg_evals_random_v[3],
+                    challenge_W_challenge_E_kzg_evals_random_v[5]
+                ),
+                "KZG: verifying proof for challenge E failed"
+            );
+        }
+
+        {
+            // add challenges and evaluations
+            for (uint i = 0; i < 4; i++) {
+                public_inputs[{{ z_len * 2 + 2 + num_limbs * 4 }} + i] = challenge_W_challenge_E_kzg_evals_random_v[i];
+            }
+
+            // cmT
+            inputizePoint(public_inputs, {{ z_len * 2 + 6 + num_limbs * 4 }}, [cmT_cmT_r_r[0], cmT_cmT_r_r[1]]);
+
+            // U_r, allocated as [u, x, cmE, cmW]
+            for (uint i = 0; i < {{ 1 + io_len }}; i++) {
+                public_inputs[{{ z_len * 2 + 6 + num_limbs * 6 }} + i] = U_r[i];
+            }
+            inputizePoint(public_inputs, {{ z_len * 2 + 7 + io_len + num_limbs * 6 }}, [U_r[{{ 1 + io_len }}], U_r[{{ 2 + io_len }}]]);
+            inputizePoint(public_inputs, {{ z_len * 2 + 7 + io_len + num_limbs * 8 }}, [U_r[{{ 3 + io_len }}], U_r[{{ 4 + io_len }}]]);
+
+            // cmT_r
+            inputizePoint(public_inputs, {{ z_len * 2 + 7 + io_len + num_limbs * 10 }}, [cmT_cmT_r_r[2], cmT_cmT_r_r[3]]);
+
+            bool success_g16 = this.verifyProof(pA, pB, pC, public_inputs);
+            require(success_g16 == true, "Groth16: verifying proof failed");
+        }
+
+        return(true);
+    }
+}
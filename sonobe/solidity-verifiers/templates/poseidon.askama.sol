@@ -0,0 +1,77 @@
+/**
+ * @title   Poseidon hasher over the BN254 scalar field.
+ * @notice  Generated from an arkworks `PoseidonConfig`: width {{ width }} (rate {{ rate }}, capacity {{ capacity }}),
+ *          x^5 S-box, {{ full_rounds }} full and {{ partial_rounds }} partial rounds.
+ * @dev     `hash` matches `PoseidonSponge::absorb(inputs)` followed by `squeeze_field_elements(1)`:
+ *          the inputs are added to the rate part of a zero state, {{ rate }} at a time with a permutation
+ *          whenever the rate is full and more inputs remain, and the output is the first rate element
+ *          after a final permutation. A hash of n inputs thus runs max(1, ceil(n / {{ rate }})) permutations.
+ */
+contract Poseidon {
+    uint256 internal constant SCALAR_FIELD =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+
+    /// Round constants, {{ width }} big-endian words per round
+    bytes internal constant ARK = hex"{{ ark }}";
+
+    /**
+     * @notice  Absorbs `inputs` into a fresh sponge and squeezes one element.
+     * @dev     Reverts on inputs which are not canonical field elements.
+     */
+    function hash(uint256[] calldata inputs) external pure returns (uint256) {
+        uint256[{{ width }}] memory state;
+        bytes memory ark = ARK;
+        uint256 absorbed = 0;
+        for (uint256 i = 0; i < inputs.length; i++) {
+            require(inputs[i] < SCALAR_FIELD, "Poseidon: input is not a field element");
+            if (absorbed == {{ rate }}) {
+                permute(state, ark);
+                absorbed = 0;
+            }
+            state[{{ capacity }} + absorbed] = addmod(state[{{ capacity }} + absorbed], inputs[i], SCALAR_FIELD);
+            absorbed++;
+        }
+        permute(state, ark);
+        return state[{{ capacity }}];
+    }
+
+    /**
+     * @notice  Poseidon permutation of `state`, in place.
+     * @dev     Each round adds the round constants, applies the S-box to the whole state (full rounds)
+     *          or to its first element (partial rounds), and multiplies the state by the MDS matrix.
+     *          The new state is computed in the scratch space past the free memory pointer.
+     */
+    function permute(uint256[{{ width }}] memory state, bytes memory ark) internal pure {
+        assembly {
+            let next := mload(0x40)
+            let constants := add(ark, 0x20)
+            for { let round := 0 } lt(round, {{ full_rounds + partial_rounds }}) { round := add(round, 1) } {
+                for { let i := 0 } lt(i, {{ width * 32 }}) { i := add(i, 0x20) } {
+                    mstore(add(state, i), addmod(mload(add(state, i)), mload(add(constants, i)), SCALAR_FIELD))
+                }
+                constants := add(constants, {{ width * 32 }})
+
+                let full := or(lt(round, {{ full_rounds / 2 }}), iszero(lt(round, {{ full_rounds / 2 + partial_rounds }})))
+                for { let i := 0 } lt(i, {{ width * 32 }}) { i := add(i, 0x20) } {
+                    let x := mload(add(state, i))
+                    let x2 := mulmod(x, x, SCALAR_FIELD)
+                    mstore(add(state, i), mulmod(mulmod(x2, x2, SCALAR_FIELD), x, SCALAR_FIELD))
+                    if iszero(full) { break }
+                }
+
+                {%- for (i, row) in mds.iter().enumerate() %}
+                {
+                    let t := 0
+                    {%- for (j, m) in row.iter().enumerate() %}
+                    t := addmod(t, mulmod({{ m }}, mload(add(state, {{ j * 32 }})), SCALAR_FIELD), SCALAR_FIELD)
+                    {%- endfor %}
+                    mstore(add(next, {{ i * 32 }}), t)
+                }
+                {%- endfor %}
+                for { let i := 0 } lt(i, {{ width * 32 }}) { i := add(i, 0x20) } {
+                    mstore(add(state, i), mload(add(next, i)))
+                }
+            }
+        }
+    }
+}